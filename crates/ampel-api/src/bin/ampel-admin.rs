@@ -4,12 +4,20 @@
 //! read back out of the database. The only safe recovery path is to set a NEW
 //! password, which this tool does. It is intended for local / docker-compose
 //! setups where an operator with database access needs to get a locked-out user
-//! signed in again.
+//! signed in again, or to sign out a user whose device or token was lost.
+//! Resetting a password also revokes all of the user's sessions.
+//!
+//! It also replaces the VAPID keys browser push notifications are signed with,
+//! e.g. after the private key leaked. Rotating needs ENCRYPTION_KEY as well.
 //!
 //! Usage:
 //!   DATABASE_URL=postgres://... ampel-admin list-users
 //!   DATABASE_URL=postgres://... ampel-admin reset-password --email user@example.com
 //!   DATABASE_URL=postgres://... ampel-admin reset-password --id <uuid> --password 'new-secret'
+//!   DATABASE_URL=postgres://... ampel-admin list-sessions --email user@example.com
+//!   DATABASE_URL=postgres://... ampel-admin revoke-sessions --email user@example.com
+//!   DATABASE_URL=postgres://... ampel-admin revoke-sessions --id <uuid> --session <session-id>
+//!   DATABASE_URL=postgres://... ENCRYPTION_KEY=... ampel-admin rotate-vapid-keys
 //!
 //! In docker compose, run it inside the API container, which already has
 //! DATABASE_URL set, e.g.:
@@ -17,8 +25,11 @@
 //!   docker compose exec api ampel-admin reset-password --email user@example.com
 
 use ampel_core::services::AuthService;
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::user;
 use ampel_db::init_database;
-use ampel_db::queries::UserQueries;
+use ampel_db::queries::{RefreshTokenQueries, UserQueries};
+use ampel_worker::services::web_push::rotate_vapid_keys;
 use anyhow::{anyhow, bail, Context, Result};
 use clap::{Parser, Subcommand};
 use uuid::Uuid;
@@ -29,10 +40,10 @@ const MIN_PASSWORD_LEN: usize = 8;
 #[derive(Parser)]
 #[command(
     name = "ampel-admin",
-    about = "Ampel admin utility: list users and reset forgotten passwords.",
+    about = "Ampel admin utility: list users, reset forgotten passwords, revoke sessions and rotate push keys.",
     long_about = "Passwords are stored as Argon2id hashes and cannot be recovered. \
-This tool lists users and sets a NEW password for a locked-out account. \
-Requires DATABASE_URL to point at the Ampel database."
+This tool lists users, sets a NEW password for a locked-out account and lists or \
+revokes a user's signed-in sessions. Requires DATABASE_URL to point at the Ampel database."
 )]
 struct Cli {
     #[command(subcommand)]
@@ -58,6 +69,36 @@ enum Command {
         #[arg(long)]
         password: Option<String>,
     },
+
+    /// List a user's active sessions, identified by email or id.
+    ListSessions {
+        /// Email address of the user.
+        #[arg(long, conflicts_with = "id")]
+        email: Option<String>,
+
+        /// User id (UUID) of the user.
+        #[arg(long, conflicts_with = "email")]
+        id: Option<Uuid>,
+    },
+
+    /// Revoke a user's sessions: all of them, or one with --session.
+    RevokeSessions {
+        /// Email address of the user.
+        #[arg(long, conflicts_with = "id")]
+        email: Option<String>,
+
+        /// User id (UUID) of the user.
+        #[arg(long, conflicts_with = "email")]
+        id: Option<Uuid>,
+
+        /// Session id (from list-sessions) to revoke instead of all sessions.
+        #[arg(long)]
+        session: Option<Uuid>,
+    },
+
+    /// Replace the VAPID keys used for browser push. Every push subscription is
+    /// deleted; browsers subscribe again with the new key.
+    RotateVapidKeys,
 }
 
 #[tokio::main]
@@ -82,9 +123,35 @@ async fn main() -> Result<()> {
             id,
             password,
         } => reset_password(&db, email, id, password).await,
+        Command::ListSessions { email, id } => list_sessions(&db, email, id).await,
+        Command::RevokeSessions { email, id, session } => {
+            revoke_sessions(&db, email, id, session).await
+        }
+        Command::RotateVapidKeys => rotate_push_keys(&db).await,
     }
 }
 
+/// Locate a user by exactly one of email or id.
+async fn find_user(
+    db: &sea_orm::DatabaseConnection,
+    email: Option<String>,
+    id: Option<Uuid>,
+) -> Result<user::Model> {
+    let user = match (email, id) {
+        (Some(email), None) => UserQueries::find_by_email(db, &email)
+            .await
+            .context("failed to look up user by email")?
+            .ok_or_else(|| anyhow!("no user found with email '{email}'"))?,
+        (None, Some(id)) => UserQueries::find_by_id(db, id)
+            .await
+            .context("failed to look up user by id")?
+            .ok_or_else(|| anyhow!("no user found with id '{id}'"))?,
+        (None, None) => bail!("provide either --email or --id to identify the user"),
+        (Some(_), Some(_)) => bail!("provide only one of --email or --id, not both"),
+    };
+    Ok(user)
+}
+
 async fn list_users(db: &sea_orm::DatabaseConnection) -> Result<()> {
     let users = UserQueries::list(db)
         .await
@@ -120,18 +187,7 @@ async fn reset_password(
     password: Option<String>,
 ) -> Result<()> {
     // Locate the target user.
-    let user = match (email, id) {
-        (Some(email), None) => UserQueries::find_by_email(db, &email)
-            .await
-            .context("failed to look up user by email")?
-            .ok_or_else(|| anyhow!("no user found with email '{email}'"))?,
-        (None, Some(id)) => UserQueries::find_by_id(db, id)
-            .await
-            .context("failed to look up user by id")?
-            .ok_or_else(|| anyhow!("no user found with id '{id}'"))?,
-        (None, None) => bail!("provide either --email or --id to identify the user"),
-        (Some(_), Some(_)) => bail!("provide only one of --email or --id, not both"),
-    };
+    let user = find_user(db, email, id).await?;
 
     // Obtain the new password: from --password, or an interactive hidden prompt
     // with confirmation.
@@ -166,9 +222,94 @@ async fn reset_password(
         .await
         .context("failed to update password")?;
 
+    // Whoever knew the old password must not stay signed in.
+    RefreshTokenQueries::revoke_all_for_user(db, user.id)
+        .await
+        .context("failed to revoke sessions")?;
+
     println!(
-        "Password reset for {} ({}). They can now sign in with the new password.",
+        "Password reset for {} ({}). Existing sessions were signed out; they can now sign in with the new password.",
         user.email, user.id
     );
     Ok(())
 }
+
+async fn list_sessions(
+    db: &sea_orm::DatabaseConnection,
+    email: Option<String>,
+    id: Option<Uuid>,
+) -> Result<()> {
+    let user = find_user(db, email, id).await?;
+    let sessions = RefreshTokenQueries::list_active_for_user(db, user.id)
+        .await
+        .context("failed to query sessions")?;
+
+    if sessions.is_empty() {
+        println!("No active sessions for {} ({}).", user.email, user.id);
+        return Ok(());
+    }
+
+    println!(
+        "{:<38} {:<24} {:<24} USER AGENT",
+        "SESSION", "STARTED", "LAST REFRESHED"
+    );
+    for session in &sessions {
+        println!(
+            "{:<38} {:<24} {:<24} {}",
+            session.family_id,
+            session.session_started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            session.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            session.user_agent.as_deref().unwrap_or("-")
+        );
+    }
+    println!("\n{} session(s).", sessions.len());
+    Ok(())
+}
+
+async fn revoke_sessions(
+    db: &sea_orm::DatabaseConnection,
+    email: Option<String>,
+    id: Option<Uuid>,
+    session: Option<Uuid>,
+) -> Result<()> {
+    let user = find_user(db, email, id).await?;
+
+    match session {
+        Some(session) => {
+            let revoked = RefreshTokenQueries::revoke_user_family(db, user.id, session)
+                .await
+                .context("failed to revoke session")?;
+            if !revoked {
+                bail!("no active session '{session}' for {}", user.email);
+            }
+            println!("Revoked session {session} of {} ({}).", user.email, user.id);
+        }
+        None => {
+            RefreshTokenQueries::revoke_all_for_user(db, user.id)
+                .await
+                .context("failed to revoke sessions")?;
+            println!("Revoked all sessions of {} ({}).", user.email, user.id);
+        }
+    }
+
+    println!(
+        "Access tokens already issued stay valid until they expire (JWT_ACCESS_EXPIRY_MINUTES)."
+    );
+    Ok(())
+}
+
+async fn rotate_push_keys(db: &sea_orm::DatabaseConnection) -> Result<()> {
+    let encryption_key = std::env::var("ENCRYPTION_KEY")
+        .map_err(|_| anyhow!("ENCRYPTION_KEY must be set to rotate VAPID keys"))?;
+    let encryption_service =
+        EncryptionService::from_base64_key(&encryption_key).context("invalid ENCRYPTION_KEY")?;
+
+    let (keys, deleted) = rotate_vapid_keys(db, &encryption_service)
+        .await
+        .context("failed to rotate VAPID keys")?;
+    println!("New VAPID public key: {}", keys.public_key());
+    println!(
+        "Deleted {deleted} push subscription(s); browsers subscribe again on their next visit."
+    );
+    Ok(())
+}
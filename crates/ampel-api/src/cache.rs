@@ -98,6 +98,24 @@ pub async fn set_dashboard_cache<T: Serialize>(
     }
 }
 
+/// Drop the cached dashboard summary for a user
+///
+/// Called when PR state changes outside the normal request path (e.g. an
+/// inbound provider webhook), so the next dashboard read is not up to
+/// `DASHBOARD_CACHE_TTL` seconds stale. Errors are logged, never returned.
+pub async fn invalidate_dashboard_cache(redis: &mut RedisConnectionManager, user_id: Uuid) {
+    let key = dashboard_cache_key(user_id);
+
+    if let Err(e) = redis.del::<_, ()>(&key).await {
+        tracing::warn!(
+            error = %e,
+            user_id = %user_id,
+            key = %key,
+            "Failed to invalidate dashboard cache"
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -11,6 +11,10 @@ pub struct Config {
     pub jwt_refresh_expiry_days: i64,
     pub encryption_key: String,
     pub cors_origins: Vec<String>,
+    /// Email/password sign-in; deployments using SSO only turn it off
+    pub local_login_enabled: bool,
+    /// Public URL of the frontend, used for links in emails
+    pub app_url: String,
 }
 
 impl Config {
@@ -38,6 +42,13 @@ impl Config {
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
+            local_login_enabled: std::env::var("LOCAL_LOGIN_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            app_url: std::env::var("APP_URL")
+                .unwrap_or_else(|_| "http://localhost:3000".to_string())
+                .trim_end_matches('/')
+                .to_string(),
         }
     }
 }
@@ -1,16 +1,26 @@
 use axum::{
-    extract::FromRequestParts,
-    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts, Method, StatusCode},
 };
 use uuid::Uuid;
 
+use ampel_core::models::{ApiTokenScope, API_TOKEN_PREFIX};
+use ampel_core::services::AuthService;
+use ampel_db::queries::ApiTokenQueries;
+
 use crate::AppState;
 
-/// Authenticated user extracted from JWT token
+/// Authenticated user extracted from a JWT access token or a personal API
+/// token
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: Uuid,
     pub email: String,
+    /// Session the access token was issued for; `None` for tokens issued
+    /// before sessions were tracked and for API tokens
+    pub session_id: Option<Uuid>,
+    /// Personal API token the request was made with; `None` for JWTs
+    pub api_token_id: Option<Uuid>,
 }
 
 impl FromRequestParts<AppState> for AuthUser {
@@ -31,6 +41,10 @@ impl FromRequestParts<AppState> for AuthUser {
             "Invalid authorization header format",
         ))?;
 
+        if token.starts_with(API_TOKEN_PREFIX) {
+            return authenticate_api_token(parts, state, token).await;
+        }
+
         let claims = state
             .auth_service
             .validate_access_token(token)
@@ -39,6 +53,120 @@ impl FromRequestParts<AppState> for AuthUser {
         Ok(AuthUser {
             user_id: claims.sub,
             email: claims.email,
+            session_id: claims.sid,
+            api_token_id: None,
         })
     }
 }
+
+async fn authenticate_api_token(
+    parts: &Parts,
+    state: &AppState,
+    token: &str,
+) -> Result<AuthUser, (StatusCode, &'static str)> {
+    const INVALID: (StatusCode, &str) = (StatusCode::UNAUTHORIZED, "Invalid or expired token");
+
+    let (api_token, user) =
+        ApiTokenQueries::find_active_by_hash(&state.db, &AuthService::hash_api_token(token))
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Failed to look up API token");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            })?
+            .ok_or(INVALID)?;
+
+    // An unreadable scope list grants nothing
+    let scopes: Vec<ApiTokenScope> = serde_json::from_str(&api_token.scopes).unwrap_or_default();
+    if !required_scope(&parts.method, parts.uri.path()).granted_by(&scopes) {
+        return Err((StatusCode::FORBIDDEN, "Token lacks the required scope"));
+    }
+
+    if let Err(e) = ApiTokenQueries::touch(&state.db, api_token.id).await {
+        tracing::warn!(error = %e, "Failed to record API token use");
+    }
+
+    Ok(AuthUser {
+        user_id: user.id,
+        email: user.email,
+        session_id: None,
+        api_token_id: Some(api_token.id),
+    })
+}
+
+/// The scope an API token needs for a request. Reads need `read`, merges
+/// `merge` and remediation changes `remediation`; every other change, and
+/// anything under `/api/auth` other than reading the profile, needs `admin`.
+fn required_scope(method: &Method, path: &str) -> ApiTokenScope {
+    let read = matches!(*method, Method::GET | Method::HEAD);
+
+    if path.starts_with("/api/auth/") {
+        return if read && path == "/api/auth/me" {
+            ApiTokenScope::Read
+        } else {
+            ApiTokenScope::Admin
+        };
+    }
+
+    if read {
+        ApiTokenScope::Read
+    } else if path.starts_with("/api/merge/") || path.ends_with("/merge") {
+        ApiTokenScope::Merge
+    } else if path.starts_with("/api/remediation/") {
+        ApiTokenScope::Remediation
+    } else {
+        ApiTokenScope::Admin
+    }
+}
+
+/// `Option<AuthUser>` treats a missing or invalid token as anonymous.
+impl OptionalFromRequestParts<AppState> for AuthUser {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(
+            <AuthUser as FromRequestParts<AppState>>::from_request_parts(parts, state)
+                .await
+                .ok(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        let cases = [
+            (Method::GET, "/api/pull-requests", ApiTokenScope::Read),
+            (Method::GET, "/api/auth/me", ApiTokenScope::Read),
+            (Method::PUT, "/api/auth/me", ApiTokenScope::Admin),
+            (Method::GET, "/api/auth/tokens", ApiTokenScope::Admin),
+            (Method::POST, "/api/merge/bulk", ApiTokenScope::Merge),
+            (
+                Method::POST,
+                "/api/repositories/1/pull-requests/2/merge",
+                ApiTokenScope::Merge,
+            ),
+            (
+                Method::POST,
+                "/api/remediation/repositories/1/run",
+                ApiTokenScope::Remediation,
+            ),
+            (Method::GET, "/api/remediation/runs", ApiTokenScope::Read),
+            (
+                Method::PUT,
+                "/api/repositories/1/auto-merge",
+                ApiTokenScope::Admin,
+            ),
+            (Method::DELETE, "/api/accounts/1", ApiTokenScope::Admin),
+        ];
+
+        for (method, path, expected) in cases {
+            assert_eq!(required_scope(&method, path), expected, "{method} {path}");
+        }
+    }
+}
@@ -1,5 +1,14 @@
 mod auth;
+mod org;
+mod repository;
 mod validated;
 
 pub use auth::AuthUser;
+pub use org::{
+    authorize_org, team_role, Admin, Member, MinOrgRole, MinTeamRole, OrgAccess, OrgAdmin,
+    OrgMember, OrgOwner, Owner, TeamAccess, TeamAdmin, TeamViewer, Viewer,
+};
+pub use repository::{
+    authorize_repository, repository_role, RepoAccess, RepoAdmin, RepoMember, RepoViewer,
+};
 pub use validated::ValidatedJson;
@@ -0,0 +1,114 @@
+use std::marker::PhantomData;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use rust_i18n::t;
+use sea_orm::EntityTrait;
+use uuid::Uuid;
+
+use ampel_core::models::{OrgRole, TeamRole};
+use ampel_db::entities::{repository, team};
+use ampel_db::queries::{OrganizationQueries, RepoQueries, RepositoryShareQueries};
+
+use super::org::{authenticate, path_uuid, team_role, Admin, Member, MinTeamRole, Viewer};
+use super::AuthUser;
+use crate::handlers::ApiError;
+use crate::AppState;
+
+/// Authenticated user holding at least role `R` on the repository named by
+/// the `{repo_id}` path parameter, either as its owner or through a share
+/// with one of their teams or organizations.
+///
+/// Users without access get `404`; those below `R` get `403`.
+pub struct RepoAccess<R: MinTeamRole = Viewer> {
+    pub auth: AuthUser,
+    pub repo: repository::Model,
+    /// The caller's effective role, which may exceed `R`
+    pub role: TeamRole,
+    _min: PhantomData<R>,
+}
+
+pub type RepoViewer = RepoAccess<Viewer>;
+pub type RepoMember = RepoAccess<Member>;
+pub type RepoAdmin = RepoAccess<Admin>;
+
+impl<R: MinTeamRole> FromRequestParts<AppState> for RepoAccess<R> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = authenticate(parts, state).await?;
+        let repo_id = path_uuid(parts, state, "repo_id")
+            .await
+            .ok_or_else(|| ApiError::not_found(t!("errors.repository.not_found")))?;
+        let (repo, role) = authorize_repository(state, auth.user_id, repo_id, R::ROLE).await?;
+
+        Ok(Self {
+            auth,
+            repo,
+            role,
+            _min: PhantomData,
+        })
+    }
+}
+
+/// Check that `user_id` holds at least `min` on a repository, for handlers
+/// that take the repository from the request body rather than the path. Same
+/// status codes as [`RepoAccess`].
+pub async fn authorize_repository(
+    state: &AppState,
+    user_id: Uuid,
+    repo_id: Uuid,
+    min: TeamRole,
+) -> Result<(repository::Model, TeamRole), ApiError> {
+    let not_found = || ApiError::not_found(t!("errors.repository.not_found"));
+    let repo = RepoQueries::find_by_id(&state.db, repo_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let role = repository_role(state, user_id, &repo)
+        .await?
+        .ok_or_else(not_found)?;
+    if role < min {
+        return Err(ApiError::forbidden(t!(
+            "errors.repository.insufficient_access"
+        )));
+    }
+    Ok((repo, role))
+}
+
+/// The user's effective role on a repository: `admin` for its owner,
+/// otherwise the highest role granted by its shares. A team share grants the
+/// user's role in that team; an organization share grants `admin` to the
+/// organization's admins and owners and `viewer` to its other members.
+pub async fn repository_role(
+    state: &AppState,
+    user_id: Uuid,
+    repo: &repository::Model,
+) -> Result<Option<TeamRole>, ApiError> {
+    if repo.user_id == user_id {
+        return Ok(Some(TeamRole::Admin));
+    }
+
+    let mut best = None;
+    for share in RepositoryShareQueries::list_for_repository(&state.db, repo.id).await? {
+        let role = if let Some(team_id) = share.team_id {
+            match team::Entity::find_by_id(team_id).one(&state.db).await? {
+                Some(team) => team_role(state, user_id, &team).await?,
+                None => None,
+            }
+        } else if let Some(org_id) = share.organization_id {
+            OrganizationQueries::role_of(&state.db, org_id, user_id)
+                .await?
+                .map(|role| match role {
+                    OrgRole::Member => TeamRole::Viewer,
+                    OrgRole::Admin | OrgRole::Owner => TeamRole::Admin,
+                })
+        } else {
+            None
+        };
+        best = best.max(role);
+    }
+
+    Ok(best)
+}
@@ -3,7 +3,7 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rust_i18n::t;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set, TransactionTrait,
@@ -14,6 +14,10 @@ use uuid::Uuid;
 use ampel_core::models::GitProvider;
 use ampel_db::entities::{provider_account, repository};
 use ampel_providers::traits::ProviderCredentials;
+use ampel_worker::services::account_credentials::{
+    account_credentials, decrypt_credentials, encrypt_credentials, AUTH_TYPE_GITHUB_APP,
+    AUTH_TYPE_OAUTH, AUTH_TYPE_PAT,
+};
 
 use crate::extractors::AuthUser;
 use crate::handlers::{ApiError, ApiResponse};
@@ -30,10 +34,78 @@ pub struct AddAccountRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instance_url: Option<String>,
     pub account_label: String,
-    pub access_token: String,
+    /// `pat` (default), `github_app` or `oauth`
+    #[serde(default = "default_auth_type")]
+    pub auth_type: String,
+    /// PAT, App Password or OAuth access token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
     /// Required for Bitbucket (username for Basic Auth)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub username: Option<String>,
+    /// GitHub App ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<i64>,
+    /// GitHub App installation ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installation_id: Option<i64>,
+    /// PEM-encoded GitHub App private key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// OAuth refresh token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// When the OAuth access token expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+}
+
+fn default_auth_type() -> String {
+    AUTH_TYPE_PAT.to_string()
+}
+
+impl AddAccountRequest {
+    /// Credentials described by the request, checked against its auth type
+    fn credentials(&self) -> Result<ProviderCredentials, ApiError> {
+        let missing = |fields: &str| {
+            ApiError::bad_request(t!(
+                "errors.account.missing_credentials",
+                auth_type = self.auth_type,
+                fields = fields
+            ))
+        };
+
+        match self.auth_type.as_str() {
+            AUTH_TYPE_PAT => Ok(ProviderCredentials::Pat {
+                token: self
+                    .access_token
+                    .clone()
+                    .ok_or_else(|| missing("accessToken"))?,
+                username: self.username.clone(),
+            }),
+            AUTH_TYPE_GITHUB_APP => match (self.app_id, self.installation_id, &self.private_key) {
+                (Some(app_id), Some(installation_id), Some(private_key)) => {
+                    Ok(ProviderCredentials::GitHubApp {
+                        app_id,
+                        private_key: private_key.clone(),
+                        installation_id,
+                    })
+                }
+                _ => Err(missing("appId, installationId, privateKey")),
+            },
+            AUTH_TYPE_OAUTH => Ok(ProviderCredentials::OAuth {
+                access_token: self
+                    .access_token
+                    .clone()
+                    .ok_or_else(|| missing("accessToken"))?,
+                refresh_token: self.refresh_token.clone(),
+                expires_at: self.token_expires_at,
+            }),
+            _ => Err(ApiError::bad_request(t!(
+                "errors.account.invalid_auth_type"
+            ))),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,8 +113,17 @@ pub struct AddAccountRequest {
 pub struct UpdateAccountRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account_label: Option<String>,
+    /// New PAT or OAuth access token
     #[serde(skip_serializing_if = "Option::is_none")]
     pub access_token: Option<String>,
+    /// New OAuth refresh token, sent with `access_token`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+    /// New GitHub App private key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_active: Option<bool>,
 }
@@ -61,6 +142,7 @@ pub struct ProviderAccountResponse {
     pub provider_username: String,
     pub provider_email: Option<String>,
     pub avatar_url: Option<String>,
+    pub auth_type: String,
     pub scopes: Vec<String>,
     pub token_expires_at: Option<String>,
     pub validation_status: String,
@@ -88,6 +170,7 @@ impl ProviderAccountResponse {
             provider_username: model.provider_username,
             provider_email: model.provider_email,
             avatar_url: model.avatar_url,
+            auth_type: model.auth_type,
             scopes,
             token_expires_at: model.token_expires_at.map(|dt| dt.to_rfc3339()),
             validation_status: model.validation_status,
@@ -143,7 +226,7 @@ pub async fn list_accounts(
     Ok(Json(ApiResponse::success(responses)))
 }
 
-/// Add a new PAT-based account
+/// Add a new account (PAT, GitHub App installation or OAuth)
 /// POST /api/accounts
 pub async fn add_account(
     State(state): State<AppState>,
@@ -162,10 +245,7 @@ pub async fn add_account(
         .create(provider_type, request.instance_url.clone());
 
     // 3. Build credentials
-    let credentials = ProviderCredentials::Pat {
-        token: request.access_token.clone(),
-        username: request.username.clone(),
-    };
+    let credentials = request.credentials()?;
 
     // 4. Validate token with provider
     let validation = provider
@@ -207,10 +287,8 @@ pub async fn add_account(
         return Err(ApiError::conflict(t!("errors.account.already_connected")));
     }
 
-    // 6. Encrypt and store token
-    let token_encrypted = state
-        .encryption_service
-        .encrypt(&request.access_token)
+    // 6. Encrypt and store credentials
+    let stored = encrypt_credentials(&state.encryption_service, &credentials)
         .map_err(|e| ApiError::internal(t!("errors.encryption.failed", error = e.to_string())))?;
 
     // 7. Check if this should be the default account for this provider
@@ -241,11 +319,11 @@ pub async fn add_account(
         provider_username: Set(provider_username),
         provider_email: Set(validation.email),
         avatar_url: Set(validation.avatar_url),
-        auth_type: Set("pat".to_string()),
-        access_token_encrypted: Set(token_encrypted),
+        auth_type: Set(stored.auth_type.to_string()),
+        access_token_encrypted: Set(stored.access_token_encrypted),
         auth_username: Set(request.username),
         scopes: Set(Some(scopes_json)),
-        token_expires_at: Set(validation.expires_at),
+        token_expires_at: Set(stored.token_expires_at.or(validation.expires_at)),
         last_validated_at: Set(Some(now)),
         validation_status: Set("valid".to_string()),
         is_active: Set(true),
@@ -328,8 +406,8 @@ pub async fn update_account(
         active.is_active = Set(is_active);
     }
 
-    // Update access_token if provided (re-validate and re-encrypt)
-    if let Some(new_token) = request.access_token {
+    // Update credentials if provided (re-validate and re-encrypt)
+    if request.access_token.is_some() || request.private_key.is_some() {
         // Create provider instance
         let provider_type: GitProvider = account
             .provider
@@ -339,10 +417,42 @@ pub async fn update_account(
             .provider_factory
             .create(provider_type, account.instance_url.clone());
 
-        // Build credentials with new token
-        let credentials = ProviderCredentials::Pat {
-            token: new_token.clone(),
-            username: account.auth_username.clone(),
+        // Swap the new secrets into the stored credentials
+        let current = decrypt_credentials(&state.encryption_service, &account).map_err(|e| {
+            ApiError::internal(t!(
+                "errors.encryption.decrypt_failed",
+                error = e.to_string()
+            ))
+        })?;
+        let credentials = match (current, request.access_token, request.private_key) {
+            (ProviderCredentials::Pat { username, .. }, Some(token), None) => {
+                ProviderCredentials::Pat { token, username }
+            }
+            (ProviderCredentials::OAuth { refresh_token, .. }, Some(access_token), None) => {
+                ProviderCredentials::OAuth {
+                    access_token,
+                    refresh_token: request.refresh_token.or(refresh_token),
+                    expires_at: request.token_expires_at,
+                }
+            }
+            (
+                ProviderCredentials::GitHubApp {
+                    app_id,
+                    installation_id,
+                    ..
+                },
+                None,
+                Some(private_key),
+            ) => ProviderCredentials::GitHubApp {
+                app_id,
+                private_key,
+                installation_id,
+            },
+            _ => {
+                return Err(ApiError::bad_request(t!(
+                    "errors.account.invalid_auth_type"
+                )))
+            }
         };
 
         // Validate new token
@@ -364,12 +474,15 @@ pub async fn update_account(
             ));
         }
 
-        // Encrypt new token
-        let token_encrypted = state.encryption_service.encrypt(&new_token).map_err(|e| {
+        // Encrypt new credentials
+        let stored = encrypt_credentials(&state.encryption_service, &credentials).map_err(|e| {
             ApiError::internal(t!("errors.encryption.failed", error = e.to_string()))
         })?;
 
-        active.access_token_encrypted = Set(token_encrypted);
+        active.access_token_encrypted = Set(stored.access_token_encrypted);
+        if stored.token_expires_at.is_some() {
+            active.token_expires_at = Set(stored.token_expires_at);
+        }
         active.last_validated_at = Set(Some(now));
         active.validation_status = Set("valid".to_string());
 
@@ -471,16 +584,20 @@ pub async fn validate_account(
         return Err(ApiError::not_found("Account not found"));
     }
 
-    // Decrypt token
-    let token = state
-        .encryption_service
-        .decrypt(&account.access_token_encrypted)
-        .map_err(|e| {
-            ApiError::internal(t!(
-                "errors.encryption.decrypt_failed",
-                error = e.to_string()
-            ))
-        })?;
+    // Decrypt credentials, refreshing an expiring OAuth token
+    let credentials = account_credentials(
+        &state.db,
+        &state.encryption_service,
+        &state.provider_factory,
+        &account,
+    )
+    .await
+    .map_err(|e| {
+        ApiError::internal(t!(
+            "errors.encryption.decrypt_failed",
+            error = e.to_string()
+        ))
+    })?;
 
     // Create provider instance
     let provider_type: GitProvider = account
@@ -491,12 +608,6 @@ pub async fn validate_account(
         .provider_factory
         .create(provider_type, account.instance_url.clone());
 
-    // Build credentials
-    let credentials = ProviderCredentials::Pat {
-        token,
-        username: account.auth_username.clone(),
-    };
-
     // Validate with provider
     let validation = provider.validate_credentials(&credentials).await;
 
@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use rust_i18n::t;
+use uuid::Uuid;
+
+use ampel_core::models::{
+    ApiTokenResponse, ApiTokenScope, CreateApiTokenRequest, CreatedApiTokenResponse,
+    API_TOKEN_PREFIX,
+};
+use ampel_core::services::AuthService;
+use ampel_db::entities::api_token;
+use ampel_db::queries::{ApiTokenQueries, NewApiToken};
+
+use crate::extractors::{AuthUser, ValidatedJson};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// Random bytes in a token, before encoding
+const API_TOKEN_BYTES: usize = 32;
+
+/// Characters of the token shown when listing tokens: the prefix plus the
+/// first few random characters
+const DISPLAY_PREFIX_LEN: usize = API_TOKEN_PREFIX.len() + 4;
+
+fn token_response(token: api_token::Model) -> ApiTokenResponse {
+    ApiTokenResponse {
+        id: token.id,
+        name: token.name,
+        token_prefix: token.token_prefix,
+        scopes: serde_json::from_str(&token.scopes).unwrap_or_default(),
+        expires_at: token.expires_at,
+        last_used_at: token.last_used_at,
+        created_at: token.created_at,
+    }
+}
+
+/// List the current user's personal API tokens
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<ApiTokenResponse>>>, ApiError> {
+    let tokens = ApiTokenQueries::list_for_user(&state.db, auth.user_id)
+        .await?
+        .into_iter()
+        .map(token_response)
+        .collect();
+
+    Ok(Json(ApiResponse::success(tokens)))
+}
+
+/// Create a personal API token. The token's value is only returned here.
+///
+/// Tokens cannot mint further tokens, so this requires a signed-in session.
+pub async fn create_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateApiTokenRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedApiTokenResponse>>), ApiError> {
+    if auth.api_token_id.is_some() {
+        return Err(ApiError::forbidden(t!(
+            "errors.auth.token_requires_session"
+        )));
+    }
+
+    let mut scopes: Vec<ApiTokenScope> = Vec::new();
+    for scope in req.scopes {
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    let scopes = serde_json::to_string(&scopes)
+        .map_err(|e| ApiError::internal(format!("Failed to serialize scopes: {}", e)))?;
+
+    let mut bytes = [0u8; API_TOKEN_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    let value = format!("{API_TOKEN_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes));
+
+    let token = ApiTokenQueries::create(
+        &state.db,
+        NewApiToken {
+            user_id: auth.user_id,
+            name: req.name.trim().to_string(),
+            token_hash: AuthService::hash_api_token(&value),
+            token_prefix: value[..DISPLAY_PREFIX_LEN].to_string(),
+            scopes,
+            expires_at: req
+                .expires_in_days
+                .map(|days| Utc::now() + Duration::days(days)),
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(CreatedApiTokenResponse {
+            token: token_response(token),
+            value,
+        })),
+    ))
+}
+
+/// Revoke one of the current user's personal API tokens
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(token_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if !ApiTokenQueries::revoke(&state.db, auth.user_id, token_id).await? {
+        return Err(ApiError::not_found(t!("errors.auth.token_not_found")));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,21 +1,88 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Path, State},
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
 use rust_i18n::t;
+use uuid::Uuid;
 
 use ampel_core::models::{
-    AuthTokens, CreateUserRequest, LoginRequest, RefreshTokenRequest, UpdateProfileRequest,
-    UserResponse,
+    AuthTokens, CreateUserRequest, LoginRequest, RefreshTokenRequest, SessionResponse,
+    UpdateProfileRequest, UserResponse,
 };
-use ampel_db::queries::UserQueries;
+use ampel_core::services::AuthService;
+use ampel_db::queries::{NewRefreshToken, RefreshTokenQueries, UserQueries};
 
 use crate::extractors::{AuthUser, ValidatedJson};
 use crate::handlers::{ApiError, ApiResponse};
 use crate::AppState;
 
+/// Longest `User-Agent` kept with a session
+const MAX_USER_AGENT_LEN: usize = 255;
+
+/// A refresh token to issue within a session
+pub(crate) struct SessionToken {
+    id: Uuid,
+    family_id: Uuid,
+    started_at: DateTime<Utc>,
+}
+
+impl SessionToken {
+    pub(crate) fn new_session() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            family_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+        }
+    }
+}
+
+/// Generate tokens for a session and record the refresh token's hash
+pub(crate) async fn issue_tokens(
+    state: &AppState,
+    user_id: Uuid,
+    email: &str,
+    session: SessionToken,
+    headers: &HeaderMap,
+) -> Result<AuthTokens, ApiError> {
+    let tokens = state
+        .auth_service
+        .generate_tokens(user_id, email, session.family_id)
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect());
+
+    RefreshTokenQueries::create(
+        &state.db,
+        NewRefreshToken {
+            id: session.id,
+            user_id,
+            family_id: session.family_id,
+            token_hash: AuthService::hash_refresh_token(&tokens.refresh_token),
+            user_agent,
+            session_started_at: session.started_at,
+            expires_at: Utc::now() + state.auth_service.refresh_token_ttl(),
+        },
+    )
+    .await?;
+
+    Ok(tokens)
+}
+
 /// Register a new user
 pub async fn register(
     State(state): State<AppState>,
+    headers: HeaderMap,
     ValidatedJson(req): ValidatedJson<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<AuthTokens>>), ApiError> {
+    if !state.config.local_login_enabled {
+        return Err(ApiError::forbidden(t!("errors.auth.local_login_disabled")));
+    }
+
     // Check if user already exists
     if UserQueries::find_by_email(&state.db, &req.email)
         .await?
@@ -39,11 +106,14 @@ pub async fn register(
     )
     .await?;
 
-    // Generate tokens
-    let tokens = state
-        .auth_service
-        .generate_tokens(user.id, &user.email)
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let tokens = issue_tokens(
+        &state,
+        user.id,
+        &user.email,
+        SessionToken::new_session(),
+        &headers,
+    )
+    .await?;
 
     Ok((StatusCode::CREATED, Json(ApiResponse::success(tokens))))
 }
@@ -51,8 +121,13 @@ pub async fn register(
 /// Login with email and password
 pub async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
     ValidatedJson(req): ValidatedJson<LoginRequest>,
 ) -> Result<Json<ApiResponse<AuthTokens>>, ApiError> {
+    if !state.config.local_login_enabled {
+        return Err(ApiError::forbidden(t!("errors.auth.local_login_disabled")));
+    }
+
     // Find user
     let user = UserQueries::find_by_email(&state.db, &req.email)
         .await?
@@ -70,36 +145,78 @@ pub async fn login(
         )));
     }
 
-    // Generate tokens
-    let tokens = state
-        .auth_service
-        .generate_tokens(user.id, &user.email)
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let tokens = issue_tokens(
+        &state,
+        user.id,
+        &user.email,
+        SessionToken::new_session(),
+        &headers,
+    )
+    .await?;
 
     Ok(Json(ApiResponse::success(tokens)))
 }
 
 /// Refresh access token
+///
+/// Refresh tokens are single use: the presented token is revoked and a new one
+/// is issued in the same session. Presenting a token that was already used
+/// means it has leaked, so the whole session is revoked.
 pub async fn refresh(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<RefreshTokenRequest>,
 ) -> Result<Json<ApiResponse<AuthTokens>>, ApiError> {
+    let invalid = || ApiError::unauthorized(t!("errors.auth.invalid_refresh_token"));
+
     // Validate refresh token
-    let claims = state
+    state
         .auth_service
         .validate_refresh_token(&req.refresh_token)
-        .map_err(|_| ApiError::unauthorized(t!("errors.auth.invalid_refresh_token")))?;
+        .map_err(|_| invalid())?;
+
+    let stored = RefreshTokenQueries::find_by_hash(
+        &state.db,
+        &AuthService::hash_refresh_token(&req.refresh_token),
+    )
+    .await?
+    .ok_or_else(invalid)?;
+
+    // A token that was already rotated out (possibly by a concurrent request
+    // that won the race) has been copied: revoke the rest of its session.
+    // Tokens revoked by logout have nothing left to revoke and are just rejected.
+    let next_id = Uuid::new_v4();
+    if stored.revoked_at.is_some()
+        || !RefreshTokenQueries::rotate(&state.db, stored.id, next_id).await?
+    {
+        let revoked = RefreshTokenQueries::revoke_family(&state.db, stored.family_id).await?;
+        if stored.replaced_by.is_some() || revoked > 0 {
+            tracing::warn!(
+                user_id = %stored.user_id,
+                session_id = %stored.family_id,
+                "Refresh token reused; session revoked"
+            );
+        }
+        return Err(invalid());
+    }
 
     // Verify user still exists
-    let user = UserQueries::find_by_id(&state.db, claims.sub)
+    let user = UserQueries::find_by_id(&state.db, stored.user_id)
         .await?
         .ok_or_else(|| ApiError::unauthorized(t!("errors.auth.user_not_found")))?;
 
-    // Generate new tokens
-    let tokens = state
-        .auth_service
-        .generate_tokens(user.id, &user.email)
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let tokens = issue_tokens(
+        &state,
+        user.id,
+        &user.email,
+        SessionToken {
+            id: next_id,
+            family_id: stored.family_id,
+            started_at: stored.session_started_at,
+        },
+        &headers,
+    )
+    .await?;
 
     Ok(Json(ApiResponse::success(tokens)))
 }
@@ -147,9 +264,70 @@ pub async fn update_me(
     Ok(Json(ApiResponse::success(response)))
 }
 
-/// Logout (client should discard tokens)
-pub async fn logout() -> StatusCode {
-    // In a stateless JWT setup, logout is handled client-side
-    // For enhanced security, you could maintain a token blacklist
-    StatusCode::NO_CONTENT
+/// Logout, revoking the session of the presented access token or refresh token
+///
+/// Access tokens already issued stay valid until they expire, which is
+/// `JWT_ACCESS_EXPIRY_MINUTES` at most; clients should discard them.
+pub async fn logout(
+    State(state): State<AppState>,
+    auth: Option<AuthUser>,
+    body: Option<Json<RefreshTokenRequest>>,
+) -> Result<StatusCode, ApiError> {
+    if let Some(session_id) = auth.and_then(|auth| auth.session_id) {
+        RefreshTokenQueries::revoke_family(&state.db, session_id).await?;
+    }
+
+    if let Some(Json(req)) = body {
+        let hash = AuthService::hash_refresh_token(&req.refresh_token);
+        if let Some(stored) = RefreshTokenQueries::find_by_hash(&state.db, &hash).await? {
+            RefreshTokenQueries::revoke_family(&state.db, stored.family_id).await?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Sign out of all devices by revoking every session of the current user
+pub async fn logout_all(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    let revoked = RefreshTokenQueries::revoke_all_for_user(&state.db, auth.user_id).await?;
+    tracing::info!(user_id = %auth.user_id, "Signed out of all sessions ({} tokens revoked)", revoked);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the current user's active sessions
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<SessionResponse>>>, ApiError> {
+    let sessions = RefreshTokenQueries::list_active_for_user(&state.db, auth.user_id)
+        .await?
+        .into_iter()
+        .map(|token| SessionResponse {
+            id: token.family_id,
+            user_agent: token.user_agent,
+            started_at: token.session_started_at,
+            last_refreshed_at: token.created_at,
+            expires_at: token.expires_at,
+            current: auth.session_id == Some(token.family_id),
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(sessions)))
+}
+
+/// Revoke one of the current user's sessions
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if !RefreshTokenQueries::revoke_user_family(&state.db, auth.user_id, session_id).await? {
+        return Err(ApiError::not_found(t!("errors.auth.session_not_found")));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
 }
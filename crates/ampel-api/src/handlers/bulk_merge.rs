@@ -1,29 +1,45 @@
 use std::collections::HashMap;
-use std::time::Duration;
+use std::convert::Infallible;
 
 use axum::{
     extract::{Path, Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
     Json,
 };
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use tokio::time::sleep;
 use uuid::Uuid;
 
-use ampel_core::models::{GitProvider, MergeRequest, MergeStrategy};
-use ampel_db::entities::provider_account;
+use ampel_core::models::TeamRole;
+use ampel_db::entities::{merge_operation, merge_operation_item};
 use ampel_db::queries::{
     MergeOperationItemQueries, MergeOperationQueries, PrQueries, RepoQueries, UserSettingsQueries,
 };
 
-use crate::extractors::AuthUser;
+use crate::extractors::{repository_role, AuthUser};
+use crate::handlers::pull_requests::matching_pull_requests;
+use crate::handlers::remediation_runs::{
+    authenticate_sse, sse_event, EventsQuery, SSE_KEEPALIVE_INTERVAL, SSE_MAX_DURATION,
+    SSE_POLL_INTERVAL,
+};
+use crate::handlers::saved_views::resolve_filter;
 use crate::handlers::{ApiError, ApiResponse};
 use crate::AppState;
 
+/// Merge strategies accepted in requests
+const MERGE_STRATEGIES: [&str; 3] = ["merge", "squash", "rebase"];
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BulkMergeRequest {
+    /// PRs to merge; leave empty to select them with `query` or `viewId`
+    #[serde(default)]
     pub pull_request_ids: Vec<Uuid>,
+    /// Filter query selecting the open PRs of the caller's repositories
+    pub query: Option<String>,
+    /// Saved view selecting the open PRs of the caller's repositories
+    pub view_id: Option<Uuid>,
     pub strategy: Option<String>,
     pub delete_branch: Option<bool>,
 }
@@ -33,6 +49,8 @@ pub struct BulkMergeRequest {
 pub struct BulkMergeResponse {
     pub operation_id: Uuid,
     pub status: String,
+    /// `manual` for API bulk merges, `auto_merge` for the auto-merge job
+    pub trigger: String,
     pub total: i32,
     pub success: i32,
     pub failed: i32,
@@ -58,12 +76,38 @@ pub struct PaginationQuery {
     pub per_page: Option<u64>,
 }
 
-/// Bulk merge multiple PRs
+/// Enqueue a bulk merge of multiple PRs
+///
+/// PRs are validated and persisted as a `queued` merge operation, then merged
+/// by the worker's merge queue. The response returns immediately with every
+/// item `pending`; follow progress via `GET /api/merge/operations/{id}` or the
+/// `/events` SSE stream.
 pub async fn bulk_merge(
     State(state): State<AppState>,
     auth: AuthUser,
-    Json(req): Json<BulkMergeRequest>,
+    Json(mut req): Json<BulkMergeRequest>,
 ) -> Result<Json<ApiResponse<BulkMergeResponse>>, ApiError> {
+    let selection = resolve_filter(&state, auth.user_id, req.query.as_deref(), req.view_id).await?;
+    if let Some(query) = selection {
+        if !req.pull_request_ids.is_empty() {
+            return Err(ApiError::bad_request(
+                "Specify either pull request IDs or a query, not both",
+            ));
+        }
+        // A query only selects PRs of repositories the user may merge in
+        let mut repos = Vec::new();
+        for repo in RepoQueries::find_accessible_by_user(&state.db, auth.user_id).await? {
+            if repository_role(&state, auth.user_id, &repo).await? >= Some(TeamRole::Member) {
+                repos.push(repo);
+            }
+        }
+        req.pull_request_ids = matching_pull_requests(&state, repos, &query)
+            .await?
+            .into_iter()
+            .map(|details| details.pull_request.id)
+            .collect();
+    }
+
     if req.pull_request_ids.is_empty() {
         return Err(ApiError::bad_request("No pull requests specified"));
     }
@@ -77,35 +121,22 @@ pub async fn bulk_merge(
     // Get user settings for defaults
     let settings = UserSettingsQueries::get_or_create_default(&state.db, auth.user_id).await?;
 
-    let merge_strategy = req
+    let strategy = req
         .strategy
-        .as_ref()
-        .and_then(|s| match s.as_str() {
-            "merge" => Some(MergeStrategy::Merge),
-            "squash" => Some(MergeStrategy::Squash),
-            "rebase" => Some(MergeStrategy::Rebase),
-            _ => None,
-        })
+        .as_deref()
+        .filter(|s| MERGE_STRATEGIES.contains(s))
         .unwrap_or(match settings.default_merge_strategy.as_str() {
-            "merge" => MergeStrategy::Merge,
-            "rebase" => MergeStrategy::Rebase,
-            _ => MergeStrategy::Squash,
+            "merge" => "merge",
+            "rebase" => "rebase",
+            _ => "squash",
         });
 
     let delete_branch = req
         .delete_branch
         .unwrap_or(settings.delete_branches_default);
-    let merge_delay = Duration::from_secs(settings.merge_delay_seconds as u64);
-
-    // Validate PRs and group by repository
-    let mut prs_by_repo: HashMap<
-        Uuid,
-        Vec<(
-            ampel_db::entities::pull_request::Model,
-            ampel_db::entities::repository::Model,
-        )>,
-    > = HashMap::new();
 
+    // Validate every PR before enqueueing anything
+    let mut prs = Vec::with_capacity(req.pull_request_ids.len());
     for pr_id in &req.pull_request_ids {
         let pr = PrQueries::find_by_id(&state.db, *pr_id)
             .await?
@@ -115,346 +146,70 @@ pub async fn bulk_merge(
             .await?
             .ok_or_else(|| ApiError::internal("Repository not found"))?;
 
-        // Verify ownership
-        if repo.user_id != auth.user_id {
-            return Err(ApiError::not_found(format!(
-                "Pull request {} not found",
-                pr_id
-            )));
-        }
-
-        prs_by_repo.entry(repo.id).or_default().push((pr, repo));
-    }
-
-    // Create merge operation record
-    let operation =
-        MergeOperationQueries::create(&state.db, auth.user_id, req.pull_request_ids.len() as i32)
-            .await?;
-
-    // Create items for each PR
-    for pr_id in &req.pull_request_ids {
-        let pr = PrQueries::find_by_id(&state.db, *pr_id).await?.unwrap();
-        MergeOperationItemQueries::create(&state.db, operation.id, *pr_id, pr.repository_id)
-            .await?;
-    }
-
-    // Process merges
-    let mut results: Vec<MergeItemResult> = Vec::new();
-    let mut success_count = 0;
-    let mut failed_count = 0;
-    let mut skipped_count = 0;
-
-    for (_repo_id, repo_prs) in prs_by_repo {
-        let mut is_first = true;
-
-        for (pr, repo) in repo_prs {
-            // Add delay between merges in same repo (except first)
-            if !is_first && merge_delay.as_secs() > 0 {
-                sleep(merge_delay).await;
-            }
-            is_first = false;
-
-            let item = ampel_db::entities::merge_operation_item::Entity::find()
-                .filter(
-                    ampel_db::entities::merge_operation_item::Column::MergeOperationId
-                        .eq(operation.id),
-                )
-                .filter(ampel_db::entities::merge_operation_item::Column::PullRequestId.eq(pr.id))
-                .one(&state.db)
-                .await?
-                .unwrap();
-
-            // Get provider and token first - we need these for the pre-flight check
-            let provider_type: GitProvider = repo
-                .provider
-                .parse()
-                .map_err(|_| ApiError::internal("Invalid provider"))?;
-
-            // Get provider account
-            let account = match repo.provider_account_id {
-                Some(account_id) => provider_account::Entity::find_by_id(account_id)
-                    .one(&state.db)
-                    .await?
-                    .ok_or_else(|| ApiError::not_found("Provider account not found"))?,
-                None => {
-                    let result = MergeItemResult {
-                        pull_request_id: pr.id,
-                        repository_name: repo.full_name.clone(),
-                        pr_number: pr.number,
-                        pr_title: pr.title.clone(),
-                        status: "failed".to_string(),
-                        error_message: Some("Repository not linked to account".to_string()),
-                        merge_sha: None,
-                    };
-                    results.push(result);
-                    failed_count += 1;
-                    MergeOperationItemQueries::update_status(
-                        &state.db,
-                        item.id,
-                        "failed",
-                        Some("Repository not linked to account".to_string()),
-                        None,
-                    )
-                    .await?;
-                    continue;
-                }
-            };
-
-            // Decrypt token
-            let access_token = match state
-                .encryption_service
-                .decrypt(&account.access_token_encrypted)
-            {
-                Ok(t) => t,
-                Err(e) => {
-                    let result = MergeItemResult {
-                        pull_request_id: pr.id,
-                        repository_name: repo.full_name.clone(),
-                        pr_number: pr.number,
-                        pr_title: pr.title.clone(),
-                        status: "failed".to_string(),
-                        error_message: Some(format!("Token error: {}", e)),
-                        merge_sha: None,
-                    };
-                    results.push(result);
-                    failed_count += 1;
-                    MergeOperationItemQueries::update_status(
-                        &state.db,
-                        item.id,
-                        "failed",
-                        Some(format!("Token error: {}", e)),
-                        None,
-                    )
-                    .await?;
-                    continue;
-                }
-            };
-
-            // Create credentials for provider calls
-            let credentials = ampel_providers::traits::ProviderCredentials::Pat {
-                token: access_token.clone(),
-                username: account.auth_username.clone(),
-            };
-
-            let provider = state
-                .provider_factory
-                .create(provider_type, account.instance_url.clone());
-
-            // Pre-flight check: verify PR is still open on the provider
-            // This catches cases where the local DB is stale (PR was closed/merged externally)
-            let fresh_pr = match provider
-                .get_pull_request(&credentials, &repo.owner, &repo.name, pr.number)
-                .await
-            {
-                Ok(fresh) => fresh,
-                Err(e) => {
-                    let result = MergeItemResult {
-                        pull_request_id: pr.id,
-                        repository_name: repo.full_name.clone(),
-                        pr_number: pr.number,
-                        pr_title: pr.title.clone(),
-                        status: "failed".to_string(),
-                        error_message: Some(format!("Failed to verify PR state: {}", e)),
-                        merge_sha: None,
-                    };
-                    results.push(result);
-                    failed_count += 1;
-                    MergeOperationItemQueries::update_status(
-                        &state.db,
-                        item.id,
-                        "failed",
-                        Some(format!("Failed to verify PR state: {}", e)),
-                        None,
-                    )
-                    .await?;
-                    continue;
-                }
-            };
-
-            // Check if PR is still open (either locally or from fresh data)
-            if pr.state != "open" || fresh_pr.state != "open" {
-                // Update local state if stale
-                if pr.state == "open" && fresh_pr.state != "open" {
-                    PrQueries::update_state(
-                        &state.db,
-                        pr.id,
-                        fresh_pr.state.clone(),
-                        fresh_pr.merged_at,
-                        fresh_pr.closed_at,
-                    )
-                    .await?;
-                }
-
-                let status_msg = if fresh_pr.state == "merged" {
-                    "PR was already merged"
-                } else if fresh_pr.state == "closed" {
-                    "PR was closed"
-                } else {
-                    "PR is not open"
-                };
-
-                let result = MergeItemResult {
-                    pull_request_id: pr.id,
-                    repository_name: repo.full_name.clone(),
-                    pr_number: pr.number,
-                    pr_title: pr.title.clone(),
-                    status: "skipped".to_string(),
-                    error_message: Some(status_msg.to_string()),
-                    merge_sha: None,
-                };
-                results.push(result);
-                skipped_count += 1;
-                MergeOperationItemQueries::update_status(
-                    &state.db,
-                    item.id,
-                    "skipped",
-                    Some(status_msg.to_string()),
-                    None,
-                )
-                .await?;
-                continue;
+        // Merging needs the member role on a repository the user can see
+        match repository_role(&state, auth.user_id, &repo).await? {
+            None => {
+                return Err(ApiError::not_found(format!(
+                    "Pull request {} not found",
+                    pr_id
+                )))
             }
-
-            // Attempt merge (provider and credentials already obtained above)
-            let merge_request = MergeRequest {
-                strategy: merge_strategy,
-                commit_title: None,
-                commit_message: None,
-                delete_branch,
-            };
-
-            match provider
-                .merge_pull_request(
-                    &credentials,
-                    &repo.owner,
-                    &repo.name,
-                    pr.number,
-                    &merge_request,
-                )
-                .await
-            {
-                Ok(merge_result) => {
-                    if merge_result.merged {
-                        // Update PR state
-                        PrQueries::update_state(
-                            &state.db,
-                            pr.id,
-                            "merged".to_string(),
-                            Some(chrono::Utc::now()),
-                            Some(chrono::Utc::now()),
-                        )
-                        .await?;
-
-                        let result = MergeItemResult {
-                            pull_request_id: pr.id,
-                            repository_name: repo.full_name.clone(),
-                            pr_number: pr.number,
-                            pr_title: pr.title.clone(),
-                            status: "success".to_string(),
-                            error_message: None,
-                            merge_sha: merge_result.sha.clone(),
-                        };
-                        results.push(result);
-                        success_count += 1;
-                        MergeOperationItemQueries::update_status(
-                            &state.db,
-                            item.id,
-                            "success",
-                            None,
-                            merge_result.sha,
-                        )
-                        .await?;
-                    } else {
-                        let result = MergeItemResult {
-                            pull_request_id: pr.id,
-                            repository_name: repo.full_name.clone(),
-                            pr_number: pr.number,
-                            pr_title: pr.title.clone(),
-                            status: "failed".to_string(),
-                            error_message: Some(merge_result.message),
-                            merge_sha: None,
-                        };
-                        results.push(result);
-                        failed_count += 1;
-                        MergeOperationItemQueries::update_status(
-                            &state.db,
-                            item.id,
-                            "failed",
-                            Some("Merge not completed".to_string()),
-                            None,
-                        )
-                        .await?;
-                    }
-                }
-                Err(e) => {
-                    let result = MergeItemResult {
-                        pull_request_id: pr.id,
-                        repository_name: repo.full_name.clone(),
-                        pr_number: pr.number,
-                        pr_title: pr.title.clone(),
-                        status: "failed".to_string(),
-                        error_message: Some(e.to_string()),
-                        merge_sha: None,
-                    };
-                    results.push(result);
-                    failed_count += 1;
-                    MergeOperationItemQueries::update_status(
-                        &state.db,
-                        item.id,
-                        "failed",
-                        Some(e.to_string()),
-                        None,
-                    )
-                    .await?;
-                }
+            Some(role) if role < TeamRole::Member => {
+                return Err(ApiError::forbidden(format!(
+                    "Not allowed to merge pull request {}",
+                    pr_id
+                )))
             }
+            Some(_) => {}
         }
-    }
 
-    // Update operation status
-    let final_status = if failed_count == 0 && skipped_count == 0 {
-        "completed"
-    } else if success_count == 0 {
-        "failed"
-    } else {
-        "completed"
-    };
+        prs.push((pr, repo));
+    }
 
-    MergeOperationQueries::update_counts(
+    let operation = MergeOperationQueries::enqueue(
         &state.db,
-        operation.id,
-        success_count,
-        failed_count,
-        skipped_count,
-        final_status,
+        auth.user_id,
+        prs.len() as i32,
+        strategy,
+        delete_branch,
+        settings.merge_delay_seconds,
     )
     .await?;
 
-    // TODO: Send notifications via notification service
+    let mut results = Vec::with_capacity(prs.len());
+    for (position, (pr, repo)) in prs.into_iter().enumerate() {
+        MergeOperationItemQueries::create(&state.db, operation.id, pr.id, repo.id, position as i32)
+            .await?;
+
+        results.push(MergeItemResult {
+            pull_request_id: pr.id,
+            repository_name: repo.full_name,
+            pr_number: pr.number,
+            pr_title: pr.title,
+            status: "pending".to_string(),
+            error_message: None,
+            merge_sha: None,
+        });
+    }
 
     Ok(Json(ApiResponse::success(BulkMergeResponse {
         operation_id: operation.id,
-        status: final_status.to_string(),
-        total: req.pull_request_ids.len() as i32,
-        success: success_count,
-        failed: failed_count,
-        skipped: skipped_count,
+        status: operation.status,
+        trigger: operation.trigger,
+        total: operation.total_count,
+        success: 0,
+        failed: 0,
+        skipped: 0,
         results,
     })))
 }
 
-/// Get a single merge operation
-pub async fn get_operation(
-    State(state): State<AppState>,
-    auth: AuthUser,
-    Path(operation_id): Path<Uuid>,
-) -> Result<Json<ApiResponse<BulkMergeResponse>>, ApiError> {
-    let operation =
-        MergeOperationQueries::find_by_id_and_user(&state.db, operation_id, auth.user_id)
-            .await?
-            .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
-
-    let items = MergeOperationItemQueries::find_by_operation(&state.db, operation_id).await?;
+/// Build the API view of an operation and its items
+async fn operation_response(
+    state: &AppState,
+    operation: merge_operation::Model,
+) -> Result<BulkMergeResponse, ApiError> {
+    let items = MergeOperationItemQueries::find_by_operation(&state.db, operation.id).await?;
 
     let mut results = Vec::with_capacity(items.len());
     for item in items {
@@ -477,15 +232,32 @@ pub async fn get_operation(
         });
     }
 
-    Ok(Json(ApiResponse::success(BulkMergeResponse {
+    Ok(BulkMergeResponse {
         operation_id: operation.id,
         status: operation.status,
+        trigger: operation.trigger,
         total: operation.total_count,
         success: operation.success_count,
         failed: operation.failed_count,
         skipped: operation.skipped_count,
         results,
-    })))
+    })
+}
+
+/// Get a single merge operation
+pub async fn get_operation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(operation_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<BulkMergeResponse>>, ApiError> {
+    let operation =
+        MergeOperationQueries::find_by_id_and_user(&state.db, operation_id, auth.user_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
+
+    Ok(Json(ApiResponse::success(
+        operation_response(&state, operation).await?,
+    )))
 }
 
 /// List merge operations for user
@@ -500,39 +272,174 @@ pub async fn list_operations(
     let mut result = Vec::with_capacity(operations.len());
 
     for operation in operations {
-        let items = MergeOperationItemQueries::find_by_operation(&state.db, operation.id).await?;
+        result.push(operation_response(&state, operation).await?);
+    }
 
-        let mut results = Vec::with_capacity(items.len());
-        for item in items {
-            let pr = PrQueries::find_by_id(&state.db, item.pull_request_id).await?;
-            let repo = RepoQueries::find_by_id(&state.db, item.repository_id).await?;
+    Ok(Json(ApiResponse::success(result)))
+}
 
-            let (pr_number, pr_title, repo_name) = match (pr, repo) {
-                (Some(p), Some(r)) => (p.number, p.title, r.full_name),
-                _ => (0, "Unknown".to_string(), "Unknown".to_string()),
-            };
+/// Cancel a queued or running merge operation
+///
+/// A queued operation is cancelled immediately. A running one stops before
+/// its next PR; PRs already merged stay merged.
+pub async fn cancel_operation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(operation_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<BulkMergeResponse>>, ApiError> {
+    MergeOperationQueries::find_by_id_and_user(&state.db, operation_id, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
 
-            results.push(MergeItemResult {
-                pull_request_id: item.pull_request_id,
-                repository_name: repo_name,
-                pr_number,
-                pr_title,
-                status: item.status,
-                error_message: item.error_message,
-                merge_sha: item.merge_sha,
-            });
-        }
+    if !MergeOperationQueries::request_cancel(&state.db, operation_id).await? {
+        return Err(ApiError::conflict("Merge operation has already finished"));
+    }
 
-        result.push(BulkMergeResponse {
-            operation_id: operation.id,
-            status: operation.status,
-            total: operation.total_count,
-            success: operation.success_count,
-            failed: operation.failed_count,
-            skipped: operation.skipped_count,
-            results,
-        });
+    let operation = MergeOperationQueries::find_by_id(&state.db, operation_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
+
+    Ok(Json(ApiResponse::success(
+        operation_response(&state, operation).await?,
+    )))
+}
+
+/// Re-queue a cancelled or failed merge operation
+///
+/// Cancelled PRs go back to pending; PRs that were already merged, skipped or
+/// failed keep their result.
+pub async fn resume_operation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(operation_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<BulkMergeResponse>>, ApiError> {
+    MergeOperationQueries::find_by_id_and_user(&state.db, operation_id, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
+
+    if !MergeOperationQueries::resume(&state.db, operation_id).await? {
+        return Err(ApiError::conflict(
+            "Only cancelled or failed merge operations can be resumed",
+        ));
     }
 
-    Ok(Json(ApiResponse::success(result)))
+    let operation = MergeOperationQueries::find_by_id(&state.db, operation_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
+
+    Ok(Json(ApiResponse::success(
+        operation_response(&state, operation).await?,
+    )))
+}
+
+fn is_terminal(status: &str) -> bool {
+    matches!(status, "completed" | "failed" | "cancelled")
+}
+
+fn progress_event(operation: &merge_operation::Model) -> Event {
+    sse_event(
+        "operation_progress",
+        serde_json::json!({
+            "operationId": operation.id,
+            "status": operation.status,
+            "total": operation.total_count,
+            "success": operation.success_count,
+            "failed": operation.failed_count,
+            "skipped": operation.skipped_count,
+        }),
+    )
+}
+
+fn item_event(item: &merge_operation_item::Model) -> Event {
+    sse_event(
+        "item_updated",
+        serde_json::json!({
+            "operationId": item.merge_operation_id,
+            "pullRequestId": item.pull_request_id,
+            "status": item.status,
+            "errorMessage": item.error_message,
+            "mergeSha": item.merge_sha,
+        }),
+    )
+}
+
+/// GET /api/merge/operations/{id}/events — SSE progress of a merge operation
+///
+/// Authenticates like the remediation run stream (Bearer header or a
+/// `?token=` minted via `POST /api/remediation/sse-token`) and polls the
+/// operation, emitting `operation_progress` and `item_updated` on change and
+/// a final `operation_finished` once it reaches a terminal status.
+pub async fn operation_events(
+    State(state): State<AppState>,
+    Path(operation_id): Path<Uuid>,
+    Query(q): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let user_id = authenticate_sse(&state, &headers, q.token.as_deref())?;
+    let operation = MergeOperationQueries::find_by_id_and_user(&state.db, operation_id, user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Merge operation not found"))?;
+
+    let db = state.db.clone();
+    let stream = async_stream::stream! {
+        let mut last = operation;
+        let mut item_status: HashMap<Uuid, String> = HashMap::new();
+
+        yield Ok(progress_event(&last));
+        if is_terminal(&last.status) {
+            yield Ok(sse_event("operation_finished", serde_json::json!({
+                "operationId": last.id,
+                "status": last.status,
+            })));
+            return;
+        }
+
+        let started = std::time::Instant::now();
+        loop {
+            let items = MergeOperationItemQueries::find_by_operation(&db, operation_id)
+                .await
+                .unwrap_or_default();
+            for item in items {
+                if item_status.get(&item.id) != Some(&item.status) {
+                    item_status.insert(item.id, item.status.clone());
+                    yield Ok(item_event(&item));
+                }
+            }
+
+            tokio::time::sleep(SSE_POLL_INTERVAL).await;
+            if started.elapsed() >= SSE_MAX_DURATION {
+                break;
+            }
+
+            let current = match MergeOperationQueries::find_by_id(&db, operation_id).await {
+                Ok(Some(m)) => m,
+                // Row gone or DB error: end the stream cleanly.
+                _ => break,
+            };
+
+            if current != last {
+                yield Ok(progress_event(&current));
+            }
+            last = current;
+
+            if is_terminal(&last.status) {
+                // Flush the final item results before closing
+                let items = MergeOperationItemQueries::find_by_operation(&db, operation_id)
+                    .await
+                    .unwrap_or_default();
+                for item in items {
+                    if item_status.get(&item.id) != Some(&item.status) {
+                        yield Ok(item_event(&item));
+                    }
+                }
+                yield Ok(sse_event("operation_finished", serde_json::json!({
+                    "operationId": last.id,
+                    "status": last.status,
+                })));
+                break;
+            }
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(SSE_KEEPALIVE_INTERVAL)))
 }
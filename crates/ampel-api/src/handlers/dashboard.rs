@@ -1,11 +1,33 @@
-use axum::{extract::State, Json};
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures::Stream;
 use metrics::{counter, histogram};
+use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
-
-use ampel_core::models::{AmpelStatus, RepositoryWithStatus};
-use ampel_db::queries::{CICheckQueries, PrQueries, RepoQueries, ReviewQueries};
-
-use crate::extractors::AuthUser;
+use tokio::sync::broadcast::error::RecvError;
+
+use ampel_core::models::{
+    AmpelStatus, PrQuery, PrQueryTarget, PullRequest, PullRequestMetadata, RepositoryWithStatus,
+    StatusPolicy,
+};
+use ampel_db::entities::repository;
+use ampel_db::queries::{
+    CICheckQueries, PrMetadataQueries, PrQueries, RepoQueries, RepositoryShareQueries,
+    ReviewQueries, StatusPolicyQueries,
+};
+use ampel_worker::services::live_events;
+
+use crate::extractors::{AuthUser, TeamViewer};
+use crate::handlers::remediation_runs::{
+    authenticate_sse, sse_event, EventsQuery, SSE_KEEPALIVE_INTERVAL, SSE_MAX_DURATION,
+};
+use crate::handlers::saved_views::{repository_matches, resolve_filter, ViewFilter};
 use crate::handlers::{ApiError, ApiResponse};
 use crate::AppState;
 
@@ -46,16 +68,25 @@ pub struct VisibilityBreakdown {
     pub archived: i32,
 }
 
-/// Get dashboard summary statistics
-#[tracing::instrument(skip(state), fields(user_id = %auth.user_id))]
+/// Get dashboard summary statistics, narrowed by `q` and `view` when given
+#[tracing::instrument(skip(state, filter), fields(user_id = %auth.user_id))]
 pub async fn get_summary(
     State(mut state): State<AppState>,
     auth: AuthUser,
+    Query(filter): Query<ViewFilter>,
 ) -> Result<Json<ApiResponse<DashboardSummary>>, ApiError> {
-    use std::collections::HashMap;
     use std::time::Instant;
     let start = Instant::now();
 
+    // Filtered summaries are computed fresh and never cached
+    if let Some(query) =
+        resolve_filter(&state, auth.user_id, filter.q.as_deref(), filter.view).await?
+    {
+        let repos = RepoQueries::find_accessible_by_user(&state.db, auth.user_id).await?;
+        let summary = summarize(&state.db, &repos, Some(&query)).await?;
+        return Ok(Json(ApiResponse::success(summary)));
+    }
+
     // Try to get from cache if Redis is available
     if let Some(redis) = &mut state.redis {
         if let Some(cached_summary) = crate::cache::get_dashboard_cache(redis, auth.user_id).await {
@@ -70,12 +101,67 @@ pub async fn get_summary(
         }
     }
 
-    // Query 1: Get all repositories for user
-    let repos = RepoQueries::find_by_user_id(&state.db, auth.user_id).await?;
+    // Query 1: Get all repositories the user can access
+    let repos = RepoQueries::find_accessible_by_user(&state.db, auth.user_id).await?;
     tracing::debug!(repo_count = repos.len(), "Retrieved repositories for user");
 
+    let summary = summarize(&state.db, &repos, None).await?;
+    let duration = start.elapsed();
+
+    // Cache the summary if Redis is available
+    if let Some(redis) = &mut state.redis {
+        crate::cache::set_dashboard_cache(redis, auth.user_id, &summary).await;
+    }
+
+    // Log summary statistics with structured fields
+    tracing::info!(
+        duration_ms = duration.as_millis(),
+        total_repos = repos.len(),
+        total_open_prs = summary.total_open_prs,
+        green_count = summary.status_counts.green,
+        yellow_count = summary.status_counts.yellow,
+        red_count = summary.status_counts.red,
+        github_count = summary.provider_counts.github,
+        gitlab_count = summary.provider_counts.gitlab,
+        bitbucket_count = summary.provider_counts.bitbucket,
+        public_repos = summary.repository_breakdown.public,
+        private_repos = summary.repository_breakdown.private,
+        archived_repos = summary.repository_breakdown.archived,
+        cache_hit = false,
+        "Dashboard summary generated"
+    );
+
+    // METRICS COLLECTION:
+    // Record response duration as histogram
+    histogram!("ampel_dashboard_summary_duration_seconds").record(duration.as_secs_f64());
+
+    // Record breakdown counts by visibility status
+    counter!("ampel_dashboard_breakdown_total", "visibility" => "green")
+        .increment(summary.status_counts.green as u64);
+    counter!("ampel_dashboard_breakdown_total", "visibility" => "yellow")
+        .increment(summary.status_counts.yellow as u64);
+    counter!("ampel_dashboard_breakdown_total", "visibility" => "red")
+        .increment(summary.status_counts.red as u64);
+
+    Ok(Json(ApiResponse::success(summary)))
+}
+
+/// Aggregate the traffic-light summary of `repos` and their open PRs. With
+/// a `query`, only the repositories and PRs it matches count.
+async fn summarize(
+    db: &DatabaseConnection,
+    repos: &[repository::Model],
+    query: Option<&PrQuery>,
+) -> Result<DashboardSummary, ApiError> {
+    use std::collections::HashMap;
+
+    let repos: Vec<&repository::Model> = repos
+        .iter()
+        .filter(|repo| query.is_none_or(|query| repository_matches(query, repo)))
+        .collect();
+
     if repos.is_empty() {
-        return Ok(Json(ApiResponse::success(DashboardSummary {
+        return Ok(DashboardSummary {
             total_repositories: 0,
             total_open_prs: 0,
             status_counts: StatusCounts {
@@ -92,15 +178,15 @@ pub async fn get_summary(
             open_prs_breakdown: VisibilityBreakdown::default(),
             ready_to_merge_breakdown: VisibilityBreakdown::default(),
             needs_attention_breakdown: VisibilityBreakdown::default(),
-        })));
+        });
     }
 
     // Create repo lookup map for quick access
     let repo_ids: Vec<_> = repos.iter().map(|r| r.id).collect();
-    let repo_map: HashMap<_, _> = repos.iter().map(|r| (r.id, r)).collect();
+    let repo_map: HashMap<_, _> = repos.iter().map(|r| (r.id, *r)).collect();
 
     // Query 2: Batch load all open PRs for all repositories
-    let all_open_prs = PrQueries::find_open_for_repositories(&state.db, &repo_ids).await?;
+    let all_open_prs = PrQueries::find_open_for_repositories(db, &repo_ids).await?;
     tracing::debug!(
         pr_count = all_open_prs.len(),
         "Loaded all open PRs in batch"
@@ -130,7 +216,7 @@ pub async fn get_summary(
     }
 
     if all_open_prs.is_empty() {
-        return Ok(Json(ApiResponse::success(DashboardSummary {
+        return Ok(DashboardSummary {
             total_repositories: repos.len() as i32,
             total_open_prs: 0,
             status_counts: StatusCounts {
@@ -147,20 +233,23 @@ pub async fn get_summary(
             open_prs_breakdown: VisibilityBreakdown::default(),
             ready_to_merge_breakdown: VisibilityBreakdown::default(),
             needs_attention_breakdown: VisibilityBreakdown::default(),
-        })));
+        });
     }
 
     let pr_ids: Vec<_> = all_open_prs.iter().map(|pr| pr.id).collect();
+    let policies = status_policies(db, &repo_ids).await?;
+    let default_policy = StatusPolicy::default();
+    let metadata = query_metadata(db, query, &pr_ids).await?;
 
     // Query 3: Batch load all CI checks for all PRs
-    let all_ci_checks = CICheckQueries::find_for_pull_requests(&state.db, &pr_ids).await?;
+    let all_ci_checks = CICheckQueries::find_for_pull_requests(db, &pr_ids).await?;
     tracing::debug!(
         ci_check_count = all_ci_checks.len(),
         "Loaded all CI checks in batch"
     );
 
     // Query 4: Batch load all reviews for all PRs
-    let all_reviews = ReviewQueries::find_for_pull_requests(&state.db, &pr_ids).await?;
+    let all_reviews = ReviewQueries::find_for_pull_requests(db, &pr_ids).await?;
     tracing::debug!(
         review_count = all_reviews.len(),
         "Loaded all reviews in batch"
@@ -184,7 +273,8 @@ pub async fn get_summary(
     }
 
     // Initialize counters
-    let total_open_prs = all_open_prs.len() as i32;
+    let now = chrono::Utc::now();
+    let mut total_open_prs = 0;
     let mut green_count = 0;
     let mut yellow_count = 0;
     let mut red_count = 0;
@@ -198,15 +288,6 @@ pub async fn get_summary(
             .get(&pr_model.repository_id)
             .expect("PR repository must exist");
 
-        // Count open PRs by repo visibility
-        if repo.is_archived {
-            open_prs_breakdown.archived += 1;
-        } else if repo.is_private {
-            open_prs_breakdown.private += 1;
-        } else {
-            open_prs_breakdown.public += 1;
-        }
-
         // Get CI checks and reviews for this PR from lookup maps
         let ci_checks = ci_checks_by_pr
             .get(&pr_model.id)
@@ -215,14 +296,29 @@ pub async fn get_summary(
         let reviews = reviews_by_pr.get(&pr_model.id).cloned().unwrap_or_default();
 
         // Convert database models to core models
-        let pr: ampel_core::models::PullRequest = pr_model.clone().into();
+        let pr: PullRequest = pr_model.clone().into();
         let ci_checks: Vec<ampel_core::models::CICheck> =
             ci_checks.into_iter().map(|c| c.into()).collect();
         let reviews: Vec<ampel_core::models::Review> =
             reviews.into_iter().map(|r| r.into()).collect();
 
-        // Calculate status using the actual logic
-        let status = AmpelStatus::for_pull_request(&pr, &ci_checks, &reviews);
+        // Calculate status under the repository's policy
+        let policy = policies.get(&repo.id).unwrap_or(&default_policy);
+        let status = AmpelStatus::evaluate(&pr, &ci_checks, &reviews, policy).status;
+
+        if !query.is_none_or(|query| pr_matches(query, &pr, repo, status, &metadata, now)) {
+            continue;
+        }
+
+        // Count open PRs by repo visibility
+        total_open_prs += 1;
+        if repo.is_archived {
+            open_prs_breakdown.archived += 1;
+        } else if repo.is_private {
+            open_prs_breakdown.private += 1;
+        } else {
+            open_prs_breakdown.public += 1;
+        }
 
         // Count by status
         match status {
@@ -251,10 +347,7 @@ pub async fn get_summary(
         }
     }
 
-    let duration = start.elapsed();
-
-    // Build summary
-    let summary = DashboardSummary {
+    Ok(DashboardSummary {
         total_repositories: repos.len() as i32,
         total_open_prs,
         status_counts: StatusCounts {
@@ -271,62 +364,98 @@ pub async fn get_summary(
         open_prs_breakdown,
         ready_to_merge_breakdown: ready_breakdown,
         needs_attention_breakdown,
-    };
-
-    // Cache the summary if Redis is available
-    if let Some(redis) = &mut state.redis {
-        crate::cache::set_dashboard_cache(redis, auth.user_id, &summary).await;
-    }
-
-    // Log summary statistics with structured fields
-    tracing::info!(
-        duration_ms = duration.as_millis(),
-        total_repos = repos.len(),
-        total_open_prs,
-        green_count,
-        yellow_count,
-        red_count,
-        github_count,
-        gitlab_count,
-        bitbucket_count,
-        public_repos = summary.repository_breakdown.public,
-        private_repos = summary.repository_breakdown.private,
-        archived_repos = summary.repository_breakdown.archived,
-        cache_hit = false,
-        "Dashboard summary generated"
-    );
+    })
+}
 
-    // METRICS COLLECTION:
-    // Record response duration as histogram
-    histogram!("ampel_dashboard_summary_duration_seconds").record(duration.as_secs_f64());
+/// The labels and people of `pr_ids`, loaded only when a query needs them
+async fn query_metadata(
+    db: &DatabaseConnection,
+    query: Option<&PrQuery>,
+    pr_ids: &[uuid::Uuid],
+) -> Result<std::collections::HashMap<uuid::Uuid, PullRequestMetadata>, ApiError> {
+    Ok(match query {
+        Some(_) => PrMetadataQueries::for_pull_requests(db, pr_ids).await?,
+        None => Default::default(),
+    })
+}
 
-    // Record breakdown counts by visibility status
-    counter!("ampel_dashboard_breakdown_total", "visibility" => "green")
-        .increment(green_count as u64);
-    counter!("ampel_dashboard_breakdown_total", "visibility" => "yellow")
-        .increment(yellow_count as u64);
-    counter!("ampel_dashboard_breakdown_total", "visibility" => "red").increment(red_count as u64);
+/// Whether `query` matches an open PR of `repo` with the given status
+fn pr_matches(
+    query: &PrQuery,
+    pr: &PullRequest,
+    repo: &repository::Model,
+    status: AmpelStatus,
+    metadata: &std::collections::HashMap<uuid::Uuid, PullRequestMetadata>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    let none = PullRequestMetadata::default();
+    query.matches(
+        &PrQueryTarget {
+            pull_request: pr,
+            repository: &repo.full_name,
+            status,
+            metadata: metadata.get(&pr.id).unwrap_or(&none),
+        },
+        now,
+    )
+}
 
-    Ok(Json(ApiResponse::success(summary)))
+/// The status policy of each of `repo_ids` that has one
+async fn status_policies(
+    db: &DatabaseConnection,
+    repo_ids: &[uuid::Uuid],
+) -> Result<std::collections::HashMap<uuid::Uuid, StatusPolicy>, ApiError> {
+    Ok(
+        StatusPolicyQueries::effective_for_repositories(db, repo_ids)
+            .await?
+            .into_iter()
+            .map(|(id, policy)| (id, policy.into()))
+            .collect(),
+    )
 }
 
-/// Get repositories for grid/list view
+/// Get repositories for grid/list view, narrowed by `q` and `view` when
+/// given
 pub async fn get_grid(
     State(state): State<AppState>,
     auth: AuthUser,
+    Query(filter): Query<ViewFilter>,
 ) -> Result<Json<ApiResponse<Vec<RepositoryWithStatus>>>, ApiError> {
+    let query = resolve_filter(&state, auth.user_id, filter.q.as_deref(), filter.view).await?;
+
+    // Query 1: Get all repositories the user can access
+    let repos = RepoQueries::find_accessible_by_user(&state.db, auth.user_id).await?;
+
+    Ok(Json(ApiResponse::success(
+        grid(&state.db, repos, query.as_ref()).await?,
+    )))
+}
+
+/// Each of `repos` with the traffic light of its worst open PR. With a
+/// `query`, only the PRs it matches count and repositories without any are
+/// left out.
+async fn grid(
+    db: &DatabaseConnection,
+    repos: Vec<repository::Model>,
+    query: Option<&PrQuery>,
+) -> Result<Vec<RepositoryWithStatus>, ApiError> {
     use std::collections::HashMap;
 
-    // Query 1: Get all repositories for user
-    let repos = RepoQueries::find_by_user_id(&state.db, auth.user_id).await?;
+    let repos: Vec<repository::Model> = match query {
+        Some(query) => repos
+            .into_iter()
+            .filter(|repo| repository_matches(query, repo))
+            .collect(),
+        None => repos,
+    };
 
     if repos.is_empty() {
-        return Ok(Json(ApiResponse::success(Vec::new())));
+        return Ok(Vec::new());
     }
 
     // Query 2: Batch load all open PRs for all repositories
     let repo_ids: Vec<_> = repos.iter().map(|r| r.id).collect();
-    let all_open_prs = PrQueries::find_open_for_repositories(&state.db, &repo_ids).await?;
+    let all_open_prs = PrQueries::find_open_for_repositories(db, &repo_ids).await?;
 
     // Group PRs by repository for easy access
     let mut prs_by_repo: HashMap<uuid::Uuid, Vec<_>> = HashMap::new();
@@ -336,6 +465,9 @@ pub async fn get_grid(
 
     // If no PRs at all, return repos with None status
     if all_open_prs.is_empty() {
+        if query.is_some() {
+            return Ok(Vec::new());
+        }
         let result: Vec<_> = repos
             .into_iter()
             .map(|repo| RepositoryWithStatus {
@@ -344,16 +476,19 @@ pub async fn get_grid(
                 open_pr_count: 0,
             })
             .collect();
-        return Ok(Json(ApiResponse::success(result)));
+        return Ok(result);
     }
 
     let pr_ids: Vec<_> = all_open_prs.iter().map(|pr| pr.id).collect();
+    let policies = status_policies(db, &repo_ids).await?;
+    let default_policy = StatusPolicy::default();
+    let metadata = query_metadata(db, query, &pr_ids).await?;
 
     // Query 3: Batch load all CI checks for all PRs
-    let all_ci_checks = CICheckQueries::find_for_pull_requests(&state.db, &pr_ids).await?;
+    let all_ci_checks = CICheckQueries::find_for_pull_requests(db, &pr_ids).await?;
 
     // Query 4: Batch load all reviews for all PRs
-    let all_reviews = ReviewQueries::find_for_pull_requests(&state.db, &pr_ids).await?;
+    let all_reviews = ReviewQueries::find_for_pull_requests(db, &pr_ids).await?;
 
     // Build lookup maps for O(1) access
     let mut ci_checks_by_pr: HashMap<uuid::Uuid, Vec<_>> = HashMap::new();
@@ -373,11 +508,11 @@ pub async fn get_grid(
     }
 
     // Process each repository
+    let now = chrono::Utc::now();
     let mut result = Vec::with_capacity(repos.len());
 
     for repo in repos {
         let repo_prs = prs_by_repo.get(&repo.id);
-        let open_pr_count = repo_prs.map(|prs| prs.len() as i32).unwrap_or(0);
 
         // Calculate repository status based on actual PR statuses
         let mut pr_statuses = Vec::new();
@@ -392,18 +527,28 @@ pub async fn get_grid(
                 let reviews = reviews_by_pr.get(&pr_model.id).cloned().unwrap_or_default();
 
                 // Convert database models to core models
-                let pr: ampel_core::models::PullRequest = (*pr_model).clone().into();
+                let pr: PullRequest = (*pr_model).clone().into();
                 let ci_checks: Vec<ampel_core::models::CICheck> =
                     ci_checks.into_iter().map(|c| c.into()).collect();
                 let reviews: Vec<ampel_core::models::Review> =
                     reviews.into_iter().map(|r| r.into()).collect();
 
-                // Calculate status for this PR
-                let pr_status = AmpelStatus::for_pull_request(&pr, &ci_checks, &reviews);
-                pr_statuses.push(pr_status);
+                // Calculate status for this PR under the repository's policy
+                let policy = policies.get(&repo.id).unwrap_or(&default_policy);
+                let pr_status = AmpelStatus::evaluate(&pr, &ci_checks, &reviews, policy).status;
+                if query
+                    .is_none_or(|query| pr_matches(query, &pr, &repo, pr_status, &metadata, now))
+                {
+                    pr_statuses.push(pr_status);
+                }
             }
         }
 
+        let open_pr_count = pr_statuses.len() as i32;
+        if query.is_some() && open_pr_count == 0 {
+            continue;
+        }
+
         // Aggregate status for the repository (worst status wins)
         let status = AmpelStatus::for_repository(&pr_statuses);
 
@@ -414,7 +559,94 @@ pub async fn get_grid(
         });
     }
 
-    Ok(Json(ApiResponse::success(result)))
+    Ok(result)
+}
+
+/// Get dashboard summary statistics for the repositories shared with a team
+/// or its organization
+pub async fn get_team_summary(
+    State(state): State<AppState>,
+    access: TeamViewer,
+    Query(filter): Query<ViewFilter>,
+) -> Result<Json<ApiResponse<DashboardSummary>>, ApiError> {
+    let query = resolve_filter(
+        &state,
+        access.auth.user_id,
+        filter.q.as_deref(),
+        filter.view,
+    )
+    .await?;
+    let repos = RepositoryShareQueries::repositories_for_team(
+        &state.db,
+        access.team.id,
+        access.team.organization_id,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(
+        summarize(&state.db, &repos, query.as_ref()).await?,
+    )))
+}
+
+/// Get the repositories shared with a team or its organization for
+/// grid/list view
+pub async fn get_team_grid(
+    State(state): State<AppState>,
+    access: TeamViewer,
+    Query(filter): Query<ViewFilter>,
+) -> Result<Json<ApiResponse<Vec<RepositoryWithStatus>>>, ApiError> {
+    let query = resolve_filter(
+        &state,
+        access.auth.user_id,
+        filter.q.as_deref(),
+        filter.view,
+    )
+    .await?;
+    let repos = RepositoryShareQueries::repositories_for_team(
+        &state.db,
+        access.team.id,
+        access.team.organization_id,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(
+        grid(&state.db, repos, query.as_ref()).await?,
+    )))
+}
+
+/// Stream changes to the caller's dashboard as server-sent events
+///
+/// Accepts a Bearer access token or a `?token=` minted by
+/// `POST /api/remediation/sse-token`. A stream that falls behind is sent
+/// `resync`, after which the client should reload the dashboard.
+pub async fn events(
+    State(state): State<AppState>,
+    Query(q): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let user_id = authenticate_sse(&state, &headers, q.token.as_deref())?;
+    let mut events = live_events::subscribe();
+
+    let stream = async_stream::stream! {
+        let deadline = tokio::time::Instant::now() + SSE_MAX_DURATION;
+        loop {
+            match tokio::time::timeout_at(deadline, events.recv()).await {
+                Ok(Ok(live)) if live.user_id == user_id => {
+                    let name = live.event.name();
+                    let payload = serde_json::to_value(&live.event).unwrap_or_default();
+                    yield Ok(sse_event(name, payload));
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(RecvError::Lagged(missed))) => {
+                    yield Ok(sse_event("resync", serde_json::json!({ "missed": missed })));
+                }
+                // The bus closed or the safety cap was reached
+                Ok(Err(RecvError::Closed)) | Err(_) => break,
+            }
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(SSE_KEEPALIVE_INTERVAL)))
 }
 
 #[cfg(test)]
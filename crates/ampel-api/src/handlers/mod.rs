@@ -1,22 +1,31 @@
 pub mod accounts;
 pub mod analytics;
+pub mod api_tokens;
 pub mod auth;
 pub mod bot_rules;
 pub mod bulk_merge;
 pub mod dashboard;
 pub mod model_accounts;
 pub mod model_catalog;
+pub mod notification_channels;
 pub mod notifications;
+pub mod oidc;
+pub mod organizations;
 pub mod pr_filters;
 pub mod pull_requests;
 pub mod remediation;
 pub mod remediation_playbooks;
 pub mod remediation_runs;
 pub mod repositories;
+pub mod repository_shares;
+pub mod saved_views;
 pub mod security;
+pub mod status_policies;
 pub mod teams;
 pub mod user_preferences;
 pub mod user_settings;
+pub mod web_push;
+pub mod webhooks;
 
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use metrics::counter;
@@ -15,8 +15,8 @@
 //!   (`claude`/`gemini`, or an explicit `egressClass=external`) for an
 //!   organization with `air_gapped = true` is rejected with `422`.
 //! - **Scope isolation.** Every read/write asserts the caller owns the account
-//!   (user-scoped) or owns the org it belongs to (org-scoped); cross-scope access
-//!   returns `404` (never leaks existence).
+//!   (user-scoped) or is an admin or owner of the org it belongs to
+//!   (org-scoped); cross-scope access returns `404` (never leaks existence).
 
 use axum::{
     extract::{Path, State},
@@ -30,8 +30,10 @@ use sea_orm::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use ampel_core::models::OrgRole;
 use ampel_core::remediation::{Egress, ModelCredentials, ModelProvider, ProviderKind};
 use ampel_db::entities::{model_provider_account, organization};
+use ampel_db::queries::OrganizationQueries;
 
 use crate::extractors::AuthUser;
 use crate::handlers::security::assert_endpoint_safe;
@@ -161,7 +163,8 @@ fn default_auth_type(kind: ProviderKind) -> &'static str {
     }
 }
 
-/// Assert `user_id` may access `account` (user-scoped self, or owns the org).
+/// Assert `user_id` may access `account` (user-scoped self, or administers the
+/// org).
 /// Denial returns `404` so resource existence is never leaked.
 async fn assert_account_access(
     state: &AppState,
@@ -172,12 +175,10 @@ async fn assert_account_access(
         return Ok(());
     }
     if let Some(org_id) = account.organization_id {
-        let owns = organization::Entity::find_by_id(org_id)
-            .one(&state.db)
+        let administers = OrganizationQueries::role_of(&state.db, org_id, user_id)
             .await?
-            .map(|o| o.owner_id == user_id)
-            .unwrap_or(false);
-        if owns {
+            .is_some_and(|role| role >= OrgRole::Admin);
+        if administers {
             return Ok(());
         }
     }
@@ -199,18 +200,14 @@ fn build_provider(kind: ProviderKind) -> Option<Box<dyn ModelProvider>> {
 // Handlers
 // ============================================================================
 
-/// GET /api/model-accounts — accounts the caller can manage (self + owned orgs).
+/// GET /api/model-accounts — accounts the caller can manage (self + administered
+/// orgs).
 pub async fn list_model_accounts(
     State(state): State<AppState>,
     auth: AuthUser,
 ) -> Result<Json<ApiResponse<Vec<ModelAccountResponse>>>, ApiError> {
-    let owned_org_ids: Vec<Uuid> = organization::Entity::find()
-        .filter(organization::Column::OwnerId.eq(auth.user_id))
-        .all(&state.db)
-        .await?
-        .into_iter()
-        .map(|o| o.id)
-        .collect();
+    let owned_org_ids =
+        OrganizationQueries::org_ids_for_user(&state.db, auth.user_id, OrgRole::Admin).await?;
 
     let mut condition =
         Condition::any().add(model_provider_account::Column::UserId.eq(auth.user_id));
@@ -251,14 +248,17 @@ pub async fn create_model_account(
         None => default_egress(kind),
     };
 
-    // Org-scoped accounts: caller must own the org, and the ADR-014 air-gapped
+    // Org-scoped accounts: caller must administer the org, and the ADR-014 air-gapped
     // ceiling forbids creating an External-egress account in an air-gapped org.
     if let Some(org_id) = req.organization_id {
         let org = organization::Entity::find_by_id(org_id)
             .one(&state.db)
             .await?
             .ok_or_else(|| ApiError::not_found("Organization not found"))?;
-        if org.owner_id != auth.user_id {
+        let administers = OrganizationQueries::role_of(&state.db, org_id, auth.user_id)
+            .await?
+            .is_some_and(|role| role >= OrgRole::Admin);
+        if !administers {
             return Err(ApiError::not_found("Organization not found"));
         }
         if org.air_gapped && egress == Egress::External {
@@ -11,8 +11,8 @@
 //!   guard — reaching `localhost` is their purpose — so this proxy deliberately
 //!   relies on the shared function rather than a bespoke copy.
 //! - **Air-gap ceiling (ADR-014).** The catalog endpoint filters out
-//!   external-egress providers (Claude/Gemini) when the caller's owned
-//!   organization is `air_gapped`.
+//!   external-egress providers (Claude/Gemini) when the requested organization
+//!   is `air_gapped` and the caller belongs to it.
 //! - **Scope isolation.** Ollama endpoints load the authorized account exactly
 //!   like `model_accounts`; cross-scope access returns `404` (never leaks).
 //!
@@ -29,14 +29,13 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
-use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use ampel_core::remediation::{
     CatalogModel, CostModel, Egress, ModelCaps, ModelCatalog, ProviderKind,
 };
-use ampel_db::entities::organization;
+use ampel_db::queries::OrganizationQueries;
 
 use crate::extractors::AuthUser;
 use crate::handlers::model_accounts::load_authorized_account;
@@ -443,7 +442,7 @@ async fn resolve_ollama_target(
 // ============================================================================
 
 /// GET /api/model-catalog — the embedded catalog grouped by provider. When
-/// `organization_id` is supplied AND owned AND air-gapped, external-egress
+/// `organization_id` is supplied, the caller belongs to it AND it is air-gapped, external-egress
 /// providers are omitted (ADR-014).
 pub async fn get_model_catalog(
     State(state): State<AppState>,
@@ -453,14 +452,16 @@ pub async fn get_model_catalog(
     let catalog = ModelCatalog::load_default();
 
     let air_gapped = match query.organization_id {
-        Some(org_id) => organization::Entity::find_by_id(org_id)
-            .one(&state.db)
-            .await?
-            // Only the owner's air-gapped flag filters the view; a missing or
-            // unowned org simply applies no filter (returns everything usable).
-            .filter(|o| o.owner_id == auth.user_id)
-            .map(|o| o.air_gapped)
-            .unwrap_or(false),
+        // Only a member's air-gapped flag filters the view; a missing or foreign
+        // org simply applies no filter (returns everything usable).
+        Some(org_id) => {
+            match OrganizationQueries::role_of(&state.db, org_id, auth.user_id).await? {
+                Some(_) => OrganizationQueries::find_by_id(&state.db, org_id)
+                    .await?
+                    .is_some_and(|o| o.air_gapped),
+                None => false,
+            }
+        }
         None => false,
     };
 
@@ -0,0 +1,298 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use ampel_core::models::{NotificationKind, TeamRole};
+use ampel_core::services::{ChannelConfig, ChannelMessage};
+use ampel_db::entities::{notification_channel, team};
+use ampel_db::queries::{NewNotificationChannel, NotificationChannelQueries};
+
+use crate::extractors::{team_role, AuthUser, TeamAdmin, TeamViewer};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// A configured notification channel, without its credentials
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannelResponse {
+    pub id: Uuid,
+    pub team_id: Option<Uuid>,
+    pub kind: String,
+    pub name: String,
+    /// Where the channel delivers to, e.g. the webhook host or Matrix room
+    pub target: String,
+    /// Notification kinds the channel receives; `None` means all
+    pub events: Option<Vec<String>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNotificationChannelRequest {
+    pub name: String,
+    pub config: ChannelConfig,
+    /// Notification kinds to receive; missing or empty means all
+    pub events: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNotificationChannelRequest {
+    pub name: Option<String>,
+    /// Replaces the stored settings, credentials included
+    pub config: Option<ChannelConfig>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+fn channel_response(
+    state: &AppState,
+    channel: notification_channel::Model,
+) -> NotificationChannelResponse {
+    let target = decrypt_config(state, &channel)
+        .map(|config| config.target())
+        .unwrap_or_default();
+    NotificationChannelResponse {
+        id: channel.id,
+        team_id: channel.team_id,
+        kind: channel.kind,
+        name: channel.name,
+        target,
+        events: channel
+            .events
+            .as_deref()
+            .and_then(|events| serde_json::from_str(events).ok()),
+        enabled: channel.enabled,
+        created_at: channel.created_at,
+        updated_at: channel.updated_at,
+    }
+}
+
+fn decrypt_config(
+    state: &AppState,
+    channel: &notification_channel::Model,
+) -> Result<ChannelConfig, ApiError> {
+    let json = state
+        .encryption_service
+        .decrypt(&channel.config_encrypted)
+        .map_err(|e| ApiError::internal(format!("Failed to decrypt channel settings: {}", e)))?;
+    serde_json::from_str(&json)
+        .map_err(|e| ApiError::internal(format!("Invalid channel settings: {}", e)))
+}
+
+fn encrypt_config(state: &AppState, config: &ChannelConfig) -> Result<Vec<u8>, ApiError> {
+    config
+        .validate()
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    let json = serde_json::to_string(config)
+        .map_err(|e| ApiError::internal(format!("Failed to serialize channel settings: {}", e)))?;
+    state
+        .encryption_service
+        .encrypt(&json)
+        .map_err(|e| ApiError::internal(format!("Failed to encrypt channel settings: {}", e)))
+}
+
+fn validate_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(ApiError::bad_request(
+            "Channel name must be between 1 and 100 characters",
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Check each event names a notification kind and store them as JSON,
+/// storing `None` for "all"
+fn encode_events(events: Option<Vec<String>>) -> Result<Option<String>, ApiError> {
+    let Some(events) = events.filter(|events| !events.is_empty()) else {
+        return Ok(None);
+    };
+    for event in &events {
+        event
+            .parse::<NotificationKind>()
+            .map_err(|_| ApiError::bad_request(format!("Unknown notification event: {}", event)))?;
+    }
+    serde_json::to_string(&events)
+        .map(Some)
+        .map_err(|e| ApiError::internal(format!("Failed to serialize events: {}", e)))
+}
+
+/// Load a channel the user may change: their own, or one belonging to a team
+/// they administer. Channels the user cannot see are reported as missing.
+async fn manageable_channel(
+    state: &AppState,
+    auth: &AuthUser,
+    channel_id: Uuid,
+) -> Result<notification_channel::Model, ApiError> {
+    let not_found = || ApiError::not_found("Notification channel not found");
+    let channel = NotificationChannelQueries::find_by_id(&state.db, channel_id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    if channel.user_id == Some(auth.user_id) {
+        return Ok(channel);
+    }
+    let Some(team_id) = channel.team_id else {
+        return Err(not_found());
+    };
+    let team = team::Entity::find_by_id(team_id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(not_found)?;
+    match team_role(state, auth.user_id, &team).await? {
+        None => Err(not_found()),
+        Some(role) if role < TeamRole::Admin => Err(ApiError::forbidden(
+            "Only team admins can manage team notification channels",
+        )),
+        Some(_) => Ok(channel),
+    }
+}
+
+async fn create_channel(
+    state: &AppState,
+    user_id: Option<Uuid>,
+    team_id: Option<Uuid>,
+    req: CreateNotificationChannelRequest,
+) -> Result<notification_channel::Model, ApiError> {
+    let name = validate_name(&req.name)?;
+    let config_encrypted = encrypt_config(state, &req.config)?;
+    let events = encode_events(req.events)?;
+
+    Ok(NotificationChannelQueries::create(
+        &state.db,
+        NewNotificationChannel {
+            user_id,
+            team_id,
+            kind: req.config.kind().to_string(),
+            name,
+            config_encrypted,
+            events,
+        },
+    )
+    .await?)
+}
+
+/// List the current user's personal notification channels
+pub async fn list_channels(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<NotificationChannelResponse>>>, ApiError> {
+    let channels = NotificationChannelQueries::list_for_user(&state.db, auth.user_id)
+        .await?
+        .into_iter()
+        .map(|channel| channel_response(&state, channel))
+        .collect();
+
+    Ok(Json(ApiResponse::success(channels)))
+}
+
+/// Add a personal notification channel
+pub async fn create_user_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<CreateNotificationChannelRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<NotificationChannelResponse>>), ApiError> {
+    let channel = create_channel(&state, Some(auth.user_id), None, req).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(channel_response(&state, channel))),
+    ))
+}
+
+/// List a team's notification channels
+pub async fn list_team_channels(
+    State(state): State<AppState>,
+    access: TeamViewer,
+) -> Result<Json<ApiResponse<Vec<NotificationChannelResponse>>>, ApiError> {
+    let channels = NotificationChannelQueries::list_for_team(&state.db, access.team.id)
+        .await?
+        .into_iter()
+        .map(|channel| channel_response(&state, channel))
+        .collect();
+
+    Ok(Json(ApiResponse::success(channels)))
+}
+
+/// Add a notification channel that receives every team member's notifications
+pub async fn create_team_channel(
+    State(state): State<AppState>,
+    access: TeamAdmin,
+    Json(req): Json<CreateNotificationChannelRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<NotificationChannelResponse>>), ApiError> {
+    let channel = create_channel(&state, None, Some(access.team.id), req).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(channel_response(&state, channel))),
+    ))
+}
+
+/// Update a personal or team notification channel
+pub async fn update_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    Json(req): Json<UpdateNotificationChannelRequest>,
+) -> Result<Json<ApiResponse<NotificationChannelResponse>>, ApiError> {
+    let channel = manageable_channel(&state, &auth, channel_id).await?;
+    let mut active: notification_channel::ActiveModel = channel.into();
+
+    if let Some(name) = &req.name {
+        active.name = Set(validate_name(name)?);
+    }
+    if let Some(config) = &req.config {
+        active.config_encrypted = Set(encrypt_config(&state, config)?);
+        active.kind = Set(config.kind().to_string());
+    }
+    if req.events.is_some() {
+        active.events = Set(encode_events(req.events)?);
+    }
+    if let Some(enabled) = req.enabled {
+        active.enabled = Set(enabled);
+    }
+    active.updated_at = Set(Utc::now());
+    let updated = active.update(&state.db).await?;
+
+    Ok(Json(ApiResponse::success(channel_response(
+        &state, updated,
+    ))))
+}
+
+/// Delete a personal or team notification channel, with its undelivered
+/// notifications
+pub async fn delete_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let channel = manageable_channel(&state, &auth, channel_id).await?;
+    NotificationChannelQueries::delete(&state.db, channel.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a test message to a notification channel
+pub async fn test_channel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<bool>>, ApiError> {
+    let channel = manageable_channel(&state, &auth, channel_id).await?;
+    decrypt_config(&state, &channel)?
+        .into_channel()
+        .send(&ChannelMessage::test())
+        .await
+        .map_err(|e| ApiError::bad_request(format!("Failed to send test message: {}", e)))?;
+
+    Ok(Json(ApiResponse::success(true)))
+}
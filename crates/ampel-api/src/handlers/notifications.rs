@@ -1,10 +1,12 @@
 use axum::{extract::State, Json};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use ampel_db::entities::notification_preferences;
+use ampel_core::models::{DigestFrequency, PrNotification};
+use ampel_db::entities::{notification_log, notification_preferences};
+use ampel_db::queries::NotificationLogQueries;
 
 use crate::extractors::AuthUser;
 use crate::handlers::{ApiError, ApiResponse};
@@ -59,6 +61,42 @@ pub struct UpdateNotificationPreferencesRequest {
     pub slack_channel: Option<String>,
 }
 
+/// Entries returned by the notification log endpoint
+const NOTIFICATION_LOG_LIMIT: u64 = 50;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationLogEntryResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub channel: String,
+    /// The configured notification channel, if not a preference channel
+    pub channel_id: Option<Uuid>,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub notification: Option<PrNotification>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+impl From<notification_log::Model> for NotificationLogEntryResponse {
+    fn from(entry: notification_log::Model) -> Self {
+        Self {
+            id: entry.id,
+            kind: entry.kind,
+            channel: entry.channel,
+            channel_id: entry.channel_id,
+            status: entry.status,
+            attempts: entry.attempts,
+            last_error: entry.last_error,
+            notification: serde_json::from_str(&entry.payload).ok(),
+            created_at: entry.created_at,
+            sent_at: entry.sent_at,
+        }
+    }
+}
+
 fn parse_smtp_to_emails(json_str: Option<&String>) -> Option<Vec<String>> {
     json_str.and_then(|s| serde_json::from_str(s).ok())
 }
@@ -123,6 +161,12 @@ pub async fn update_preferences(
     auth: AuthUser,
     Json(req): Json<UpdateNotificationPreferencesRequest>,
 ) -> Result<Json<ApiResponse<NotificationPreferencesResponse>>, ApiError> {
+    if let Some(frequency) = &req.digest_frequency {
+        frequency
+            .parse::<DigestFrequency>()
+            .map_err(|_| ApiError::bad_request("Digest frequency must be none, daily or weekly"))?;
+    }
+
     let existing = notification_preferences::Entity::find()
         .filter(notification_preferences::Column::UserId.eq(auth.user_id))
         .one(&state.db)
@@ -379,3 +423,18 @@ pub async fn test_email_smtp(
 
     Ok(Json(ApiResponse::success(true)))
 }
+
+/// List the current user's most recent notifications and their delivery state
+pub async fn list_log(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<NotificationLogEntryResponse>>>, ApiError> {
+    let entries =
+        NotificationLogQueries::list_for_user(&state.db, auth.user_id, NOTIFICATION_LOG_LIMIT)
+            .await?
+            .into_iter()
+            .map(NotificationLogEntryResponse::from)
+            .collect();
+
+    Ok(Json(ApiResponse::success(entries)))
+}
@@ -0,0 +1,388 @@
+//! OpenID Connect sign-in endpoints.
+//!
+//! `GET /api/auth/oidc/login` sends the browser to the IdP. The login's
+//! `state`, `nonce` and PKCE verifier travel in an encrypted, HttpOnly cookie
+//! scoped to `/api/auth/oidc`, so any API instance can complete the flow.
+//!
+//! `GET /api/auth/oidc/callback` verifies the IdP's answer, provisions the user
+//! just in time, syncs team roles from the IdP's groups and redirects to
+//! `OIDC_POST_LOGIN_REDIRECT` with the Ampel tokens in the URL fragment
+//! (`#accessToken=...&refreshToken=...&tokenType=Bearer&expiresIn=...`), or
+//! with `#error=<code>` if sign-in failed.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Query, State},
+    http::{
+        header::{COOKIE, SET_COOKIE},
+        HeaderMap,
+    },
+    response::{IntoResponse, Redirect, Response},
+    Json,
+};
+use chrono::Utc;
+use cookie::{Cookie, SameSite};
+use rust_i18n::t;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use ampel_core::models::AuthTokens;
+use ampel_db::entities::{team, team_member, user};
+use ampel_db::queries::{UserIdentityQueries, UserQueries};
+
+use crate::handlers::auth::{issue_tokens, SessionToken};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::oidc::{random_token, GroupMapping, IdTokenClaims, OidcClient, Pkce};
+use crate::AppState;
+
+const LOGIN_COOKIE: &str = "ampel_oidc_login";
+const LOGIN_COOKIE_PATH: &str = "/api/auth/oidc";
+
+/// How long the user has to complete sign-in at the IdP
+const LOGIN_TTL_MINUTES: i64 = 10;
+
+/// Sign-in methods offered by this deployment
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthMethodsResponse {
+    pub local_login: bool,
+    pub sso: Option<SsoMethod>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SsoMethod {
+    pub name: String,
+    pub login_url: String,
+}
+
+/// What the login cookie carries from `/login` to `/callback`
+#[derive(Serialize, Deserialize)]
+struct LoginState {
+    state: String,
+    nonce: String,
+    pkce_verifier: String,
+    created_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackParams {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+/// Why an SSO sign-in failed, as reported to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SsoError {
+    /// Missing, expired or mismatched login state
+    InvalidState,
+    /// The IdP denied the request
+    Denied,
+    /// The IdP reported no email address
+    MissingEmail,
+    /// A local account has the email, and the IdP did not verify it
+    EmailConflict,
+    Failed,
+}
+
+impl SsoError {
+    fn code(self) -> &'static str {
+        match self {
+            Self::InvalidState => "invalid_state",
+            Self::Denied => "access_denied",
+            Self::MissingEmail => "missing_email",
+            Self::EmailConflict => "email_conflict",
+            Self::Failed => "sso_failed",
+        }
+    }
+}
+
+impl From<DbErr> for SsoError {
+    fn from(e: DbErr) -> Self {
+        tracing::error!(error = %e, "Database error during SSO sign-in");
+        Self::Failed
+    }
+}
+
+impl From<ApiError> for SsoError {
+    fn from(_: ApiError) -> Self {
+        Self::Failed
+    }
+}
+
+/// List the sign-in methods the login page should offer
+pub async fn methods(State(state): State<AppState>) -> Json<ApiResponse<AuthMethodsResponse>> {
+    Json(ApiResponse::success(AuthMethodsResponse {
+        local_login: state.config.local_login_enabled,
+        sso: state.oidc.as_ref().map(|oidc| SsoMethod {
+            name: oidc.config().provider_name.clone(),
+            login_url: format!("{}/login", LOGIN_COOKIE_PATH),
+        }),
+    }))
+}
+
+/// Start an SSO sign-in by redirecting to the IdP
+pub async fn login(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let oidc = state
+        .oidc
+        .as_ref()
+        .ok_or_else(|| ApiError::not_found(t!("errors.auth.sso_not_configured")))?;
+
+    let pkce = Pkce::generate();
+    let login = LoginState {
+        state: random_token(),
+        nonce: random_token(),
+        pkce_verifier: pkce.verifier,
+        created_at: Utc::now().timestamp(),
+    };
+
+    let url = oidc
+        .authorization_url(&login.state, &login.nonce, &pkce.challenge)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to build OIDC authorization URL");
+            ApiError::bad_gateway(t!("errors.auth.sso_unavailable"))
+        })?;
+
+    let sealed = serde_json::to_string(&login)
+        .map_err(|e| ApiError::internal(e.to_string()))
+        .and_then(|json| {
+            state
+                .encryption_service
+                .encrypt(&json)
+                .map_err(|e| ApiError::internal(e.to_string()))
+        })?;
+    let cookie = Cookie::build((LOGIN_COOKIE, hex::encode(sealed)))
+        .path(LOGIN_COOKIE_PATH)
+        .http_only(true)
+        .secure(oidc.config().redirect_url.starts_with("https://"))
+        .same_site(SameSite::Lax)
+        .max_age(cookie::time::Duration::minutes(LOGIN_TTL_MINUTES))
+        .build();
+
+    Ok((
+        [(SET_COOKIE, cookie.to_string())],
+        Redirect::to(url.as_str()),
+    )
+        .into_response())
+}
+
+/// Complete an SSO sign-in and hand the tokens to the frontend
+pub async fn callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<CallbackParams>,
+) -> Result<Response, ApiError> {
+    let oidc = state
+        .oidc
+        .clone()
+        .ok_or_else(|| ApiError::not_found(t!("errors.auth.sso_not_configured")))?;
+
+    let fragment = match complete_login(&state, &oidc, params, &headers).await {
+        Ok(tokens) => format!(
+            "accessToken={}&refreshToken={}&tokenType={}&expiresIn={}",
+            tokens.access_token, tokens.refresh_token, tokens.token_type, tokens.expires_in
+        ),
+        Err(e) => format!("error={}", e.code()),
+    };
+
+    // The login state is single use
+    let expired = Cookie::build((LOGIN_COOKIE, ""))
+        .path(LOGIN_COOKIE_PATH)
+        .max_age(cookie::time::Duration::ZERO)
+        .build();
+
+    Ok((
+        [(SET_COOKIE, expired.to_string())],
+        Redirect::to(&format!(
+            "{}#{}",
+            oidc.config().post_login_redirect,
+            fragment
+        )),
+    )
+        .into_response())
+}
+
+async fn complete_login(
+    state: &AppState,
+    oidc: &OidcClient,
+    params: CallbackParams,
+    headers: &HeaderMap,
+) -> Result<AuthTokens, SsoError> {
+    if let Some(error) = params.error {
+        tracing::info!(error = %error, "IdP declined SSO sign-in");
+        return Err(SsoError::Denied);
+    }
+
+    let login = read_login_state(state, headers).ok_or(SsoError::InvalidState)?;
+    let expired = Utc::now().timestamp() - login.created_at > LOGIN_TTL_MINUTES * 60;
+    if expired || params.state.as_deref() != Some(login.state.as_str()) {
+        return Err(SsoError::InvalidState);
+    }
+    let code = params.code.ok_or(SsoError::InvalidState)?;
+
+    let claims = oidc
+        .exchange_code(&code, &login.pkce_verifier, &login.nonce)
+        .await
+        .map_err(|e| {
+            tracing::warn!(error = %e, "SSO sign-in failed");
+            SsoError::Failed
+        })?;
+
+    let user = provision_user(state, &claims).await?;
+
+    let config = oidc.config();
+    if !config.group_mappings.is_empty() {
+        let groups = claims.groups(&config.groups_claim);
+        sync_team_roles(&state.db, user.id, &config.group_mappings, &groups).await?;
+    }
+
+    Ok(issue_tokens(
+        state,
+        user.id,
+        &user.email,
+        SessionToken::new_session(),
+        headers,
+    )
+    .await?)
+}
+
+fn read_login_state(state: &AppState, headers: &HeaderMap) -> Option<LoginState> {
+    let sealed = headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| Cookie::parse(pair.trim()).ok())
+        .find(|cookie| cookie.name() == LOGIN_COOKIE)?;
+
+    let json = state
+        .encryption_service
+        .decrypt(&hex::decode(sealed.value()).ok()?)
+        .ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// The user an ID token signs in: the linked user, else the local user with
+/// the same verified email, else a new user
+async fn provision_user(state: &AppState, claims: &IdTokenClaims) -> Result<user::Model, SsoError> {
+    let db = &state.db;
+
+    if let Some(identity) =
+        UserIdentityQueries::find_by_subject(db, &claims.iss, &claims.sub).await?
+    {
+        let user_id = identity.user_id;
+        UserIdentityQueries::record_login(db, identity, claims.email.clone()).await?;
+        return UserQueries::find_by_id(db, user_id)
+            .await?
+            .ok_or(SsoError::Failed);
+    }
+
+    let email = claims.email.clone().ok_or(SsoError::MissingEmail)?;
+    let user = match UserQueries::find_by_email(db, &email).await? {
+        // Linking on an unverified email would let anyone who can set that
+        // address at the IdP take over the local account
+        Some(_) if !claims.email_verified() => return Err(SsoError::EmailConflict),
+        Some(existing) => existing,
+        None => {
+            // SSO users have a password nobody knows
+            let password_hash = state
+                .auth_service
+                .hash_password(&random_token())
+                .map_err(|_| SsoError::Failed)?;
+            let display_name = claims
+                .name
+                .clone()
+                .or_else(|| claims.preferred_username.clone());
+            let user = UserQueries::create(db, email.clone(), password_hash, display_name).await?;
+            tracing::info!(user_id = %user.id, "Provisioned user from SSO sign-in");
+            user
+        }
+    };
+
+    UserIdentityQueries::create(
+        db,
+        user.id,
+        claims.iss.clone(),
+        claims.sub.clone(),
+        claims.email.clone(),
+    )
+    .await?;
+    Ok(user)
+}
+
+fn role_rank(role: &str) -> u8 {
+    match role {
+        "admin" => 3,
+        "member" => 2,
+        _ => 1,
+    }
+}
+
+/// Make the user's membership of every mapped team match the IdP's groups.
+///
+/// Teams that no mapping names are left alone. A user in several groups
+/// mapped to the same team gets the highest of their roles.
+async fn sync_team_roles(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    mappings: &[GroupMapping],
+    groups: &[String],
+) -> Result<(), DbErr> {
+    let groups: HashSet<&str> = groups.iter().map(String::as_str).collect();
+    let mut desired: HashMap<Uuid, &str> = HashMap::new();
+    for mapping in mappings
+        .iter()
+        .filter(|m| groups.contains(m.group.as_str()))
+    {
+        let role = desired.entry(mapping.team_id).or_insert(&mapping.role);
+        if role_rank(&mapping.role) > role_rank(role) {
+            *role = &mapping.role;
+        }
+    }
+
+    let managed: HashSet<Uuid> = mappings.iter().map(|m| m.team_id).collect();
+    for team_id in managed {
+        let membership = team_member::Entity::find()
+            .filter(team_member::Column::TeamId.eq(team_id))
+            .filter(team_member::Column::UserId.eq(user_id))
+            .one(db)
+            .await?;
+
+        match (membership, desired.get(&team_id)) {
+            (Some(membership), Some(role)) if membership.role != *role => {
+                let mut active: team_member::ActiveModel = membership.into();
+                active.role = Set(role.to_string());
+                active.update(db).await?;
+            }
+            (Some(membership), None) => {
+                team_member::Entity::delete_by_id(membership.id)
+                    .exec(db)
+                    .await?;
+            }
+            (None, Some(role)) => {
+                if team::Entity::find_by_id(team_id).one(db).await?.is_none() {
+                    tracing::warn!(%team_id, "OIDC group mapping names a team that does not exist");
+                    continue;
+                }
+                team_member::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    team_id: Set(team_id),
+                    user_id: Set(user_id),
+                    role: Set(role.to_string()),
+                    joined_at: Set(Utc::now()),
+                }
+                .insert(db)
+                .await?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
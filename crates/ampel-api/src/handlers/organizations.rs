@@ -0,0 +1,635 @@
+//! Organization management: CRUD, membership roles and email invitations.
+//!
+//! Access is enforced by the [`OrgAccess`](crate::extractors::OrgAccess)
+//! extractors: members can read, admins manage members, invitations and
+//! settings, and only owners change the ADR-014 `air_gapped` ceiling, grant or
+//! revoke ownership, and delete the organization.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use rust_i18n::t;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use ampel_core::models::OrgRole;
+use ampel_core::services::{NotificationService, SmtpConfig};
+use ampel_db::entities::{notification_preferences, organization, organization_invitation};
+use ampel_db::queries::{
+    NewInvitation, NewOrganization, OrganizationInvitationQueries, OrganizationQueries, UserQueries,
+};
+
+use crate::extractors::{AuthUser, OrgAdmin, OrgMember, OrgOwner};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// How long an invitation can be accepted
+const INVITATION_TTL_DAYS: i64 = 7;
+
+/// Random bytes in an invitation token
+const INVITATION_TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub logo_url: Option<String>,
+    pub air_gapped: bool,
+    /// The caller's role in the organization
+    pub role: OrgRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OrganizationResponse {
+    fn new(org: organization::Model, role: OrgRole) -> Self {
+        Self {
+            id: org.id,
+            name: org.name,
+            slug: org.slug,
+            description: org.description,
+            logo_url: org.logo_url,
+            air_gapped: org.air_gapped,
+            role,
+            created_at: org.created_at,
+            updated_at: org.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+    /// Derived from the name when omitted
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    pub logo_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrganizationRequest {
+    pub name: Option<String>,
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    pub logo_url: Option<String>,
+    /// Owner only
+    pub air_gapped: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberResponse {
+    pub user_id: Uuid,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub role: OrgRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMemberRequest {
+    pub role: OrgRole,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    pub role: OrgRole,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvitationResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<organization_invitation::Model> for InvitationResponse {
+    fn from(m: organization_invitation::Model) -> Self {
+        Self {
+            id: m.id,
+            email: m.email,
+            role: m.role,
+            invited_by: m.invited_by,
+            expires_at: m.expires_at,
+            created_at: m.created_at,
+        }
+    }
+}
+
+/// Returned once, when the invitation is created; the token is not stored
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedInvitationResponse {
+    pub invitation: InvitationResponse,
+    pub token: String,
+    pub accept_url: String,
+    /// Whether the invitation was emailed through the inviter's SMTP settings
+    pub email_sent: bool,
+}
+
+/// Lowercase, hyphen separated slug of a name
+fn slugify(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "-")
+}
+
+fn hash_invitation_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+async fn ensure_slug_available(
+    state: &AppState,
+    slug: &str,
+    except: Option<Uuid>,
+) -> Result<(), ApiError> {
+    match OrganizationQueries::find_by_slug(&state.db, slug).await? {
+        Some(existing) if Some(existing.id) != except => {
+            Err(ApiError::conflict(t!("errors.organization.slug_taken")))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn load_organization(
+    state: &AppState,
+    org_id: Uuid,
+) -> Result<organization::Model, ApiError> {
+    OrganizationQueries::find_by_id(&state.db, org_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.organization.not_found")))
+}
+
+/// Reject changes that would leave the organization without an owner
+async fn ensure_not_last_owner(state: &AppState, org_id: Uuid) -> Result<(), ApiError> {
+    if OrganizationQueries::count_owners(&state.db, org_id).await? <= 1 {
+        return Err(ApiError::conflict(t!("errors.organization.last_owner")));
+    }
+    Ok(())
+}
+
+/// List the caller's organizations
+pub async fn list_organizations(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<OrganizationResponse>>>, ApiError> {
+    let orgs = OrganizationQueries::list_for_user(&state.db, auth.user_id).await?;
+
+    Ok(Json(ApiResponse::success(
+        orgs.into_iter()
+            .map(|(org, role)| OrganizationResponse::new(org, role))
+            .collect(),
+    )))
+}
+
+/// Create an organization owned by the caller
+pub async fn create_organization(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<CreateOrganizationRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<OrganizationResponse>>), ApiError> {
+    let name = req.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::bad_request(t!(
+            "errors.organization.name_required"
+        )));
+    }
+    let slug = slugify(req.slug.as_deref().unwrap_or(&name));
+    ensure_slug_available(&state, &slug, None).await?;
+
+    let org = OrganizationQueries::create(
+        &state.db,
+        NewOrganization {
+            owner_id: auth.user_id,
+            name,
+            slug,
+            description: req.description,
+            logo_url: req.logo_url,
+        },
+    )
+    .await?;
+
+    tracing::info!(user_id = %auth.user_id, org_id = %org.id, "Organization created");
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(OrganizationResponse::new(
+            org,
+            OrgRole::Owner,
+        ))),
+    ))
+}
+
+/// Get an organization
+pub async fn get_organization(
+    State(state): State<AppState>,
+    access: OrgMember,
+) -> Result<Json<ApiResponse<OrganizationResponse>>, ApiError> {
+    let org = load_organization(&state, access.org_id).await?;
+    Ok(Json(ApiResponse::success(OrganizationResponse::new(
+        org,
+        access.role,
+    ))))
+}
+
+/// Update an organization's settings. Changing `airGapped` requires the
+/// owner role.
+pub async fn update_organization(
+    State(state): State<AppState>,
+    access: OrgAdmin,
+    Json(req): Json<UpdateOrganizationRequest>,
+) -> Result<Json<ApiResponse<OrganizationResponse>>, ApiError> {
+    let org = load_organization(&state, access.org_id).await?;
+
+    if req.air_gapped.is_some_and(|v| v != org.air_gapped) && access.role < OrgRole::Owner {
+        return Err(ApiError::forbidden(t!(
+            "errors.organization.insufficient_role"
+        )));
+    }
+
+    let mut active: organization::ActiveModel = org.clone().into();
+    if let Some(name) = req.name {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(ApiError::bad_request(t!(
+                "errors.organization.name_required"
+            )));
+        }
+        active.name = Set(name);
+    }
+    if let Some(slug) = req.slug {
+        let slug = slugify(&slug);
+        ensure_slug_available(&state, &slug, Some(org.id)).await?;
+        active.slug = Set(slug);
+    }
+    if let Some(description) = req.description {
+        active.description = Set(Some(description));
+    }
+    if let Some(logo_url) = req.logo_url {
+        active.logo_url = Set(Some(logo_url));
+    }
+    if let Some(air_gapped) = req.air_gapped {
+        if air_gapped != org.air_gapped {
+            tracing::info!(
+                user_id = %access.auth.user_id,
+                org_id = %org.id,
+                air_gapped,
+                "Organization air-gapped ceiling changed"
+            );
+        }
+        active.air_gapped = Set(air_gapped);
+    }
+    active.updated_at = Set(Utc::now());
+
+    let updated = active.update(&state.db).await?;
+    Ok(Json(ApiResponse::success(OrganizationResponse::new(
+        updated,
+        access.role,
+    ))))
+}
+
+/// Delete an organization, its teams and everything scoped to them
+pub async fn delete_organization(
+    State(state): State<AppState>,
+    access: OrgOwner,
+) -> Result<StatusCode, ApiError> {
+    OrganizationQueries::delete(&state.db, access.org_id).await?;
+    tracing::info!(user_id = %access.auth.user_id, org_id = %access.org_id, "Organization deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List an organization's members
+pub async fn list_members(
+    State(state): State<AppState>,
+    access: OrgMember,
+) -> Result<Json<ApiResponse<Vec<MemberResponse>>>, ApiError> {
+    let members = OrganizationQueries::list_members(&state.db, access.org_id).await?;
+
+    Ok(Json(ApiResponse::success(
+        members
+            .into_iter()
+            .filter_map(|(member, user)| {
+                Some(MemberResponse {
+                    user_id: user.id,
+                    email: user.email,
+                    display_name: user.display_name,
+                    role: member.role.parse().ok()?,
+                    joined_at: member.joined_at,
+                })
+            })
+            .collect(),
+    )))
+}
+
+/// Change a member's role. Only owners grant or revoke the owner role.
+pub async fn update_member(
+    State(state): State<AppState>,
+    access: OrgAdmin,
+    Path((_, user_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<UpdateMemberRequest>,
+) -> Result<StatusCode, ApiError> {
+    let current = OrganizationQueries::role_of(&state.db, access.org_id, user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.organization.not_a_member")))?;
+
+    if (current == OrgRole::Owner || req.role == OrgRole::Owner) && access.role < OrgRole::Owner {
+        return Err(ApiError::forbidden(t!(
+            "errors.organization.insufficient_role"
+        )));
+    }
+    if current == OrgRole::Owner && req.role != OrgRole::Owner {
+        ensure_not_last_owner(&state, access.org_id).await?;
+    }
+
+    OrganizationQueries::upsert_member(&state.db, access.org_id, user_id, req.role).await?;
+
+    let org = load_organization(&state, access.org_id).await?;
+    if org.owner_id == user_id && req.role != OrgRole::Owner {
+        OrganizationQueries::reassign_primary_owner(&state.db, org).await?;
+    }
+
+    tracing::info!(
+        user_id = %access.auth.user_id,
+        org_id = %access.org_id,
+        member_id = %user_id,
+        role = %req.role,
+        "Organization member role changed"
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove a member, or leave the organization. Removing an owner requires
+/// the owner role, and the last owner cannot leave.
+pub async fn remove_member(
+    State(state): State<AppState>,
+    access: OrgMember,
+    Path((_, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let target = OrganizationQueries::role_of(&state.db, access.org_id, user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.organization.not_a_member")))?;
+
+    let leaving = user_id == access.auth.user_id;
+    let required = if target == OrgRole::Owner {
+        OrgRole::Owner
+    } else {
+        OrgRole::Admin
+    };
+    if !leaving && access.role < required {
+        return Err(ApiError::forbidden(t!(
+            "errors.organization.insufficient_role"
+        )));
+    }
+    if target == OrgRole::Owner {
+        ensure_not_last_owner(&state, access.org_id).await?;
+    }
+
+    let org = load_organization(&state, access.org_id).await?;
+    if org.owner_id == user_id {
+        OrganizationQueries::reassign_primary_owner(&state.db, org).await?;
+    }
+    OrganizationQueries::remove_member(&state.db, access.org_id, user_id).await?;
+
+    tracing::info!(
+        user_id = %access.auth.user_id,
+        org_id = %access.org_id,
+        member_id = %user_id,
+        "Organization member removed"
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Invite someone to the organization by email.
+///
+/// The token is returned once; it is also emailed when the inviter has SMTP
+/// configured in their notification settings.
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    access: OrgAdmin,
+    Json(req): Json<CreateInvitationRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedInvitationResponse>>), ApiError> {
+    if req.role == OrgRole::Owner {
+        return Err(ApiError::bad_request(t!(
+            "errors.organization.invalid_role"
+        )));
+    }
+    let email = req.email.trim().to_string();
+    if !email.contains('@') {
+        return Err(ApiError::bad_request(t!(
+            "errors.organization.invalid_email"
+        )));
+    }
+
+    if let Some(user) = UserQueries::find_by_email(&state.db, &email).await? {
+        if OrganizationQueries::role_of(&state.db, access.org_id, user.id)
+            .await?
+            .is_some()
+        {
+            return Err(ApiError::conflict(t!("errors.organization.already_member")));
+        }
+    }
+
+    let mut bytes = [0u8; INVITATION_TOKEN_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+
+    let invitation = OrganizationInvitationQueries::create(
+        &state.db,
+        NewInvitation {
+            organization_id: access.org_id,
+            email,
+            role: req.role,
+            token_hash: hash_invitation_token(&token),
+            invited_by: access.auth.user_id,
+            expires_at: Utc::now() + Duration::days(INVITATION_TTL_DAYS),
+        },
+    )
+    .await?;
+
+    let accept_url = format!("{}/invitations/{}", state.config.app_url, token);
+    let org = load_organization(&state, access.org_id).await?;
+    let email_sent =
+        send_invitation_email(&state, access.auth.user_id, &org, &invitation, &accept_url).await;
+
+    tracing::info!(
+        user_id = %access.auth.user_id,
+        org_id = %access.org_id,
+        invitation_id = %invitation.id,
+        email_sent,
+        "Organization invitation created"
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(CreatedInvitationResponse {
+            invitation: InvitationResponse::from(invitation),
+            token,
+            accept_url,
+            email_sent,
+        })),
+    ))
+}
+
+/// Email an invitation through the inviter's SMTP settings. Returns whether
+/// it was sent; failures are logged and never fail the request.
+async fn send_invitation_email(
+    state: &AppState,
+    inviter_id: Uuid,
+    org: &organization::Model,
+    invitation: &organization_invitation::Model,
+    accept_url: &str,
+) -> bool {
+    let prefs = match notification_preferences::Entity::find()
+        .filter(notification_preferences::Column::UserId.eq(inviter_id))
+        .one(&state.db)
+        .await
+    {
+        Ok(Some(prefs)) if prefs.email_enabled => prefs,
+        _ => return false,
+    };
+
+    let (Some(host), Some(port), Some(username), Some(password_encrypted), Some(from_email)) = (
+        prefs.smtp_host,
+        prefs.smtp_port,
+        prefs.smtp_username,
+        prefs.smtp_password_encrypted,
+        prefs.smtp_from_email,
+    ) else {
+        return false;
+    };
+    let password = match state.encryption_service.decrypt(&password_encrypted) {
+        Ok(password) => password,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to decrypt SMTP password for invitation email");
+            return false;
+        }
+    };
+
+    let config = SmtpConfig {
+        host,
+        port: port as u16,
+        username,
+        password,
+        from_email,
+        use_tls: prefs.smtp_use_tls,
+    };
+    match NotificationService::send_invitation_email(
+        &config,
+        &invitation.email,
+        &org.name,
+        &invitation.role,
+        accept_url,
+        invitation.expires_at,
+    )
+    .await
+    {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(error = %e, invitation_id = %invitation.id, "Invitation email failed");
+            false
+        }
+    }
+}
+
+/// List pending invitations
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    access: OrgAdmin,
+) -> Result<Json<ApiResponse<Vec<InvitationResponse>>>, ApiError> {
+    let invitations = OrganizationInvitationQueries::list_pending(&state.db, access.org_id).await?;
+    Ok(Json(ApiResponse::success(
+        invitations
+            .into_iter()
+            .map(InvitationResponse::from)
+            .collect(),
+    )))
+}
+
+/// Revoke an invitation
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    access: OrgAdmin,
+    Path((_, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    if !OrganizationInvitationQueries::delete(&state.db, access.org_id, invitation_id).await? {
+        return Err(ApiError::not_found(t!(
+            "errors.organization.invitation_not_found"
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Accept an invitation. The caller's email must match the invited address.
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(token): Path<String>,
+) -> Result<Json<ApiResponse<OrganizationResponse>>, ApiError> {
+    let invalid = || ApiError::not_found(t!("errors.organization.invitation_invalid"));
+
+    let invitation =
+        OrganizationInvitationQueries::find_by_hash(&state.db, &hash_invitation_token(&token))
+            .await?
+            .filter(|i| i.accepted_at.is_none() && i.expires_at > Utc::now())
+            .ok_or_else(invalid)?;
+
+    let user = UserQueries::find_by_id(&state.db, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.auth.user_not_found")))?;
+    if !user.email.eq_ignore_ascii_case(&invitation.email) {
+        return Err(ApiError::forbidden(t!(
+            "errors.organization.invitation_email_mismatch"
+        )));
+    }
+
+    if !OrganizationInvitationQueries::accept(&state.db, invitation.id).await? {
+        return Err(invalid());
+    }
+
+    let invited: OrgRole = invitation
+        .role
+        .parse()
+        .map_err(|_| ApiError::internal("invalid invitation role in database"))?;
+    let current =
+        OrganizationQueries::role_of(&state.db, invitation.organization_id, user.id).await?;
+    // Accepting never demotes an existing member
+    let role = match current {
+        Some(current) if current >= invited => current,
+        _ => {
+            OrganizationQueries::upsert_member(
+                &state.db,
+                invitation.organization_id,
+                user.id,
+                invited,
+            )
+            .await?;
+            invited
+        }
+    };
+
+    let org = load_organization(&state, invitation.organization_id).await?;
+    tracing::info!(
+        user_id = %user.id,
+        org_id = %org.id,
+        invitation_id = %invitation.id,
+        "Organization invitation accepted"
+    );
+    Ok(Json(ApiResponse::success(OrganizationResponse::new(
+        org, role,
+    ))))
+}
@@ -2,7 +2,7 @@ use axum::{extract::State, Json};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use ampel_db::queries::PrFilterQueries;
+use ampel_db::queries::{PrFilterQueries, DEFAULT_SKIP_LABELS};
 
 use crate::extractors::AuthUser;
 use crate::handlers::{ApiError, ApiResponse};
@@ -26,6 +26,10 @@ pub struct UpdatePrFilterRequest {
     pub max_age_days: Option<i32>,
 }
 
+fn default_skip_labels() -> Vec<String> {
+    DEFAULT_SKIP_LABELS.iter().map(|l| l.to_string()).collect()
+}
+
 /// Get PR filter settings for the current user
 pub async fn get_pr_filters(
     State(state): State<AppState>,
@@ -54,12 +58,7 @@ pub async fn get_pr_filters(
                 "renovate[bot]".to_string(),
                 "snyk-bot".to_string(),
             ],
-            skip_labels: vec![
-                "do-not-merge".to_string(),
-                "wip".to_string(),
-                "draft".to_string(),
-                "hold".to_string(),
-            ],
+            skip_labels: default_skip_labels(),
             max_age_days: None,
         }
     };
@@ -90,14 +89,7 @@ pub async fn update_pr_filters(
     let current_skip: Vec<String> = existing
         .as_ref()
         .map(|f| serde_json::from_str(&f.skip_labels).unwrap_or_default())
-        .unwrap_or_else(|| {
-            vec![
-                "do-not-merge".to_string(),
-                "wip".to_string(),
-                "draft".to_string(),
-                "hold".to_string(),
-            ]
-        });
+        .unwrap_or_else(default_skip_labels);
 
     let current_max_age = existing.as_ref().and_then(|f| f.max_age_days);
 
@@ -147,12 +139,7 @@ pub async fn reset_pr_filters(
             "renovate[bot]".to_string(),
             "snyk-bot".to_string(),
         ],
-        skip_labels: vec![
-            "do-not-merge".to_string(),
-            "wip".to_string(),
-            "draft".to_string(),
-            "hold".to_string(),
-        ],
+        skip_labels: default_skip_labels(),
         max_age_days: None,
     })))
 }
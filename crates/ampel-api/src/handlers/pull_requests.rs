@@ -1,33 +1,131 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Path, Query, State},
-    Json,
+    Extension, Json,
 };
+use chrono::Utc;
 use rust_i18n::t;
 use sea_orm::EntityTrait;
 use uuid::Uuid;
 
 use ampel_core::models::{
-    AmpelStatus, CICheck, GitProvider, MergeRequest, PaginatedResponse, PullRequest,
-    PullRequestFilter, PullRequestWithDetails, Review,
+    CICheck, GitProvider, MergeRequest, PaginatedResponse, PrQuery, PrQueryTarget,
+    PullRequestFilter, PullRequestMetadata, PullRequestWithDetails, Review, StatusPolicy,
+    StatusReason,
+};
+use ampel_core::services::PrService;
+use ampel_db::entities::{provider_account, repository};
+use ampel_db::queries::{
+    CICheckQueries, PrMetadataQueries, PrQueries, RepoQueries, ReviewQueries, StatusPolicyQueries,
 };
-use ampel_db::entities::provider_account;
-use ampel_db::queries::{CICheckQueries, PrQueries, RepoQueries, ReviewQueries};
+use ampel_worker::services::account_credentials::account_credentials;
+use ampel_worker::services::pr_events::record_pr_closed;
 
-use crate::extractors::AuthUser;
+use crate::extractors::{AuthUser, RepoMember, RepoViewer};
+use crate::handlers::saved_views::{repository_matches, resolve_filter};
 use crate::handlers::{ApiError, ApiResponse};
+use crate::middleware::DetectedLocale;
 use crate::AppState;
 
-/// List all open PRs for the user
+/// Fill in the message of each of a PR's status reasons in `locale`
+fn localize_reasons(details: &mut PullRequestWithDetails, locale: &str) {
+    for explanation in &mut details.reasons {
+        explanation.message = Some(reason_message(&explanation.reason, locale));
+    }
+}
+
+fn reason_message(reason: &StatusReason, locale: &str) -> String {
+    match reason {
+        StatusReason::Draft => t!("status_reasons.draft", locale = locale),
+        StatusReason::Conflicts => t!("status_reasons.conflicts", locale = locale),
+        StatusReason::NotMergeable => t!("status_reasons.not_mergeable", locale = locale),
+        StatusReason::ChecksFailed { checks } => t!(
+            "status_reasons.checks_failed",
+            locale = locale,
+            checks = checks.join(", ")
+        ),
+        StatusReason::ChecksPending { checks } => t!(
+            "status_reasons.checks_pending",
+            locale = locale,
+            checks = checks.join(", ")
+        ),
+        StatusReason::RequiredChecksMissing { checks } => t!(
+            "status_reasons.required_checks_missing",
+            locale = locale,
+            checks = checks.join(", ")
+        ),
+        StatusReason::ChangesRequested { reviewers } => t!(
+            "status_reasons.changes_requested",
+            locale = locale,
+            reviewers = reviewers.join(", ")
+        ),
+        StatusReason::ApprovalsMissing {
+            approvals,
+            required,
+        } => t!(
+            "status_reasons.approvals_missing",
+            locale = locale,
+            approvals = approvals,
+            required = required
+        ),
+        StatusReason::StaleApprovals { reviewers } => t!(
+            "status_reasons.stale_approvals",
+            locale = locale,
+            reviewers = reviewers.join(", ")
+        ),
+        StatusReason::RequiredApproverMissing => {
+            t!("status_reasons.required_approver_missing", locale = locale)
+        }
+    }
+    .into_owned()
+}
+
+/// List all open PRs of the repositories the user can access, narrowed by `q`
+/// and `view` when given
 pub async fn list_pull_requests(
     State(state): State<AppState>,
+    Extension(locale): Extension<DetectedLocale>,
     auth: AuthUser,
     Query(filter): Query<PullRequestFilter>,
 ) -> Result<Json<ApiResponse<PaginatedResponse<PullRequestWithDetails>>>, ApiError> {
     let page = filter.page.unwrap_or(1);
     let per_page = filter.per_page.unwrap_or(20);
 
-    let (prs, total) =
-        PrQueries::find_open_by_user(&state.db, auth.user_id, page as u64, per_page as u64).await?;
+    if let Some(query) =
+        resolve_filter(&state, auth.user_id, filter.q.as_deref(), filter.view).await?
+    {
+        let repos = RepoQueries::find_accessible_by_user(&state.db, auth.user_id).await?;
+        let matching = matching_pull_requests(&state, repos, &query).await?;
+        let total = matching.len();
+        let result = matching
+            .into_iter()
+            .skip((page.max(1) - 1) as usize * per_page.max(0) as usize)
+            .take(per_page.max(0) as usize)
+            .map(|mut details| {
+                localize_reasons(&mut details, &locale.code);
+                details
+            })
+            .collect();
+
+        return Ok(Json(ApiResponse::success(PaginatedResponse::new(
+            result,
+            total as i64,
+            page,
+            per_page,
+        ))));
+    }
+
+    let (prs, total) = PrQueries::find_open_accessible_by_user(
+        &state.db,
+        auth.user_id,
+        page as u64,
+        per_page as u64,
+    )
+    .await?;
+
+    let repo_ids: Vec<Uuid> = prs.iter().map(|pr| pr.repository_id).collect();
+    let policies = StatusPolicyQueries::effective_for_repositories(&state.db, &repo_ids).await?;
 
     let mut result = Vec::with_capacity(prs.len());
 
@@ -48,17 +146,25 @@ pub async fn list_pull_requests(
             .map(|r| r.into())
             .collect();
 
-        let pr_model: PullRequest = pr.into();
-        let status = AmpelStatus::for_pull_request(&pr_model, &checks, &reviews);
+        let policy = policies
+            .get(&repo.id)
+            .cloned()
+            .map(StatusPolicy::from)
+            .unwrap_or_default();
+
+        let metadata = PrMetadataQueries::for_pull_request(&state.db, pr.id).await?;
 
-        result.push(PullRequestWithDetails {
-            pull_request: pr_model,
-            status,
-            ci_checks: checks,
+        let mut details = PrService::new().with_details(
+            pr.into(),
+            checks,
             reviews,
-            repository_name: repo.name,
-            repository_owner: repo.owner,
-        });
+            metadata,
+            repo.owner,
+            repo.name,
+            &policy,
+        );
+        localize_reasons(&mut details, &locale.code);
+        result.push(details);
     }
 
     Ok(Json(ApiResponse::success(PaginatedResponse::new(
@@ -69,22 +175,82 @@ pub async fn list_pull_requests(
     ))))
 }
 
-/// Get PRs for a specific repository
+/// The open PRs of `repos` that match `query`, most recently updated first
+pub(crate) async fn matching_pull_requests(
+    state: &AppState,
+    repos: Vec<repository::Model>,
+    query: &PrQuery,
+) -> Result<Vec<PullRequestWithDetails>, ApiError> {
+    let repos: HashMap<Uuid, repository::Model> = repos
+        .into_iter()
+        .filter(|repo| repository_matches(query, repo))
+        .map(|repo| (repo.id, repo))
+        .collect();
+    let repo_ids: Vec<Uuid> = repos.keys().copied().collect();
+    let prs = PrQueries::find_open_for_repositories(&state.db, &repo_ids).await?;
+    let policies = StatusPolicyQueries::effective_for_repositories(&state.db, &repo_ids).await?;
+    let pr_ids: Vec<Uuid> = prs.iter().map(|pr| pr.id).collect();
+    let mut metadata = PrMetadataQueries::for_pull_requests(&state.db, &pr_ids).await?;
+    let now = Utc::now();
+
+    let mut result = Vec::new();
+    for pr in prs {
+        let Some(repo) = repos.get(&pr.repository_id) else {
+            continue;
+        };
+
+        let checks: Vec<CICheck> = CICheckQueries::find_by_pull_request(&state.db, pr.id)
+            .await?
+            .into_iter()
+            .map(|c| c.into())
+            .collect();
+
+        let reviews: Vec<Review> = ReviewQueries::find_latest_by_pull_request(&state.db, pr.id)
+            .await?
+            .into_iter()
+            .map(|r| r.into())
+            .collect();
+
+        let policy = policies
+            .get(&repo.id)
+            .cloned()
+            .map(StatusPolicy::from)
+            .unwrap_or_default();
+
+        let pr_metadata = metadata.remove(&pr.id).unwrap_or_default();
+        let details = PrService::new().with_details(
+            pr.into(),
+            checks,
+            reviews,
+            pr_metadata,
+            repo.owner.clone(),
+            repo.name.clone(),
+            &policy,
+        );
+        let target = PrQueryTarget {
+            pull_request: &details.pull_request,
+            repository: &repo.full_name,
+            status: details.status,
+            metadata: &details.metadata,
+        };
+        if query.matches(&target, now) {
+            result.push(details);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Get PRs for a repository the user owns or that is shared with them
 pub async fn list_repository_prs(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(repo_id): Path<Uuid>,
+    Extension(locale): Extension<DetectedLocale>,
+    access: RepoViewer,
 ) -> Result<Json<ApiResponse<Vec<PullRequestWithDetails>>>, ApiError> {
-    let repo = RepoQueries::find_by_id(&state.db, repo_id)
-        .await?
-        .ok_or_else(|| ApiError::not_found(t!("errors.repository.not_found")))?;
-
-    // Verify ownership
-    if repo.user_id != auth.user_id {
-        return Err(ApiError::not_found(t!("errors.repository.not_found")));
-    }
+    let repo = access.repo;
 
-    let prs = PrQueries::find_open_by_repository(&state.db, repo_id).await?;
+    let prs = PrQueries::find_open_by_repository(&state.db, repo.id).await?;
+    let policy = StatusPolicyQueries::policy_for_repository(&state.db, repo.id).await?;
 
     let mut result = Vec::with_capacity(prs.len());
 
@@ -101,17 +267,19 @@ pub async fn list_repository_prs(
             .map(|r| r.into())
             .collect();
 
-        let pr_model: PullRequest = pr.into();
-        let status = AmpelStatus::for_pull_request(&pr_model, &checks, &reviews);
+        let metadata = PrMetadataQueries::for_pull_request(&state.db, pr.id).await?;
 
-        result.push(PullRequestWithDetails {
-            pull_request: pr_model,
-            status,
-            ci_checks: checks,
+        let mut details = PrService::new().with_details(
+            pr.into(),
+            checks,
             reviews,
-            repository_name: repo.name.clone(),
-            repository_owner: repo.owner.clone(),
-        });
+            metadata,
+            repo.owner.clone(),
+            repo.name.clone(),
+            &policy,
+        );
+        localize_reasons(&mut details, &locale.code);
+        result.push(details);
     }
 
     Ok(Json(ApiResponse::success(result)))
@@ -120,24 +288,18 @@ pub async fn list_repository_prs(
 /// Get a single PR with full details
 pub async fn get_pull_request(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((repo_id, pr_id)): Path<(Uuid, Uuid)>,
+    Extension(locale): Extension<DetectedLocale>,
+    access: RepoViewer,
+    Path((_, pr_id)): Path<(Uuid, Uuid)>,
 ) -> Result<Json<ApiResponse<PullRequestWithDetails>>, ApiError> {
-    let repo = RepoQueries::find_by_id(&state.db, repo_id)
-        .await?
-        .ok_or_else(|| ApiError::not_found(t!("errors.repository.not_found")))?;
-
-    // Verify ownership
-    if repo.user_id != auth.user_id {
-        return Err(ApiError::not_found(t!("errors.repository.not_found")));
-    }
+    let repo = access.repo;
 
     let pr = PrQueries::find_by_id(&state.db, pr_id)
         .await?
         .ok_or_else(|| ApiError::not_found(t!("errors.pull_request.not_found")))?;
 
     // Verify PR belongs to repo
-    if pr.repository_id != repo_id {
+    if pr.repository_id != repo.id {
         return Err(ApiError::not_found(t!("errors.pull_request.not_found")));
     }
 
@@ -153,41 +315,39 @@ pub async fn get_pull_request(
         .map(|r| r.into())
         .collect();
 
-    let pr_model: PullRequest = pr.into();
-    let status = AmpelStatus::for_pull_request(&pr_model, &checks, &reviews);
+    let metadata = PrMetadataQueries::for_pull_request(&state.db, pr.id).await?;
+    let policy = StatusPolicyQueries::policy_for_repository(&state.db, repo.id).await?;
 
-    Ok(Json(ApiResponse::success(PullRequestWithDetails {
-        pull_request: pr_model,
-        status,
-        ci_checks: checks,
+    let mut details = PrService::new().with_details(
+        pr.into(),
+        checks,
         reviews,
-        repository_name: repo.name,
-        repository_owner: repo.owner,
-    })))
+        metadata,
+        repo.owner,
+        repo.name,
+        &policy,
+    );
+    localize_reasons(&mut details, &locale.code);
+
+    Ok(Json(ApiResponse::success(details)))
 }
 
-/// Merge a pull request
+/// Merge a pull request; requires the member role on its repository. The
+/// merge uses the provider account the repository was added with.
 pub async fn merge_pull_request(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((repo_id, pr_id)): Path<(Uuid, Uuid)>,
+    access: RepoMember,
+    Path((_, pr_id)): Path<(Uuid, Uuid)>,
     Json(merge_req): Json<MergeRequest>,
 ) -> Result<Json<ApiResponse<MergeResultResponse>>, ApiError> {
-    let repo = RepoQueries::find_by_id(&state.db, repo_id)
-        .await?
-        .ok_or_else(|| ApiError::not_found(t!("errors.repository.not_found")))?;
-
-    // Verify ownership
-    if repo.user_id != auth.user_id {
-        return Err(ApiError::not_found(t!("errors.repository.not_found")));
-    }
+    let repo = access.repo;
 
     let pr = PrQueries::find_by_id(&state.db, pr_id)
         .await?
         .ok_or_else(|| ApiError::not_found(t!("errors.pull_request.not_found")))?;
 
     // Verify PR belongs to repo
-    if pr.repository_id != repo_id {
+    if pr.repository_id != repo.id {
         return Err(ApiError::not_found(t!("errors.pull_request.not_found")));
     }
 
@@ -204,19 +364,15 @@ pub async fn merge_pull_request(
     .await?
     .ok_or(ApiError::not_found(t!("errors.account.not_found")))?;
 
-    // Decrypt access token
-    let access_token = state
-        .encryption_service
-        .decrypt(&account.access_token_encrypted)
-        .map_err(|e| {
-            ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string()))
-        })?;
-
-    // Create credentials
-    let credentials = ampel_providers::traits::ProviderCredentials::Pat {
-        token: access_token,
-        username: account.auth_username.clone(),
-    };
+    // Decrypt credentials, refreshing an expiring OAuth token
+    let credentials = account_credentials(
+        &state.db,
+        &state.encryption_service,
+        &state.provider_factory,
+        &account,
+    )
+    .await
+    .map_err(|e| ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string())))?;
 
     let provider = state
         .provider_factory
@@ -234,7 +390,7 @@ pub async fn merge_pull_request(
 
     // Update PR state in database
     if result.merged {
-        PrQueries::update_state(
+        let merged = PrQueries::update_state(
             &state.db,
             pr_id,
             "merged".to_string(),
@@ -242,6 +398,7 @@ pub async fn merge_pull_request(
             Some(chrono::Utc::now()),
         )
         .await?;
+        record_pr_closed(&repo, &merged).await;
     }
 
     Ok(Json(ApiResponse::success(MergeResultResponse {
@@ -258,27 +415,21 @@ pub struct MergeResultResponse {
     pub message: String,
 }
 
-/// Refresh PR data from provider
+/// Refresh PR data from provider; requires the member role on its repository
 pub async fn refresh_pull_request(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((repo_id, pr_id)): Path<(Uuid, Uuid)>,
+    Extension(locale): Extension<DetectedLocale>,
+    access: RepoMember,
+    Path((_, pr_id)): Path<(Uuid, Uuid)>,
 ) -> Result<Json<ApiResponse<PullRequestWithDetails>>, ApiError> {
-    let repo = RepoQueries::find_by_id(&state.db, repo_id)
-        .await?
-        .ok_or_else(|| ApiError::not_found(t!("errors.repository.not_found")))?;
-
-    // Verify ownership
-    if repo.user_id != auth.user_id {
-        return Err(ApiError::not_found(t!("errors.repository.not_found")));
-    }
+    let repo = access.repo;
 
     let pr = PrQueries::find_by_id(&state.db, pr_id)
         .await?
         .ok_or_else(|| ApiError::not_found(t!("errors.pull_request.not_found")))?;
 
     // Verify PR belongs to repo
-    if pr.repository_id != repo_id {
+    if pr.repository_id != repo.id {
         return Err(ApiError::not_found(t!("errors.pull_request.not_found")));
     }
 
@@ -295,19 +446,15 @@ pub async fn refresh_pull_request(
     .await?
     .ok_or(ApiError::not_found(t!("errors.account.not_found")))?;
 
-    // Decrypt access token
-    let access_token = state
-        .encryption_service
-        .decrypt(&account.access_token_encrypted)
-        .map_err(|e| {
-            ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string()))
-        })?;
-
-    // Create credentials
-    let credentials = ampel_providers::traits::ProviderCredentials::Pat {
-        token: access_token.clone(),
-        username: account.auth_username.clone(),
-    };
+    // Decrypt credentials, refreshing an expiring OAuth token
+    let credentials = account_credentials(
+        &state.db,
+        &state.encryption_service,
+        &state.provider_factory,
+        &account,
+    )
+    .await
+    .map_err(|e| ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string())))?;
 
     let provider = state
         .provider_factory
@@ -321,9 +468,14 @@ pub async fn refresh_pull_request(
 
     // Update PR in database
     let state_str = fresh_pr.state.clone();
+    let metadata = PullRequestMetadata {
+        labels: fresh_pr.labels,
+        assignees: fresh_pr.assignees,
+        requested_reviewers: fresh_pr.requested_reviewers,
+    };
     let updated_pr = PrQueries::upsert(
         &state.db,
-        repo_id,
+        repo.id,
         provider_type.to_string(),
         fresh_pr.provider_id,
         fresh_pr.number,
@@ -347,8 +499,11 @@ pub async fn refresh_pull_request(
         fresh_pr.updated_at,
         fresh_pr.merged_at,
         fresh_pr.closed_at,
+        fresh_pr.head_sha,
+        fresh_pr.milestone,
     )
     .await?;
+    PrMetadataQueries::replace(&state.db, pr_id, &metadata).await?;
 
     // Fetch and update CI checks
     CICheckQueries::delete_by_pull_request(&state.db, pr_id).await?;
@@ -389,6 +544,7 @@ pub async fn refresh_pull_request(
             review.reviewer.clone(),
             review.reviewer_avatar_url.clone(),
             review.state.clone(),
+            review.commit_sha.clone(),
             review.body.clone(),
             review.submitted_at,
         )
@@ -408,15 +564,18 @@ pub async fn refresh_pull_request(
         .map(|r| r.into())
         .collect();
 
-    let pr_model: PullRequest = updated_pr.into();
-    let status = AmpelStatus::for_pull_request(&pr_model, &checks, &reviews);
+    let policy = StatusPolicyQueries::policy_for_repository(&state.db, repo.id).await?;
 
-    Ok(Json(ApiResponse::success(PullRequestWithDetails {
-        pull_request: pr_model,
-        status,
-        ci_checks: checks,
+    let mut details = PrService::new().with_details(
+        updated_pr.into(),
+        checks,
         reviews,
-        repository_name: repo.name,
-        repository_owner: repo.owner,
-    })))
+        metadata,
+        repo.owner,
+        repo.name,
+        &policy,
+    );
+    localize_reasons(&mut details, &locale.code);
+
+    Ok(Json(ApiResponse::success(details)))
 }
@@ -23,15 +23,16 @@ use sea_orm::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use ampel_core::models::{OrgRole, TeamRole};
 use ampel_core::remediation::{
-    AutonomyLevel, ConsolidationPlan, PrSelectionStrategy, RemediationTier, ScopeType,
+    AutonomyLevel, ConsolidationPlan, ConsolidationStrategy, PrSelectionStrategy, RemediationTier,
+    ScopeType,
 };
 use ampel_core::services::{PolicyResolver, RemediationService};
-use ampel_db::entities::{
-    organization, pull_request, remediation_policy, repository, team, team_member, user,
-};
+use ampel_db::entities::{pull_request, remediation_policy, repository, team, team_member, user};
+use ampel_db::queries::OrganizationQueries;
 
-use crate::extractors::AuthUser;
+use crate::extractors::{team_role, AuthUser};
 use crate::handlers::{ApiError, ApiResponse};
 use crate::AppState;
 
@@ -52,6 +53,8 @@ pub struct CreatePolicyRequest {
     pub autonomy_level: AutonomyLevel,
     /// Required when `autonomy_level` is `fully_autonomous` (DDD invariant).
     pub remediation_tier: Option<RemediationTier>,
+    /// Defaults to `merge`.
+    pub consolidation_strategy: Option<ConsolidationStrategy>,
     pub max_prs_per_run: i32,
     pub allowed_targets: Option<Vec<String>>,
     pub skip_draft: Option<bool>,
@@ -74,6 +77,7 @@ pub struct UpdatePolicyRequest {
     pub pr_selection: Option<PrSelectionStrategy>,
     pub autonomy_level: Option<AutonomyLevel>,
     pub remediation_tier: Option<RemediationTier>,
+    pub consolidation_strategy: Option<ConsolidationStrategy>,
     pub max_prs_per_run: Option<i32>,
     pub allowed_targets: Option<Vec<String>>,
     pub skip_draft: Option<bool>,
@@ -98,6 +102,7 @@ pub struct PolicyResponse {
     pub pr_selection: PrSelectionStrategy,
     pub autonomy_level: String,
     pub remediation_tier: String,
+    pub consolidation_strategy: String,
     pub max_prs_per_run: i32,
     pub allowed_targets: Vec<String>,
     pub skip_draft: bool,
@@ -124,6 +129,7 @@ impl From<remediation_policy::Model> for PolicyResponse {
             pr_selection: serde_json::from_str(&m.pr_selection).unwrap_or_default(),
             autonomy_level: m.autonomy_level,
             remediation_tier: m.remediation_tier,
+            consolidation_strategy: m.consolidation_strategy,
             max_prs_per_run: m.max_prs_per_run,
             allowed_targets: serde_json::from_str(&m.allowed_targets).unwrap_or_default(),
             skip_draft: m.skip_draft,
@@ -190,13 +196,24 @@ fn validate_invariants(
 // Scope / tenant authorization
 // ============================================================================
 
-/// Ensure `user_id` may manage policies for `(scope_type, scope_id)`. Returns a
-/// 404 (rather than 403) on denial to avoid leaking the existence of resources.
+/// What a caller wants to do with a policy scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopeAccess {
+    /// Team members (any role) and organization members
+    Read,
+    /// Team admins and organization admins
+    Manage,
+}
+
+/// Ensure `user_id` may read or manage policies for `(scope_type, scope_id)`.
+/// Returns a 404 (rather than 403) on denial to avoid leaking the existence of
+/// resources.
 async fn assert_scope_access(
     state: &AppState,
     user_id: Uuid,
     scope_type: ScopeType,
     scope_id: Uuid,
+    access: ScopeAccess,
 ) -> Result<(), ApiError> {
     let allowed = match scope_type {
         ScopeType::User => scope_id == user_id,
@@ -205,19 +222,27 @@ async fn assert_scope_access(
             .await?
             .map(|r| r.user_id == user_id)
             .unwrap_or(false),
-        ScopeType::Team => {
-            team_member::Entity::find()
-                .filter(team_member::Column::TeamId.eq(scope_id))
-                .filter(team_member::Column::UserId.eq(user_id))
-                .count(&state.db)
+        ScopeType::Team => match team::Entity::find_by_id(scope_id).one(&state.db).await? {
+            Some(team) => {
+                let min = match access {
+                    ScopeAccess::Read => TeamRole::Viewer,
+                    ScopeAccess::Manage => TeamRole::Admin,
+                };
+                team_role(state, user_id, &team)
+                    .await?
+                    .is_some_and(|role| role >= min)
+            }
+            None => false,
+        },
+        ScopeType::Org => {
+            let min = match access {
+                ScopeAccess::Read => OrgRole::Member,
+                ScopeAccess::Manage => OrgRole::Admin,
+            };
+            OrganizationQueries::role_of(&state.db, scope_id, user_id)
                 .await?
-                > 0
+                .is_some_and(|role| role >= min)
         }
-        ScopeType::Org => organization::Entity::find_by_id(scope_id)
-            .one(&state.db)
-            .await?
-            .map(|o| o.owner_id == user_id)
-            .unwrap_or(false),
     };
 
     if allowed {
@@ -227,7 +252,7 @@ async fn assert_scope_access(
     }
 }
 
-/// Collect the scope ids the caller can manage, grouped by scope type.
+/// Collect the scope ids the caller can read, grouped by scope type.
 struct CallerScopes {
     user_id: Uuid,
     repo_ids: Vec<Uuid>,
@@ -244,21 +269,9 @@ async fn caller_scopes(state: &AppState, user_id: Uuid) -> Result<CallerScopes,
         .map(|r| r.id)
         .collect();
 
-    let team_ids: Vec<Uuid> = team_member::Entity::find()
-        .filter(team_member::Column::UserId.eq(user_id))
-        .all(&state.db)
-        .await?
-        .into_iter()
-        .map(|m| m.team_id)
-        .collect();
-
-    let org_ids = organization::Entity::find()
-        .filter(organization::Column::OwnerId.eq(user_id))
-        .all(&state.db)
-        .await?
-        .into_iter()
-        .map(|o| o.id)
-        .collect();
+    let team_ids = team_ids_for(state, user_id, ScopeAccess::Read).await?;
+    let org_ids =
+        OrganizationQueries::org_ids_for_user(&state.db, user_id, OrgRole::Member).await?;
 
     Ok(CallerScopes {
         user_id,
@@ -268,6 +281,43 @@ async fn caller_scopes(state: &AppState, user_id: Uuid) -> Result<CallerScopes,
     })
 }
 
+/// Teams the caller may read or manage policies for: their own team
+/// memberships (admin ones only for `Manage`) plus every team of the
+/// organizations they administer.
+async fn team_ids_for(
+    state: &AppState,
+    user_id: Uuid,
+    access: ScopeAccess,
+) -> Result<Vec<Uuid>, ApiError> {
+    let mut memberships =
+        team_member::Entity::find().filter(team_member::Column::UserId.eq(user_id));
+    if access == ScopeAccess::Manage {
+        memberships = memberships.filter(team_member::Column::Role.eq(TeamRole::Admin.to_string()));
+    }
+    let mut team_ids: Vec<Uuid> = memberships
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|m| m.team_id)
+        .collect();
+
+    let admin_org_ids =
+        OrganizationQueries::org_ids_for_user(&state.db, user_id, OrgRole::Admin).await?;
+    if !admin_org_ids.is_empty() {
+        let org_team_ids = team::Entity::find()
+            .filter(team::Column::OrganizationId.is_in(admin_org_ids))
+            .all(&state.db)
+            .await?
+            .into_iter()
+            .map(|t| t.id);
+        team_ids.extend(org_team_ids);
+    }
+
+    team_ids.sort_unstable();
+    team_ids.dedup();
+    Ok(team_ids)
+}
+
 // ============================================================================
 // Policy CRUD
 // ============================================================================
@@ -366,13 +416,7 @@ pub async fn list_scopes(
         })
         .collect();
 
-    let team_ids: Vec<Uuid> = team_member::Entity::find()
-        .filter(team_member::Column::UserId.eq(auth.user_id))
-        .all(&state.db)
-        .await?
-        .into_iter()
-        .map(|m| m.team_id)
-        .collect();
+    let team_ids = team_ids_for(&state, auth.user_id, ScopeAccess::Manage).await?;
     let teams = if team_ids.is_empty() {
         Vec::new()
     } else {
@@ -388,12 +432,11 @@ pub async fn list_scopes(
             .collect()
     };
 
-    let orgs = organization::Entity::find()
-        .filter(organization::Column::OwnerId.eq(auth.user_id))
-        .all(&state.db)
+    let orgs = OrganizationQueries::list_for_user(&state.db, auth.user_id)
         .await?
         .into_iter()
-        .map(|o| ScopeOption {
+        .filter(|(_, role)| *role >= OrgRole::Admin)
+        .map(|(o, _)| ScopeOption {
             id: o.id,
             label: o.name,
         })
@@ -413,7 +456,14 @@ pub async fn create_policy(
     auth: AuthUser,
     Json(req): Json<CreatePolicyRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<PolicyResponse>>), ApiError> {
-    assert_scope_access(&state, auth.user_id, req.scope_type, req.scope_id).await?;
+    assert_scope_access(
+        &state,
+        auth.user_id,
+        req.scope_type,
+        req.scope_id,
+        ScopeAccess::Manage,
+    )
+    .await?;
 
     let auto_merge_enabled = req.auto_merge_enabled.unwrap_or(false);
     let require_human_approval = req.require_human_approval.unwrap_or(false);
@@ -451,6 +501,7 @@ pub async fn create_policy(
         pr_selection: Set(pr_selection_json),
         autonomy_level: Set(req.autonomy_level.to_string()),
         remediation_tier: Set(remediation_tier.to_string()),
+        consolidation_strategy: Set(req.consolidation_strategy.unwrap_or_default().to_string()),
         max_prs_per_run: Set(req.max_prs_per_run),
         allowed_targets: Set(allowed_targets_json),
         skip_draft: Set(req.skip_draft.unwrap_or(true)),
@@ -485,6 +536,7 @@ async fn load_authorized_policy(
     state: &AppState,
     user_id: Uuid,
     policy_id: Uuid,
+    access: ScopeAccess,
 ) -> Result<remediation_policy::Model, ApiError> {
     let policy = remediation_policy::Entity::find_by_id(policy_id)
         .one(&state.db)
@@ -495,7 +547,7 @@ async fn load_authorized_policy(
         .scope_type
         .parse()
         .map_err(|_| ApiError::internal("invalid scope_type in database"))?;
-    assert_scope_access(state, user_id, scope_type, policy.scope_id).await?;
+    assert_scope_access(state, user_id, scope_type, policy.scope_id, access).await?;
     Ok(policy)
 }
 
@@ -505,7 +557,7 @@ pub async fn get_policy(
     auth: AuthUser,
     Path(policy_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<PolicyResponse>>, ApiError> {
-    let policy = load_authorized_policy(&state, auth.user_id, policy_id).await?;
+    let policy = load_authorized_policy(&state, auth.user_id, policy_id, ScopeAccess::Read).await?;
     Ok(Json(ApiResponse::success(PolicyResponse::from(policy))))
 }
 
@@ -516,7 +568,8 @@ pub async fn update_policy(
     Path(policy_id): Path<Uuid>,
     Json(req): Json<UpdatePolicyRequest>,
 ) -> Result<Json<ApiResponse<PolicyResponse>>, ApiError> {
-    let policy = load_authorized_policy(&state, auth.user_id, policy_id).await?;
+    let policy =
+        load_authorized_policy(&state, auth.user_id, policy_id, ScopeAccess::Manage).await?;
 
     // Compute effective values for invariant validation.
     let effective_auto_merge = req.auto_merge_enabled.unwrap_or(policy.auto_merge_enabled);
@@ -551,6 +604,9 @@ pub async fn update_policy(
     if let Some(v) = req.remediation_tier {
         active.remediation_tier = Set(v.to_string());
     }
+    if let Some(v) = req.consolidation_strategy {
+        active.consolidation_strategy = Set(v.to_string());
+    }
     if let Some(v) = req.max_prs_per_run {
         active.max_prs_per_run = Set(v);
     }
@@ -599,7 +655,8 @@ pub async fn delete_policy(
     auth: AuthUser,
     Path(policy_id): Path<Uuid>,
 ) -> Result<StatusCode, ApiError> {
-    let policy = load_authorized_policy(&state, auth.user_id, policy_id).await?;
+    let policy =
+        load_authorized_policy(&state, auth.user_id, policy_id, ScopeAccess::Manage).await?;
     remediation_policy::Entity::delete_by_id(policy.id)
         .exec(&state.db)
         .await?;
@@ -613,7 +670,8 @@ pub async fn toggle_policy(
     auth: AuthUser,
     Path(policy_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<PolicyResponse>>, ApiError> {
-    let policy = load_authorized_policy(&state, auth.user_id, policy_id).await?;
+    let policy =
+        load_authorized_policy(&state, auth.user_id, policy_id, ScopeAccess::Manage).await?;
     let next = !policy.enabled;
     let mut active: remediation_policy::ActiveModel = policy.into();
     active.enabled = Set(next);
@@ -667,13 +725,12 @@ pub async fn get_fleet(
         .all(&state.db)
         .await?;
 
-    // Owned-org air-gapped ceiling (matches ADR-014 for the common case).
-    let org_air_gapped = organization::Entity::find()
-        .filter(organization::Column::OwnerId.eq(auth.user_id))
-        .filter(organization::Column::AirGapped.eq(true))
-        .count(&state.db)
+    // Air-gapped ceiling of the caller's organizations (matches ADR-014 for the
+    // common case).
+    let org_air_gapped = OrganizationQueries::list_for_user(&state.db, auth.user_id)
         .await?
-        > 0;
+        .iter()
+        .any(|(org, _)| org.air_gapped);
 
     let resolver = PolicyResolver::new(state.db.clone());
     let mut rows = Vec::with_capacity(repos.len());
@@ -8,7 +8,7 @@
 //!
 //! ## Authorization
 //! - `scope_type=user`   → `scope_id == auth.user_id`.
-//! - `scope_type=org`    → caller owns the organization.
+//! - `scope_type=org`    → caller is an organization admin or owner.
 //! - `scope_type=team`   → caller is an **admin** member (`team_member.role='admin'`).
 //! - `scope_type=repository` → caller owns the repository.
 //! - `scope_id IS NULL`  → built-in/global sentinel: readable by any authenticated
@@ -42,8 +42,10 @@ use sea_orm::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use ampel_core::models::OrgRole;
 use ampel_core::remediation::{FailureClass, ScopeType};
-use ampel_db::entities::{organization, remediation_playbook, repository, team_member};
+use ampel_db::entities::{remediation_playbook, repository, team_member};
+use ampel_db::queries::OrganizationQueries;
 use ampel_worker::services::playbook_resolver::{
     build_system_instruction, resolve, PlaybookContext, PlaybookScope,
 };
@@ -152,8 +154,8 @@ fn invalid_playbook(e: ampel_worker::services::playbook::PlaybookSchemaError) ->
 // Scope / ownership authorization
 // ============================================================================
 
-/// Whether `user_id` administers `(scope_type, scope_id)` — owner for
-/// org/repository, `admin` member for team, self for user.
+/// Whether `user_id` administers `(scope_type, scope_id)` — admin or owner for
+/// org, owner for repository, `admin` member for team, self for user.
 async fn scope_admin_access(
     state: &AppState,
     user_id: Uuid,
@@ -176,11 +178,9 @@ async fn scope_admin_access(
                 .await?
                 > 0
         }
-        ScopeType::Org => organization::Entity::find_by_id(scope_id)
-            .one(&state.db)
+        ScopeType::Org => OrganizationQueries::role_of(&state.db, scope_id, user_id)
             .await?
-            .map(|o| o.owner_id == user_id)
-            .unwrap_or(false),
+            .is_some_and(|role| role >= OrgRole::Admin),
     };
     Ok(allowed)
 }
@@ -269,13 +269,8 @@ pub async fn list_playbooks(
 ) -> Result<Json<ApiResponse<Vec<PlaybookResponse>>>, ApiError> {
     let user_id = auth.user_id;
 
-    let owned_org_ids: Vec<Uuid> = organization::Entity::find()
-        .filter(organization::Column::OwnerId.eq(user_id))
-        .all(&state.db)
-        .await?
-        .into_iter()
-        .map(|o| o.id)
-        .collect();
+    let owned_org_ids =
+        OrganizationQueries::org_ids_for_user(&state.db, user_id, OrgRole::Admin).await?;
     let admin_team_ids: Vec<Uuid> = team_member::Entity::find()
         .filter(team_member::Column::UserId.eq(user_id))
         .filter(team_member::Column::Role.eq("admin"))
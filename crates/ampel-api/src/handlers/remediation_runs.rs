@@ -55,11 +55,11 @@ use crate::AppState;
 // ============================================================================
 
 /// How often the SSE stream re-reads the run row.
-const SSE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub(crate) const SSE_POLL_INTERVAL: Duration = Duration::from_secs(2);
 /// KeepAlive comment cadence to defeat idle proxies.
-const SSE_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+pub(crate) const SSE_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
 /// Safety cap: never hold an SSE connection open longer than this.
-const SSE_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
+pub(crate) const SSE_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
 /// TTL of a minted SSE token.
 const SSE_TOKEN_TTL_SECS: i64 = 30;
 /// JWT scope claim distinguishing an SSE token from an access token.
@@ -146,6 +146,9 @@ pub struct CiMatrix {
 pub struct ConflictEntry {
     pub pr_number: i64,
     pub reason: String,
+    /// Files that conflicted; empty for PRs left open, and for conflicts
+    /// recorded before file lists were kept.
+    pub files: Vec<String>,
 }
 
 #[derive(Debug, Default, Serialize)]
@@ -356,16 +359,18 @@ pub async fn get_run(
 
         if let Ok(disp) = serde_json::from_str::<MergeDisposition>(&row.disposition) {
             match disp {
-                MergeDisposition::SkippedConflict { reason } => {
+                MergeDisposition::SkippedConflict { reason, files } => {
                     conflict_report.conflicts.push(ConflictEntry {
                         pr_number: row.pr_number,
                         reason,
+                        files,
                     });
                 }
                 MergeDisposition::LeftOpen { reason } => {
                     conflict_report.skipped.push(ConflictEntry {
                         pr_number: row.pr_number,
                         reason,
+                        files: Vec::new(),
                     });
                 }
                 _ => {}
@@ -453,7 +458,7 @@ pub(crate) fn diff_snapshot(last: &RunSnapshot, current: &RunSnapshot) -> SseDec
     }
 }
 
-fn sse_event(name: &str, payload: serde_json::Value) -> Event {
+pub(crate) fn sse_event(name: &str, payload: serde_json::Value) -> Event {
     Event::default()
         .event(name)
         .data(serde_json::to_string(&payload).unwrap_or_default())
@@ -492,7 +497,7 @@ fn run_finished_event(m: &remediation_run::Model) -> Event {
 
 /// Authenticate an SSE request from either a `Bearer` access token (header) or a
 /// short-lived `?token=` SSE token. Returns the authenticated user id.
-fn authenticate_sse(
+pub(crate) fn authenticate_sse(
     state: &AppState,
     headers: &HeaderMap,
     token: Option<&str>,
@@ -677,8 +682,8 @@ pub async fn cancel_run(
 // ============================================================================
 
 /// POST /api/remediation/repositories/{repo_id}/run — create a `created` run for
-/// the worker sweep to pick up. Rejects unowned repos (404) and repos with no
-/// enabled policy (422).
+/// the worker to enqueue on its run queue. Rejects unowned repos (404) and repos
+/// with no enabled policy (422).
 pub async fn trigger_run(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -725,6 +730,8 @@ pub async fn trigger_run(
         attempts: sea_orm::ActiveValue::Set(0),
         error_message: sea_orm::ActiveValue::Set(None),
         error_class: sea_orm::ActiveValue::Set(None),
+        queued_at: sea_orm::ActiveValue::Set(None),
+        heartbeat_at: sea_orm::ActiveValue::Set(None),
         started_at: sea_orm::ActiveValue::Set(now),
         completed_at: sea_orm::ActiveValue::Set(None),
         created_at: sea_orm::ActiveValue::Set(now),
@@ -17,6 +17,7 @@ use ampel_core::models::{
 use ampel_db::entities::provider_account;
 use ampel_db::queries::{PrQueries, RepoQueries};
 use ampel_worker::jobs::poll_repository::PollRepositoryJob;
+use ampel_worker::services::account_credentials::account_credentials;
 
 use crate::extractors::{AuthUser, ValidatedJson};
 use crate::handlers::{ApiError, ApiResponse};
@@ -121,19 +122,15 @@ pub async fn discover_repositories(
         .await?
         .ok_or_else(|| ApiError::bad_request(t!("errors.repository.provider_not_connected")))?;
 
-    // Decrypt access token
-    let access_token = state
-        .encryption_service
-        .decrypt(&account.access_token_encrypted)
-        .map_err(|e| {
-            ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string()))
-        })?;
-
-    // Create credentials
-    let credentials = ampel_providers::traits::ProviderCredentials::Pat {
-        token: access_token,
-        username: account.auth_username.clone(),
-    };
+    // Decrypt credentials, refreshing an expiring OAuth token
+    let credentials = account_credentials(
+        &state.db,
+        &state.encryption_service,
+        &state.provider_factory,
+        &account,
+    )
+    .await
+    .map_err(|e| ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string())))?;
 
     let provider = state
         .provider_factory
@@ -196,19 +193,15 @@ pub async fn add_repository(
         .await?
         .ok_or_else(|| ApiError::bad_request(t!("errors.repository.provider_not_connected")))?;
 
-    // Decrypt access token
-    let access_token = state
-        .encryption_service
-        .decrypt(&account.access_token_encrypted)
-        .map_err(|e| {
-            ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string()))
-        })?;
-
-    // Create credentials
-    let credentials = ampel_providers::traits::ProviderCredentials::Pat {
-        token: access_token,
-        username: account.auth_username.clone(),
-    };
+    // Decrypt credentials, refreshing an expiring OAuth token
+    let credentials = account_credentials(
+        &state.db,
+        &state.encryption_service,
+        &state.provider_factory,
+        &account,
+    )
+    .await
+    .map_err(|e| ApiError::internal(t!("errors.provider.decrypt_failed", error = e.to_string())))?;
 
     let provider = state
         .provider_factory
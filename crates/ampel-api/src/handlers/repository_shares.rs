@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rust_i18n::t;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use ampel_db::entities::{repository_share, team};
+use ampel_db::queries::{NewRepositoryShare, OrganizationQueries, RepositoryShareQueries};
+
+use crate::extractors::{team_role, RepoAdmin, RepoViewer};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryShareResponse {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub team_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<repository_share::Model> for RepositoryShareResponse {
+    fn from(share: repository_share::Model) -> Self {
+        Self {
+            id: share.id,
+            repository_id: share.repository_id,
+            team_id: share.team_id,
+            organization_id: share.organization_id,
+            created_at: share.created_at,
+        }
+    }
+}
+
+/// Share with exactly one of a team or an organization
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRepositoryShareRequest {
+    pub team_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+}
+
+/// List the teams and organizations a repository is shared with
+pub async fn list_shares(
+    State(state): State<AppState>,
+    access: RepoViewer,
+) -> Result<Json<ApiResponse<Vec<RepositoryShareResponse>>>, ApiError> {
+    let shares = RepositoryShareQueries::list_for_repository(&state.db, access.repo.id)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Json(ApiResponse::success(shares)))
+}
+
+/// Share a repository with a team or an organization the caller belongs to;
+/// requires the admin role on the repository
+pub async fn create_share(
+    State(state): State<AppState>,
+    access: RepoAdmin,
+    Json(req): Json<CreateRepositoryShareRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<RepositoryShareResponse>>), ApiError> {
+    let user_id = access.auth.user_id;
+    match (req.team_id, req.organization_id) {
+        (Some(team_id), None) => {
+            let not_found = || ApiError::not_found(t!("errors.team.not_found"));
+            let team = team::Entity::find_by_id(team_id)
+                .one(&state.db)
+                .await?
+                .ok_or_else(not_found)?;
+            team_role(&state, user_id, &team)
+                .await?
+                .ok_or_else(not_found)?;
+        }
+        (None, Some(org_id)) => {
+            OrganizationQueries::role_of(&state.db, org_id, user_id)
+                .await?
+                .ok_or_else(|| ApiError::not_found(t!("errors.organization.not_found")))?;
+        }
+        _ => {
+            return Err(ApiError::bad_request(t!(
+                "errors.repository.share_target_required"
+            )))
+        }
+    }
+
+    let existing = RepositoryShareQueries::list_for_repository(&state.db, access.repo.id).await?;
+    if existing.iter().any(|share| {
+        (req.team_id.is_some() && share.team_id == req.team_id)
+            || (req.organization_id.is_some() && share.organization_id == req.organization_id)
+    }) {
+        return Err(ApiError::conflict(t!("errors.repository.already_shared")));
+    }
+
+    let share = RepositoryShareQueries::create(
+        &state.db,
+        NewRepositoryShare {
+            repository_id: access.repo.id,
+            team_id: req.team_id,
+            organization_id: req.organization_id,
+            created_by: user_id,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(share.into())),
+    ))
+}
+
+/// Stop sharing a repository; requires the admin role on the repository
+pub async fn delete_share(
+    State(state): State<AppState>,
+    access: RepoAdmin,
+    Path((_, share_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let share = RepositoryShareQueries::find_by_id(&state.db, share_id)
+        .await?
+        .filter(|share| share.repository_id == access.repo.id)
+        .ok_or_else(|| ApiError::not_found(t!("errors.repository.share_not_found")))?;
+    RepositoryShareQueries::delete(&state.db, share.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,283 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rust_i18n::t;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use ampel_core::errors::AmpelError;
+use ampel_core::models::{GitProvider, PrQuery, TeamRole};
+use ampel_db::entities::{repository, saved_view, team, team_member};
+use ampel_db::queries::{ProviderAccountQueries, SavedViewQueries};
+
+use crate::extractors::{team_role, AuthUser};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// Longest view name accepted, in characters
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedViewResponse {
+    pub id: Uuid,
+    /// The user who created the view
+    pub user_id: Uuid,
+    /// The team the view is shared with
+    pub team_id: Option<Uuid>,
+    pub name: String,
+    pub query: String,
+    /// Dashboard link showing the view; it stays valid when the view changes
+    pub url: String,
+    /// Whether the caller may change or delete the view
+    pub can_edit: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SavedViewResponse {
+    fn new(state: &AppState, view: saved_view::Model, can_edit: bool) -> Self {
+        Self {
+            url: format!("{}/dashboard?view={}", state.config.app_url, view.id),
+            id: view.id,
+            user_id: view.user_id,
+            team_id: view.team_id,
+            name: view.name,
+            query: view.query,
+            can_edit,
+            created_at: view.created_at,
+            updated_at: view.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedViewRequest {
+    pub name: String,
+    pub query: String,
+    /// Team to share the view with; omit to keep it private
+    pub team_id: Option<Uuid>,
+}
+
+/// Query parameters that narrow a PR listing to a filter query, a saved
+/// view, or both
+#[derive(Debug, Default, Deserialize)]
+pub struct ViewFilter {
+    /// Filter query, e.g. `status:red repo:org/*`
+    pub q: Option<String>,
+    /// Saved view whose query applies
+    pub view: Option<Uuid>,
+}
+
+/// Parse a filter query, rejecting malformed ones with 400
+pub(crate) fn parse_query(query: &str) -> Result<PrQuery, ApiError> {
+    PrQuery::parse(query).map_err(|e| {
+        let reason = match e {
+            AmpelError::ValidationError(reason) => reason,
+            other => other.to_string(),
+        };
+        ApiError::bad_request(t!("errors.saved_view.invalid_query", reason = reason))
+    })
+}
+
+/// The query PRs are filtered by: the terms of the saved view `view` and of
+/// `q` together, or `None` when neither narrows anything. Callers match it
+/// against every repository the user can access, so a view shared with a
+/// team selects the same PRs for each member the repositories are shared with
+pub(crate) async fn resolve_filter(
+    state: &AppState,
+    user_id: Uuid,
+    q: Option<&str>,
+    view: Option<Uuid>,
+) -> Result<Option<PrQuery>, ApiError> {
+    let mut query = PrQuery::default();
+    if let Some(view_id) = view {
+        let (view, _) = find_view(state, user_id, view_id).await?;
+        query = query.and(parse_query(&view.query)?);
+    }
+    if let Some(q) = q {
+        query = query.and(parse_query(q)?);
+    }
+    if query.is_empty() {
+        return Ok(None);
+    }
+
+    // `@me` is the caller under any of their provider logins
+    let logins: Vec<String> = ProviderAccountQueries::find_by_user(&state.db, user_id)
+        .await?
+        .into_iter()
+        .map(|account| account.provider_username)
+        .collect();
+
+    Ok(Some(query.for_viewer(&logins)))
+}
+
+/// Whether `query` can match PRs of `repo`, judging by its name and provider
+pub(crate) fn repository_matches(query: &PrQuery, repo: &repository::Model) -> bool {
+    repo.provider
+        .parse::<GitProvider>()
+        .is_ok_and(|provider| query.matches_repository(&repo.full_name, provider))
+}
+
+/// Whether `user_id` can see a view, and if so whether they may change it.
+/// Owners may change their views; members of the team a view is shared with
+/// see it, and its admins may change it.
+async fn view_access(
+    state: &AppState,
+    user_id: Uuid,
+    view: &saved_view::Model,
+) -> Result<Option<bool>, ApiError> {
+    if view.user_id == user_id {
+        return Ok(Some(true));
+    }
+    let Some(team_id) = view.team_id else {
+        return Ok(None);
+    };
+    let Some(team) = team::Entity::find_by_id(team_id).one(&state.db).await? else {
+        return Ok(None);
+    };
+
+    Ok(team_role(state, user_id, &team)
+        .await?
+        .map(|role| role >= TeamRole::Admin))
+}
+
+/// Load a view the user can see, with whether they may change it
+async fn find_view(
+    state: &AppState,
+    user_id: Uuid,
+    view_id: Uuid,
+) -> Result<(saved_view::Model, bool), ApiError> {
+    let not_found = || ApiError::not_found(t!("errors.saved_view.not_found"));
+    let view = SavedViewQueries::find_by_id(&state.db, view_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let can_edit = view_access(state, user_id, &view)
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok((view, can_edit))
+}
+
+/// Check a view's name, query and team, returning the trimmed name and query
+async fn validate(
+    state: &AppState,
+    user_id: Uuid,
+    req: &SavedViewRequest,
+) -> Result<(String, String), ApiError> {
+    let name = req.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ApiError::bad_request(t!("errors.saved_view.name_required")));
+    }
+    let query = req.query.trim();
+    parse_query(query)?;
+
+    if let Some(team_id) = req.team_id {
+        let not_found = || ApiError::not_found(t!("errors.team.not_found"));
+        let team = team::Entity::find_by_id(team_id)
+            .one(&state.db)
+            .await?
+            .ok_or_else(not_found)?;
+        team_role(state, user_id, &team)
+            .await?
+            .ok_or_else(not_found)?;
+    }
+
+    Ok((name.to_string(), query.to_string()))
+}
+
+/// List the caller's views and those shared with their teams, by name
+pub async fn list_views(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<SavedViewResponse>>>, ApiError> {
+    let team_ids: Vec<Uuid> = team_member::Entity::find()
+        .filter(team_member::Column::UserId.eq(auth.user_id))
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|membership| membership.team_id)
+        .collect();
+
+    let views = SavedViewQueries::list_visible(&state.db, auth.user_id, &team_ids).await?;
+    let mut responses = Vec::with_capacity(views.len());
+    for view in views {
+        let can_edit = view_access(&state, auth.user_id, &view)
+            .await?
+            .unwrap_or(false);
+        responses.push(SavedViewResponse::new(&state, view, can_edit));
+    }
+
+    Ok(Json(ApiResponse::success(responses)))
+}
+
+/// Save a view, optionally sharing it with a team the caller belongs to
+pub async fn create_view(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<SavedViewRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<SavedViewResponse>>), ApiError> {
+    let (name, query) = validate(&state, auth.user_id, &req).await?;
+    let view = SavedViewQueries::create(&state.db, auth.user_id, req.team_id, name, query).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(SavedViewResponse::new(
+            &state, view, true,
+        ))),
+    ))
+}
+
+/// Get a view the caller owns or that is shared with one of their teams
+pub async fn get_view(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(view_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<SavedViewResponse>>, ApiError> {
+    let (view, can_edit) = find_view(&state, auth.user_id, view_id).await?;
+
+    Ok(Json(ApiResponse::success(SavedViewResponse::new(
+        &state, view, can_edit,
+    ))))
+}
+
+/// Replace a view's name, query and team; requires owning the view or
+/// administering the team it is shared with
+pub async fn update_view(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(view_id): Path<Uuid>,
+    Json(req): Json<SavedViewRequest>,
+) -> Result<Json<ApiResponse<SavedViewResponse>>, ApiError> {
+    let (view, can_edit) = find_view(&state, auth.user_id, view_id).await?;
+    if !can_edit {
+        return Err(ApiError::forbidden(t!("errors.saved_view.forbidden")));
+    }
+    let (name, query) = validate(&state, auth.user_id, &req).await?;
+    let view = SavedViewQueries::update(&state.db, view, req.team_id, name, query).await?;
+
+    Ok(Json(ApiResponse::success(SavedViewResponse::new(
+        &state, view, true,
+    ))))
+}
+
+/// Delete a view; requires owning it or administering the team it is
+/// shared with
+pub async fn delete_view(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(view_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let (view, can_edit) = find_view(&state, auth.user_id, view_id).await?;
+    if !can_edit {
+        return Err(ApiError::forbidden(t!("errors.saved_view.forbidden")));
+    }
+    SavedViewQueries::delete(&state.db, view.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
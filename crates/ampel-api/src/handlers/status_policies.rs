@@ -0,0 +1,172 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use rust_i18n::t;
+use serde::Serialize;
+use uuid::Uuid;
+
+use ampel_core::models::{StatusPolicy, StatusPolicySource};
+use ampel_db::entities::status_policy;
+use ampel_db::queries::{RepositoryShareQueries, StatusPolicyQueries};
+
+use crate::extractors::{RepoAdmin, RepoViewer, TeamAdmin, TeamViewer};
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusPolicyResponse {
+    /// Where the policy comes from
+    pub source: StatusPolicySource,
+    /// The team whose default applies, when `source` is `team`
+    pub team_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub policy: StatusPolicy,
+    /// When the policy was last changed; absent for the built-in rules
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Option<status_policy::Model>> for StatusPolicyResponse {
+    fn from(model: Option<status_policy::Model>) -> Self {
+        match model {
+            Some(model) => Self {
+                source: if model.repository_id.is_some() {
+                    StatusPolicySource::Repository
+                } else {
+                    StatusPolicySource::Team
+                },
+                team_id: model.team_id,
+                updated_at: Some(model.updated_at),
+                policy: model.into(),
+            },
+            None => Self {
+                source: StatusPolicySource::Default,
+                team_id: None,
+                policy: StatusPolicy::default(),
+                updated_at: None,
+            },
+        }
+    }
+}
+
+fn validate(policy: &StatusPolicy) -> Result<(), ApiError> {
+    if policy
+        .required_checks
+        .iter()
+        .chain(&policy.ignored_checks)
+        .any(|pattern| pattern.trim().is_empty())
+    {
+        return Err(ApiError::bad_request(t!(
+            "errors.status_policy.empty_check_pattern"
+        )));
+    }
+    if policy.require_named_approver && policy.required_approvers.is_empty() {
+        return Err(ApiError::bad_request(t!(
+            "errors.status_policy.approvers_required"
+        )));
+    }
+    Ok(())
+}
+
+/// Drop the cached dashboard summaries of `owners`, whose PR statuses a
+/// policy change may have moved
+async fn invalidate_summaries(state: &AppState, owners: impl IntoIterator<Item = Uuid>) {
+    if let Some(redis) = &state.redis {
+        for user_id in owners {
+            crate::cache::invalidate_dashboard_cache(&mut redis.clone(), user_id).await;
+        }
+    }
+}
+
+/// Get the policy a repository's PRs are evaluated with: its own, the
+/// default of a team it is shared with, or the built-in rules
+pub async fn get_repository_policy(
+    State(state): State<AppState>,
+    access: RepoViewer,
+) -> Result<Json<ApiResponse<StatusPolicyResponse>>, ApiError> {
+    let policy = StatusPolicyQueries::effective_for_repositories(&state.db, &[access.repo.id])
+        .await?
+        .remove(&access.repo.id);
+
+    Ok(Json(ApiResponse::success(policy.into())))
+}
+
+/// Set a repository's own policy; requires the admin role on the repository
+pub async fn update_repository_policy(
+    State(state): State<AppState>,
+    access: RepoAdmin,
+    Json(policy): Json<StatusPolicy>,
+) -> Result<Json<ApiResponse<StatusPolicyResponse>>, ApiError> {
+    validate(&policy)?;
+    let model = StatusPolicyQueries::upsert(&state.db, Some(access.repo.id), None, &policy).await?;
+    invalidate_summaries(&state, [access.repo.user_id]).await;
+
+    Ok(Json(ApiResponse::success(Some(model).into())))
+}
+
+/// Remove a repository's own policy, falling back to its team default or
+/// the built-in rules; requires the admin role on the repository
+pub async fn delete_repository_policy(
+    State(state): State<AppState>,
+    access: RepoAdmin,
+) -> Result<StatusCode, ApiError> {
+    let policy = StatusPolicyQueries::find_for_repository(&state.db, access.repo.id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.status_policy.not_found")))?;
+    StatusPolicyQueries::delete(&state.db, policy.id).await?;
+    invalidate_summaries(&state, [access.repo.user_id]).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get a team's default policy, or the built-in rules when it has none
+pub async fn get_team_policy(
+    State(state): State<AppState>,
+    access: TeamViewer,
+) -> Result<Json<ApiResponse<StatusPolicyResponse>>, ApiError> {
+    let policy = StatusPolicyQueries::find_for_team(&state.db, access.team.id).await?;
+
+    Ok(Json(ApiResponse::success(policy.into())))
+}
+
+/// Set a team's default policy for the repositories shared with it that
+/// have none of their own; requires the team admin role
+pub async fn update_team_policy(
+    State(state): State<AppState>,
+    access: TeamAdmin,
+    Json(policy): Json<StatusPolicy>,
+) -> Result<Json<ApiResponse<StatusPolicyResponse>>, ApiError> {
+    validate(&policy)?;
+    let model = StatusPolicyQueries::upsert(&state.db, None, Some(access.team.id), &policy).await?;
+    invalidate_team_summaries(&state, &access.team).await?;
+
+    Ok(Json(ApiResponse::success(Some(model).into())))
+}
+
+/// Remove a team's default policy; requires the team admin role
+pub async fn delete_team_policy(
+    State(state): State<AppState>,
+    access: TeamAdmin,
+) -> Result<StatusCode, ApiError> {
+    let policy = StatusPolicyQueries::find_for_team(&state.db, access.team.id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.status_policy.not_found")))?;
+    StatusPolicyQueries::delete(&state.db, policy.id).await?;
+    invalidate_team_summaries(&state, &access.team).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Drop the cached summaries of the owners of a team's shared repositories
+async fn invalidate_team_summaries(
+    state: &AppState,
+    team: &ampel_db::entities::team::Model,
+) -> Result<(), ApiError> {
+    if state.redis.is_none() {
+        return Ok(());
+    }
+    let repos =
+        RepositoryShareQueries::repositories_for_team(&state.db, team.id, team.organization_id)
+            .await?;
+    invalidate_summaries(state, repos.into_iter().map(|repo| repo.user_id)).await;
+    Ok(())
+}
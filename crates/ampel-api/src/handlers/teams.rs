@@ -4,13 +4,16 @@ use axum::{
     Json,
 };
 use chrono::Utc;
+use rust_i18n::t;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use ampel_core::models::{OrgRole, TeamRole};
 use ampel_db::entities::{team, team_member};
+use ampel_db::queries::OrganizationQueries;
 
-use crate::extractors::AuthUser;
+use crate::extractors::{authorize_org, AuthUser, OrgMember, TeamAdmin, TeamViewer};
 use crate::handlers::{ApiError, ApiResponse};
 use crate::AppState;
 
@@ -41,6 +44,23 @@ pub struct AddMemberRequest {
     pub role: String, // admin, member, viewer
 }
 
+async fn team_response(state: &AppState, t: team::Model) -> Result<TeamResponse, ApiError> {
+    let member_count = team_member::Entity::find()
+        .filter(team_member::Column::TeamId.eq(t.id))
+        .count(&state.db)
+        .await? as i64;
+
+    Ok(TeamResponse {
+        id: t.id,
+        organization_id: t.organization_id,
+        name: t.name,
+        slug: t.slug,
+        description: t.description,
+        member_count,
+        created_at: t.created_at,
+    })
+}
+
 /// List teams for the current user
 pub async fn list_teams(
     State(state): State<AppState>,
@@ -60,31 +80,38 @@ pub async fn list_teams(
 
     let mut responses = Vec::new();
     for t in teams {
-        let member_count = team_member::Entity::find()
-            .filter(team_member::Column::TeamId.eq(t.id))
-            .count(&state.db)
-            .await? as i64;
-
-        responses.push(TeamResponse {
-            id: t.id,
-            organization_id: t.organization_id,
-            name: t.name,
-            slug: t.slug,
-            description: t.description,
-            member_count,
-            created_at: t.created_at,
-        });
+        responses.push(team_response(&state, t).await?);
+    }
+
+    Ok(Json(ApiResponse::success(responses)))
+}
+
+/// List the teams of an organization
+pub async fn list_organization_teams(
+    State(state): State<AppState>,
+    access: OrgMember,
+) -> Result<Json<ApiResponse<Vec<TeamResponse>>>, ApiError> {
+    let teams = team::Entity::find()
+        .filter(team::Column::OrganizationId.eq(access.org_id))
+        .all(&state.db)
+        .await?;
+
+    let mut responses = Vec::new();
+    for t in teams {
+        responses.push(team_response(&state, t).await?);
     }
 
     Ok(Json(ApiResponse::success(responses)))
 }
 
-/// Create a new team
+/// Create a new team; requires the admin role in the organization
 pub async fn create_team(
     State(state): State<AppState>,
     auth: AuthUser,
     Json(req): Json<CreateTeamRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<TeamResponse>>), ApiError> {
+    authorize_org(&state, auth.user_id, req.organization_id, OrgRole::Admin).await?;
+
     let slug = req.name.to_lowercase().replace(' ', "-");
     let now = Utc::now();
 
@@ -105,7 +132,7 @@ pub async fn create_team(
         id: Set(Uuid::new_v4()),
         team_id: Set(team.id),
         user_id: Set(auth.user_id),
-        role: Set("admin".to_string()),
+        role: Set(TeamRole::Admin.to_string()),
         joined_at: Set(now),
     };
     member.insert(&state.db).await?;
@@ -127,89 +154,74 @@ pub async fn create_team(
 /// Get team details
 pub async fn get_team(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(team_id): Path<Uuid>,
+    access: TeamViewer,
 ) -> Result<Json<ApiResponse<TeamResponse>>, ApiError> {
-    // Verify membership
-    team_member::Entity::find()
-        .filter(team_member::Column::TeamId.eq(team_id))
-        .filter(team_member::Column::UserId.eq(auth.user_id))
-        .one(&state.db)
-        .await?
-        .ok_or_else(|| ApiError::not_found("Team not found"))?;
-
-    let team = team::Entity::find_by_id(team_id)
-        .one(&state.db)
-        .await?
-        .ok_or_else(|| ApiError::not_found("Team not found"))?;
-
-    let member_count = team_member::Entity::find()
-        .filter(team_member::Column::TeamId.eq(team_id))
-        .count(&state.db)
-        .await? as i64;
-
-    Ok(Json(ApiResponse::success(TeamResponse {
-        id: team.id,
-        organization_id: team.organization_id,
-        name: team.name,
-        slug: team.slug,
-        description: team.description,
-        member_count,
-        created_at: team.created_at,
-    })))
+    Ok(Json(ApiResponse::success(
+        team_response(&state, access.team).await?,
+    )))
 }
 
-/// Add member to team
+/// Add a member of the team's organization to the team, or change their role
 pub async fn add_member(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path(team_id): Path<Uuid>,
+    access: TeamAdmin,
     Json(req): Json<AddMemberRequest>,
 ) -> Result<StatusCode, ApiError> {
-    // Verify admin access
-    let membership = team_member::Entity::find()
-        .filter(team_member::Column::TeamId.eq(team_id))
-        .filter(team_member::Column::UserId.eq(auth.user_id))
-        .one(&state.db)
-        .await?
-        .ok_or_else(|| ApiError::not_found("Team not found"))?;
+    let role: TeamRole = req
+        .role
+        .parse()
+        .map_err(|_| ApiError::bad_request(t!("errors.organization.invalid_role")))?;
 
-    if membership.role != "admin" {
-        return Err(ApiError::unauthorized("Admin access required"));
+    if OrganizationQueries::role_of(&state.db, access.team.organization_id, req.user_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::bad_request(t!(
+            "errors.organization.not_a_member"
+        )));
     }
 
-    let member = team_member::ActiveModel {
-        id: Set(Uuid::new_v4()),
-        team_id: Set(team_id),
-        user_id: Set(req.user_id),
-        role: Set(req.role),
-        joined_at: Set(Utc::now()),
-    };
-    member.insert(&state.db).await?;
+    let existing = team_member::Entity::find()
+        .filter(team_member::Column::TeamId.eq(access.team.id))
+        .filter(team_member::Column::UserId.eq(req.user_id))
+        .one(&state.db)
+        .await?;
+
+    match existing {
+        Some(member) => {
+            let mut active: team_member::ActiveModel = member.into();
+            active.role = Set(role.to_string());
+            active.update(&state.db).await?;
+        }
+        None => {
+            let member = team_member::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                team_id: Set(access.team.id),
+                user_id: Set(req.user_id),
+                role: Set(role.to_string()),
+                joined_at: Set(Utc::now()),
+            };
+            member.insert(&state.db).await?;
+        }
+    }
 
     Ok(StatusCode::CREATED)
 }
 
-/// Remove member from team
+/// Remove member from team; members may remove themselves
 pub async fn remove_member(
     State(state): State<AppState>,
-    auth: AuthUser,
-    Path((team_id, user_id)): Path<(Uuid, Uuid)>,
+    access: TeamViewer,
+    Path((_, user_id)): Path<(Uuid, Uuid)>,
 ) -> Result<StatusCode, ApiError> {
-    // Verify admin access
-    let membership = team_member::Entity::find()
-        .filter(team_member::Column::TeamId.eq(team_id))
-        .filter(team_member::Column::UserId.eq(auth.user_id))
-        .one(&state.db)
-        .await?
-        .ok_or_else(|| ApiError::not_found("Team not found"))?;
-
-    if membership.role != "admin" && auth.user_id != user_id {
-        return Err(ApiError::unauthorized("Admin access required"));
+    if access.role < TeamRole::Admin && access.auth.user_id != user_id {
+        return Err(ApiError::forbidden(t!(
+            "errors.organization.insufficient_role"
+        )));
     }
 
     team_member::Entity::delete_many()
-        .filter(team_member::Column::TeamId.eq(team_id))
+        .filter(team_member::Column::TeamId.eq(access.team.id))
         .filter(team_member::Column::UserId.eq(user_id))
         .exec(&state.db)
         .await?;
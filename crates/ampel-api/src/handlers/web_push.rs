@@ -0,0 +1,178 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use ampel_core::services::{push_payload, ChannelMessage, WebPushSender, WebPushSubscription};
+use ampel_db::entities::push_subscription;
+use ampel_db::queries::{NewPushSubscription, PushSubscriptionQueries};
+use ampel_worker::services::web_push::{load_vapid_keys, push_to_user, vapid_subject};
+
+use crate::extractors::AuthUser;
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// Longest `User-Agent` kept with a push subscription
+const MAX_USER_AGENT_LEN: usize = 255;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VapidPublicKeyResponse {
+    /// Base64url-encoded key to pass to `pushManager.subscribe` as the
+    /// `applicationServerKey`
+    pub public_key: String,
+}
+
+/// A browser subscription, as returned by `PushSubscription.toJSON()`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribePushRequest {
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushSubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushSubscriptionResponse {
+    pub id: Uuid,
+    pub endpoint: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<push_subscription::Model> for PushSubscriptionResponse {
+    fn from(subscription: push_subscription::Model) -> Self {
+        Self {
+            id: subscription.id,
+            endpoint: subscription.endpoint,
+            user_agent: subscription.user_agent,
+            created_at: subscription.created_at,
+            last_used_at: subscription.last_used_at,
+        }
+    }
+}
+
+/// Get the public key browsers subscribe with
+pub async fn get_vapid_public_key(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+) -> Result<Json<ApiResponse<VapidPublicKeyResponse>>, ApiError> {
+    let keys = load_vapid_keys(&state.db, &state.encryption_service)
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to load VAPID keys: {}", e)))?;
+
+    Ok(Json(ApiResponse::success(VapidPublicKeyResponse {
+        public_key: keys.public_key(),
+    })))
+}
+
+/// List the browsers receiving the current user's push notifications
+pub async fn list_subscriptions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<PushSubscriptionResponse>>>, ApiError> {
+    let subscriptions = PushSubscriptionQueries::list_for_user(&state.db, auth.user_id)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Json(ApiResponse::success(subscriptions)))
+}
+
+/// Register this browser for push notifications
+pub async fn subscribe(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(req): Json<SubscribePushRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<PushSubscriptionResponse>>), ApiError> {
+    let subscription = WebPushSubscription {
+        endpoint: req.endpoint.trim().to_string(),
+        p256dh: req.keys.p256dh.trim().to_string(),
+        auth: req.keys.auth.trim().to_string(),
+    };
+    subscription
+        .validate()
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect());
+
+    let stored = PushSubscriptionQueries::upsert(
+        &state.db,
+        NewPushSubscription {
+            user_id: auth.user_id,
+            endpoint: subscription.endpoint,
+            p256dh: subscription.p256dh,
+            auth: subscription.auth,
+            user_agent,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(stored.into())),
+    ))
+}
+
+/// Stop sending push notifications to a browser
+pub async fn unsubscribe(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(subscription_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if !PushSubscriptionQueries::delete_for_user(&state.db, auth.user_id, subscription_id).await? {
+        return Err(ApiError::not_found("Push subscription not found"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a test push notification to every browser the user subscribed.
+/// Returns how many accepted it.
+pub async fn test_push(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<usize>>, ApiError> {
+    if PushSubscriptionQueries::count_for_user(&state.db, auth.user_id).await? == 0 {
+        return Err(ApiError::bad_request("No push subscriptions registered"));
+    }
+
+    let keys = load_vapid_keys(&state.db, &state.encryption_service)
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to load VAPID keys: {}", e)))?;
+    let sender = WebPushSender::new(keys, vapid_subject());
+    let outcome = push_to_user(
+        &state.db,
+        &sender,
+        auth.user_id,
+        &push_payload(&ChannelMessage::test()),
+    )
+    .await?;
+
+    if outcome.delivered > 0 {
+        Ok(Json(ApiResponse::success(outcome.delivered)))
+    } else if outcome.errors.is_empty() {
+        Err(ApiError::bad_request("All push subscriptions have expired"))
+    } else {
+        Err(ApiError::bad_request(format!(
+            "Failed to send test message: {}",
+            outcome.errors.join("; ")
+        )))
+    }
+}
@@ -0,0 +1,446 @@
+//! Inbound provider webhooks.
+//!
+//! Deliveries arrive at `POST /api/webhooks/{provider}/{repo_id}` without a
+//! user session; they are authenticated by the per-repository shared secret
+//! instead (HMAC-SHA256 for GitHub and Bitbucket, the `X-Gitlab-Token` header
+//! for GitLab). A verified delivery is normalised by `ampel-providers` and
+//! written through the same `PrQueries` / `CICheckQueries` / `ReviewQueries`
+//! calls the poller uses, so both paths produce identical rows. Both also
+//! record PR status changes and review requests for notification, and publish
+//! the changes to the owner's open dashboards.
+//!
+//! Repository owners manage the secret via `/api/repositories/{repo_id}/webhook`.
+//! The plaintext secret is returned exactly once, on creation or rotation; it is
+//! stored encrypted and never logged.
+//!
+//! Any repository with an active webhook that delivered recently is treated as
+//! "live" by `PollRepositoryJob`, which then only reconciles it occasionally.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use metrics::counter;
+use rand::RngCore;
+use rust_i18n::t;
+use serde::Serialize;
+use uuid::Uuid;
+
+use ampel_core::models::{GitProvider, PullRequestMetadata};
+use ampel_db::entities::{pull_request, repository};
+use ampel_db::queries::{
+    CICheckQueries, PrMetadataQueries, PrQueries, RepoQueries, RepositoryWebhookQueries,
+    ReviewQueries,
+};
+use ampel_providers::traits::{ProviderCICheck, ProviderPullRequest, ProviderReview};
+use ampel_providers::webhook::{verify_hmac_sha256, verify_token};
+use ampel_providers::{bitbucket, github, gitlab, WebhookEvent};
+use ampel_worker::services::pr_events::{record_check_changes, record_pr_closed, record_pr_events};
+
+use crate::extractors::AuthUser;
+use crate::handlers::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// Bytes of entropy in a generated webhook secret (hex-encoded on the wire).
+const WEBHOOK_SECRET_BYTES: usize = 32;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookStatusResponse {
+    pub configured: bool,
+    pub provider: String,
+    pub delivery_path: String,
+    pub is_active: bool,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub last_event: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSecretResponse {
+    pub provider: String,
+    pub delivery_path: String,
+    /// Shared secret to paste into the provider's webhook settings. Only ever
+    /// returned by the create/rotate call.
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryResponse {
+    pub event: String,
+    pub applied: bool,
+}
+
+fn delivery_path(provider: &str, repo_id: Uuid) -> String {
+    format!("/api/webhooks/{}/{}", provider, repo_id)
+}
+
+async fn find_owned_repository(
+    state: &AppState,
+    auth: &AuthUser,
+    repo_id: Uuid,
+) -> Result<repository::Model, ApiError> {
+    let repo = RepoQueries::find_by_id(&state.db, repo_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(t!("errors.repository.not_found")))?;
+
+    if repo.user_id != auth.user_id {
+        return Err(ApiError::not_found(t!("errors.repository.not_found")));
+    }
+
+    Ok(repo)
+}
+
+/// Get webhook configuration for a repository
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(repo_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WebhookStatusResponse>>, ApiError> {
+    let repo = find_owned_repository(&state, &auth, repo_id).await?;
+    let webhook = RepositoryWebhookQueries::find_by_repository(&state.db, repo.id).await?;
+
+    let response = match webhook {
+        Some(w) => WebhookStatusResponse {
+            configured: true,
+            delivery_path: delivery_path(&w.provider, repo.id),
+            provider: w.provider,
+            is_active: w.is_active,
+            last_delivery_at: w.last_delivery_at,
+            last_event: w.last_event,
+        },
+        None => WebhookStatusResponse {
+            configured: false,
+            delivery_path: delivery_path(&repo.provider, repo.id),
+            provider: repo.provider,
+            is_active: false,
+            last_delivery_at: None,
+            last_event: None,
+        },
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// Create (or rotate) the webhook secret for a repository
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(repo_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WebhookSecretResponse>>, ApiError> {
+    let repo = find_owned_repository(&state, &auth, repo_id).await?;
+
+    let mut bytes = [0u8; WEBHOOK_SECRET_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    let secret = hex::encode(bytes);
+
+    let secret_encrypted = state
+        .encryption_service
+        .encrypt(&secret)
+        .map_err(|e| ApiError::internal(format!("Encryption error: {}", e)))?;
+
+    let webhook = RepositoryWebhookQueries::upsert_secret(
+        &state.db,
+        repo.id,
+        repo.provider.clone(),
+        secret_encrypted,
+    )
+    .await?;
+
+    tracing::info!(repository_id = %repo.id, "Webhook secret created");
+
+    Ok(Json(ApiResponse::success(WebhookSecretResponse {
+        delivery_path: delivery_path(&webhook.provider, repo.id),
+        provider: webhook.provider,
+        secret,
+    })))
+}
+
+/// Delete the webhook for a repository (the poller takes over again)
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(repo_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let repo = find_owned_repository(&state, &auth, repo_id).await?;
+    RepositoryWebhookQueries::delete_by_repository(&state.db, repo.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Receive a provider webhook delivery
+pub async fn receive_webhook(
+    State(state): State<AppState>,
+    Path((provider, repo_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApiResponse<WebhookDeliveryResponse>>, ApiError> {
+    let provider_type: GitProvider = provider
+        .parse()
+        .map_err(|_| ApiError::not_found("Webhook not found"))?;
+
+    // Unknown repo, no webhook, a disabled webhook and a provider mismatch all
+    // look the same to the caller so the endpoint can't be used to probe.
+    let webhook = RepositoryWebhookQueries::find_by_repository(&state.db, repo_id)
+        .await?
+        .filter(|w| w.is_active && w.provider == provider_type.to_string())
+        .ok_or_else(|| ApiError::not_found("Webhook not found"))?;
+    let repo = RepoQueries::find_by_id(&state.db, repo_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Webhook not found"))?;
+
+    let secret = state
+        .encryption_service
+        .decrypt(&webhook.secret_encrypted)
+        .map_err(|e| {
+            tracing::error!(repository_id = %repo_id, "Failed to decrypt webhook secret: {}", e);
+            ApiError::internal("Webhook misconfigured")
+        })?;
+
+    let (verified, event) = match provider_type {
+        GitProvider::GitHub => (
+            header(&headers, "x-hub-signature-256")
+                .is_some_and(|sig| verify_hmac_sha256(&secret, &body, sig)),
+            header(&headers, "x-github-event"),
+        ),
+        GitProvider::GitLab => (
+            header(&headers, "x-gitlab-token").is_some_and(|tok| verify_token(&secret, tok)),
+            header(&headers, "x-gitlab-event"),
+        ),
+        GitProvider::Bitbucket => (
+            header(&headers, "x-hub-signature")
+                .is_some_and(|sig| verify_hmac_sha256(&secret, &body, sig)),
+            header(&headers, "x-event-key"),
+        ),
+    };
+
+    if !verified {
+        counter!("ampel_webhook_deliveries_total", "provider" => provider.clone(), "outcome" => "rejected")
+            .increment(1);
+        tracing::warn!(repository_id = %repo_id, provider = %provider, "Webhook signature verification failed");
+        return Err(ApiError::unauthorized("Invalid webhook signature"));
+    }
+
+    let event = event
+        .ok_or_else(|| ApiError::bad_request("Missing webhook event header"))?
+        .to_string();
+
+    let parsed = match provider_type {
+        GitProvider::GitHub => github::parse_webhook(&event, &body),
+        GitProvider::GitLab => gitlab::parse_webhook(&event, &body),
+        GitProvider::Bitbucket => bitbucket::parse_webhook(&event, &body),
+    }
+    .map_err(|e| ApiError::bad_request(format!("Invalid webhook payload: {}", e)))?;
+
+    let applied = match parsed {
+        WebhookEvent::PullRequest(pr) => {
+            apply_pull_request(&state, &repo, *pr).await?;
+            true
+        }
+        WebhookEvent::Checks {
+            pr_numbers,
+            source_branch,
+            checks,
+        } => apply_checks(&state, &repo, &pr_numbers, source_branch.as_deref(), checks).await?,
+        WebhookEvent::Review { pr_number, review } => {
+            apply_review(&state, &repo, pr_number, review).await?
+        }
+        WebhookEvent::Ignored(reason) => {
+            tracing::debug!(repository_id = %repo_id, "Ignoring webhook delivery: {}", reason);
+            false
+        }
+    };
+
+    RepositoryWebhookQueries::record_delivery(&state.db, webhook.id, event.clone()).await?;
+
+    if applied {
+        if let Some(redis) = &state.redis {
+            crate::cache::invalidate_dashboard_cache(&mut redis.clone(), repo.user_id).await;
+        }
+    }
+
+    counter!(
+        "ampel_webhook_deliveries_total",
+        "provider" => provider,
+        "outcome" => if applied { "applied" } else { "ignored" }
+    )
+    .increment(1);
+
+    Ok(Json(ApiResponse::success(WebhookDeliveryResponse {
+        event,
+        applied,
+    })))
+}
+
+async fn apply_pull_request(
+    state: &AppState,
+    repo: &repository::Model,
+    mut pr: ProviderPullRequest,
+) -> Result<(), ApiError> {
+    let existing = PrQueries::find_by_number(&state.db, repo.id, pr.number).await?;
+    let was_open = existing.as_ref().is_some_and(|e| e.state == "open");
+
+    if let Some(existing) = existing {
+        // Payloads that only name the actor leave the author blank; keep ours.
+        if pr.author.is_empty() {
+            pr.author = existing.author;
+            pr.author_avatar_url = existing.author_avatar_url;
+        }
+        // GitLab hooks carry the milestone's id but not its title; keep ours.
+        if repo.provider == "gitlab" {
+            pr.milestone = existing.milestone;
+        }
+    }
+
+    let metadata = PullRequestMetadata {
+        labels: pr.labels,
+        assignees: pr.assignees,
+        requested_reviewers: pr.requested_reviewers.clone(),
+    };
+
+    let model = PrQueries::upsert(
+        &state.db,
+        repo.id,
+        repo.provider.clone(),
+        pr.provider_id,
+        pr.number,
+        pr.title,
+        pr.description,
+        pr.url,
+        pr.state,
+        pr.source_branch,
+        pr.target_branch,
+        pr.author,
+        pr.author_avatar_url,
+        pr.is_draft,
+        pr.is_mergeable,
+        pr.has_conflicts,
+        pr.additions,
+        pr.deletions,
+        pr.changed_files,
+        pr.commits_count,
+        pr.comments_count,
+        pr.created_at,
+        pr.updated_at,
+        pr.merged_at,
+        pr.closed_at,
+        pr.head_sha,
+        pr.milestone,
+    )
+    .await?;
+    PrMetadataQueries::replace(&state.db, model.id, &metadata).await?;
+
+    record_events(state, repo, &model, &pr.requested_reviewers).await;
+    if was_open && model.state != "open" {
+        record_pr_closed(repo, &model).await;
+    }
+
+    Ok(())
+}
+
+async fn apply_checks(
+    state: &AppState,
+    repo: &repository::Model,
+    pr_numbers: &[i32],
+    source_branch: Option<&str>,
+    checks: Vec<ProviderCICheck>,
+) -> Result<bool, ApiError> {
+    let mut prs = Vec::new();
+    for number in pr_numbers {
+        if let Some(pr) = PrQueries::find_by_number(&state.db, repo.id, *number).await? {
+            prs.push(pr);
+        }
+    }
+    if prs.is_empty() {
+        if let Some(branch) = source_branch {
+            prs = PrQueries::find_open_by_repository(&state.db, repo.id)
+                .await?
+                .into_iter()
+                .filter(|pr| pr.source_branch == branch)
+                .collect();
+        }
+    }
+
+    for pr in &prs {
+        let previous = CICheckQueries::find_by_pull_request(&state.db, pr.id).await?;
+        for check in &checks {
+            CICheckQueries::upsert(
+                &state.db,
+                pr.id,
+                check.name.clone(),
+                check.status.clone(),
+                check.conclusion.clone(),
+                check.url.clone(),
+                check.started_at,
+                check.completed_at,
+                check
+                    .completed_at
+                    .and_then(|c| check.started_at.map(|s| (c - s).num_seconds() as i32)),
+            )
+            .await?;
+        }
+        if let Err(e) = record_check_changes(&state.db, repo, pr, &previous).await {
+            tracing::warn!(pr_id = %pr.id, "Failed to publish CI changes: {}", e);
+        }
+        record_events(state, repo, pr, &[]).await;
+    }
+
+    Ok(!prs.is_empty())
+}
+
+async fn apply_review(
+    state: &AppState,
+    repo: &repository::Model,
+    pr_number: i32,
+    review: ProviderReview,
+) -> Result<bool, ApiError> {
+    let Some(pr) = PrQueries::find_by_number(&state.db, repo.id, pr_number).await? else {
+        // The PR row will arrive with its own event or the next reconciliation.
+        return Ok(false);
+    };
+
+    // One row per reviewer, matching what `find_latest_by_pull_request` reports.
+    let id = ReviewQueries::find_by_pull_request(&state.db, pr.id)
+        .await?
+        .into_iter()
+        .find(|r| r.reviewer == review.reviewer)
+        .map(|r| r.id)
+        .unwrap_or_else(Uuid::new_v4);
+
+    ReviewQueries::upsert(
+        &state.db,
+        id,
+        pr.id,
+        review.reviewer,
+        review.reviewer_avatar_url,
+        review.state,
+        review.commit_sha,
+        review.body,
+        review.submitted_at,
+    )
+    .await?;
+
+    record_events(state, repo, &pr, &[]).await;
+
+    Ok(true)
+}
+
+/// Record notification events for a PR a delivery changed. A notification
+/// problem is logged and must not fail the delivery.
+async fn record_events(
+    state: &AppState,
+    repo: &repository::Model,
+    pr: &pull_request::Model,
+    requested_reviewers: &[String],
+) {
+    if let Err(e) = record_pr_events(&state.db, repo, pr, requested_reviewers).await {
+        tracing::warn!(pr_id = %pr.id, "Failed to record PR events: {}", e);
+    }
+}
@@ -14,8 +14,10 @@ pub mod cache;
 pub mod config;
 pub mod extractors;
 pub mod handlers;
+pub mod live;
 pub mod middleware;
 pub mod observability;
+pub mod oidc;
 pub mod routes;
 pub mod state;
 
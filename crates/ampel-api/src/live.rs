@@ -0,0 +1,58 @@
+//! Relays live dashboard events from Redis to this replica's dashboards.
+//!
+//! The worker, and every API replica, publish to one Redis pub/sub channel
+//! (see `ampel_worker::services::live_events`). Each replica runs one
+//! subscription that hands the events to its local bus, where the dashboard
+//! streams pick out their user's events, and drops the user's cached dashboard
+//! summary so the next read reflects the change.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use ampel_core::models::LiveEvent;
+use ampel_worker::services::live_events::{deliver_local, LIVE_EVENTS_CHANNEL};
+
+use crate::cache::{invalidate_dashboard_cache, RedisConnectionManager};
+
+/// Wait before resubscribing after the subscription drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Relay events until the process exits, resubscribing whenever the
+/// connection drops
+pub fn spawn_relay(client: redis::Client, cache: RedisConnectionManager) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = relay(&client, &cache).await {
+                tracing::warn!(error = %e, "Live event subscription failed");
+            }
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    });
+}
+
+async fn relay(client: &redis::Client, cache: &RedisConnectionManager) -> redis::RedisResult<()> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(LIVE_EVENTS_CHANNEL).await?;
+    tracing::info!("Subscribed to live dashboard events");
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: String = match message.get_payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!(error = %e, "Ignoring unreadable live event");
+                continue;
+            }
+        };
+        match serde_json::from_str::<LiveEvent>(&payload) {
+            Ok(event) => {
+                invalidate_dashboard_cache(&mut cache.clone(), event.user_id).await;
+                deliver_local(event);
+            }
+            Err(e) => tracing::warn!(error = %e, "Ignoring malformed live event"),
+        }
+    }
+
+    Ok(())
+}
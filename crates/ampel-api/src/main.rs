@@ -4,10 +4,12 @@ use axum::http::{header, Method};
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use ampel_api::{observability, routes, AppState, Config};
+use ampel_api::oidc::{OidcClient, OidcConfig};
+use ampel_api::{live, observability, routes, AppState, Config};
 use ampel_core::services::AuthService;
 use ampel_db::{encryption::EncryptionService, init_database, run_migrations};
 use ampel_providers::ProviderFactory;
+use ampel_worker::services::live_events;
 
 // Initialize rust-i18n with all supported locales
 rust_i18n::i18n!("locales", fallback = "en");
@@ -50,6 +52,10 @@ async fn main() -> anyhow::Result<()> {
             Ok(client) => match client.get_connection_manager().await {
                 Ok(conn_mgr) => {
                     tracing::info!("Redis connection established");
+                    // Live dashboard events go through Redis so every replica
+                    // and the worker share them
+                    live_events::use_redis(conn_mgr.clone());
+                    live::spawn_relay(client, conn_mgr.clone());
                     Some(conn_mgr)
                 }
                 Err(e) => {
@@ -77,10 +83,10 @@ async fn main() -> anyhow::Result<()> {
     let encryption_service =
         EncryptionService::from_base64_key(&config.encryption_key).expect("Invalid encryption key");
 
-    let provider_factory = ProviderFactory::new();
+    let provider_factory = ProviderFactory::new().with_oauth_clients_from_env();
 
     // Create app state
-    let state = AppState::new(
+    let mut state = AppState::new(
         db,
         redis,
         auth_service,
@@ -90,6 +96,14 @@ async fn main() -> anyhow::Result<()> {
         metrics_handle,
     );
 
+    if let Some(oidc_config) = OidcConfig::from_env()? {
+        tracing::info!(issuer = %oidc_config.issuer_url, "OIDC single sign-on enabled");
+        state = state.with_oidc(OidcClient::new(oidc_config));
+    }
+    if !config.local_login_enabled {
+        tracing::info!("Local email/password sign-in is disabled");
+    }
+
     // Configure CORS
     let cors = CorsLayer::new()
         .allow_origin(
@@ -0,0 +1,482 @@
+//! OpenID Connect single sign-on.
+//!
+//! Ampel is a confidential (or public) OIDC client using the authorization code
+//! flow with PKCE. The IdP is configured by its issuer URL; endpoints and
+//! signing keys come from its discovery document and JWKS, which are cached and
+//! refetched when the IdP rotates keys. ID tokens are verified against the
+//! JWKS, the issuer, the client id and the nonce sent with the authorization
+//! request.
+//!
+//! Configuration (all optional; SSO is off without `OIDC_ISSUER_URL`):
+//!
+//! - `OIDC_ISSUER_URL`, `OIDC_CLIENT_ID`, `OIDC_CLIENT_SECRET`
+//! - `OIDC_REDIRECT_URL`: this API's `/api/auth/oidc/callback` as the IdP sees it
+//! - `OIDC_POST_LOGIN_REDIRECT`: frontend page that receives the Ampel tokens
+//! - `OIDC_SCOPES` (default `openid email profile`)
+//! - `OIDC_GROUPS_CLAIM` (default `groups`)
+//! - `OIDC_GROUP_TEAM_MAP`: `group=<team id>:<role>` entries separated by commas
+//! - `OIDC_PROVIDER_NAME`: label for the login button (default `SSO`)
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use url::Url;
+use uuid::Uuid;
+
+/// Discovery documents are refetched after this long.
+const DISCOVERY_TTL: Duration = Duration::from_secs(3600);
+
+/// An unknown key id triggers a JWKS refetch at most this often.
+const JWKS_MIN_REFRESH: Duration = Duration::from_secs(60);
+
+/// Clock skew tolerated when checking ID token lifetimes.
+const ID_TOKEN_LEEWAY_SECS: u64 = 60;
+
+/// Signature algorithms accepted for ID tokens. Symmetric algorithms are
+/// excluded: they would let anyone holding the client secret mint tokens.
+const ALLOWED_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Team role granted to members of an IdP group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupMapping {
+    pub group: String,
+    pub team_id: Uuid,
+    /// `admin`, `member` or `viewer`
+    pub role: String,
+}
+
+impl FromStr for GroupMapping {
+    type Err = anyhow::Error;
+
+    fn from_str(entry: &str) -> Result<Self, Self::Err> {
+        let (group, target) = entry
+            .rsplit_once('=')
+            .ok_or_else(|| anyhow!("expected group=<team id>:<role>, got '{entry}'"))?;
+        let (team_id, role) = target
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected group=<team id>:<role>, got '{entry}'"))?;
+        let role = role.trim().to_lowercase();
+        if !matches!(role.as_str(), "admin" | "member" | "viewer") {
+            bail!("unknown team role '{role}' in '{entry}'");
+        }
+
+        Ok(Self {
+            group: group.trim().to_string(),
+            team_id: team_id
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid team id in '{entry}'"))?,
+            role,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub post_login_redirect: String,
+    pub scopes: Vec<String>,
+    pub groups_claim: String,
+    pub group_mappings: Vec<GroupMapping>,
+    pub provider_name: String,
+}
+
+impl OidcConfig {
+    /// Read the configuration from the environment; `None` when SSO is not
+    /// configured.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(issuer_url) = std::env::var("OIDC_ISSUER_URL") else {
+            return Ok(None);
+        };
+        let required = |name: &str| {
+            std::env::var(name).with_context(|| format!("{name} must be set when OIDC is enabled"))
+        };
+
+        let group_mappings = match std::env::var("OIDC_GROUP_TEAM_MAP") {
+            Ok(map) => map
+                .split(',')
+                .filter(|entry| !entry.trim().is_empty())
+                .map(str::parse)
+                .collect::<anyhow::Result<_>>()
+                .context("invalid OIDC_GROUP_TEAM_MAP")?,
+            Err(_) => Vec::new(),
+        };
+
+        Ok(Some(Self {
+            issuer_url,
+            client_id: required("OIDC_CLIENT_ID")?,
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_url: required("OIDC_REDIRECT_URL")?,
+            post_login_redirect: required("OIDC_POST_LOGIN_REDIRECT")?,
+            scopes: std::env::var("OIDC_SCOPES")
+                .unwrap_or_else(|_| "openid email profile".to_string())
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            groups_claim: std::env::var("OIDC_GROUPS_CLAIM")
+                .unwrap_or_else(|_| "groups".to_string()),
+            group_mappings,
+            provider_name: std::env::var("OIDC_PROVIDER_NAME")
+                .unwrap_or_else(|_| "SSO".to_string()),
+        }))
+    }
+}
+
+/// The parts of the IdP's discovery document Ampel uses
+#[derive(Debug, Clone, Deserialize)]
+pub struct Discovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// Verified claims of an ID token
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub email: Option<String>,
+    /// Some IdPs send this as a string
+    #[serde(default)]
+    email_verified: Option<Value>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    nonce: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl IdTokenClaims {
+    pub fn email_verified(&self) -> bool {
+        match &self.email_verified {
+            Some(Value::Bool(verified)) => *verified,
+            Some(Value::String(verified)) => verified.eq_ignore_ascii_case("true"),
+            _ => false,
+        }
+    }
+
+    /// Group names in `claim`, which may be a list or a single string
+    pub fn groups(&self, claim: &str) -> Vec<String> {
+        match self.extra.get(claim) {
+            Some(Value::Array(groups)) => groups
+                .iter()
+                .filter_map(|g| g.as_str().map(str::to_string))
+                .collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A PKCE verifier and its S256 challenge
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Self {
+        Self::from_verifier(random_token())
+    }
+
+    fn from_verifier(verifier: String) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+/// 256 random bits, URL-safe; used for `state`, `nonce` and PKCE verifiers
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// OIDC client for one IdP, with cached discovery document and keys
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    discovery: RwLock<Option<(Discovery, Instant)>>,
+    jwks: RwLock<Option<(JwkSet, Instant)>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            discovery: RwLock::new(None),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    /// The IdP's discovery document, fetched from
+    /// `{issuer}/.well-known/openid-configuration`
+    pub async fn discovery(&self) -> anyhow::Result<Discovery> {
+        if let Some((discovery, fetched_at)) = self.discovery.read().await.as_ref() {
+            if fetched_at.elapsed() < DISCOVERY_TTL {
+                return Ok(discovery.clone());
+            }
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer_url.trim_end_matches('/')
+        );
+        let discovery: Discovery = self
+            .http
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()
+            .context("failed to fetch OIDC discovery document")?
+            .json()
+            .await
+            .context("invalid OIDC discovery document")?;
+
+        if discovery.issuer.trim_end_matches('/') != self.config.issuer_url.trim_end_matches('/') {
+            bail!(
+                "discovery document issuer {} does not match {}",
+                discovery.issuer,
+                self.config.issuer_url
+            );
+        }
+
+        *self.discovery.write().await = Some((discovery.clone(), Instant::now()));
+        Ok(discovery)
+    }
+
+    /// Where to send the browser to sign in
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        pkce_challenge: &str,
+    ) -> anyhow::Result<Url> {
+        let discovery = self.discovery().await?;
+        let mut url = Url::parse(&discovery.authorization_endpoint)
+            .context("invalid authorization endpoint")?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", pkce_challenge)
+            .append_pair("code_challenge_method", "S256");
+        Ok(url)
+    }
+
+    /// Redeem an authorization code and verify the ID token it returns
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        nonce: &str,
+    ) -> anyhow::Result<IdTokenClaims> {
+        let discovery = self.discovery().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", pkce_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http
+            .post(&discovery.token_endpoint)
+            .form(&form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("token endpoint returned {}", response.status());
+        }
+        let tokens: TokenResponse = response.json().await.context("invalid token response")?;
+        let id_token = tokens
+            .id_token
+            .ok_or_else(|| anyhow!("token response has no id_token"))?;
+
+        self.verify_id_token(&discovery, &id_token, nonce).await
+    }
+
+    async fn verify_id_token(
+        &self,
+        discovery: &Discovery,
+        id_token: &str,
+        nonce: &str,
+    ) -> anyhow::Result<IdTokenClaims> {
+        let header = decode_header(id_token).context("malformed ID token")?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            bail!(
+                "ID token signed with unsupported algorithm {:?}",
+                header.alg
+            );
+        }
+
+        let key = self.signing_key(discovery, header.kid.as_deref()).await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&discovery.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = ID_TOKEN_LEEWAY_SECS;
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .context("ID token failed verification")?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            bail!("ID token nonce does not match the login request");
+        }
+        Ok(claims)
+    }
+
+    /// The JWKS key for `kid`, refetching the key set once if the IdP has
+    /// rotated to a key we have not seen yet
+    async fn signing_key(
+        &self,
+        discovery: &Discovery,
+        kid: Option<&str>,
+    ) -> anyhow::Result<DecodingKey> {
+        let cached = self.jwks.read().await.clone();
+        let stale = cached
+            .as_ref()
+            .is_none_or(|(_, fetched_at)| fetched_at.elapsed() >= JWKS_MIN_REFRESH);
+
+        if let Some((jwks, _)) = &cached {
+            if let Some(key) = find_key(jwks, kid) {
+                return key;
+            }
+        }
+        if !stale {
+            bail!("no JWKS key matches ID token key id {:?}", kid);
+        }
+
+        let jwks: JwkSet = self
+            .http
+            .get(&discovery.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()
+            .context("failed to fetch JWKS")?
+            .json()
+            .await
+            .context("invalid JWKS")?;
+        let key = find_key(&jwks, kid);
+        *self.jwks.write().await = Some((jwks, Instant::now()));
+
+        key.unwrap_or_else(|| bail!("no JWKS key matches ID token key id {:?}", kid))
+    }
+}
+
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Option<anyhow::Result<DecodingKey>> {
+    let jwk = match kid {
+        Some(kid) => jwks.find(kid)?,
+        // Without a key id the IdP must publish exactly one key
+        None if jwks.keys.len() == 1 => &jwks.keys[0],
+        None => return None,
+    };
+    Some(DecodingKey::from_jwk(jwk).context("unusable JWKS key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_group_mapping() {
+        let team_id = Uuid::new_v4();
+
+        let mapping: GroupMapping = format!("platform-admins={}:Admin", team_id)
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            mapping,
+            GroupMapping {
+                group: "platform-admins".to_string(),
+                team_id,
+                role: "admin".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_team_role() {
+        let result = format!("devs={}:owner", Uuid::new_v4()).parse::<GroupMapping>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_derive_s256_pkce_challenge() {
+        // RFC 7636, appendix B
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string();
+
+        let pkce = Pkce::from_verifier(verifier);
+
+        assert_eq!(
+            pkce.challenge,
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn should_read_groups_from_list_or_string() {
+        let claims: IdTokenClaims = serde_json::from_value(serde_json::json!({
+            "iss": "https://idp.example.com",
+            "sub": "u1",
+            "groups": ["a", "b"],
+            "role": "c",
+            "email_verified": "true"
+        }))
+        .unwrap();
+
+        assert_eq!(claims.groups("groups"), vec!["a", "b"]);
+        assert_eq!(claims.groups("role"), vec!["c"]);
+        assert!(claims.groups("missing").is_empty());
+        assert!(claims.email_verified());
+    }
+}
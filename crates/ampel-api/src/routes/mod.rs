@@ -1,13 +1,15 @@
 use axum::{
     middleware,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 
 use crate::handlers::{
-    accounts, analytics, auth, bot_rules, bulk_merge, dashboard, model_accounts, model_catalog,
-    notifications, pr_filters, pull_requests, remediation, remediation_playbooks, remediation_runs,
-    repositories, teams, user_preferences, user_settings,
+    accounts, analytics, api_tokens, auth, bot_rules, bulk_merge, dashboard, model_accounts,
+    model_catalog, notification_channels, notifications, oidc, organizations, pr_filters,
+    pull_requests, remediation, remediation_playbooks, remediation_runs, repositories,
+    repository_shares, saved_views, status_policies, teams, user_preferences, user_settings,
+    web_push, webhooks,
 };
 use crate::{
     health_handler, metrics_handler,
@@ -29,6 +31,17 @@ pub fn create_router(state: AppState) -> Router {
         // Auth routes (protected)
         .route("/api/auth/me", get(auth::me).put(auth::update_me))
         .route("/api/auth/logout", post(auth::logout))
+        .route("/api/auth/logout-all", post(auth::logout_all))
+        .route("/api/auth/methods", get(oidc::methods))
+        .route("/api/auth/oidc/login", get(oidc::login))
+        .route("/api/auth/oidc/callback", get(oidc::callback))
+        .route("/api/auth/sessions", get(auth::list_sessions))
+        .route("/api/auth/sessions/{id}", delete(auth::revoke_session))
+        .route(
+            "/api/auth/tokens",
+            get(api_tokens::list_tokens).post(api_tokens::create_token),
+        )
+        .route("/api/auth/tokens/{id}", delete(api_tokens::revoke_token))
         // Account management routes (PAT-based multi-account support)
         .route(
             "/api/accounts",
@@ -69,6 +82,20 @@ pub fn create_router(state: AppState) -> Router {
                 .patch(repositories::update_repository)
                 .delete(repositories::remove_repository),
         )
+        .route(
+            "/api/repositories/{repo_id}/shares",
+            get(repository_shares::list_shares).post(repository_shares::create_share),
+        )
+        .route(
+            "/api/repositories/{repo_id}/shares/{share_id}",
+            delete(repository_shares::delete_share),
+        )
+        .route(
+            "/api/repositories/{repo_id}/status-policy",
+            get(status_policies::get_repository_policy)
+                .put(status_policies::update_repository_policy)
+                .delete(status_policies::delete_repository_policy),
+        )
         // Pull request routes
         .route("/api/pull-requests", get(pull_requests::list_pull_requests))
         .route(
@@ -87,9 +114,56 @@ pub fn create_router(state: AppState) -> Router {
             "/api/repositories/{repo_id}/pull-requests/{pr_id}/refresh",
             post(pull_requests::refresh_pull_request),
         )
+        // Saved view routes
+        .route(
+            "/api/views",
+            get(saved_views::list_views).post(saved_views::create_view),
+        )
+        .route(
+            "/api/views/{view_id}",
+            get(saved_views::get_view)
+                .put(saved_views::update_view)
+                .delete(saved_views::delete_view),
+        )
         // Dashboard routes
         .route("/api/dashboard/summary", get(dashboard::get_summary))
         .route("/api/dashboard/grid", get(dashboard::get_grid))
+        .route("/api/dashboard/events", get(dashboard::events))
+        // Organization routes
+        .route(
+            "/api/organizations",
+            get(organizations::list_organizations).post(organizations::create_organization),
+        )
+        .route(
+            "/api/organizations/{org_id}",
+            get(organizations::get_organization)
+                .patch(organizations::update_organization)
+                .delete(organizations::delete_organization),
+        )
+        .route(
+            "/api/organizations/{org_id}/members",
+            get(organizations::list_members),
+        )
+        .route(
+            "/api/organizations/{org_id}/members/{user_id}",
+            put(organizations::update_member).delete(organizations::remove_member),
+        )
+        .route(
+            "/api/organizations/{org_id}/teams",
+            get(teams::list_organization_teams),
+        )
+        .route(
+            "/api/organizations/{org_id}/invitations",
+            get(organizations::list_invitations).post(organizations::create_invitation),
+        )
+        .route(
+            "/api/organizations/{org_id}/invitations/{invitation_id}",
+            delete(organizations::revoke_invitation),
+        )
+        .route(
+            "/api/invitations/{token}/accept",
+            post(organizations::accept_invitation),
+        )
         // Team routes
         .route(
             "/api/teams",
@@ -101,6 +175,25 @@ pub fn create_router(state: AppState) -> Router {
             "/api/teams/{team_id}/members/{user_id}",
             delete(teams::remove_member),
         )
+        .route(
+            "/api/teams/{team_id}/dashboard/summary",
+            get(dashboard::get_team_summary),
+        )
+        .route(
+            "/api/teams/{team_id}/dashboard/grid",
+            get(dashboard::get_team_grid),
+        )
+        .route(
+            "/api/teams/{team_id}/status-policy",
+            get(status_policies::get_team_policy)
+                .put(status_policies::update_team_policy)
+                .delete(status_policies::delete_team_policy),
+        )
+        .route(
+            "/api/teams/{team_id}/notification-channels",
+            get(notification_channels::list_team_channels)
+                .post(notification_channels::create_team_channel),
+        )
         // Notification routes
         .route(
             "/api/notifications/preferences",
@@ -114,6 +207,34 @@ pub fn create_router(state: AppState) -> Router {
             "/api/notifications/test-email",
             post(notifications::test_email_smtp),
         )
+        .route("/api/notifications/test-push", post(web_push::test_push))
+        .route(
+            "/api/notifications/push/vapid-public-key",
+            get(web_push::get_vapid_public_key),
+        )
+        .route(
+            "/api/notifications/push/subscriptions",
+            get(web_push::list_subscriptions).post(web_push::subscribe),
+        )
+        .route(
+            "/api/notifications/push/subscriptions/{subscription_id}",
+            delete(web_push::unsubscribe),
+        )
+        .route("/api/notifications/log", get(notifications::list_log))
+        .route(
+            "/api/notifications/channels",
+            get(notification_channels::list_channels)
+                .post(notification_channels::create_user_channel),
+        )
+        .route(
+            "/api/notifications/channels/{channel_id}",
+            put(notification_channels::update_channel)
+                .delete(notification_channels::delete_channel),
+        )
+        .route(
+            "/api/notifications/channels/{channel_id}/test",
+            post(notification_channels::test_channel),
+        )
         // User settings routes (behavior config)
         .route(
             "/api/settings/behavior",
@@ -132,6 +253,18 @@ pub fn create_router(state: AppState) -> Router {
             "/api/merge/operations/{operation_id}",
             get(bulk_merge::get_operation),
         )
+        .route(
+            "/api/merge/operations/{operation_id}/cancel",
+            post(bulk_merge::cancel_operation),
+        )
+        .route(
+            "/api/merge/operations/{operation_id}/resume",
+            post(bulk_merge::resume_operation),
+        )
+        .route(
+            "/api/merge/operations/{operation_id}/events",
+            get(bulk_merge::operation_events),
+        )
         // Bot/Auto-merge routes
         .route(
             "/api/repositories/{repo_id}/auto-merge",
@@ -139,6 +272,18 @@ pub fn create_router(state: AppState) -> Router {
                 .put(bot_rules::upsert_auto_merge_rule)
                 .delete(bot_rules::delete_auto_merge_rule),
         )
+        // Inbound webhook management (per repository)
+        .route(
+            "/api/repositories/{repo_id}/webhook",
+            get(webhooks::get_webhook)
+                .post(webhooks::create_webhook)
+                .delete(webhooks::delete_webhook),
+        )
+        // Inbound webhook deliveries (authenticated by the repository secret)
+        .route(
+            "/api/webhooks/{provider}/{repo_id}",
+            post(webhooks::receive_webhook),
+        )
         // PR Filters routes (global user settings)
         .route(
             "/api/pr-filters",
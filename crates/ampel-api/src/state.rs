@@ -7,7 +7,7 @@ use ampel_core::services::AuthService;
 use ampel_db::encryption::EncryptionService;
 use ampel_providers::ProviderFactory;
 
-use crate::{cache::RedisConnectionManager, Config};
+use crate::{cache::RedisConnectionManager, oidc::OidcClient, Config};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -18,6 +18,8 @@ pub struct AppState {
     pub provider_factory: Arc<ProviderFactory>,
     pub config: Arc<Config>,
     pub metrics_handle: PrometheusHandle,
+    /// OpenID Connect client, when SSO is configured
+    pub oidc: Option<Arc<OidcClient>>,
 }
 
 impl AppState {
@@ -38,6 +40,13 @@ impl AppState {
             provider_factory: Arc::new(provider_factory),
             config: Arc::new(config),
             metrics_handle,
+            oidc: None,
         }
     }
+
+    /// Enable SSO sign-in through an OpenID Connect provider
+    pub fn with_oidc(mut self, oidc: OidcClient) -> Self {
+        self.oidc = Some(Arc::new(oidc));
+        self
+    }
 }
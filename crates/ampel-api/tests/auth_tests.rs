@@ -822,3 +822,228 @@ async fn test_logout() {
 
     test_db.cleanup().await;
 }
+
+/// Send a JSON request, optionally with a bearer token, and return status and body
+async fn send_json(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    bearer: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(token) = bearer {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    };
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, json)
+}
+
+/// Register a user and return the (access, refresh) tokens of its first session
+async fn register_session(app: &axum::Router, email: &str) -> (String, String) {
+    let (status, json) = send_json(
+        app,
+        "POST",
+        "/api/auth/register",
+        None,
+        Some(json!({
+            "email": email,
+            "password": "SecurePassword123!",
+            "displayName": "Session Test User"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    (
+        json["data"]["accessToken"].as_str().unwrap().to_string(),
+        json["data"]["refreshToken"].as_str().unwrap().to_string(),
+    )
+}
+
+async fn refresh_with(app: &axum::Router, refresh_token: &str) -> (StatusCode, Value) {
+    send_json(
+        app,
+        "POST",
+        "/api/auth/refresh",
+        None,
+        Some(json!({ "refreshToken": refresh_token })),
+    )
+    .await
+}
+
+/// Test reusing a rotated refresh token revokes the whole session
+#[tokio::test]
+async fn test_refresh_token_reuse_revokes_session() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+    let (_, first_refresh) = register_session(&app, "rotation@example.com").await;
+
+    // Rotating issues a different refresh token
+    let (status, json) = refresh_with(&app, &first_refresh).await;
+    assert_eq!(status, StatusCode::OK);
+    let second_refresh = json["data"]["refreshToken"].as_str().unwrap().to_string();
+    assert_ne!(second_refresh, first_refresh);
+
+    // The rotated-out token is rejected...
+    let (status, _) = refresh_with(&app, &first_refresh).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // ...and its reuse revoked the token that replaced it
+    let (status, _) = refresh_with(&app, &second_refresh).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "Reuse should revoke the whole session"
+    );
+
+    test_db.cleanup().await;
+}
+
+/// Test logout revokes the session of the presented access token
+#[tokio::test]
+async fn test_logout_revokes_session() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+    let (access, refresh) = register_session(&app, "logout@example.com").await;
+
+    let (status, _) = send_json(&app, "POST", "/api/auth/logout", Some(&access), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = refresh_with(&app, &refresh).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    test_db.cleanup().await;
+}
+
+/// Test signing out of all devices revokes every session of the user
+#[tokio::test]
+async fn test_logout_all_revokes_every_session() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+    let (access, first_refresh) = register_session(&app, "logoutall@example.com").await;
+    let (status, json) = send_json(
+        &app,
+        "POST",
+        "/api/auth/login",
+        None,
+        Some(json!({
+            "email": "logoutall@example.com",
+            "password": "SecurePassword123!"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let second_refresh = json["data"]["refreshToken"].as_str().unwrap().to_string();
+
+    let (status, _) = send_json(&app, "POST", "/api/auth/logout-all", Some(&access), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert_eq!(
+        refresh_with(&app, &first_refresh).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        refresh_with(&app, &second_refresh).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+
+    test_db.cleanup().await;
+}
+
+/// Test listing sessions and revoking another device's session
+#[tokio::test]
+async fn test_list_and_revoke_sessions() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+    let (access, _) = register_session(&app, "sessions@example.com").await;
+    let (_, json) = send_json(
+        &app,
+        "POST",
+        "/api/auth/login",
+        None,
+        Some(json!({
+            "email": "sessions@example.com",
+            "password": "SecurePassword123!"
+        })),
+    )
+    .await;
+    let other_refresh = json["data"]["refreshToken"].as_str().unwrap().to_string();
+
+    let (status, json) = send_json(&app, "GET", "/api/auth/sessions", Some(&access), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions = json["data"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(
+        sessions.iter().filter(|s| s["current"] == true).count(),
+        1,
+        "Exactly one session should be the caller's"
+    );
+    let other_id = sessions.iter().find(|s| s["current"] == false).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let uri = format!("/api/auth/sessions/{}", other_id);
+    let (status, _) = send_json(&app, "DELETE", &uri, Some(&access), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        refresh_with(&app, &other_refresh).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Already revoked
+    let (status, _) = send_json(&app, "DELETE", &uri, Some(&access), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
@@ -206,6 +206,12 @@ fn create_test_encryption_key() -> [u8; 32] {
 /// - App state with test configuration
 /// - Full router with all routes
 pub async fn create_test_app(db: DatabaseConnection) -> Router {
+    create_router(create_test_state(db))
+}
+
+/// Create the app state behind [`create_test_app`], for tests that need to
+/// adjust it (e.g. configure SSO) before building the router
+pub fn create_test_state(db: DatabaseConnection) -> AppState {
     let config = create_test_config();
 
     let auth_service = AuthService::new(
@@ -222,7 +228,7 @@ pub async fn create_test_app(db: DatabaseConnection) -> Router {
     // Create metrics handle for tests
     let metrics_handle = ampel_api::init_metrics();
 
-    let state = AppState::new(
+    AppState::new(
         db,
         None, // No Redis for tests
         auth_service,
@@ -230,9 +236,7 @@ pub async fn create_test_app(db: DatabaseConnection) -> Router {
         provider_factory,
         config,
         metrics_handle,
-    );
-
-    create_router(state)
+    )
 }
 
 /// Create test configuration with safe defaults
@@ -247,5 +251,7 @@ fn create_test_config() -> Config {
         jwt_refresh_expiry_days: 7,
         encryption_key: "test-encryption-key-32-bytes!!!!".to_string(),
         cors_origins: vec!["http://localhost:3000".to_string()],
+        local_login_enabled: true,
+        app_url: "http://localhost:3000".to_string(),
     }
 }
@@ -0,0 +1,278 @@
+/// Integration tests for personal API tokens
+///
+/// Covers creating, listing and revoking tokens, and how `AuthUser` accepts
+/// them in place of JWTs subject to their scopes.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use common::{create_test_app, TestDb};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn register_and_login(app: &Router, email: &str) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": "Token User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    json["data"]["accessToken"].as_str().unwrap().to_string()
+}
+
+/// Create a token as `jwt` and return `(id, value)`
+async fn create_token(app: &Router, jwt: &str, scopes: Value) -> (String, String) {
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/auth/tokens",
+        jwt,
+        Some(json!({ "name": "ci", "scopes": scopes, "expiresInDays": 30 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    (
+        json["data"]["id"].as_str().unwrap().to_string(),
+        json["data"]["value"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_token_authenticates_and_records_use() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let jwt = register_and_login(&app, "tokens@example.com").await;
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/auth/tokens",
+        &jwt,
+        Some(json!({ "name": "ci", "scopes": ["read", "merge", "read"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let value = json["data"]["value"].as_str().unwrap().to_string();
+    assert!(value.starts_with("ampel_pat_"));
+    assert!(value.starts_with(json["data"]["tokenPrefix"].as_str().unwrap()));
+    assert_eq!(json["data"]["scopes"], json!(["read", "merge"]));
+    assert!(json["data"]["expiresAt"].is_null());
+    assert!(json["data"]["lastUsedAt"].is_null());
+
+    let (status, json) = send(&app, Method::GET, "/api/auth/me", &value, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["email"], "tokens@example.com");
+
+    let (status, _) = send(&app, Method::GET, "/api/pull-requests", &value, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Listing never discloses the value
+    let (status, json) = send(&app, Method::GET, "/api/auth/tokens", &jwt, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let tokens = json["data"].as_array().unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert!(tokens[0].get("value").is_none());
+    assert!(tokens[0].get("tokenHash").is_none());
+    assert!(tokens[0]["lastUsedAt"].is_string());
+
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        "/api/auth/me",
+        "ampel_pat_not-a-real-token",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_token_scopes_are_enforced() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let jwt = register_and_login(&app, "scopes@example.com").await;
+    let (_, read) = create_token(&app, &jwt, json!(["read"])).await;
+    let (_, merge) = create_token(&app, &jwt, json!(["merge"])).await;
+    let (_, admin) = create_token(&app, &jwt, json!(["admin"])).await;
+
+    let bulk = json!({ "pullRequestIds": [] });
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &read,
+        Some(bulk.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(&app, Method::POST, "/api/merge/bulk", &merge, Some(bulk)).await;
+    assert_ne!(status, StatusCode::FORBIDDEN);
+    assert_ne!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(&app, Method::GET, "/api/pull-requests", &merge, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/remediation/policies",
+        &merge,
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Token management needs admin, and tokens never mint tokens
+    let (status, _) = send(&app, Method::GET, "/api/auth/tokens", &read, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(&app, Method::GET, "/api/auth/tokens", &admin, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/auth/tokens",
+        &admin,
+        Some(json!({ "name": "nested", "scopes": ["read"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_revoked_token_is_rejected() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let jwt = register_and_login(&app, "revoke@example.com").await;
+    let other = register_and_login(&app, "other@example.com").await;
+    let (id, value) = create_token(&app, &jwt, json!(["read"])).await;
+    let uri = format!("/api/auth/tokens/{id}");
+
+    // Only the owner can revoke a token
+    let (status, _) = send(&app, Method::DELETE, &uri, &other, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, Method::DELETE, &uri, &jwt, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, Method::DELETE, &uri, &jwt, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, Method::GET, "/api/auth/me", &value, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (_, json) = send(&app, Method::GET, "/api/auth/tokens", &jwt, None).await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_create_token_validates_request() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let jwt = register_and_login(&app, "validate@example.com").await;
+
+    for body in [
+        json!({ "name": "", "scopes": ["read"] }),
+        json!({ "name": "ci", "scopes": [] }),
+        json!({ "name": "ci", "scopes": ["read"], "expiresInDays": 0 }),
+    ] {
+        let (status, _) = send(&app, Method::POST, "/api/auth/tokens", &jwt, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/auth/tokens",
+        &jwt,
+        Some(json!({ "name": "ci", "scopes": ["superuser"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    test_db.cleanup().await;
+}
@@ -7,9 +7,15 @@ use axum::{
     body::Body,
     http::{header, Request, StatusCode},
 };
+use chrono::Utc;
 use common::{create_test_app, TestDb};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use serde_json::{json, Value};
 use tower::ServiceExt;
+use uuid::Uuid;
+
+use ampel_db::entities::{repository, user};
+use ampel_db::queries::PrQueries;
 
 /// Helper to register a user and return access token
 async fn register_and_login(app: &axum::Router) -> String {
@@ -308,3 +314,160 @@ async fn test_list_operations_with_pagination() {
 
     test_db.cleanup().await;
 }
+
+/// Seed a repository with one open PR for the registered user
+async fn seed_pull_request(conn: &DatabaseConnection) -> Uuid {
+    let user_id = user::Entity::find()
+        .one(conn)
+        .await
+        .unwrap()
+        .expect("a registered user")
+        .id;
+    let repo_id = Uuid::new_v4();
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(repo_id),
+        user_id: Set(user_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("p-{repo_id}")),
+        owner: Set("octocat".to_string()),
+        name: Set("repo".to_string()),
+        full_name: Set("octocat/repo".to_string()),
+        description: Set(None),
+        url: Set("https://example.com/octocat/repo".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        poll_interval_seconds: Set(300),
+        last_polled_at: Set(None),
+        group_id: Set(None),
+        provider_account_id: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+
+    PrQueries::upsert(
+        conn,
+        repo_id,
+        "github".to_string(),
+        "pr-1".to_string(),
+        1,
+        "Bump serde".to_string(),
+        None,
+        "https://example.com/octocat/repo/pull/1".to_string(),
+        "open".to_string(),
+        "deps/serde".to_string(),
+        "main".to_string(),
+        "dependabot[bot]".to_string(),
+        None,
+        false,
+        Some(true),
+        false,
+        1,
+        1,
+        1,
+        1,
+        0,
+        now,
+        now,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+    .unwrap()
+    .id
+}
+
+async fn post_json(app: &axum::Router, token: &str, uri: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_bulk_merge_enqueues_and_supports_cancel_and_resume() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+    let token = register_and_login(&app).await;
+    let pr_id = seed_pull_request(test_db.connection()).await;
+
+    // Enqueue returns immediately without contacting the provider
+    let (status, json) = post_json(
+        &app,
+        &token,
+        "/api/merge/bulk",
+        json!({ "pullRequestIds": [pr_id], "strategy": "rebase" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["status"], "queued");
+    assert_eq!(json["data"]["results"][0]["status"], "pending");
+    let operation_id = json["data"]["operationId"].as_str().unwrap().to_string();
+
+    let (status, _) = post_json(
+        &app,
+        &token,
+        &format!("/api/merge/operations/{}/resume", operation_id),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, json) = post_json(
+        &app,
+        &token,
+        &format!("/api/merge/operations/{}/cancel", operation_id),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["status"], "cancelled");
+    assert_eq!(json["data"]["results"][0]["status"], "cancelled");
+
+    let (status, _) = post_json(
+        &app,
+        &token,
+        &format!("/api/merge/operations/{}/cancel", operation_id),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, json) = post_json(
+        &app,
+        &token,
+        &format!("/api/merge/operations/{}/resume", operation_id),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["status"], "queued");
+    assert_eq!(json["data"]["results"][0]["status"], "pending");
+
+    test_db.cleanup().await;
+}
@@ -1,15 +1,22 @@
 /// Integration tests for dashboard handlers
 ///
-/// Tests dashboard summary statistics and grid/list views.
+/// Tests dashboard summary statistics, grid/list views and the live event
+/// stream.
 mod common;
 
+use std::time::Duration;
+
+use ampel_core::models::{DashboardEvent, LiveEvent};
+use ampel_worker::services::live_events;
 use axum::{
     body::Body,
     http::{header, Request, StatusCode},
 };
 use common::{create_test_app, TestDb};
+use futures::StreamExt;
 use serde_json::{json, Value};
 use tower::ServiceExt;
+use uuid::Uuid;
 
 /// Helper to register a user and return access token
 async fn register_and_login(app: &axum::Router) -> String {
@@ -262,3 +269,79 @@ async fn test_grid_returns_array_of_repositories() {
 
     test_db.cleanup().await;
 }
+
+#[tokio::test]
+async fn test_events_stream_delivers_own_changes() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+    let token = register_and_login(&app).await;
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/api/auth/me")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let me: Value = serde_json::from_slice(&body).unwrap();
+    let user_id: Uuid = me["data"]["id"].as_str().unwrap().parse().unwrap();
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/api/dashboard/events")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/api/dashboard/events")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+
+    let closed = |user_id| LiveEvent {
+        user_id,
+        event: DashboardEvent::PrClosed {
+            pull_request_id: Uuid::new_v4(),
+            repository_id: Uuid::new_v4(),
+            number: 42,
+            state: "merged".to_string(),
+        },
+    };
+    // Another user's change is not streamed
+    live_events::publish(closed(Uuid::new_v4())).await;
+    live_events::publish(closed(user_id)).await;
+
+    let mut stream = response.into_body().into_data_stream();
+    let frame = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("No event streamed")
+        .unwrap()
+        .unwrap();
+    let frame = String::from_utf8(frame.to_vec()).unwrap();
+    assert!(frame.starts_with("event: pr_closed\n"), "{frame}");
+    assert!(frame.contains(r#""number":42"#));
+    assert!(frame.contains(r#""state":"merged""#));
+
+    test_db.cleanup().await;
+}
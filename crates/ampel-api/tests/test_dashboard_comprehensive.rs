@@ -144,6 +144,10 @@ async fn create_pull_request(
         merged_at: Set(None),
         closed_at: Set(None),
         last_synced_at: Set(Utc::now()),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     };
 
     pr.insert(db).await.unwrap()
@@ -190,6 +194,7 @@ async fn create_review(
         state: Set(state.to_string()),
         body: Set(Some("LGTM".to_string())),
         submitted_at: Set(Utc::now()),
+        commit_sha: Set(None),
     };
 
     review.insert(db).await.unwrap()
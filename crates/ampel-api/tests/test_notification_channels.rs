@@ -0,0 +1,298 @@
+/// Integration tests for notification channels
+///
+/// Covers creating, testing, updating and deleting personal channels, team
+/// channels and who may manage them, and that responses never echo the
+/// credentials a channel is configured with.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use common::{create_test_app, TestDb};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use wiremock::matchers::{header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn register_and_login(app: &Router, email: &str) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": "Channel User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    json["data"]["accessToken"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_personal_channel_lifecycle() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register_and_login(&app, "owner@example.com").await;
+    let other = register_and_login(&app, "other@example.com").await;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/ampel"))
+        .and(header_exists("X-Ampel-Signature"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let webhook = json!({
+        "kind": "webhook",
+        "url": format!("{}/ampel", server.uri()),
+        "secret": "a-very-secret-signing-key"
+    });
+
+    // Invalid settings and events are rejected
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/channels",
+        &owner,
+        Some(json!({
+            "name": "Hook",
+            "config": { "kind": "webhook", "url": server.uri(), "secret": "short" }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/channels",
+        &owner,
+        Some(json!({ "name": "Hook", "config": webhook, "events": ["pr_exploded"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/channels",
+        &owner,
+        Some(json!({ "name": "Hook", "config": webhook, "events": ["pr_failed"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let channel = &json["data"];
+    let channel_id = channel["id"].as_str().unwrap().to_string();
+    assert_eq!(channel["kind"], "webhook");
+    assert_eq!(channel["target"], "127.0.0.1");
+    assert_eq!(channel["events"], json!(["pr_failed"]));
+    assert!(!json.to_string().contains("a-very-secret-signing-key"));
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/notifications/channels/{channel_id}/test"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Other users cannot see or change the channel
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &format!("/api/notifications/channels/{channel_id}"),
+        &other,
+        Some(json!({ "enabled": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/notifications/channels",
+        &other,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    let (status, json) = send(
+        &app,
+        Method::PUT,
+        &format!("/api/notifications/channels/{channel_id}"),
+        &owner,
+        Some(json!({ "enabled": false, "events": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["enabled"], false);
+    assert_eq!(json["data"]["events"], Value::Null);
+
+    let (status, _) = send(
+        &app,
+        Method::DELETE,
+        &format!("/api/notifications/channels/{channel_id}"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/notifications/channels",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_team_channels_are_managed_by_team_admins() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register_and_login(&app, "owner@example.com").await;
+    let outsider = register_and_login(&app, "outsider@example.com").await;
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/organizations",
+        &owner,
+        Some(json!({ "name": "Acme" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let org_id = json["data"]["id"].as_str().unwrap().to_string();
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/teams",
+        &owner,
+        Some(json!({ "organizationId": org_id, "name": "Platform" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let team_id = json["data"]["id"].as_str().unwrap().to_string();
+    let channels_uri = format!("/api/teams/{team_id}/notification-channels");
+
+    let discord = json!({
+        "name": "Team room",
+        "config": {
+            "kind": "discord",
+            "webhookUrl": "https://discord.com/api/webhooks/1/secret-token"
+        }
+    });
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &channels_uri,
+        &outsider,
+        Some(discord.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Organization owners administer every team
+    let (status, json) = send(&app, Method::POST, &channels_uri, &owner, Some(discord)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let channel_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["teamId"], team_id.as_str());
+    assert_eq!(json["data"]["target"], "discord.com");
+    assert!(!json.to_string().contains("secret-token"));
+
+    let (status, json) = send(&app, Method::GET, &channels_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    let (status, _) = send(&app, Method::GET, &channels_uri, &outsider, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Team channels are changed through the shared channel endpoints
+    let (status, _) = send(
+        &app,
+        Method::DELETE,
+        &format!("/api/notifications/channels/{channel_id}"),
+        &outsider,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, json) = send(
+        &app,
+        Method::PUT,
+        &format!("/api/notifications/channels/{channel_id}"),
+        &owner,
+        Some(json!({ "name": "Alerts", "events": ["merge_failed"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["name"], "Alerts");
+    assert_eq!(json["data"]["events"], json!(["merge_failed"]));
+
+    test_db.cleanup().await;
+}
@@ -0,0 +1,539 @@
+/// Integration tests for OpenID Connect single sign-on
+///
+/// A wiremock server plays the IdP: it serves the discovery document, the JWKS
+/// for `test-fixtures/oidc_signing_key.pem` and a token endpoint returning an
+/// ID token signed with that key.
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use chrono::Utc;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use url::Url;
+use uuid::Uuid;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use ampel_api::oidc::{GroupMapping, OidcClient, OidcConfig};
+use ampel_api::routes::create_router;
+use ampel_api::Config;
+use ampel_db::entities::{organization, team, team_member, user, user_identity};
+use common::{create_test_app, create_test_state, TestDb};
+
+const SIGNING_KEY: &[u8] = include_bytes!("test-fixtures/oidc_signing_key.pem");
+const JWKS: &str = include_str!("test-fixtures/oidc_jwks.json");
+const CLIENT_ID: &str = "ampel-test";
+const POST_LOGIN_REDIRECT: &str = "http://localhost:3000/auth/sso";
+
+/// Start a mock IdP serving discovery and JWKS
+async fn mock_idp() -> MockServer {
+    let idp = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/.well-known/openid-configuration"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "issuer": idp.uri(),
+            "authorization_endpoint": format!("{}/authorize", idp.uri()),
+            "token_endpoint": format!("{}/token", idp.uri()),
+            "jwks_uri": format!("{}/jwks", idp.uri()),
+        })))
+        .mount(&idp)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/jwks"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::from_str::<Value>(JWKS).unwrap()),
+        )
+        .mount(&idp)
+        .await;
+    idp
+}
+
+fn sso_app(db: DatabaseConnection, idp: &MockServer, group_mappings: Vec<GroupMapping>) -> Router {
+    let state = create_test_state(db).with_oidc(OidcClient::new(OidcConfig {
+        issuer_url: idp.uri(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: Some("test-secret".to_string()),
+        redirect_url: "http://localhost:8080/api/auth/oidc/callback".to_string(),
+        post_login_redirect: POST_LOGIN_REDIRECT.to_string(),
+        scopes: vec!["openid".to_string(), "email".to_string()],
+        groups_claim: "groups".to_string(),
+        group_mappings,
+        provider_name: "Test IdP".to_string(),
+    }));
+    create_router(state)
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn location(response: &Response) -> Url {
+    Url::parse(
+        response
+            .headers()
+            .get(header::LOCATION)
+            .expect("a redirect")
+            .to_str()
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+/// A login in progress at the IdP
+struct PendingLogin {
+    state: String,
+    nonce: String,
+    cookie: String,
+}
+
+async fn start_login(app: &Router) -> PendingLogin {
+    let response = app
+        .clone()
+        .oneshot(get("/api/auth/oidc/login"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+    let cookie = response
+        .headers()
+        .get(header::SET_COOKIE)
+        .expect("a login cookie")
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    let params: HashMap<String, String> = location(&response).query_pairs().into_owned().collect();
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert_eq!(params["client_id"], CLIENT_ID);
+
+    PendingLogin {
+        state: params["state"].clone(),
+        nonce: params["nonce"].clone(),
+        cookie,
+    }
+}
+
+/// Have the mock IdP's token endpoint answer the next code exchange with an
+/// ID token carrying `claims`
+async fn issue_id_token(idp: &MockServer, nonce: &str, claims: Value) {
+    let now = Utc::now().timestamp();
+    let mut payload = json!({
+        "iss": idp.uri(),
+        "aud": CLIENT_ID,
+        "iat": now,
+        "exp": now + 300,
+        "nonce": nonce,
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(claims.as_object().unwrap().clone());
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("test-key".to_string());
+    let id_token = encode(
+        &header,
+        &payload,
+        &EncodingKey::from_rsa_pem(SIGNING_KEY).unwrap(),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("code_verifier="))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "idp-access-token",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        })))
+        .up_to_n_times(1)
+        .mount(idp)
+        .await;
+}
+
+/// Return to the callback and collect the fragment of the final redirect
+async fn finish_login(app: &Router, login: &PendingLogin, state: &str) -> HashMap<String, String> {
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!(
+            "/api/auth/oidc/callback?code=test-code&state={}",
+            state
+        ))
+        .header(header::COOKIE, &login.cookie)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+    let target = location(&response);
+    assert!(target.as_str().starts_with(POST_LOGIN_REDIRECT));
+    url::form_urlencoded::parse(target.fragment().unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect()
+}
+
+async fn seed_team(db: &DatabaseConnection) -> Uuid {
+    let now = Utc::now();
+    let owner = user::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        email: Set(format!("owner-{}@example.com", Uuid::new_v4())),
+        password_hash: Set("unused".to_string()),
+        display_name: Set(None),
+        avatar_url: Set(None),
+        language: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let org_id = Uuid::new_v4();
+    organization::ActiveModel {
+        id: Set(org_id),
+        owner_id: Set(owner.id),
+        name: Set("Org".to_string()),
+        slug: Set(format!("org-{org_id}")),
+        description: Set(None),
+        logo_url: Set(None),
+        air_gapped: Set(false),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let team_id = Uuid::new_v4();
+    team::ActiveModel {
+        id: Set(team_id),
+        organization_id: Set(org_id),
+        name: Set("Platform".to_string()),
+        slug: Set("platform".to_string()),
+        description: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .unwrap();
+    team_id
+}
+
+/// Test a first SSO sign-in creates the user and grants mapped team roles
+#[tokio::test]
+async fn test_sso_login_provisions_user_and_maps_groups() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection().clone();
+
+    let team_id = seed_team(&db).await;
+    let idp = mock_idp().await;
+    let app = sso_app(
+        db.clone(),
+        &idp,
+        vec![
+            format!("engineers={team_id}:member").parse().unwrap(),
+            format!("platform-leads={team_id}:admin").parse().unwrap(),
+        ],
+    );
+
+    let login = start_login(&app).await;
+    issue_id_token(
+        &idp,
+        &login.nonce,
+        json!({
+            "sub": "idp-user-1",
+            "email": "sso.user@example.com",
+            "email_verified": true,
+            "name": "SSO User",
+            "groups": ["engineers", "platform-leads"],
+        }),
+    )
+    .await;
+    let result = finish_login(&app, &login, &login.state).await;
+
+    assert_eq!(result.get("error"), None);
+    assert_eq!(result["tokenType"], "Bearer");
+    assert!(!result["refreshToken"].is_empty());
+
+    let me = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/auth/me")
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Bearer {}", result["accessToken"]),
+                )
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(me.status(), StatusCode::OK);
+
+    let user = user::Entity::find()
+        .filter(user::Column::Email.eq("sso.user@example.com"))
+        .one(&db)
+        .await
+        .unwrap()
+        .expect("user provisioned just in time");
+    assert_eq!(user.display_name.as_deref(), Some("SSO User"));
+
+    let identity = user_identity::Entity::find()
+        .filter(user_identity::Column::UserId.eq(user.id))
+        .one(&db)
+        .await
+        .unwrap()
+        .expect("identity linked");
+    assert_eq!(identity.subject, "idp-user-1");
+
+    let membership = team_member::Entity::find()
+        .filter(team_member::Column::TeamId.eq(team_id))
+        .filter(team_member::Column::UserId.eq(user.id))
+        .one(&db)
+        .await
+        .unwrap()
+        .expect("team membership from groups");
+    assert_eq!(membership.role, "admin", "highest mapped role wins");
+
+    test_db.cleanup().await;
+}
+
+/// Test a later sign-in without the mapped groups removes the membership
+#[tokio::test]
+async fn test_sso_login_removes_membership_when_group_is_gone() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection().clone();
+
+    let team_id = seed_team(&db).await;
+    let idp = mock_idp().await;
+    let app = sso_app(
+        db.clone(),
+        &idp,
+        vec![format!("engineers={team_id}:member").parse().unwrap()],
+    );
+
+    for groups in [json!(["engineers"]), json!([])] {
+        let login = start_login(&app).await;
+        issue_id_token(
+            &idp,
+            &login.nonce,
+            json!({
+                "sub": "idp-user-2",
+                "email": "leaver@example.com",
+                "email_verified": true,
+                "groups": groups,
+            }),
+        )
+        .await;
+        let result = finish_login(&app, &login, &login.state).await;
+        assert_eq!(result.get("error"), None);
+    }
+
+    let memberships = team_member::Entity::find()
+        .filter(team_member::Column::TeamId.eq(team_id))
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(memberships.is_empty());
+    assert_eq!(
+        user_identity::Entity::find().all(&db).await.unwrap().len(),
+        1,
+        "the second sign-in reuses the linked identity"
+    );
+
+    test_db.cleanup().await;
+}
+
+/// Test the callback rejects a state that does not match the login cookie
+#[tokio::test]
+async fn test_sso_callback_rejects_mismatched_state() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let idp = mock_idp().await;
+    let app = sso_app(test_db.connection().clone(), &idp, Vec::new());
+
+    let login = start_login(&app).await;
+    let result = finish_login(&app, &login, "forged-state").await;
+
+    assert_eq!(result["error"], "invalid_state");
+    assert!(!result.contains_key("accessToken"));
+
+    test_db.cleanup().await;
+}
+
+/// Test an unverified IdP email is not linked to an existing local account
+#[tokio::test]
+async fn test_sso_does_not_link_unverified_email() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let idp = mock_idp().await;
+    let app = sso_app(test_db.connection().clone(), &idp, Vec::new());
+
+    let register = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": "local@example.com",
+                "password": "SecurePassword123!"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(register).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let login = start_login(&app).await;
+    issue_id_token(
+        &idp,
+        &login.nonce,
+        json!({
+            "sub": "idp-user-3",
+            "email": "local@example.com",
+            "email_verified": false,
+        }),
+    )
+    .await;
+    let result = finish_login(&app, &login, &login.state).await;
+
+    assert_eq!(result["error"], "email_conflict");
+    assert!(user_identity::Entity::find()
+        .all(test_db.connection())
+        .await
+        .unwrap()
+        .is_empty());
+
+    test_db.cleanup().await;
+}
+
+/// Test password login and registration are refused when disabled
+#[tokio::test]
+async fn test_local_login_disabled() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let mut state = create_test_state(test_db.connection().clone());
+    state.config = Arc::new(Config {
+        local_login_enabled: false,
+        ..(*state.config).clone()
+    });
+    let app = create_router(state);
+
+    for uri in ["/api/auth/register", "/api/auth/login"] {
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({
+                    "email": "local@example.com",
+                    "password": "SecurePassword123!"
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{uri}");
+    }
+
+    let response = app.clone().oneshot(get("/api/auth/methods")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["data"]["localLogin"], false);
+
+    test_db.cleanup().await;
+}
+
+/// Test the sign-in methods of a deployment without SSO
+#[tokio::test]
+async fn test_auth_methods_without_sso() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+
+    let app = create_test_app(test_db.connection().clone()).await;
+
+    let response = app.clone().oneshot(get("/api/auth/methods")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["data"]["localLogin"], true);
+    assert!(json["data"]["sso"].is_null());
+
+    let response = app.oneshot(get("/api/auth/oidc/login")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
@@ -0,0 +1,625 @@
+/// Integration tests for organization management and org-level RBAC
+///
+/// Covers organization CRUD, invitations, member roles and the role checks
+/// that gate teams, remediation policies and the air-gapped ceiling.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use common::{create_test_app, TestDb};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+/// A registered user
+struct User {
+    id: String,
+    token: String,
+}
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn register(app: &Router, email: &str) -> User {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": email
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    let token = json["data"]["accessToken"].as_str().unwrap().to_string();
+
+    let (_, me) = send(app, Method::GET, "/api/auth/me", &token, None).await;
+    User {
+        id: me["data"]["id"].as_str().unwrap().to_string(),
+        token,
+    }
+}
+
+async fn create_org(app: &Router, owner: &User, name: &str) -> String {
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/organizations",
+        &owner.token,
+        Some(json!({ "name": name })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    json["data"]["id"].as_str().unwrap().to_string()
+}
+
+/// Invite `user` to the organization and accept as them
+async fn join(app: &Router, org_id: &str, inviter: &User, user: &User, email: &str, role: &str) {
+    let (status, json) = send(
+        app,
+        Method::POST,
+        &format!("/api/organizations/{org_id}/invitations"),
+        &inviter.token,
+        Some(json!({ "email": email, "role": role })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let token = json["data"]["token"].as_str().unwrap();
+
+    let (status, json) = send(
+        app,
+        Method::POST,
+        &format!("/api/invitations/{token}/accept"),
+        &user.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["role"], role);
+}
+
+#[tokio::test]
+async fn test_create_and_list_organizations() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register(&app, "owner@example.com").await;
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/organizations",
+        &owner.token,
+        Some(json!({ "name": "Acme Corp" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["data"]["slug"], "acme-corp");
+    assert_eq!(json["data"]["role"], "owner");
+    assert_eq!(json["data"]["airGapped"], false);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/organizations",
+        &owner.token,
+        Some(json!({ "name": "Other", "slug": "acme-corp" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, json) = send(&app, Method::GET, "/api/organizations", &owner.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    let org_id = json["data"][0]["id"].as_str().unwrap();
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}/members"),
+        &owner.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"][0]["userId"], owner.id.as_str());
+    assert_eq!(json["data"][0]["role"], "owner");
+
+    // Organizations are invisible to non-members
+    let stranger = register(&app, "stranger@example.com").await;
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}"),
+        &stranger.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_invitation_grants_membership_once() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register(&app, "owner@example.com").await;
+    let invitee = register(&app, "invitee@example.com").await;
+    let other = register(&app, "other@example.com").await;
+    let org_id = create_org(&app, &owner, "Acme").await;
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        &format!("/api/organizations/{org_id}/invitations"),
+        &owner.token,
+        Some(json!({ "email": "Invitee@Example.com", "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["data"]["emailSent"], false);
+    let token = json["data"]["token"].as_str().unwrap().to_string();
+    assert!(json["data"]["acceptUrl"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/invitations/{token}")));
+
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}/invitations"),
+        &owner.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    assert!(json["data"][0].get("tokenHash").is_none());
+
+    // Owners cannot be invited
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/organizations/{org_id}/invitations"),
+        &owner.token,
+        Some(json!({ "email": "other@example.com", "role": "owner" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Only the invited address can accept, case-insensitively
+    let accept = format!("/api/invitations/{token}/accept");
+    let (status, _) = send(&app, Method::POST, &accept, &other.token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, json) = send(&app, Method::POST, &accept, &invitee.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["role"], "admin");
+
+    let (status, _) = send(&app, Method::POST, &accept, &invitee.token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}/invitations"),
+        &owner.token,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_air_gap_and_deletion_require_owner() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register(&app, "owner@example.com").await;
+    let admin = register(&app, "admin@example.com").await;
+    let org_id = create_org(&app, &owner, "Acme").await;
+    join(&app, &org_id, &owner, &admin, "admin@example.com", "admin").await;
+    let org_uri = format!("/api/organizations/{org_id}");
+
+    let (status, json) = send(
+        &app,
+        Method::PATCH,
+        &org_uri,
+        &admin.token,
+        Some(json!({ "description": "Platform team" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["description"], "Platform team");
+
+    let (status, _) = send(
+        &app,
+        Method::PATCH,
+        &org_uri,
+        &admin.token,
+        Some(json!({ "airGapped": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, json) = send(
+        &app,
+        Method::PATCH,
+        &org_uri,
+        &owner.token,
+        Some(json!({ "airGapped": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["airGapped"], true);
+
+    let (status, _) = send(&app, Method::DELETE, &org_uri, &admin.token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(&app, Method::DELETE, &org_uri, &owner.token, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, Method::GET, &org_uri, &owner.token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_member_roles_and_last_owner() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register(&app, "owner@example.com").await;
+    let member = register(&app, "member@example.com").await;
+    let org_id = create_org(&app, &owner, "Acme").await;
+    join(
+        &app,
+        &org_id,
+        &owner,
+        &member,
+        "member@example.com",
+        "member",
+    )
+    .await;
+
+    // Members cannot invite or change roles
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/organizations/{org_id}/invitations"),
+        &member.token,
+        Some(json!({ "email": "x@example.com", "role": "member" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let owner_uri = format!("/api/organizations/{org_id}/members/{}", owner.id);
+    let member_uri = format!("/api/organizations/{org_id}/members/{}", member.id);
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &member_uri,
+        &member.token,
+        Some(json!({ "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The last owner can neither leave nor step down
+    let (status, _) = send(&app, Method::DELETE, &owner_uri, &owner.token, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &owner_uri,
+        &owner.token,
+        Some(json!({ "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Handing over ownership lets the original owner leave
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &member_uri,
+        &owner.token,
+        Some(json!({ "role": "owner" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, Method::DELETE, &owner_uri, &owner.token, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}/members"),
+        &member.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let members = json["data"].as_array().unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0]["userId"], member.id.as_str());
+    assert_eq!(members[0]["role"], "owner");
+
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}"),
+        &owner.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_team_management_follows_roles() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register(&app, "owner@example.com").await;
+    let member = register(&app, "member@example.com").await;
+    let outsider = register(&app, "outsider@example.com").await;
+    let org_id = create_org(&app, &owner, "Acme").await;
+    join(
+        &app,
+        &org_id,
+        &owner,
+        &member,
+        "member@example.com",
+        "member",
+    )
+    .await;
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/teams",
+        &member.token,
+        Some(json!({ "organizationId": org_id, "name": "Platform" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/teams",
+        &owner.token,
+        Some(json!({ "organizationId": org_id, "name": "Platform" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let team_id = json["data"]["id"].as_str().unwrap().to_string();
+    let members_uri = format!("/api/teams/{team_id}/members");
+
+    // Only organization members can join, with a known role
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &members_uri,
+        &owner.token,
+        Some(json!({ "userId": outsider.id, "role": "member" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &members_uri,
+        &owner.token,
+        Some(json!({ "userId": member.id, "role": "superuser" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &members_uri,
+        &owner.token,
+        Some(json!({ "userId": member.id, "role": "viewer" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/teams/{team_id}"),
+        &member.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["memberCount"], 2);
+
+    // Viewers cannot manage the team
+    let (status, _) = send(
+        &app,
+        Method::DELETE,
+        &format!("/api/teams/{team_id}/members/{}", owner.id),
+        &member.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/organizations/{org_id}/teams"),
+        &member.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_org_policies_require_org_admin() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register(&app, "owner@example.com").await;
+    let member = register(&app, "member@example.com").await;
+    let org_id = create_org(&app, &owner, "Acme").await;
+    join(
+        &app,
+        &org_id,
+        &owner,
+        &member,
+        "member@example.com",
+        "member",
+    )
+    .await;
+
+    let policy = json!({
+        "scopeType": "org",
+        "scopeId": org_id,
+        "minOpenPrs": 2,
+        "autonomyLevel": "dry_run_only",
+        "remediationTier": "consolidate_only",
+        "maxPrsPerRun": 5,
+        "prSelection": "all_open"
+    });
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/remediation/policies",
+        &member.token,
+        Some(policy.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/remediation/policies",
+        &owner.token,
+        Some(policy),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let policy_uri = format!(
+        "/api/remediation/policies/{}",
+        json["data"]["id"].as_str().unwrap()
+    );
+
+    // Members can read the organization's policies but not change them
+    let (status, _) = send(&app, Method::GET, &policy_uri, &member.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, Method::DELETE, &policy_uri, &member.token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        "/api/remediation/scopes",
+        &member.token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["orgs"].as_array().unwrap().len(), 0);
+
+    test_db.cleanup().await;
+}
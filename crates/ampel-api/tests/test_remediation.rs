@@ -119,6 +119,10 @@ async fn seed_open_pr(conn: &DatabaseConnection, repo_id: Uuid, number: i32) {
         merged_at: Set(None),
         closed_at: Set(None),
         last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     }
     .insert(conn)
     .await
@@ -145,6 +149,7 @@ async fn seed_policy(
         pr_selection: Set("\"all_open\"".to_string()),
         autonomy_level: Set(autonomy_level.to_string()),
         remediation_tier: Set("consolidate_only".to_string()),
+        consolidation_strategy: Set("merge".to_string()),
         max_prs_per_run: Set(10),
         allowed_targets: Set("[\"main\"]".to_string()),
         skip_draft: Set(false),
@@ -226,6 +231,7 @@ async fn test_policy_crud_lifecycle() {
     assert_eq!(resp.status(), StatusCode::CREATED);
     let created = parse_json(resp).await;
     assert_eq!(created["data"]["enabled"], true);
+    assert_eq!(created["data"]["consolidationStrategy"], "merge");
     let policy_id = created["data"]["id"].as_str().unwrap().to_string();
 
     // Get.
@@ -271,7 +277,10 @@ async fn test_policy_crud_lifecycle() {
                 .uri(format!("/api/remediation/policies/{policy_id}"))
                 .header(header::CONTENT_TYPE, "application/json")
                 .header(header::AUTHORIZATION, format!("Bearer {token}"))
-                .body(Body::from(json!({ "maxPrsPerRun": 9 }).to_string()))
+                .body(Body::from(
+                    json!({ "maxPrsPerRun": 9, "consolidationStrategy": "rebase_onto_base" })
+                        .to_string(),
+                ))
                 .unwrap(),
         )
         .await
@@ -279,6 +288,7 @@ async fn test_policy_crud_lifecycle() {
     assert_eq!(resp.status(), StatusCode::OK);
     let patched = parse_json(resp).await;
     assert_eq!(patched["data"]["maxPrsPerRun"], 9);
+    assert_eq!(patched["data"]["consolidationStrategy"], "rebase_onto_base");
 
     // Toggle (true -> false).
     let resp = app
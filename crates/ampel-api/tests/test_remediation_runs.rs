@@ -134,6 +134,8 @@ async fn seed_run(conn: &DatabaseConnection, repo_id: Uuid, state: &str, ci_stat
         attempts: Set(0),
         error_message: Set(None),
         error_class: Set(None),
+        queued_at: Set(None),
+        heartbeat_at: Set(None),
         started_at: Set(now),
         completed_at: Set(None),
         created_at: Set(now),
@@ -198,6 +200,7 @@ async fn seed_policy(conn: &DatabaseConnection, scope_id: Uuid, enabled: bool) {
         pr_selection: Set("\"all_open\"".to_string()),
         autonomy_level: Set("dry_run_only".to_string()),
         remediation_tier: Set("consolidate_only".to_string()),
+        consolidation_strategy: Set("merge".to_string()),
         max_prs_per_run: Set(10),
         allowed_targets: Set("[\"main\"]".to_string()),
         skip_draft: Set(false),
@@ -315,7 +318,7 @@ async fn test_get_run_returns_dispositions_and_conflict_report() {
         &conn,
         run,
         2,
-        r#"{"disposition":"skipped_conflict","reason":"merge conflict in Cargo.lock"}"#,
+        r#"{"disposition":"skipped_conflict","reason":"merge conflict in src/lib.rs","files":["src/lib.rs"]}"#,
     )
     .await;
     seed_disposition(
@@ -347,6 +350,10 @@ async fn test_get_run_returns_dispositions_and_conflict_report() {
         detail["data"]["conflictReport"]["conflicts"][0]["prNumber"],
         2
     );
+    assert_eq!(
+        detail["data"]["conflictReport"]["conflicts"][0]["files"],
+        json!(["src/lib.rs"])
+    );
     assert_eq!(
         detail["data"]["conflictReport"]["skipped"]
             .as_array()
@@ -0,0 +1,499 @@
+/// Integration tests for repository sharing and team dashboards
+///
+/// Covers sharing a repository with a team or an organization, the access
+/// each member then has to its pull requests, and the team dashboard that
+/// aggregates the repositories shared with a team.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use common::{create_test_app, TestDb};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use ampel_core::models::OrgRole;
+use ampel_db::entities::{pull_request, repository};
+use ampel_db::queries::OrganizationQueries;
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+/// Register a user, returning their access token and id
+async fn register_and_login(app: &Router, email: &str) -> (String, Uuid) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": "Share User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    let token = json["data"]["accessToken"].as_str().unwrap().to_string();
+
+    let (_, me) = send(app, Method::GET, "/api/auth/me", &token, None).await;
+    let user_id = me["data"]["id"].as_str().unwrap().parse().unwrap();
+    (token, user_id)
+}
+
+async fn seed_repository(conn: &DatabaseConnection, user_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("p-{id}")),
+        owner: Set("octocat".to_string()),
+        name: Set("shared".to_string()),
+        full_name: Set("octocat/shared".to_string()),
+        description: Set(None),
+        url: Set("https://example.com/octocat/shared".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(true),
+        is_archived: Set(false),
+        poll_interval_seconds: Set(300),
+        last_polled_at: Set(None),
+        group_id: Set(None),
+        provider_account_id: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+async fn seed_open_pr(conn: &DatabaseConnection, repo_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    pull_request::ActiveModel {
+        id: Set(id),
+        repository_id: Set(repo_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("pr-{repo_id}")),
+        number: Set(1),
+        title: Set("Shared PR".to_string()),
+        description: Set(None),
+        url: Set("https://example.com/pr/1".to_string()),
+        state: Set("open".to_string()),
+        source_branch: Set("feature/shared".to_string()),
+        target_branch: Set("main".to_string()),
+        author: Set("octocat".to_string()),
+        author_avatar_url: Set(None),
+        is_draft: Set(false),
+        is_mergeable: Set(Some(true)),
+        has_conflicts: Set(false),
+        additions: Set(1),
+        deletions: Set(0),
+        changed_files: Set(1),
+        commits_count: Set(1),
+        comments_count: Set(0),
+        created_at: Set(now),
+        updated_at: Set(now),
+        merged_at: Set(None),
+        closed_at: Set(None),
+        last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+/// Create an organization owned by `token`'s user and one team in it,
+/// returning their ids
+async fn create_org_and_team(app: &Router, token: &str) -> (String, String) {
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/organizations",
+        token,
+        Some(json!({ "name": "Acme" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let org_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/teams",
+        token,
+        Some(json!({ "organizationId": org_id, "name": "Platform" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let team_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    (org_id, team_id)
+}
+
+#[tokio::test]
+async fn test_team_share_grants_team_roles() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+    let (member, member_id) = register_and_login(&app, "member@example.com").await;
+    let (viewer, viewer_id) = register_and_login(&app, "viewer@example.com").await;
+    let (outsider, _) = register_and_login(&app, "outsider@example.com").await;
+
+    let (org_id, team_id) = create_org_and_team(&app, &owner).await;
+    let org_uuid: Uuid = org_id.parse().unwrap();
+    for (user_id, role) in [(member_id, "member"), (viewer_id, "viewer")] {
+        OrganizationQueries::upsert_member(db, org_uuid, user_id, OrgRole::Member)
+            .await
+            .unwrap();
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &format!("/api/teams/{team_id}/members"),
+            &owner,
+            Some(json!({ "userId": user_id, "role": role })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let repo_id = seed_repository(db, owner_id).await;
+    let pr_id = seed_open_pr(db, repo_id).await;
+    let prs_uri = format!("/api/repositories/{repo_id}/pull-requests");
+    let merge_uri = format!("{prs_uri}/{pr_id}/merge");
+    let shares_uri = format!("/api/repositories/{repo_id}/shares");
+    let merge_body = json!({ "strategy": "squash", "deleteBranch": false });
+
+    // Before sharing only the owner sees the repository
+    let (status, _) = send(&app, Method::GET, &prs_uri, &member, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &shares_uri,
+        &member,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A share names exactly one team or organization
+    for body in [
+        json!({}),
+        json!({ "teamId": team_id, "organizationId": org_id }),
+    ] {
+        let (status, _) = send(&app, Method::POST, &shares_uri, &owner, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        &shares_uri,
+        &owner,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let share_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["teamId"], team_id.as_str());
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &shares_uri,
+        &owner,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Team viewers read, team members may also merge
+    for token in [&member, &viewer] {
+        let (status, json) = send(&app, Method::GET, &prs_uri, token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    }
+    let (status, _) = send(&app, Method::GET, &prs_uri, &outsider, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &merge_uri,
+        &viewer,
+        Some(merge_body.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    // Access is granted; the merge itself fails on the unlinked test repository
+    let (status, _) = send(&app, Method::POST, &merge_uri, &member, Some(merge_body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let bulk = json!({ "pullRequestIds": [pr_id] });
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &viewer,
+        Some(bulk.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, json) = send(&app, Method::POST, "/api/merge/bulk", &member, Some(bulk)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["total"], 1);
+
+    // Only repository admins manage shares
+    let (status, json) = send(&app, Method::GET, &shares_uri, &viewer, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    let share_uri = format!("{shares_uri}/{share_id}");
+    let (status, _) = send(&app, Method::DELETE, &share_uri, &member, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(&app, Method::DELETE, &share_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, Method::GET, &prs_uri, &member, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_team_dashboard_shows_shared_repositories() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+    let (member, member_id) = register_and_login(&app, "member@example.com").await;
+    let (outsider, _) = register_and_login(&app, "outsider@example.com").await;
+
+    let (org_id, team_id) = create_org_and_team(&app, &owner).await;
+    OrganizationQueries::upsert_member(db, org_id.parse().unwrap(), member_id, OrgRole::Member)
+        .await
+        .unwrap();
+    let repo_id = seed_repository(db, owner_id).await;
+    let pr_id = seed_open_pr(db, repo_id).await;
+    // The owner's other repositories stay private
+    seed_repository(db, owner_id).await;
+
+    let summary_uri = format!("/api/teams/{team_id}/dashboard/summary");
+    let grid_uri = format!("/api/teams/{team_id}/dashboard/grid");
+    let (status, json) = send(&app, Method::GET, &grid_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    // Sharing with the organization reaches every team in it
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/repositories/{repo_id}/shares"),
+        &owner,
+        Some(json!({ "organizationId": org_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, json) = send(&app, Method::GET, &grid_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let grid = json["data"].as_array().unwrap();
+    assert_eq!(grid.len(), 1);
+    assert_eq!(grid[0]["id"], repo_id.to_string());
+    assert_eq!(grid[0]["openPrCount"], 1);
+
+    let (status, json) = send(&app, Method::GET, &summary_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["totalRepositories"], 1);
+    assert_eq!(json["data"]["totalOpenPrs"], 1);
+    assert_eq!(json["data"]["repositoryBreakdown"]["private"], 1);
+
+    let (status, _) = send(&app, Method::GET, &summary_uri, &outsider, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Organization members who are not on the team only view the repository
+    let (status, _) = send(&app, Method::GET, &grid_uri, &member, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        &format!("/api/repositories/{repo_id}/pull-requests/{pr_id}"),
+        &member,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/repositories/{repo_id}/pull-requests/{pr_id}/refresh"),
+        &member,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_team_members_list_and_bulk_merge_shared_repositories() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+    let (member, member_id) = register_and_login(&app, "member@example.com").await;
+    let (viewer, viewer_id) = register_and_login(&app, "viewer@example.com").await;
+    let (outsider, _) = register_and_login(&app, "outsider@example.com").await;
+
+    let (org_id, team_id) = create_org_and_team(&app, &owner).await;
+    let org_uuid: Uuid = org_id.parse().unwrap();
+    for (user_id, role) in [(member_id, "member"), (viewer_id, "viewer")] {
+        OrganizationQueries::upsert_member(db, org_uuid, user_id, OrgRole::Member)
+            .await
+            .unwrap();
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &format!("/api/teams/{team_id}/members"),
+            &owner,
+            Some(json!({ "userId": user_id, "role": role })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    let repo_id = seed_repository(db, owner_id).await;
+    let pr_id = seed_open_pr(db, repo_id).await;
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/repositories/{repo_id}/shares"),
+        &owner,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // The shared repository's PRs appear in every team member's list and
+    // dashboard, but not in an outsider's
+    for token in [&owner, &member, &viewer] {
+        let (status, json) = send(&app, Method::GET, "/api/pull-requests", token, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let items = json["data"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["id"], pr_id.to_string());
+
+        let (_, json) = send(
+            &app,
+            Method::GET,
+            "/api/pull-requests?q=repo:octocat/shared",
+            token,
+            None,
+        )
+        .await;
+        assert_eq!(json["data"]["items"].as_array().unwrap().len(), 1);
+
+        let (_, json) = send(&app, Method::GET, "/api/dashboard/summary", token, None).await;
+        assert_eq!(json["data"]["totalRepositories"], 1);
+        assert_eq!(json["data"]["totalOpenPrs"], 1);
+        let (_, json) = send(&app, Method::GET, "/api/dashboard/grid", token, None).await;
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    }
+    let (_, json) = send(&app, Method::GET, "/api/pull-requests", &outsider, None).await;
+    assert_eq!(json["data"]["items"].as_array().unwrap().len(), 0);
+    let (_, json) = send(&app, Method::GET, "/api/dashboard/grid", &outsider, None).await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    // A query-based bulk merge selects the PRs the caller may merge
+    let bulk = json!({ "query": "repo:octocat/shared" });
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &member,
+        Some(bulk.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["total"], 1);
+    assert_eq!(
+        json["data"]["results"][0]["pullRequestId"],
+        pr_id.to_string()
+    );
+    let (status, _) = send(&app, Method::POST, "/api/merge/bulk", &viewer, Some(bulk)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_db.cleanup().await;
+}
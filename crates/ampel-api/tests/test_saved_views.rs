@@ -0,0 +1,720 @@
+/// Integration tests for saved views and PR filter queries
+///
+/// Covers saving, sharing and editing views, the access team members get to
+/// a shared view, and narrowing the PR list, the dashboard and a bulk merge
+/// selection with a query or a saved view, including by the labels and
+/// people synced for each PR, and applying a team's view to the
+/// repositories shared with it.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, Utc};
+use common::{create_test_app, TestDb};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use ampel_core::models::{OrgRole, PullRequestMetadata};
+use ampel_db::entities::{provider_account, pull_request, repository};
+use ampel_db::queries::{OrganizationQueries, PrMetadataQueries};
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+/// Register a user, returning their access token and id
+async fn register_and_login(app: &Router, email: &str) -> (String, Uuid) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": "View User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    let token = json["data"]["accessToken"].as_str().unwrap().to_string();
+
+    let (_, me) = send(app, Method::GET, "/api/auth/me", &token, None).await;
+    let user_id = me["data"]["id"].as_str().unwrap().parse().unwrap();
+    (token, user_id)
+}
+
+async fn seed_repository(conn: &DatabaseConnection, user_id: Uuid, full_name: &str) -> Uuid {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    let (owner, name) = full_name.split_once('/').unwrap();
+    repository::ActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("p-{id}")),
+        owner: Set(owner.to_string()),
+        name: Set(name.to_string()),
+        full_name: Set(full_name.to_string()),
+        description: Set(None),
+        url: Set(format!("https://example.com/{full_name}")),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        poll_interval_seconds: Set(300),
+        last_polled_at: Set(None),
+        group_id: Set(None),
+        provider_account_id: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+/// Seed an open PR; conflicting PRs are red, the others yellow for want of
+/// an approval
+async fn seed_open_pr(
+    conn: &DatabaseConnection,
+    repo_id: Uuid,
+    number: i32,
+    author: &str,
+    has_conflicts: bool,
+    age_days: i64,
+) -> Uuid {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    pull_request::ActiveModel {
+        id: Set(id),
+        repository_id: Set(repo_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("pr-{id}")),
+        number: Set(number),
+        title: Set(format!("Change {number}")),
+        description: Set(None),
+        url: Set(format!("https://example.com/pr/{number}")),
+        state: Set("open".to_string()),
+        source_branch: Set(format!("feature/{number}")),
+        target_branch: Set("main".to_string()),
+        author: Set(author.to_string()),
+        author_avatar_url: Set(None),
+        is_draft: Set(false),
+        is_mergeable: Set(Some(!has_conflicts)),
+        has_conflicts: Set(has_conflicts),
+        additions: Set(1),
+        deletions: Set(0),
+        changed_files: Set(1),
+        commits_count: Set(1),
+        comments_count: Set(0),
+        created_at: Set(now - Duration::days(age_days)),
+        updated_at: Set(now),
+        merged_at: Set(None),
+        closed_at: Set(None),
+        last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+/// Link a GitHub account signed in as `login` to the user
+async fn seed_provider_account(conn: &DatabaseConnection, user_id: Uuid, login: &str) {
+    let now = Utc::now();
+    provider_account::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider: Set("github".to_string()),
+        instance_url: Set(None),
+        account_label: Set("work".to_string()),
+        provider_user_id: Set(format!("id-{login}")),
+        provider_username: Set(login.to_string()),
+        provider_email: Set(None),
+        avatar_url: Set(None),
+        auth_type: Set("pat".to_string()),
+        access_token_encrypted: Set(vec![0]),
+        auth_username: Set(None),
+        scopes: Set(None),
+        token_expires_at: Set(None),
+        last_validated_at: Set(None),
+        validation_status: Set("valid".to_string()),
+        is_active: Set(true),
+        is_default: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+/// Create an organization owned by `token`'s user and one team in it,
+/// returning their ids
+async fn create_org_and_team(app: &Router, token: &str) -> (String, String) {
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/organizations",
+        token,
+        Some(json!({ "name": "Acme" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let org_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/teams",
+        token,
+        Some(json!({ "organizationId": org_id, "name": "Platform" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let team_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    (org_id, team_id)
+}
+
+fn numbers(items: &Value) -> Vec<i64> {
+    let mut numbers: Vec<i64> = items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["number"].as_i64().unwrap())
+        .collect();
+    numbers.sort();
+    numbers
+}
+
+#[tokio::test]
+async fn test_saved_views_are_shared_with_teams() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, _) = register_and_login(&app, "owner@example.com").await;
+    let (viewer, viewer_id) = register_and_login(&app, "viewer@example.com").await;
+    let (admin, admin_id) = register_and_login(&app, "admin@example.com").await;
+    let (outsider, _) = register_and_login(&app, "outsider@example.com").await;
+    let (org_id, team_id) = create_org_and_team(&app, &owner).await;
+    for (user_id, role) in [(viewer_id, "viewer"), (admin_id, "admin")] {
+        OrganizationQueries::upsert_member(db, org_id.parse().unwrap(), user_id, OrgRole::Member)
+            .await
+            .unwrap();
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &format!("/api/teams/{team_id}/members"),
+            &owner,
+            Some(json!({ "userId": user_id, "role": role })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    // Invalid names and queries are rejected
+    for body in [
+        json!({ "name": " ", "query": "status:red" }),
+        json!({ "name": "Broken", "query": "status:purple" }),
+        json!({ "name": "Broken", "query": "age:7d" }),
+    ] {
+        let (status, _) = send(&app, Method::POST, "/api/views", &owner, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    // A view can only be shared with a team the caller belongs to
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/views",
+        &outsider,
+        Some(json!({ "name": "Theirs", "query": "status:red", "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/views",
+        &owner,
+        Some(json!({
+            "name": " Red bots ",
+            "query": "status:red author:dependabot",
+            "teamId": team_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let view_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["name"], "Red bots");
+    assert_eq!(json["data"]["teamId"], team_id.as_str());
+    assert_eq!(json["data"]["canEdit"], true);
+    assert!(json["data"]["url"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/dashboard?view={view_id}")));
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/views",
+        &owner,
+        Some(json!({ "name": "Private", "query": "status:yellow" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Team members see the shared view but not the private one
+    let view_uri = format!("/api/views/{view_id}");
+    let (_, json) = send(&app, Method::GET, "/api/views", &owner, None).await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+    let (_, json) = send(&app, Method::GET, "/api/views", &viewer, None).await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    assert_eq!(json["data"][0]["id"], view_id.as_str());
+    assert_eq!(json["data"][0]["canEdit"], false);
+    let (status, json) = send(&app, Method::GET, &view_uri, &admin, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["canEdit"], true);
+    let (status, _) = send(&app, Method::GET, &view_uri, &outsider, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Viewers cannot change it; team admins can
+    let update = json!({ "name": "Red", "query": "status:red", "teamId": team_id });
+    let (status, _) = send(&app, Method::PUT, &view_uri, &viewer, Some(update.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, json) = send(&app, Method::PUT, &view_uri, &admin, Some(update)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["query"], "status:red");
+    assert_eq!(json["data"]["id"], view_id.as_str());
+
+    // Unsharing takes it away from the team
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &view_uri,
+        &owner,
+        Some(json!({ "name": "Red", "query": "status:red" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, Method::GET, &view_uri, &viewer, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, Method::DELETE, &view_uri, &admin, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, Method::DELETE, &view_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, Method::GET, &view_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_teammates_apply_shared_views_to_shared_repositories() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+    let (mate, mate_id) = register_and_login(&app, "mate@example.com").await;
+    let (org_id, team_id) = create_org_and_team(&app, &owner).await;
+    OrganizationQueries::upsert_member(db, org_id.parse().unwrap(), mate_id, OrgRole::Member)
+        .await
+        .unwrap();
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/teams/{team_id}/members"),
+        &owner,
+        Some(json!({ "userId": mate_id, "role": "member" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let api = seed_repository(db, owner_id, "acme/api").await;
+    seed_open_pr(db, api, 1, "dependabot", true, 1).await;
+    seed_open_pr(db, api, 2, "alice", false, 1).await;
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/repositories/{api}/shares"),
+        &owner,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, json) = send(
+        &app,
+        Method::POST,
+        "/api/views",
+        &owner,
+        Some(json!({
+            "name": "Red bots",
+            "query": "status:red author:dependabot",
+            "teamId": team_id
+        })),
+    )
+    .await;
+    let view_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // The teammate gets the same PRs from the view as its author
+    for token in [&owner, &mate] {
+        let (status, json) = send(
+            &app,
+            Method::GET,
+            &format!("/api/pull-requests?view={view_id}"),
+            token,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(numbers(&json["data"]["items"]), vec![1]);
+
+        let (_, json) = send(
+            &app,
+            Method::GET,
+            &format!("/api/dashboard/summary?view={view_id}"),
+            token,
+            None,
+        )
+        .await;
+        assert_eq!(json["data"]["totalRepositories"], 1);
+        assert_eq!(json["data"]["totalOpenPrs"], 1);
+    }
+
+    // ...and can bulk-merge its selection
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &mate,
+        Some(json!({ "viewId": view_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["total"], 1);
+    assert_eq!(json["data"]["results"][0]["prNumber"], 1);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_queries_filter_prs_dashboard_and_bulk_merge() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+
+    let api = seed_repository(db, owner_id, "acme/api").await;
+    let web = seed_repository(db, owner_id, "other/web").await;
+    seed_open_pr(db, api, 1, "dependabot", true, 10).await;
+    seed_open_pr(db, api, 2, "alice", false, 1).await;
+    seed_open_pr(db, api, 3, "dependabot", true, 2).await;
+    seed_open_pr(db, web, 4, "dependabot", true, 10).await;
+
+    // Queries narrow the PR list
+    for (query, expected) in [
+        ("status:red+author:dependabot+repo:acme/*", vec![1, 3]),
+        ("status:red+age:%3E7d", vec![1, 4]),
+        ("-author:dependabot", vec![2]),
+        ("status:yellow,red+repo:other/*", vec![4]),
+        ("%22Change+2%22", vec![2]),
+    ] {
+        let (status, json) = send(
+            &app,
+            Method::GET,
+            &format!("/api/pull-requests?q={query}"),
+            &owner,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{query}");
+        assert_eq!(numbers(&json["data"]["items"]), expected, "{query}");
+        assert_eq!(json["data"]["total"], expected.len(), "{query}");
+    }
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        "/api/pull-requests?q=colour:red",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A saved view applies the same way, together with any extra query
+    let (_, json) = send(
+        &app,
+        Method::POST,
+        "/api/views",
+        &owner,
+        Some(json!({ "name": "Red bots", "query": "status:red author:dependabot" })),
+    )
+    .await;
+    let view_id = json["data"]["id"].as_str().unwrap().to_string();
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/pull-requests?view={view_id}"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(numbers(&json["data"]["items"]), vec![1, 3, 4]);
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/pull-requests?view={view_id}&q=repo:other/*"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(numbers(&json["data"]["items"]), vec![4]);
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        &format!("/api/pull-requests?view={}", Uuid::new_v4()),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The dashboard counts only what the view matches
+    let (_, json) = send(&app, Method::GET, "/api/dashboard/summary", &owner, None).await;
+    assert_eq!(json["data"]["totalOpenPrs"], 4);
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/dashboard/summary?view={view_id}&q=repo:acme/*"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["totalRepositories"], 1);
+    assert_eq!(json["data"]["totalOpenPrs"], 2);
+    assert_eq!(json["data"]["statusCounts"]["red"], 2);
+    assert_eq!(json["data"]["statusCounts"]["yellow"], 0);
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/dashboard/grid?q=author:alice",
+        &owner,
+        None,
+    )
+    .await;
+    let grid = json["data"].as_array().unwrap();
+    assert_eq!(grid.len(), 1);
+    assert_eq!(grid[0]["fullName"], "acme/api");
+    assert_eq!(grid[0]["openPrCount"], 1);
+    assert_eq!(grid[0]["status"], "yellow");
+
+    // Bulk merges can select their PRs with a view or a query
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &owner,
+        Some(json!({ "viewId": view_id, "query": "repo:acme/*" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["total"], 2);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &owner,
+        Some(json!({ "query": "author:nobody" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/merge/bulk",
+        &owner,
+        Some(json!({ "query": "status:red", "pullRequestIds": [Uuid::new_v4()] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_queries_filter_by_labels_and_people() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+    seed_provider_account(db, owner_id, "Octo").await;
+
+    let api = seed_repository(db, owner_id, "acme/api").await;
+    let first = seed_open_pr(db, api, 1, "alice", false, 1).await;
+    seed_open_pr(db, api, 2, "bob", false, 1).await;
+    let third = seed_open_pr(db, api, 3, "carol", false, 1).await;
+    PrMetadataQueries::replace(
+        db,
+        first,
+        &PullRequestMetadata {
+            labels: strings(&["security", "deps", "security"]),
+            assignees: strings(&["alice"]),
+            requested_reviewers: strings(&["octo", "dave"]),
+        },
+    )
+    .await
+    .unwrap();
+    PrMetadataQueries::replace(
+        db,
+        third,
+        &PullRequestMetadata {
+            labels: strings(&["docs"]),
+            assignees: Vec::new(),
+            requested_reviewers: strings(&["dave"]),
+        },
+    )
+    .await
+    .unwrap();
+
+    // The PR responses carry the synced labels and people
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        &format!("/api/repositories/{api}/pull-requests/{first}"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["labels"], json!(["deps", "security"]));
+    assert_eq!(json["data"]["assignees"], json!(["alice"]));
+    assert_eq!(json["data"]["requestedReviewers"], json!(["dave", "octo"]));
+    assert_eq!(json["data"]["milestone"], Value::Null);
+
+    for (query, expected) in [
+        ("label:security", vec![1]),
+        ("-label:security", vec![2, 3]),
+        ("reviewer:@me", vec![1]),
+        ("reviewer:dave+no:assignee", vec![3]),
+        ("no:label", vec![2]),
+        ("assignee:@me", vec![]),
+    ] {
+        let (status, json) = send(
+            &app,
+            Method::GET,
+            &format!("/api/pull-requests?q={query}"),
+            &owner,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{query}");
+        assert_eq!(numbers(&json["data"]["items"]), expected, "{query}");
+    }
+
+    // Dashboards count by the same metadata
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/dashboard/summary?q=label:docs",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"]["totalOpenPrs"], 1);
+
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        "/api/pull-requests?q=no:author",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_db.cleanup().await;
+}
@@ -0,0 +1,442 @@
+/// Integration tests for status policies
+///
+/// Covers configuring a repository's status policy and a team default, how
+/// each changes the status and reasons of the repository's pull requests,
+/// the localized explanation of that status, and the fallback from a
+/// repository's policy to its team's default.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use common::{create_test_app, TestDb};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use ampel_core::models::OrgRole;
+use ampel_db::entities::{ci_check, pull_request, repository, review};
+use ampel_db::queries::OrganizationQueries;
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+/// Register a user, returning their access token and id
+async fn register_and_login(app: &Router, email: &str) -> (String, Uuid) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": "Policy User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    let token = json["data"]["accessToken"].as_str().unwrap().to_string();
+
+    let (_, me) = send(app, Method::GET, "/api/auth/me", &token, None).await;
+    let user_id = me["data"]["id"].as_str().unwrap().parse().unwrap();
+    (token, user_id)
+}
+
+async fn seed_repository(conn: &DatabaseConnection, user_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("p-{id}")),
+        owner: Set("octocat".to_string()),
+        name: Set("policed".to_string()),
+        full_name: Set("octocat/policed".to_string()),
+        description: Set(None),
+        url: Set("https://example.com/octocat/policed".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(true),
+        is_archived: Set(false),
+        poll_interval_seconds: Set(300),
+        last_polled_at: Set(None),
+        group_id: Set(None),
+        provider_account_id: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+async fn seed_open_pr(conn: &DatabaseConnection, repo_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    pull_request::ActiveModel {
+        id: Set(id),
+        repository_id: Set(repo_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("pr-{repo_id}")),
+        number: Set(1),
+        title: Set("Policed PR".to_string()),
+        description: Set(None),
+        url: Set("https://example.com/pr/1".to_string()),
+        state: Set("open".to_string()),
+        source_branch: Set("feature/policed".to_string()),
+        target_branch: Set("main".to_string()),
+        author: Set("octocat".to_string()),
+        author_avatar_url: Set(None),
+        is_draft: Set(false),
+        is_mergeable: Set(Some(true)),
+        has_conflicts: Set(false),
+        additions: Set(1),
+        deletions: Set(0),
+        changed_files: Set(1),
+        commits_count: Set(1),
+        comments_count: Set(0),
+        created_at: Set(now),
+        updated_at: Set(now),
+        merged_at: Set(None),
+        closed_at: Set(None),
+        last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+async fn seed_check(conn: &DatabaseConnection, pr_id: Uuid, name: &str, conclusion: &str) {
+    ci_check::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        pull_request_id: Set(pr_id),
+        name: Set(name.to_string()),
+        status: Set("completed".to_string()),
+        conclusion: Set(Some(conclusion.to_string())),
+        url: Set(None),
+        started_at: Set(None),
+        completed_at: Set(Some(Utc::now())),
+        duration_seconds: Set(None),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+}
+
+async fn seed_approval(conn: &DatabaseConnection, pr_id: Uuid, reviewer: &str) {
+    review::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        pull_request_id: Set(pr_id),
+        reviewer: Set(reviewer.to_string()),
+        reviewer_avatar_url: Set(None),
+        state: Set("approved".to_string()),
+        body: Set(None),
+        submitted_at: Set(Utc::now()),
+        commit_sha: Set(None),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+}
+
+/// Create an organization owned by `token`'s user and one team in it,
+/// returning their ids
+async fn create_org_and_team(app: &Router, token: &str) -> (String, String) {
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/organizations",
+        token,
+        Some(json!({ "name": "Acme" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let org_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        app,
+        Method::POST,
+        "/api/teams",
+        token,
+        Some(json!({ "organizationId": org_id, "name": "Platform" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let team_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    (org_id, team_id)
+}
+
+#[tokio::test]
+async fn test_repository_policy_explains_status() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+    let (viewer, viewer_id) = register_and_login(&app, "viewer@example.com").await;
+
+    let repo_id = seed_repository(db, owner_id).await;
+    let pr_id = seed_open_pr(db, repo_id).await;
+    seed_check(db, pr_id, "build", "success").await;
+    seed_check(db, pr_id, "coverage", "failure").await;
+    seed_approval(db, pr_id, "alice").await;
+    let policy_uri = format!("/api/repositories/{repo_id}/status-policy");
+    let pr_uri = format!("/api/repositories/{repo_id}/pull-requests/{pr_id}");
+
+    // Without a policy the built-in rules apply
+    let (status, json) = send(&app, Method::GET, &policy_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["source"], "default");
+    assert_eq!(json["data"]["minApprovals"], 1);
+    assert_eq!(json["data"]["draftStatus"], "yellow");
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "red");
+    assert_eq!(
+        json["data"]["reasons"],
+        json!([{
+            "code": "checks_failed",
+            "checks": ["coverage"],
+            "message": "Failing checks: coverage"
+        }])
+    );
+
+    // Ignoring the failing check turns the PR green
+    let (status, json) = send(
+        &app,
+        Method::PUT,
+        &policy_uri,
+        &owner,
+        Some(json!({ "ignoredChecks": ["cover*"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["source"], "repository");
+    assert_eq!(json["data"]["ignoredChecks"], json!(["cover*"]));
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "green");
+    assert_eq!(json["data"]["reasons"], json!([]));
+
+    // Requiring a second approval holds it at yellow
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &policy_uri,
+        &owner,
+        Some(json!({ "ignoredChecks": ["coverage"], "minApprovals": 2 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "yellow");
+    assert_eq!(
+        json["data"]["reasons"],
+        json!([{
+            "code": "approvals_missing",
+            "approvals": 1,
+            "required": 2,
+            "message": "1 of 2 required approvals"
+        }])
+    );
+
+    // Messages follow the requested language
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        &format!("{pr_uri}?lang=de"),
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(
+        json["data"]["reasons"][0]["message"],
+        "1 von 2 erforderlichen Freigaben"
+    );
+    let (_, json) = send(&app, Method::GET, "/api/pull-requests", &owner, None).await;
+    assert_eq!(json["data"]["items"][0]["status"], "yellow");
+
+    // Invalid policies are rejected
+    for body in [
+        json!({ "requiredChecks": [" "] }),
+        json!({ "requireNamedApprover": true }),
+    ] {
+        let (status, _) = send(&app, Method::PUT, &policy_uri, &owner, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    // Only admins change the policy; outsiders do not see it at all
+    let (org_id, team_id) = create_org_and_team(&app, &owner).await;
+    let (status, _) = send(&app, Method::GET, &policy_uri, &viewer, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    OrganizationQueries::upsert_member(db, org_id.parse().unwrap(), viewer_id, OrgRole::Member)
+        .await
+        .unwrap();
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/teams/{team_id}/members"),
+        &owner,
+        Some(json!({ "userId": viewer_id, "role": "viewer" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/repositories/{repo_id}/shares"),
+        &owner,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, json) = send(&app, Method::GET, &policy_uri, &viewer, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["minApprovals"], 2);
+    let (status, _) = send(&app, Method::PUT, &policy_uri, &viewer, Some(json!({}))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(&app, Method::DELETE, &policy_uri, &viewer, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_team_default_applies_to_shared_repositories() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let app = create_test_app(db.clone()).await;
+    let (owner, owner_id) = register_and_login(&app, "owner@example.com").await;
+
+    let repo_id = seed_repository(db, owner_id).await;
+    let pr_id = seed_open_pr(db, repo_id).await;
+    seed_check(db, pr_id, "build", "success").await;
+    seed_approval(db, pr_id, "alice").await;
+    let (_, team_id) = create_org_and_team(&app, &owner).await;
+    let team_policy_uri = format!("/api/teams/{team_id}/status-policy");
+    let policy_uri = format!("/api/repositories/{repo_id}/status-policy");
+    let pr_uri = format!("/api/repositories/{repo_id}/pull-requests/{pr_id}");
+
+    let (status, json) = send(&app, Method::GET, &team_policy_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["source"], "default");
+    let (status, _) = send(&app, Method::DELETE, &team_policy_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, json) = send(
+        &app,
+        Method::PUT,
+        &team_policy_uri,
+        &owner,
+        Some(json!({ "requiredChecks": ["deploy"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["source"], "team");
+    assert_eq!(json["data"]["teamId"], team_id.as_str());
+
+    // The team default only applies once the repository is shared
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "green");
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        &format!("/api/repositories/{repo_id}/shares"),
+        &owner,
+        Some(json!({ "teamId": team_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, json) = send(&app, Method::GET, &policy_uri, &owner, None).await;
+    assert_eq!(json["data"]["source"], "team");
+    assert_eq!(json["data"]["teamId"], team_id.as_str());
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "yellow");
+    assert_eq!(
+        json["data"]["reasons"],
+        json!([{
+            "code": "required_checks_missing",
+            "checks": ["deploy"],
+            "message": "Required checks not reported: deploy"
+        }])
+    );
+
+    // The repository's own policy takes precedence, and removing it falls
+    // back to the team default
+    let (status, _) = send(&app, Method::PUT, &policy_uri, &owner, Some(json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "green");
+    let (status, _) = send(&app, Method::DELETE, &policy_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, json) = send(&app, Method::GET, &pr_uri, &owner, None).await;
+    assert_eq!(json["data"]["status"], "yellow");
+
+    let (status, _) = send(&app, Method::DELETE, &team_policy_uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, json) = send(&app, Method::GET, &policy_uri, &owner, None).await;
+    assert_eq!(json["data"]["source"], "default");
+
+    test_db.cleanup().await;
+}
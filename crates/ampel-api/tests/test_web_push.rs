@@ -0,0 +1,210 @@
+/// Integration tests for browser push subscriptions
+///
+/// Covers the VAPID public key, registering, listing and removing a browser's
+/// subscription, re-registering an endpoint, and rejecting subscriptions that
+/// push services could never deliver to.
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use common::{create_test_app, TestDb};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .header(
+            header::USER_AGENT,
+            "Mozilla/5.0 (X11; Linux x86_64) Firefox/140.0",
+        );
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn register_and_login(app: &Router, email: &str) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": email,
+                "password": "SecurePassword123!",
+                "displayName": "Push User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    json["data"]["accessToken"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_push_subscription_lifecycle() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let owner = register_and_login(&app, "owner@example.com").await;
+    let other = register_and_login(&app, "other@example.com").await;
+
+    // The key is generated once and then stays the same
+    let (status, json) = send(
+        &app,
+        Method::GET,
+        "/api/notifications/push/vapid-public-key",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let public_key = json["data"]["publicKey"].as_str().unwrap().to_string();
+    assert_eq!(public_key.len(), 87);
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/notifications/push/vapid-public-key",
+        &other,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"]["publicKey"], public_key.as_str());
+
+    // Any P-256 public key will do as the browser's key
+    let subscription = json!({
+        "endpoint": "https://updates.push.services.mozilla.com/wpush/v2/abc",
+        "expirationTime": null,
+        "keys": { "p256dh": public_key, "auth": "BTBZMqHH6r4Tts7J_aSIgg" }
+    });
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/push/subscriptions",
+        &owner,
+        Some(json!({
+            "endpoint": "http://push.example.net/abc",
+            "keys": { "p256dh": public_key, "auth": "BTBZMqHH6r4Tts7J_aSIgg" }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/push/subscriptions",
+        &owner,
+        Some(json!({
+            "endpoint": "https://push.example.net/abc",
+            "keys": { "p256dh": "bm90IGEga2V5", "auth": "BTBZMqHH6r4Tts7J_aSIgg" }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/push/subscriptions",
+        &owner,
+        Some(subscription.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let subscription_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert!(json["data"]["userAgent"]
+        .as_str()
+        .unwrap()
+        .contains("Firefox"));
+    assert!(json["data"].get("auth").is_none());
+
+    // Registering the endpoint again keeps one subscription
+    let (status, json) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/push/subscriptions",
+        &owner,
+        Some(subscription.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["data"]["id"], subscription_id.as_str());
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/notifications/push/subscriptions",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    // Other users cannot remove it
+    let uri = format!("/api/notifications/push/subscriptions/{subscription_id}");
+    let (status, _) = send(&app, Method::DELETE, &uri, &other, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, Method::DELETE, &uri, &owner, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, json) = send(
+        &app,
+        Method::GET,
+        "/api/notifications/push/subscriptions",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(json["data"].as_array().unwrap().len(), 0);
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/api/notifications/test-push",
+        &owner,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_db.cleanup().await;
+}
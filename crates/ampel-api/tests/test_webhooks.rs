@@ -0,0 +1,236 @@
+//! Integration tests for inbound provider webhooks: secret management and
+//! signed delivery handling. Postgres-gated (early-return on SQLite).
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::Utc;
+use common::{create_test_app, TestDb};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use ampel_db::entities::{repository, user};
+use ampel_db::queries::PrQueries;
+use ampel_providers::webhook::sign_hmac_sha256;
+
+async fn register_and_login(app: &axum::Router) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/auth/register")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({
+                "email": "webhooks@example.com",
+                "password": "SecurePassword123!",
+                "displayName": "Webhook User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    json["data"]["accessToken"].as_str().unwrap().to_string()
+}
+
+async fn parse_json(response: axum::response::Response) -> Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn seed_repository(conn: &DatabaseConnection) -> Uuid {
+    let user_id = user::Entity::find()
+        .one(conn)
+        .await
+        .unwrap()
+        .expect("a registered user")
+        .id;
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("p-{id}")),
+        owner: Set("octocat".to_string()),
+        name: Set("repo".to_string()),
+        full_name: Set("octocat/repo".to_string()),
+        description: Set(None),
+        url: Set("https://example.com/octocat/repo".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        poll_interval_seconds: Set(300),
+        last_polled_at: Set(None),
+        group_id: Set(None),
+        provider_account_id: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+}
+
+fn pull_request_payload() -> String {
+    json!({
+        "action": "opened",
+        "pull_request": {
+            "id": 4242, "number": 7, "title": "Bump serde", "body": null,
+            "html_url": "https://github.com/octocat/repo/pull/7", "state": "open",
+            "draft": false, "mergeable": null, "mergeable_state": null,
+            "head": {"ref": "deps/serde"}, "base": {"ref": "main"},
+            "user": {"login": "dependabot[bot]", "avatar_url": null},
+            "created_at": "2026-01-01T00:00:00Z", "updated_at": "2026-01-01T00:00:00Z",
+            "merged_at": null, "closed_at": null
+        }
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_webhook_management_requires_auth() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/repositories/{}/webhook", Uuid::new_v4()))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_delivery_without_webhook_is_not_found() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/webhooks/github/{}", Uuid::new_v4()))
+        .header("x-github-event", "ping")
+        .body(Body::from("{}"))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_signed_github_delivery_upserts_pull_request() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let app = create_test_app(test_db.connection().clone()).await;
+    let token = register_and_login(&app).await;
+    let repo_id = seed_repository(test_db.connection()).await;
+
+    // Create the secret
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/repositories/{}/webhook", repo_id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = parse_json(response).await;
+    let secret = json["data"]["secret"].as_str().unwrap().to_string();
+    let path = json["data"]["deliveryPath"].as_str().unwrap().to_string();
+    assert_eq!(path, format!("/api/webhooks/github/{}", repo_id));
+
+    let payload = pull_request_payload();
+
+    // A delivery signed with the wrong secret is rejected
+    let request = Request::builder()
+        .method("POST")
+        .uri(&path)
+        .header("x-github-event", "pull_request")
+        .header(
+            "x-hub-signature-256",
+            sign_hmac_sha256("wrong", payload.as_bytes()),
+        )
+        .body(Body::from(payload.clone()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(PrQueries::find_by_number(test_db.connection(), repo_id, 7)
+        .await
+        .unwrap()
+        .is_none());
+
+    // A correctly signed delivery is applied
+    let request = Request::builder()
+        .method("POST")
+        .uri(&path)
+        .header("x-github-event", "pull_request")
+        .header(
+            "x-hub-signature-256",
+            sign_hmac_sha256(&secret, payload.as_bytes()),
+        )
+        .body(Body::from(payload))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = parse_json(response).await;
+    assert_eq!(json["data"]["applied"], true);
+
+    let pr = PrQueries::find_by_number(test_db.connection(), repo_id, 7)
+        .await
+        .unwrap()
+        .expect("PR row created from webhook");
+    assert_eq!(pr.title, "Bump serde");
+    assert_eq!(pr.author, "dependabot[bot]");
+
+    // The status endpoint reports the delivery without leaking the secret
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!("/api/repositories/{}/webhook", repo_id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let json = parse_json(response).await;
+    assert_eq!(json["data"]["configured"], true);
+    assert_eq!(json["data"]["lastEvent"], "pull_request");
+    assert!(json["data"].get("secret").is_none());
+
+    test_db.cleanup().await;
+}
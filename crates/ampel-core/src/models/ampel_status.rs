@@ -1,6 +1,12 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use super::{CICheck, CICheckConclusion, CICheckStatus, PullRequest, Review, ReviewState};
+use super::{
+    CICheck, CICheckConclusion, CICheckStatus, CheckTreatment, PullRequest, Review, ReviewState,
+    StatusEvaluation, StatusPolicy, StatusReason,
+};
+use crate::services::RepoService;
 
 /// Traffic light status for a PR or repository
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
@@ -12,7 +18,7 @@ pub enum AmpelStatus {
     Yellow,
     /// Checks failed OR conflicts OR blocked
     Red,
-    /// No open PRs
+    /// No open PRs, or a PR the status policy shows grey
     None,
 }
 
@@ -27,108 +33,218 @@ impl std::fmt::Display for AmpelStatus {
     }
 }
 
+impl std::str::FromStr for AmpelStatus {
+    type Err = crate::errors::AmpelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "green" => Ok(AmpelStatus::Green),
+            "yellow" => Ok(AmpelStatus::Yellow),
+            "red" => Ok(AmpelStatus::Red),
+            "none" => Ok(AmpelStatus::None),
+            other => Err(crate::errors::AmpelError::ValidationError(format!(
+                "unknown ampel status: {other}"
+            ))),
+        }
+    }
+}
+
 impl AmpelStatus {
-    /// Calculate status for a single PR based on CI checks and reviews
+    /// Calculate status for a single PR based on CI checks and reviews, using
+    /// the built-in rules
     pub fn for_pull_request(pr: &PullRequest, ci_checks: &[CICheck], reviews: &[Review]) -> Self {
-        // Draft PRs are always yellow
+        Self::evaluate(pr, ci_checks, reviews, &StatusPolicy::default()).status
+    }
+
+    /// Evaluate a PR against a status policy. Every reason the PR is not green
+    /// is collected and the worst status among them wins.
+    pub fn evaluate(
+        pr: &PullRequest,
+        ci_checks: &[CICheck],
+        reviews: &[Review],
+        policy: &StatusPolicy,
+    ) -> StatusEvaluation {
+        // Drafts take the policy's draft status whatever else is going on
         if pr.is_draft {
-            return AmpelStatus::Yellow;
+            return StatusEvaluation {
+                status: policy.draft_status,
+                reasons: vec![StatusReason::Draft],
+            };
         }
 
-        // Conflicts or explicitly not mergeable = red
-        if pr.has_conflicts {
-            return AmpelStatus::Red;
-        }
+        let mut reasons = Vec::new();
 
-        if let Some(false) = pr.is_mergeable {
-            return AmpelStatus::Red;
+        if pr.has_conflicts {
+            reasons.push(StatusReason::Conflicts);
+        } else if let Some(false) = pr.is_mergeable {
+            reasons.push(StatusReason::NotMergeable);
         }
 
-        // Check CI status
-        let ci_status = Self::evaluate_ci_checks(ci_checks);
+        Self::evaluate_ci_checks(ci_checks, policy, &mut reasons);
+        Self::evaluate_reviews(pr, reviews, policy, &mut reasons);
 
-        // Check review status
-        let review_status = Self::evaluate_reviews(reviews);
+        // Worst status wins
+        let statuses: Vec<AmpelStatus> = reasons.iter().map(|r| r.status(policy)).collect();
+        let status = if statuses.contains(&AmpelStatus::Red) {
+            AmpelStatus::Red
+        } else if statuses.contains(&AmpelStatus::Yellow) {
+            AmpelStatus::Yellow
+        } else {
+            AmpelStatus::Green
+        };
 
-        // Combine statuses - worst status wins
-        match (ci_status, review_status) {
-            (AmpelStatus::Red, _) | (_, AmpelStatus::Red) => AmpelStatus::Red,
-            (AmpelStatus::Yellow, _) | (_, AmpelStatus::Yellow) => AmpelStatus::Yellow,
-            (AmpelStatus::Green, AmpelStatus::Green) => AmpelStatus::Green,
-            (AmpelStatus::None, status) | (status, AmpelStatus::None) => status,
-        }
+        StatusEvaluation { status, reasons }
     }
 
-    /// Evaluate CI checks to determine status
-    fn evaluate_ci_checks(checks: &[CICheck]) -> AmpelStatus {
-        if checks.is_empty() {
-            return AmpelStatus::None;
-        }
-
-        let mut has_pending = false;
-        let mut has_failure = false;
-
-        for check in checks {
-            match check.status {
-                CICheckStatus::Queued | CICheckStatus::InProgress => {
-                    has_pending = true;
-                }
-                CICheckStatus::Completed => {
-                    if let Some(conclusion) = &check.conclusion {
-                        match conclusion {
-                            CICheckConclusion::Failure
-                            | CICheckConclusion::TimedOut
-                            | CICheckConclusion::ActionRequired => {
-                                has_failure = true;
-                            }
-                            CICheckConclusion::Cancelled => {
-                                has_pending = true;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
+    /// Evaluate CI checks, leaving out ignored ones. No checks at all is not a
+    /// reason by itself unless the policy requires some.
+    fn evaluate_ci_checks(
+        checks: &[CICheck],
+        policy: &StatusPolicy,
+        reasons: &mut Vec<StatusReason>,
+    ) {
+        let matches_any = |name: &str, patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| RepoService::matches_pattern(name, pattern))
+        };
+        let checks: Vec<&CICheck> = checks
+            .iter()
+            .filter(|check| !matches_any(&check.name, &policy.ignored_checks))
+            .collect();
+
+        let mut failed = Vec::new();
+        let mut pending = Vec::new();
+
+        for check in &checks {
+            let treatment = match check.status {
+                CICheckStatus::Queued | CICheckStatus::InProgress => CheckTreatment::Pending,
+                CICheckStatus::Completed => match check.conclusion {
+                    Some(
+                        CICheckConclusion::Failure
+                        | CICheckConclusion::TimedOut
+                        | CICheckConclusion::ActionRequired,
+                    ) => CheckTreatment::Fail,
+                    Some(CICheckConclusion::Cancelled) => policy.cancelled_checks,
+                    Some(CICheckConclusion::Skipped) => policy.skipped_checks,
+                    _ => CheckTreatment::Pass,
+                },
+            };
+            match treatment {
+                CheckTreatment::Fail => failed.push(check.name.clone()),
+                CheckTreatment::Pending => pending.push(check.name.clone()),
+                CheckTreatment::Pass => {}
             }
         }
 
-        if has_failure {
-            AmpelStatus::Red
-        } else if has_pending {
-            AmpelStatus::Yellow
-        } else {
-            AmpelStatus::Green
+        let missing: Vec<String> = policy
+            .required_checks
+            .iter()
+            .filter(|pattern| {
+                !checks
+                    .iter()
+                    .any(|check| RepoService::matches_pattern(&check.name, pattern))
+            })
+            .cloned()
+            .collect();
+
+        if !failed.is_empty() {
+            reasons.push(StatusReason::ChecksFailed { checks: failed });
+        }
+        if !pending.is_empty() {
+            reasons.push(StatusReason::ChecksPending { checks: pending });
+        }
+        if !missing.is_empty() {
+            reasons.push(StatusReason::RequiredChecksMissing { checks: missing });
         }
     }
 
-    /// Evaluate reviews to determine status
-    fn evaluate_reviews(reviews: &[Review]) -> AmpelStatus {
-        if reviews.is_empty() {
-            return AmpelStatus::Yellow; // Awaiting review
+    /// Evaluate reviews. Each reviewer's latest approval, change request or
+    /// dismissal decides where they stand; comments do not change it.
+    fn evaluate_reviews(
+        pr: &PullRequest,
+        reviews: &[Review],
+        policy: &StatusPolicy,
+        reasons: &mut Vec<StatusReason>,
+    ) {
+        let mut latest: HashMap<&str, &Review> = HashMap::new();
+        for review in reviews.iter().filter(|r| {
+            matches!(
+                r.state,
+                ReviewState::Approved | ReviewState::ChangesRequested | ReviewState::Dismissed
+            )
+        }) {
+            latest
+                .entry(review.reviewer.as_str())
+                .and_modify(|current| {
+                    if review.submitted_at > current.submitted_at {
+                        *current = review;
+                    }
+                })
+                .or_insert(review);
         }
-
-        let mut has_approval = false;
-        let mut has_changes_requested = false;
-
-        for review in reviews {
-            match review.state {
-                ReviewState::Approved => has_approval = true,
-                ReviewState::ChangesRequested => has_changes_requested = true,
-                _ => {}
-            }
+        let mut decisive: Vec<&Review> = latest.into_values().collect();
+        decisive.sort_by(|a, b| a.reviewer.cmp(&b.reviewer));
+
+        // An approval is stale when it was given on another commit than the
+        // PR's head. Reviews the provider reports no commit for never are.
+        let is_stale = |review: &Review| {
+            policy.dismiss_stale_approvals
+                && matches!(
+                    (&review.commit_sha, &pr.head_sha),
+                    (Some(reviewed), Some(head)) if reviewed != head
+                )
+        };
+
+        let changes_requested: Vec<String> = decisive
+            .iter()
+            .filter(|r| r.state == ReviewState::ChangesRequested)
+            .map(|r| r.reviewer.clone())
+            .collect();
+        let (stale, approvals): (Vec<&Review>, Vec<&Review>) = decisive
+            .into_iter()
+            .filter(|r| r.state == ReviewState::Approved)
+            .partition(|r| is_stale(r));
+
+        if !changes_requested.is_empty() {
+            reasons.push(StatusReason::ChangesRequested {
+                reviewers: changes_requested,
+            });
         }
 
-        if has_changes_requested {
-            AmpelStatus::Red
-        } else if has_approval {
-            AmpelStatus::Green
-        } else {
-            AmpelStatus::Yellow
+        let approvals_missing = (approvals.len() as u32) < policy.min_approvals;
+        let approver_missing = policy.require_named_approver
+            && !approvals.iter().any(|review| {
+                policy
+                    .required_approvers
+                    .iter()
+                    .any(|owner| owner.eq_ignore_ascii_case(&review.reviewer))
+            });
+
+        // Stale approvals only matter when they leave the PR short
+        if (approvals_missing || approver_missing) && !stale.is_empty() {
+            reasons.push(StatusReason::StaleApprovals {
+                reviewers: stale.iter().map(|r| r.reviewer.clone()).collect(),
+            });
+        }
+        if approvals_missing {
+            reasons.push(StatusReason::ApprovalsMissing {
+                approvals: approvals.len() as u32,
+                required: policy.min_approvals,
+            });
+        }
+        if approver_missing {
+            reasons.push(StatusReason::RequiredApproverMissing);
         }
     }
 
-    /// Calculate aggregate status for a repository based on its PRs
+    /// Calculate aggregate status for a repository based on its PRs. Grey
+    /// PRs, such as drafts under a policy that shows them grey, do not count.
     pub fn for_repository(pr_statuses: &[AmpelStatus]) -> Self {
-        if pr_statuses.is_empty() {
+        if pr_statuses
+            .iter()
+            .all(|status| *status == AmpelStatus::None)
+        {
             return AmpelStatus::None;
         }
 
@@ -173,6 +289,8 @@ mod tests {
             state: crate::models::PullRequestState::Open,
             source_branch: "feature".to_string(),
             target_branch: "main".to_string(),
+            head_sha: Some("head".to_string()),
+            milestone: None,
             author: "testuser".to_string(),
             author_avatar_url: None,
             is_draft: false,
@@ -211,6 +329,7 @@ mod tests {
             reviewer: "reviewer".to_string(),
             reviewer_avatar_url: None,
             state: ReviewState::Approved,
+            commit_sha: None,
             body: None,
             submitted_at: Utc::now(),
         }];
@@ -241,6 +360,7 @@ mod tests {
             reviewer: "reviewer".to_string(),
             reviewer_avatar_url: None,
             state: ReviewState::Approved,
+            commit_sha: None,
             body: None,
             submitted_at: Utc::now(),
         }];
@@ -294,4 +414,190 @@ mod tests {
             AmpelStatus::Yellow
         );
     }
+
+    fn check(name: &str, conclusion: Option<CICheckConclusion>) -> CICheck {
+        CICheck {
+            id: Uuid::new_v4(),
+            pull_request_id: Uuid::nil(),
+            name: name.to_string(),
+            status: if conclusion.is_some() {
+                CICheckStatus::Completed
+            } else {
+                CICheckStatus::InProgress
+            },
+            conclusion,
+            url: None,
+            started_at: None,
+            completed_at: None,
+            duration_seconds: None,
+        }
+    }
+
+    fn review(reviewer: &str, state: ReviewState, commit_sha: &str, minutes_ago: i64) -> Review {
+        Review {
+            id: Uuid::new_v4(),
+            pull_request_id: Uuid::nil(),
+            reviewer: reviewer.to_string(),
+            reviewer_avatar_url: None,
+            state,
+            commit_sha: Some(commit_sha.to_string()),
+            body: None,
+            submitted_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn test_default_policy_explains_each_reason() {
+        let mut pr = create_test_pr();
+        pr.has_conflicts = true;
+        let checks = vec![
+            check("build", Some(CICheckConclusion::Failure)),
+            check("lint", None),
+        ];
+        let reviews = vec![review("alice", ReviewState::ChangesRequested, "head", 5)];
+
+        let evaluation = AmpelStatus::evaluate(&pr, &checks, &reviews, &StatusPolicy::default());
+
+        assert_eq!(evaluation.status, AmpelStatus::Red);
+        assert_eq!(
+            evaluation.reasons,
+            vec![
+                StatusReason::Conflicts,
+                StatusReason::ChecksFailed {
+                    checks: vec!["build".to_string()]
+                },
+                StatusReason::ChecksPending {
+                    checks: vec!["lint".to_string()]
+                },
+                StatusReason::ChangesRequested {
+                    reviewers: vec!["alice".to_string()]
+                },
+                StatusReason::ApprovalsMissing {
+                    approvals: 0,
+                    required: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latest_decisive_review_per_reviewer_counts() {
+        let pr = create_test_pr();
+        let reviews = vec![
+            review("alice", ReviewState::ChangesRequested, "head", 30),
+            review("alice", ReviewState::Approved, "head", 10),
+            review("alice", ReviewState::Commented, "head", 5),
+        ];
+
+        let evaluation = AmpelStatus::evaluate(&pr, &[], &reviews, &StatusPolicy::default());
+
+        assert_eq!(evaluation.status, AmpelStatus::Green);
+        assert!(evaluation.reasons.is_empty());
+    }
+
+    #[test]
+    fn test_policy_checks() {
+        let pr = create_test_pr();
+        let reviews = vec![review("alice", ReviewState::Approved, "head", 5)];
+        let checks = vec![
+            check("build", Some(CICheckConclusion::Success)),
+            check("codecov/patch", Some(CICheckConclusion::Failure)),
+            check("e2e", Some(CICheckConclusion::Skipped)),
+        ];
+        let policy = StatusPolicy {
+            required_checks: vec!["build".to_string(), "deploy-*".to_string()],
+            ignored_checks: vec!["codecov/*".to_string()],
+            skipped_checks: CheckTreatment::Fail,
+            ..StatusPolicy::default()
+        };
+
+        let evaluation = AmpelStatus::evaluate(&pr, &checks, &reviews, &policy);
+
+        assert_eq!(evaluation.status, AmpelStatus::Red);
+        assert_eq!(
+            evaluation.reasons,
+            vec![
+                StatusReason::ChecksFailed {
+                    checks: vec!["e2e".to_string()]
+                },
+                StatusReason::RequiredChecksMissing {
+                    checks: vec!["deploy-*".to_string()]
+                },
+            ]
+        );
+
+        // The built-in rules pass skipped checks and do not ignore coverage
+        assert_eq!(
+            AmpelStatus::evaluate(&pr, &checks, &reviews, &StatusPolicy::default()).reasons,
+            vec![StatusReason::ChecksFailed {
+                checks: vec!["codecov/patch".to_string()]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_policy_approvals() {
+        let pr = create_test_pr();
+        let reviews = vec![
+            review("alice", ReviewState::Approved, "head", 5),
+            review("bob", ReviewState::Approved, "old", 60),
+        ];
+        let policy = StatusPolicy {
+            min_approvals: 2,
+            dismiss_stale_approvals: true,
+            require_named_approver: true,
+            required_approvers: vec!["Bob".to_string()],
+            ..StatusPolicy::default()
+        };
+
+        let evaluation = AmpelStatus::evaluate(&pr, &[], &reviews, &policy);
+
+        assert_eq!(evaluation.status, AmpelStatus::Yellow);
+        assert_eq!(
+            evaluation.reasons,
+            vec![
+                StatusReason::StaleApprovals {
+                    reviewers: vec!["bob".to_string()]
+                },
+                StatusReason::ApprovalsMissing {
+                    approvals: 1,
+                    required: 2
+                },
+                StatusReason::RequiredApproverMissing,
+            ]
+        );
+
+        // Without stale-approval handling both approvals count
+        let policy = StatusPolicy {
+            dismiss_stale_approvals: false,
+            ..policy
+        };
+        assert_eq!(
+            AmpelStatus::evaluate(&pr, &[], &reviews, &policy).status,
+            AmpelStatus::Green
+        );
+    }
+
+    #[test]
+    fn test_grey_drafts_do_not_color_the_repository() {
+        let mut pr = create_test_pr();
+        pr.is_draft = true;
+        let policy = StatusPolicy {
+            draft_status: AmpelStatus::None,
+            ..StatusPolicy::default()
+        };
+
+        let evaluation = AmpelStatus::evaluate(&pr, &[], &[], &policy);
+
+        assert_eq!(evaluation.status, AmpelStatus::None);
+        assert_eq!(evaluation.reasons, vec![StatusReason::Draft]);
+        assert_eq!(
+            AmpelStatus::for_repository(&[AmpelStatus::None, AmpelStatus::Green]),
+            AmpelStatus::Green
+        );
+        assert_eq!(
+            AmpelStatus::for_repository(&[AmpelStatus::None]),
+            AmpelStatus::None
+        );
+    }
 }
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Prefix of every personal API token, which tells them apart from JWTs
+pub const API_TOKEN_PREFIX: &str = "ampel_pat_";
+
+/// What a personal API token may do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Read-only requests
+    Read,
+    /// Merging pull requests, individually or in bulk
+    Merge,
+    /// Managing and triggering remediation
+    Remediation,
+    /// Everything, including account and organization management
+    Admin,
+}
+
+impl ApiTokenScope {
+    /// Whether a token holding `scopes` may make a request that needs `self`
+    pub fn granted_by(self, scopes: &[ApiTokenScope]) -> bool {
+        scopes
+            .iter()
+            .any(|scope| *scope == self || *scope == Self::Admin)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiTokenRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Vec<ApiTokenScope>,
+    /// Days until the token expires; the token never expires when omitted
+    #[validate(range(min = 1, max = 365, message = "Expiry must be 1-365 days"))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// Leading characters of the token, to tell tokens apart
+    pub token_prefix: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A newly created token; the only time its value is returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedApiTokenResponse {
+    #[serde(flatten)]
+    pub token: ApiTokenResponse,
+    pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_grants_every_scope() {
+        let admin = [ApiTokenScope::Admin];
+        assert!(ApiTokenScope::Read.granted_by(&admin));
+        assert!(ApiTokenScope::Merge.granted_by(&admin));
+        assert!(ApiTokenScope::Remediation.granted_by(&admin));
+
+        let read_merge = [ApiTokenScope::Read, ApiTokenScope::Merge];
+        assert!(ApiTokenScope::Merge.granted_by(&read_merge));
+        assert!(!ApiTokenScope::Remediation.granted_by(&read_merge));
+        assert!(!ApiTokenScope::Admin.granted_by(&read_merge));
+    }
+}
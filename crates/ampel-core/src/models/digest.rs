@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AmpelError, AmpelResult};
+
+/// Notification log kind used for digest emails
+pub const DIGEST_NOTIFICATION_KIND: &str = "digest";
+
+/// How often a user receives a digest email
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    None,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    /// The most recent period that has fully ended by `now`, as a half-open
+    /// `[start, end)` range. Daily periods are UTC days; weekly periods start
+    /// on Monday at 00:00 UTC.
+    pub fn last_period(self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let today = Utc
+            .with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+            .single()?;
+        match self {
+            Self::None => None,
+            Self::Daily => Some((today - Duration::days(1), today)),
+            Self::Weekly => {
+                let end = today - Duration::days(now.weekday().num_days_from_monday() as i64);
+                Some((end - Duration::weeks(1), end))
+            }
+        }
+    }
+}
+
+impl fmt::Display for DigestFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DigestFrequency {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown digest frequency: {other}"
+            ))),
+        }
+    }
+}
+
+/// A pull request listed in a digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestPullRequest {
+    /// Repository full name, e.g. `owner/repo`
+    pub repository: String,
+    pub number: i32,
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a repository's health score moved over a digest period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthScoreChange {
+    pub repository: String,
+    pub previous: i32,
+    pub current: i32,
+}
+
+/// Summary of a user's repositories over one digest period, as queued in the
+/// notification log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Digest {
+    pub frequency: DigestFrequency,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Language the digest is rendered in
+    pub locale: String,
+    /// Open PRs that are green and waiting for the user to merge them
+    pub ready_to_merge: Vec<DigestPullRequest>,
+    /// Open PRs with no activity for a week or more
+    pub stale: Vec<DigestPullRequest>,
+    /// Open PRs that turned red during the period
+    pub newly_red: Vec<DigestPullRequest>,
+    /// PRs merged during the period
+    pub merged_count: u64,
+    pub health_changes: Vec<HealthScoreChange>,
+}
+
+impl Digest {
+    /// Whether the digest has nothing worth sending
+    pub fn is_empty(&self) -> bool {
+        self.ready_to_merge.is_empty()
+            && self.stale.is_empty()
+            && self.newly_red.is_empty()
+            && self.merged_count == 0
+            && self.health_changes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_period_covers_previous_day_and_week() {
+        // A Wednesday afternoon
+        let now = Utc.with_ymd_and_hms(2026, 7, 8, 15, 30, 0).unwrap();
+
+        let (start, end) = DigestFrequency::Daily.last_period(now).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 7, 7, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2026, 7, 8, 0, 0, 0).unwrap());
+
+        let (start, end) = DigestFrequency::Weekly.last_period(now).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 6, 29, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2026, 7, 6, 0, 0, 0).unwrap());
+
+        assert_eq!(DigestFrequency::None.last_period(now), None);
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::AmpelStatus;
+
+/// A change to a user's dashboard, pushed to their open dashboards as soon as
+/// it is persisted
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveEvent {
+    /// The user whose dashboard changed
+    pub user_id: Uuid,
+    pub event: DashboardEvent,
+}
+
+/// What changed on a dashboard
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum DashboardEvent {
+    /// A PR was seen for the first time
+    PrOpened {
+        pull_request_id: Uuid,
+        repository_id: Uuid,
+        number: i32,
+        title: String,
+        status: AmpelStatus,
+    },
+    /// An open PR's traffic light changed
+    PrStatusChanged {
+        pull_request_id: Uuid,
+        repository_id: Uuid,
+        number: i32,
+        previous: AmpelStatus,
+        status: AmpelStatus,
+    },
+    /// An open PR was closed or merged; `state` says which
+    PrClosed {
+        pull_request_id: Uuid,
+        repository_id: Uuid,
+        number: i32,
+        state: String,
+    },
+    /// A CI check on an open PR started, progressed or finished. The previous
+    /// status is absent for a check that was not reported before.
+    CiCheckChanged {
+        pull_request_id: Uuid,
+        repository_id: Uuid,
+        number: i32,
+        name: String,
+        previous_status: Option<String>,
+        previous_conclusion: Option<String>,
+        status: String,
+        conclusion: Option<String>,
+    },
+}
+
+impl DashboardEvent {
+    /// The event's `type`, also used as the SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PrOpened { .. } => "pr_opened",
+            Self::PrStatusChanged { .. } => "pr_status_changed",
+            Self::PrClosed { .. } => "pr_closed",
+            Self::CiCheckChanged { .. } => "ci_check_changed",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_tagged_with_their_name() {
+        let event = DashboardEvent::PrStatusChanged {
+            pull_request_id: Uuid::nil(),
+            repository_id: Uuid::nil(),
+            number: 7,
+            previous: AmpelStatus::Yellow,
+            status: AmpelStatus::Green,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], event.name());
+        assert_eq!(json["pullRequestId"], Uuid::nil().to_string());
+        assert_eq!(json["previous"], "yellow");
+        assert_eq!(
+            serde_json::from_value::<DashboardEvent>(json).unwrap(),
+            event
+        );
+    }
+}
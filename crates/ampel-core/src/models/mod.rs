@@ -1,9 +1,23 @@
 mod ampel_status;
+mod api_token;
+mod digest;
+mod live_event;
+mod notification;
+mod organization;
+mod pr_query;
 mod pull_request;
 mod repository;
+mod status_policy;
 mod user;
 
 pub use ampel_status::*;
+pub use api_token::*;
+pub use digest::*;
+pub use live_event::*;
+pub use notification::*;
+pub use organization::*;
+pub use pr_query::*;
 pub use pull_request::*;
 pub use repository::*;
+pub use status_policy::*;
 pub use user::*;
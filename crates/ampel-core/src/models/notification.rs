@@ -0,0 +1,196 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use super::AmpelStatus;
+use crate::errors::{AmpelError, AmpelResult};
+
+/// Delivery attempts before a notification is given up on
+pub const NOTIFICATION_MAX_ATTEMPTS: i32 = 5;
+
+/// Delay before the first retry; each further retry waits twice as long
+const NOTIFICATION_RETRY_BASE_SECS: i64 = 60;
+
+/// An event that a user can be notified of
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// The PR turned green
+    PrReady,
+    /// The PR turned red
+    PrFailed,
+    /// The user was asked to review the PR
+    ReviewRequested,
+    /// A bulk merge the user started finished with every PR merged or skipped
+    MergeSucceeded,
+    /// A bulk merge the user started finished with failures
+    MergeFailed,
+    /// A remediation run on one of the user's repositories merged its
+    /// consolidated PR
+    RemediationCompleted,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PrReady => "pr_ready",
+            Self::PrFailed => "pr_failed",
+            Self::ReviewRequested => "review_requested",
+            Self::MergeSucceeded => "merge_succeeded",
+            Self::MergeFailed => "merge_failed",
+            Self::RemediationCompleted => "remediation_completed",
+        }
+    }
+
+    /// The notification raised when a PR's status goes from `previous` to
+    /// `current`, if any
+    pub fn for_transition(previous: AmpelStatus, current: AmpelStatus) -> Option<Self> {
+        match current {
+            _ if previous == current => None,
+            AmpelStatus::Green => Some(Self::PrReady),
+            AmpelStatus::Red => Some(Self::PrFailed),
+            AmpelStatus::Yellow | AmpelStatus::None => None,
+        }
+    }
+}
+
+impl fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "pr_ready" => Ok(Self::PrReady),
+            "pr_failed" => Ok(Self::PrFailed),
+            "review_requested" => Ok(Self::ReviewRequested),
+            "merge_succeeded" => Ok(Self::MergeSucceeded),
+            "merge_failed" => Ok(Self::MergeFailed),
+            "remediation_completed" => Ok(Self::RemediationCompleted),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown notification kind: {other}"
+            ))),
+        }
+    }
+}
+
+/// Where a notification is delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryChannel {
+    Slack,
+    Email,
+    /// Every browser the user subscribed for push notifications
+    Push,
+}
+
+impl DeliveryChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Slack => "slack",
+            Self::Email => "email",
+            Self::Push => "push",
+        }
+    }
+}
+
+impl fmt::Display for DeliveryChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DeliveryChannel {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "slack" => Ok(Self::Slack),
+            "email" => Ok(Self::Email),
+            "push" => Ok(Self::Push),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown delivery channel: {other}"
+            ))),
+        }
+    }
+}
+
+/// What a PR notification says, as queued in the notification log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrNotification {
+    pub kind: NotificationKind,
+    /// Repository full name, e.g. `owner/repo`
+    pub repository: String,
+    pub pr_number: i32,
+    pub pr_title: String,
+    pub pr_url: String,
+    pub author: String,
+    /// The reviewer asked, for review requests
+    pub reviewer: Option<String>,
+}
+
+impl PrNotification {
+    /// One-line description of the event
+    pub fn headline(&self) -> String {
+        let event = match self.kind {
+            NotificationKind::PrReady => "Ready to merge",
+            NotificationKind::PrFailed => "Needs attention",
+            NotificationKind::ReviewRequested => "Review requested",
+            NotificationKind::MergeSucceeded => "Merged",
+            NotificationKind::MergeFailed => "Merge failed",
+            NotificationKind::RemediationCompleted => "Remediated",
+        };
+        format!(
+            "{event}: {} #{} {}",
+            self.repository, self.pr_number, self.pr_title
+        )
+    }
+}
+
+/// How long to wait before retrying a notification that has now failed
+/// `attempts` times, or `None` once it should be given up on
+pub fn notification_retry_delay(attempts: i32) -> Option<Duration> {
+    if attempts >= NOTIFICATION_MAX_ATTEMPTS {
+        return None;
+    }
+    let exponent = attempts.max(1) as u32 - 1;
+    Some(Duration::seconds(
+        NOTIFICATION_RETRY_BASE_SECS * 2_i64.pow(exponent),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_into_green_and_red_notify() {
+        use AmpelStatus::{Green, Red, Yellow};
+
+        assert_eq!(
+            NotificationKind::for_transition(Yellow, Green),
+            Some(NotificationKind::PrReady)
+        );
+        assert_eq!(
+            NotificationKind::for_transition(Green, Red),
+            Some(NotificationKind::PrFailed)
+        );
+        assert_eq!(NotificationKind::for_transition(Green, Green), None);
+        assert_eq!(NotificationKind::for_transition(Red, Yellow), None);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_until_exhausted() {
+        assert_eq!(notification_retry_delay(1), Some(Duration::minutes(1)));
+        assert_eq!(notification_retry_delay(2), Some(Duration::minutes(2)));
+        assert_eq!(notification_retry_delay(4), Some(Duration::minutes(8)));
+        assert_eq!(notification_retry_delay(NOTIFICATION_MAX_ATTEMPTS), None);
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AmpelError, AmpelResult};
+
+/// A user's role in an organization. Roles are ordered: each role can do
+/// everything the roles below it can.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgRole {
+    /// Sees the organization, its teams and its policies
+    Member,
+    /// Manages teams, members, invitations and remediation policies
+    Admin,
+    /// Also controls the air-gapped ceiling and deletes the organization
+    Owner,
+}
+
+impl OrgRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Member => "member",
+            Self::Admin => "admin",
+            Self::Owner => "owner",
+        }
+    }
+}
+
+impl fmt::Display for OrgRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OrgRole {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "member" => Ok(Self::Member),
+            "admin" => Ok(Self::Admin),
+            "owner" => Ok(Self::Owner),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown organization role: {other}"
+            ))),
+        }
+    }
+}
+
+/// A user's role in a team, ordered like [`OrgRole`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamRole {
+    /// Read-only access to the team's repositories and policies
+    Viewer,
+    Member,
+    /// Manages the team's members and remediation policies
+    Admin,
+}
+
+impl TeamRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Member => "member",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for TeamRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TeamRole {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "viewer" => Ok(Self::Viewer),
+            "member" => Ok(Self::Member),
+            "admin" => Ok(Self::Admin),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown team role: {other}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_order_roles_by_privilege() {
+        assert!(OrgRole::Owner > OrgRole::Admin);
+        assert!(OrgRole::Admin > OrgRole::Member);
+        assert!(TeamRole::Admin > TeamRole::Member);
+        assert!(TeamRole::Member > TeamRole::Viewer);
+    }
+
+    #[test]
+    fn should_round_trip_role_strings() {
+        for role in [OrgRole::Member, OrgRole::Admin, OrgRole::Owner] {
+            assert_eq!(role.as_str().parse::<OrgRole>().unwrap(), role);
+        }
+        for role in [TeamRole::Viewer, TeamRole::Member, TeamRole::Admin] {
+            assert_eq!(role.as_str().parse::<TeamRole>().unwrap(), role);
+        }
+        assert!("owner".parse::<TeamRole>().is_err());
+    }
+}
@@ -0,0 +1,560 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::{AmpelStatus, GitProvider, PullRequest, PullRequestMetadata};
+use crate::errors::AmpelError;
+use crate::services::RepoService;
+
+/// A PR filter written as a query, e.g.
+/// `status:red author:dependabot* label:security age:>7d repo:org/*`
+///
+/// Terms are separated by whitespace and must all match. A term is a
+/// `key:value` filter or a bare word looked up in the title. A comma
+/// separates alternatives (`status:red,yellow`), a leading `-` negates a term
+/// (`-label:wip`) and double quotes allow spaces (`label:"needs review"`).
+///
+/// `author`, `label`, `assignee`, `reviewer`, `milestone` and `repo` values
+/// are patterns as accepted by `RepoService::matches_pattern` and, like the
+/// title, match case-insensitively. `no:` takes `label`, `assignee`,
+/// `reviewer` or `milestone` and matches PRs without one. `@me` stands for
+/// the viewer in `author`, `assignee` and `reviewer` once the query is
+/// bound with [`PrQuery::for_viewer`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrQuery {
+    terms: Vec<PrQueryTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PrQueryTerm {
+    negated: bool,
+    filter: PrQueryFilter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PrQueryFilter {
+    Status(Vec<AmpelStatus>),
+    Author(Vec<String>),
+    Label(Vec<String>),
+    Assignee(Vec<String>),
+    Reviewer(Vec<String>),
+    Milestone(Vec<String>),
+    Missing(Vec<PrQueryField>),
+    Repo(Vec<String>),
+    Provider(Vec<GitProvider>),
+    Draft(bool),
+    Age(AgeComparison, Duration),
+    Text(String),
+}
+
+/// What `no:` can find missing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrQueryField {
+    Label,
+    Assignee,
+    Reviewer,
+    Milestone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AgeComparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+/// A PR as a query sees it
+pub struct PrQueryTarget<'a> {
+    pub pull_request: &'a PullRequest,
+    /// Full name of the PR's repository, e.g. `org/repo`
+    pub repository: &'a str,
+    pub status: AmpelStatus,
+    pub metadata: &'a PullRequestMetadata,
+}
+
+/// Stands for the viewer's logins in `author`, `assignee` and `reviewer`
+const VIEWER: &str = "@me";
+
+impl PrQuery {
+    /// Parse a query; an empty or blank query matches every PR
+    pub fn parse(input: &str) -> Result<Self, AmpelError> {
+        let terms = tokenize(input)?
+            .into_iter()
+            .map(|token| parse_term(&token))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+
+    /// Whether the query has no terms and so matches every PR
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Both queries' terms, which a PR must all match
+    pub fn and(mut self, other: PrQuery) -> Self {
+        self.terms.extend(other.terms);
+        self
+    }
+
+    /// Replace `@me` with the viewer's provider `logins`; with none, `@me`
+    /// matches nobody
+    pub fn for_viewer(mut self, logins: &[String]) -> Self {
+        for term in &mut self.terms {
+            if let PrQueryFilter::Author(patterns)
+            | PrQueryFilter::Assignee(patterns)
+            | PrQueryFilter::Reviewer(patterns) = &mut term.filter
+            {
+                if patterns.iter().any(|p| p == VIEWER) {
+                    patterns.retain(|p| p != VIEWER);
+                    patterns.extend(logins.iter().map(|login| login.to_lowercase()));
+                }
+            }
+        }
+        self
+    }
+
+    /// Whether the PR matches every term, with ages measured at `now`
+    pub fn matches(&self, target: &PrQueryTarget<'_>, now: DateTime<Utc>) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.filter.matches(target, now) != term.negated)
+    }
+
+    /// Whether a repository can hold matching PRs at all, judged by the
+    /// query's `repo` and `provider` terms
+    pub fn matches_repository(&self, full_name: &str, provider: GitProvider) -> bool {
+        self.terms.iter().all(|term| match &term.filter {
+            PrQueryFilter::Repo(patterns) => any_matches(patterns, full_name) != term.negated,
+            PrQueryFilter::Provider(providers) => providers.contains(&provider) != term.negated,
+            _ => true,
+        })
+    }
+}
+
+impl std::str::FromStr for PrQuery {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl PrQueryFilter {
+    fn matches(&self, target: &PrQueryTarget<'_>, now: DateTime<Utc>) -> bool {
+        let pr = target.pull_request;
+        match self {
+            Self::Status(statuses) => statuses.contains(&target.status),
+            Self::Author(patterns) => any_matches(patterns, &pr.author),
+            Self::Label(patterns) => any_of_matches(patterns, &target.metadata.labels),
+            Self::Assignee(patterns) => any_of_matches(patterns, &target.metadata.assignees),
+            Self::Reviewer(patterns) => {
+                any_of_matches(patterns, &target.metadata.requested_reviewers)
+            }
+            Self::Milestone(patterns) => pr
+                .milestone
+                .as_deref()
+                .is_some_and(|milestone| any_matches(patterns, milestone)),
+            Self::Missing(fields) => fields.iter().any(|field| match field {
+                PrQueryField::Label => target.metadata.labels.is_empty(),
+                PrQueryField::Assignee => target.metadata.assignees.is_empty(),
+                PrQueryField::Reviewer => target.metadata.requested_reviewers.is_empty(),
+                PrQueryField::Milestone => pr.milestone.is_none(),
+            }),
+            Self::Repo(patterns) => any_matches(patterns, target.repository),
+            Self::Provider(providers) => providers.contains(&pr.provider),
+            Self::Draft(draft) => pr.is_draft == *draft,
+            Self::Age(comparison, age) => {
+                let actual = now - pr.created_at;
+                match comparison {
+                    AgeComparison::Above => actual > *age,
+                    AgeComparison::AtLeast => actual >= *age,
+                    AgeComparison::Below => actual < *age,
+                    AgeComparison::AtMost => actual <= *age,
+                }
+            }
+            Self::Text(text) => pr.title.to_lowercase().contains(text),
+        }
+    }
+}
+
+/// Whether `value` matches any of the lowercase `patterns`, ignoring case
+fn any_matches(patterns: &[String], value: &str) -> bool {
+    let value = value.to_lowercase();
+    patterns
+        .iter()
+        .any(|pattern| RepoService::matches_pattern(&value, pattern))
+}
+
+/// Whether any of `values` matches any of the lowercase `patterns`
+fn any_of_matches(patterns: &[String], values: &[String]) -> bool {
+    values.iter().any(|value| any_matches(patterns, value))
+}
+
+fn invalid(message: String) -> AmpelError {
+    AmpelError::ValidationError(message)
+}
+
+/// Split a query at whitespace outside double quotes, dropping the quotes
+fn tokenize(input: &str) -> Result<Vec<String>, AmpelError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err(invalid("unterminated quote".to_string()));
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+fn parse_term(token: &str) -> Result<PrQueryTerm, AmpelError> {
+    let (negated, body) = match token.strip_prefix('-') {
+        Some(body) if !body.is_empty() => (true, body),
+        _ => (false, token),
+    };
+
+    let Some((key, value)) = body.split_once(':') else {
+        return Ok(PrQueryTerm {
+            negated,
+            filter: PrQueryFilter::Text(body.to_lowercase()),
+        });
+    };
+    if value.is_empty() {
+        return Err(invalid(format!("`{key}:` needs a value")));
+    }
+    let values = || -> Result<Vec<String>, AmpelError> {
+        value
+            .split(',')
+            .map(|v| match v.trim() {
+                "" => Err(invalid(format!("`{key}:` has an empty value"))),
+                v => Ok(v.to_lowercase()),
+            })
+            .collect()
+    };
+
+    let filter = match key.to_lowercase().as_str() {
+        "status" => PrQueryFilter::Status(
+            values()?
+                .iter()
+                .map(|v| v.parse::<AmpelStatus>())
+                .collect::<Result<_, _>>()?,
+        ),
+        "author" => PrQueryFilter::Author(values()?),
+        "label" => PrQueryFilter::Label(values()?),
+        "assignee" => PrQueryFilter::Assignee(values()?),
+        "reviewer" => PrQueryFilter::Reviewer(values()?),
+        "milestone" => PrQueryFilter::Milestone(values()?),
+        "no" => PrQueryFilter::Missing(
+            values()?
+                .iter()
+                .map(|v| match v.as_str() {
+                    "label" => Ok(PrQueryField::Label),
+                    "assignee" => Ok(PrQueryField::Assignee),
+                    "reviewer" => Ok(PrQueryField::Reviewer),
+                    "milestone" => Ok(PrQueryField::Milestone),
+                    other => Err(invalid(format!(
+                        "`no:` expects label, assignee, reviewer or milestone, got `{other}`"
+                    ))),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        "repo" => PrQueryFilter::Repo(values()?),
+        "provider" => PrQueryFilter::Provider(
+            values()?
+                .iter()
+                .map(|v| v.parse::<GitProvider>().map_err(invalid))
+                .collect::<Result<_, _>>()?,
+        ),
+        "draft" => match value.to_lowercase().as_str() {
+            "true" | "yes" => PrQueryFilter::Draft(true),
+            "false" | "no" => PrQueryFilter::Draft(false),
+            other => {
+                return Err(invalid(format!(
+                    "`draft:` expects true or false, got `{other}`"
+                )))
+            }
+        },
+        "age" => {
+            let (comparison, age) = parse_age(value)?;
+            PrQueryFilter::Age(comparison, age)
+        }
+        other => return Err(invalid(format!("unknown filter `{other}:`"))),
+    };
+
+    Ok(PrQueryTerm { negated, filter })
+}
+
+/// Parse an age such as `>7d`, `<=12h` or `>2w`
+fn parse_age(value: &str) -> Result<(AgeComparison, Duration), AmpelError> {
+    let (comparison, amount) = if let Some(rest) = value.strip_prefix(">=") {
+        (AgeComparison::AtLeast, rest)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (AgeComparison::AtMost, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (AgeComparison::Above, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (AgeComparison::Below, rest)
+    } else {
+        return Err(invalid(format!(
+            "`age:` expects a comparison such as >7d, got `{value}`"
+        )));
+    };
+
+    let bad_amount = || {
+        invalid(format!(
+            "`age:` expects an amount such as 7d, got `{amount}`"
+        ))
+    };
+    let (number, unit) = match amount.char_indices().last() {
+        Some((unit_at, _)) => amount.split_at(unit_at),
+        None => return Err(bad_amount()),
+    };
+    let number: i64 = number.parse().map_err(|_| bad_amount())?;
+    let age = match unit {
+        "m" => Duration::try_minutes(number),
+        "h" => Duration::try_hours(number),
+        "d" => Duration::try_days(number),
+        "w" => Duration::try_weeks(number),
+        _ => None,
+    };
+
+    Ok((comparison, age.ok_or_else(bad_amount)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PullRequestState;
+    use uuid::Uuid;
+
+    fn pr(author: &str, title: &str, days_old: i64) -> PullRequest {
+        let created_at = Utc::now() - Duration::days(days_old);
+        PullRequest {
+            id: Uuid::new_v4(),
+            repository_id: Uuid::new_v4(),
+            provider: GitProvider::GitHub,
+            provider_id: "1".to_string(),
+            number: 1,
+            title: title.to_string(),
+            description: None,
+            url: "https://github.com/org/app/pull/1".to_string(),
+            state: PullRequestState::Open,
+            source_branch: "feature".to_string(),
+            target_branch: "main".to_string(),
+            head_sha: None,
+            milestone: None,
+            author: author.to_string(),
+            author_avatar_url: None,
+            is_draft: false,
+            is_mergeable: Some(true),
+            has_conflicts: false,
+            additions: 1,
+            deletions: 0,
+            changed_files: 1,
+            commits_count: 1,
+            comments_count: 0,
+            created_at,
+            updated_at: created_at,
+            merged_at: None,
+            closed_at: None,
+            last_synced_at: created_at,
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn matches(query: &str, pr: &PullRequest, status: AmpelStatus, labels: &[&str]) -> bool {
+        let metadata = PullRequestMetadata {
+            labels: strings(labels),
+            ..Default::default()
+        };
+        matches_with(PrQuery::parse(query).unwrap(), pr, status, &metadata)
+    }
+
+    fn matches_with(
+        query: PrQuery,
+        pr: &PullRequest,
+        status: AmpelStatus,
+        metadata: &PullRequestMetadata,
+    ) -> bool {
+        query.matches(
+            &PrQueryTarget {
+                pull_request: pr,
+                repository: "Org/App",
+                status,
+                metadata,
+            },
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_example_query() {
+        let query = "status:red author:dependabot* label:security age:>7d repo:org/*";
+        let old = pr("dependabot[bot]", "Bump openssl", 10);
+        assert!(matches(query, &old, AmpelStatus::Red, &["Security"]));
+        assert!(!matches(query, &old, AmpelStatus::Yellow, &["security"]));
+        assert!(!matches(query, &old, AmpelStatus::Red, &[]));
+        assert!(!matches(
+            query,
+            &pr("dependabot[bot]", "Bump", 3),
+            AmpelStatus::Red,
+            &["security"]
+        ));
+        assert!(!matches(
+            query,
+            &pr("alice", "Bump", 10),
+            AmpelStatus::Red,
+            &["security"]
+        ));
+    }
+
+    #[test]
+    fn test_alternatives_negation_and_text() {
+        let pr = pr("alice", "Fix login redirect", 1);
+        assert!(matches("status:red,yellow", &pr, AmpelStatus::Yellow, &[]));
+        assert!(!matches(
+            "-status:red,yellow",
+            &pr,
+            AmpelStatus::Yellow,
+            &[]
+        ));
+        assert!(matches("-label:wip", &pr, AmpelStatus::Green, &["ready"]));
+        assert!(!matches("-label:wip", &pr, AmpelStatus::Green, &["WIP"]));
+        assert!(matches("LOGIN draft:false", &pr, AmpelStatus::Green, &[]));
+        assert!(matches(
+            "\"login redirect\" age:<2d",
+            &pr,
+            AmpelStatus::Green,
+            &[]
+        ));
+        assert!(!matches(
+            "label:\"needs review\"",
+            &pr,
+            AmpelStatus::Green,
+            &["needs"]
+        ));
+        assert!(matches("", &pr, AmpelStatus::Red, &[]));
+    }
+
+    #[test]
+    fn test_people_and_milestone() {
+        let mut pr = pr("alice", "Add search", 1);
+        pr.milestone = Some("v2.0".to_string());
+        let metadata = PullRequestMetadata {
+            labels: Vec::new(),
+            assignees: strings(&["Bob"]),
+            requested_reviewers: strings(&["carol", "dave"]),
+        };
+        let check = |query: &str| {
+            matches_with(
+                PrQuery::parse(query).unwrap(),
+                &pr,
+                AmpelStatus::Green,
+                &metadata,
+            )
+        };
+
+        assert!(check("assignee:bob reviewer:dave milestone:v2*"));
+        assert!(!check("assignee:carol"));
+        assert!(!check("milestone:v1*"));
+        assert!(check("no:label"));
+        assert!(!check("no:assignee,milestone"));
+        assert!(check("-no:reviewer"));
+
+        let mut bare = pr.clone();
+        bare.milestone = None;
+        assert!(matches(
+            "no:milestone no:assignee no:reviewer",
+            &bare,
+            AmpelStatus::Green,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn test_viewer() {
+        let pr = pr("alice", "Add search", 1);
+        let metadata = PullRequestMetadata {
+            requested_reviewers: strings(&["carol"]),
+            ..Default::default()
+        };
+        let query = || PrQuery::parse("reviewer:@me").unwrap();
+
+        assert!(matches_with(
+            query().for_viewer(&strings(&["Carol", "carol-gl"])),
+            &pr,
+            AmpelStatus::Green,
+            &metadata
+        ));
+        assert!(!matches_with(
+            query().for_viewer(&strings(&["alice"])),
+            &pr,
+            AmpelStatus::Green,
+            &metadata
+        ));
+        assert!(!matches_with(
+            query().for_viewer(&[]),
+            &pr,
+            AmpelStatus::Green,
+            &metadata
+        ));
+        assert!(matches_with(
+            PrQuery::parse("author:@me,bob")
+                .unwrap()
+                .for_viewer(&strings(&["alice"])),
+            &pr,
+            AmpelStatus::Green,
+            &metadata
+        ));
+    }
+
+    #[test]
+    fn test_matches_repository() {
+        let query = PrQuery::parse("repo:org/* -repo:*-archive provider:github").unwrap();
+        assert!(query.matches_repository("Org/App", GitProvider::GitHub));
+        assert!(!query.matches_repository("org/app-archive", GitProvider::GitHub));
+        assert!(!query.matches_repository("org/app", GitProvider::GitLab));
+        assert!(!query.matches_repository("other/app", GitProvider::GitHub));
+        assert!(PrQuery::parse("status:red")
+            .unwrap()
+            .matches_repository("any/repo", GitProvider::Bitbucket));
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        for query in [
+            "status:purple",
+            "colour:red",
+            "author:",
+            "label:a,,b",
+            "age:7d",
+            "age:>7y",
+            "age:>d",
+            "age:>99999999999999w",
+            "draft:maybe",
+            "provider:svn",
+            "no:author",
+            "label:\"open",
+        ] {
+            assert!(
+                matches!(PrQuery::parse(query), Err(AmpelError::ValidationError(_))),
+                "{query} should be rejected"
+            );
+        }
+    }
+}
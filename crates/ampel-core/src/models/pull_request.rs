@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{AmpelStatus, GitProvider};
+use super::{AmpelStatus, GitProvider, StatusExplanation};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,6 +18,10 @@ pub struct PullRequest {
     pub state: PullRequestState,
     pub source_branch: String,
     pub target_branch: String,
+    /// Commit at the tip of the source branch, when the provider reports it
+    pub head_sha: Option<String>,
+    /// Title of the PR's milestone
+    pub milestone: Option<String>,
     pub author: String,
     pub author_avatar_url: Option<String>,
     pub is_draft: bool,
@@ -59,12 +63,27 @@ pub struct PullRequestWithDetails {
     #[serde(flatten)]
     pub pull_request: PullRequest,
     pub status: AmpelStatus,
+    /// Why the PR is not green, in the order they were found
+    pub reasons: Vec<StatusExplanation>,
     pub ci_checks: Vec<CICheck>,
     pub reviews: Vec<Review>,
+    #[serde(flatten)]
+    pub metadata: PullRequestMetadata,
     pub repository_name: String,
     pub repository_owner: String,
 }
 
+/// Labels and people attached to a PR
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestMetadata {
+    pub labels: Vec<String>,
+    /// Logins of the users the PR is assigned to
+    pub assignees: Vec<String>,
+    /// Logins of reviewers whose review is requested and still outstanding
+    pub requested_reviewers: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CICheck {
@@ -107,6 +126,8 @@ pub struct Review {
     pub reviewer: String,
     pub reviewer_avatar_url: Option<String>,
     pub state: ReviewState,
+    /// Commit the review was given on, when the provider reports it
+    pub commit_sha: Option<String>,
     pub body: Option<String>,
     pub submitted_at: DateTime<Utc>,
 }
@@ -137,6 +158,10 @@ pub struct PullRequestFilter {
     pub per_page: Option<i32>,
     pub sort_by: Option<PullRequestSortField>,
     pub sort_order: Option<SortOrder>,
+    /// Filter query, e.g. `status:red repo:org/*`
+    pub q: Option<String>,
+    /// Saved view whose query applies
+    pub view: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use super::AmpelStatus;
+
+/// How a completed check with a given conclusion counts towards the status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckTreatment {
+    /// The check counts as passed
+    #[default]
+    Pass,
+    /// The check counts as still running
+    Pending,
+    /// The check counts as failed
+    Fail,
+}
+
+impl std::fmt::Display for CheckTreatment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckTreatment::Pass => write!(f, "pass"),
+            CheckTreatment::Pending => write!(f, "pending"),
+            CheckTreatment::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+impl std::str::FromStr for CheckTreatment {
+    type Err = crate::errors::AmpelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pass" => Ok(CheckTreatment::Pass),
+            "pending" => Ok(CheckTreatment::Pending),
+            "fail" => Ok(CheckTreatment::Fail),
+            other => Err(crate::errors::AmpelError::ValidationError(format!(
+                "unknown check treatment: {other}"
+            ))),
+        }
+    }
+}
+
+/// Rules that decide a PR's traffic light, configured per repository or as a
+/// team default. The default policy reproduces the built-in rules.
+///
+/// Check names in `required_checks` and `ignored_checks` are patterns as
+/// accepted by `RepoService::matches_pattern` (`*`, `prefix*`, `*suffix` or
+/// an exact name).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatusPolicy {
+    /// Checks that must report and pass before a PR can turn green
+    pub required_checks: Vec<String>,
+    /// Checks left out of the status entirely, e.g. flaky coverage reports
+    pub ignored_checks: Vec<String>,
+    /// Approvals needed before a PR can turn green
+    pub min_approvals: u32,
+    /// Whether one approval must come from `required_approvers`
+    pub require_named_approver: bool,
+    /// Usernames whose approval satisfies `require_named_approver`. The list
+    /// applies to the whole repository: it is not read from CODEOWNERS and
+    /// does not depend on the paths a PR touches.
+    pub required_approvers: Vec<String>,
+    /// Whether approvals given before the latest push stop counting
+    pub dismiss_stale_approvals: bool,
+    /// How checks concluding `skipped` count
+    pub skipped_checks: CheckTreatment,
+    /// How checks concluding `cancelled` count
+    pub cancelled_checks: CheckTreatment,
+    /// Status of draft PRs; `none` shows them grey
+    pub draft_status: AmpelStatus,
+}
+
+impl Default for StatusPolicy {
+    fn default() -> Self {
+        Self {
+            required_checks: Vec::new(),
+            ignored_checks: Vec::new(),
+            min_approvals: 1,
+            require_named_approver: false,
+            required_approvers: Vec::new(),
+            dismiss_stale_approvals: false,
+            skipped_checks: CheckTreatment::Pass,
+            cancelled_checks: CheckTreatment::Pending,
+            draft_status: AmpelStatus::Yellow,
+        }
+    }
+}
+
+/// Why a PR is not green
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "code",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum StatusReason {
+    /// The PR is a draft
+    Draft,
+    /// The PR has merge conflicts
+    Conflicts,
+    /// The provider reports the PR cannot be merged
+    NotMergeable,
+    /// Checks that failed, or count as failed under the policy
+    ChecksFailed { checks: Vec<String> },
+    /// Checks still running, or counting as running under the policy
+    ChecksPending { checks: Vec<String> },
+    /// Required checks that have not reported
+    RequiredChecksMissing { checks: Vec<String> },
+    /// Reviewers whose latest review requests changes
+    ChangesRequested { reviewers: Vec<String> },
+    /// Fewer current approvals than the policy requires
+    ApprovalsMissing { approvals: u32, required: u32 },
+    /// Reviewers whose approval predates the latest push
+    StaleApprovals { reviewers: Vec<String> },
+    /// None of the required approvers has approved
+    RequiredApproverMissing,
+}
+
+impl StatusReason {
+    /// The status this reason holds the PR at, at best
+    pub fn status(&self, policy: &StatusPolicy) -> AmpelStatus {
+        match self {
+            Self::Draft => policy.draft_status,
+            Self::Conflicts
+            | Self::NotMergeable
+            | Self::ChecksFailed { .. }
+            | Self::ChangesRequested { .. } => AmpelStatus::Red,
+            Self::ChecksPending { .. }
+            | Self::RequiredChecksMissing { .. }
+            | Self::ApprovalsMissing { .. }
+            | Self::StaleApprovals { .. }
+            | Self::RequiredApproverMissing => AmpelStatus::Yellow,
+        }
+    }
+}
+
+/// A reason a PR is not green, as returned with the PR
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusExplanation {
+    #[serde(flatten)]
+    pub reason: StatusReason,
+    /// The reason in the reader's language; filled in by the API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl From<StatusReason> for StatusExplanation {
+    fn from(reason: StatusReason) -> Self {
+        Self {
+            reason,
+            message: None,
+        }
+    }
+}
+
+/// A PR's status together with the reasons it is not green
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusEvaluation {
+    pub status: AmpelStatus,
+    pub reasons: Vec<StatusReason>,
+}
+
+/// Where a repository's effective status policy comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPolicySource {
+    /// The repository's own policy
+    Repository,
+    /// The default of a team the repository is shared with
+    Team,
+    /// No policy is configured; the built-in rules apply
+    Default,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explanation_flattens_reason() {
+        let mut explanation = StatusExplanation::from(StatusReason::ApprovalsMissing {
+            approvals: 0,
+            required: 2,
+        });
+        assert_eq!(
+            serde_json::to_value(&explanation).unwrap(),
+            serde_json::json!({ "code": "approvals_missing", "approvals": 0, "required": 2 })
+        );
+
+        explanation.message = Some("0 of 2 required approvals".to_string());
+        let json = serde_json::to_value(&explanation).unwrap();
+        assert_eq!(json["message"], "0 of 2 required approvals");
+        assert_eq!(
+            serde_json::from_value::<StatusExplanation>(json).unwrap(),
+            explanation
+        );
+    }
+}
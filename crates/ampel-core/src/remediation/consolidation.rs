@@ -24,10 +24,19 @@ pub enum MergeDisposition {
     Consolidated,
     /// The PR was closed; the consolidating PR number is recorded for traceability.
     ClosedWithRef { consolidated_pr_number: u64 },
-    /// Skipped because of an unresolved merge conflict.
-    SkippedConflict { reason: String },
+    /// Skipped because of an unresolved merge conflict in `files`. Rows
+    /// recorded before the file list existed deserialize with it empty.
+    SkippedConflict {
+        reason: String,
+        #[serde(default)]
+        files: Vec<String>,
+    },
     /// No action taken; records why (e.g. `"draft"`, `"excluded by label"`).
     LeftOpen { reason: String },
+    /// Merged outside the run (e.g. by a human) before it was superseded.
+    MergedExternally,
+    /// Closed outside the run, without its "Superseded by" reference.
+    ClosedExternally,
 }
 
 impl MergeDisposition {
@@ -39,7 +48,7 @@ impl MergeDisposition {
     /// The reason text, when the variant carries one.
     pub fn reason(&self) -> Option<&str> {
         match self {
-            Self::SkippedConflict { reason } | Self::LeftOpen { reason } => Some(reason),
+            Self::SkippedConflict { reason, .. } | Self::LeftOpen { reason } => Some(reason),
             _ => None,
         }
     }
@@ -151,6 +160,7 @@ mod tests {
             },
             MergeDisposition::SkippedConflict {
                 reason: "lockfile".into(),
+                files: vec!["Cargo.lock".into()],
             },
             MergeDisposition::LeftOpen {
                 reason: "draft".into(),
@@ -161,6 +171,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_read_skipped_conflict_recorded_without_files() {
+        let json = r#"{"disposition":"skipped_conflict","reason":"lockfile"}"#;
+        assert_eq!(
+            serde_json::from_str::<MergeDisposition>(json).unwrap(),
+            MergeDisposition::SkippedConflict {
+                reason: "lockfile".into(),
+                files: Vec::new(),
+            }
+        );
+    }
+
     #[test]
     fn should_tag_merge_disposition_with_snake_case_key() {
         let json = serde_json::to_string(&MergeDisposition::Consolidated).unwrap();
@@ -174,7 +196,11 @@ mod tests {
             consolidated_pr_number: 1
         }
         .is_terminal());
-        assert!(!MergeDisposition::SkippedConflict { reason: "x".into() }.is_terminal());
+        assert!(!MergeDisposition::SkippedConflict {
+            reason: "x".into(),
+            files: Vec::new(),
+        }
+        .is_terminal());
         assert!(!MergeDisposition::LeftOpen { reason: "x".into() }.is_terminal());
     }
 
@@ -182,7 +208,8 @@ mod tests {
     fn should_expose_reason_only_for_reasoned_dispositions() {
         assert_eq!(
             MergeDisposition::SkippedConflict {
-                reason: "conflict".into()
+                reason: "conflict".into(),
+                files: Vec::new(),
             }
             .reason(),
             Some("conflict")
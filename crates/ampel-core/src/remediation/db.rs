@@ -28,6 +28,7 @@ pub mod remediation_policy {
         pub pr_selection: String,
         pub autonomy_level: String,
         pub remediation_tier: String,
+        pub consolidation_strategy: String,
         pub max_prs_per_run: i32,
         pub allowed_targets: String,
         pub skip_draft: bool,
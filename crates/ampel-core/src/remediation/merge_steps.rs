@@ -0,0 +1,542 @@
+//! The git step plan for a sandbox consolidation (ADR-005).
+//!
+//! Pure: these functions turn the selected PRs and the policy's
+//! [`ConsolidationStrategy`] into git argv, and decide how a conflicted step is
+//! settled. Executing them is the job of
+//! [`crate::services::combine_prs`] against a [`crate::services::GitWorkspace`],
+//! so the container runner and the in-process fake share one sequence.
+
+use super::{detect_lockfile_kind, ConsolidationStrategy, LockfileKind, MergeDisposition, PrRef};
+
+/// How many times one PR step may stop on conflicts before it is skipped. A
+/// rebase stops once per conflicting commit; a merge or squash stops once.
+pub const MAX_CONFLICT_ROUNDS: usize = 10;
+
+/// Manifests share a file name table with lockfiles for fingerprinting, but
+/// they carry the PR's intent, so a conflict in one is never auto-resolved.
+const MANIFESTS: &[&str] = &[
+    "go.mod",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "Gemfile",
+];
+
+/// A single git step in the consolidation sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitStep {
+    /// `git clone --depth <depth> <url> <dir>` then checkout the default branch.
+    Clone { depth: u32 },
+    /// `git checkout -b <branch>` for the deterministic consolidation branch.
+    CreateBranch { branch: String },
+    /// `git merge --no-ff origin/<source_branch>` for one source PR.
+    Merge {
+        pr_number: i32,
+        source_branch: String,
+    },
+    /// Replay the PR's own commits onto the consolidation branch, i.e. rebase
+    /// it onto the base plus the PRs before it. Merge commits on the PR branch
+    /// are left out.
+    Rebase {
+        pr_number: i32,
+        source_branch: String,
+    },
+    /// `git merge --squash origin/<source_branch>`, committed as one commit
+    /// titled after the PR.
+    Squash {
+        pr_number: i32,
+        source_branch: String,
+        title: String,
+    },
+}
+
+impl GitStep {
+    /// The source PR this step combines; `None` for setup steps.
+    pub fn pr_number(&self) -> Option<i32> {
+        match self {
+            GitStep::Merge { pr_number, .. }
+            | GitStep::Rebase { pr_number, .. }
+            | GitStep::Squash { pr_number, .. } => Some(*pr_number),
+            GitStep::Clone { .. } | GitStep::CreateBranch { .. } => None,
+        }
+    }
+
+    /// The git argv combining this PR into the consolidation branch. Setup
+    /// steps return `None`: the clone needs the credentialed URL, which never
+    /// appears in an argv.
+    pub fn apply_argv(&self, default_branch: &str) -> Option<Vec<String>> {
+        let argv: Vec<String> = match self {
+            GitStep::Merge { source_branch, .. } => argv(
+                &["git", "merge", "--no-ff", "--no-edit"],
+                &format!("origin/{source_branch}"),
+            ),
+            GitStep::Rebase { source_branch, .. } => argv(
+                &["git", "cherry-pick", "--no-merges"],
+                &format!("origin/{default_branch}..origin/{source_branch}"),
+            ),
+            GitStep::Squash { source_branch, .. } => argv(
+                &["git", "merge", "--squash"],
+                &format!("origin/{source_branch}"),
+            ),
+            GitStep::Clone { .. } | GitStep::CreateBranch { .. } => return None,
+        };
+        Some(argv)
+    }
+
+    /// The git argv that checks this PR's branch shares history with the base
+    /// before it is applied. Only a rebase needs it: its cherry-pick range
+    /// `origin/<base>..origin/<source>` silently takes in unrelated base
+    /// commits when a shallow clone stops short of the merge base, while a
+    /// merge or squash finds the base itself and refuses unrelated histories.
+    pub fn merge_base_argv(&self, default_branch: &str) -> Option<Vec<String>> {
+        match self {
+            GitStep::Rebase { source_branch, .. } => Some(vec![
+                "git".to_string(),
+                "merge-base".to_string(),
+                format!("origin/{default_branch}"),
+                format!("origin/{source_branch}"),
+            ]),
+            _ => None,
+        }
+    }
+
+    /// The git argv that records this PR once its conflicts are resolved;
+    /// `staged` says whether the index differs from `HEAD`.
+    ///
+    /// A squash always needs it, since `merge --squash` only stages. A resolution
+    /// can leave nothing staged: a replayed commit is then skipped (the
+    /// cherry-pick sequence moves on to the next one) and an empty squash has
+    /// nothing to record, so it returns `None`, as do setup steps.
+    pub fn conclude_argv(&self, staged: bool) -> Option<Vec<String>> {
+        let argv: Vec<String> = match self {
+            GitStep::Merge { .. } => argv(&["git", "commit"], "--no-edit"),
+            GitStep::Rebase { .. } if !staged => argv(&["git", "cherry-pick"], "--skip"),
+            GitStep::Rebase { .. } => argv(
+                &["git", "-c", "core.editor=true", "cherry-pick"],
+                "--continue",
+            ),
+            GitStep::Squash { .. } if !staged => return None,
+            GitStep::Squash {
+                pr_number, title, ..
+            } => argv(&["git", "commit", "-m"], &format!("{title} (#{pr_number})")),
+            GitStep::Clone { .. } | GitStep::CreateBranch { .. } => return None,
+        };
+        Some(argv)
+    }
+
+    /// The git argv that backs a conflicted PR out, leaving the consolidation
+    /// branch exactly as it was before this step.
+    pub fn abort_argv(&self) -> Option<Vec<String>> {
+        let argv: Vec<String> = match self {
+            GitStep::Merge { .. } => argv(&["git", "merge"], "--abort"),
+            GitStep::Rebase { .. } => argv(&["git", "cherry-pick"], "--abort"),
+            GitStep::Squash { .. } => argv(&["git", "reset", "--hard"], "HEAD"),
+            GitStep::Clone { .. } | GitStep::CreateBranch { .. } => return None,
+        };
+        Some(argv)
+    }
+}
+
+fn argv(fixed: &[&str], last: &str) -> Vec<String> {
+    fixed
+        .iter()
+        .map(|s| s.to_string())
+        .chain(std::iter::once(last.to_string()))
+        .collect()
+}
+
+/// Build the ordered git step sequence for a consolidation: clone, create the
+/// consolidation branch, then combine each PR in the given (oldest-first)
+/// order using `strategy`. Pure — produces the plan, executes nothing.
+pub fn build_merge_sequence(
+    branch: &str,
+    prs: &[PrRef],
+    clone_depth: u32,
+    strategy: ConsolidationStrategy,
+) -> Vec<GitStep> {
+    let mut steps = Vec::with_capacity(prs.len() + 2);
+    steps.push(GitStep::Clone { depth: clone_depth });
+    steps.push(GitStep::CreateBranch {
+        branch: branch.to_string(),
+    });
+    for pr in prs {
+        let pr_number = pr.number;
+        let source_branch = pr.branch.clone();
+        steps.push(match strategy {
+            ConsolidationStrategy::Merge => GitStep::Merge {
+                pr_number,
+                source_branch,
+            },
+            ConsolidationStrategy::RebaseOntoBase => GitStep::Rebase {
+                pr_number,
+                source_branch,
+            },
+            ConsolidationStrategy::SquashPerPr => GitStep::Squash {
+                pr_number,
+                source_branch,
+                title: pr.title.clone(),
+            },
+        });
+    }
+    steps
+}
+
+/// The unmerged paths listed by `git diff --name-only --diff-filter=U`, in
+/// order and without duplicates.
+pub fn parse_conflicted_paths(output: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if !paths.iter().any(|p| p == line) {
+            paths.push(line.to_string());
+        }
+    }
+    paths
+}
+
+/// The lockfile kind of a conflicted `path`, if it is a generated lockfile that
+/// may be rebuilt. Manifests such as `go.mod` never qualify.
+pub fn regenerable_lockfile(path: &str) -> Option<LockfileKind> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    if MANIFESTS.contains(&name) {
+        return None;
+    }
+    detect_lockfile_kind(path)
+}
+
+/// How to settle a PR step that stopped on conflicts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Only lockfiles conflicted: run `commands` in order, then conclude the
+    /// step with [`GitStep::conclude_argv`]. Each entry is `(dir, argv)`, `dir`
+    /// relative to the clone root (`""` for the root itself), so a nested
+    /// lockfile regenerates in its own project.
+    Resolve {
+        commands: Vec<(String, Vec<String>)>,
+    },
+    /// Something other than a lockfile conflicted: run `abort` and record
+    /// `disposition` for the PR; the run moves on to the next PR.
+    Skip {
+        abort: Vec<String>,
+        disposition: MergeDisposition,
+    },
+}
+
+/// Decide how to settle a conflicted PR `step` given its unmerged `conflicted`
+/// paths.
+///
+/// Lockfiles are generated, so their conflicts carry no intent worth keeping:
+/// the consolidation branch's side (`--ours`) is taken, then the lockfile is
+/// rebuilt with its [`super::regen_command_for`] command so it matches the
+/// merged manifests. Any other conflicting path — a manifest included — backs
+/// the PR out and skips it with a [`MergeDisposition::SkippedConflict`] listing
+/// every conflicted path.
+pub fn resolve_conflict(step: &GitStep, conflicted: &[String]) -> ConflictResolution {
+    let Some(abort) = step.abort_argv() else {
+        return ConflictResolution::Skip {
+            abort: Vec::new(),
+            disposition: skipped_conflict(conflicted),
+        };
+    };
+    let kinds: Option<Vec<LockfileKind>> =
+        conflicted.iter().map(|p| regenerable_lockfile(p)).collect();
+    let kinds = match kinds {
+        Some(kinds) if !kinds.is_empty() => kinds,
+        _ => {
+            return ConflictResolution::Skip {
+                abort,
+                disposition: skipped_conflict(conflicted),
+            }
+        }
+    };
+
+    let mut commands: Vec<(String, Vec<String>)> = Vec::new();
+    for path in conflicted {
+        commands.push((
+            String::new(),
+            argv(&["git", "checkout", "--ours", "--"], path),
+        ));
+    }
+    for (path, kind) in conflicted.iter().zip(kinds) {
+        let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+        let regen = (
+            dir,
+            kind.regen_command().iter().map(|s| s.to_string()).collect(),
+        );
+        // Two lockfiles of one project share one regeneration.
+        if !commands.contains(&regen) {
+            commands.push(regen);
+        }
+    }
+    let mut add = vec!["git".to_string(), "add".to_string(), "--".to_string()];
+    add.extend(conflicted.iter().cloned());
+    commands.push((String::new(), add));
+    ConflictResolution::Resolve { commands }
+}
+
+/// The disposition of a PR backed out over conflicts in `files`.
+pub fn skipped_conflict(files: &[String]) -> MergeDisposition {
+    MergeDisposition::SkippedConflict {
+        reason: format!("merge conflict in {}", files.join(", ")),
+        files: files.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(n: i32, branch: &str) -> PrRef {
+        PrRef {
+            number: n,
+            title: format!("PR {n}"),
+            branch: branch.to_string(),
+        }
+    }
+
+    fn strings(argv: &[&str]) -> Vec<String> {
+        argv.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn should_build_oldest_first_merge_sequence_with_clone_and_branch() {
+        // Arrange
+        let prs = [pr(1, "feature/a"), pr(2, "feature/b")];
+
+        // Act
+        let steps = build_merge_sequence(
+            "ampel/remediation/run",
+            &prs,
+            50,
+            ConsolidationStrategy::Merge,
+        );
+
+        // Assert: clone, create-branch, then one --no-ff merge per PR in order.
+        assert_eq!(
+            steps,
+            vec![
+                GitStep::Clone { depth: 50 },
+                GitStep::CreateBranch {
+                    branch: "ampel/remediation/run".to_string()
+                },
+                GitStep::Merge {
+                    pr_number: 1,
+                    source_branch: "feature/a".to_string()
+                },
+                GitStep::Merge {
+                    pr_number: 2,
+                    source_branch: "feature/b".to_string()
+                },
+            ]
+        );
+        assert_eq!(steps[3].pr_number(), Some(2));
+        assert_eq!(steps[0].pr_number(), None);
+    }
+
+    #[test]
+    fn should_build_rebase_and_squash_steps_per_strategy() {
+        // Arrange
+        let prs = [pr(1, "feature/a")];
+
+        // Act
+        let rebase = build_merge_sequence("b", &prs, 50, ConsolidationStrategy::RebaseOntoBase);
+        let squash = build_merge_sequence("b", &prs, 50, ConsolidationStrategy::SquashPerPr);
+
+        // Assert
+        assert_eq!(
+            rebase[2],
+            GitStep::Rebase {
+                pr_number: 1,
+                source_branch: "feature/a".to_string()
+            }
+        );
+        assert_eq!(
+            squash[2],
+            GitStep::Squash {
+                pr_number: 1,
+                source_branch: "feature/a".to_string(),
+                title: "PR 1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn should_render_apply_conclude_and_abort_argv_per_strategy() {
+        let rebase = GitStep::Rebase {
+            pr_number: 1,
+            source_branch: "feature/a".to_string(),
+        };
+        assert_eq!(
+            rebase.apply_argv("main").unwrap(),
+            [
+                "git",
+                "cherry-pick",
+                "--no-merges",
+                "origin/main..origin/feature/a"
+            ]
+        );
+        assert_eq!(
+            rebase.conclude_argv(true).unwrap(),
+            ["git", "-c", "core.editor=true", "cherry-pick", "--continue"]
+        );
+        assert_eq!(
+            rebase.conclude_argv(false).unwrap(),
+            ["git", "cherry-pick", "--skip"]
+        );
+        assert_eq!(
+            rebase.abort_argv().unwrap(),
+            ["git", "cherry-pick", "--abort"]
+        );
+
+        let squash = GitStep::Squash {
+            pr_number: 7,
+            source_branch: "feature/b".to_string(),
+            title: "Bump serde".to_string(),
+        };
+        assert_eq!(
+            squash.apply_argv("main").unwrap(),
+            ["git", "merge", "--squash", "origin/feature/b"]
+        );
+        assert_eq!(
+            squash.conclude_argv(true).unwrap(),
+            ["git", "commit", "-m", "Bump serde (#7)"]
+        );
+        assert_eq!(squash.conclude_argv(false), None);
+        assert_eq!(
+            squash.abort_argv().unwrap(),
+            ["git", "reset", "--hard", "HEAD"]
+        );
+
+        assert_eq!(GitStep::Clone { depth: 50 }.apply_argv("main"), None);
+    }
+
+    #[test]
+    fn should_parse_unmerged_paths_without_duplicates() {
+        assert_eq!(
+            parse_conflicted_paths("Cargo.lock\n\nweb/yarn.lock\nCargo.lock\n"),
+            vec!["Cargo.lock".to_string(), "web/yarn.lock".to_string()]
+        );
+        assert!(parse_conflicted_paths("").is_empty());
+    }
+
+    #[test]
+    fn should_only_regenerate_lockfiles_never_manifests() {
+        assert_eq!(
+            regenerable_lockfile("svc/go.sum"),
+            Some(LockfileKind::GoSum)
+        );
+        assert_eq!(
+            regenerable_lockfile("Cargo.lock"),
+            Some(LockfileKind::CargoLock)
+        );
+        assert_eq!(regenerable_lockfile("svc/go.mod"), None);
+        assert_eq!(regenerable_lockfile("Cargo.toml"), None);
+        assert_eq!(regenerable_lockfile("web/package.json"), None);
+    }
+
+    #[test]
+    fn should_resolve_lockfile_only_conflict_by_taking_ours_and_regenerating() {
+        // Arrange
+        let step = GitStep::Merge {
+            pr_number: 1,
+            source_branch: "feature/a".to_string(),
+        };
+        let conflicted = vec![
+            "Cargo.lock".to_string(),
+            "web/yarn.lock".to_string(),
+            "svc/go.sum".to_string(),
+        ];
+
+        // Act
+        let resolution = resolve_conflict(&step, &conflicted);
+
+        // Assert: take ours, regen each lockfile in its own dir, then stage.
+        let cmd = |dir: &str, argv: &[&str]| (dir.to_string(), strings(argv));
+        assert_eq!(
+            resolution,
+            ConflictResolution::Resolve {
+                commands: vec![
+                    cmd("", &["git", "checkout", "--ours", "--", "Cargo.lock"]),
+                    cmd("", &["git", "checkout", "--ours", "--", "web/yarn.lock"]),
+                    cmd("", &["git", "checkout", "--ours", "--", "svc/go.sum"]),
+                    cmd("", &["cargo", "generate-lockfile"]),
+                    cmd("web", &["yarn", "install", "--mode", "update-lockfile"]),
+                    cmd("svc", &["go", "mod", "tidy"]),
+                    cmd(
+                        "",
+                        &[
+                            "git",
+                            "add",
+                            "--",
+                            "Cargo.lock",
+                            "web/yarn.lock",
+                            "svc/go.sum"
+                        ]
+                    ),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn should_regenerate_a_project_once_when_two_of_its_lockfiles_conflict() {
+        let step = GitStep::Merge {
+            pr_number: 1,
+            source_branch: "feature/a".to_string(),
+        };
+        let conflicted = vec!["a/Cargo.lock".to_string(), "b/Cargo.lock".to_string()];
+
+        let ConflictResolution::Resolve { commands } = resolve_conflict(&step, &conflicted) else {
+            panic!("lockfile-only conflict should resolve");
+        };
+
+        let regens: Vec<_> = commands.iter().filter(|(_, a)| a[0] == "cargo").collect();
+        assert_eq!(regens.len(), 2, "one regen per project dir");
+    }
+
+    #[test]
+    fn should_skip_pr_listing_files_when_a_non_lockfile_conflicts() {
+        // Arrange
+        let step = GitStep::Rebase {
+            pr_number: 2,
+            source_branch: "feature/b".to_string(),
+        };
+        let conflicted = vec!["Cargo.lock".to_string(), "src/lib.rs".to_string()];
+
+        // Act
+        let resolution = resolve_conflict(&step, &conflicted);
+
+        // Assert
+        assert_eq!(
+            resolution,
+            ConflictResolution::Skip {
+                abort: strings(&["git", "cherry-pick", "--abort"]),
+                disposition: MergeDisposition::SkippedConflict {
+                    reason: "merge conflict in Cargo.lock, src/lib.rs".to_string(),
+                    files: conflicted.clone(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn should_skip_pr_when_a_manifest_conflicts_next_to_its_lockfile() {
+        // Arrange
+        let step = GitStep::Merge {
+            pr_number: 3,
+            source_branch: "feature/c".to_string(),
+        };
+        let conflicted = vec!["svc/go.mod".to_string(), "svc/go.sum".to_string()];
+
+        // Act
+        let resolution = resolve_conflict(&step, &conflicted);
+
+        // Assert
+        assert_eq!(
+            resolution,
+            ConflictResolution::Skip {
+                abort: strings(&["git", "merge", "--abort"]),
+                disposition: skipped_conflict(&conflicted),
+            }
+        );
+    }
+}
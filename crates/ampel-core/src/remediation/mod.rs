@@ -7,10 +7,12 @@
 mod consolidation;
 mod failure_classifier;
 mod fingerprint;
+mod merge_steps;
 mod model_catalog;
 mod model_provider;
 mod policy;
 mod run;
+mod suggestion;
 
 pub(crate) mod db;
 
@@ -23,6 +25,10 @@ pub use fingerprint::{
     detect_lockfile_kind, effective_completion_command, regen_command_for, Ecosystem,
     HeuristicFingerprinter, LockfileKind, RepoFingerprint, RepoFingerprinter,
 };
+pub use merge_steps::{
+    build_merge_sequence, parse_conflicted_paths, regenerable_lockfile, resolve_conflict,
+    skipped_conflict, ConflictResolution, GitStep, MAX_CONFLICT_ROUNDS,
+};
 pub use model_catalog::{
     load_catalog, CatalogEntry, CatalogModel, CatalogProvider, ModelCatalog, DEFAULT_CATALOG_YAML,
 };
@@ -32,10 +38,11 @@ pub use model_provider::{
     ModelProvider, NormalizedProviderOutput, OutputContract, ProviderKind, ToolCall,
 };
 pub use policy::{
-    AutonomyLevel, ModelSelectionMode, PrSelectionStrategy, RemediationCriteria, RemediationTier,
-    ScopeType,
+    AutonomyLevel, ConsolidationStrategy, ModelSelectionMode, PrSelectionStrategy,
+    RemediationCriteria, RemediationTier, ScopeType,
 };
 pub use run::RunState;
+pub use suggestion::{diff_hunks, suggestions_from_diff, DiffHunk, SuggestedChange};
 
 #[cfg(any(test, feature = "test-utils"))]
 pub use model_provider::MockModelProvider;
@@ -103,6 +110,7 @@ pub(crate) mod testkit {
             pr_selection: Set(pr_selection_json.to_string()),
             autonomy_level: Set(autonomy_level.to_string()),
             remediation_tier: Set(remediation_tier.to_string()),
+            consolidation_strategy: Set("merge".to_string()),
             max_prs_per_run: Set(max_prs_per_run),
             allowed_targets: Set(allowed_targets_json.to_string()),
             skip_draft: Set(skip_draft),
@@ -96,6 +96,46 @@ impl FromStr for RemediationTier {
     }
 }
 
+/// How the sandbox combines the selected PR branches into the consolidation
+/// branch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsolidationStrategy {
+    /// `git merge --no-ff` of each branch: one merge commit per PR.
+    #[default]
+    Merge,
+    /// Replay each branch's commits onto the consolidation branch: linear
+    /// history, the PRs' own commits kept.
+    RebaseOntoBase,
+    /// `git merge --squash` of each branch: one plain commit per PR.
+    SquashPerPr,
+}
+
+impl fmt::Display for ConsolidationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Merge => "merge",
+            Self::RebaseOntoBase => "rebase_onto_base",
+            Self::SquashPerPr => "squash_per_pr",
+        })
+    }
+}
+
+impl FromStr for ConsolidationStrategy {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "merge" => Ok(Self::Merge),
+            "rebase_onto_base" => Ok(Self::RebaseOntoBase),
+            "squash_per_pr" => Ok(Self::SquashPerPr),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown consolidation_strategy: {other}"
+            ))),
+        }
+    }
+}
+
 /// The scope a policy is attached to. Resolution is most-specific-wins:
 /// `Repository` > `Team` > `Org` > `User`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -211,6 +251,8 @@ pub struct RemediationCriteria {
     pub air_gapped: bool,
     pub autonomy_level: AutonomyLevel,
     pub remediation_tier: RemediationTier,
+    #[serde(default)]
+    pub consolidation_strategy: ConsolidationStrategy,
 }
 
 #[cfg(test)]
@@ -234,6 +276,18 @@ mod tests {
         assert!(AutonomyLevel::from_str("nope").is_err());
     }
 
+    #[test]
+    fn should_round_trip_consolidation_strategy_through_db_string() {
+        for v in [
+            ConsolidationStrategy::Merge,
+            ConsolidationStrategy::RebaseOntoBase,
+            ConsolidationStrategy::SquashPerPr,
+        ] {
+            assert_eq!(ConsolidationStrategy::from_str(&v.to_string()).unwrap(), v);
+        }
+        assert!(ConsolidationStrategy::from_str("octopus").is_err());
+    }
+
     #[test]
     fn should_round_trip_remediation_tier_through_db_string() {
         for v in [
@@ -319,6 +373,7 @@ mod tests {
             air_gapped: true,
             autonomy_level: AutonomyLevel::DryRunOnly,
             remediation_tier: RemediationTier::ConsolidateOnly,
+            consolidation_strategy: ConsolidationStrategy::RebaseOntoBase,
         };
         let json = serde_json::to_string(&c).unwrap();
         assert_eq!(
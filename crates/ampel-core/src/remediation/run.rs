@@ -17,14 +17,17 @@
 //!                                            ▼             │
 //!                                     awaiting_approval ───┘   (human gate)
 //! created ──► no_op
-//! <any non-terminal> ──► handoff_human | failed | cancelled
+//! <any non-terminal> ──► handoff_human | failed | cancelled | dead_letter
 //! ```
 //! The `awaiting_approval` gate is reached from `verifying` only for the
 //! `auto_with_approval` autonomy tier (a safe verification parks the run there
 //! until a human approves, which advances it to `merging`). The
 //! `fully_autonomous` tier keeps the direct `verifying → merging` edge.
+//! `dead_letter` is where the worker parks a run whose job it has given up
+//! redelivering (crashed or errored on every attempt) — distinct from `failed`,
+//! which the executor records for an orchestration error it observed.
 //! Terminal states (`completed`, `handoff_human`, `failed`, `cancelled`,
-//! `no_op`) permit no outgoing transitions.
+//! `no_op`, `dead_letter`) permit no outgoing transitions.
 
 use crate::errors::{AmpelError, AmpelResult};
 use serde::{Deserialize, Serialize};
@@ -48,6 +51,7 @@ pub enum RunState {
     Failed,
     Cancelled,
     NoOp,
+    DeadLetter,
 }
 
 impl RunState {
@@ -55,7 +59,12 @@ impl RunState {
     pub fn is_terminal(self) -> bool {
         matches!(
             self,
-            Self::Completed | Self::HandoffHuman | Self::Failed | Self::Cancelled | Self::NoOp
+            Self::Completed
+                | Self::HandoffHuman
+                | Self::Failed
+                | Self::Cancelled
+                | Self::NoOp
+                | Self::DeadLetter
         )
     }
 
@@ -66,8 +75,8 @@ impl RunState {
 
     /// Whether a transition from `self` to `next` is legal.
     ///
-    /// Any non-terminal state may bail out to `handoff_human`, `failed`, or
-    /// `cancelled`. Terminal states are sinks.
+    /// Any non-terminal state may bail out to `handoff_human`, `failed`,
+    /// `cancelled` or `dead_letter`. Terminal states are sinks.
     pub fn can_transition_to(self, next: RunState) -> bool {
         use RunState::*;
 
@@ -77,7 +86,7 @@ impl RunState {
         }
 
         // Universal bail-outs available from any active state.
-        if matches!(next, HandoffHuman | Failed | Cancelled) {
+        if matches!(next, HandoffHuman | Failed | Cancelled | DeadLetter) {
             return true;
         }
 
@@ -114,6 +123,7 @@ impl fmt::Display for RunState {
             Self::Failed => "failed",
             Self::Cancelled => "cancelled",
             Self::NoOp => "no_op",
+            Self::DeadLetter => "dead_letter",
         };
         f.write_str(s)
     }
@@ -137,6 +147,7 @@ impl FromStr for RunState {
             "failed" => Ok(Self::Failed),
             "cancelled" => Ok(Self::Cancelled),
             "no_op" => Ok(Self::NoOp),
+            "dead_letter" => Ok(Self::DeadLetter),
             other => Err(AmpelError::ValidationError(format!(
                 "unknown run_state: {other}"
             ))),
@@ -149,7 +160,7 @@ mod tests {
     use super::RunState::*;
     use super::*;
 
-    const ALL: [RunState; 13] = [
+    const ALL: [RunState; 14] = [
         Created,
         Selecting,
         Consolidating,
@@ -163,6 +174,7 @@ mod tests {
         Failed,
         Cancelled,
         NoOp,
+        DeadLetter,
     ];
 
     #[test]
@@ -256,6 +268,7 @@ mod tests {
             assert!(s.can_transition_to(HandoffHuman), "{s} -> handoff_human");
             assert!(s.can_transition_to(Failed), "{s} -> failed");
             assert!(s.can_transition_to(Cancelled), "{s} -> cancelled");
+            assert!(s.can_transition_to(DeadLetter), "{s} -> dead_letter");
         }
     }
 
@@ -275,7 +288,7 @@ mod tests {
 
     #[test]
     fn should_reject_all_transitions_from_terminal_states() {
-        for term in [Completed, HandoffHuman, Failed, Cancelled, NoOp] {
+        for term in [Completed, HandoffHuman, Failed, Cancelled, NoOp, DeadLetter] {
             assert!(term.is_terminal());
             assert!(!term.is_active());
             for next in ALL {
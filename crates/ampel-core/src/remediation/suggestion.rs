@@ -0,0 +1,479 @@
+//! Suggested changes for suggest-only remediation.
+//!
+//! Under [`AutonomyLevel::SuggestOnly`](super::AutonomyLevel::SuggestOnly) the
+//! agent's unified diff is never applied or pushed. Instead each hunk becomes a
+//! [`SuggestedChange`]: a line range on the PR's head revision plus the text
+//! that should replace it, which providers render as their native suggestion
+//! blocks (GitHub suggested changes, GitLab suggestions).
+//!
+//! Parsing is pure and lenient: hunks that cannot be anchored to existing lines
+//! (new files, deletions of whole files, insertions into an empty file) are
+//! dropped rather than failing the whole diff.
+//!
+//! Providers only accept suggestions on lines the PR's own diff shows.
+//! [`diff_hunks`] reads that diff so a provider can tell which suggestions fit;
+//! the rest are posted as plain comments carrying their [`SuggestedChange::diff`].
+
+use serde::{Deserialize, Serialize};
+
+/// One replacement of a contiguous line range in a file on the PR head.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuggestedChange {
+    /// Repo-relative path of the file.
+    pub path: String,
+    /// First replaced line (1-based, inclusive).
+    pub start_line: u32,
+    /// Last replaced line (1-based, inclusive).
+    pub end_line: u32,
+    /// Replacement text for `start_line..=end_line`, without a trailing newline.
+    /// Empty when the lines should be deleted.
+    pub replacement: String,
+    /// The same change as a unified diff of this file, for lines where a
+    /// suggestion cannot be anchored.
+    #[serde(default)]
+    pub diff: String,
+}
+
+impl SuggestedChange {
+    /// Number of lines the suggestion replaces.
+    pub fn line_count(&self) -> u32 {
+        self.end_line - self.start_line + 1
+    }
+
+    /// The replacement as a fenced block with the given info string (e.g.
+    /// `suggestion`, or GitLab's `suggestion:-2+0`). The fence is longer than
+    /// any backtick run in the replacement, so the block cannot be closed early.
+    pub fn fenced(&self, info: &str) -> String {
+        fenced(&self.replacement, info)
+    }
+
+    /// A plain comment body proposing the change as a unified diff, for a
+    /// suggestion that falls outside the PR's diff.
+    pub fn diff_comment(&self) -> String {
+        let lines = if self.start_line == self.end_line {
+            format!("line {}", self.start_line)
+        } else {
+            format!("lines {}-{}", self.start_line, self.end_line)
+        };
+        format!(
+            "Ampel remediation proposes this change to `{}` {lines}, outside this pull \
+             request's diff. Nothing was pushed to this branch.\n\n{}",
+            self.path,
+            fenced(self.diff.trim_end_matches('\n'), "diff")
+        )
+    }
+}
+
+/// `text` in a fenced block longer than any backtick run in it, so the block
+/// cannot be closed early.
+fn fenced(text: &str, info: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    if text.is_empty() {
+        format!("{fence}{info}\n{fence}")
+    } else {
+        format!("{fence}{info}\n{text}\n{fence}")
+    }
+}
+
+/// One hunk of a PR's diff for a file, on the new (head) side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffHunk {
+    /// First head line the hunk shows (1-based).
+    pub new_start: u32,
+    /// Number of head lines the hunk shows, context included.
+    pub new_count: u32,
+    /// Head line → base line for each unchanged context line.
+    context: Vec<(u32, u32)>,
+}
+
+impl DiffHunk {
+    /// Whether every line `suggestion` replaces is shown by this hunk.
+    pub fn covers(&self, suggestion: &SuggestedChange) -> bool {
+        suggestion.start_line >= self.new_start
+            && suggestion.end_line < self.new_start + self.new_count
+    }
+
+    /// The base line of head line `new_line`, or `None` when the PR added it.
+    pub fn old_line(&self, new_line: u32) -> Option<u32> {
+        self.context
+            .iter()
+            .find(|(new, _)| *new == new_line)
+            .map(|(_, old)| *old)
+    }
+}
+
+/// The hunks of one file's diff as a provider returns it for a PR (hunk
+/// headers and lines, without `---`/`+++` file headers).
+pub fn diff_hunks(patch: &str) -> Vec<DiffHunk> {
+    let mut out: Vec<DiffHunk> = Vec::new();
+    let (mut old, mut new) = (0, 0);
+    for raw in patch.lines() {
+        if let Some(header) = raw.strip_prefix("@@ ") {
+            if let Some(hunk) = Hunk::parse(header) {
+                (old, new) = (hunk.old_start, hunk.new_start);
+                out.push(DiffHunk {
+                    new_start: hunk.new_start,
+                    new_count: hunk.new_left,
+                    context: Vec::new(),
+                });
+            }
+            continue;
+        }
+        let Some(hunk) = out.last_mut() else {
+            continue;
+        };
+        match raw.chars().next() {
+            Some('+') => new += 1,
+            Some('-') => old += 1,
+            Some(' ') | None => {
+                hunk.context.push((new, old));
+                new += 1;
+                old += 1;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Context,
+    Removed,
+    Added,
+}
+
+/// Translate a unified diff into suggestions, one per hunk.
+///
+/// Unchanged context at either end of a hunk is trimmed so a suggestion covers
+/// only the lines it changes. A pure insertion keeps one neighbouring context
+/// line as its anchor, since a suggestion must replace at least one line.
+pub fn suggestions_from_diff(diff: &str) -> Vec<SuggestedChange> {
+    let mut out = Vec::new();
+    let mut path: Option<String> = None;
+    let mut hunk: Option<Hunk> = None;
+
+    for raw in diff.lines() {
+        // Inside a hunk its header's line counts decide what a line is, so a
+        // removed `-- comment` is never mistaken for a file header.
+        if let Some(h) = hunk.as_mut().filter(|h| h.old_left > 0 || h.new_left > 0) {
+            let (kind, text) = match raw.chars().next() {
+                Some('+') => (Line::Added, &raw[1..]),
+                Some('-') => (Line::Removed, &raw[1..]),
+                Some(' ') => (Line::Context, &raw[1..]),
+                // Some tools strip the space from an empty context line.
+                None => (Line::Context, ""),
+                // `\ No newline at end of file`.
+                _ => continue,
+            };
+            if kind != Line::Added {
+                h.old_left = h.old_left.saturating_sub(1);
+            }
+            if kind != Line::Removed {
+                h.new_left = h.new_left.saturating_sub(1);
+            }
+            h.lines.push((kind, text.to_string()));
+            continue;
+        }
+
+        finish(&path, hunk.take(), &mut out);
+        if let Some(rest) = raw.strip_prefix("+++ ") {
+            path = file_path(rest);
+        } else if let Some(header) = raw.strip_prefix("@@ ") {
+            hunk = Hunk::parse(header);
+        }
+    }
+    finish(&path, hunk, &mut out);
+    out
+}
+
+/// A hunk being read: where it starts on the old side, how many old/new lines
+/// its header still expects, and the lines read so far.
+struct Hunk {
+    old_start: u32,
+    new_start: u32,
+    old_left: u32,
+    new_left: u32,
+    lines: Vec<(Line, String)>,
+}
+
+impl Hunk {
+    /// Parse a hunk header body (`-l,s +l,s @@ ...`); an omitted count is 1.
+    fn parse(header: &str) -> Option<Self> {
+        fn range(part: &str) -> Option<(u32, u32)> {
+            let mut it = part.splitn(2, ',');
+            let start = it.next()?.parse().ok()?;
+            let count = match it.next() {
+                Some(count) => count.parse().ok()?,
+                None => 1,
+            };
+            Some((start, count))
+        }
+
+        let mut parts = header.split_whitespace();
+        let (old_start, old_left) = range(parts.next()?.strip_prefix('-')?)?;
+        let (new_start, new_left) = range(parts.next()?.strip_prefix('+')?)?;
+        Some(Self {
+            old_start,
+            new_start,
+            old_left,
+            new_left,
+            lines: Vec::new(),
+        })
+    }
+}
+
+fn finish(path: &Option<String>, hunk: Option<Hunk>, out: &mut Vec<SuggestedChange>) {
+    if let (Some(path), Some(hunk)) = (path, hunk) {
+        out.extend(anchor_hunk(path, &hunk));
+    }
+}
+
+/// The new-side path from a `+++` header, or `None` for a deleted file.
+fn file_path(header: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path.strip_prefix("b/").unwrap_or(path);
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+fn anchor_hunk(path: &str, hunk: &Hunk) -> Option<SuggestedChange> {
+    let (old_start, lines) = (hunk.old_start, &hunk.lines);
+    let first = lines.iter().position(|(k, _)| *k != Line::Context)?;
+    let last = lines.iter().rposition(|(k, _)| *k != Line::Context)?;
+    let (mut from, mut to) = (first, last);
+
+    if !lines[from..=to].iter().any(|(k, _)| *k == Line::Removed) {
+        if from > 0 {
+            from -= 1;
+        } else if to + 1 < lines.len() {
+            to += 1;
+        } else {
+            return None;
+        }
+    }
+
+    let old_before = lines[..from]
+        .iter()
+        .filter(|(k, _)| *k != Line::Added)
+        .count() as u32;
+    let old_in_range = lines[from..=to]
+        .iter()
+        .filter(|(k, _)| *k != Line::Added)
+        .count() as u32;
+    // A zero start only appears for an insertion into an empty file.
+    if old_start == 0 || old_in_range == 0 {
+        return None;
+    }
+    let start_line = old_start + old_before;
+    let replacement = lines[from..=to]
+        .iter()
+        .filter(|(k, _)| *k != Line::Removed)
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let new_before = lines[..from]
+        .iter()
+        .filter(|(k, _)| *k != Line::Removed)
+        .count() as u32;
+    let new_in_range = lines[from..=to]
+        .iter()
+        .filter(|(k, _)| *k != Line::Removed)
+        .count() as u32;
+    let mut diff = format!(
+        "--- a/{path}\n+++ b/{path}\n@@ -{start_line},{old_in_range} +{},{new_in_range} @@\n",
+        hunk.new_start + new_before
+    );
+    for (kind, text) in &lines[from..=to] {
+        let prefix = match kind {
+            Line::Context => ' ',
+            Line::Removed => '-',
+            Line::Added => '+',
+        };
+        diff.push(prefix);
+        diff.push_str(text);
+        diff.push('\n');
+    }
+
+    Some(SuggestedChange {
+        path: path.to_string(),
+        start_line,
+        end_line: start_line + old_in_range - 1,
+        replacement,
+        diff,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_trim_context_around_a_replaced_line() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,5 +10,5 @@ fn main() {
+ one
+ two
+-let x = 1;
++let x = 2;
+ four
+ five
+";
+        let changes = suggestions_from_diff(diff);
+        assert_eq!(
+            changes,
+            vec![SuggestedChange {
+                path: "src/lib.rs".into(),
+                start_line: 12,
+                end_line: 12,
+                replacement: "let x = 2;".into(),
+                diff: "\
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -12,1 +12,1 @@
+-let x = 1;
++let x = 2;
+"
+                .into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_anchor_pure_insertion_on_preceding_context_line() {
+        let diff = "\
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -3,2 +3,3 @@
+ [dependencies]
++serde = \"1\"
+ tokio = \"1\"
+";
+        let changes = suggestions_from_diff(diff);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].start_line, 3);
+        assert_eq!(changes[0].end_line, 3);
+        assert_eq!(changes[0].replacement, "[dependencies]\nserde = \"1\"");
+    }
+
+    #[test]
+    fn should_emit_one_suggestion_per_hunk_across_files() {
+        let diff = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,1 @@
+-gone
+ kept
+@@ -20,1 +19,1 @@
+-old
++new
+--- a/b.txt
++++ b/b.txt
+@@ -5 +5 @@
+-x
++y
+";
+        let changes = suggestions_from_diff(diff);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            (changes[0].start_line, changes[0].end_line),
+            (1, 1),
+            "deletion keeps its range"
+        );
+        assert_eq!(changes[0].replacement, "");
+        assert_eq!(changes[1].start_line, 20);
+        assert_eq!(changes[2].path, "b.txt");
+        assert_eq!(changes[2].line_count(), 1);
+    }
+
+    #[test]
+    fn should_fence_replacement_longer_than_its_backtick_runs() {
+        let change = SuggestedChange {
+            path: "README.md".into(),
+            start_line: 1,
+            end_line: 1,
+            replacement: "```rust".into(),
+            diff: String::new(),
+        };
+        assert_eq!(change.fenced("suggestion"), "````suggestion\n```rust\n````");
+    }
+
+    #[test]
+    fn should_read_removed_lines_that_look_like_headers_as_hunk_content() {
+        let diff = "\
+--- a/schema.sql
++++ b/schema.sql
+@@ -1,2 +1,2 @@
+--- drop me
+-++ and me
++-- kept
++SELECT 1;
+";
+        let changes = suggestions_from_diff(diff);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].start_line, changes[0].end_line), (1, 2));
+        assert_eq!(changes[0].replacement, "-- kept\nSELECT 1;");
+    }
+
+    #[test]
+    fn should_skip_new_and_deleted_files() {
+        let diff = "\
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++a
++b
+--- a/old.txt
++++ /dev/null
+@@ -1,1 +0,0 @@
+-a
+";
+        assert!(suggestions_from_diff(diff).is_empty());
+    }
+
+    #[test]
+    fn should_read_pr_hunks_with_their_context_lines() {
+        // Arrange: GitHub's per-file `patch`, without file headers.
+        let patch = "\
+@@ -3,4 +3,5 @@ [package]
+ [dependencies]
+-serde = \"1.0.100\"
++serde = \"1.0.200\"
++tokio = \"1\"
+ anyhow = \"1\"
+ 
+@@ -40,2 +41,2 @@
+-a
++b
+ c";
+        let inside = |start_line, end_line| SuggestedChange {
+            path: "Cargo.toml".into(),
+            start_line,
+            end_line,
+            replacement: String::new(),
+            diff: String::new(),
+        };
+
+        // Act
+        let hunks = diff_hunks(patch);
+
+        // Assert
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].new_start, hunks[0].new_count), (3, 5));
+        assert!(hunks[0].covers(&inside(4, 7)));
+        assert!(!hunks[0].covers(&inside(7, 8)), "runs past the hunk");
+        assert!(!hunks[1].covers(&inside(10, 10)));
+        assert_eq!(hunks[0].old_line(3), Some(3));
+        assert_eq!(hunks[0].old_line(4), None, "added by the PR");
+        assert_eq!(hunks[0].old_line(6), Some(5));
+        assert_eq!(hunks[0].old_line(7), Some(6), "blank context line");
+        assert_eq!(hunks[1].old_line(42), Some(41));
+    }
+}
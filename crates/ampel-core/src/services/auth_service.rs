@@ -4,6 +4,7 @@ use argon2::{
 };
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::errors::{AmpelError, AmpelResult};
@@ -49,10 +50,15 @@ impl AuthService {
             .is_ok())
     }
 
-    /// Generate access and refresh tokens for a user
-    pub fn generate_tokens(&self, user_id: Uuid, email: &str) -> AmpelResult<AuthTokens> {
-        let access_token = self.generate_token(user_id, email, TokenType::Access)?;
-        let refresh_token = self.generate_token(user_id, email, TokenType::Refresh)?;
+    /// Generate access and refresh tokens for a user's session
+    pub fn generate_tokens(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        session_id: Uuid,
+    ) -> AmpelResult<AuthTokens> {
+        let access_token = self.generate_token(user_id, email, session_id, TokenType::Access)?;
+        let refresh_token = self.generate_token(user_id, email, session_id, TokenType::Refresh)?;
 
         Ok(AuthTokens {
             access_token,
@@ -62,11 +68,27 @@ impl AuthService {
         })
     }
 
+    /// How long a refresh token stays valid
+    pub fn refresh_token_ttl(&self) -> Duration {
+        Duration::days(self.refresh_token_expiry_days)
+    }
+
+    /// Hash a refresh token for storage; tokens are only ever looked up by hash
+    pub fn hash_refresh_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    /// Hash a personal API token for storage, the same way as refresh tokens
+    pub fn hash_api_token(token: &str) -> String {
+        Self::hash_refresh_token(token)
+    }
+
     /// Generate a single token (access or refresh)
     fn generate_token(
         &self,
         user_id: Uuid,
         email: &str,
+        session_id: Uuid,
         token_type: TokenType,
     ) -> AmpelResult<String> {
         let now = Utc::now();
@@ -81,6 +103,8 @@ impl AuthService {
             exp: expiry.timestamp(),
             iat: now.timestamp(),
             token_type,
+            sid: Some(session_id),
+            jti: Some(Uuid::new_v4()),
         };
 
         encode(
@@ -155,25 +179,29 @@ mod tests {
         let user_id = Uuid::new_v4();
         let email = "test@example.com";
 
-        let tokens = service.generate_tokens(user_id, email).unwrap();
+        let session_id = Uuid::new_v4();
+
+        let tokens = service.generate_tokens(user_id, email, session_id).unwrap();
 
         let access_claims = service.validate_access_token(&tokens.access_token).unwrap();
         assert_eq!(access_claims.sub, user_id);
         assert_eq!(access_claims.email, email);
         assert_eq!(access_claims.token_type, TokenType::Access);
+        assert_eq!(access_claims.sid, Some(session_id));
 
         let refresh_claims = service
             .validate_refresh_token(&tokens.refresh_token)
             .unwrap();
         assert_eq!(refresh_claims.sub, user_id);
         assert_eq!(refresh_claims.token_type, TokenType::Refresh);
+        assert_eq!(refresh_claims.sid, Some(session_id));
     }
 
     #[test]
     fn test_wrong_token_type_validation() {
         let service = create_auth_service();
         let tokens = service
-            .generate_tokens(Uuid::new_v4(), "test@example.com")
+            .generate_tokens(Uuid::new_v4(), "test@example.com", Uuid::new_v4())
             .unwrap();
 
         // Access token should fail refresh validation
@@ -186,4 +214,23 @@ mod tests {
             .validate_access_token(&tokens.refresh_token)
             .is_err());
     }
+
+    #[test]
+    fn test_tokens_are_unique_within_a_session() {
+        let service = create_auth_service();
+        let (user_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first = service
+            .generate_tokens(user_id, "test@example.com", session_id)
+            .unwrap();
+        let second = service
+            .generate_tokens(user_id, "test@example.com", session_id)
+            .unwrap();
+
+        assert_ne!(first.refresh_token, second.refresh_token);
+        assert_ne!(
+            AuthService::hash_refresh_token(&first.refresh_token),
+            AuthService::hash_refresh_token(&second.refresh_token)
+        );
+    }
 }
@@ -1,5 +1,6 @@
 mod auth_service;
 mod learning_signal;
+mod notification_channel;
 mod notification_service;
 mod policy_resolver;
 mod pr_service;
@@ -9,9 +10,11 @@ mod remediation_service;
 mod repo_service;
 mod sandbox_runner;
 mod verification_service;
+mod web_push;
 
 pub use auth_service::*;
 pub use learning_signal::*;
+pub use notification_channel::*;
 pub use notification_service::*;
 pub use policy_resolver::*;
 pub use pr_service::*;
@@ -21,3 +24,4 @@ pub use remediation_service::*;
 pub use repo_service::*;
 pub use sandbox_runner::*;
 pub use verification_service::*;
+pub use web_push::*;
@@ -0,0 +1,755 @@
+//! Pluggable chat and webhook notification channels.
+//!
+//! A [`NotificationChannel`] delivers a [`ChannelMessage`], a channel-neutral
+//! description of an event, in whatever format its service expects. Users and
+//! teams configure channels as a [`ChannelConfig`], which is stored encrypted
+//! because it holds webhook URLs and tokens.
+
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::{MergeNotificationPayload, NotificationError};
+use crate::errors::{AmpelError, AmpelResult};
+use crate::models::{NotificationKind, PrNotification};
+
+/// Header carrying the generic webhook's HMAC-SHA256 signature
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Ampel-Signature";
+/// Header carrying the Unix timestamp the generic webhook signature covers
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Ampel-Timestamp";
+/// Header naming the event a generic webhook delivery is about
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Ampel-Event";
+
+/// How good or bad the news in a message is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Failure,
+}
+
+impl Severity {
+    /// RGB colour used by services that tint messages
+    fn color(self) -> u32 {
+        match self {
+            Self::Info => 0x3b82f6,
+            Self::Success => 0x22c55e,
+            Self::Warning => 0xeab308,
+            Self::Failure => 0xef4444,
+        }
+    }
+}
+
+/// An event to deliver, independent of any channel's format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelMessage {
+    /// Machine-readable event name, e.g. `pr_ready`
+    pub event: String,
+    pub title: String,
+    pub body: String,
+    /// Link to the subject of the event
+    pub url: Option<String>,
+    /// Short labelled values shown alongside the body
+    pub facts: Vec<(String, String)>,
+    pub severity: Severity,
+    /// Event details, sent as-is by the generic webhook
+    pub data: serde_json::Value,
+}
+
+impl ChannelMessage {
+    /// A message sent by the test-send endpoints
+    pub fn test() -> Self {
+        Self {
+            event: "test".to_string(),
+            title: "Ampel test notification".to_string(),
+            body: "This channel is configured correctly.".to_string(),
+            url: None,
+            facts: Vec::new(),
+            severity: Severity::Info,
+            data: json!({}),
+        }
+    }
+
+    /// A message about a single pull request event
+    pub fn from_pr(notification: &PrNotification) -> Self {
+        let severity = match notification.kind {
+            NotificationKind::PrReady => Severity::Success,
+            NotificationKind::PrFailed => Severity::Failure,
+            _ => Severity::Info,
+        };
+        let mut facts = vec![
+            ("Repository".to_string(), notification.repository.clone()),
+            ("Author".to_string(), notification.author.clone()),
+        ];
+        if let Some(reviewer) = &notification.reviewer {
+            facts.push(("Reviewer".to_string(), reviewer.clone()));
+        }
+        Self {
+            event: notification.kind.to_string(),
+            title: notification.headline(),
+            body: notification.pr_title.clone(),
+            url: Some(notification.pr_url.clone()),
+            facts,
+            severity,
+            data: serde_json::to_value(notification).unwrap_or_default(),
+        }
+    }
+
+    /// A message about a finished bulk merge
+    pub fn from_merge(payload: &MergeNotificationPayload) -> Self {
+        let (kind, severity) = if payload.failed == 0 {
+            (NotificationKind::MergeSucceeded, Severity::Success)
+        } else {
+            (NotificationKind::MergeFailed, Severity::Warning)
+        };
+        let mut body = String::new();
+        for item in payload
+            .results
+            .iter()
+            .filter(|r| r.status == "failed")
+            .take(5)
+        {
+            body.push_str(&format!(
+                "Failed: {} #{}: {}\n",
+                item.repository,
+                item.pr_number,
+                item.error.as_deref().unwrap_or("Unknown error")
+            ));
+        }
+        Self {
+            event: kind.to_string(),
+            title: "Bulk merge completed".to_string(),
+            body: body.trim_end().to_string(),
+            url: None,
+            facts: vec![
+                ("Total".to_string(), payload.total.to_string()),
+                ("Merged".to_string(), payload.success.to_string()),
+                ("Failed".to_string(), payload.failed.to_string()),
+                ("Skipped".to_string(), payload.skipped.to_string()),
+            ],
+            severity,
+            data: serde_json::to_value(payload).unwrap_or_default(),
+        }
+    }
+
+    /// Title, body, link and facts as plain text lines
+    fn plain_text(&self) -> String {
+        let mut text = self.title.clone();
+        if !self.body.is_empty() {
+            text.push_str(&format!("\n{}", self.body));
+        }
+        for (name, value) in &self.facts {
+            text.push_str(&format!("\n{}: {}", name, value));
+        }
+        if let Some(url) = &self.url {
+            text.push_str(&format!("\n{}", url));
+        }
+        text
+    }
+}
+
+/// A service notifications can be delivered to
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn kind(&self) -> ChannelKind;
+
+    async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError>;
+}
+
+/// The services a channel can be configured for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    Slack,
+    Teams,
+    Discord,
+    Matrix,
+    Webhook,
+}
+
+impl ChannelKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Slack => "slack",
+            Self::Teams => "teams",
+            Self::Discord => "discord",
+            Self::Matrix => "matrix",
+            Self::Webhook => "webhook",
+        }
+    }
+}
+
+impl fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChannelKind {
+    type Err = AmpelError;
+
+    fn from_str(s: &str) -> AmpelResult<Self> {
+        match s {
+            "slack" => Ok(Self::Slack),
+            "teams" => Ok(Self::Teams),
+            "discord" => Ok(Self::Discord),
+            "matrix" => Ok(Self::Matrix),
+            "webhook" => Ok(Self::Webhook),
+            other => Err(AmpelError::ValidationError(format!(
+                "unknown notification channel kind: {other}"
+            ))),
+        }
+    }
+}
+
+/// Settings for one configured channel
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum ChannelConfig {
+    /// Slack incoming webhook
+    Slack {
+        webhook_url: String,
+        channel: Option<String>,
+    },
+    /// Microsoft Teams incoming webhook or Workflows URL
+    Teams { webhook_url: String },
+    /// Discord channel webhook
+    Discord { webhook_url: String },
+    /// Matrix room, posted to as the user owning `access_token`
+    Matrix {
+        homeserver_url: String,
+        room_id: String,
+        access_token: String,
+    },
+    /// Any HTTPS endpoint accepting signed JSON
+    Webhook { url: String, secret: String },
+}
+
+// Every variant holds a credential, so only the kind is printed
+impl fmt::Debug for ChannelConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelConfig")
+            .field("kind", &self.kind())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChannelConfig {
+    pub fn kind(&self) -> ChannelKind {
+        match self {
+            Self::Slack { .. } => ChannelKind::Slack,
+            Self::Teams { .. } => ChannelKind::Teams,
+            Self::Discord { .. } => ChannelKind::Discord,
+            Self::Matrix { .. } => ChannelKind::Matrix,
+            Self::Webhook { .. } => ChannelKind::Webhook,
+        }
+    }
+
+    /// Reject settings that cannot work before they are stored
+    pub fn validate(&self) -> AmpelResult<()> {
+        let require_url = |name: &str, value: &str| {
+            let parsed = url::Url::parse(value)
+                .map_err(|_| AmpelError::ValidationError(format!("{name} must be a URL")))?;
+            if !matches!(parsed.scheme(), "https" | "http") {
+                return Err(AmpelError::ValidationError(format!(
+                    "{name} must be an http(s) URL"
+                )));
+            }
+            Ok(())
+        };
+        match self {
+            Self::Slack { webhook_url, .. }
+            | Self::Teams { webhook_url }
+            | Self::Discord { webhook_url } => require_url("webhookUrl", webhook_url),
+            Self::Matrix {
+                homeserver_url,
+                room_id,
+                access_token,
+            } => {
+                require_url("homeserverUrl", homeserver_url)?;
+                if !room_id.starts_with('!') && !room_id.starts_with('#') {
+                    return Err(AmpelError::ValidationError(
+                        "roomId must be a Matrix room ID or alias".to_string(),
+                    ));
+                }
+                if access_token.is_empty() {
+                    return Err(AmpelError::ValidationError(
+                        "accessToken is required".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Self::Webhook { url, secret } => {
+                require_url("url", url)?;
+                if secret.len() < 16 {
+                    return Err(AmpelError::ValidationError(
+                        "secret must be at least 16 characters".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Where the channel delivers to, without credentials, for display
+    pub fn target(&self) -> String {
+        let host = |value: &str| {
+            url::Url::parse(value)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_default()
+        };
+        match self {
+            Self::Slack {
+                webhook_url,
+                channel,
+            } => match channel {
+                Some(channel) => format!("{} {}", host(webhook_url), channel),
+                None => host(webhook_url),
+            },
+            Self::Teams { webhook_url } | Self::Discord { webhook_url } => host(webhook_url),
+            Self::Matrix {
+                homeserver_url,
+                room_id,
+                ..
+            } => format!("{} on {}", room_id, host(homeserver_url)),
+            Self::Webhook { url, .. } => host(url),
+        }
+    }
+
+    /// The channel these settings describe
+    pub fn into_channel(self) -> Box<dyn NotificationChannel> {
+        match self {
+            Self::Slack {
+                webhook_url,
+                channel,
+            } => Box::new(SlackChannel {
+                webhook_url,
+                channel,
+            }),
+            Self::Teams { webhook_url } => Box::new(TeamsChannel { webhook_url }),
+            Self::Discord { webhook_url } => Box::new(DiscordChannel { webhook_url }),
+            Self::Matrix {
+                homeserver_url,
+                room_id,
+                access_token,
+            } => Box::new(MatrixChannel {
+                homeserver_url,
+                room_id,
+                access_token,
+            }),
+            Self::Webhook { url, secret } => Box::new(WebhookChannel { url, secret }),
+        }
+    }
+}
+
+/// Send a request and turn transport errors and non-2xx responses into
+/// [`NotificationError::Delivery`]
+async fn deliver(
+    kind: ChannelKind,
+    request: reqwest::RequestBuilder,
+) -> Result<(), NotificationError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| NotificationError::Delivery(format!("{}: {}", kind, e)))?;
+    if !response.status().is_success() {
+        return Err(NotificationError::Delivery(format!(
+            "{} returned status {}",
+            kind,
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+/// Slack incoming webhook
+pub struct SlackChannel {
+    pub webhook_url: String,
+    pub channel: Option<String>,
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Slack
+    }
+
+    async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError> {
+        let mut text = format!("*{}*", message.title);
+        if !message.body.is_empty() {
+            text.push_str(&format!("\n{}", message.body));
+        }
+        for (name, value) in &message.facts {
+            text.push_str(&format!("\n• {}: {}", name, value));
+        }
+        if let Some(url) = &message.url {
+            text.push_str(&format!("\n<{}|Open in browser>", url));
+        }
+        let mut body = json!({ "text": text });
+        if let Some(channel) = &self.channel {
+            body["channel"] = json!(channel);
+        }
+        deliver(
+            self.kind(),
+            reqwest::Client::new().post(&self.webhook_url).json(&body),
+        )
+        .await
+    }
+}
+
+/// Microsoft Teams webhook, posting an Adaptive Card
+pub struct TeamsChannel {
+    pub webhook_url: String,
+}
+
+impl TeamsChannel {
+    fn card(message: &ChannelMessage) -> serde_json::Value {
+        let title_color = match message.severity {
+            Severity::Info => "Default",
+            Severity::Success => "Good",
+            Severity::Warning => "Warning",
+            Severity::Failure => "Attention",
+        };
+        let mut body = vec![json!({
+            "type": "TextBlock",
+            "text": message.title,
+            "weight": "Bolder",
+            "size": "Medium",
+            "color": title_color,
+            "wrap": true,
+        })];
+        if !message.body.is_empty() {
+            body.push(json!({ "type": "TextBlock", "text": message.body, "wrap": true }));
+        }
+        if !message.facts.is_empty() {
+            let facts: Vec<_> = message
+                .facts
+                .iter()
+                .map(|(title, value)| json!({ "title": title, "value": value }))
+                .collect();
+            body.push(json!({ "type": "FactSet", "facts": facts }));
+        }
+        let actions: Vec<_> = message
+            .url
+            .iter()
+            .map(|url| json!({ "type": "Action.OpenUrl", "title": "Open", "url": url }))
+            .collect();
+
+        json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "contentUrl": null,
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": body,
+                    "actions": actions,
+                },
+            }],
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for TeamsChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Teams
+    }
+
+    async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError> {
+        deliver(
+            self.kind(),
+            reqwest::Client::new()
+                .post(&self.webhook_url)
+                .json(&Self::card(message)),
+        )
+        .await
+    }
+}
+
+/// Discord webhook, posting an embed
+pub struct DiscordChannel {
+    pub webhook_url: String,
+}
+
+impl DiscordChannel {
+    fn embed(message: &ChannelMessage) -> serde_json::Value {
+        let fields: Vec<_> = message
+            .facts
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value, "inline": true }))
+            .collect();
+        let mut embed = json!({
+            "title": message.title,
+            "color": message.severity.color(),
+            "fields": fields,
+        });
+        if !message.body.is_empty() {
+            embed["description"] = json!(message.body);
+        }
+        if let Some(url) = &message.url {
+            embed["url"] = json!(url);
+        }
+        json!({ "embeds": [embed] })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for DiscordChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Discord
+    }
+
+    async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError> {
+        deliver(
+            self.kind(),
+            reqwest::Client::new()
+                .post(&self.webhook_url)
+                .json(&Self::embed(message)),
+        )
+        .await
+    }
+}
+
+/// Matrix room, via the client-server API
+pub struct MatrixChannel {
+    pub homeserver_url: String,
+    pub room_id: String,
+    pub access_token: String,
+}
+
+#[async_trait]
+impl NotificationChannel for MatrixChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Matrix
+    }
+
+    async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError> {
+        let mut url = url::Url::parse(&self.homeserver_url)
+            .map_err(|e| NotificationError::Config(format!("Invalid homeserver URL: {}", e)))?;
+        url.path_segments_mut()
+            .map_err(|_| NotificationError::Config("Invalid homeserver URL".into()))?
+            .pop_if_empty()
+            .extend([
+                "_matrix",
+                "client",
+                "v3",
+                "rooms",
+                &self.room_id,
+                "send",
+                "m.room.message",
+                &Uuid::new_v4().to_string(),
+            ]);
+
+        let mut html = format!("<strong>{}</strong>", escape_html(&message.title));
+        if !message.body.is_empty() {
+            html.push_str(&format!("<br>{}", escape_html(&message.body)));
+        }
+        for (name, value) in &message.facts {
+            html.push_str(&format!(
+                "<br>{}: {}",
+                escape_html(name),
+                escape_html(value)
+            ));
+        }
+        if let Some(link) = &message.url {
+            html.push_str(&format!(
+                "<br><a href=\"{}\">{}</a>",
+                escape_html(link),
+                escape_html(link)
+            ));
+        }
+
+        let body = json!({
+            "msgtype": "m.notice",
+            "body": message.plain_text(),
+            "format": "org.matrix.custom.html",
+            "formatted_body": html,
+        });
+        deliver(
+            self.kind(),
+            reqwest::Client::new()
+                .put(url)
+                .bearer_auth(&self.access_token)
+                .json(&body),
+        )
+        .await
+    }
+}
+
+/// Generic JSON webhook, signed with a shared secret
+pub struct WebhookChannel {
+    pub url: String,
+    pub secret: String,
+}
+
+impl WebhookChannel {
+    /// Hex HMAC-SHA256 of `"{timestamp}.{body}"`, prefixed with `sha256=`
+    pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Webhook
+    }
+
+    async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError> {
+        let timestamp = Utc::now().timestamp();
+        let body = serde_json::to_vec(&json!({
+            "event": message.event,
+            "title": message.title,
+            "body": message.body,
+            "url": message.url,
+            "severity": message.severity,
+            "data": message.data,
+            "timestamp": timestamp,
+        }))
+        .map_err(|e| NotificationError::Delivery(format!("webhook: {}", e)))?;
+
+        deliver(
+            self.kind(),
+            reqwest::Client::new()
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(WEBHOOK_EVENT_HEADER, &message.event)
+                .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+                .header(
+                    WEBHOOK_SIGNATURE_HEADER,
+                    Self::signature(&self.secret, timestamp, &body),
+                )
+                .body(body),
+        )
+        .await
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::MergeResultItem;
+
+    fn message() -> ChannelMessage {
+        ChannelMessage::from_pr(&PrNotification {
+            kind: NotificationKind::PrFailed,
+            repository: "acme/api".to_string(),
+            pr_number: 42,
+            pr_title: "Bump serde".to_string(),
+            pr_url: "https://github.com/acme/api/pull/42".to_string(),
+            author: "octocat".to_string(),
+            reviewer: None,
+        })
+    }
+
+    #[test]
+    fn test_merge_message_reports_failures() {
+        let message = ChannelMessage::from_merge(&MergeNotificationPayload {
+            total: 2,
+            success: 1,
+            failed: 1,
+            skipped: 0,
+            results: vec![MergeResultItem {
+                repository: "acme/api".to_string(),
+                pr_number: 7,
+                pr_title: "Bump serde".to_string(),
+                status: "failed".to_string(),
+                error: Some("Merge conflict".to_string()),
+            }],
+        });
+        assert_eq!(message.event, "merge_failed");
+        assert_eq!(message.severity, Severity::Warning);
+        assert_eq!(message.body, "Failed: acme/api #7: Merge conflict");
+        assert!(message
+            .facts
+            .contains(&("Failed".to_string(), "1".to_string())));
+    }
+
+    #[test]
+    fn test_teams_card_is_an_adaptive_card() {
+        let card = TeamsChannel::card(&message());
+        let attachment = &card["attachments"][0];
+        assert_eq!(
+            attachment["contentType"],
+            "application/vnd.microsoft.card.adaptive"
+        );
+        assert_eq!(attachment["content"]["type"], "AdaptiveCard");
+        assert_eq!(attachment["content"]["body"][0]["color"], "Attention");
+        assert_eq!(
+            attachment["content"]["actions"][0]["url"],
+            "https://github.com/acme/api/pull/42"
+        );
+    }
+
+    #[test]
+    fn test_discord_embed_links_and_colours() {
+        let payload = DiscordChannel::embed(&message());
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["url"], "https://github.com/acme/api/pull/42");
+        assert_eq!(embed["color"], Severity::Failure.color());
+        assert_eq!(embed["fields"][0]["value"], "acme/api");
+    }
+
+    #[test]
+    fn test_webhook_signature_covers_timestamp_and_body() {
+        let signature = WebhookChannel::signature("0123456789abcdef", 1700000000, b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_ne!(
+            signature,
+            WebhookChannel::signature("0123456789abcdef", 1700000001, b"{}")
+        );
+    }
+
+    #[test]
+    fn test_config_validation_and_target_hide_secrets() {
+        let config = ChannelConfig::Matrix {
+            homeserver_url: "https://matrix.example.org".to_string(),
+            room_id: "!room:example.org".to_string(),
+            access_token: "syt_secret".to_string(),
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.target(), "!room:example.org on matrix.example.org");
+        assert!(!format!("{:?}", config).contains("syt_secret"));
+
+        let weak = ChannelConfig::Webhook {
+            url: "https://hooks.example.com/ampel".to_string(),
+            secret: "short".to_string(),
+        };
+        assert!(weak.validate().is_err());
+        assert!(ChannelConfig::Discord {
+            webhook_url: "not a url".to_string()
+        }
+        .validate()
+        .is_err());
+    }
+}
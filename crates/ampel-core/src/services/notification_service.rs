@@ -1,6 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::models::{NotificationKind, PrNotification};
+
 #[derive(Debug, Error)]
 pub enum NotificationError {
     #[error("Slack error: {0}")]
@@ -9,6 +11,8 @@ pub enum NotificationError {
     Email(String),
     #[error("Configuration error: {0}")]
     Config(String),
+    #[error("Delivery error: {0}")]
+    Delivery(String),
 }
 
 #[derive(Debug, Clone)]
@@ -21,7 +25,7 @@ pub struct SmtpConfig {
     pub use_tls: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeNotificationPayload {
     pub total: i32,
     pub success: i32,
@@ -30,7 +34,7 @@ pub struct MergeNotificationPayload {
     pub results: Vec<MergeResultItem>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeResultItem {
     pub repository: String,
     pub pr_number: i32,
@@ -77,6 +81,42 @@ impl NotificationService {
             }
         }
 
+        Self::post_slack_message(webhook_url, channel, text).await
+    }
+
+    /// Send a Slack notification about a single pull request event
+    pub async fn send_pr_slack_notification(
+        webhook_url: &str,
+        channel: Option<&str>,
+        notification: &PrNotification,
+    ) -> Result<(), NotificationError> {
+        let emoji = match notification.kind {
+            NotificationKind::PrReady => "🟢",
+            NotificationKind::PrFailed => "🔴",
+            NotificationKind::ReviewRequested => "👀",
+            NotificationKind::MergeSucceeded => "✅",
+            NotificationKind::MergeFailed => "⚠️",
+            NotificationKind::RemediationCompleted => "🛠️",
+        };
+        let mut text = format!(
+            "{} *{}*\n<{}|Open pull request> by {}",
+            emoji,
+            notification.headline(),
+            notification.pr_url,
+            notification.author
+        );
+        if let Some(reviewer) = &notification.reviewer {
+            text.push_str(&format!("\nReviewer: {}", reviewer));
+        }
+
+        Self::post_slack_message(webhook_url, channel, text).await
+    }
+
+    async fn post_slack_message(
+        webhook_url: &str,
+        channel: Option<&str>,
+        text: String,
+    ) -> Result<(), NotificationError> {
         let mut message = serde_json::json!({ "text": text });
         if let Some(ch) = channel {
             message["channel"] = serde_json::json!(ch);
@@ -106,10 +146,7 @@ impl NotificationService {
         to_emails: &[String],
         payload: &MergeNotificationPayload,
     ) -> Result<(), NotificationError> {
-        use lettre::{
-            message::header::ContentType, transport::smtp::authentication::Credentials, Message,
-            SmtpTransport, Transport,
-        };
+        use lettre::{message::header::ContentType, Message, Transport};
 
         if to_emails.is_empty() {
             return Err(NotificationError::Config(
@@ -173,20 +210,7 @@ impl NotificationService {
 
         body.push_str("\n\n--\nSent by Ampel PR Manager");
 
-        let creds = Credentials::new(config.username.clone(), config.password.clone());
-
-        let mailer = if config.use_tls {
-            SmtpTransport::starttls_relay(&config.host)
-                .map_err(|e| NotificationError::Email(format!("Invalid SMTP host: {}", e)))?
-                .port(config.port)
-                .credentials(creds)
-                .build()
-        } else {
-            SmtpTransport::builder_dangerous(&config.host)
-                .port(config.port)
-                .credentials(creds)
-                .build()
-        };
+        let mailer = Self::mailer(config)?;
 
         // Send to first recipient (others as CC would require more complex setup)
         let email = Message::builder()
@@ -210,4 +234,151 @@ impl NotificationService {
 
         Ok(())
     }
+
+    /// Send an email notification about a single pull request event
+    pub async fn send_pr_email_notification(
+        config: &SmtpConfig,
+        to_emails: &[String],
+        notification: &PrNotification,
+    ) -> Result<(), NotificationError> {
+        use lettre::{message::header::ContentType, Message, Transport};
+
+        let Some(to_email) = to_emails.first() else {
+            return Err(NotificationError::Config(
+                "No recipient emails configured".into(),
+            ));
+        };
+
+        let mut body = format!(
+            "{}\n\nAuthor: {}\n",
+            notification.headline(),
+            notification.author
+        );
+        if let Some(reviewer) = &notification.reviewer {
+            body.push_str(&format!("Reviewer: {}\n", reviewer));
+        }
+        body.push_str(&format!(
+            "\nOpen the pull request:\n{}\n\n--\nSent by Ampel PR Manager",
+            notification.pr_url
+        ));
+
+        let email = Message::builder()
+            .from(
+                config
+                    .from_email
+                    .parse()
+                    .map_err(|_| NotificationError::Email("Invalid from email".into()))?,
+            )
+            .to(to_email
+                .parse()
+                .map_err(|_| NotificationError::Email("Invalid recipient email".into()))?)
+            .subject(format!("Ampel: {}", notification.headline()))
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| NotificationError::Email(format!("Failed to build email: {}", e)))?;
+
+        Self::mailer(config)?
+            .send(&email)
+            .map_err(|e| NotificationError::Email(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Send an invitation to join an organization
+    pub async fn send_invitation_email(
+        config: &SmtpConfig,
+        to_email: &str,
+        organization_name: &str,
+        role: &str,
+        accept_url: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), NotificationError> {
+        use lettre::{message::header::ContentType, Message, Transport};
+
+        let body = format!(
+            "You have been invited to join {organization_name} on Ampel as {role}.\n\n\
+             Accept the invitation:\n{accept_url}\n\n\
+             The link expires on {} UTC. If you did not expect this invitation, ignore this email.\n\n\
+             --\nSent by Ampel PR Manager",
+            expires_at.format("%Y-%m-%d %H:%M")
+        );
+
+        let email = Message::builder()
+            .from(
+                config
+                    .from_email
+                    .parse()
+                    .map_err(|_| NotificationError::Email("Invalid from email".into()))?,
+            )
+            .to(to_email
+                .parse()
+                .map_err(|_| NotificationError::Email("Invalid recipient email".into()))?)
+            .subject(format!("Ampel: invitation to join {organization_name}"))
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| NotificationError::Email(format!("Failed to build email: {}", e)))?;
+
+        Self::mailer(config)?
+            .send(&email)
+            .map_err(|e| NotificationError::Email(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Send a digest email with plain-text and HTML alternatives
+    pub async fn send_digest_email(
+        config: &SmtpConfig,
+        to_emails: &[String],
+        subject: &str,
+        text_body: String,
+        html_body: String,
+    ) -> Result<(), NotificationError> {
+        use lettre::{message::MultiPart, Message, Transport};
+
+        let Some(to_email) = to_emails.first() else {
+            return Err(NotificationError::Config(
+                "No recipient emails configured".into(),
+            ));
+        };
+
+        let email = Message::builder()
+            .from(
+                config
+                    .from_email
+                    .parse()
+                    .map_err(|_| NotificationError::Email("Invalid from email".into()))?,
+            )
+            .to(to_email
+                .parse()
+                .map_err(|_| NotificationError::Email("Invalid recipient email".into()))?)
+            .subject(subject)
+            .multipart(MultiPart::alternative_plain_html(text_body, html_body))
+            .map_err(|e| NotificationError::Email(format!("Failed to build email: {}", e)))?;
+
+        Self::mailer(config)?
+            .send(&email)
+            .map_err(|e| NotificationError::Email(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+
+    fn mailer(config: &SmtpConfig) -> Result<lettre::SmtpTransport, NotificationError> {
+        use lettre::{transport::smtp::authentication::Credentials, SmtpTransport};
+
+        let creds = Credentials::new(config.username.clone(), config.password.clone());
+
+        let mailer = if config.use_tls {
+            SmtpTransport::starttls_relay(&config.host)
+                .map_err(|e| NotificationError::Email(format!("Invalid SMTP host: {}", e)))?
+                .port(config.port)
+                .credentials(creds)
+                .build()
+        } else {
+            SmtpTransport::builder_dangerous(&config.host)
+                .port(config.port)
+                .credentials(creds)
+                .build()
+        };
+        Ok(mailer)
+    }
 }
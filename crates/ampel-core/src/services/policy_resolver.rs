@@ -5,8 +5,8 @@
 use crate::errors::{AmpelError, AmpelResult};
 use crate::remediation::db;
 use crate::remediation::{
-    AutonomyLevel, FailureClass, ModelSelectionMode, PrSelectionStrategy, ProviderKind,
-    RemediationCriteria, RemediationTier,
+    AutonomyLevel, ConsolidationStrategy, FailureClass, ModelSelectionMode, PrSelectionStrategy,
+    ProviderKind, RemediationCriteria, RemediationTier,
 };
 use crate::services::learning_signal::{bias_provider_chain, LearningStatsReader};
 use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
@@ -191,6 +191,7 @@ fn db_err(e: DbErr) -> AmpelError {
 fn to_criteria(p: db::remediation_policy::Model) -> AmpelResult<RemediationCriteria> {
     let autonomy_level: AutonomyLevel = p.autonomy_level.parse()?;
     let remediation_tier: RemediationTier = p.remediation_tier.parse()?;
+    let consolidation_strategy: ConsolidationStrategy = p.consolidation_strategy.parse()?;
 
     let pr_selection: PrSelectionStrategy = serde_json::from_str(&p.pr_selection)
         .map_err(|e| AmpelError::ValidationError(format!("invalid pr_selection: {e}")))?;
@@ -207,6 +208,7 @@ fn to_criteria(p: db::remediation_policy::Model) -> AmpelResult<RemediationCrite
         air_gapped: p.air_gapped,
         autonomy_level,
         remediation_tier,
+        consolidation_strategy,
     })
 }
 
@@ -1,4 +1,7 @@
-use crate::models::{AmpelStatus, CICheck, PullRequest, PullRequestWithDetails, Review};
+use crate::models::{
+    AmpelStatus, CICheck, PullRequest, PullRequestMetadata, PullRequestWithDetails, Review,
+    StatusPolicy,
+};
 
 /// Service for pull request-related business logic
 pub struct PrService;
@@ -8,22 +11,27 @@ impl PrService {
         Self
     }
 
-    /// Enrich a pull request with status and related data
+    /// Enrich a pull request with its status under `policy` and related data
+    #[allow(clippy::too_many_arguments)]
     pub fn with_details(
         &self,
         pull_request: PullRequest,
         ci_checks: Vec<CICheck>,
         reviews: Vec<Review>,
+        metadata: PullRequestMetadata,
         repository_owner: String,
         repository_name: String,
+        policy: &StatusPolicy,
     ) -> PullRequestWithDetails {
-        let status = AmpelStatus::for_pull_request(&pull_request, &ci_checks, &reviews);
+        let evaluation = AmpelStatus::evaluate(&pull_request, &ci_checks, &reviews, policy);
 
         PullRequestWithDetails {
             pull_request,
-            status,
+            status: evaluation.status,
+            reasons: evaluation.reasons.into_iter().map(Into::into).collect(),
             ci_checks,
             reviews,
+            metadata,
             repository_name,
             repository_owner,
         }
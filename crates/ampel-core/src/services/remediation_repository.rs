@@ -112,6 +112,16 @@ pub trait RemediationRunRepository: Send + Sync {
     /// Used by `finalize` to stay idempotent across re-entry: a partial close
     /// failure can be re-run without double-closing PRs already handled.
     async fn closed_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>>;
+
+    /// Source PR numbers recorded as skipped over a merge conflict, or left
+    /// open for another reason, for this run. Their commits are not in the
+    /// consolidated PR, so `finalize` leaves them open.
+    async fn skipped_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>>;
+
+    /// Source PR numbers recorded as merged or closed outside this run. There
+    /// is nothing left for `finalize` to close, and the run did not supersede
+    /// them.
+    async fn externally_closed_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>>;
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -268,6 +278,50 @@ mod in_memory {
                 })
                 .unwrap_or_default())
         }
+
+        async fn skipped_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>> {
+            Ok(self
+                .inner
+                .lock()
+                .unwrap()
+                .dispositions
+                .get(&run_id)
+                .map(|ds| {
+                    ds.iter()
+                        .filter(|(_, d)| {
+                            matches!(
+                                d,
+                                MergeDisposition::SkippedConflict { .. }
+                                    | MergeDisposition::LeftOpen { .. }
+                            )
+                        })
+                        .map(|(pr, _)| *pr)
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+
+        async fn externally_closed_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>> {
+            Ok(self
+                .inner
+                .lock()
+                .unwrap()
+                .dispositions
+                .get(&run_id)
+                .map(|ds| {
+                    ds.iter()
+                        .filter(|(_, d)| {
+                            matches!(
+                                d,
+                                MergeDisposition::MergedExternally
+                                    | MergeDisposition::ClosedExternally
+                            )
+                        })
+                        .map(|(pr, _)| *pr)
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
     }
 }
 
@@ -440,6 +494,7 @@ mod tests {
             2,
             MergeDisposition::SkippedConflict {
                 reason: "lockfile".into(),
+                files: vec!["Cargo.lock".into()],
             },
         )
         .await
@@ -7,9 +7,11 @@
 //! [`AutonomyLevel::allows_writes`]).
 
 use crate::errors::{AmpelError, AmpelResult};
+use crate::models::PullRequestState;
 use crate::remediation::db;
 use crate::remediation::{
-    AutonomyLevel, ConsolidationPlan, PrRef, PrSelectionStrategy, RemediationCriteria, RunState,
+    AutonomyLevel, ConsolidationPlan, ConsolidationStrategy, MergeDisposition, PrRef,
+    PrSelectionStrategy, RemediationCriteria, RunState, SuggestedChange,
 };
 use crate::services::{
     CiVerificationResult, ConsolidationOutcome, ConsolidationSpec, CredentialHandle,
@@ -164,6 +166,34 @@ pub trait RemediationProvider: Send + Sync {
 
     /// Close a source PR, posting `comment` (e.g. "Superseded by #N").
     async fn close_pull_request(&self, pr_number: i64, comment: &str) -> AmpelResult<()>;
+
+    /// Whether the PR is open, merged, or closed unmerged.
+    async fn pull_request_state(&self, pr_number: i64) -> AmpelResult<PullRequestState>;
+
+    /// Open the consolidated PR from `head` into `base`. Returns its number.
+    async fn open_pull_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> AmpelResult<i64>;
+
+    /// The open PR whose head is `branch`, if any.
+    async fn find_open_pull_request(&self, branch: &str) -> AmpelResult<Option<i64>>;
+
+    /// Post `body` as a comment on a PR.
+    async fn comment_on_pull_request(&self, pr_number: i64, body: &str) -> AmpelResult<()>;
+
+    /// The bodies of the comments on a PR, oldest first.
+    async fn list_comments(&self, pr_number: i64) -> AmpelResult<Vec<String>>;
+
+    /// Propose `suggestions` on a PR without pushing anything.
+    async fn suggest_changes(
+        &self,
+        pr_number: i64,
+        suggestions: &[SuggestedChange],
+    ) -> AmpelResult<()>;
 }
 
 /// Repository coordinates needed to drive a sandbox consolidation.
@@ -201,6 +231,15 @@ pub enum MergeOutcome {
     },
 }
 
+/// Outcome of [`RemediationOrchestrator::reconcile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reconciliation {
+    /// Every completed step is accounted for; continue from this state.
+    Resume(RunState),
+    /// The run could not be continued safely and was moved to `failed`.
+    Failed { reason: String },
+}
+
 /// Drives a single remediation run through the Phase-2 state machine using
 /// injected collaborators. All persistence flows through CAS transitions, so a
 /// lost race surfaces as an error rather than a silently dropped write.
@@ -210,6 +249,7 @@ pub struct RemediationOrchestrator {
     sandbox: Arc<dyn SandboxRunner>,
     verification: VerificationService,
     provider: Arc<dyn RemediationProvider>,
+    consolidation_strategy: ConsolidationStrategy,
 }
 
 impl RemediationOrchestrator {
@@ -224,15 +264,26 @@ impl RemediationOrchestrator {
             sandbox,
             verification,
             provider,
+            consolidation_strategy: ConsolidationStrategy::default(),
         }
     }
 
+    /// Consolidate with the policy's `strategy` instead of the default `merge`.
+    pub fn with_consolidation_strategy(mut self, strategy: ConsolidationStrategy) -> Self {
+        self.consolidation_strategy = strategy;
+        self
+    }
+
     /// Consolidate the selected PRs for `run_id`.
     ///
     /// `DryRunOnly`/`SuggestOnly` autonomy short-circuits to `no_op` with zero
     /// sandbox or provider writes. Otherwise: `created`→`selecting`→
-    /// `consolidating` (CAS), run the sandbox, persist plan + dispositions +
-    /// consolidated PR, then `consolidating`→`verifying`.
+    /// `consolidating` (CAS), run the sandbox, persist plan + dispositions,
+    /// open the consolidated PR from the pushed branch unless the sandbox
+    /// already did, record it, then `consolidating`→`verifying`.
+    ///
+    /// When every PR was skipped over conflicts there is nothing to open: the
+    /// dispositions are kept and an error is returned, which fails the run.
     pub async fn consolidate(
         &self,
         run_id: Uuid,
@@ -266,27 +317,45 @@ impl RemediationOrchestrator {
         )
         .await?;
 
+        let default_branch = repo.default_branch.clone();
         let spec = ConsolidationSpec::new(
             run.id,
             repo.clone_url,
             repo.default_branch,
             prs.clone(),
             repo.credential,
-        );
-        let outcome = self.sandbox.run_consolidation(spec).await?;
+        )
+        .with_strategy(self.consolidation_strategy);
+        let mut outcome = self.sandbox.run_consolidation(spec).await?;
 
         // Persist what the sandbox produced.
         self.repo
-            .set_consolidation_plan(run.id, ConsolidationPlan::from_selection(prs, false))
+            .set_consolidation_plan(
+                run.id,
+                ConsolidationPlan::from_selection(prs.clone(), false),
+            )
             .await?;
         for (pr_number, disposition) in &outcome.dispositions {
             self.repo
                 .record_disposition(run.id, *pr_number, disposition.clone())
                 .await?;
         }
-        if let Some(pr) = outcome.consolidated_pr_number {
-            self.repo.set_consolidated_pr(run.id, pr).await?;
-        }
+        let (title, body) = consolidated_pr_text(&prs, &outcome.dispositions).ok_or_else(|| {
+            AmpelError::ValidationError(format!(
+                "no PR could be consolidated: {}",
+                skipped_summary(&outcome.dispositions)
+            ))
+        })?;
+        let pr = match outcome.consolidated_pr_number {
+            Some(pr) => pr,
+            None => {
+                self.provider
+                    .open_pull_request(&outcome.branch_name, &default_branch, &title, &body)
+                    .await?
+            }
+        };
+        self.repo.set_consolidated_pr(run.id, pr).await?;
+        outcome.consolidated_pr_number = Some(pr);
 
         // consolidating -> verifying, anchoring the consolidated HEAD SHA.
         self.cas(
@@ -440,7 +509,10 @@ impl RemediationOrchestrator {
     /// Close each source PR with a "Superseded by #N" comment, then transition
     /// `finalizing`→`completed`. Per-PR `Consolidated` dispositions were already
     /// recorded during `consolidate`; this only performs the close + comment.
-    pub async fn finalize(&self, run_id: Uuid, source_prs: &[i64]) -> AmpelResult<()> {
+    /// PRs skipped over a merge conflict are not in the consolidated PR and stay
+    /// open; PRs merged or closed outside the run are left as they are. Returns
+    /// the source PRs the run superseded.
+    pub async fn finalize(&self, run_id: Uuid, source_prs: &[i64]) -> AmpelResult<Vec<i64>> {
         let run = self.load(run_id).await?;
 
         // Guard the irreversible side-effect: refuse to close any source PR
@@ -454,12 +526,19 @@ impl RemediationOrchestrator {
         }
 
         let consolidated = self.consolidated_pr(&run)?;
-        let comment = format!("Superseded by #{consolidated}");
+        let comment = superseded_comment(consolidated);
 
         // M4: re-entrant + idempotent. Skip PRs already recorded as closed so a
         // partial-close failure is recoverable on re-run without double-closing.
         let already_closed = self.repo.closed_source_prs(run.id).await?;
-        for &pr in source_prs {
+        let skipped = self.repo.skipped_source_prs(run.id).await?;
+        let external = self.repo.externally_closed_source_prs(run.id).await?;
+        let superseded: Vec<i64> = source_prs
+            .iter()
+            .copied()
+            .filter(|pr| !skipped.contains(pr) && !external.contains(pr))
+            .collect();
+        for &pr in &superseded {
             if already_closed.contains(&pr) {
                 continue;
             }
@@ -470,7 +549,7 @@ impl RemediationOrchestrator {
                 .record_disposition(
                     run.id,
                     pr,
-                    crate::remediation::MergeDisposition::ClosedWithRef {
+                    MergeDisposition::ClosedWithRef {
                         consolidated_pr_number: consolidated as u64,
                     },
                 )
@@ -483,7 +562,158 @@ impl RemediationOrchestrator {
             RunUpdate::none(),
         )
         .await?;
-        Ok(())
+        Ok(superseded)
+    }
+
+    /// Settle a run found past `created` — typically one whose worker died —
+    /// against the persisted `consolidated_pr_number`/`head_sha` and the
+    /// provider, so the next step neither repeats nor skips an irreversible
+    /// action. Every move is a CAS, so reconciling twice is harmless.
+    ///
+    /// - `consolidating`: adopt the consolidated PR (recorded, or found open on
+    ///   the run's branch) and continue at `verifying`, which re-anchors the
+    ///   head SHA. Without an open one the run fails — repeating the sandbox
+    ///   could open a second PR.
+    /// - `agent_fixing`: roll back to `verifying`; a fresh verify judges
+    ///   whatever the interrupted fix pushed.
+    /// - `merging`: a consolidated PR that is already merged advances to
+    ///   `finalizing` without merging again; one closed unmerged fails the run.
+    ///   An open one continues at [`Self::do_merge`], whose re-verify compares
+    ///   against the persisted `head_sha`.
+    /// - `finalizing`: a source PR closed unmerged with this run's "Superseded
+    ///   by" comment is written to the close ledger, so [`Self::finalize`] does
+    ///   not close it twice. One merged or closed any other way was settled
+    ///   outside the run and is recorded as such.
+    /// - Any other state involves no provider write and is resumed as-is.
+    ///
+    /// A run failed here never orphans its consolidated PR: one still open is
+    /// closed, and the failure reason names it.
+    pub async fn reconcile(&self, run_id: Uuid, source_prs: &[i64]) -> AmpelResult<Reconciliation> {
+        let run = self.load(run_id).await?;
+        match run.state {
+            RunState::Consolidating => self.reconcile_consolidating(&run).await,
+            RunState::AgentFixing => {
+                self.cas(
+                    run.id,
+                    RunState::AgentFixing,
+                    RunState::Verifying,
+                    RunUpdate::none(),
+                )
+                .await?;
+                Ok(Reconciliation::Resume(RunState::Verifying))
+            }
+            RunState::Merging => {
+                let pr = self.consolidated_pr(&run)?;
+                match self.provider.pull_request_state(pr).await? {
+                    PullRequestState::Open => Ok(Reconciliation::Resume(RunState::Merging)),
+                    PullRequestState::Merged => {
+                        self.cas(
+                            run.id,
+                            RunState::Merging,
+                            RunState::Finalizing,
+                            RunUpdate::none(),
+                        )
+                        .await?;
+                        Ok(Reconciliation::Resume(RunState::Finalizing))
+                    }
+                    PullRequestState::Closed => {
+                        self.fail(
+                            &run,
+                            format!("consolidated PR #{pr} was closed without merging"),
+                        )
+                        .await
+                    }
+                }
+            }
+            RunState::Finalizing => {
+                let consolidated = self.consolidated_pr(&run)?;
+                let comment = superseded_comment(consolidated);
+                let already_closed = self.repo.closed_source_prs(run.id).await?;
+                let external = self.repo.externally_closed_source_prs(run.id).await?;
+                for &pr in source_prs {
+                    if already_closed.contains(&pr) || external.contains(&pr) {
+                        continue;
+                    }
+                    let disposition = match self.provider.pull_request_state(pr).await? {
+                        PullRequestState::Open => continue,
+                        PullRequestState::Merged => MergeDisposition::MergedExternally,
+                        // Closed with our reference: the close landed and the
+                        // worker died before recording it.
+                        PullRequestState::Closed => {
+                            if self.provider.list_comments(pr).await?.contains(&comment) {
+                                MergeDisposition::ClosedWithRef {
+                                    consolidated_pr_number: consolidated as u64,
+                                }
+                            } else {
+                                MergeDisposition::ClosedExternally
+                            }
+                        }
+                    };
+                    self.repo
+                        .record_disposition(run.id, pr, disposition)
+                        .await?;
+                }
+                Ok(Reconciliation::Resume(RunState::Finalizing))
+            }
+            state => Ok(Reconciliation::Resume(state)),
+        }
+    }
+
+    async fn reconcile_consolidating(&self, run: &RemediationRun) -> AmpelResult<Reconciliation> {
+        let pr = match run.consolidated_pr_number {
+            Some(pr) => Some(pr),
+            None => {
+                self.provider
+                    .find_open_pull_request(&ConsolidationSpec::branch_name_for(run.id))
+                    .await?
+            }
+        };
+        let Some(pr) = pr else {
+            return self
+                .fail(
+                    run,
+                    "interrupted mid-consolidation before a consolidated PR was opened".into(),
+                )
+                .await;
+        };
+        if self.provider.pull_request_state(pr).await? != PullRequestState::Open {
+            return self
+                .fail(run, format!("consolidated PR #{pr} is no longer open"))
+                .await;
+        }
+        self.cas(
+            run.id,
+            RunState::Consolidating,
+            RunState::Verifying,
+            RunUpdate {
+                consolidated_pr_number: Some(pr),
+                ..RunUpdate::none()
+            },
+        )
+        .await?;
+        Ok(Reconciliation::Resume(RunState::Verifying))
+    }
+
+    /// Move `run` to `failed`. A consolidated PR that is still open would be
+    /// orphaned, so it is closed first and the failure reason says so.
+    async fn fail(&self, run: &RemediationRun, reason: String) -> AmpelResult<Reconciliation> {
+        let mut reason = reason;
+        if let Some(pr) = run.consolidated_pr_number {
+            if self.provider.pull_request_state(pr).await? == PullRequestState::Open {
+                self.provider
+                    .close_pull_request(pr, &format!("Remediation run failed: {reason}"))
+                    .await?;
+                reason = format!("{reason}; closed consolidated PR #{pr}");
+            }
+        }
+        self.cas(
+            run.id,
+            run.state,
+            RunState::Failed,
+            RunUpdate::with_error_message(reason.clone()),
+        )
+        .await?;
+        Ok(Reconciliation::Failed { reason })
     }
 
     async fn load(&self, id: Uuid) -> AmpelResult<RemediationRun> {
@@ -517,11 +747,71 @@ impl RemediationOrchestrator {
     }
 }
 
+/// Title and body of the consolidated PR, listing the PRs it combines and the
+/// ones skipped over conflicts. `None` when no PR was consolidated.
+/// The comment a superseded source PR is closed with; its presence marks a
+/// close made by the run.
+fn superseded_comment(consolidated_pr: i64) -> String {
+    format!("Superseded by #{consolidated_pr}")
+}
+
+fn consolidated_pr_text(
+    prs: &[PrRef],
+    dispositions: &[(i64, MergeDisposition)],
+) -> Option<(String, String)> {
+    let title_of = |number: i64| {
+        prs.iter()
+            .find(|p| p.number as i64 == number)
+            .map_or(String::new(), |p| format!(" {}", p.title))
+    };
+    let mut combined = String::new();
+    let mut skipped = String::new();
+    let mut count = 0;
+    for (number, disposition) in dispositions {
+        match disposition {
+            MergeDisposition::Consolidated => {
+                count += 1;
+                combined.push_str(&format!("- #{number}{}\n", title_of(*number)));
+            }
+            MergeDisposition::SkippedConflict { reason, .. }
+            | MergeDisposition::LeftOpen { reason } => {
+                skipped.push_str(&format!("- #{number}{}: {reason}\n", title_of(*number)));
+            }
+            _ => {}
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+
+    let title = format!(
+        "Ampel remediation: consolidate {count} PR{}",
+        if count == 1 { "" } else { "s" }
+    );
+    let mut body = format!("Consolidates the following pull requests:\n\n{combined}");
+    if !skipped.is_empty() {
+        body.push_str(&format!("\nLeft open:\n\n{skipped}"));
+    }
+    Some((title, body))
+}
+
+/// `#N: reason` for each skipped PR, for the failure message of a run that
+/// could consolidate nothing.
+fn skipped_summary(dispositions: &[(i64, MergeDisposition)]) -> String {
+    dispositions
+        .iter()
+        .filter_map(|(number, d)| d.reason().map(|reason| format!("#{number}: {reason}")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::remediation::testkit;
-    use crate::remediation::{AutonomyLevel, MergeDisposition, RemediationTier};
+    use crate::remediation::{
+        AutonomyLevel, ConsolidationStrategy, MergeDisposition, RemediationTier,
+    };
 
     fn criteria(strategy: PrSelectionStrategy, skip_draft: bool, max: i32) -> RemediationCriteria {
         RemediationCriteria {
@@ -534,6 +824,7 @@ mod tests {
             air_gapped: false,
             autonomy_level: AutonomyLevel::DryRunOnly,
             remediation_tier: RemediationTier::ConsolidateOnly,
+            consolidation_strategy: ConsolidationStrategy::Merge,
         }
     }
 
@@ -773,9 +1064,12 @@ mod tests {
     // -----------------------------------------------------------------------
 
     use crate::services::{FakeSandboxRunner, InMemoryRemediationRunRepository, ProviderRefStatus};
-    use std::collections::VecDeque;
+    use std::collections::{HashMap, VecDeque};
     use std::sync::Mutex;
 
+    /// The number the mock provider gives a PR opened through it.
+    const OPENED_PR: i64 = 4242;
+
     /// In-process [`RemediationProvider`] mock.
     ///
     /// `shas` is consumed front-to-back across `get_status_for_ref` calls (the
@@ -791,6 +1085,14 @@ mod tests {
         closed: Mutex<Vec<(i64, String)>>,
         /// PRs whose *next* close attempt should fail once (then succeed).
         fail_close_once: Mutex<HashSet<i64>>,
+        /// Provider-side PR states; unlisted PRs are open.
+        pr_states: Mutex<HashMap<i64, PullRequestState>>,
+        /// Open PRs by head branch.
+        branch_prs: Mutex<HashMap<String, i64>>,
+        /// `(head, base, title, body)` of each PR opened through the mock.
+        opened: Mutex<Vec<(String, String, String, String)>>,
+        /// Comments already on each PR, for `list_comments`.
+        comments: Mutex<HashMap<i64, Vec<String>>>,
     }
 
     impl MockProvider {
@@ -804,9 +1106,30 @@ mod tests {
                 merge_calls: Mutex::new(0),
                 closed: Mutex::new(Vec::new()),
                 fail_close_once: Mutex::new(HashSet::new()),
+                pr_states: Mutex::new(HashMap::new()),
+                branch_prs: Mutex::new(HashMap::new()),
+                opened: Mutex::new(Vec::new()),
+                comments: Mutex::new(HashMap::new()),
             }
         }
 
+        fn set_pr_state(&self, pr: i64, state: PullRequestState) {
+            self.pr_states.lock().unwrap().insert(pr, state);
+        }
+
+        fn add_comment(&self, pr: i64, body: &str) {
+            self.comments
+                .lock()
+                .unwrap()
+                .entry(pr)
+                .or_default()
+                .push(body.to_string());
+        }
+
+        fn open_pr_on_branch(&self, branch: String, pr: i64) {
+            self.branch_prs.lock().unwrap().insert(branch, pr);
+        }
+
         /// Arrange for the next `close_pull_request(pr)` to fail exactly once.
         fn fail_next_close(&self, pr: i64) {
             self.fail_close_once.lock().unwrap().insert(pr);
@@ -833,6 +1156,10 @@ mod tests {
         fn closed_prs(&self) -> Vec<(i64, String)> {
             self.closed.lock().unwrap().clone()
         }
+
+        fn opened_prs(&self) -> Vec<(String, String, String, String)> {
+            self.opened.lock().unwrap().clone()
+        }
     }
 
     #[async_trait]
@@ -864,6 +1191,59 @@ mod tests {
                 .push((pr_number, comment.to_string()));
             Ok(())
         }
+
+        async fn pull_request_state(&self, pr_number: i64) -> AmpelResult<PullRequestState> {
+            Ok(self
+                .pr_states
+                .lock()
+                .unwrap()
+                .get(&pr_number)
+                .copied()
+                .unwrap_or(PullRequestState::Open))
+        }
+
+        async fn open_pull_request(
+            &self,
+            head: &str,
+            base: &str,
+            title: &str,
+            body: &str,
+        ) -> AmpelResult<i64> {
+            self.opened.lock().unwrap().push((
+                head.to_string(),
+                base.to_string(),
+                title.to_string(),
+                body.to_string(),
+            ));
+            self.open_pr_on_branch(head.to_string(), OPENED_PR);
+            Ok(OPENED_PR)
+        }
+
+        async fn find_open_pull_request(&self, branch: &str) -> AmpelResult<Option<i64>> {
+            Ok(self.branch_prs.lock().unwrap().get(branch).copied())
+        }
+
+        async fn comment_on_pull_request(&self, _pr_number: i64, _body: &str) -> AmpelResult<()> {
+            Ok(())
+        }
+
+        async fn list_comments(&self, pr_number: i64) -> AmpelResult<Vec<String>> {
+            Ok(self
+                .comments
+                .lock()
+                .unwrap()
+                .get(&pr_number)
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        async fn suggest_changes(
+            &self,
+            _pr_number: i64,
+            _suggestions: &[SuggestedChange],
+        ) -> AmpelResult<()> {
+            Ok(())
+        }
     }
 
     fn pr_ref(n: i32) -> PrRef {
@@ -942,6 +1322,121 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn should_hand_policy_consolidation_strategy_to_sandbox() {
+        // Arrange
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let sandbox = Arc::new(FakeSandboxRunner::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(repo.clone(), sandbox.clone(), provider)
+            .with_consolidation_strategy(ConsolidationStrategy::SquashPerPr);
+        let run = repo
+            .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+            .await
+            .unwrap();
+
+        // Act
+        orch.consolidate(run.id, vec![pr_ref(1)], repo_ctx())
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            sandbox.last_spec().unwrap().strategy,
+            ConsolidationStrategy::SquashPerPr
+        );
+    }
+
+    #[tokio::test]
+    async fn should_open_consolidated_pr_and_leave_conflicting_pr_open() {
+        // Arrange: the sandbox pushes the branch but opens no PR; PR 2 conflicts
+        // outside lockfiles, PR 3 only on its lockfile.
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let sandbox = Arc::new(
+            FakeSandboxRunner::with_outcome(None, "headsha")
+                .with_conflict(2, &["src/lib.rs"])
+                .with_conflict(3, &["Cargo.lock"]),
+        );
+        let provider = Arc::new(MockProvider::green(&["headsha", "headsha"]));
+        let orch = orchestrator(repo.clone(), sandbox.clone(), provider.clone());
+        let run = repo
+            .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+            .await
+            .unwrap();
+
+        // Act
+        orch.consolidate(run.id, vec![pr_ref(1), pr_ref(2), pr_ref(3)], repo_ctx())
+            .await
+            .unwrap();
+        orch.verify(run.id).await.unwrap();
+        orch.do_merge(run.id).await.unwrap();
+        let superseded = orch.finalize(run.id, &[1, 2, 3]).await.unwrap();
+
+        // Assert: PR 2 skipped with its files, PR 3 regenerated and kept.
+        assert_eq!(
+            repo.dispositions_for(run.id)[..3],
+            [
+                (1, MergeDisposition::Consolidated),
+                (
+                    2,
+                    MergeDisposition::SkippedConflict {
+                        reason: "merge conflict in src/lib.rs".to_string(),
+                        files: vec!["src/lib.rs".to_string()],
+                    }
+                ),
+                (3, MergeDisposition::Consolidated),
+            ]
+        );
+        assert!(sandbox
+            .git_commands()
+            .contains(&"cargo generate-lockfile".to_string()));
+        let opened = provider.opened_prs();
+        assert_eq!(opened.len(), 1);
+        let (head, base, title, body) = &opened[0];
+        assert_eq!(head, &ConsolidationSpec::branch_name_for(run.id));
+        assert_eq!(base, "main");
+        assert_eq!(title, "Ampel remediation: consolidate 2 PRs");
+        assert!(body.contains("- #1 PR 1\n- #3 PR 3\n"));
+        assert!(body.contains("- #2 PR 2: merge conflict in src/lib.rs"));
+        let run = repo.get_run(run.id).await.unwrap().unwrap();
+        assert_eq!(run.consolidated_pr_number, Some(OPENED_PR));
+        assert_eq!(run.state, RunState::Completed);
+        assert_eq!(superseded, vec![1, 3]);
+        let closed: Vec<i64> = provider.closed_prs().iter().map(|(n, _)| *n).collect();
+        assert_eq!(closed, vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn should_fail_consolidation_when_every_pr_conflicts() {
+        // Arrange
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let sandbox = Arc::new(FakeSandboxRunner::new().with_conflict(1, &["svc/go.mod"]));
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(repo.clone(), sandbox, provider.clone());
+        let run = repo
+            .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+            .await
+            .unwrap();
+
+        // Act
+        let result = orch.consolidate(run.id, vec![pr_ref(1)], repo_ctx()).await;
+
+        // Assert: no PR opened, the skip still recorded.
+        let err = result.err().expect("nothing to consolidate");
+        assert!(err.to_string().contains("#1: merge conflict in svc/go.mod"));
+        assert!(provider.opened_prs().is_empty());
+        assert_eq!(
+            repo.dispositions_for(run.id),
+            vec![(
+                1,
+                MergeDisposition::SkippedConflict {
+                    reason: "merge conflict in svc/go.mod".to_string(),
+                    files: vec!["svc/go.mod".to_string()],
+                }
+            )]
+        );
+    }
+
     #[tokio::test]
     async fn should_no_op_under_dry_run_only_with_zero_writes() {
         // Arrange
@@ -1306,4 +1801,272 @@ mod tests {
         );
         assert_eq!(provider.merge_call_count(), 0);
     }
+
+    /// Walk a fresh run through `path` with bare transitions, as a worker that
+    /// died partway would have left it.
+    async fn park(
+        repo: &InMemoryRemediationRunRepository,
+        autonomy: AutonomyLevel,
+        path: &[(RunState, RunState, RunUpdate)],
+    ) -> Uuid {
+        let run = repo.create_run(Uuid::new_v4(), autonomy).await.unwrap();
+        for (from, to, update) in path {
+            assert!(repo
+                .transition_state(run.id, *from, *to, update.clone())
+                .await
+                .unwrap());
+        }
+        run.id
+    }
+
+    fn to_consolidating() -> Vec<(RunState, RunState, RunUpdate)> {
+        vec![
+            (RunState::Created, RunState::Selecting, RunUpdate::none()),
+            (
+                RunState::Selecting,
+                RunState::Consolidating,
+                RunUpdate::none(),
+            ),
+        ]
+    }
+
+    fn to_merging() -> Vec<(RunState, RunState, RunUpdate)> {
+        let mut path = to_consolidating();
+        path.push((
+            RunState::Consolidating,
+            RunState::Verifying,
+            RunUpdate {
+                consolidated_pr_number: Some(9001),
+                head_sha: Some("headsha".into()),
+                ..RunUpdate::none()
+            },
+        ));
+        path.push((RunState::Verifying, RunState::Merging, RunUpdate::none()));
+        path
+    }
+
+    #[tokio::test]
+    async fn should_adopt_consolidated_pr_found_on_run_branch_when_reconciling() {
+        // Arrange: the sandbox opened the PR but the worker died before recording it.
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let sandbox = Arc::new(FakeSandboxRunner::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(repo.clone(), sandbox.clone(), provider.clone());
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &to_consolidating()).await;
+        provider.open_pr_on_branch(ConsolidationSpec::branch_name_for(run_id), 9001);
+
+        // Act
+        let outcome = orch.reconcile(run_id, &[1]).await.unwrap();
+
+        // Assert: continues at verify with the PR recorded; no second sandbox run.
+        assert_eq!(outcome, Reconciliation::Resume(RunState::Verifying));
+        let run = repo.get_run(run_id).await.unwrap().unwrap();
+        assert_eq!(run.state, RunState::Verifying);
+        assert_eq!(run.consolidated_pr_number, Some(9001));
+        assert!(!sandbox.was_invoked());
+    }
+
+    #[tokio::test]
+    async fn should_fail_interrupted_consolidation_without_a_pr_when_reconciling() {
+        // Arrange
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(repo.clone(), Arc::new(FakeSandboxRunner::new()), provider);
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &to_consolidating()).await;
+
+        // Act
+        let outcome = orch.reconcile(run_id, &[1]).await.unwrap();
+
+        // Assert
+        assert!(matches!(outcome, Reconciliation::Failed { .. }));
+        let run = repo.get_run(run_id).await.unwrap().unwrap();
+        assert_eq!(run.state, RunState::Failed);
+        assert!(run.error_message.unwrap().contains("mid-consolidation"));
+    }
+
+    #[tokio::test]
+    async fn should_close_open_consolidated_pr_when_failing_a_run() {
+        // Arrange
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(
+            repo.clone(),
+            Arc::new(FakeSandboxRunner::new()),
+            provider.clone(),
+        );
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &to_merging()).await;
+        let run = repo.get_run(run_id).await.unwrap().unwrap();
+
+        // Act
+        let outcome = orch.fail(&run, "lost".into()).await.unwrap();
+
+        // Assert: the open PR is not left behind, and the failure says so.
+        assert_eq!(
+            outcome,
+            Reconciliation::Failed {
+                reason: "lost; closed consolidated PR #9001".into()
+            }
+        );
+        assert_eq!(
+            provider.closed_prs(),
+            vec![(9001, "Remediation run failed: lost".to_string())]
+        );
+        assert_eq!(
+            repo.get_run(run_id).await.unwrap().unwrap().state,
+            RunState::Failed
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_merge_twice_when_consolidated_pr_already_merged() {
+        // Arrange: the merge landed, the worker died before `merging → finalizing`.
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(
+            repo.clone(),
+            Arc::new(FakeSandboxRunner::new()),
+            provider.clone(),
+        );
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &to_merging()).await;
+        provider.set_pr_state(9001, PullRequestState::Merged);
+
+        // Act: reconciling twice is harmless.
+        let outcome = orch.reconcile(run_id, &[1, 2]).await.unwrap();
+        let again = orch.reconcile(run_id, &[1, 2]).await.unwrap();
+        orch.finalize(run_id, &[1, 2]).await.unwrap();
+
+        // Assert: finalized without another merge call.
+        assert_eq!(outcome, Reconciliation::Resume(RunState::Finalizing));
+        assert_eq!(again, Reconciliation::Resume(RunState::Finalizing));
+        assert_eq!(provider.merge_call_count(), 0);
+        assert_eq!(
+            repo.get_run(run_id).await.unwrap().unwrap().state,
+            RunState::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_consolidated_pr_closed_unmerged_while_merging() {
+        // Arrange
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(
+            repo.clone(),
+            Arc::new(FakeSandboxRunner::new()),
+            provider.clone(),
+        );
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &to_merging()).await;
+        provider.set_pr_state(9001, PullRequestState::Closed);
+
+        // Act
+        let outcome = orch.reconcile(run_id, &[1]).await.unwrap();
+
+        // Assert
+        assert!(matches!(outcome, Reconciliation::Failed { .. }));
+        assert_eq!(
+            repo.get_run(run_id).await.unwrap().unwrap().state,
+            RunState::Failed
+        );
+        assert_eq!(provider.merge_call_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_not_close_source_pr_twice_when_close_was_not_recorded() {
+        // Arrange: #1 was closed on the provider, but the worker died before
+        // writing it to the close ledger.
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(
+            repo.clone(),
+            Arc::new(FakeSandboxRunner::new()),
+            provider.clone(),
+        );
+        let mut path = to_merging();
+        path.push((RunState::Merging, RunState::Finalizing, RunUpdate::none()));
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &path).await;
+        provider.set_pr_state(1, PullRequestState::Closed);
+        provider.add_comment(1, "Superseded by #9001");
+
+        // Act
+        let outcome = orch.reconcile(run_id, &[1, 2]).await.unwrap();
+        let superseded = orch.finalize(run_id, &[1, 2]).await.unwrap();
+
+        // Assert: #1 is recorded as closed by the run; only #2 is closed by the
+        // resumed finalize.
+        assert_eq!(outcome, Reconciliation::Resume(RunState::Finalizing));
+        assert!(repo.dispositions_for(run_id).contains(&(
+            1,
+            MergeDisposition::ClosedWithRef {
+                consolidated_pr_number: 9001
+            }
+        )));
+        let closed: Vec<i64> = provider.closed_prs().iter().map(|(n, _)| *n).collect();
+        assert_eq!(closed, vec![2]);
+        assert_eq!(superseded, vec![1, 2]);
+        assert_eq!(
+            repo.get_run(run_id).await.unwrap().unwrap().state,
+            RunState::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn should_record_source_prs_merged_or_closed_outside_the_run() {
+        // Arrange: while the worker was down, a human merged #1 and closed #2
+        // without the run's reference.
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(
+            repo.clone(),
+            Arc::new(FakeSandboxRunner::new()),
+            provider.clone(),
+        );
+        let mut path = to_merging();
+        path.push((RunState::Merging, RunState::Finalizing, RunUpdate::none()));
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &path).await;
+        provider.set_pr_state(1, PullRequestState::Merged);
+        provider.set_pr_state(2, PullRequestState::Closed);
+        provider.add_comment(2, "Closing, not needed anymore");
+
+        // Act
+        orch.reconcile(run_id, &[1, 2, 3]).await.unwrap();
+        let superseded = orch.finalize(run_id, &[1, 2, 3]).await.unwrap();
+
+        // Assert: no false `ClosedWithRef` audit record, and finalize neither
+        // touches nor claims the externally settled PRs.
+        let dispositions = repo.dispositions_for(run_id);
+        assert!(dispositions.contains(&(1, MergeDisposition::MergedExternally)));
+        assert!(dispositions.contains(&(2, MergeDisposition::ClosedExternally)));
+        assert!(!dispositions
+            .iter()
+            .any(|(pr, d)| *pr != 3 && matches!(d, MergeDisposition::ClosedWithRef { .. })));
+        let closed: Vec<i64> = provider.closed_prs().iter().map(|(n, _)| *n).collect();
+        assert_eq!(closed, vec![3]);
+        assert_eq!(superseded, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn should_roll_back_interrupted_agent_fix_to_verifying() {
+        // Arrange
+        let repo = Arc::new(InMemoryRemediationRunRepository::new());
+        let provider = Arc::new(MockProvider::green(&["headsha"]));
+        let orch = orchestrator(repo.clone(), Arc::new(FakeSandboxRunner::new()), provider);
+        let mut path = to_merging();
+        path.pop();
+        path.push((
+            RunState::Verifying,
+            RunState::AgentFixing,
+            RunUpdate::none(),
+        ));
+        let run_id = park(&repo, AutonomyLevel::FullyAutonomous, &path).await;
+
+        // Act
+        let outcome = orch.reconcile(run_id, &[1]).await.unwrap();
+
+        // Assert
+        assert_eq!(outcome, Reconciliation::Resume(RunState::Verifying));
+        assert_eq!(
+            repo.get_run(run_id).await.unwrap().unwrap().state,
+            RunState::Verifying
+        );
+    }
 }
@@ -2,16 +2,25 @@
 //!
 //! The mechanical "merge N PR branches in an isolated environment" work is
 //! expressed as the [`SandboxRunner`] trait. The real Podman-backed
-//! implementation is a later slice that lives in `ampel-worker` (it shells out
-//! to `git`/`podman`); `ampel-core` only owns the trait + value objects + a
-//! deterministic [`FakeSandboxRunner`] so the orchestration brain is fully
-//! unit-testable with no containers, subprocesses, or network.
+//! implementation lives in `ampel-worker` (it shells out to `git`/`podman`);
+//! `ampel-core` only owns the trait + value objects + a deterministic
+//! [`FakeSandboxRunner`] so the orchestration brain is fully unit-testable with
+//! no containers, subprocesses, or network.
+//!
+//! Both runners combine the PR branches through [`combine_prs`], which drives
+//! the pure [`GitStep`] plan against a [`GitWorkspace`]: the container runner's
+//! workspace shells out to `git` in the sandbox, the fake's plays back scripted
+//! conflicts. Conflict handling is therefore the same code in tests and in
+//! production.
 //!
 //! Credentials are carried in an opaque [`CredentialHandle`] whose `Debug` impl
 //! redacts the secret — a PAT must never reach a log line.
 
-use crate::errors::AmpelResult;
-use crate::remediation::{MergeDisposition, PrRef};
+use crate::errors::{AmpelError, AmpelResult};
+use crate::remediation::{
+    parse_conflicted_paths, resolve_conflict, skipped_conflict, ConflictResolution,
+    ConsolidationStrategy, GitStep, MergeDisposition, PrRef, MAX_CONFLICT_ROUNDS,
+};
 use async_trait::async_trait;
 use std::fmt;
 use uuid::Uuid;
@@ -21,17 +30,34 @@ use uuid::Uuid;
 /// Deliberately does **not** derive `Debug`/`Serialize`; the manual `Debug`
 /// redacts the value so accidental `{:?}` formatting can never leak the secret.
 #[derive(Clone)]
-pub struct CredentialHandle(String);
+pub struct CredentialHandle {
+    secret: String,
+    username: Option<String>,
+}
 
 impl CredentialHandle {
     pub fn new(secret: impl Into<String>) -> Self {
-        Self(secret.into())
+        Self {
+            secret: secret.into(),
+            username: None,
+        }
+    }
+
+    /// The HTTPS username git presents with the secret (e.g. the account of a
+    /// Bitbucket app password). Not secret, but kept out of `Debug` all the same.
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
     }
 
     /// Explicit, greppable accessor for the few places that genuinely need the
     /// plaintext (e.g. building a git askpass env inside the sandbox).
     pub fn expose(&self) -> &str {
-        &self.0
+        &self.secret
     }
 }
 
@@ -51,6 +77,9 @@ pub struct ConsolidationSpec {
     pub prs: Vec<PrRef>,
     /// Deterministic target branch: `ampel/remediation/<run_id>`.
     pub branch_name: String,
+    /// How each PR branch is combined into `branch_name` (the policy's
+    /// `consolidation_strategy`).
+    pub strategy: ConsolidationStrategy,
     /// Opaque credential — never logged in plaintext.
     pub credential: CredentialHandle,
 }
@@ -75,16 +104,24 @@ impl ConsolidationSpec {
             clone_url: clone_url.into(),
             default_branch: default_branch.into(),
             prs,
+            strategy: ConsolidationStrategy::default(),
             credential,
         }
     }
+
+    /// Use `strategy` instead of the default `merge`.
+    pub fn with_strategy(mut self, strategy: ConsolidationStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
 }
 
 /// Result of a sandbox consolidation run.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConsolidationOutcome {
     pub branch_name: String,
-    /// The consolidating PR number, if one was opened.
+    /// The consolidating PR number, if the sandbox opened one. When `None` the
+    /// orchestrator opens it from the pushed branch.
     pub consolidated_pr_number: Option<i64>,
     /// Per-source-PR disposition (pr_number -> outcome).
     pub dispositions: Vec<(i64, MergeDisposition)>,
@@ -99,6 +136,162 @@ pub trait SandboxRunner: Send + Sync {
         -> AmpelResult<ConsolidationOutcome>;
 }
 
+/// What one command run in a [`GitWorkspace`] produced.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub success: bool,
+    /// Combined stdout and stderr.
+    pub output: String,
+}
+
+/// A clone checked out on the consolidation branch, in which git and the
+/// lockfile tools run.
+#[async_trait]
+pub trait GitWorkspace: Send + Sync {
+    /// Run `argv` (program first) in `dir`, relative to the clone root (`""`
+    /// for the root). A non-zero exit is reported through
+    /// [`CommandOutput::success`]; `Err` means the command could not run at all.
+    async fn run(&self, dir: &str, argv: &[String]) -> AmpelResult<CommandOutput>;
+}
+
+const UNMERGED_PATHS: &[&str] = &["git", "diff", "--name-only", "--diff-filter=U"];
+const NOTHING_STAGED: &[&str] = &["git", "diff", "--cached", "--quiet"];
+
+/// Combine the PR steps of `steps`, in order, into the branch checked out in
+/// `workspace`, returning one disposition per PR. Setup steps are the
+/// workspace's own business and are passed over.
+///
+/// A step that stops on conflicts is settled by [`resolve_conflict`]: a
+/// lockfile-only conflict is regenerated and the step continues — for a rebase
+/// once per conflicting commit, up to [`MAX_CONFLICT_ROUNDS`] — while any other
+/// conflict, a failed regeneration, or running out of rounds backs the PR out
+/// and records it as skipped. A rebase whose branch has no merge base with the
+/// default branch in the (shallow) clone is left open without being applied.
+/// A step that fails without conflicts is backed out and ends the
+/// consolidation with an error.
+pub async fn combine_prs(
+    workspace: &dyn GitWorkspace,
+    steps: &[GitStep],
+    default_branch: &str,
+) -> AmpelResult<Vec<(i64, MergeDisposition)>> {
+    let mut dispositions = Vec::new();
+    for step in steps {
+        let (Some(pr_number), Some(apply)) = (step.pr_number(), step.apply_argv(default_branch))
+        else {
+            continue;
+        };
+        let disposition = match step.merge_base_argv(default_branch) {
+            Some(argv) if !workspace.run("", &argv).await?.success => MergeDisposition::LeftOpen {
+                reason: format!(
+                    "no merge base with origin/{default_branch} within the fetched history"
+                ),
+            },
+            _ => combine_pr(workspace, step, &apply).await?,
+        };
+        dispositions.push((pr_number as i64, disposition));
+    }
+    Ok(dispositions)
+}
+
+async fn combine_pr(
+    workspace: &dyn GitWorkspace,
+    step: &GitStep,
+    apply: &[String],
+) -> AmpelResult<MergeDisposition> {
+    let mut last = workspace.run("", apply).await?;
+    let mut last_argv = apply.to_vec();
+    // `merge --squash` only stages, so even a clean squash still commits.
+    if last.success && matches!(step, GitStep::Squash { .. }) {
+        match conclude(workspace, step).await? {
+            Some((argv, out)) => (last_argv, last) = (argv, out),
+            None => return Ok(MergeDisposition::Consolidated),
+        }
+    }
+
+    let mut rounds = 0;
+    while !last.success {
+        let unmerged = workspace.run("", &strings(UNMERGED_PATHS)).await?;
+        let conflicted = parse_conflicted_paths(&unmerged.output);
+        if conflicted.is_empty() {
+            if let Some(abort) = step.abort_argv() {
+                workspace.run("", &abort).await?;
+            }
+            return Err(AmpelError::InternalError(format!(
+                "`{}` failed: {}",
+                last_argv.join(" "),
+                last.output.trim()
+            )));
+        }
+
+        rounds += 1;
+        let resolution = if rounds > MAX_CONFLICT_ROUNDS {
+            ConflictResolution::Skip {
+                abort: step.abort_argv().unwrap_or_default(),
+                disposition: skipped_conflict(&conflicted),
+            }
+        } else {
+            resolve_conflict(step, &conflicted)
+        };
+        let commands = match resolution {
+            ConflictResolution::Skip { abort, disposition } => {
+                back_out(workspace, &abort).await?;
+                return Ok(disposition);
+            }
+            ConflictResolution::Resolve { commands } => commands,
+        };
+        for (dir, argv) in &commands {
+            if !workspace.run(dir, argv).await?.success {
+                back_out(workspace, &step.abort_argv().unwrap_or_default()).await?;
+                return Ok(MergeDisposition::SkippedConflict {
+                    reason: format!(
+                        "merge conflict in {}; `{}` failed",
+                        conflicted.join(", "),
+                        argv.join(" ")
+                    ),
+                    files: conflicted,
+                });
+            }
+        }
+        match conclude(workspace, step).await? {
+            Some((argv, out)) => (last_argv, last) = (argv, out),
+            None => break,
+        }
+    }
+    Ok(MergeDisposition::Consolidated)
+}
+
+/// Run the step's conclude command, if the index calls for one.
+async fn conclude(
+    workspace: &dyn GitWorkspace,
+    step: &GitStep,
+) -> AmpelResult<Option<(Vec<String>, CommandOutput)>> {
+    let staged = !workspace.run("", &strings(NOTHING_STAGED)).await?.success;
+    let Some(argv) = step.conclude_argv(staged) else {
+        return Ok(None);
+    };
+    let out = workspace.run("", &argv).await?;
+    Ok(Some((argv, out)))
+}
+
+async fn back_out(workspace: &dyn GitWorkspace, abort: &[String]) -> AmpelResult<()> {
+    if abort.is_empty() {
+        return Ok(());
+    }
+    let out = workspace.run("", abort).await?;
+    if !out.success {
+        return Err(AmpelError::InternalError(format!(
+            "`{}` failed: {}",
+            abort.join(" "),
+            out.output.trim()
+        )));
+    }
+    Ok(())
+}
+
+fn strings(argv: &[&str]) -> Vec<String> {
+    argv.iter().map(|s| s.to_string()).collect()
+}
+
 #[cfg(any(test, feature = "test-utils"))]
 pub use fake::FakeSandboxRunner;
 
@@ -107,18 +300,26 @@ mod fake {
     //! Deterministic in-process fake — no subprocess, no container.
 
     use super::*;
-    use crate::errors::AmpelError;
+    use crate::remediation::build_merge_sequence;
+    use std::collections::{HashMap, VecDeque};
     use std::sync::Mutex;
 
-    /// Returns a deterministic outcome (all PRs `Consolidated`) and records the
-    /// spec it last received so tests can assert on it.
+    /// Combines the PRs through [`combine_prs`] against an in-memory workspace
+    /// that merges every branch cleanly unless a conflict was scripted for it
+    /// with [`Self::with_conflict`]. Records the spec and the commands it last
+    /// ran so tests can assert on them.
     pub struct FakeSandboxRunner {
         consolidated_pr_number: Option<i64>,
         head_sha: String,
         /// When set, `run_consolidation` returns this error instead of an outcome
         /// (simulates a sandbox/infra crash for chaos tests).
         error: Option<String>,
+        /// Scripted conflicts per PR number, one entry per stop.
+        conflicts: HashMap<i32, Vec<Vec<String>>>,
+        /// PRs whose branch shares no history with the base in the clone.
+        unrelated: Vec<i32>,
         last_spec: Mutex<Option<ConsolidationSpec>>,
+        commands: Mutex<Vec<String>>,
     }
 
     impl Default for FakeSandboxRunner {
@@ -127,7 +328,10 @@ mod fake {
                 consolidated_pr_number: Some(9001),
                 head_sha: "fakehead0000000000000000000000000000cafe".to_string(),
                 error: None,
+                conflicts: HashMap::new(),
+                unrelated: Vec::new(),
                 last_spec: Mutex::new(None),
+                commands: Mutex::new(Vec::new()),
             }
         }
     }
@@ -138,6 +342,7 @@ mod fake {
         }
 
         /// Configure the consolidated PR number and head SHA the fake returns.
+        /// A `None` PR number leaves opening the PR to the orchestrator.
         pub fn with_outcome(
             consolidated_pr_number: Option<i64>,
             head_sha: impl Into<String>,
@@ -145,8 +350,7 @@ mod fake {
             Self {
                 consolidated_pr_number,
                 head_sha: head_sha.into(),
-                error: None,
-                last_spec: Mutex::new(None),
+                ..Self::default()
             }
         }
 
@@ -158,6 +362,24 @@ mod fake {
             }
         }
 
+        /// Make PR `pr_number` stop on conflicts in `files`. Each call scripts
+        /// one more stop; only a rebase, which replays commit by commit, stops
+        /// more than once.
+        pub fn with_conflict(mut self, pr_number: i32, files: &[&str]) -> Self {
+            self.conflicts
+                .entry(pr_number)
+                .or_default()
+                .push(files.iter().map(|f| f.to_string()).collect());
+            self
+        }
+
+        /// Make PR `pr_number`'s branch share no merge base with the default
+        /// branch, as when a shallow clone stops short of it.
+        pub fn without_merge_base(mut self, pr_number: i32) -> Self {
+            self.unrelated.push(pr_number);
+            self
+        }
+
         /// The spec passed to the most recent `run_consolidation` call.
         pub fn last_spec(&self) -> Option<ConsolidationSpec> {
             self.last_spec.lock().unwrap().clone()
@@ -167,6 +389,12 @@ mod fake {
         pub fn was_invoked(&self) -> bool {
             self.last_spec.lock().unwrap().is_some()
         }
+
+        /// The commands the most recent consolidation ran, one per line,
+        /// prefixed with `cd <dir> && ` outside the clone root.
+        pub fn git_commands(&self) -> Vec<String> {
+            self.commands.lock().unwrap().clone()
+        }
     }
 
     #[async_trait]
@@ -175,23 +403,148 @@ mod fake {
             &self,
             spec: ConsolidationSpec,
         ) -> AmpelResult<ConsolidationOutcome> {
+            *self.last_spec.lock().unwrap() = Some(spec.clone());
             if let Some(message) = &self.error {
-                *self.last_spec.lock().unwrap() = Some(spec);
                 return Err(AmpelError::InternalError(message.clone()));
             }
-            let dispositions = spec
-                .prs
+
+            let workspace = ScriptedWorkspace::new(&spec.prs, &self.conflicts, &self.unrelated);
+            let steps = build_merge_sequence(&spec.branch_name, &spec.prs, 1, spec.strategy);
+            let result = combine_prs(&workspace, &steps, &spec.default_branch).await;
+            *self.commands.lock().unwrap() = workspace.state.into_inner().unwrap().commands;
+            let dispositions = result?;
+
+            // Like the real runner, nothing is pushed when every PR was skipped.
+            let any_consolidated = dispositions
                 .iter()
-                .map(|p| (p.number as i64, MergeDisposition::Consolidated))
-                .collect();
-            let outcome = ConsolidationOutcome {
-                branch_name: spec.branch_name.clone(),
-                consolidated_pr_number: self.consolidated_pr_number,
+                .any(|(_, d)| *d == MergeDisposition::Consolidated);
+            Ok(ConsolidationOutcome {
+                branch_name: spec.branch_name,
+                consolidated_pr_number: self.consolidated_pr_number.filter(|_| any_consolidated),
                 dispositions,
                 head_sha: self.head_sha.clone(),
+            })
+        }
+    }
+
+    /// In-memory [`GitWorkspace`]: applying a PR branch plays back that PR's
+    /// next scripted conflict, `git add` resolves it, and an abort drops the
+    /// PR's remaining stops.
+    struct ScriptedWorkspace {
+        /// `origin/<branch>` → PR number.
+        refs: HashMap<String, i32>,
+        unrelated: Vec<i32>,
+        state: Mutex<ScriptState>,
+    }
+
+    #[derive(Default)]
+    struct ScriptState {
+        conflicts: HashMap<i32, VecDeque<Vec<String>>>,
+        current: Option<i32>,
+        unmerged: Vec<String>,
+        commands: Vec<String>,
+    }
+
+    impl ScriptedWorkspace {
+        fn new(
+            prs: &[PrRef],
+            conflicts: &HashMap<i32, Vec<Vec<String>>>,
+            unrelated: &[i32],
+        ) -> Self {
+            Self {
+                refs: prs
+                    .iter()
+                    .map(|p| (format!("origin/{}", p.branch), p.number))
+                    .collect(),
+                unrelated: unrelated.to_vec(),
+                state: Mutex::new(ScriptState {
+                    conflicts: conflicts
+                        .iter()
+                        .map(|(pr, stops)| (*pr, stops.iter().cloned().collect()))
+                        .collect(),
+                    ..ScriptState::default()
+                }),
+            }
+        }
+    }
+
+    impl ScriptState {
+        /// Stop on the current PR's next scripted conflict, if any is left.
+        fn next_stop(&mut self) -> CommandOutput {
+            let stop = self
+                .current
+                .and_then(|pr| self.conflicts.get_mut(&pr))
+                .and_then(VecDeque::pop_front);
+            match stop {
+                Some(files) => {
+                    let output = files
+                        .iter()
+                        .map(|f| format!("CONFLICT (content): Merge conflict in {f}\n"))
+                        .collect();
+                    self.unmerged = files;
+                    CommandOutput {
+                        success: false,
+                        output,
+                    }
+                }
+                None => ok(""),
+            }
+        }
+    }
+
+    fn ok(output: &str) -> CommandOutput {
+        CommandOutput {
+            success: true,
+            output: output.to_string(),
+        }
+    }
+
+    #[async_trait]
+    impl GitWorkspace for ScriptedWorkspace {
+        async fn run(&self, dir: &str, argv: &[String]) -> AmpelResult<CommandOutput> {
+            let mut state = self.state.lock().unwrap();
+            let line = argv.join(" ");
+            state.commands.push(if dir.is_empty() {
+                line
+            } else {
+                format!("cd {dir} && {line}")
+            });
+
+            let args: Vec<&str> = argv.iter().map(String::as_str).collect();
+            let out = match args.as_slice() {
+                ["git", "merge" | "cherry-pick", .., target] if target.starts_with("origin/") => {
+                    let branch = target.rsplit("..").next().unwrap_or(target);
+                    state.current = self.refs.get(branch).copied();
+                    state.next_stop()
+                }
+                ["git", "merge-base", _, source] => CommandOutput {
+                    success: !self
+                        .refs
+                        .get(*source)
+                        .is_some_and(|pr| self.unrelated.contains(pr)),
+                    output: String::new(),
+                },
+                ["git", "diff", "--name-only", ..] => ok(&state.unmerged.join("\n")),
+                // Something is always staged.
+                ["git", "diff", "--cached", "--quiet"] => CommandOutput {
+                    success: false,
+                    output: String::new(),
+                },
+                ["git", "add", ..] => {
+                    state.unmerged.clear();
+                    ok("")
+                }
+                [.., "cherry-pick", "--continue" | "--skip"] => state.next_stop(),
+                ["git", "merge" | "cherry-pick", "--abort"] | ["git", "reset", "--hard", ..] => {
+                    if let Some(pr) = state.current {
+                        state.conflicts.remove(&pr);
+                    }
+                    state.unmerged.clear();
+                    ok("")
+                }
+                _ => ok(""),
             };
-            *self.last_spec.lock().unwrap() = Some(spec);
-            Ok(outcome)
+            Ok(out)
         }
     }
 }
@@ -261,4 +614,222 @@ mod tests {
         assert!(runner.was_invoked());
         assert_eq!(runner.last_spec().unwrap().branch_name, spec.branch_name);
     }
+
+    fn spec(prs: Vec<PrRef>, strategy: ConsolidationStrategy) -> ConsolidationSpec {
+        ConsolidationSpec::new(
+            Uuid::nil(),
+            "https://example.test/repo.git",
+            "main",
+            prs,
+            CredentialHandle::new("pat"),
+        )
+        .with_strategy(strategy)
+    }
+
+    #[tokio::test]
+    async fn should_skip_conflicting_pr_with_its_files_and_continue_with_the_next() {
+        // Arrange
+        let runner = FakeSandboxRunner::new().with_conflict(1, &["src/lib.rs", "Cargo.lock"]);
+
+        // Act
+        let outcome = runner
+            .run_consolidation(spec(vec![pr(1), pr(2)], ConsolidationStrategy::Merge))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            outcome.dispositions,
+            vec![
+                (
+                    1,
+                    MergeDisposition::SkippedConflict {
+                        reason: "merge conflict in src/lib.rs, Cargo.lock".to_string(),
+                        files: vec!["src/lib.rs".to_string(), "Cargo.lock".to_string()],
+                    }
+                ),
+                (2, MergeDisposition::Consolidated),
+            ]
+        );
+        assert_eq!(
+            runner.git_commands(),
+            vec![
+                "git merge --no-ff --no-edit origin/feature/1",
+                "git diff --name-only --diff-filter=U",
+                "git merge --abort",
+                "git merge --no-ff --no-edit origin/feature/2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_regenerate_lockfile_only_conflict_and_commit_the_merge() {
+        // Arrange
+        let runner = FakeSandboxRunner::new().with_conflict(1, &["web/package-lock.json"]);
+
+        // Act
+        let outcome = runner
+            .run_consolidation(spec(vec![pr(1)], ConsolidationStrategy::Merge))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            outcome.dispositions,
+            vec![(1, MergeDisposition::Consolidated)]
+        );
+        assert_eq!(
+            runner.git_commands(),
+            vec![
+                "git merge --no-ff --no-edit origin/feature/1",
+                "git diff --name-only --diff-filter=U",
+                "git checkout --ours -- web/package-lock.json",
+                "cd web && npm install --package-lock-only",
+                "git add -- web/package-lock.json",
+                "git diff --cached --quiet",
+                "git commit --no-edit",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_continue_a_rebase_through_a_conflict_on_each_of_two_commits() {
+        // Arrange: the PR's first and second commits both conflict on lockfiles.
+        let runner = FakeSandboxRunner::new()
+            .with_conflict(1, &["Cargo.lock"])
+            .with_conflict(1, &["Cargo.lock"]);
+
+        // Act
+        let outcome = runner
+            .run_consolidation(spec(vec![pr(1)], ConsolidationStrategy::RebaseOntoBase))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            outcome.dispositions,
+            vec![(1, MergeDisposition::Consolidated)]
+        );
+        let commands = runner.git_commands();
+        assert_eq!(commands[0], "git merge-base origin/main origin/feature/1");
+        assert_eq!(
+            commands[1],
+            "git cherry-pick --no-merges origin/main..origin/feature/1"
+        );
+        let continues = commands
+            .iter()
+            .filter(|c| c.ends_with("cherry-pick --continue"))
+            .count();
+        assert_eq!(continues, 2);
+        assert!(!commands.iter().any(|c| c.ends_with("--abort")));
+    }
+
+    #[tokio::test]
+    async fn should_abort_a_rebase_when_a_later_commit_conflicts_outside_lockfiles() {
+        // Arrange
+        let runner = FakeSandboxRunner::new()
+            .with_conflict(1, &["Cargo.lock"])
+            .with_conflict(1, &["src/main.rs"]);
+
+        // Act
+        let outcome = runner
+            .run_consolidation(spec(vec![pr(1)], ConsolidationStrategy::RebaseOntoBase))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            outcome.dispositions,
+            vec![(
+                1,
+                MergeDisposition::SkippedConflict {
+                    reason: "merge conflict in src/main.rs".to_string(),
+                    files: vec!["src/main.rs".to_string()],
+                }
+            )]
+        );
+        assert_eq!(
+            runner.git_commands().last().unwrap(),
+            "git cherry-pick --abort"
+        );
+        assert_eq!(outcome.consolidated_pr_number, None);
+    }
+
+    #[tokio::test]
+    async fn should_skip_a_rebase_that_keeps_conflicting_past_the_round_limit() {
+        // Arrange
+        let mut runner = FakeSandboxRunner::new();
+        for _ in 0..=MAX_CONFLICT_ROUNDS {
+            runner = runner.with_conflict(1, &["Cargo.lock"]);
+        }
+
+        // Act
+        let outcome = runner
+            .run_consolidation(spec(vec![pr(1)], ConsolidationStrategy::RebaseOntoBase))
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(matches!(
+            outcome.dispositions[0].1,
+            MergeDisposition::SkippedConflict { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_leave_a_rebase_open_when_its_branch_has_no_merge_base_in_the_clone() {
+        // Arrange
+        let runner = FakeSandboxRunner::new().without_merge_base(1);
+
+        // Act
+        let outcome = runner
+            .run_consolidation(spec(
+                vec![pr(1), pr(2)],
+                ConsolidationStrategy::RebaseOntoBase,
+            ))
+            .await
+            .unwrap();
+
+        // Assert: nothing of PR 1 was cherry-picked; PR 2 still went in.
+        assert_eq!(
+            outcome.dispositions,
+            vec![
+                (
+                    1,
+                    MergeDisposition::LeftOpen {
+                        reason: "no merge base with origin/main within the fetched history"
+                            .to_string(),
+                    }
+                ),
+                (2, MergeDisposition::Consolidated),
+            ]
+        );
+        assert_eq!(
+            runner.git_commands(),
+            vec![
+                "git merge-base origin/main origin/feature/1",
+                "git merge-base origin/main origin/feature/2",
+                "git cherry-pick --no-merges origin/main..origin/feature/2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_commit_each_squash_titled_after_its_pr() {
+        let runner = FakeSandboxRunner::new();
+
+        runner
+            .run_consolidation(spec(vec![pr(4)], ConsolidationStrategy::SquashPerPr))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            runner.git_commands(),
+            vec![
+                "git merge --squash origin/feature/4",
+                "git diff --cached --quiet",
+                "git commit -m PR 4 (#4)",
+            ]
+        );
+    }
 }
@@ -0,0 +1,496 @@
+//! Browser push notifications.
+//!
+//! Messages are encrypted for the receiving browser as described in RFC 8291
+//! (`aes128gcm` content coding) and POSTed to the push service named by the
+//! subscription endpoint (RFC 8030). Requests are authenticated with a VAPID
+//! token (RFC 8292) signed by the deployment's [`VapidKeys`], whose public key
+//! browsers are given when they subscribe.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use hkdf::Hkdf;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::{PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use thiserror::Error;
+
+use super::ChannelMessage;
+
+/// Record size advertised in the encrypted payload header. The whole payload
+/// is sent as a single record, so this only needs to exceed its length.
+const RECORD_SIZE: u32 = 4096;
+
+/// Largest plaintext push services are required to accept: 4096 bytes less
+/// the header (86), the padding delimiter (1) and the AEAD tag (16)
+pub const MAX_PUSH_PAYLOAD: usize = 3993;
+
+/// How long push services keep an undelivered message for an offline browser
+const PUSH_TTL_SECS: u32 = 24 * 60 * 60;
+
+/// How long a VAPID token is valid; RFC 8292 allows at most 24 hours
+const VAPID_TOKEN_HOURS: i64 = 12;
+
+#[derive(Debug, Error)]
+pub enum WebPushError {
+    #[error("Invalid VAPID key: {0}")]
+    InvalidKey(String),
+
+    #[error("Invalid push subscription: {0}")]
+    InvalidSubscription(String),
+
+    #[error("Failed to encrypt push payload")]
+    Encryption,
+
+    /// The browser unsubscribed; the subscription should be deleted
+    #[error("Push subscription has expired")]
+    Expired,
+
+    #[error("Push service rejected the message ({status}): {reason}")]
+    Rejected { status: u16, reason: String },
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// The key pair identifying this deployment to push services
+#[derive(Clone)]
+pub struct VapidKeys {
+    signing_key: SigningKey,
+}
+
+impl std::fmt::Debug for VapidKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VapidKeys")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+impl VapidKeys {
+    /// Generate a new key pair
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::from(random_secret_key()),
+        }
+    }
+
+    /// Load a key pair from its base64url-encoded private key
+    pub fn from_private_key(private_key: &str) -> Result<Self, WebPushError> {
+        let bytes = decode_base64url(private_key)
+            .map_err(|_| WebPushError::InvalidKey("not base64url".to_string()))?;
+        let signing_key = SigningKey::from_slice(&bytes)
+            .map_err(|_| WebPushError::InvalidKey("not a P-256 private key".to_string()))?;
+        Ok(Self { signing_key })
+    }
+
+    /// The base64url-encoded private key, for storage
+    pub fn private_key(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.signing_key.to_bytes())
+    }
+
+    /// The base64url-encoded uncompressed public key, which browsers pass to
+    /// `pushManager.subscribe` as the `applicationServerKey`
+    pub fn public_key(&self) -> String {
+        URL_SAFE_NO_PAD.encode(
+            self.signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes(),
+        )
+    }
+
+    /// The `Authorization` header for a request to `endpoint`
+    pub fn authorization(&self, endpoint: &str, subject: &str) -> Result<String, WebPushError> {
+        let url = url::Url::parse(endpoint)
+            .map_err(|_| WebPushError::InvalidSubscription("endpoint is not a URL".to_string()))?;
+        let audience = url.origin().ascii_serialization();
+        let expires = Utc::now() + Duration::hours(VAPID_TOKEN_HOURS);
+
+        let header = URL_SAFE_NO_PAD.encode(json!({ "typ": "JWT", "alg": "ES256" }).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(
+            json!({ "aud": audience, "exp": expires.timestamp(), "sub": subject }).to_string(),
+        );
+        let signing_input = format!("{}.{}", header, claims);
+        let signature: Signature = self.signing_key.sign(signing_input.as_bytes());
+
+        Ok(format!(
+            "vapid t={}.{}, k={}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+            self.public_key()
+        ))
+    }
+}
+
+/// Where a browser receives push messages, as returned by
+/// `PushSubscription.toJSON()`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebPushSubscription {
+    pub endpoint: String,
+    /// The browser's base64url-encoded P-256 public key
+    pub p256dh: String,
+    /// The base64url-encoded 16-byte authentication secret
+    pub auth: String,
+}
+
+impl WebPushSubscription {
+    /// Check the endpoint is an HTTPS URL and the keys decode
+    pub fn validate(&self) -> Result<(), WebPushError> {
+        let url = url::Url::parse(&self.endpoint)
+            .map_err(|_| WebPushError::InvalidSubscription("endpoint is not a URL".to_string()))?;
+        if url.scheme() != "https" {
+            return Err(WebPushError::InvalidSubscription(
+                "endpoint must use https".to_string(),
+            ));
+        }
+        self.keys().map(|_| ())
+    }
+
+    fn keys(&self) -> Result<(PublicKey, [u8; 16]), WebPushError> {
+        let invalid = |what: &str| WebPushError::InvalidSubscription(format!("invalid {what} key"));
+        let p256dh = decode_base64url(&self.p256dh).map_err(|_| invalid("p256dh"))?;
+        let public_key = PublicKey::from_sec1_bytes(&p256dh).map_err(|_| invalid("p256dh"))?;
+        let auth = decode_base64url(&self.auth)
+            .ok()
+            .and_then(|auth| <[u8; 16]>::try_from(auth).ok())
+            .ok_or_else(|| invalid("auth"))?;
+        Ok((public_key, auth))
+    }
+}
+
+/// Encrypt `plaintext` for a subscription with the `aes128gcm` content coding
+pub fn encrypt_payload(
+    subscription: &WebPushSubscription,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, WebPushError> {
+    let (ua_public, auth_secret) = subscription.keys()?;
+    let salt: [u8; 16] = rand::random();
+    encrypt_with(
+        &random_secret_key(),
+        &salt,
+        &ua_public,
+        &auth_secret,
+        plaintext,
+    )
+}
+
+/// RFC 8291 section 3 with the sender key and salt supplied, so the
+/// derivation can be checked against the RFC's worked example
+fn encrypt_with(
+    as_secret: &SecretKey,
+    salt: &[u8; 16],
+    ua_public: &PublicKey,
+    auth_secret: &[u8; 16],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, WebPushError> {
+    if plaintext.len() > MAX_PUSH_PAYLOAD {
+        return Err(WebPushError::Encryption);
+    }
+    let ua_public_bytes = ua_public.to_encoded_point(false);
+    let as_public_bytes = as_secret.public_key().to_encoded_point(false);
+
+    let shared = p256::ecdh::diffie_hellman(as_secret.to_nonzero_scalar(), ua_public.as_affine());
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(ua_public_bytes.as_bytes());
+    key_info.extend_from_slice(as_public_bytes.as_bytes());
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(auth_secret), shared.raw_secret_bytes())
+        .expand(&key_info, &mut ikm)
+        .map_err(|_| WebPushError::Encryption)?;
+
+    let hkdf = Hkdf::<Sha256>::new(Some(salt), &ikm);
+    let mut cek = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    hkdf.expand(b"Content-Encoding: aes128gcm\0", &mut cek)
+        .and_then(|_| hkdf.expand(b"Content-Encoding: nonce\0", &mut nonce))
+        .map_err(|_| WebPushError::Encryption)?;
+
+    // A single, final record: the plaintext followed by the 0x02 delimiter
+    let mut record = plaintext.to_vec();
+    record.push(2);
+    let ciphertext = Aes128Gcm::new_from_slice(&cek)
+        .map_err(|_| WebPushError::Encryption)?
+        .encrypt(Nonce::from_slice(&nonce), record.as_slice())
+        .map_err(|_| WebPushError::Encryption)?;
+
+    let mut body = Vec::with_capacity(86 + ciphertext.len());
+    body.extend_from_slice(salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public_bytes.len() as u8);
+    body.extend_from_slice(as_public_bytes.as_bytes());
+    body.extend_from_slice(&ciphertext);
+    Ok(body)
+}
+
+/// The JSON a service worker receives for `message`, trimmed to fit the
+/// payload limit: the body is shortened first, then the title, and the link
+/// is dropped as a last resort
+pub fn push_payload(message: &ChannelMessage) -> Vec<u8> {
+    let payload = |title: &str, body: &str, url: Option<&str>| {
+        json!({
+            "event": message.event,
+            "title": title,
+            "body": body,
+            "url": url,
+        })
+        .to_string()
+        .into_bytes()
+    };
+
+    let mut title = message.title.clone();
+    let mut body = message.body.clone();
+    let mut url = message.url.clone();
+    loop {
+        let bytes = payload(&title, &body, url.as_deref());
+        if bytes.len() <= MAX_PUSH_PAYLOAD {
+            return bytes;
+        }
+        let excess = bytes.len() - MAX_PUSH_PAYLOAD;
+        if !body.is_empty() {
+            shorten(&mut body, excess);
+        } else if !title.is_empty() {
+            shorten(&mut title, excess);
+        } else if url.is_some() {
+            url = None;
+        } else {
+            return bytes;
+        }
+    }
+}
+
+/// Cut at least `excess` bytes off `text`, marking the cut with an ellipsis
+fn shorten(text: &mut String, excess: usize) {
+    let mut keep = text.len().saturating_sub(excess + '…'.len_utf8());
+    while !text.is_char_boundary(keep) {
+        keep -= 1;
+    }
+    text.truncate(keep);
+    if !text.is_empty() {
+        text.push('…');
+    }
+}
+
+/// Sends push messages signed with one set of VAPID keys
+pub struct WebPushSender {
+    client: reqwest::Client,
+    keys: VapidKeys,
+    /// `mailto:` or `https:` contact for push service operators
+    subject: String,
+}
+
+impl WebPushSender {
+    pub fn new(keys: VapidKeys, subject: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            keys,
+            subject: subject.into(),
+        }
+    }
+
+    /// Encrypt and send `payload` to one subscription. A 404 or 410 from the
+    /// push service is reported as [`WebPushError::Expired`].
+    pub async fn send(
+        &self,
+        subscription: &WebPushSubscription,
+        payload: &[u8],
+    ) -> Result<(), WebPushError> {
+        let body = encrypt_payload(subscription, payload)?;
+        let authorization = self
+            .keys
+            .authorization(&subscription.endpoint, &self.subject)?;
+
+        let response = self
+            .client
+            .post(&subscription.endpoint)
+            .header("Authorization", authorization)
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .header("TTL", PUSH_TTL_SECS.to_string())
+            .header("Urgency", "normal")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE {
+            return Err(WebPushError::Expired);
+        }
+        let reason = response.text().await.unwrap_or_default();
+        Err(WebPushError::Rejected {
+            status: status.as_u16(),
+            reason: reason.chars().take(200).collect(),
+        })
+    }
+}
+
+fn random_secret_key() -> SecretKey {
+    // Nearly every 32-byte string is a valid scalar; retry on the rare miss
+    loop {
+        let bytes: [u8; 32] = rand::random();
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            return key;
+        }
+    }
+}
+
+/// Decode base64url, tolerating padding and the standard alphabet, which
+/// some browsers and libraries produce
+fn decode_base64url(value: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let normalized: String = value
+        .trim()
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect();
+    URL_SAFE_NO_PAD.decode(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(value: &str) -> Vec<u8> {
+        decode_base64url(value).unwrap()
+    }
+
+    #[test]
+    fn test_encryption_matches_rfc8291_example() {
+        // RFC 8291 appendix A
+        let as_secret =
+            SecretKey::from_slice(&decode("yfWPiYE-n46HLnH0KqZOF1fJJU3MYrct3AELtAQ-oRw")).unwrap();
+        let ua_public = PublicKey::from_sec1_bytes(&decode(
+            "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4",
+        ))
+        .unwrap();
+        let salt: [u8; 16] = decode("DGv6ra1nlYgDCS1FRnbzlw").try_into().unwrap();
+        let auth: [u8; 16] = decode("BTBZMqHH6r4Tts7J_aSIgg").try_into().unwrap();
+
+        let body = encrypt_with(
+            &as_secret,
+            &salt,
+            &ua_public,
+            &auth,
+            b"When I grow up, I want to be a watermelon",
+        )
+        .unwrap();
+
+        assert_eq!(
+            URL_SAFE_NO_PAD.encode(body),
+            "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLo\
+             cInmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBW\
+             LVWGNWQexSgSxsj_Qulcy4a-fN"
+        );
+    }
+
+    #[test]
+    fn test_vapid_authorization_is_a_signed_es256_token() {
+        let keys = VapidKeys::generate();
+        let restored = VapidKeys::from_private_key(&keys.private_key()).unwrap();
+        assert_eq!(restored.public_key(), keys.public_key());
+        assert_eq!(decode(&keys.public_key()).len(), 65);
+
+        let header = keys
+            .authorization(
+                "https://push.example.net:8443/send/abc",
+                "mailto:ops@example.com",
+            )
+            .unwrap();
+        let (token, key) = header
+            .strip_prefix("vapid t=")
+            .unwrap()
+            .split_once(", k=")
+            .unwrap();
+        assert_eq!(key, keys.public_key());
+
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let claims: serde_json::Value = serde_json::from_slice(&decode(parts[1])).unwrap();
+        assert_eq!(claims["aud"], "https://push.example.net:8443");
+        assert_eq!(claims["sub"], "mailto:ops@example.com");
+
+        use p256::ecdsa::signature::Verifier;
+        let signature = Signature::from_slice(&decode(parts[2])).unwrap();
+        keys.signing_key
+            .verifying_key()
+            .verify(format!("{}.{}", parts[0], parts[1]).as_bytes(), &signature)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_subscription_validation() {
+        let keys = VapidKeys::generate();
+        let subscription = WebPushSubscription {
+            endpoint: "https://push.example.net/send/abc".to_string(),
+            p256dh: keys.public_key(),
+            auth: "BTBZMqHH6r4Tts7J_aSIgg==".to_string(),
+        };
+        assert!(subscription.validate().is_ok());
+
+        let http = WebPushSubscription {
+            endpoint: "http://push.example.net/send/abc".to_string(),
+            ..subscription.clone()
+        };
+        assert!(http.validate().is_err());
+        let short_auth = WebPushSubscription {
+            auth: "BTBZMqHH".to_string(),
+            ..subscription.clone()
+        };
+        assert!(short_auth.validate().is_err());
+        let bad_key = WebPushSubscription {
+            p256dh: "BTBZMqHH6r4Tts7J_aSIgg".to_string(),
+            ..subscription
+        };
+        assert!(bad_key.validate().is_err());
+    }
+
+    #[test]
+    fn test_push_payload_fits_the_limit() {
+        let mut message = ChannelMessage::test();
+        message.body = "ü".repeat(4000);
+        let payload = push_payload(&message);
+        assert!(payload.len() <= MAX_PUSH_PAYLOAD);
+
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(json["title"], "Ampel test notification");
+        assert!(json["body"].as_str().unwrap().ends_with('…'));
+    }
+
+    #[test]
+    fn test_push_payload_shortens_a_title_over_the_limit() {
+        let mut message = ChannelMessage::test();
+        message.title = "T".repeat(5000);
+        message.url = Some("https://ampel.example.com/pulls/1".to_string());
+        let payload = push_payload(&message);
+        assert!(payload.len() <= MAX_PUSH_PAYLOAD);
+
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(json["body"], "");
+        assert!(json["title"].as_str().unwrap().ends_with('…'));
+        assert_eq!(json["url"], "https://ampel.example.com/pulls/1");
+    }
+
+    #[test]
+    fn test_push_payload_drops_a_url_over_the_limit() {
+        let mut message = ChannelMessage::test();
+        message.url = Some(format!("https://ampel.example.com/{}", "p".repeat(5000)));
+        let payload = push_payload(&message);
+        assert!(payload.len() <= MAX_PUSH_PAYLOAD);
+
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert!(json["url"].is_null());
+    }
+}
@@ -13,8 +13,17 @@ pub struct Model {
     pub success_count: i32,
     pub failed_count: i32,
     pub skipped_count: i32,
-    pub status: String, // in_progress, completed, failed
+    pub status: String, // queued, in_progress, completed, failed, cancelled
     pub notification_sent: bool,
+    pub trigger: String,          // manual, auto_merge
+    pub strategy: Option<String>, // merge, squash, rebase
+    pub delete_branch: bool,
+    pub merge_delay_seconds: i32,
+    /// Set by the cancel endpoint; the worker stops before the next item.
+    pub cancel_requested: bool,
+    /// Refreshed by the worker while it drains the operation. A stale heartbeat
+    /// on an `in_progress` operation means the worker died and it can be reclaimed.
+    pub heartbeat_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -9,10 +9,14 @@ pub struct Model {
     pub merge_operation_id: Uuid,
     pub pull_request_id: Uuid,
     pub repository_id: Uuid,
-    pub status: String, // pending, success, failed, skipped
+    pub status: String, // pending, success, failed, skipped, waiting, cancelled
     pub error_message: Option<String>,
     pub merge_sha: Option<String>,
     pub merged_at: Option<DateTimeUtc>,
+    /// Order in the original request; the worker merges in this order.
+    pub position: i32,
+    /// Merge attempts made, including transient-error retries.
+    pub attempts: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
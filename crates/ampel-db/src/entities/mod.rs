@@ -1,3 +1,4 @@
+pub mod api_token;
 pub mod auto_merge_rule;
 pub mod ci_check;
 pub mod health_score;
@@ -5,24 +6,39 @@ pub mod learning_signal;
 pub mod merge_operation;
 pub mod merge_operation_item;
 pub mod model_provider_account;
+pub mod notification_channel;
+pub mod notification_log;
 pub mod notification_preferences;
 pub mod organization;
+pub mod organization_invitation;
+pub mod organization_member;
 pub mod pr_filter;
 pub mod pr_metrics;
 pub mod provider_account;
 pub mod pull_request;
+pub mod pull_request_label;
+pub mod pull_request_participant;
+pub mod push_subscription;
+pub mod refresh_token;
 pub mod remediation_agent_session;
 pub mod remediation_playbook;
 pub mod remediation_policy;
 pub mod remediation_run;
 pub mod remediation_run_pr;
 pub mod repository;
+pub mod repository_share;
+pub mod repository_webhook;
 pub mod review;
+pub mod saved_view;
+pub mod status_policy;
 pub mod team;
 pub mod team_member;
 pub mod user;
+pub mod user_identity;
 pub mod user_settings;
+pub mod vapid_key;
 
+pub use api_token::Entity as ApiTokenEntity;
 pub use auto_merge_rule::Entity as AutoMergeRuleEntity;
 pub use ci_check::Entity as CICheckEntity;
 pub use health_score::Entity as HealthScoreEntity;
@@ -30,20 +46,34 @@ pub use learning_signal::Entity as LearningSignalEntity;
 pub use merge_operation::Entity as MergeOperationEntity;
 pub use merge_operation_item::Entity as MergeOperationItemEntity;
 pub use model_provider_account::Entity as ModelProviderAccountEntity;
+pub use notification_channel::Entity as NotificationChannelEntity;
+pub use notification_log::Entity as NotificationLogEntity;
 pub use notification_preferences::Entity as NotificationPreferencesEntity;
 pub use organization::Entity as OrganizationEntity;
+pub use organization_invitation::Entity as OrganizationInvitationEntity;
+pub use organization_member::Entity as OrganizationMemberEntity;
 pub use pr_filter::Entity as PrFilterEntity;
 pub use pr_metrics::Entity as PrMetricsEntity;
 pub use provider_account::Entity as ProviderAccountEntity;
 pub use pull_request::Entity as PullRequestEntity;
+pub use pull_request_label::Entity as PullRequestLabelEntity;
+pub use pull_request_participant::Entity as PullRequestParticipantEntity;
+pub use push_subscription::Entity as PushSubscriptionEntity;
+pub use refresh_token::Entity as RefreshTokenEntity;
 pub use remediation_agent_session::Entity as RemediationAgentSessionEntity;
 pub use remediation_playbook::Entity as RemediationPlaybookEntity;
 pub use remediation_policy::Entity as RemediationPolicyEntity;
 pub use remediation_run::Entity as RemediationRunEntity;
 pub use remediation_run_pr::Entity as RemediationRunPrEntity;
 pub use repository::Entity as RepositoryEntity;
+pub use repository_share::Entity as RepositoryShareEntity;
+pub use repository_webhook::Entity as RepositoryWebhookEntity;
 pub use review::Entity as ReviewEntity;
+pub use saved_view::Entity as SavedViewEntity;
+pub use status_policy::Entity as StatusPolicyEntity;
 pub use team::Entity as TeamEntity;
 pub use team_member::Entity as TeamMemberEntity;
 pub use user::Entity as UserEntity;
+pub use user_identity::Entity as UserIdentityEntity;
 pub use user_settings::Entity as UserSettingsEntity;
+pub use vapid_key::Entity as VapidKeyEntity;
@@ -0,0 +1,71 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One notification to one user over one channel, and its delivery state.
+///
+/// Rows start `pending` and become `sent`, or `failed` once retries are
+/// exhausted. `dedup_key` identifies the event, so the same event is only
+/// queued once per user and channel.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "notification_log")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub pull_request_id: Option<Uuid>,
+    pub kind: String, // pr_ready, pr_failed, review_requested, merge_succeeded, ...
+    pub channel: String, // slack, email, or the kind of a configured channel
+    /// The configured notification channel, when not delivered through the
+    /// user's preferences
+    pub channel_id: Option<Uuid>,
+    pub dedup_key: String,
+    pub payload: String, // JSON object stored as text
+    pub status: String,  // pending, sent, failed
+    pub attempts: i32,
+    pub next_attempt_at: DateTimeUtc,
+    pub last_error: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub sent_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::pull_request::Entity",
+        from = "Column::PullRequestId",
+        to = "super::pull_request::Column::Id"
+    )]
+    PullRequest,
+    #[sea_orm(
+        belongs_to = "super::notification_channel::Entity",
+        from = "Column::ChannelId",
+        to = "super::notification_channel::Column::Id"
+    )]
+    Channel,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::pull_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PullRequest.def()
+    }
+}
+
+impl Related<super::notification_channel::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Channel.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
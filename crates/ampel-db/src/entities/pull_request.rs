@@ -31,6 +31,14 @@ pub struct Model {
     pub merged_at: Option<DateTimeUtc>,
     pub closed_at: Option<DateTimeUtc>,
     pub last_synced_at: DateTimeUtc,
+    /// Traffic-light status as of the last sync, used to detect transitions
+    pub ampel_status: Option<String>,
+    /// When `ampel_status` last changed
+    pub ampel_status_changed_at: Option<DateTimeUtc>,
+    /// Commit at the tip of the source branch
+    pub head_sha: Option<String>,
+    /// Title of the PR's milestone
+    pub milestone: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -45,6 +53,10 @@ pub enum Relation {
     CIChecks,
     #[sea_orm(has_many = "super::review::Entity")]
     Reviews,
+    #[sea_orm(has_many = "super::pull_request_label::Entity")]
+    Labels,
+    #[sea_orm(has_many = "super::pull_request_participant::Entity")]
+    Participants,
 }
 
 impl Related<super::repository::Entity> for Entity {
@@ -65,6 +77,18 @@ impl Related<super::review::Entity> for Entity {
     }
 }
 
+impl Related<super::pull_request_label::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Labels.def()
+    }
+}
+
+impl Related<super::pull_request_participant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Participants.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 impl From<Model> for ampel_core::models::PullRequest {
@@ -91,6 +115,8 @@ impl From<Model> for ampel_core::models::PullRequest {
             state,
             source_branch: model.source_branch,
             target_branch: model.target_branch,
+            head_sha: model.head_sha,
+            milestone: model.milestone,
             author: model.author,
             author_avatar_url: model.author_avatar_url,
             is_draft: model.is_draft,
@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "pull_request_labels")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub pull_request_id: Uuid,
+    pub name: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::pull_request::Entity",
+        from = "Column::PullRequestId",
+        to = "super::pull_request::Column::Id"
+    )]
+    PullRequest,
+}
+
+impl Related<super::pull_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PullRequest.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// `role` of a user assigned to a PR
+pub const ROLE_ASSIGNEE: &str = "assignee";
+/// `role` of a user whose review of a PR is requested and still outstanding
+pub const ROLE_REVIEWER: &str = "reviewer";
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "pull_request_participants")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub pull_request_id: Uuid,
+    /// Provider login
+    pub login: String,
+    pub role: String, // assignee, reviewer
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::pull_request::Entity",
+        from = "Column::PullRequestId",
+        to = "super::pull_request::Column::Id"
+    )]
+    PullRequest,
+}
+
+impl Related<super::pull_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PullRequest.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,41 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A browser that receives a user's push notifications.
+///
+/// `p256dh` and `auth` are the keys the browser gave when it subscribed,
+/// base64url-encoded; messages to `endpoint` are encrypted with them.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "push_subscriptions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[sea_orm(unique, column_type = "Text")]
+    pub endpoint: String,
+    pub p256dh: String,
+    #[serde(skip_serializing)]
+    pub auth: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeUtc,
+    /// When a message was last accepted for this browser
+    pub last_used_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -20,6 +20,8 @@ pub struct Model {
     // Autonomy / tiering
     pub autonomy_level: String,
     pub remediation_tier: String,
+    /// `merge`, `rebase_onto_base` or `squash_per_pr`.
+    pub consolidation_strategy: String,
     pub max_prs_per_run: i32,
     /// JSON array of allowed targets; (de)serialized at the service layer.
     pub allowed_targets: String,
@@ -40,6 +40,10 @@ pub struct Model {
     pub attempts: i32,
     pub error_message: Option<String>,
     pub error_class: Option<String>,
+    /// When the run was handed to the job queue; NULL while awaiting dispatch.
+    pub queued_at: Option<DateTimeUtc>,
+    /// Lease refreshed by the worker driving the run; NULL when unheld.
+    pub heartbeat_at: Option<DateTimeUtc>,
 
     // Timestamps
     pub started_at: DateTimeUtc,
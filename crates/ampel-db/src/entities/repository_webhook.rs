@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Inbound webhook registration for a tracked repository.
+///
+/// `secret_encrypted` holds the shared secret (HMAC key or GitLab token)
+/// encrypted with the same `EncryptionService` used for provider PATs. It is
+/// never serialised back to clients after creation.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "repository_webhooks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub repository_id: Uuid,
+    pub provider: String, // github, gitlab, bitbucket
+    #[serde(skip_serializing)]
+    pub secret_encrypted: Vec<u8>,
+    pub is_active: bool,
+    pub last_delivery_at: Option<DateTimeUtc>,
+    /// Provider event name of the most recent verified delivery.
+    pub last_event: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::repository::Entity",
+        from = "Column::RepositoryId",
+        to = "super::repository::Column::Id"
+    )]
+    Repository,
+}
+
+impl Related<super::repository::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Repository.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,6 +12,8 @@ pub struct Model {
     pub state: String, // approved, changes_requested, commented, pending, dismissed
     pub body: Option<String>,
     pub submitted_at: DateTimeUtc,
+    /// Commit the review was given on
+    pub commit_sha: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -49,6 +51,7 @@ impl From<Model> for ampel_core::models::Review {
             reviewer: model.reviewer,
             reviewer_avatar_url: model.reviewer_avatar_url,
             state,
+            commit_sha: model.commit_sha,
             body: model.body,
             submitted_at: model.submitted_at,
         }
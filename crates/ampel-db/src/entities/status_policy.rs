@@ -0,0 +1,81 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Traffic-light rules for a repository, or the default for a team's shared
+/// repositories.
+///
+/// Exactly one of `repository_id` and `team_id` is set.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "status_policies")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub repository_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+    pub required_checks: String, // JSON array of check name patterns
+    pub ignored_checks: String,  // JSON array of check name patterns
+    pub min_approvals: i32,
+    pub require_named_approver: bool,
+    pub required_approvers: String, // JSON array of usernames
+    pub dismiss_stale_approvals: bool,
+    pub skipped_checks: String,   // pass, pending, fail
+    pub cancelled_checks: String, // pass, pending, fail
+    pub draft_status: String,     // green, yellow, red, none
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::repository::Entity",
+        from = "Column::RepositoryId",
+        to = "super::repository::Column::Id"
+    )]
+    Repository,
+    #[sea_orm(
+        belongs_to = "super::team::Entity",
+        from = "Column::TeamId",
+        to = "super::team::Column::Id"
+    )]
+    Team,
+}
+
+impl Related<super::repository::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Repository.def()
+    }
+}
+
+impl Related<super::team::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Team.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl From<Model> for ampel_core::models::StatusPolicy {
+    fn from(model: Model) -> Self {
+        let defaults = Self::default();
+        let patterns = |json: &str| serde_json::from_str(json).unwrap_or_default();
+
+        Self {
+            required_checks: patterns(&model.required_checks),
+            ignored_checks: patterns(&model.ignored_checks),
+            min_approvals: model.min_approvals.max(0) as u32,
+            require_named_approver: model.require_named_approver,
+            required_approvers: patterns(&model.required_approvers),
+            dismiss_stale_approvals: model.dismiss_stale_approvals,
+            skipped_checks: model
+                .skipped_checks
+                .parse()
+                .unwrap_or(defaults.skipped_checks),
+            cancelled_checks: model
+                .cancelled_checks
+                .parse()
+                .unwrap_or(defaults.cancelled_checks),
+            draft_status: model.draft_status.parse().unwrap_or(defaults.draft_status),
+        }
+    }
+}
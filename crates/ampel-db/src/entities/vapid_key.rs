@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Row id of the deployment's VAPID key pair; there is only ever one
+pub const CURRENT_VAPID_KEY_ID: i32 = 1;
+
+/// The key pair this deployment signs browser push requests with
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "vapid_keys")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    /// Base64url-encoded uncompressed P-256 public key
+    pub public_key: String,
+    #[serde(skip_serializing)]
+    pub private_key_encrypted: Vec<u8>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
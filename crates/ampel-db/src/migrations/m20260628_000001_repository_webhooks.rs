@@ -0,0 +1,121 @@
+//! `repository_webhooks` table — inbound provider webhook registration.
+//!
+//! One row per tracked repository that has a webhook configured. The shared
+//! secret (GitHub/Bitbucket HMAC key, GitLab `X-Gitlab-Token`) is stored
+//! AES-256-GCM encrypted exactly like provider PATs, so it can be decrypted to
+//! verify each delivery. `last_delivery_at` is what the poller consults to treat
+//! a repository as "live" and fall back to slow reconciliation polling.
+//!
+//! The single foreign key is declared inline in `CREATE TABLE`, so the migration
+//! also applies on SQLite.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RepositoryWebhooks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::RepositoryId)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::Provider)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::SecretEncrypted)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::IsActive)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::LastDeliveryAt)
+                            .timestamp_with_time_zone(),
+                    )
+                    .col(ColumnDef::new(RepositoryWebhooks::LastEvent).string())
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryWebhooks::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_repository_webhooks_repository")
+                            .from(RepositoryWebhooks::Table, RepositoryWebhooks::RepositoryId)
+                            .to(Repositories::Table, Repositories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The poller looks up live webhooks by recency of the last delivery.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_repository_webhooks_last_delivery")
+                    .table(RepositoryWebhooks::Table)
+                    .col(RepositoryWebhooks::LastDeliveryAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RepositoryWebhooks::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum RepositoryWebhooks {
+    Table,
+    Id,
+    RepositoryId,
+    Provider,
+    SecretEncrypted,
+    IsActive,
+    LastDeliveryAt,
+    LastEvent,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Repositories {
+    Table,
+    Id,
+}
@@ -0,0 +1,50 @@
+//! `trigger` column for `merge_operations`.
+//!
+//! Bulk merges started from the UI and merges performed by the auto-merge job
+//! share the operations history. The column records which path created the
+//! row (`manual` or `auto_merge`) so the history can tell them apart. Existing
+//! rows predate auto-merge and default to `manual`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperations::Trigger)
+                            .string()
+                            .not_null()
+                            .default("manual"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .drop_column(MergeOperations::Trigger)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MergeOperations {
+    Table,
+    Trigger,
+}
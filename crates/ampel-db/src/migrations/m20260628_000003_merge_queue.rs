@@ -0,0 +1,180 @@
+//! Merge-queue columns for `merge_operations` and `merge_operation_items`.
+//!
+//! Bulk merges used to run inside the HTTP request. They are now persisted as
+//! `queued` operations that the worker drains, so the operation row has to carry
+//! what the request used to hold in memory (strategy, branch deletion, merge
+//! delay) plus the queue bookkeeping: a `cancel_requested` flag the worker checks
+//! between items, and a `heartbeat_at` lease so an operation left `in_progress`
+//! by a crashed worker can be reclaimed. Items gain a `position` (request order)
+//! and an `attempts` counter for transient-error retries.
+//!
+//! Plain `ADD COLUMN`s, so the migration applies on SQLite as well as PostgreSQL.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .add_column(ColumnDef::new(MergeOperations::Strategy).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperations::DeleteBranch)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperations::MergeDelaySeconds)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperations::CancelRequested)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperations::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperations::HeartbeatAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperationItems::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperationItems::Position)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MergeOperationItems::Table)
+                    .add_column(
+                        ColumnDef::new(MergeOperationItems::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The worker claims the oldest queued operation on every tick.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_merge_operations_status_started")
+                    .table(MergeOperations::Table)
+                    .col(MergeOperations::Status)
+                    .col(MergeOperations::StartedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_merge_operations_status_started")
+                    .table(MergeOperations::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        for col in [MergeOperationItems::Attempts, MergeOperationItems::Position] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MergeOperationItems::Table)
+                        .drop_column(col)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        for col in [
+            MergeOperations::HeartbeatAt,
+            MergeOperations::CancelRequested,
+            MergeOperations::MergeDelaySeconds,
+            MergeOperations::DeleteBranch,
+            MergeOperations::Strategy,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MergeOperations::Table)
+                        .drop_column(col)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum MergeOperations {
+    Table,
+    Status,
+    StartedAt,
+    Strategy,
+    DeleteBranch,
+    MergeDelaySeconds,
+    CancelRequested,
+    HeartbeatAt,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum MergeOperationItems {
+    Table,
+    Position,
+    Attempts,
+}
@@ -0,0 +1,258 @@
+//! `organization_members` and `organization_invitations` tables — org-level RBAC.
+//!
+//! Every organization member has one role: `owner`, `admin` or `member`. The
+//! existing `organizations.owner_id` stays as the creator/billing owner and is
+//! backfilled as an `owner` member, so organizations created before this
+//! migration keep exactly one owner.
+//!
+//! Invitations are addressed to an email and accepted with a single-use token,
+//! of which only the SHA-256 hash is stored.
+//!
+//! Foreign keys are declared inline in `CREATE TABLE`, so the migration also
+//! applies on SQLite.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrganizationMembers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrganizationMembers::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMembers::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMembers::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMembers::Role)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMembers::JoinedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_organization_members_organization")
+                            .from(
+                                OrganizationMembers::Table,
+                                OrganizationMembers::OrganizationId,
+                            )
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_organization_members_user")
+                            .from(OrganizationMembers::Table, OrganizationMembers::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_organization_members_org_user")
+                    .table(OrganizationMembers::Table)
+                    .col(OrganizationMembers::OrganizationId)
+                    .col(OrganizationMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_organization_members_user")
+                    .table(OrganizationMembers::Table)
+                    .col(OrganizationMembers::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing owners become `owner` members. Reusing the organization id
+        // as the membership id keeps the statement portable (no UUID function).
+        let backfill = Query::insert()
+            .into_table(OrganizationMembers::Table)
+            .columns([
+                OrganizationMembers::Id,
+                OrganizationMembers::OrganizationId,
+                OrganizationMembers::UserId,
+                OrganizationMembers::Role,
+                OrganizationMembers::JoinedAt,
+            ])
+            .select_from(
+                Query::select()
+                    .expr(Expr::col(Organizations::Id))
+                    .expr(Expr::col(Organizations::Id))
+                    .column(Organizations::OwnerId)
+                    .expr(Expr::val("owner"))
+                    .column(Organizations::CreatedAt)
+                    .from(Organizations::Table)
+                    .to_owned(),
+            )
+            .map_err(|e| DbErr::Migration(e.to_string()))?
+            .to_owned();
+        manager.exec_stmt(backfill).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrganizationInvitations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::Email)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::Role)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::TokenHash)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(OrganizationInvitations::InvitedBy).uuid())
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::AcceptedAt)
+                            .timestamp_with_time_zone(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationInvitations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_organization_invitations_organization")
+                            .from(
+                                OrganizationInvitations::Table,
+                                OrganizationInvitations::OrganizationId,
+                            )
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_organization_invitations_invited_by")
+                            .from(
+                                OrganizationInvitations::Table,
+                                OrganizationInvitations::InvitedBy,
+                            )
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_organization_invitations_org")
+                    .table(OrganizationInvitations::Table)
+                    .col(OrganizationInvitations::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(OrganizationInvitations::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(OrganizationMembers::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrganizationMembers {
+    Table,
+    Id,
+    OrganizationId,
+    UserId,
+    Role,
+    JoinedAt,
+}
+
+#[derive(DeriveIden)]
+enum OrganizationInvitations {
+    Table,
+    Id,
+    OrganizationId,
+    Email,
+    Role,
+    TokenHash,
+    InvitedBy,
+    ExpiresAt,
+    AcceptedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Organizations {
+    Table,
+    Id,
+    OwnerId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,166 @@
+//! `notification_log` table and `pull_requests.ampel_status` column.
+//!
+//! The worker records each PR's last computed traffic-light status so a sync
+//! can tell when a PR turns green or red. Every notification it raises is
+//! queued in `notification_log`, one row per user and delivery channel, and
+//! the dispatch job sends pending rows, retrying failures with backoff. The
+//! unique `(user_id, channel, dedup_key)` index makes enqueueing idempotent,
+//! so an event observed twice (say by a webhook and a poll) is sent once.
+//!
+//! Existing PRs start without a status; their first sync records a baseline
+//! rather than raising a notification.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .add_column(ColumnDef::new(PullRequests::AmpelStatus).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NotificationLog::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NotificationLog::UserId).uuid().not_null())
+                    .col(ColumnDef::new(NotificationLog::PullRequestId).uuid())
+                    .col(ColumnDef::new(NotificationLog::Kind).string().not_null())
+                    .col(ColumnDef::new(NotificationLog::Channel).string().not_null())
+                    .col(
+                        ColumnDef::new(NotificationLog::DedupKey)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NotificationLog::Payload).text().not_null())
+                    .col(
+                        ColumnDef::new(NotificationLog::Status)
+                            .string()
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationLog::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationLog::NextAttemptAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NotificationLog::LastError).text())
+                    .col(
+                        ColumnDef::new(NotificationLog::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(NotificationLog::SentAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_log_user")
+                            .from(NotificationLog::Table, NotificationLog::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_log_pull_request")
+                            .from(NotificationLog::Table, NotificationLog::PullRequestId)
+                            .to(PullRequests::Table, PullRequests::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_log_dedup")
+                    .table(NotificationLog::Table)
+                    .col(NotificationLog::UserId)
+                    .col(NotificationLog::Channel)
+                    .col(NotificationLog::DedupKey)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_log_due")
+                    .table(NotificationLog::Table)
+                    .col(NotificationLog::Status)
+                    .col(NotificationLog::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NotificationLog::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .drop_column(PullRequests::AmpelStatus)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotificationLog {
+    Table,
+    Id,
+    UserId,
+    PullRequestId,
+    Kind,
+    Channel,
+    DedupKey,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    LastError,
+    CreatedAt,
+    SentAt,
+}
+
+#[derive(DeriveIden)]
+enum PullRequests {
+    Table,
+    Id,
+    AmpelStatus,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,44 @@
+//! `pull_requests.ampel_status_changed_at` column.
+//!
+//! Records when a PR's stored traffic-light status last changed, so digests
+//! can list the PRs that turned red during their period. PRs whose status has
+//! not changed since this migration keep a null timestamp.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .add_column(
+                        ColumnDef::new(PullRequests::AmpelStatusChangedAt)
+                            .timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .drop_column(PullRequests::AmpelStatusChangedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PullRequests {
+    Table,
+    AmpelStatusChangedAt,
+}
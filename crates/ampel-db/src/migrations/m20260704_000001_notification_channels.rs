@@ -0,0 +1,183 @@
+//! `notification_channels` table and `notification_log.channel_id` column.
+//!
+//! A notification channel is a chat room or endpoint (Slack, Microsoft Teams,
+//! Discord, Matrix or a signed JSON webhook) that a user configures for
+//! themselves or a team admin configures for their team. Its settings hold
+//! webhook URLs and tokens, so they are stored encrypted. `events` lists the
+//! notification kinds the channel receives, as a JSON array; `NULL` means all.
+//!
+//! Notifications queued for a configured channel carry its id, so deleting a
+//! channel drops its undelivered notifications with it.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationChannels::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NotificationChannels::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NotificationChannels::UserId).uuid())
+                    .col(ColumnDef::new(NotificationChannels::TeamId).uuid())
+                    .col(
+                        ColumnDef::new(NotificationChannels::Kind)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationChannels::Name)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationChannels::ConfigEncrypted)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NotificationChannels::Events).text())
+                    .col(
+                        ColumnDef::new(NotificationChannels::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationChannels::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationChannels::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_channels_user")
+                            .from(NotificationChannels::Table, NotificationChannels::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_channels_team")
+                            .from(NotificationChannels::Table, NotificationChannels::TeamId)
+                            .to(Teams::Table, Teams::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_channels_user")
+                    .table(NotificationChannels::Table)
+                    .col(NotificationChannels::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_channels_team")
+                    .table(NotificationChannels::Table)
+                    .col(NotificationChannels::TeamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationLog::Table)
+                    .add_column(ColumnDef::new(NotificationLog::ChannelId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_notification_log_channel")
+                    .from(NotificationLog::Table, NotificationLog::ChannelId)
+                    .to(NotificationChannels::Table, NotificationChannels::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .table(NotificationLog::Table)
+                    .name("fk_notification_log_channel")
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationLog::Table)
+                    .drop_column(NotificationLog::ChannelId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(NotificationChannels::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotificationChannels {
+    Table,
+    Id,
+    UserId,
+    TeamId,
+    Kind,
+    Name,
+    ConfigEncrypted,
+    Events,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum NotificationLog {
+    Table,
+    ChannelId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Teams {
+    Table,
+    Id,
+}
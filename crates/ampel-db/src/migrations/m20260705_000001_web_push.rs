@@ -0,0 +1,138 @@
+//! `vapid_keys` and `push_subscriptions` tables for browser push notifications.
+//!
+//! `vapid_keys` holds the single key pair this deployment signs push requests
+//! with (row id 1), generated on first use. Its private key is encrypted.
+//!
+//! A push subscription is one browser on one device, identified by the push
+//! service endpoint it was given. Re-registering an endpoint moves it to the
+//! user now signed in on that browser, so endpoints are unique.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VapidKeys::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VapidKeys::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VapidKeys::PublicKey).string().not_null())
+                    .col(
+                        ColumnDef::new(VapidKeys::PrivateKeyEncrypted)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VapidKeys::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PushSubscriptions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PushSubscriptions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PushSubscriptions::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(PushSubscriptions::Endpoint)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PushSubscriptions::P256dh)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PushSubscriptions::Auth).string().not_null())
+                    .col(ColumnDef::new(PushSubscriptions::UserAgent).string())
+                    .col(
+                        ColumnDef::new(PushSubscriptions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(PushSubscriptions::LastUsedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_push_subscriptions_user")
+                            .from(PushSubscriptions::Table, PushSubscriptions::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_push_subscriptions_user")
+                    .table(PushSubscriptions::Table)
+                    .col(PushSubscriptions::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PushSubscriptions::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(VapidKeys::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum VapidKeys {
+    Table,
+    Id,
+    PublicKey,
+    PrivateKeyEncrypted,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PushSubscriptions {
+    Table,
+    Id,
+    UserId,
+    Endpoint,
+    P256dh,
+    Auth,
+    UserAgent,
+    CreatedAt,
+    LastUsedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
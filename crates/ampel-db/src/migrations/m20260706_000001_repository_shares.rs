@@ -0,0 +1,161 @@
+//! `repository_shares` table.
+//!
+//! A repository belongs to the user who added it. Sharing it with a team or
+//! an organization lets their members see its PRs on team dashboards and act
+//! on them, with permissions that follow their team or organization role.
+//! Each share names exactly one of `team_id` and `organization_id`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RepositoryShares::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RepositoryShares::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RepositoryShares::RepositoryId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RepositoryShares::TeamId).uuid())
+                    .col(ColumnDef::new(RepositoryShares::OrganizationId).uuid())
+                    .col(ColumnDef::new(RepositoryShares::CreatedBy).uuid())
+                    .col(
+                        ColumnDef::new(RepositoryShares::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_repository_shares_repository")
+                            .from(RepositoryShares::Table, RepositoryShares::RepositoryId)
+                            .to(Repositories::Table, Repositories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_repository_shares_team")
+                            .from(RepositoryShares::Table, RepositoryShares::TeamId)
+                            .to(Teams::Table, Teams::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_repository_shares_organization")
+                            .from(RepositoryShares::Table, RepositoryShares::OrganizationId)
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_repository_shares_created_by")
+                            .from(RepositoryShares::Table, RepositoryShares::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_repository_shares_repository_team")
+                    .table(RepositoryShares::Table)
+                    .col(RepositoryShares::RepositoryId)
+                    .col(RepositoryShares::TeamId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_repository_shares_repository_organization")
+                    .table(RepositoryShares::Table)
+                    .col(RepositoryShares::RepositoryId)
+                    .col(RepositoryShares::OrganizationId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_repository_shares_team")
+                    .table(RepositoryShares::Table)
+                    .col(RepositoryShares::TeamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_repository_shares_organization")
+                    .table(RepositoryShares::Table)
+                    .col(RepositoryShares::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RepositoryShares::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum RepositoryShares {
+    Table,
+    Id,
+    RepositoryId,
+    TeamId,
+    OrganizationId,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Repositories {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Teams {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Organizations {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
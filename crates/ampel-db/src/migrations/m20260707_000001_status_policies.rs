@@ -0,0 +1,169 @@
+//! `status_policies` table.
+//!
+//! A status policy tunes the traffic-light rules: which checks are required
+//! or ignored, how skipped and cancelled checks count, how many approvals a
+//! PR needs and whether drafts show grey. Each policy names exactly one of
+//! `repository_id` and `team_id`; a team's policy is the default for the
+//! repositories shared with it. List columns hold JSON arrays of strings.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StatusPolicies::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StatusPolicies::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(StatusPolicies::RepositoryId).uuid())
+                    .col(ColumnDef::new(StatusPolicies::TeamId).uuid())
+                    .col(
+                        ColumnDef::new(StatusPolicies::RequiredChecks)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::IgnoredChecks)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::MinApprovals)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::RequireCodeOwnerApproval)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(StatusPolicies::CodeOwners).text().not_null())
+                    .col(
+                        ColumnDef::new(StatusPolicies::DismissStaleApprovals)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::SkippedChecks)
+                            .string_len(16)
+                            .not_null()
+                            .default("pass"),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::CancelledChecks)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::DraftStatus)
+                            .string_len(16)
+                            .not_null()
+                            .default("yellow"),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(StatusPolicies::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_status_policies_repository")
+                            .from(StatusPolicies::Table, StatusPolicies::RepositoryId)
+                            .to(Repositories::Table, Repositories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_status_policies_team")
+                            .from(StatusPolicies::Table, StatusPolicies::TeamId)
+                            .to(Teams::Table, Teams::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_status_policies_repository")
+                    .table(StatusPolicies::Table)
+                    .col(StatusPolicies::RepositoryId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_status_policies_team")
+                    .table(StatusPolicies::Table)
+                    .col(StatusPolicies::TeamId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StatusPolicies::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum StatusPolicies {
+    Table,
+    Id,
+    RepositoryId,
+    TeamId,
+    RequiredChecks,
+    IgnoredChecks,
+    MinApprovals,
+    RequireCodeOwnerApproval,
+    CodeOwners,
+    DismissStaleApprovals,
+    SkippedChecks,
+    CancelledChecks,
+    DraftStatus,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Repositories {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Teams {
+    Table,
+    Id,
+}
@@ -0,0 +1,65 @@
+//! `pull_requests.head_sha` and `reviews.commit_sha` columns.
+//!
+//! Record the commit at the tip of a PR and the commit each review was given
+//! on, so a status policy can stop counting approvals given before the latest
+//! push. Both stay null where the provider does not report them.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .add_column(ColumnDef::new(PullRequests::HeadSha).string_len(64))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reviews::Table)
+                    .add_column(ColumnDef::new(Reviews::CommitSha).string_len(64))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reviews::Table)
+                    .drop_column(Reviews::CommitSha)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .drop_column(PullRequests::HeadSha)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PullRequests {
+    Table,
+    HeadSha,
+}
+
+#[derive(DeriveIden)]
+enum Reviews {
+    Table,
+    CommitSha,
+}
@@ -0,0 +1,112 @@
+//! `saved_views` table.
+//!
+//! A saved view is a named PR filter query owned by a user. Setting
+//! `team_id` shares it with the team's members.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedViews::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SavedViews::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SavedViews::UserId).uuid().not_null())
+                    .col(ColumnDef::new(SavedViews::TeamId).uuid())
+                    .col(ColumnDef::new(SavedViews::Name).string_len(100).not_null())
+                    .col(ColumnDef::new(SavedViews::Query).text().not_null())
+                    .col(
+                        ColumnDef::new(SavedViews::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(SavedViews::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_saved_views_user")
+                            .from(SavedViews::Table, SavedViews::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_saved_views_team")
+                            .from(SavedViews::Table, SavedViews::TeamId)
+                            .to(Teams::Table, Teams::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_saved_views_user")
+                    .table(SavedViews::Table)
+                    .col(SavedViews::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_saved_views_team")
+                    .table(SavedViews::Table)
+                    .col(SavedViews::TeamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SavedViews::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SavedViews {
+    Table,
+    Id,
+    UserId,
+    TeamId,
+    Name,
+    Query,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Teams {
+    Table,
+    Id,
+}
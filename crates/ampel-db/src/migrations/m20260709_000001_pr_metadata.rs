@@ -0,0 +1,171 @@
+//! `pull_requests.milestone` column and the `pull_request_labels` and
+//! `pull_request_participants` tables.
+//!
+//! Labels, assignees and requested reviewers are replaced wholesale on every
+//! sync. A participant's `role` is `assignee` or `reviewer`; reviewers are
+//! those whose review is requested and still outstanding.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .add_column(ColumnDef::new(PullRequests::Milestone).string_len(255))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PullRequestLabels::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PullRequestLabels::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PullRequestLabels::PullRequestId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PullRequestLabels::Name)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pull_request_labels_pull_request")
+                            .from(PullRequestLabels::Table, PullRequestLabels::PullRequestId)
+                            .to(PullRequests::Table, PullRequests::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pull_request_labels_unique")
+                    .table(PullRequestLabels::Table)
+                    .col(PullRequestLabels::PullRequestId)
+                    .col(PullRequestLabels::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PullRequestParticipants::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PullRequestParticipants::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PullRequestParticipants::PullRequestId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PullRequestParticipants::Login)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PullRequestParticipants::Role)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_pull_request_participants_pull_request")
+                            .from(
+                                PullRequestParticipants::Table,
+                                PullRequestParticipants::PullRequestId,
+                            )
+                            .to(PullRequests::Table, PullRequests::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pull_request_participants_unique")
+                    .table(PullRequestParticipants::Table)
+                    .col(PullRequestParticipants::PullRequestId)
+                    .col(PullRequestParticipants::Role)
+                    .col(PullRequestParticipants::Login)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PullRequestParticipants::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PullRequestLabels::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PullRequests::Table)
+                    .drop_column(PullRequests::Milestone)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PullRequests {
+    Table,
+    Id,
+    Milestone,
+}
+
+#[derive(DeriveIden)]
+enum PullRequestLabels {
+    Table,
+    Id,
+    PullRequestId,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum PullRequestParticipants {
+    Table,
+    Id,
+    PullRequestId,
+    Login,
+    Role,
+}
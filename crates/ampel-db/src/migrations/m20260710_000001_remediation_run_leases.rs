@@ -0,0 +1,67 @@
+//! Queue bookkeeping columns for `remediation_run`.
+//!
+//! Runs are executed from a durable job queue rather than inline in the sweep.
+//! `queued_at` records when a run was handed to the queue (NULL = waiting to be
+//! dispatched); `heartbeat_at` is the lease a worker refreshes while it drives
+//! the run (NULL = nobody holds it). A lease older than the worker's TTL
+//! belongs to a dead worker and may be taken over. Plain `ADD COLUMN`s, so they
+//! apply on SQLite as well as PostgreSQL.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RemediationRun::Table)
+                    .add_column(ColumnDef::new(RemediationRun::QueuedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RemediationRun::Table)
+                    .add_column(
+                        ColumnDef::new(RemediationRun::HeartbeatAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RemediationRun::Table)
+                    .drop_column(RemediationRun::HeartbeatAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RemediationRun::Table)
+                    .drop_column(RemediationRun::QueuedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum RemediationRun {
+    Table,
+    QueuedAt,
+    HeartbeatAt,
+}
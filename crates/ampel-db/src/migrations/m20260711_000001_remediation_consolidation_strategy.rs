@@ -0,0 +1,46 @@
+//! `remediation_policy.consolidation_strategy` column.
+//!
+//! How the sandbox combines the selected PR branches: `merge` (one merge commit
+//! per PR, the previous behavior and the default for existing policies),
+//! `rebase_onto_base` or `squash_per_pr`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RemediationPolicy::Table)
+                    .add_column(
+                        ColumnDef::new(RemediationPolicy::ConsolidationStrategy)
+                            .string()
+                            .not_null()
+                            .default("merge"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RemediationPolicy::Table)
+                    .drop_column(RemediationPolicy::ConsolidationStrategy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RemediationPolicy {
+    Table,
+    ConsolidationStrategy,
+}
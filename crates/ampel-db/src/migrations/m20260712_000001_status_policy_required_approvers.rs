@@ -0,0 +1,67 @@
+//! Rename `status_policies.require_code_owner_approval` and `code_owners` to
+//! `require_named_approver` and `required_approvers`.
+//!
+//! The list was never read from CODEOWNERS and does not depend on the paths a
+//! PR touches, so the old names promised more than the rule does.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        rename(
+            manager,
+            StatusPolicies::RequireCodeOwnerApproval,
+            StatusPolicies::RequireNamedApprover,
+        )
+        .await?;
+        rename(
+            manager,
+            StatusPolicies::CodeOwners,
+            StatusPolicies::RequiredApprovers,
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        rename(
+            manager,
+            StatusPolicies::RequireNamedApprover,
+            StatusPolicies::RequireCodeOwnerApproval,
+        )
+        .await?;
+        rename(
+            manager,
+            StatusPolicies::RequiredApprovers,
+            StatusPolicies::CodeOwners,
+        )
+        .await
+    }
+}
+
+async fn rename(
+    manager: &SchemaManager<'_>,
+    from: StatusPolicies,
+    to: StatusPolicies,
+) -> Result<(), DbErr> {
+    manager
+        .alter_table(
+            Table::alter()
+                .table(StatusPolicies::Table)
+                .rename_column(from, to)
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+enum StatusPolicies {
+    Table,
+    RequireCodeOwnerApproval,
+    CodeOwners,
+    RequireNamedApprover,
+    RequiredApprovers,
+}
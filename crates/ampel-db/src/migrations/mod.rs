@@ -14,6 +14,25 @@ mod m20260627_000001_remediation_run_phase2_columns;
 mod m20260627_000002_model_provider_phase4_columns;
 mod m20260627_000003_remediation_playbook_scope;
 mod m20260627_000004_learning_signal;
+mod m20260628_000001_repository_webhooks;
+mod m20260628_000002_merge_operation_trigger;
+mod m20260628_000003_merge_queue;
+mod m20260629_000001_refresh_tokens;
+mod m20260629_000002_user_identities;
+mod m20260630_000001_organization_members;
+mod m20260701_000001_api_tokens;
+mod m20260702_000001_notification_log;
+mod m20260703_000001_pr_status_changed_at;
+mod m20260704_000001_notification_channels;
+mod m20260705_000001_web_push;
+mod m20260706_000001_repository_shares;
+mod m20260707_000001_status_policies;
+mod m20260707_000002_commit_shas;
+mod m20260708_000001_saved_views;
+mod m20260709_000001_pr_metadata;
+mod m20260710_000001_remediation_run_leases;
+mod m20260711_000001_remediation_consolidation_strategy;
+mod m20260712_000001_status_policy_required_approvers;
 
 use sea_orm_migration::prelude::*;
 
@@ -25,14 +44,16 @@ pub struct Migrator;
 /// The full [`Migrator`] cannot run against SQLite (the `provider_accounts`
 /// migration uses `ALTER TABLE ... ADD FOREIGN KEY` + a partial unique index).
 /// The remediation migrations, however, are self-contained, so this helper
-/// applies exactly those — the loops tables plus the Phase-2 columns — directly
-/// via a [`SchemaManager`]. It is intentionally `pub` (not `#[cfg(test)]`) so
-/// integration tests in other crates can reuse it.
+/// applies exactly those — the loops tables, the Phase-2 columns, the queue
+/// lease columns and the policy's consolidation strategy — directly via a
+/// [`SchemaManager`]. It is intentionally `pub`
+/// (not `#[cfg(test)]`) so integration tests in other crates can reuse it.
 pub mod test_support {
     use sea_orm_migration::prelude::DbErr;
     use sea_orm_migration::{MigrationTrait, SchemaManager};
 
-    /// Apply the remediation schema (loops tables + Phase-2 columns) to `manager`.
+    /// Apply the remediation schema (loops tables + Phase-2, lease and
+    /// consolidation-strategy columns) to `manager`.
     pub async fn apply_remediation_schema(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
         super::m20260626_000001_remediation_loops::Migration
             .up(manager)
@@ -40,6 +61,12 @@ pub mod test_support {
         super::m20260627_000001_remediation_run_phase2_columns::Migration
             .up(manager)
             .await?;
+        super::m20260710_000001_remediation_run_leases::Migration
+            .up(manager)
+            .await?;
+        super::m20260711_000001_remediation_consolidation_strategy::Migration
+            .up(manager)
+            .await?;
         Ok(())
     }
 }
@@ -64,6 +91,25 @@ impl MigratorTrait for Migrator {
             Box::new(m20260627_000002_model_provider_phase4_columns::Migration),
             Box::new(m20260627_000003_remediation_playbook_scope::Migration),
             Box::new(m20260627_000004_learning_signal::Migration),
+            Box::new(m20260628_000001_repository_webhooks::Migration),
+            Box::new(m20260628_000002_merge_operation_trigger::Migration),
+            Box::new(m20260628_000003_merge_queue::Migration),
+            Box::new(m20260629_000001_refresh_tokens::Migration),
+            Box::new(m20260629_000002_user_identities::Migration),
+            Box::new(m20260630_000001_organization_members::Migration),
+            Box::new(m20260701_000001_api_tokens::Migration),
+            Box::new(m20260702_000001_notification_log::Migration),
+            Box::new(m20260703_000001_pr_status_changed_at::Migration),
+            Box::new(m20260704_000001_notification_channels::Migration),
+            Box::new(m20260705_000001_web_push::Migration),
+            Box::new(m20260706_000001_repository_shares::Migration),
+            Box::new(m20260707_000001_status_policies::Migration),
+            Box::new(m20260707_000002_commit_shas::Migration),
+            Box::new(m20260708_000001_saved_views::Migration),
+            Box::new(m20260709_000001_pr_metadata::Migration),
+            Box::new(m20260710_000001_remediation_run_leases::Migration),
+            Box::new(m20260711_000001_remediation_consolidation_strategy::Migration),
+            Box::new(m20260712_000001_status_policy_required_approvers::Migration),
         ]
     }
 }
@@ -80,10 +126,13 @@ mod tests {
     //! SQLite database.
 
     use crate::entities::{
-        learning_signal, model_provider_account, remediation_agent_session, remediation_playbook,
-        remediation_policy, remediation_run, remediation_run_pr,
+        learning_signal, model_provider_account, organization_invitation, organization_member,
+        refresh_token, remediation_agent_session, remediation_playbook, remediation_policy,
+        remediation_run, remediation_run_pr, repository_webhook, user_identity,
+    };
+    use sea_orm::{
+        ConnectionTrait, Database, DatabaseConnection, DbBackend, EntityTrait, Statement,
     };
-    use sea_orm::{Database, DatabaseConnection, EntityTrait};
     use sea_orm_migration::{MigrationTrait, SchemaManager};
 
     async fn apply_remediation_migrations() -> DatabaseConnection {
@@ -116,6 +165,14 @@ mod tests {
             .up(&manager)
             .await
             .expect("up learning_signal");
+        super::m20260710_000001_remediation_run_leases::Migration
+            .up(&manager)
+            .await
+            .expect("up remediation_run_leases");
+        super::m20260711_000001_remediation_consolidation_strategy::Migration
+            .up(&manager)
+            .await
+            .expect("up remediation_consolidation_strategy");
 
         conn
     }
@@ -156,6 +213,164 @@ mod tests {
             .expect("learning_signal table exists");
     }
 
+    #[tokio::test]
+    async fn should_create_and_drop_repository_webhooks_on_sqlite() {
+        // Arrange
+        let conn = Database::connect("sqlite::memory:")
+            .await
+            .expect("connect sqlite");
+        let manager = SchemaManager::new(&conn);
+
+        // Act
+        super::m20260628_000001_repository_webhooks::Migration
+            .up(&manager)
+            .await
+            .expect("up repository_webhooks");
+
+        // Assert
+        repository_webhook::Entity::find()
+            .all(&conn)
+            .await
+            .expect("repository_webhooks table exists");
+
+        super::m20260628_000001_repository_webhooks::Migration
+            .down(&manager)
+            .await
+            .expect("down repository_webhooks");
+        assert!(
+            repository_webhook::Entity::find().all(&conn).await.is_err(),
+            "repository_webhooks should be dropped"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_create_and_drop_refresh_tokens_on_sqlite() {
+        // Arrange
+        let conn = Database::connect("sqlite::memory:")
+            .await
+            .expect("connect sqlite");
+        let manager = SchemaManager::new(&conn);
+
+        // Act
+        super::m20260629_000001_refresh_tokens::Migration
+            .up(&manager)
+            .await
+            .expect("up refresh_tokens");
+
+        // Assert
+        refresh_token::Entity::find()
+            .all(&conn)
+            .await
+            .expect("refresh_tokens table exists");
+
+        super::m20260629_000001_refresh_tokens::Migration
+            .down(&manager)
+            .await
+            .expect("down refresh_tokens");
+        assert!(
+            refresh_token::Entity::find().all(&conn).await.is_err(),
+            "refresh_tokens should be dropped"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_create_and_drop_user_identities_on_sqlite() {
+        // Arrange
+        let conn = Database::connect("sqlite::memory:")
+            .await
+            .expect("connect sqlite");
+        let manager = SchemaManager::new(&conn);
+
+        // Act
+        super::m20260629_000002_user_identities::Migration
+            .up(&manager)
+            .await
+            .expect("up user_identities");
+
+        // Assert
+        user_identity::Entity::find()
+            .all(&conn)
+            .await
+            .expect("user_identities table exists");
+
+        super::m20260629_000002_user_identities::Migration
+            .down(&manager)
+            .await
+            .expect("down user_identities");
+        assert!(
+            user_identity::Entity::find().all(&conn).await.is_err(),
+            "user_identities should be dropped"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_backfill_organization_owners_on_sqlite() {
+        // Arrange: an organization created before memberships existed
+        let conn = Database::connect("sqlite::memory:")
+            .await
+            .expect("connect sqlite");
+        let manager = SchemaManager::new(&conn);
+        super::m20250101_000001_initial::Migration
+            .up(&manager)
+            .await
+            .expect("up initial");
+        super::m20260626_000003_org_air_gapped::Migration
+            .up(&manager)
+            .await
+            .expect("up org_air_gapped");
+
+        let user_id = uuid::Uuid::new_v4();
+        let org_id = uuid::Uuid::new_v4();
+        conn.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "INSERT INTO users (id, email, password_hash, created_at, updated_at) \
+             VALUES (?, 'owner@example.com', 'x', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+            [user_id.into()],
+        ))
+        .await
+        .expect("insert user");
+        conn.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "INSERT INTO organizations (id, owner_id, name, slug, created_at, updated_at) \
+             VALUES (?, ?, 'Acme', 'acme', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+            [org_id.into(), user_id.into()],
+        ))
+        .await
+        .expect("insert organization");
+
+        // Act
+        super::m20260630_000001_organization_members::Migration
+            .up(&manager)
+            .await
+            .expect("up organization_members");
+
+        // Assert
+        let members = organization_member::Entity::find()
+            .all(&conn)
+            .await
+            .expect("organization_members table exists");
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].organization_id, org_id);
+        assert_eq!(members[0].user_id, user_id);
+        assert_eq!(members[0].role, "owner");
+        organization_invitation::Entity::find()
+            .all(&conn)
+            .await
+            .expect("organization_invitations table exists");
+
+        super::m20260630_000001_organization_members::Migration
+            .down(&manager)
+            .await
+            .expect("down organization_members");
+        assert!(
+            organization_member::Entity::find()
+                .all(&conn)
+                .await
+                .is_err(),
+            "organization_members should be dropped"
+        );
+    }
+
     #[tokio::test]
     async fn should_drop_all_remediation_tables_on_down() {
         // Arrange
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    sea_query::{Condition, Expr},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::entities::api_token::{ActiveModel, Column, Entity, Model};
+use crate::entities::user;
+
+/// How stale `last_used_at` may get before a request refreshes it, so busy
+/// scripts do not write on every call
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// A personal API token to record when it is created
+pub struct NewApiToken {
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub token_prefix: String,
+    /// JSON array of scope names
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub struct ApiTokenQueries;
+
+impl ApiTokenQueries {
+    /// Record a created token
+    pub async fn create(db: &DatabaseConnection, token: NewApiToken) -> Result<Model, DbErr> {
+        let token = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(token.user_id),
+            name: Set(token.name),
+            token_hash: Set(token.token_hash),
+            token_prefix: Set(token.token_prefix),
+            scopes: Set(token.scopes),
+            expires_at: Set(token.expires_at),
+            last_used_at: Set(None),
+            revoked_at: Set(None),
+            created_at: Set(Utc::now()),
+        };
+
+        token.insert(db).await
+    }
+
+    /// Find a live token by the hash of its value, together with its owner
+    pub async fn find_active_by_hash(
+        db: &DatabaseConnection,
+        token_hash: &str,
+    ) -> Result<Option<(Model, user::Model)>, DbErr> {
+        let found = Entity::find()
+            .filter(Column::TokenHash.eq(token_hash))
+            .filter(Column::RevokedAt.is_null())
+            .filter(
+                Condition::any()
+                    .add(Column::ExpiresAt.is_null())
+                    .add(Column::ExpiresAt.gt(Utc::now())),
+            )
+            .find_also_related(user::Entity)
+            .one(db)
+            .await?;
+
+        Ok(found.and_then(|(token, user)| user.map(|user| (token, user))))
+    }
+
+    /// Record that a token was just used
+    pub async fn touch(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        let now = Utc::now();
+        Entity::update_many()
+            .col_expr(Column::LastUsedAt, Expr::value(now))
+            .filter(Column::Id.eq(id))
+            .filter(
+                Condition::any()
+                    .add(Column::LastUsedAt.is_null())
+                    .add(Column::LastUsedAt.lt(now - Duration::seconds(LAST_USED_RESOLUTION_SECS))),
+            )
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// A user's tokens that have not been revoked, newest first
+    pub async fn list_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::RevokedAt.is_null())
+            .order_by_desc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Revoke one of a user's tokens. Returns `false` if the user has no
+    /// unrevoked token with that id.
+    pub async fn revoke(db: &DatabaseConnection, user_id: Uuid, id: Uuid) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Delete tokens that were revoked or expired before `before`
+    pub async fn delete_inactive(
+        db: &DatabaseConnection,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::delete_many()
+            .filter(
+                Condition::any()
+                    .add(Column::RevokedAt.lt(before))
+                    .add(Column::ExpiresAt.lt(before)),
+            )
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
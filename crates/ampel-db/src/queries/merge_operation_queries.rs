@@ -1,7 +1,7 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
-    QuerySelect, Set,
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
 };
 use uuid::Uuid;
 
@@ -43,10 +43,14 @@ impl MergeOperationQueries {
     }
 
     /// Create a new merge operation
+    ///
+    /// `trigger` records what started it: `manual` for bulk merges requested
+    /// through the API, `auto_merge` for the worker's auto-merge job.
     pub async fn create(
         db: &DatabaseConnection,
         user_id: Uuid,
         total_count: i32,
+        trigger: &str,
     ) -> Result<Model, DbErr> {
         let now = Utc::now();
         let operation = ActiveModel {
@@ -60,10 +64,165 @@ impl MergeOperationQueries {
             skipped_count: Set(0),
             status: Set("in_progress".to_string()),
             notification_sent: Set(false),
+            trigger: Set(trigger.to_string()),
+            strategy: Set(None),
+            delete_branch: Set(false),
+            merge_delay_seconds: Set(0),
+            cancel_requested: Set(false),
+            heartbeat_at: Set(None),
         };
         operation.insert(db).await
     }
 
+    /// Create a `queued` bulk merge for the worker's merge queue to drain
+    pub async fn enqueue(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        total_count: i32,
+        strategy: &str,
+        delete_branch: bool,
+        merge_delay_seconds: i32,
+    ) -> Result<Model, DbErr> {
+        let now = Utc::now();
+        let operation = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            started_at: Set(now),
+            completed_at: Set(None),
+            total_count: Set(total_count),
+            success_count: Set(0),
+            failed_count: Set(0),
+            skipped_count: Set(0),
+            status: Set("queued".to_string()),
+            notification_sent: Set(false),
+            trigger: Set("manual".to_string()),
+            strategy: Set(Some(strategy.to_string())),
+            delete_branch: Set(delete_branch),
+            merge_delay_seconds: Set(merge_delay_seconds),
+            cancel_requested: Set(false),
+            heartbeat_at: Set(None),
+        };
+        operation.insert(db).await
+    }
+
+    /// Claim the oldest runnable queued operation for this worker.
+    ///
+    /// Runnable means `queued`, or `in_progress` with a heartbeat older than
+    /// `stale_before` (its worker died). The claim is a compare-and-set on the
+    /// observed status/heartbeat, so two workers never drain the same operation.
+    pub async fn claim_next(
+        db: &DatabaseConnection,
+        stale_before: DateTime<Utc>,
+    ) -> Result<Option<Model>, DbErr> {
+        let candidates = Entity::find()
+            .filter(
+                Condition::any().add(Column::Status.eq("queued")).add(
+                    Condition::all()
+                        .add(Column::Status.eq("in_progress"))
+                        .add(Column::HeartbeatAt.lt(stale_before)),
+                ),
+            )
+            .order_by_asc(Column::StartedAt)
+            .limit(10)
+            .all(db)
+            .await?;
+
+        let now = Utc::now();
+        for candidate in candidates {
+            let mut claim = Entity::update_many()
+                .col_expr(Column::Status, Expr::value("in_progress"))
+                .col_expr(Column::HeartbeatAt, Expr::value(now))
+                .filter(Column::Id.eq(candidate.id))
+                .filter(Column::Status.eq(candidate.status.clone()));
+            claim = match candidate.heartbeat_at {
+                Some(hb) => claim.filter(Column::HeartbeatAt.eq(hb)),
+                None => claim.filter(Column::HeartbeatAt.is_null()),
+            };
+
+            if claim.exec(db).await?.rows_affected == 1 {
+                return Self::find_by_id(db, candidate.id).await;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Refresh the worker lease on an operation being drained
+    pub async fn heartbeat(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::HeartbeatAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Ask the worker to stop an operation.
+    ///
+    /// A `queued` operation has no worker yet, so it is cancelled immediately;
+    /// an `in_progress` one is flagged and the worker stops before its next item.
+    /// Returns `false` if the operation has already finished.
+    pub async fn request_cancel(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbErr> {
+        let cancelled_queued = Entity::update_many()
+            .col_expr(Column::Status, Expr::value("cancelled"))
+            .col_expr(Column::CancelRequested, Expr::value(true))
+            .col_expr(Column::CompletedAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq("queued"))
+            .exec(db)
+            .await?;
+        if cancelled_queued.rows_affected == 1 {
+            MergeOperationItemQueries::cancel_pending(db, id).await?;
+            return Ok(true);
+        }
+
+        let flagged = Entity::update_many()
+            .col_expr(Column::CancelRequested, Expr::value(true))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq("in_progress"))
+            .exec(db)
+            .await?;
+        Ok(flagged.rows_affected == 1)
+    }
+
+    /// Put a cancelled or failed operation back on the queue.
+    ///
+    /// Cancelled items return to `pending`; items that already succeeded,
+    /// failed or were skipped keep their result. Returns `false` if the
+    /// operation is not in a resumable state.
+    pub async fn resume(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbErr> {
+        let resumed = Entity::update_many()
+            .col_expr(Column::Status, Expr::value("queued"))
+            .col_expr(Column::CancelRequested, Expr::value(false))
+            .col_expr(
+                Column::CompletedAt,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .col_expr(
+                Column::HeartbeatAt,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.is_in(["cancelled", "failed"]))
+            .exec(db)
+            .await?;
+        if resumed.rows_affected == 0 {
+            return Ok(false);
+        }
+
+        merge_operation_item::Entity::update_many()
+            .col_expr(merge_operation_item::Column::Status, Expr::value("pending"))
+            .col_expr(
+                merge_operation_item::Column::ErrorMessage,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(merge_operation_item::Column::MergeOperationId.eq(id))
+            .filter(merge_operation_item::Column::Status.eq("cancelled"))
+            .exec(db)
+            .await?;
+        Ok(true)
+    }
+
     /// Update operation counts and status
     pub async fn update_counts(
         db: &DatabaseConnection,
@@ -85,7 +244,7 @@ impl MergeOperationQueries {
         active.failed_count = Set(failed_count);
         active.skipped_count = Set(skipped_count);
         active.status = Set(status.to_string());
-        if status == "completed" || status == "failed" {
+        if matches!(status, "completed" | "failed" | "cancelled") {
             active.completed_at = Set(Some(Utc::now()));
         }
         active.update(db).await
@@ -117,16 +276,46 @@ impl MergeOperationItemQueries {
     ) -> Result<Vec<merge_operation_item::Model>, DbErr> {
         merge_operation_item::Entity::find()
             .filter(merge_operation_item::Column::MergeOperationId.eq(operation_id))
+            .order_by_asc(merge_operation_item::Column::Position)
             .all(db)
             .await
     }
 
+    /// Items of an operation still waiting to be merged, in request order
+    pub async fn find_pending_by_operation(
+        db: &DatabaseConnection,
+        operation_id: Uuid,
+    ) -> Result<Vec<merge_operation_item::Model>, DbErr> {
+        merge_operation_item::Entity::find()
+            .filter(merge_operation_item::Column::MergeOperationId.eq(operation_id))
+            .filter(merge_operation_item::Column::Status.eq("pending"))
+            .order_by_asc(merge_operation_item::Column::Position)
+            .all(db)
+            .await
+    }
+
+    /// The most recent item recorded for a PR by operations with `trigger`
+    pub async fn latest_for_pull_request(
+        db: &DatabaseConnection,
+        pull_request_id: Uuid,
+        trigger: &str,
+    ) -> Result<Option<merge_operation_item::Model>, DbErr> {
+        merge_operation_item::Entity::find()
+            .inner_join(Entity)
+            .filter(merge_operation_item::Column::PullRequestId.eq(pull_request_id))
+            .filter(Column::Trigger.eq(trigger))
+            .order_by_desc(Column::StartedAt)
+            .one(db)
+            .await
+    }
+
     /// Create a new merge operation item
     pub async fn create(
         db: &DatabaseConnection,
         operation_id: Uuid,
         pull_request_id: Uuid,
         repository_id: Uuid,
+        position: i32,
     ) -> Result<merge_operation_item::Model, DbErr> {
         let item = merge_operation_item::ActiveModel {
             id: Set(Uuid::new_v4()),
@@ -137,10 +326,39 @@ impl MergeOperationItemQueries {
             error_message: Set(None),
             merge_sha: Set(None),
             merged_at: Set(None),
+            position: Set(position),
+            attempts: Set(0),
         };
         item.insert(db).await
     }
 
+    /// Record one more merge attempt on an item
+    pub async fn increment_attempts(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        merge_operation_item::Entity::update_many()
+            .col_expr(
+                merge_operation_item::Column::Attempts,
+                Expr::col(merge_operation_item::Column::Attempts).add(1),
+            )
+            .filter(merge_operation_item::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark every still-pending item of an operation as cancelled
+    pub async fn cancel_pending(db: &DatabaseConnection, operation_id: Uuid) -> Result<u64, DbErr> {
+        let result = merge_operation_item::Entity::update_many()
+            .col_expr(
+                merge_operation_item::Column::Status,
+                Expr::value("cancelled"),
+            )
+            .filter(merge_operation_item::Column::MergeOperationId.eq(operation_id))
+            .filter(merge_operation_item::Column::Status.eq("pending"))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
     /// Update item status after merge attempt
     pub async fn update_status(
         db: &DatabaseConnection,
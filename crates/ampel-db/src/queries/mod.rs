@@ -1,19 +1,49 @@
+pub mod api_token_queries;
 pub mod ci_check_queries;
 pub mod merge_operation_queries;
+pub mod notification_channel_queries;
+pub mod notification_log_queries;
+pub mod organization_invitation_queries;
+pub mod organization_queries;
 pub mod pr_filter_queries;
+pub mod pr_metadata_queries;
 pub mod pr_queries;
 pub mod provider_account_queries;
+pub mod push_subscription_queries;
+pub mod refresh_token_queries;
+pub mod remediation_run_queries;
 pub mod repo_queries;
+pub mod repository_share_queries;
+pub mod repository_webhook_queries;
 pub mod review_queries;
+pub mod saved_view_queries;
+pub mod status_policy_queries;
+pub mod user_identity_queries;
 pub mod user_queries;
 pub mod user_settings_queries;
+pub mod vapid_key_queries;
 
+pub use api_token_queries::*;
 pub use ci_check_queries::*;
 pub use merge_operation_queries::*;
+pub use notification_channel_queries::*;
+pub use notification_log_queries::*;
+pub use organization_invitation_queries::*;
+pub use organization_queries::*;
 pub use pr_filter_queries::*;
+pub use pr_metadata_queries::*;
 pub use pr_queries::*;
 pub use provider_account_queries::*;
+pub use push_subscription_queries::*;
+pub use refresh_token_queries::*;
+pub use remediation_run_queries::*;
 pub use repo_queries::*;
+pub use repository_share_queries::*;
+pub use repository_webhook_queries::*;
 pub use review_queries::*;
+pub use saved_view_queries::*;
+pub use status_policy_queries::*;
+pub use user_identity_queries::*;
 pub use user_queries::*;
 pub use user_settings_queries::*;
+pub use vapid_key_queries::*;
@@ -0,0 +1,103 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Condition, Query},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::entities::notification_channel::{ActiveModel, Column, Entity, Model};
+use crate::entities::team_member;
+
+/// A notification channel to create, owned by a user or a team
+pub struct NewNotificationChannel {
+    pub user_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+    pub kind: String,
+    pub name: String,
+    pub config_encrypted: Vec<u8>,
+    /// JSON array of notification kinds, or `None` for all
+    pub events: Option<String>,
+}
+
+pub struct NotificationChannelQueries;
+
+impl NotificationChannelQueries {
+    pub async fn create(
+        db: &DatabaseConnection,
+        channel: NewNotificationChannel,
+    ) -> Result<Model, DbErr> {
+        let now = Utc::now();
+        let channel = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(channel.user_id),
+            team_id: Set(channel.team_id),
+            kind: Set(channel.kind),
+            name: Set(channel.name),
+            config_encrypted: Set(channel.config_encrypted),
+            events: Set(channel.events),
+            enabled: Set(true),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        channel.insert(db).await
+    }
+
+    pub async fn find_by_id(db: &DatabaseConnection, id: Uuid) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(id).one(db).await
+    }
+
+    /// A user's personal channels, oldest first
+    pub async fn list_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// A team's channels, oldest first
+    pub async fn list_for_team(
+        db: &DatabaseConnection,
+        team_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::TeamId.eq(team_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Enabled channels that receive a user's notifications: their own, and
+    /// those of every team they belong to
+    pub async fn find_enabled_for_recipient(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        let teams = Query::select()
+            .column(team_member::Column::TeamId)
+            .from(team_member::Entity)
+            .and_where(team_member::Column::UserId.eq(user_id))
+            .to_owned();
+
+        Entity::find()
+            .filter(Column::Enabled.eq(true))
+            .filter(
+                Condition::any()
+                    .add(Column::UserId.eq(user_id))
+                    .add(Column::TeamId.in_subquery(teams)),
+            )
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::delete_by_id(id).exec(db).await?;
+        Ok(())
+    }
+}
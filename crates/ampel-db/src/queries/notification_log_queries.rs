@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::notification_log::{ActiveModel, Column, Entity, Model};
+
+/// A notification to queue for delivery
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub pull_request_id: Option<Uuid>,
+    pub kind: String,
+    pub channel: String,
+    /// The configured notification channel to deliver through, if any
+    pub channel_id: Option<Uuid>,
+    pub dedup_key: String,
+    /// JSON object describing the event
+    pub payload: String,
+}
+
+pub struct NotificationLogQueries;
+
+impl NotificationLogQueries {
+    /// Queue a notification for immediate delivery. Returns `false` if the
+    /// same event was already queued for this user and channel.
+    pub async fn enqueue(
+        db: &DatabaseConnection,
+        notification: NewNotification,
+    ) -> Result<bool, DbErr> {
+        let now = Utc::now();
+        let entry = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(notification.user_id),
+            pull_request_id: Set(notification.pull_request_id),
+            kind: Set(notification.kind),
+            channel: Set(notification.channel),
+            channel_id: Set(notification.channel_id),
+            dedup_key: Set(notification.dedup_key),
+            payload: Set(notification.payload),
+            status: Set("pending".to_string()),
+            attempts: Set(0),
+            next_attempt_at: Set(now),
+            last_error: Set(None),
+            created_at: Set(now),
+            sent_at: Set(None),
+        };
+
+        let inserted = Entity::insert(entry)
+            .on_conflict(
+                OnConflict::columns([Column::UserId, Column::Channel, Column::DedupKey])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(inserted > 0)
+    }
+
+    /// Whether a notification with this dedup key was already queued for the
+    /// user and channel
+    pub async fn exists(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        channel: &str,
+        dedup_key: &str,
+    ) -> Result<bool, DbErr> {
+        let count = Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::Channel.eq(channel))
+            .filter(Column::DedupKey.eq(dedup_key))
+            .count(db)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// Pending notifications whose next attempt is due, oldest first
+    pub async fn find_due(
+        db: &DatabaseConnection,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Status.eq("pending"))
+            .filter(Column::NextAttemptAt.lte(now))
+            .order_by_asc(Column::NextAttemptAt)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Claim a due notification by pushing its next attempt out to
+    /// `lease_until`. Returns `false` if another worker claimed it first.
+    pub async fn claim(
+        db: &DatabaseConnection,
+        id: Uuid,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+    ) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::NextAttemptAt, Expr::value(lease_until))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq("pending"))
+            .filter(Column::NextAttemptAt.lte(now))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+
+    /// Record a successful delivery
+    pub async fn mark_sent(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::Status, Expr::value("sent"))
+            .col_expr(Column::Attempts, Expr::col(Column::Attempts).add(1))
+            .col_expr(Column::LastError, Expr::value(Option::<String>::None))
+            .col_expr(Column::SentAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Record a failed delivery attempt. The notification is retried at
+    /// `retry_at`, or marked `failed` for good when that is `None`.
+    pub async fn mark_attempt_failed(
+        db: &DatabaseConnection,
+        id: Uuid,
+        error: String,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), DbErr> {
+        let mut update = Entity::update_many()
+            .col_expr(Column::Attempts, Expr::col(Column::Attempts).add(1))
+            .col_expr(Column::LastError, Expr::value(error));
+        update = match retry_at {
+            Some(at) => update.col_expr(Column::NextAttemptAt, Expr::value(at)),
+            None => update.col_expr(Column::Status, Expr::value("failed")),
+        };
+        update.filter(Column::Id.eq(id)).exec(db).await?;
+        Ok(())
+    }
+
+    /// A user's most recent notifications, newest first
+    pub async fn list_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_desc(Column::CreatedAt)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Delete delivered and failed notifications created before `before`
+    pub async fn delete_finished_before(
+        db: &DatabaseConnection,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::delete_many()
+            .filter(Column::Status.ne("pending"))
+            .filter(Column::CreatedAt.lt(before))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
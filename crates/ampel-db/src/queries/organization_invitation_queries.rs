@@ -0,0 +1,102 @@
+use ampel_core::models::OrgRole;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::organization_invitation::{ActiveModel, Column, Entity, Model};
+
+/// An invitation to record when it is sent
+pub struct NewInvitation {
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role: OrgRole,
+    pub token_hash: String,
+    pub invited_by: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct OrganizationInvitationQueries;
+
+impl OrganizationInvitationQueries {
+    /// Record an invitation
+    pub async fn create(
+        db: &DatabaseConnection,
+        invitation: NewInvitation,
+    ) -> Result<Model, DbErr> {
+        let invitation = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            organization_id: Set(invitation.organization_id),
+            email: Set(invitation.email),
+            role: Set(invitation.role.to_string()),
+            token_hash: Set(invitation.token_hash),
+            invited_by: Set(Some(invitation.invited_by)),
+            expires_at: Set(invitation.expires_at),
+            accepted_at: Set(None),
+            created_at: Set(Utc::now()),
+        };
+
+        invitation.insert(db).await
+    }
+
+    /// Find an invitation by the hash of its token
+    pub async fn find_by_hash(
+        db: &DatabaseConnection,
+        token_hash: &str,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::TokenHash.eq(token_hash))
+            .one(db)
+            .await
+    }
+
+    /// Invitations of an organization that are neither accepted nor expired,
+    /// newest first
+    pub async fn list_pending(db: &DatabaseConnection, org_id: Uuid) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::OrganizationId.eq(org_id))
+            .filter(Column::AcceptedAt.is_null())
+            .filter(Column::ExpiresAt.gt(Utc::now()))
+            .order_by_desc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Mark an invitation accepted.
+    ///
+    /// Returns `false` if it had already been accepted, e.g. by a concurrent
+    /// request with the same token.
+    pub async fn accept(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::AcceptedAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::AcceptedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+
+    /// Revoke an invitation of an organization
+    pub async fn delete(db: &DatabaseConnection, org_id: Uuid, id: Uuid) -> Result<bool, DbErr> {
+        let result = Entity::delete_many()
+            .filter(Column::Id.eq(id))
+            .filter(Column::OrganizationId.eq(org_id))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Delete invitations that expired before `before`
+    pub async fn delete_expired(
+        db: &DatabaseConnection,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::delete_many()
+            .filter(Column::ExpiresAt.lt(before))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use ampel_core::models::OrgRole;
+use ampel_core::remediation::ScopeType;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{
+    model_provider_account, organization, organization_member, remediation_playbook,
+    remediation_policy, team, team_member, user,
+};
+
+/// An organization to create
+pub struct NewOrganization {
+    pub owner_id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub logo_url: Option<String>,
+}
+
+pub struct OrganizationQueries;
+
+impl OrganizationQueries {
+    /// Create an organization with its creator as the first owner
+    pub async fn create(
+        db: &DatabaseConnection,
+        new: NewOrganization,
+    ) -> Result<organization::Model, DbErr> {
+        let now = Utc::now();
+        let txn = db.begin().await?;
+
+        let org = organization::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            owner_id: Set(new.owner_id),
+            name: Set(new.name),
+            slug: Set(new.slug),
+            description: Set(new.description),
+            logo_url: Set(new.logo_url),
+            air_gapped: Set(false),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+
+        organization_member::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            organization_id: Set(org.id),
+            user_id: Set(new.owner_id),
+            role: Set(OrgRole::Owner.to_string()),
+            joined_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+        Ok(org)
+    }
+
+    /// Find an organization by ID
+    pub async fn find_by_id(
+        db: &DatabaseConnection,
+        id: Uuid,
+    ) -> Result<Option<organization::Model>, DbErr> {
+        organization::Entity::find_by_id(id).one(db).await
+    }
+
+    /// Find an organization by its unique slug
+    pub async fn find_by_slug(
+        db: &DatabaseConnection,
+        slug: &str,
+    ) -> Result<Option<organization::Model>, DbErr> {
+        organization::Entity::find()
+            .filter(organization::Column::Slug.eq(slug))
+            .one(db)
+            .await
+    }
+
+    /// The user's role in an organization, if they belong to it.
+    ///
+    /// `organizations.owner_id` is always an owner, even for organizations
+    /// inserted without a membership row.
+    pub async fn role_of(
+        db: &DatabaseConnection,
+        org_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<OrgRole>, DbErr> {
+        let membership = organization_member::Entity::find()
+            .filter(organization_member::Column::OrganizationId.eq(org_id))
+            .filter(organization_member::Column::UserId.eq(user_id))
+            .one(db)
+            .await?;
+        if let Some(membership) = membership {
+            return Ok(membership.role.parse().ok());
+        }
+
+        let is_owner = organization::Entity::find_by_id(org_id)
+            .filter(organization::Column::OwnerId.eq(user_id))
+            .count(db)
+            .await?
+            > 0;
+        Ok(is_owner.then_some(OrgRole::Owner))
+    }
+
+    /// Organizations the user belongs to, with their role in each
+    pub async fn list_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<(organization::Model, OrgRole)>, DbErr> {
+        let mut roles: HashMap<Uuid, OrgRole> = organization_member::Entity::find()
+            .filter(organization_member::Column::UserId.eq(user_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|m| Some((m.organization_id, m.role.parse().ok()?)))
+            .collect();
+
+        let orgs = organization::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(organization::Column::Id.is_in(roles.keys().copied().collect::<Vec<_>>()))
+                    .add(organization::Column::OwnerId.eq(user_id)),
+            )
+            .order_by_asc(organization::Column::Name)
+            .all(db)
+            .await?;
+
+        Ok(orgs
+            .into_iter()
+            .map(|org| {
+                let role = roles.remove(&org.id).unwrap_or(OrgRole::Owner);
+                (org, role)
+            })
+            .collect())
+    }
+
+    /// IDs of the organizations in which the user holds at least `min`
+    pub async fn org_ids_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        min: OrgRole,
+    ) -> Result<Vec<Uuid>, DbErr> {
+        Ok(Self::list_for_user(db, user_id)
+            .await?
+            .into_iter()
+            .filter(|(_, role)| *role >= min)
+            .map(|(org, _)| org.id)
+            .collect())
+    }
+
+    /// Members of an organization with their user records, oldest first
+    pub async fn list_members(
+        db: &DatabaseConnection,
+        org_id: Uuid,
+    ) -> Result<Vec<(organization_member::Model, user::Model)>, DbErr> {
+        let rows = organization_member::Entity::find()
+            .filter(organization_member::Column::OrganizationId.eq(org_id))
+            .order_by_asc(organization_member::Column::JoinedAt)
+            .find_also_related(user::Entity)
+            .all(db)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(member, user)| Some((member, user?)))
+            .collect())
+    }
+
+    /// Add a user to an organization, or change their role if they already
+    /// belong to it
+    pub async fn upsert_member(
+        db: &DatabaseConnection,
+        org_id: Uuid,
+        user_id: Uuid,
+        role: OrgRole,
+    ) -> Result<organization_member::Model, DbErr> {
+        let existing = organization_member::Entity::find()
+            .filter(organization_member::Column::OrganizationId.eq(org_id))
+            .filter(organization_member::Column::UserId.eq(user_id))
+            .one(db)
+            .await?;
+
+        match existing {
+            Some(member) => {
+                let mut active: organization_member::ActiveModel = member.into();
+                active.role = Set(role.to_string());
+                active.update(db).await
+            }
+            None => {
+                organization_member::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    organization_id: Set(org_id),
+                    user_id: Set(user_id),
+                    role: Set(role.to_string()),
+                    joined_at: Set(Utc::now()),
+                }
+                .insert(db)
+                .await
+            }
+        }
+    }
+
+    /// Number of owners of an organization
+    pub async fn count_owners(db: &DatabaseConnection, org_id: Uuid) -> Result<u64, DbErr> {
+        organization_member::Entity::find()
+            .filter(organization_member::Column::OrganizationId.eq(org_id))
+            .filter(organization_member::Column::Role.eq(OrgRole::Owner.to_string()))
+            .count(db)
+            .await
+    }
+
+    /// Hand `organizations.owner_id` to another owner, e.g. before the current
+    /// one leaves or is demoted. Does nothing if no other owner exists.
+    pub async fn reassign_primary_owner(
+        db: &DatabaseConnection,
+        org: organization::Model,
+    ) -> Result<(), DbErr> {
+        let successor = organization_member::Entity::find()
+            .filter(organization_member::Column::OrganizationId.eq(org.id))
+            .filter(organization_member::Column::Role.eq(OrgRole::Owner.to_string()))
+            .filter(organization_member::Column::UserId.ne(org.owner_id))
+            .order_by_asc(organization_member::Column::JoinedAt)
+            .one(db)
+            .await?;
+
+        if let Some(successor) = successor {
+            let mut active: organization::ActiveModel = org.into();
+            active.owner_id = Set(successor.user_id);
+            active.updated_at = Set(Utc::now());
+            active.update(db).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove a user from an organization and from all of its teams
+    pub async fn remove_member(
+        db: &DatabaseConnection,
+        org_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let txn = db.begin().await?;
+
+        let team_ids = Self::team_ids(&txn, org_id).await?;
+        if !team_ids.is_empty() {
+            team_member::Entity::delete_many()
+                .filter(team_member::Column::TeamId.is_in(team_ids))
+                .filter(team_member::Column::UserId.eq(user_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        let result = organization_member::Entity::delete_many()
+            .filter(organization_member::Column::OrganizationId.eq(org_id))
+            .filter(organization_member::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Delete an organization with its teams and everything scoped to them.
+    ///
+    /// Memberships and invitations go through `ON DELETE CASCADE`; teams,
+    /// remediation policies/playbooks and model accounts have no foreign key
+    /// to the organization and are removed here.
+    pub async fn delete(db: &DatabaseConnection, org_id: Uuid) -> Result<(), DbErr> {
+        let txn = db.begin().await?;
+
+        let team_ids = Self::team_ids(&txn, org_id).await?;
+        let scopes = Condition::any()
+            .add(
+                Condition::all()
+                    .add(remediation_policy::Column::ScopeType.eq(ScopeType::Org.to_string()))
+                    .add(remediation_policy::Column::ScopeId.eq(org_id)),
+            )
+            .add(
+                Condition::all()
+                    .add(remediation_policy::Column::ScopeType.eq(ScopeType::Team.to_string()))
+                    .add(remediation_policy::Column::ScopeId.is_in(team_ids.clone())),
+            );
+        remediation_policy::Entity::delete_many()
+            .filter(scopes)
+            .exec(&txn)
+            .await?;
+
+        let playbook_scopes = Condition::any()
+            .add(
+                Condition::all()
+                    .add(remediation_playbook::Column::ScopeType.eq(ScopeType::Org.to_string()))
+                    .add(remediation_playbook::Column::ScopeId.eq(org_id)),
+            )
+            .add(
+                Condition::all()
+                    .add(remediation_playbook::Column::ScopeType.eq(ScopeType::Team.to_string()))
+                    .add(remediation_playbook::Column::ScopeId.is_in(team_ids.clone())),
+            );
+        remediation_playbook::Entity::delete_many()
+            .filter(playbook_scopes)
+            .exec(&txn)
+            .await?;
+
+        model_provider_account::Entity::delete_many()
+            .filter(model_provider_account::Column::OrganizationId.eq(org_id))
+            .exec(&txn)
+            .await?;
+
+        if !team_ids.is_empty() {
+            team_member::Entity::delete_many()
+                .filter(team_member::Column::TeamId.is_in(team_ids))
+                .exec(&txn)
+                .await?;
+        }
+        team::Entity::delete_many()
+            .filter(team::Column::OrganizationId.eq(org_id))
+            .exec(&txn)
+            .await?;
+
+        organization::Entity::delete_by_id(org_id)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await
+    }
+
+    async fn team_ids<C: sea_orm::ConnectionTrait>(
+        db: &C,
+        org_id: Uuid,
+    ) -> Result<Vec<Uuid>, DbErr> {
+        Ok(team::Entity::find()
+            .filter(team::Column::OrganizationId.eq(org_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|t| t.id)
+            .collect())
+    }
+}
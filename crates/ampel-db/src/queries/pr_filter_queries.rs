@@ -6,6 +6,9 @@ use uuid::Uuid;
 
 use crate::entities::pr_filter::{ActiveModel, Column, Entity, Model};
 
+/// Labels that keep a PR out of merges until a user sets their own
+pub const DEFAULT_SKIP_LABELS: &[&str] = &["do-not-merge", "wip", "draft", "hold"];
+
 pub struct PrFilterQueries;
 
 impl PrFilterQueries {
@@ -20,6 +23,18 @@ impl PrFilterQueries {
             .await
     }
 
+    /// The labels that keep a user's PRs out of merges, or the defaults
+    /// when they have no settings
+    pub async fn skip_labels_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<String>, DbErr> {
+        Ok(match Self::find_by_user(db, user_id).await? {
+            Some(filter) => serde_json::from_str(&filter.skip_labels).unwrap_or_default(),
+            None => DEFAULT_SKIP_LABELS.iter().map(|l| l.to_string()).collect(),
+        })
+    }
+
     /// Create or update PR filter settings for a user
     pub async fn upsert(
         db: &DatabaseConnection,
@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, Set};
+use uuid::Uuid;
+
+use ampel_core::models::PullRequestMetadata;
+
+use crate::entities::pull_request_label;
+use crate::entities::pull_request_participant::{self, ROLE_ASSIGNEE, ROLE_REVIEWER};
+
+pub struct PrMetadataQueries;
+
+impl PrMetadataQueries {
+    /// The labels, assignees and requested reviewers of a PR
+    pub async fn for_pull_request(
+        db: &DatabaseConnection,
+        pull_request_id: Uuid,
+    ) -> Result<PullRequestMetadata, DbErr> {
+        Ok(Self::for_pull_requests(db, &[pull_request_id])
+            .await?
+            .remove(&pull_request_id)
+            .unwrap_or_default())
+    }
+
+    /// The labels, assignees and requested reviewers of each of
+    /// `pull_request_ids` that has any, sorted by name
+    pub async fn for_pull_requests(
+        db: &DatabaseConnection,
+        pull_request_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, PullRequestMetadata>, DbErr> {
+        let mut metadata: HashMap<Uuid, PullRequestMetadata> = HashMap::new();
+        if pull_request_ids.is_empty() {
+            return Ok(metadata);
+        }
+
+        let labels = pull_request_label::Entity::find()
+            .filter(
+                pull_request_label::Column::PullRequestId.is_in(pull_request_ids.iter().copied()),
+            )
+            .order_by_asc(pull_request_label::Column::Name)
+            .all(db)
+            .await?;
+        for label in labels {
+            metadata
+                .entry(label.pull_request_id)
+                .or_default()
+                .labels
+                .push(label.name);
+        }
+
+        let participants = pull_request_participant::Entity::find()
+            .filter(
+                pull_request_participant::Column::PullRequestId
+                    .is_in(pull_request_ids.iter().copied()),
+            )
+            .order_by_asc(pull_request_participant::Column::Login)
+            .all(db)
+            .await?;
+        for participant in participants {
+            let entry = metadata.entry(participant.pull_request_id).or_default();
+            match participant.role.as_str() {
+                ROLE_ASSIGNEE => entry.assignees.push(participant.login),
+                ROLE_REVIEWER => entry.requested_reviewers.push(participant.login),
+                _ => {}
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Replace a PR's labels, assignees and requested reviewers, dropping
+    /// duplicates
+    pub async fn replace(
+        db: &DatabaseConnection,
+        pull_request_id: Uuid,
+        metadata: &PullRequestMetadata,
+    ) -> Result<(), DbErr> {
+        pull_request_label::Entity::delete_many()
+            .filter(pull_request_label::Column::PullRequestId.eq(pull_request_id))
+            .exec(db)
+            .await?;
+        pull_request_participant::Entity::delete_many()
+            .filter(pull_request_participant::Column::PullRequestId.eq(pull_request_id))
+            .exec(db)
+            .await?;
+
+        let labels: Vec<_> = unique(&metadata.labels)
+            .map(|name| pull_request_label::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                pull_request_id: Set(pull_request_id),
+                name: Set(name.clone()),
+            })
+            .collect();
+        if !labels.is_empty() {
+            pull_request_label::Entity::insert_many(labels)
+                .exec(db)
+                .await?;
+        }
+
+        let participants: Vec<_> = unique(&metadata.assignees)
+            .map(|login| (login, ROLE_ASSIGNEE))
+            .chain(unique(&metadata.requested_reviewers).map(|login| (login, ROLE_REVIEWER)))
+            .map(|(login, role)| pull_request_participant::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                pull_request_id: Set(pull_request_id),
+                login: Set(login.clone()),
+                role: Set(role.to_string()),
+            })
+            .collect();
+        if !participants.is_empty() {
+            pull_request_participant::Entity::insert_many(participants)
+                .exec(db)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `values` without repeats, in their original order
+fn unique(values: &[String]) -> impl Iterator<Item = &String> {
+    let mut seen = HashSet::new();
+    values
+        .iter()
+        .filter(move |value| seen.insert(value.as_str()))
+}
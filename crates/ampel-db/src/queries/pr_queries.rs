@@ -1,7 +1,7 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
 };
 use uuid::Uuid;
 
@@ -55,20 +55,21 @@ impl PrQueries {
             .await
     }
 
-    /// Find all open PRs for a user (across all their repositories)
-    pub async fn find_open_by_user(
+    /// Find all open PRs across the repositories a user can access
+    pub async fn find_open_accessible_by_user(
         db: &DatabaseConnection,
         user_id: Uuid,
         page: u64,
         per_page: u64,
     ) -> Result<(Vec<Model>, u64), DbErr> {
         use crate::entities::repository;
+        use crate::queries::RepoQueries;
 
         let offset = (page - 1) * per_page;
 
         let prs = Entity::find()
             .inner_join(repository::Entity)
-            .filter(repository::Column::UserId.eq(user_id))
+            .filter(RepoQueries::accessible_by(user_id))
             .filter(Column::State.eq("open"))
             .order_by_desc(Column::UpdatedAt)
             .offset(offset)
@@ -78,7 +79,7 @@ impl PrQueries {
 
         let total = Entity::find()
             .inner_join(repository::Entity)
-            .filter(repository::Column::UserId.eq(user_id))
+            .filter(RepoQueries::accessible_by(user_id))
             .filter(Column::State.eq("open"))
             .count(db)
             .await?;
@@ -114,6 +115,8 @@ impl PrQueries {
         updated_at: chrono::DateTime<Utc>,
         merged_at: Option<chrono::DateTime<Utc>>,
         closed_at: Option<chrono::DateTime<Utc>>,
+        head_sha: Option<String>,
+        milestone: Option<String>,
     ) -> Result<Model, DbErr> {
         // Check if PR already exists
         if let Some(existing) = Self::find_by_number(db, repository_id, number).await? {
@@ -132,6 +135,11 @@ impl PrQueries {
             active.updated_at = Set(updated_at);
             active.merged_at = Set(merged_at);
             active.closed_at = Set(closed_at);
+            // Webhook payloads may omit the head; keep the last one known
+            if head_sha.is_some() {
+                active.head_sha = Set(head_sha);
+            }
+            active.milestone = Set(milestone);
             active.last_synced_at = Set(Utc::now());
             return active.update(db).await;
         }
@@ -164,6 +172,10 @@ impl PrQueries {
             merged_at: Set(merged_at),
             closed_at: Set(closed_at),
             last_synced_at: Set(Utc::now()),
+            ampel_status: Set(None),
+            ampel_status_changed_at: Set(None),
+            head_sha: Set(head_sha),
+            milestone: Set(milestone),
         };
 
         pr.insert(db).await
@@ -191,6 +203,30 @@ impl PrQueries {
         active.update(db).await
     }
 
+    /// Record a PR's traffic-light status, but only if it is still `previous`.
+    ///
+    /// Returns whether this call made the change, so when two syncs race
+    /// exactly one of them observes the transition.
+    pub async fn transition_ampel_status(
+        db: &DatabaseConnection,
+        id: Uuid,
+        previous: Option<&str>,
+        current: &str,
+    ) -> Result<bool, DbErr> {
+        let previous = match previous {
+            Some(status) => Column::AmpelStatus.eq(status),
+            None => Column::AmpelStatus.is_null(),
+        };
+        let result = Entity::update_many()
+            .col_expr(Column::AmpelStatus, Expr::value(current))
+            .col_expr(Column::AmpelStatusChangedAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(previous)
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+
     /// Delete PR
     pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
         Entity::delete_by_id(id).exec(db).await?;
@@ -226,4 +262,30 @@ impl PrQueries {
             .all(db)
             .await
     }
+
+    /// Of the given repositories, those with an open PR or a PR updated since
+    /// `since`. The poll scheduler polls these ahead of idle repositories.
+    pub async fn find_active_repository_ids(
+        db: &DatabaseConnection,
+        repository_ids: &[Uuid],
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, DbErr> {
+        if repository_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Entity::find()
+            .select_only()
+            .column(Column::RepositoryId)
+            .distinct()
+            .filter(Column::RepositoryId.is_in(repository_ids.iter().copied()))
+            .filter(
+                Condition::any()
+                    .add(Column::State.eq("open"))
+                    .add(Column::UpdatedAt.gte(since)),
+            )
+            .into_tuple()
+            .all(db)
+            .await
+    }
 }
@@ -1,5 +1,6 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_orm::{
+    sea_query::{Expr, Func},
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
     QueryFilter, Set,
 };
@@ -139,6 +140,24 @@ impl ProviderAccountQueries {
             .await
     }
 
+    /// Find active accounts on `provider` whose username matches `username`,
+    /// ignoring case
+    pub async fn find_by_provider_username(
+        db: &DatabaseConnection,
+        provider: &str,
+        username: &str,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Provider.eq(provider))
+            .filter(Column::IsActive.eq(true))
+            .filter(
+                Expr::expr(Func::lower(Expr::col(Column::ProviderUsername)))
+                    .eq(username.to_lowercase()),
+            )
+            .all(db)
+            .await
+    }
+
     /// Update validation status and timestamp
     pub async fn update_validation_status(
         db: &DatabaseConnection,
@@ -178,6 +197,27 @@ impl ProviderAccountQueries {
         active.update(db).await
     }
 
+    /// Replace stored credentials after an OAuth refresh
+    pub async fn update_credentials(
+        db: &DatabaseConnection,
+        id: Uuid,
+        access_token_encrypted: Vec<u8>,
+        token_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Model, DbErr> {
+        let account = Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound(
+                "Provider account not found".to_string(),
+            ))?;
+
+        let mut active: ActiveModel = account.into();
+        active.access_token_encrypted = Set(access_token_encrypted);
+        active.token_expires_at = Set(token_expires_at);
+        active.updated_at = Set(Utc::now());
+        active.update(db).await
+    }
+
     /// Toggle active status
     pub async fn set_active_status(
         db: &DatabaseConnection,
@@ -0,0 +1,120 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::push_subscription::{ActiveModel, Column, Entity, Model};
+
+/// A browser push subscription to register
+pub struct NewPushSubscription {
+    pub user_id: Uuid,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+    pub user_agent: Option<String>,
+}
+
+pub struct PushSubscriptionQueries;
+
+impl PushSubscriptionQueries {
+    /// Register a subscription. An endpoint that is already registered gets
+    /// the new keys and moves to `user_id`, keeping its id.
+    pub async fn upsert(
+        db: &DatabaseConnection,
+        subscription: NewPushSubscription,
+    ) -> Result<Model, DbErr> {
+        let endpoint = subscription.endpoint.clone();
+        let model = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(subscription.user_id),
+            endpoint: Set(subscription.endpoint),
+            p256dh: Set(subscription.p256dh),
+            auth: Set(subscription.auth),
+            user_agent: Set(subscription.user_agent),
+            created_at: Set(Utc::now()),
+            last_used_at: Set(None),
+        };
+
+        Entity::insert(model)
+            .on_conflict(
+                OnConflict::column(Column::Endpoint)
+                    .update_columns([
+                        Column::UserId,
+                        Column::P256dh,
+                        Column::Auth,
+                        Column::UserAgent,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Entity::find()
+            .filter(Column::Endpoint.eq(endpoint))
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound("push subscription".to_string()))
+    }
+
+    /// A user's subscriptions, oldest first
+    pub async fn list_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    pub async fn count_for_user(db: &DatabaseConnection, user_id: Uuid) -> Result<u64, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .count(db)
+            .await
+    }
+
+    /// Delete one of a user's subscriptions. Returns `false` if the user has
+    /// no subscription with this id.
+    pub async fn delete_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let result = Entity::delete_many()
+            .filter(Column::Id.eq(id))
+            .filter(Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Delete a subscription the push service reported as expired
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::delete_by_id(id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Delete every subscription, e.g. after the VAPID keys they were
+    /// created for were replaced. Returns how many were deleted.
+    pub async fn delete_all(db: &DatabaseConnection) -> Result<u64, DbErr> {
+        let result = Entity::delete_many().exec(db).await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Record that a push service accepted a message for the subscription
+    pub async fn touch(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(
+                Column::LastUsedAt,
+                sea_orm::sea_query::Expr::value(Utc::now()),
+            )
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
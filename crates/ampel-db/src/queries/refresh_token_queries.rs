@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::refresh_token::{ActiveModel, Column, Entity, Model};
+
+/// A refresh token to record when it is issued
+pub struct NewRefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub user_agent: Option<String>,
+    pub session_started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct RefreshTokenQueries;
+
+impl RefreshTokenQueries {
+    /// Record an issued refresh token
+    pub async fn create(db: &DatabaseConnection, token: NewRefreshToken) -> Result<Model, DbErr> {
+        let token = ActiveModel {
+            id: Set(token.id),
+            user_id: Set(token.user_id),
+            family_id: Set(token.family_id),
+            token_hash: Set(token.token_hash),
+            user_agent: Set(token.user_agent),
+            session_started_at: Set(token.session_started_at),
+            expires_at: Set(token.expires_at),
+            revoked_at: Set(None),
+            replaced_by: Set(None),
+            created_at: Set(Utc::now()),
+        };
+
+        token.insert(db).await
+    }
+
+    /// Find a refresh token by the hash of its value
+    pub async fn find_by_hash(
+        db: &DatabaseConnection,
+        token_hash: &str,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::TokenHash.eq(token_hash))
+            .one(db)
+            .await
+    }
+
+    /// Revoke a live token in favour of its successor.
+    ///
+    /// Returns `false` if the token had already been revoked, e.g. by a
+    /// concurrent refresh with the same token.
+    pub async fn rotate(
+        db: &DatabaseConnection,
+        id: Uuid,
+        replaced_by: Uuid,
+    ) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::RevokedAt, Expr::value(Utc::now()))
+            .col_expr(Column::ReplacedBy, Expr::value(replaced_by))
+            .filter(Column::Id.eq(id))
+            .filter(Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+
+    /// Revoke every live token of a session
+    pub async fn revoke_family(db: &DatabaseConnection, family_id: Uuid) -> Result<u64, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(Column::FamilyId.eq(family_id))
+            .filter(Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Revoke one session of a user. Returns `false` if the user has no live
+    /// session with that id.
+    pub async fn revoke_user_family(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::FamilyId.eq(family_id))
+            .filter(Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Revoke every session of a user
+    pub async fn revoke_all_for_user(db: &DatabaseConnection, user_id: Uuid) -> Result<u64, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::RevokedAt, Expr::value(Utc::now()))
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// The live token of each of a user's sessions, most recent session first
+    pub async fn list_active_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::RevokedAt.is_null())
+            .filter(Column::ExpiresAt.gt(Utc::now()))
+            .order_by_desc(Column::SessionStartedAt)
+            .all(db)
+            .await
+    }
+
+    /// Delete tokens that expired before `before`; they can no longer be
+    /// presented, so reuse detection does not need them either.
+    pub async fn delete_expired(
+        db: &DatabaseConnection,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::delete_many()
+            .filter(Column::ExpiresAt.lt(before))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use uuid::Uuid;
+
+use ampel_core::remediation::{AutonomyLevel, RunState};
+
+use crate::entities::remediation_run::{Column, Entity, Model};
+
+/// States a worker can drive without a human: every active state except the
+/// `awaiting_approval` gate, which only the approve endpoint moves on.
+pub const DISPATCHABLE_RUN_STATES: [RunState; 7] = [
+    RunState::Created,
+    RunState::Selecting,
+    RunState::Consolidating,
+    RunState::Verifying,
+    RunState::AgentFixing,
+    RunState::Merging,
+    RunState::Finalizing,
+];
+
+fn dispatchable() -> Vec<String> {
+    DISPATCHABLE_RUN_STATES
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Queue bookkeeping for remediation runs: dispatch to the job queue and the
+/// per-run worker lease. State transitions stay in
+/// [`crate::repositories::SeaOrmRemediationRunRepository`].
+pub struct RemediationRunQueries;
+
+impl RemediationRunQueries {
+    /// Runs a worker can drive that have not been handed to the queue yet,
+    /// oldest first.
+    pub async fn find_undispatched(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::State.is_in(dispatchable()))
+            .filter(Column::QueuedAt.is_null())
+            .order_by_asc(Column::CreatedAt)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Runs held by a worker that stopped refreshing the lease before
+    /// `stale_before`, i.e. whose worker died mid-run.
+    pub async fn find_stale_leases(
+        db: &DatabaseConnection,
+        stale_before: DateTime<Utc>,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::State.is_in(dispatchable()))
+            .filter(Column::HeartbeatAt.lt(stale_before))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Whether the repository has a run that has not reached a terminal state
+    /// (including one parked at the approval gate).
+    pub async fn has_unfinished_run(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let mut states = dispatchable();
+        states.push(RunState::AwaitingApproval.to_string());
+        let found = Entity::find()
+            .filter(Column::RepositoryId.eq(repository_id))
+            .filter(Column::State.is_in(states))
+            .one(db)
+            .await?;
+        Ok(found.is_some())
+    }
+
+    /// The consolidation plan most recently published by a suggest-only run on
+    /// the repository, as stored JSON. Lets a new suggest-only run skip
+    /// re-posting a plan its PRs already carry.
+    pub async fn latest_suggested_plan(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<Option<String>, DbErr> {
+        let run = Entity::find()
+            .filter(Column::RepositoryId.eq(repository_id))
+            .filter(Column::AutonomyLevel.eq(AutonomyLevel::SuggestOnly.to_string()))
+            .filter(Column::ConsolidationPlan.is_not_null())
+            .order_by_desc(Column::CreatedAt)
+            .one(db)
+            .await?;
+        Ok(run.and_then(|r| r.consolidation_plan))
+    }
+
+    /// Record that a run was handed to the queue. Returns `false` if another
+    /// dispatcher got there first.
+    pub async fn mark_queued(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbErr> {
+        let res = Entity::update_many()
+            .col_expr(Column::QueuedAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .filter(Column::QueuedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
+
+    /// Drop the lease and queue marker so the dispatcher hands the run to the
+    /// queue again (after an approval, or to retry a failed delivery).
+    pub async fn requeue(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::QueuedAt, Expr::value(Option::<DateTime<Utc>>::None))
+            .col_expr(
+                Column::HeartbeatAt,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Take the worker lease on a run and count the delivery attempt.
+    ///
+    /// The lease is free when it was never taken, released, or last refreshed
+    /// before `stale_before` (its worker died). The claim is a single guarded
+    /// `UPDATE`, so two workers never drive the same run. Returns the refreshed
+    /// row, or `None` if the run is leased elsewhere or no longer dispatchable.
+    pub async fn acquire_lease(
+        db: &DatabaseConnection,
+        id: Uuid,
+        stale_before: DateTime<Utc>,
+    ) -> Result<Option<Model>, DbErr> {
+        let res = Entity::update_many()
+            .col_expr(Column::HeartbeatAt, Expr::value(Utc::now()))
+            .col_expr(Column::Attempts, Expr::col(Column::Attempts).add(1))
+            .filter(Column::Id.eq(id))
+            .filter(Column::State.is_in(dispatchable()))
+            .filter(
+                Condition::any()
+                    .add(Column::HeartbeatAt.is_null())
+                    .add(Column::HeartbeatAt.lt(stale_before)),
+            )
+            .exec(db)
+            .await?;
+        if res.rows_affected == 0 {
+            return Ok(None);
+        }
+        Entity::find_by_id(id).one(db).await
+    }
+
+    /// Refresh the worker lease on a run being driven
+    pub async fn heartbeat(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::HeartbeatAt, Expr::value(Utc::now()))
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Give up the worker lease once the run has stopped or parked
+    pub async fn release_lease(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(
+                Column::HeartbeatAt,
+                Expr::value(Option::<DateTime<Utc>>::None),
+            )
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 use chrono::Utc;
 use sea_orm::{
+    sea_query::{Condition, Query},
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
     Set,
 };
 use uuid::Uuid;
 
 use crate::entities::repository::{ActiveModel, Column, Entity, Model};
+use crate::entities::{organization, organization_member, repository_share, team, team_member};
 
 pub struct RepoQueries;
 
@@ -27,6 +29,76 @@ impl RepoQueries {
             .await
     }
 
+    /// Find all repositories a user can access: those they own and those
+    /// shared with one of their teams or organizations
+    pub async fn find_accessible_by_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Self::accessible_by(user_id))
+            .order_by_asc(Column::FullName)
+            .all(db)
+            .await
+    }
+
+    /// Condition on `repositories` matching those `user_id` can access, by the
+    /// same rules as the API's `repository_role`: a team share reaches the
+    /// team's members and its organization's admins, an organization share
+    /// every member of the organization.
+    pub fn accessible_by(user_id: Uuid) -> Condition {
+        let member_orgs = Query::select()
+            .column(organization_member::Column::OrganizationId)
+            .from(organization_member::Entity)
+            .and_where(organization_member::Column::UserId.eq(user_id))
+            .to_owned();
+        // An owner without a membership row counts as the org's owner
+        let owned_orgs = Query::select()
+            .column(organization::Column::Id)
+            .from(organization::Entity)
+            .and_where(organization::Column::OwnerId.eq(user_id))
+            .to_owned();
+        let admin_orgs = Query::select()
+            .column(organization_member::Column::OrganizationId)
+            .from(organization_member::Entity)
+            .and_where(organization_member::Column::UserId.eq(user_id))
+            .and_where(organization_member::Column::Role.is_in(["admin", "owner"]))
+            .to_owned();
+        let member_teams = Query::select()
+            .column(team_member::Column::TeamId)
+            .from(team_member::Entity)
+            .and_where(team_member::Column::UserId.eq(user_id))
+            .to_owned();
+        let admin_teams = Query::select()
+            .column(team::Column::Id)
+            .from(team::Entity)
+            .cond_where(
+                Condition::any()
+                    .add(team::Column::OrganizationId.in_subquery(admin_orgs))
+                    .add(
+                        Condition::all()
+                            .add(team::Column::OrganizationId.in_subquery(owned_orgs.clone()))
+                            .add(team::Column::OrganizationId.not_in_subquery(member_orgs.clone())),
+                    ),
+            )
+            .to_owned();
+        let shared = Query::select()
+            .column(repository_share::Column::RepositoryId)
+            .from(repository_share::Entity)
+            .cond_where(
+                Condition::any()
+                    .add(repository_share::Column::TeamId.in_subquery(member_teams))
+                    .add(repository_share::Column::TeamId.in_subquery(admin_teams))
+                    .add(repository_share::Column::OrganizationId.in_subquery(member_orgs))
+                    .add(repository_share::Column::OrganizationId.in_subquery(owned_orgs)),
+            )
+            .to_owned();
+
+        Condition::any()
+            .add(Column::UserId.eq(user_id))
+            .add(Column::Id.in_subquery(shared))
+    }
+
     /// Find repository by user, provider, and provider ID
     pub async fn find_by_provider_id(
         db: &DatabaseConnection,
@@ -0,0 +1,83 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Condition, Query},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::entities::repository;
+use crate::entities::repository_share::{ActiveModel, Column, Entity, Model};
+
+/// A repository share to create, naming a team or an organization
+pub struct NewRepositoryShare {
+    pub repository_id: Uuid,
+    pub team_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub created_by: Uuid,
+}
+
+pub struct RepositoryShareQueries;
+
+impl RepositoryShareQueries {
+    pub async fn create(
+        db: &DatabaseConnection,
+        share: NewRepositoryShare,
+    ) -> Result<Model, DbErr> {
+        let share = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            repository_id: Set(share.repository_id),
+            team_id: Set(share.team_id),
+            organization_id: Set(share.organization_id),
+            created_by: Set(Some(share.created_by)),
+            created_at: Set(Utc::now()),
+        };
+
+        share.insert(db).await
+    }
+
+    pub async fn find_by_id(db: &DatabaseConnection, id: Uuid) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(id).one(db).await
+    }
+
+    /// A repository's shares, oldest first
+    pub async fn list_for_repository(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::RepositoryId.eq(repository_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Repositories a team's dashboard shows: those shared with the team and
+    /// those shared with its whole organization
+    pub async fn repositories_for_team(
+        db: &DatabaseConnection,
+        team_id: Uuid,
+        organization_id: Uuid,
+    ) -> Result<Vec<repository::Model>, DbErr> {
+        let shared = Query::select()
+            .column(Column::RepositoryId)
+            .from(Entity)
+            .cond_where(
+                Condition::any()
+                    .add(Column::TeamId.eq(team_id))
+                    .add(Column::OrganizationId.eq(organization_id)),
+            )
+            .to_owned();
+
+        repository::Entity::find()
+            .filter(repository::Column::Id.in_subquery(shared))
+            .order_by_asc(repository::Column::FullName)
+            .all(db)
+            .await
+    }
+
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::delete_by_id(id).exec(db).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::{Query, SelectStatement},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QuerySelect, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::repository_webhook::{ActiveModel, Column, Entity, Model};
+
+pub struct RepositoryWebhookQueries;
+
+impl RepositoryWebhookQueries {
+    /// Find the webhook registration for a repository
+    pub async fn find_by_repository(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::RepositoryId.eq(repository_id))
+            .one(db)
+            .await
+    }
+
+    /// Create the registration, or rotate its secret if one already exists.
+    ///
+    /// Rotating re-activates the webhook and clears delivery state, since
+    /// deliveries signed with the old secret will no longer verify.
+    pub async fn upsert_secret(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+        provider: String,
+        secret_encrypted: Vec<u8>,
+    ) -> Result<Model, DbErr> {
+        let now = Utc::now();
+
+        if let Some(existing) = Self::find_by_repository(db, repository_id).await? {
+            let mut active: ActiveModel = existing.into();
+            active.provider = Set(provider);
+            active.secret_encrypted = Set(secret_encrypted);
+            active.is_active = Set(true);
+            active.last_delivery_at = Set(None);
+            active.last_event = Set(None);
+            active.updated_at = Set(now);
+            return active.update(db).await;
+        }
+
+        let webhook = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            repository_id: Set(repository_id),
+            provider: Set(provider),
+            secret_encrypted: Set(secret_encrypted),
+            is_active: Set(true),
+            last_delivery_at: Set(None),
+            last_event: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        webhook.insert(db).await
+    }
+
+    /// Record a verified delivery
+    pub async fn record_delivery(
+        db: &DatabaseConnection,
+        id: Uuid,
+        event: String,
+    ) -> Result<Model, DbErr> {
+        let webhook = Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Webhook not found".to_string()))?;
+
+        let now = Utc::now();
+        let mut active: ActiveModel = webhook.into();
+        active.last_delivery_at = Set(Some(now));
+        active.last_event = Set(Some(event));
+        active.updated_at = Set(now);
+        active.update(db).await
+    }
+
+    /// Repository IDs whose webhook is active and delivered something since `since`.
+    ///
+    /// These repositories are kept current by pushes, so the poller only needs
+    /// to reconcile them occasionally.
+    pub async fn find_live_repository_ids(
+        db: &DatabaseConnection,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, DbErr> {
+        Entity::find()
+            .select_only()
+            .column(Column::RepositoryId)
+            .filter(Column::IsActive.eq(true))
+            .filter(Column::LastDeliveryAt.gte(since))
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    /// Subquery selecting the ids [`Self::find_live_repository_ids`] returns,
+    /// for filtering repositories in SQL
+    pub fn live_repository_ids_query(since: DateTime<Utc>) -> SelectStatement {
+        Query::select()
+            .column(Column::RepositoryId)
+            .from(Entity)
+            .and_where(Column::IsActive.eq(true))
+            .and_where(Column::LastDeliveryAt.gte(since))
+            .to_owned()
+    }
+
+    /// Delete the webhook registration for a repository
+    pub async fn delete_by_repository(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<u64, DbErr> {
+        let result = Entity::delete_many()
+            .filter(Column::RepositoryId.eq(repository_id))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
@@ -52,6 +52,7 @@ impl ReviewQueries {
         reviewer: String,
         reviewer_avatar_url: Option<String>,
         state: String,
+        commit_sha: Option<String>,
         body: Option<String>,
         submitted_at: chrono::DateTime<Utc>,
     ) -> Result<Model, DbErr> {
@@ -59,6 +60,7 @@ impl ReviewQueries {
         if let Some(existing) = Entity::find_by_id(id).one(db).await? {
             let mut active: ActiveModel = existing.into();
             active.state = Set(state);
+            active.commit_sha = Set(commit_sha);
             active.body = Set(body);
             active.submitted_at = Set(submitted_at);
             return active.update(db).await;
@@ -73,6 +75,7 @@ impl ReviewQueries {
             state: Set(state),
             body: Set(body),
             submitted_at: Set(submitted_at),
+            commit_sha: Set(commit_sha),
         };
 
         review.insert(db).await
@@ -0,0 +1,75 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::Condition, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::saved_view::{ActiveModel, Column, Entity, Model};
+
+pub struct SavedViewQueries;
+
+impl SavedViewQueries {
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        team_id: Option<Uuid>,
+        name: String,
+        query: String,
+    ) -> Result<Model, DbErr> {
+        let now = Utc::now();
+        let view = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            team_id: Set(team_id),
+            name: Set(name),
+            query: Set(query),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        view.insert(db).await
+    }
+
+    pub async fn find_by_id(db: &DatabaseConnection, id: Uuid) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(id).one(db).await
+    }
+
+    /// Views a user owns or that are shared with one of `team_ids`, by name
+    pub async fn list_visible(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        team_ids: &[Uuid],
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(
+                Condition::any()
+                    .add(Column::UserId.eq(user_id))
+                    .add(Column::TeamId.is_in(team_ids.iter().copied())),
+            )
+            .order_by_asc(Column::Name)
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    pub async fn update(
+        db: &DatabaseConnection,
+        view: Model,
+        team_id: Option<Uuid>,
+        name: String,
+        query: String,
+    ) -> Result<Model, DbErr> {
+        let mut active: ActiveModel = view.into();
+        active.team_id = Set(team_id);
+        active.name = Set(name);
+        active.query = Set(query);
+        active.updated_at = Set(Utc::now());
+        active.update(db).await
+    }
+
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::delete_by_id(id).exec(db).await?;
+        Ok(())
+    }
+}
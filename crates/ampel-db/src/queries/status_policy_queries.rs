@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use ampel_core::models::StatusPolicy;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::entities::repository_share;
+use crate::entities::status_policy::{ActiveModel, Column, Entity, Model};
+
+pub struct StatusPolicyQueries;
+
+impl StatusPolicyQueries {
+    pub async fn find_for_repository(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::RepositoryId.eq(repository_id))
+            .one(db)
+            .await
+    }
+
+    pub async fn find_for_team(
+        db: &DatabaseConnection,
+        team_id: Uuid,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::TeamId.eq(team_id))
+            .one(db)
+            .await
+    }
+
+    /// Create or replace the policy of a repository or of a team. Exactly one
+    /// of `repository_id` and `team_id` must be set.
+    pub async fn upsert(
+        db: &DatabaseConnection,
+        repository_id: Option<Uuid>,
+        team_id: Option<Uuid>,
+        policy: &StatusPolicy,
+    ) -> Result<Model, DbErr> {
+        let existing = match (repository_id, team_id) {
+            (Some(repository_id), _) => Self::find_for_repository(db, repository_id).await?,
+            (None, Some(team_id)) => Self::find_for_team(db, team_id).await?,
+            (None, None) => None,
+        };
+        let patterns = |list: &[String]| serde_json::to_string(list).unwrap_or_default();
+        let now = Utc::now();
+
+        let is_new = existing.is_none();
+        let mut active = match existing {
+            Some(existing) => existing.into(),
+            None => ActiveModel {
+                id: Set(Uuid::new_v4()),
+                repository_id: Set(repository_id),
+                team_id: Set(team_id),
+                created_at: Set(now),
+                ..Default::default()
+            },
+        };
+        active.required_checks = Set(patterns(&policy.required_checks));
+        active.ignored_checks = Set(patterns(&policy.ignored_checks));
+        active.min_approvals = Set(policy.min_approvals as i32);
+        active.require_named_approver = Set(policy.require_named_approver);
+        active.required_approvers = Set(patterns(&policy.required_approvers));
+        active.dismiss_stale_approvals = Set(policy.dismiss_stale_approvals);
+        active.skipped_checks = Set(policy.skipped_checks.to_string());
+        active.cancelled_checks = Set(policy.cancelled_checks.to_string());
+        active.draft_status = Set(policy.draft_status.to_string());
+        active.updated_at = Set(now);
+
+        if is_new {
+            active.insert(db).await
+        } else {
+            active.update(db).await
+        }
+    }
+
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+        Entity::delete_by_id(id).exec(db).await?;
+        Ok(())
+    }
+
+    /// The policy that applies to each of the given repositories that has
+    /// one: its own, otherwise that of the earliest team it was shared with
+    /// that has a policy. Repositories without one are left out.
+    pub async fn effective_for_repositories(
+        db: &DatabaseConnection,
+        repository_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Model>, DbErr> {
+        if repository_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut effective: HashMap<Uuid, Model> = Entity::find()
+            .filter(Column::RepositoryId.is_in(repository_ids.iter().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|policy| policy.repository_id.map(|id| (id, policy)))
+            .collect();
+
+        let remaining: Vec<Uuid> = repository_ids
+            .iter()
+            .copied()
+            .filter(|id| !effective.contains_key(id))
+            .collect();
+        if remaining.is_empty() {
+            return Ok(effective);
+        }
+
+        let shares = repository_share::Entity::find()
+            .filter(repository_share::Column::RepositoryId.is_in(remaining))
+            .filter(repository_share::Column::TeamId.is_not_null())
+            .order_by_asc(repository_share::Column::CreatedAt)
+            .all(db)
+            .await?;
+        let team_policies: HashMap<Uuid, Model> = Entity::find()
+            .filter(Column::TeamId.is_in(shares.iter().filter_map(|share| share.team_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|policy| policy.team_id.map(|id| (id, policy)))
+            .collect();
+
+        for share in shares {
+            if let Some(policy) = share.team_id.and_then(|id| team_policies.get(&id)) {
+                effective
+                    .entry(share.repository_id)
+                    .or_insert_with(|| policy.clone());
+            }
+        }
+
+        Ok(effective)
+    }
+
+    /// The policy a repository's PRs are evaluated with; the built-in rules
+    /// when no policy applies
+    pub async fn policy_for_repository(
+        db: &DatabaseConnection,
+        repository_id: Uuid,
+    ) -> Result<StatusPolicy, DbErr> {
+        Ok(Self::effective_for_repositories(db, &[repository_id])
+            .await?
+            .remove(&repository_id)
+            .map(StatusPolicy::from)
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::user_identity::{ActiveModel, Column, Entity, Model};
+
+pub struct UserIdentityQueries;
+
+impl UserIdentityQueries {
+    /// Find the identity an IdP reports for a subject
+    pub async fn find_by_subject(
+        db: &DatabaseConnection,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Issuer.eq(issuer))
+            .filter(Column::Subject.eq(subject))
+            .one(db)
+            .await
+    }
+
+    /// Link an identity to a user
+    pub async fn create(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        issuer: String,
+        subject: String,
+        email: Option<String>,
+    ) -> Result<Model, DbErr> {
+        let now = Utc::now();
+        let identity = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            issuer: Set(issuer),
+            subject: Set(subject),
+            email: Set(email),
+            created_at: Set(now),
+            last_login_at: Set(now),
+        };
+
+        identity.insert(db).await
+    }
+
+    /// Record a sign-in, keeping the email the IdP reported
+    pub async fn record_login(
+        db: &DatabaseConnection,
+        identity: Model,
+        email: Option<String>,
+    ) -> Result<Model, DbErr> {
+        let mut active: ActiveModel = identity.into();
+        active.email = Set(email);
+        active.last_login_at = Set(Utc::now());
+        active.update(db).await
+    }
+}
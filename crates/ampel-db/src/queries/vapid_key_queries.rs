@@ -0,0 +1,58 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, Set,
+};
+
+use crate::entities::vapid_key::{ActiveModel, Column, Entity, Model, CURRENT_VAPID_KEY_ID};
+
+pub struct VapidKeyQueries;
+
+impl VapidKeyQueries {
+    /// The deployment's VAPID key pair, if one was generated
+    pub async fn current(db: &DatabaseConnection) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(CURRENT_VAPID_KEY_ID).one(db).await
+    }
+
+    /// Store a key pair unless one exists, and return whichever is stored.
+    /// Processes racing to generate the first key pair all end up using the
+    /// same one.
+    pub async fn insert_if_absent(
+        db: &DatabaseConnection,
+        public_key: String,
+        private_key_encrypted: Vec<u8>,
+    ) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            id: Set(CURRENT_VAPID_KEY_ID),
+            public_key: Set(public_key),
+            private_key_encrypted: Set(private_key_encrypted),
+            created_at: Set(Utc::now()),
+        };
+        Entity::insert(model)
+            .on_conflict(OnConflict::column(Column::Id).do_nothing().to_owned())
+            .exec_without_returning(db)
+            .await?;
+
+        Self::current(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound("VAPID key".to_string()))
+    }
+
+    /// Replace the key pair
+    pub async fn replace(
+        db: &DatabaseConnection,
+        public_key: String,
+        private_key_encrypted: Vec<u8>,
+    ) -> Result<Model, DbErr> {
+        if Self::current(db).await?.is_none() {
+            return Self::insert_if_absent(db, public_key, private_key_encrypted).await;
+        }
+        ActiveModel {
+            id: Set(CURRENT_VAPID_KEY_ID),
+            public_key: Set(public_key),
+            private_key_encrypted: Set(private_key_encrypted),
+            created_at: Set(Utc::now()),
+        }
+        .update(db)
+        .await
+    }
+}
@@ -86,6 +86,8 @@ impl RemediationRunRepository for SeaOrmRemediationRunRepository {
             attempts: Set(0),
             error_message: Set(None),
             error_class: Set(None),
+            queued_at: Set(None),
+            heartbeat_at: Set(None),
             started_at: Set(now),
             completed_at: Set(None),
             created_at: Set(now),
@@ -230,6 +232,43 @@ impl RemediationRunRepository for SeaOrmRemediationRunRepository {
         }
         Ok(out)
     }
+
+    async fn skipped_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>> {
+        let rows = remediation_run_pr::Entity::find()
+            .filter(remediation_run_pr::Column::RemediationRunId.eq(run_id))
+            .all(&self.db)
+            .await
+            .map_err(db_err)?;
+        Ok(rows
+            .into_iter()
+            .filter(|row| {
+                matches!(
+                    serde_json::from_str::<MergeDisposition>(&row.disposition),
+                    Ok(MergeDisposition::SkippedConflict { .. }
+                        | MergeDisposition::LeftOpen { .. })
+                )
+            })
+            .map(|row| row.pr_number)
+            .collect())
+    }
+
+    async fn externally_closed_source_prs(&self, run_id: Uuid) -> AmpelResult<Vec<i64>> {
+        let rows = remediation_run_pr::Entity::find()
+            .filter(remediation_run_pr::Column::RemediationRunId.eq(run_id))
+            .all(&self.db)
+            .await
+            .map_err(db_err)?;
+        Ok(rows
+            .into_iter()
+            .filter(|row| {
+                matches!(
+                    serde_json::from_str::<MergeDisposition>(&row.disposition),
+                    Ok(MergeDisposition::MergedExternally | MergeDisposition::ClosedExternally)
+                )
+            })
+            .map(|row| row.pr_number)
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -435,4 +474,77 @@ mod tests {
         assert_eq!(rows[0].pr_number, 7);
         assert!(rows[0].disposition.contains("consolidated"));
     }
+
+    #[tokio::test]
+    async fn should_list_only_skipped_or_left_open_source_prs() {
+        // Arrange
+        let conn = sqlite_with_remediation_tables().await;
+        let repo = SeaOrmRemediationRunRepository::new(conn);
+        let run = repo
+            .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+            .await
+            .unwrap();
+        repo.record_disposition(run.id, 1, MergeDisposition::Consolidated)
+            .await
+            .unwrap();
+        repo.record_disposition(
+            run.id,
+            2,
+            MergeDisposition::SkippedConflict {
+                reason: "merge conflict in src/lib.rs".into(),
+                files: vec!["src/lib.rs".into()],
+            },
+        )
+        .await
+        .unwrap();
+        repo.record_disposition(
+            run.id,
+            3,
+            MergeDisposition::LeftOpen {
+                reason: "no merge base with origin/main".into(),
+            },
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let mut skipped = repo.skipped_source_prs(run.id).await.unwrap();
+        skipped.sort();
+
+        // Assert
+        assert_eq!(skipped, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn should_list_source_prs_merged_or_closed_outside_the_run() {
+        // Arrange
+        let conn = sqlite_with_remediation_tables().await;
+        let repo = SeaOrmRemediationRunRepository::new(conn);
+        let run = repo
+            .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+            .await
+            .unwrap();
+        repo.record_disposition(run.id, 1, MergeDisposition::MergedExternally)
+            .await
+            .unwrap();
+        repo.record_disposition(run.id, 2, MergeDisposition::ClosedExternally)
+            .await
+            .unwrap();
+        repo.record_disposition(
+            run.id,
+            3,
+            MergeDisposition::ClosedWithRef {
+                consolidated_pr_number: 9,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let mut external = repo.externally_closed_source_prs(run.id).await.unwrap();
+        external.sort();
+
+        // Assert
+        assert_eq!(external, vec![1, 2]);
+    }
 }
@@ -465,6 +465,10 @@ impl PullRequestFixture {
             merged_at: Set(None),
             closed_at: Set(None),
             last_synced_at: Set(now),
+            ampel_status: Set(None),
+            ampel_status_changed_at: Set(None),
+            head_sha: Set(None),
+            milestone: Set(None),
         };
 
         pr.insert(db).await
@@ -611,6 +615,7 @@ impl ReviewFixture {
             state: Set(self.state),
             body: Set(self.body),
             submitted_at: Set(Utc::now()),
+            commit_sha: Set(None),
         };
 
         review.insert(db).await
@@ -145,6 +145,10 @@ async fn test_find_open_prs_by_repository_performance() {
             merged_at: Set(None),
             closed_at: Set(None),
             last_synced_at: Set(Utc::now()),
+            ampel_status: Set(None),
+            ampel_status_changed_at: Set(None),
+            head_sha: Set(None),
+            milestone: Set(None),
         };
 
         pr.insert(test_db.connection()).await.unwrap();
@@ -212,6 +216,10 @@ async fn test_batch_ci_check_queries() {
         merged_at: Set(None),
         closed_at: Set(None),
         last_synced_at: Set(Utc::now()),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     };
 
     let pr = pr.insert(test_db.connection()).await.unwrap();
@@ -297,6 +305,10 @@ async fn test_batch_review_queries() {
         merged_at: Set(None),
         closed_at: Set(None),
         last_synced_at: Set(Utc::now()),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     };
 
     let pr = pr.insert(test_db.connection()).await.unwrap();
@@ -313,6 +325,7 @@ async fn test_batch_review_queries() {
             state: Set("approved".to_string()),
             body: Set(Some("LGTM".to_string())),
             submitted_at: Set(Utc::now()),
+            commit_sha: Set(None),
         };
 
         review.insert(test_db.connection()).await.unwrap();
@@ -77,6 +77,8 @@ async fn create_test_pr(
         now,
         None,
         None,
+        None,
+        None,
     )
     .await
 }
@@ -166,7 +168,7 @@ async fn test_find_open_by_repository() {
 
 /// Test finding open PRs for a user with pagination
 #[tokio::test]
-async fn test_find_open_by_user() {
+async fn test_find_open_accessible_by_user() {
     if TestDb::skip_if_sqlite() {
         return;
     }
@@ -199,7 +201,7 @@ async fn test_find_open_by_user() {
         .unwrap();
 
     // Find open PRs for user (page 1, 2 per page)
-    let (prs, total) = PrQueries::find_open_by_user(db, user.id, 1, 2)
+    let (prs, total) = PrQueries::find_open_accessible_by_user(db, user.id, 1, 2)
         .await
         .expect("Failed to find user PRs");
 
@@ -207,7 +209,7 @@ async fn test_find_open_by_user() {
     assert_eq!(total, 3, "Total should be 3 open PRs");
 
     // Page 2
-    let (prs_page2, _) = PrQueries::find_open_by_user(db, user.id, 2, 2)
+    let (prs_page2, _) = PrQueries::find_open_accessible_by_user(db, user.id, 2, 2)
         .await
         .expect("Failed to find user PRs page 2");
 
@@ -262,6 +264,8 @@ async fn test_upsert_create() {
         now,
         None,
         None,
+        None,
+        None,
     )
     .await
     .expect("Failed to create PR");
@@ -327,6 +331,8 @@ async fn test_upsert_update() {
         now,
         None,
         None,
+        None,
+        None,
     )
     .await
     .expect("Failed to update PR");
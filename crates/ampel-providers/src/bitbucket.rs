@@ -1,22 +1,28 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{ProviderError, ProviderResult};
+use crate::http::{self, ResponseCache};
 use crate::remediation::{RemediationCapable, RemediationCaps};
 use crate::traits::{
-    GitProvider, MergeResult, ProviderCICheck, ProviderCredentials, ProviderPullRequest,
-    ProviderReview, ProviderUser, RateLimitInfo, TokenValidation,
+    GitProvider, MergeResult, OAuthClient, OAuthTokens, ProviderCICheck, ProviderCredentials,
+    ProviderPullRequest, ProviderReview, ProviderUser, RateLimitInfo, TokenValidation,
 };
+use crate::webhook::WebhookEvent;
 use ampel_core::models::{
     DiscoveredRepository, GitProvider as Provider, MergeRequest, MergeStrategy,
 };
+use ampel_core::remediation::SuggestedChange;
 
 pub struct BitbucketProvider {
     client: Client,
     base_url: String,
+    cache: Arc<ResponseCache>,
 }
 
 impl BitbucketProvider {
@@ -33,24 +39,76 @@ impl BitbucketProvider {
 
         let base_url = instance_url.unwrap_or_else(|| "https://api.bitbucket.org/2.0".to_string());
 
-        Self { client, base_url }
+        Self {
+            client,
+            base_url,
+            cache: Arc::new(ResponseCache::new()),
+        }
+    }
+
+    /// Share an ETag cache with other provider instances
+    pub fn with_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
     }
 
     fn api_url(&self, path: &str) -> String {
         format!("{}{}", self.base_url, path)
     }
 
-    /// Generate appropriate authentication header for PAT (App Password)
-    fn auth_header(&self, credentials: &ProviderCredentials) -> String {
+    /// Generate the authentication header: Basic for App Passwords, Bearer
+    /// for OAuth access tokens
+    fn auth_header(&self, credentials: &ProviderCredentials) -> ProviderResult<String> {
         match credentials {
             ProviderCredentials::Pat { token, username } => {
                 // Bitbucket App Passwords require Basic Auth with username:token
                 let username = username.as_deref().unwrap_or("");
                 let auth = BASE64.encode(format!("{}:{}", username, token));
-                format!("Basic {}", auth)
+                Ok(format!("Basic {}", auth))
+            }
+            ProviderCredentials::OAuth { access_token, .. } => {
+                Ok(format!("Bearer {}", access_token))
             }
+            ProviderCredentials::GitHubApp { .. } => Err(ProviderError::ConfigError(
+                "GitHub App credentials cannot be used with Bitbucket".to_string(),
+            )),
         }
     }
+
+    /// Authenticated GET for an absolute URL (`next` links are absolute)
+    fn get(&self, credentials: &ProviderCredentials, url: &str) -> ProviderResult<RequestBuilder> {
+        Ok(self.get_with(&self.auth_header(credentials)?, url))
+    }
+
+    /// GET with an already resolved `Authorization` header
+    fn get_with(&self, auth_header: &str, url: &str) -> RequestBuilder {
+        self.client.get(url).header("Authorization", auth_header)
+    }
+
+    /// Fetch every page of a paginated collection by following its `next` link
+    async fn get_all<T>(
+        &self,
+        credentials: &ProviderCredentials,
+        first: RequestBuilder,
+        error_message: &str,
+    ) -> ProviderResult<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let auth_header = self.auth_header(credentials)?;
+        http::collect_pages(
+            &self.client,
+            &self.cache,
+            first,
+            error_message,
+            |page| {
+                let page: BitbucketPaginated<T> = page.json()?;
+                Ok((page.values, page.next))
+            },
+            |url| self.get_with(&auth_header, url),
+        )
+        .await
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -73,7 +131,6 @@ struct BitbucketLink {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct BitbucketPaginated<T> {
     values: Vec<T>,
     next: Option<String>,
@@ -123,6 +180,8 @@ struct BitbucketPR {
     merge_commit: Option<BitbucketMergeCommit>,
     closed_by: Option<BitbucketPRAuthor>,
     comment_count: Option<i32>,
+    #[serde(default)]
+    reviewers: Vec<BitbucketPRAuthor>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -133,6 +192,12 @@ struct BitbucketPRLinks {
 #[derive(Debug, Deserialize)]
 struct BitbucketPRRef {
     branch: BitbucketBranch,
+    commit: Option<BitbucketPRCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPRCommit {
+    hash: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -216,6 +281,7 @@ fn bitbucket_pr_to_provider(pr: BitbucketPR) -> ProviderPullRequest {
         state: state.to_string(),
         source_branch: pr.source.branch.name,
         target_branch: pr.destination.branch.name,
+        head_sha: pr.source.commit.map(|c| c.hash),
         author: pr
             .author
             .username
@@ -234,6 +300,139 @@ fn bitbucket_pr_to_provider(pr: BitbucketPR) -> ProviderPullRequest {
         updated_at: parse_datetime(&pr.updated_on),
         merged_at,
         closed_at,
+        requested_reviewers: pr
+            .reviewers
+            .into_iter()
+            .filter_map(|r| r.username.or(r.display_name))
+            .collect(),
+        // Bitbucket pull requests have no labels, assignees or milestones
+        labels: Vec::new(),
+        assignees: Vec::new(),
+        milestone: None,
+    }
+}
+
+// --- Inbound webhooks ---
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPullRequestHook {
+    pullrequest: BitbucketPR,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketReviewHook {
+    pullrequest: BitbucketPRId,
+    approval: Option<BitbucketHookParticipant>,
+    changes_request: Option<BitbucketHookParticipant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPRId {
+    id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketHookParticipant {
+    date: String,
+    user: BitbucketPRAuthor,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketCommitStatusHook {
+    commit_status: BitbucketHookCommitStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketHookCommitStatus {
+    name: Option<String>,
+    key: String,
+    state: String,
+    url: Option<String>,
+    refname: Option<String>,
+    created_on: Option<String>,
+    updated_on: Option<String>,
+}
+
+/// Normalise a Bitbucket Cloud webhook delivery.
+///
+/// `event` is the `X-Event-Key` header. `pullrequest:*` lifecycle keys become
+/// PR updates, `pullrequest:approved`/`unapproved`/`changes_request_created`
+/// become reviews, and `repo:commit_status_*` becomes a check matched to open
+/// PRs by branch (Bitbucket statuses carry no PR id).
+pub fn parse_webhook(event: &str, body: &[u8]) -> ProviderResult<WebhookEvent> {
+    match event {
+        "pullrequest:created"
+        | "pullrequest:updated"
+        | "pullrequest:fulfilled"
+        | "pullrequest:rejected" => {
+            let payload: BitbucketPullRequestHook = serde_json::from_slice(body)?;
+            Ok(WebhookEvent::PullRequest(Box::new(
+                bitbucket_pr_to_provider(payload.pullrequest),
+            )))
+        }
+        "pullrequest:approved"
+        | "pullrequest:unapproved"
+        | "pullrequest:changes_request_created" => {
+            let payload: BitbucketReviewHook = serde_json::from_slice(body)?;
+            let (participant, state) = match event {
+                "pullrequest:approved" => (payload.approval, "approved"),
+                "pullrequest:unapproved" => (payload.approval, "dismissed"),
+                _ => (payload.changes_request, "changes_requested"),
+            };
+            let Some(participant) = participant else {
+                return Ok(WebhookEvent::Ignored(format!(
+                    "{} without participant",
+                    event
+                )));
+            };
+            let user = participant.user;
+            let reviewer = user.username.or(user.display_name).unwrap_or_default();
+            Ok(WebhookEvent::Review {
+                pr_number: payload.pullrequest.id,
+                review: ProviderReview {
+                    id: format!("{}:{}", payload.pullrequest.id, reviewer),
+                    reviewer,
+                    reviewer_avatar_url: user.links.and_then(|l| l.avatar.map(|a| a.href)),
+                    state: state.to_string(),
+                    commit_sha: None,
+                    body: None,
+                    submitted_at: parse_datetime(&participant.date),
+                },
+            })
+        }
+        "repo:commit_status_created" | "repo:commit_status_updated" => {
+            let payload: BitbucketCommitStatusHook = serde_json::from_slice(body)?;
+            let s = payload.commit_status;
+            let Some(branch) = s.refname else {
+                return Ok(WebhookEvent::Ignored(
+                    "commit status without branch".to_string(),
+                ));
+            };
+            let (status, conclusion) = match s.state.as_str() {
+                "INPROGRESS" => ("in_progress".to_string(), None),
+                "SUCCESSFUL" => ("completed".to_string(), Some("success".to_string())),
+                "FAILED" => ("completed".to_string(), Some("failure".to_string())),
+                "STOPPED" => ("completed".to_string(), Some("cancelled".to_string())),
+                _ => ("queued".to_string(), None),
+            };
+            let completed_at = conclusion
+                .is_some()
+                .then(|| parse_datetime_opt(&s.updated_on))
+                .flatten();
+            Ok(WebhookEvent::Checks {
+                pr_numbers: Vec::new(),
+                source_branch: Some(branch),
+                checks: vec![ProviderCICheck {
+                    name: s.name.unwrap_or(s.key),
+                    status,
+                    conclusion,
+                    url: s.url,
+                    started_at: parse_datetime_opt(&s.created_on),
+                    completed_at,
+                }],
+            })
+        }
+        other => Ok(WebhookEvent::Ignored(format!("event '{}'", other))),
     }
 }
 
@@ -260,6 +459,17 @@ struct BitbucketCommentResponse {
     id: i64,
 }
 
+#[derive(Debug, Deserialize)]
+struct BitbucketComment {
+    content: BitbucketCommentContent,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketCommentContent {
+    #[serde(default)]
+    raw: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct BitbucketCommitStatus {
     key: Option<String>,
@@ -289,8 +499,13 @@ impl GitProvider for BitbucketProvider {
         credentials: &ProviderCredentials,
     ) -> ProviderResult<TokenValidation> {
         // For PAT with Bitbucket, username is required
-        let ProviderCredentials::Pat { username, .. } = credentials;
-        if username.is_none() || username.as_ref().unwrap().is_empty() {
+        let missing_username = match credentials {
+            ProviderCredentials::Pat { username, .. } => {
+                username.as_deref().is_none_or(str::is_empty)
+            }
+            _ => false,
+        };
+        if missing_username {
             return Ok(TokenValidation {
                 is_valid: false,
                 user_id: None,
@@ -308,7 +523,7 @@ impl GitProvider for BitbucketProvider {
         let response = self
             .client
             .get(self.api_url("/user"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -356,7 +571,7 @@ impl GitProvider for BitbucketProvider {
         let response = self
             .client
             .get(self.api_url("/user"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -392,7 +607,7 @@ impl GitProvider for BitbucketProvider {
         let response = self
             .client
             .get(self.api_url("/repositories"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[
                 ("role", "member"),
                 ("page", &page.to_string()),
@@ -455,7 +670,7 @@ impl GitProvider for BitbucketProvider {
         let response = self
             .client
             .get(self.api_url(&format!("/repositories/{}/{}", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -507,72 +722,23 @@ impl GitProvider for BitbucketProvider {
             _ => state,
         };
 
-        let response = self
-            .client
-            .get(self.api_url(&format!("/repositories/{}/{}/pullrequests", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
-            .query(&[("state", bb_state)])
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
-                message: "Failed to list pull requests".to_string(),
-            });
-        }
-
-        let prs: BitbucketPaginated<BitbucketPR> = response.json().await?;
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!("/repositories/{}/{}/pullrequests", owner, repo)),
+            )?
+            // Reviewers are left out of list responses unless asked for
+            .query(&[
+                ("state", bb_state),
+                ("pagelen", "50"),
+                ("fields", "+values.reviewers"),
+            ]);
 
-        Ok(prs
-            .values
-            .into_iter()
-            .map(|pr| {
-                let state = match pr.state.as_str() {
-                    "OPEN" => "open",
-                    "MERGED" => "merged",
-                    "DECLINED" | "SUPERSEDED" => "closed",
-                    _ => &pr.state,
-                };
+        let prs: Vec<BitbucketPR> = self
+            .get_all(credentials, first, "Failed to list pull requests")
+            .await?;
 
-                ProviderPullRequest {
-                    provider_id: pr.id.to_string(),
-                    number: pr.id,
-                    title: pr.title,
-                    description: pr.description,
-                    url: pr.links.html.href,
-                    state: state.to_string(),
-                    source_branch: pr.source.branch.name,
-                    target_branch: pr.destination.branch.name,
-                    author: pr
-                        .author
-                        .username
-                        .or(pr.author.display_name)
-                        .unwrap_or_default(),
-                    author_avatar_url: pr.author.links.and_then(|l| l.avatar.map(|a| a.href)),
-                    is_draft: false, // Bitbucket doesn't have draft PRs
-                    is_mergeable: None,
-                    has_conflicts: false,
-                    additions: 0,
-                    deletions: 0,
-                    changed_files: 0,
-                    commits_count: 0,
-                    comments_count: pr.comment_count.unwrap_or(0),
-                    created_at: parse_datetime(&pr.created_on),
-                    updated_at: parse_datetime(&pr.updated_on),
-                    merged_at: if pr.state == "MERGED" {
-                        Some(parse_datetime(&pr.updated_on))
-                    } else {
-                        None
-                    },
-                    closed_at: if pr.state != "OPEN" {
-                        Some(parse_datetime(&pr.updated_on))
-                    } else {
-                        None
-                    },
-                }
-            })
-            .collect())
+        Ok(prs.into_iter().map(bitbucket_pr_to_provider).collect())
     }
 
     async fn get_pull_request(
@@ -582,74 +748,36 @@ impl GitProvider for BitbucketProvider {
         repo: &str,
         number: i32,
     ) -> ProviderResult<ProviderPullRequest> {
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/repositories/{}/{}/pullrequests/{}",
-                owner, repo, number
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .send()
-            .await?;
-
-        if response.status() == 404 {
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repositories/{}/{}/pullrequests/{}",
+                    owner, repo, number
+                )),
+            )?,
+        )
+        .await?;
+
+        if response.status == 404 {
             return Err(ProviderError::NotFound(format!(
                 "Pull request #{} not found",
                 number
             )));
         }
 
-        if !response.status().is_success() {
+        if !response.status.is_success() {
             return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
+                status_code: response.status.as_u16(),
                 message: "Failed to get pull request".to_string(),
             });
         }
 
-        let pr: BitbucketPR = response.json().await?;
-        let state = match pr.state.as_str() {
-            "OPEN" => "open",
-            "MERGED" => "merged",
-            "DECLINED" | "SUPERSEDED" => "closed",
-            _ => &pr.state,
-        };
+        let pr: BitbucketPR = response.json()?;
 
-        Ok(ProviderPullRequest {
-            provider_id: pr.id.to_string(),
-            number: pr.id,
-            title: pr.title,
-            description: pr.description,
-            url: pr.links.html.href,
-            state: state.to_string(),
-            source_branch: pr.source.branch.name,
-            target_branch: pr.destination.branch.name,
-            author: pr
-                .author
-                .username
-                .or(pr.author.display_name)
-                .unwrap_or_default(),
-            author_avatar_url: pr.author.links.and_then(|l| l.avatar.map(|a| a.href)),
-            is_draft: false,
-            is_mergeable: None,
-            has_conflicts: false,
-            additions: 0,
-            deletions: 0,
-            changed_files: 0,
-            commits_count: 0,
-            comments_count: pr.comment_count.unwrap_or(0),
-            created_at: parse_datetime(&pr.created_on),
-            updated_at: parse_datetime(&pr.updated_on),
-            merged_at: if pr.state == "MERGED" {
-                Some(parse_datetime(&pr.updated_on))
-            } else {
-                None
-            },
-            closed_at: if pr.state != "OPEN" {
-                Some(parse_datetime(&pr.updated_on))
-            } else {
-                None
-            },
-        })
+        Ok(bitbucket_pr_to_provider(pr))
     }
 
     async fn get_ci_checks(
@@ -659,20 +787,23 @@ impl GitProvider for BitbucketProvider {
         repo: &str,
         _pr_number: i32,
     ) -> ProviderResult<Vec<ProviderCICheck>> {
-        // Get latest pipelines for the repo
-        let response = self
-            .client
-            .get(self.api_url(&format!("/repositories/{}/{}/pipelines/", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
-            .query(&[("sort", "-created_on"), ("pagelen", "5")])
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
+        // Get latest pipelines for the repo (deliberately a single page)
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!("/repositories/{}/{}/pipelines/", owner, repo)),
+            )?
+            .query(&[("sort", "-created_on"), ("pagelen", "5")]),
+        )
+        .await?;
+
+        if !response.status.is_success() {
             return Ok(vec![]);
         }
 
-        let pipelines: BitbucketPaginated<BitbucketPipeline> = response.json().await?;
+        let pipelines: BitbucketPaginated<BitbucketPipeline> = response.json()?;
 
         Ok(pipelines
             .values
@@ -712,30 +843,32 @@ impl GitProvider for BitbucketProvider {
         repo: &str,
         pr_number: i32,
     ) -> ProviderResult<Vec<ProviderReview>> {
-        // Get approvals for the PR
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/repositories/{}/{}/pullrequests/{}/activity",
-                owner, repo, pr_number
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Ok(vec![]);
-        }
-
         #[derive(Debug, Deserialize)]
         struct Activity {
             approval: Option<BitbucketApproval>,
         }
 
-        let activities: BitbucketPaginated<Activity> = response.json().await?;
+        // Get approvals for the PR from its full activity log
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repositories/{}/{}/pullrequests/{}/activity",
+                    owner, repo, pr_number
+                )),
+            )?
+            .query(&[("pagelen", "50")]);
+
+        let activities: Vec<Activity> = match self
+            .get_all(credentials, first, "Failed to get pull request activity")
+            .await
+        {
+            Ok(activities) => activities,
+            Err(ProviderError::ApiError { .. }) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
 
         Ok(activities
-            .values
             .into_iter()
             .filter_map(|a| {
                 a.approval.map(|approval| ProviderReview {
@@ -743,6 +876,7 @@ impl GitProvider for BitbucketProvider {
                     reviewer: approval.user.username,
                     reviewer_avatar_url: approval.user.links.avatar.map(|a| a.href),
                     state: "approved".to_string(),
+                    commit_sha: None,
                     body: None,
                     submitted_at: parse_datetime(&approval.date),
                 })
@@ -776,7 +910,7 @@ impl GitProvider for BitbucketProvider {
                 "/repositories/{}/{}/pullrequests/{}/merge",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .json(&body)
             .send()
             .await?;
@@ -806,15 +940,40 @@ impl GitProvider for BitbucketProvider {
             reset_at: Utc::now() + chrono::Duration::hours(1),
         })
     }
+
+    async fn refresh_oauth_token(
+        &self,
+        client: &OAuthClient,
+        refresh_token: &str,
+    ) -> ProviderResult<OAuthTokens> {
+        // Bitbucket Cloud serves OAuth from the web host, not the API host
+        let token_url = if self.base_url == "https://api.bitbucket.org/2.0" {
+            "https://bitbucket.org/site/oauth2/access_token".to_string()
+        } else {
+            format!("{}/site/oauth2/access_token", self.base_url)
+        };
+        let request = self
+            .client
+            .post(token_url)
+            .basic_auth(&client.client_id, Some(&client.client_secret))
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+            ]);
+
+        http::refresh_oauth_token(request).await
+    }
 }
 
 #[async_trait]
 impl RemediationCapable for BitbucketProvider {
     fn capabilities(&self) -> RemediationCaps {
-        // Bitbucket lacks a branch-level merge/rebase primitive and a PR label concept.
+        // Bitbucket lacks a branch-level merge/rebase primitive, a PR label concept
+        // and suggested changes.
         RemediationCaps {
             update_branch_from_base: false,
             add_labels: false,
+            create_suggestions: false,
             ..RemediationCaps::all()
         }
     }
@@ -832,7 +991,7 @@ impl RemediationCapable for BitbucketProvider {
                 "/repositories/{}/{}/refs/branches/{}",
                 owner, repo, repository.default_branch
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -859,7 +1018,7 @@ impl RemediationCapable for BitbucketProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/repositories/{}/{}/refs/branches", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .json(&body)
             .send()
             .await?;
@@ -906,7 +1065,7 @@ impl RemediationCapable for BitbucketProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/repositories/{}/{}/pullrequests", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .json(&payload)
             .send()
             .await?;
@@ -922,6 +1081,39 @@ impl RemediationCapable for BitbucketProvider {
         Ok(bitbucket_pr_to_provider(pr))
     }
 
+    async fn find_open_pull_request_by_head(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> ProviderResult<Option<ProviderPullRequest>> {
+        let query = format!(
+            "source.branch.name=\"{}\" AND state=\"OPEN\"",
+            branch.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!("/repositories/{}/{}/pullrequests", owner, repo)),
+            )?
+            .query(&[("q", query.as_str())]),
+        )
+        .await?;
+
+        if !response.status.is_success() {
+            return Err(ProviderError::ApiError {
+                status_code: response.status.as_u16(),
+                message: "Failed to find pull request by source branch".to_string(),
+            });
+        }
+
+        let page: BitbucketPaginated<BitbucketPR> = response.json()?;
+        Ok(page.values.into_iter().next().map(bitbucket_pr_to_provider))
+    }
+
     async fn update_pull_request(
         &self,
         credentials: &ProviderCredentials,
@@ -950,7 +1142,7 @@ impl RemediationCapable for BitbucketProvider {
                 "/repositories/{}/{}/pullrequests/{}",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .json(&serde_json::Value::Object(payload))
             .send()
             .await?;
@@ -978,7 +1170,7 @@ impl RemediationCapable for BitbucketProvider {
                 "/repositories/{}/{}/pullrequests/{}/decline",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -1006,7 +1198,7 @@ impl RemediationCapable for BitbucketProvider {
                 "/repositories/{}/{}/pullrequests/{}/comments",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .json(&payload)
             .send()
             .await?;
@@ -1022,6 +1214,44 @@ impl RemediationCapable for BitbucketProvider {
         Ok(comment.id)
     }
 
+    async fn list_comments(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> ProviderResult<Vec<String>> {
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repositories/{}/{}/pullrequests/{}/comments",
+                    owner, repo, pr_number
+                )),
+            )?
+            .query(&[("pagelen", "100")]);
+
+        let comments: Vec<BitbucketComment> = self
+            .get_all(credentials, first, "Failed to list pull request comments")
+            .await?;
+        Ok(comments.into_iter().map(|c| c.content.raw).collect())
+    }
+
+    async fn create_suggestions(
+        &self,
+        _credentials: &ProviderCredentials,
+        _owner: &str,
+        _repo: &str,
+        _pr_number: i32,
+        _suggestions: &[SuggestedChange],
+    ) -> ProviderResult<()> {
+        // Unsupported on Bitbucket — see `capabilities()`. The adapter falls back
+        // to posting the diff as a plain comment.
+        Err(ProviderError::NotSupported(
+            "Bitbucket pull requests do not support suggested changes".to_string(),
+        ))
+    }
+
     async fn add_labels(
         &self,
         _credentials: &ProviderCredentials,
@@ -1049,7 +1279,7 @@ impl RemediationCapable for BitbucketProvider {
                 "/repositories/{}/{}/commit/{}/statuses",
                 owner, repo, git_ref
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -1097,7 +1327,7 @@ impl RemediationCapable for BitbucketProvider {
                 "/repositories/{}/{}/refs/branches/{}",
                 owner, repo, branch_name
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone)]
@@ -33,6 +34,32 @@ pub enum ProviderError {
     NotSupported(String),
 }
 
+impl ProviderError {
+    /// Whether retrying the same call later may succeed: network failures,
+    /// rate limiting and provider-side 5xx errors. Auth, permission, not-found
+    /// and validation errors will fail the same way again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ProviderError::NetworkError(_) | ProviderError::RateLimitExceeded(_) => true,
+            ProviderError::ApiError { status_code, .. } => {
+                *status_code == 429 || *status_code >= 500
+            }
+            _ => false,
+        }
+    }
+
+    /// When a rate-limited account may call the provider again, if the
+    /// provider told us. The HTTP layer stores it as RFC 3339.
+    pub fn rate_limit_reset(&self) -> Option<DateTime<Utc>> {
+        match self {
+            ProviderError::RateLimitExceeded(reset_at) => DateTime::parse_from_rfc3339(reset_at)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc)),
+            _ => None,
+        }
+    }
+}
+
 impl From<reqwest::Error> for ProviderError {
     fn from(err: reqwest::Error) -> Self {
         if err.is_timeout() {
@@ -52,3 +79,42 @@ impl From<serde_json::Error> for ProviderError {
 }
 
 pub type ProviderResult<T> = Result<T, ProviderError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_classify_transient_errors() {
+        assert!(ProviderError::NetworkError("timeout".into()).is_transient());
+        assert!(ProviderError::RateLimitExceeded("soon".into()).is_transient());
+        assert!(ProviderError::ApiError {
+            status_code: 502,
+            message: "bad gateway".into()
+        }
+        .is_transient());
+        assert!(!ProviderError::ApiError {
+            status_code: 405,
+            message: "not mergeable".into()
+        }
+        .is_transient());
+        assert!(!ProviderError::AuthenticationFailed("nope".into()).is_transient());
+    }
+
+    #[test]
+    fn should_parse_rate_limit_reset() {
+        let reset = "2026-06-01T12:00:00+00:00";
+
+        assert_eq!(
+            ProviderError::RateLimitExceeded(reset.into())
+                .rate_limit_reset()
+                .map(|dt| dt.to_rfc3339()),
+            Some(reset.to_string())
+        );
+        assert_eq!(
+            ProviderError::RateLimitExceeded("soon".into()).rate_limit_reset(),
+            None
+        );
+        assert_eq!(ProviderError::NotFound("x".into()).rate_limit_reset(), None);
+    }
+}
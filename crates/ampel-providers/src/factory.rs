@@ -1,23 +1,87 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use ampel_core::models::GitProvider as Provider;
 
-use crate::{BitbucketProvider, GitHubProvider, GitLabProvider, GitProvider};
+use crate::traits::OAuthClient;
+use crate::{
+    BitbucketProvider, GitHubProvider, GitLabProvider, GitProvider, InstallationTokenCache,
+    ResponseCache,
+};
 
 /// Factory for creating Git provider instances
-pub struct ProviderFactory;
+///
+/// Every provider created by one factory shares its ETag cache and GitHub App
+/// installation tokens, so conditional requests and minted tokens survive
+/// across the short-lived provider instances the API and worker create per
+/// call. OAuth applications registered with [`Self::with_oauth_client`] are
+/// used to refresh OAuth-connected accounts.
+pub struct ProviderFactory {
+    cache: Arc<ResponseCache>,
+    installation_tokens: Arc<InstallationTokenCache>,
+    oauth_clients: HashMap<Provider, OAuthClient>,
+}
 
 impl ProviderFactory {
     pub fn new() -> Self {
-        Self
+        Self {
+            cache: Arc::new(ResponseCache::new()),
+            installation_tokens: Arc::new(InstallationTokenCache::new()),
+            oauth_clients: HashMap::new(),
+        }
+    }
+
+    /// Register the OAuth application used to refresh `provider` accounts
+    pub fn with_oauth_client(mut self, provider: Provider, client: OAuthClient) -> Self {
+        self.oauth_clients.insert(provider, client);
+        self
+    }
+
+    /// Register OAuth applications from `GITLAB_OAUTH_CLIENT_ID`/`_SECRET`
+    /// and `BITBUCKET_OAUTH_CLIENT_ID`/`_SECRET`, where both are set
+    pub fn with_oauth_clients_from_env(self) -> Self {
+        [
+            (Provider::GitLab, "GITLAB"),
+            (Provider::Bitbucket, "BITBUCKET"),
+        ]
+        .into_iter()
+        .fold(self, |factory, (provider, prefix)| {
+            let id = std::env::var(format!("{}_OAUTH_CLIENT_ID", prefix));
+            let secret = std::env::var(format!("{}_OAUTH_CLIENT_SECRET", prefix));
+            match (id, secret) {
+                (Ok(client_id), Ok(client_secret)) => factory.with_oauth_client(
+                    provider,
+                    OAuthClient {
+                        client_id,
+                        client_secret,
+                    },
+                ),
+                _ => factory,
+            }
+        })
+    }
+
+    /// The OAuth application registered for a provider, if any
+    pub fn oauth_client(&self, provider: Provider) -> Option<&OAuthClient> {
+        self.oauth_clients.get(&provider)
+    }
+
+    /// The ETag cache shared by this factory's providers
+    pub fn cache(&self) -> &Arc<ResponseCache> {
+        &self.cache
     }
 
     /// Create a provider instance for the given type and optional instance URL
     pub fn create(&self, provider: Provider, instance_url: Option<String>) -> Arc<dyn GitProvider> {
+        let cache = self.cache.clone();
         match provider {
-            Provider::GitHub => Arc::new(GitHubProvider::new(instance_url)),
-            Provider::GitLab => Arc::new(GitLabProvider::new(instance_url)),
-            Provider::Bitbucket => Arc::new(BitbucketProvider::new(instance_url)),
+            Provider::GitHub => Arc::new(
+                GitHubProvider::new(instance_url)
+                    .with_cache(cache)
+                    .with_installation_tokens(self.installation_tokens.clone()),
+            ),
+            Provider::GitLab => Arc::new(GitLabProvider::new(instance_url).with_cache(cache)),
+            Provider::Bitbucket => Arc::new(BitbucketProvider::new(instance_url).with_cache(cache)),
         }
     }
 }
@@ -1,21 +1,34 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{ProviderError, ProviderResult};
+use crate::github_app::{self, InstallationTokenCache};
+use crate::http::{self, ResponseCache};
 use crate::remediation::{RemediationCapable, RemediationCaps};
 use crate::traits::{
     GitProvider, MergeResult, ProviderCICheck, ProviderCredentials, ProviderPullRequest,
     ProviderReview, ProviderUser, RateLimitInfo, TokenValidation,
 };
+use crate::webhook::WebhookEvent;
 use ampel_core::models::{
     DiscoveredRepository, GitProvider as Provider, MergeRequest, MergeStrategy,
 };
+use ampel_core::remediation::{diff_hunks, DiffHunk, SuggestedChange};
+
+/// Summary posted with a suggested-changes review (GitHub requires one).
+const SUGGESTION_REVIEW_BODY: &str =
+    "Ampel remediation proposes the fixes below. Nothing was pushed to this branch.";
 
 pub struct GitHubProvider {
     client: Client,
     base_url: String,
+    cache: Arc<ResponseCache>,
+    installation_tokens: Arc<InstallationTokenCache>,
 }
 
 impl GitHubProvider {
@@ -28,16 +41,142 @@ impl GitHubProvider {
         // Support GitHub Enterprise
         let base_url = instance_url.unwrap_or_else(|| "https://api.github.com".to_string());
 
-        Self { client, base_url }
+        Self {
+            client,
+            base_url,
+            cache: Arc::new(ResponseCache::new()),
+            installation_tokens: Arc::new(InstallationTokenCache::new()),
+        }
+    }
+
+    /// Share an ETag cache with other provider instances
+    pub fn with_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Share GitHub App installation tokens with other provider instances
+    pub fn with_installation_tokens(mut self, tokens: Arc<InstallationTokenCache>) -> Self {
+        self.installation_tokens = tokens;
+        self
     }
 
     fn api_url(&self, path: &str) -> String {
         format!("{}{}", self.base_url, path)
     }
 
-    fn auth_header(&self, credentials: &ProviderCredentials) -> String {
-        match credentials {
-            ProviderCredentials::Pat { token, .. } => format!("Bearer {}", token),
+    async fn auth_header(&self, credentials: &ProviderCredentials) -> ProviderResult<String> {
+        Ok(format!("Bearer {}", self.access_token(credentials).await?))
+    }
+
+    /// Authenticated GET for an absolute URL
+    async fn get(
+        &self,
+        credentials: &ProviderCredentials,
+        url: &str,
+    ) -> ProviderResult<RequestBuilder> {
+        Ok(self.get_with(&self.auth_header(credentials).await?, url))
+    }
+
+    /// GET with an already resolved `Authorization` header (pagination `Link`
+    /// targets are absolute)
+    fn get_with(&self, auth_header: &str, url: &str) -> RequestBuilder {
+        self.client
+            .get(url)
+            .header("Authorization", auth_header)
+            .header("Accept", "application/vnd.github+json")
+    }
+
+    /// Fetch every page of a list endpoint by following `Link: rel="next"`
+    async fn get_all<P, T>(
+        &self,
+        credentials: &ProviderCredentials,
+        first: RequestBuilder,
+        error_message: &str,
+        items: impl Fn(P) -> Vec<T>,
+    ) -> ProviderResult<Vec<T>>
+    where
+        P: serde::de::DeserializeOwned,
+    {
+        let auth_header = self.auth_header(credentials).await?;
+        http::collect_pages(
+            &self.client,
+            &self.cache,
+            first,
+            error_message,
+            |page| Ok((items(page.json()?), page.next_link())),
+            |url| self.get_with(&auth_header, url),
+        )
+        .await
+    }
+
+    /// The hunks of each file a pull request changes, keyed by path
+    async fn pull_request_hunks(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> ProviderResult<HashMap<String, Vec<DiffHunk>>> {
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repos/{}/{}/pulls/{}/files",
+                    owner, repo, pr_number
+                )),
+            )
+            .await?
+            .query(&[("per_page", "100")]);
+
+        let files: Vec<GitHubPullRequestFile> = self
+            .get_all(
+                credentials,
+                first,
+                "Failed to list pull request files",
+                |page| page,
+            )
+            .await?;
+        Ok(files
+            .into_iter()
+            .map(|f| {
+                (
+                    f.filename,
+                    diff_hunks(f.patch.as_deref().unwrap_or_default()),
+                )
+            })
+            .collect())
+    }
+
+    /// The account a GitHub App is installed on, read with the app JWT
+    async fn get_installation(
+        &self,
+        app_id: i64,
+        private_key: &str,
+        installation_id: i64,
+    ) -> ProviderResult<GitHubInstallation> {
+        let jwt = github_app::app_jwt(app_id, private_key, Utc::now())?;
+        let response = self
+            .client
+            .get(self.api_url(&format!("/app/installations/{}", installation_id)))
+            .header("Authorization", format!("Bearer {}", jwt))
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            401 => Err(ProviderError::AuthenticationFailed(
+                "GitHub App credentials were rejected".to_string(),
+            )),
+            404 => Err(ProviderError::NotFound(format!(
+                "GitHub App installation {} not found",
+                installation_id
+            ))),
+            status if !response.status().is_success() => Err(ProviderError::ApiError {
+                status_code: status,
+                message: "Failed to get app installation".to_string(),
+            }),
+            _ => Ok(response.json().await?),
         }
     }
 }
@@ -50,6 +189,16 @@ struct GitHubUser {
     avatar_url: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GitHubInstallation {
+    account: GitHubUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubInstallationRepos {
+    repositories: Vec<GitHubRepo>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GitHubRepo {
     id: i64,
@@ -91,12 +240,31 @@ struct GitHubPR {
     updated_at: String,
     merged_at: Option<String>,
     closed_at: Option<String>,
+    #[serde(default)]
+    requested_reviewers: Vec<GitHubPRUser>,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    assignees: Vec<GitHubPRUser>,
+    milestone: Option<GitHubMilestone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubMilestone {
+    title: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct GitHubPRBranch {
     #[serde(rename = "ref")]
     branch_ref: String,
+    #[serde(default)]
+    sha: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -127,6 +295,8 @@ struct GitHubReview {
     state: String,
     body: Option<String>,
     submitted_at: Option<String>,
+    #[serde(default)]
+    commit_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -187,6 +357,7 @@ fn github_pr_to_provider(pr: GitHubPR) -> ProviderPullRequest {
         state: pr.state,
         source_branch: pr.head.branch_ref,
         target_branch: pr.base.branch_ref,
+        head_sha: pr.head.sha,
         author: pr.user.login,
         author_avatar_url: pr.user.avatar_url,
         is_draft: pr.draft.unwrap_or(false),
@@ -201,6 +372,103 @@ fn github_pr_to_provider(pr: GitHubPR) -> ProviderPullRequest {
         updated_at: parse_datetime(&pr.updated_at),
         merged_at: parse_datetime_opt(&pr.merged_at),
         closed_at: parse_datetime_opt(&pr.closed_at),
+        requested_reviewers: pr
+            .requested_reviewers
+            .into_iter()
+            .map(|user| user.login)
+            .collect(),
+        labels: pr.labels.into_iter().map(|label| label.name).collect(),
+        assignees: pr.assignees.into_iter().map(|user| user.login).collect(),
+        milestone: pr.milestone.map(|milestone| milestone.title),
+    }
+}
+
+// --- Inbound webhooks ---
+
+#[derive(Debug, Deserialize)]
+struct GitHubPullRequestEvent {
+    pull_request: GitHubPR,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCheckRunEvent {
+    check_run: GitHubWebhookCheckRun,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubWebhookCheckRun {
+    #[serde(flatten)]
+    run: GitHubCheckRun,
+    #[serde(default)]
+    pull_requests: Vec<GitHubPRNumber>,
+    check_suite: Option<GitHubCheckSuite>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCheckSuite {
+    head_branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubPRNumber {
+    number: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubReviewEvent {
+    review: GitHubReview,
+    pull_request: GitHubPRNumber,
+}
+
+/// Normalise a GitHub webhook delivery.
+///
+/// `event` is the `X-GitHub-Event` header. Supported events are
+/// `pull_request`, `check_run` and `pull_request_review`; anything else
+/// (including `ping`) is [`WebhookEvent::Ignored`].
+pub fn parse_webhook(event: &str, body: &[u8]) -> ProviderResult<WebhookEvent> {
+    match event {
+        "pull_request" => {
+            let payload: GitHubPullRequestEvent = serde_json::from_slice(body)?;
+            Ok(WebhookEvent::PullRequest(Box::new(github_pr_to_provider(
+                payload.pull_request,
+            ))))
+        }
+        "check_run" => {
+            let payload: GitHubCheckRunEvent = serde_json::from_slice(body)?;
+            let c = payload.check_run;
+            Ok(WebhookEvent::Checks {
+                pr_numbers: c.pull_requests.iter().map(|p| p.number).collect(),
+                source_branch: c.check_suite.and_then(|s| s.head_branch),
+                checks: vec![ProviderCICheck {
+                    name: c.run.name,
+                    status: c.run.status,
+                    conclusion: c.run.conclusion,
+                    url: c.run.html_url,
+                    started_at: parse_datetime_opt(&c.run.started_at),
+                    completed_at: parse_datetime_opt(&c.run.completed_at),
+                }],
+            })
+        }
+        "pull_request_review" => {
+            let payload: GitHubReviewEvent = serde_json::from_slice(body)?;
+            let r = payload.review;
+            match r.submitted_at {
+                Some(submitted_at) => Ok(WebhookEvent::Review {
+                    pr_number: payload.pull_request.number,
+                    review: ProviderReview {
+                        id: r.id.to_string(),
+                        reviewer: r.user.login,
+                        reviewer_avatar_url: r.user.avatar_url,
+                        state: r.state.to_lowercase(),
+                        commit_sha: r.commit_id,
+                        body: r.body,
+                        submitted_at: parse_datetime(&submitted_at),
+                    },
+                }),
+                None => Ok(WebhookEvent::Ignored("pending review".to_string())),
+            }
+        }
+        other => Ok(WebhookEvent::Ignored(format!("event '{}'", other))),
     }
 }
 
@@ -257,6 +525,37 @@ struct GitHubCommentResponse {
     id: i64,
 }
 
+#[derive(Debug, Deserialize)]
+struct GitHubIssueComment {
+    body: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GitHubCreateReview<'a> {
+    event: &'static str,
+    body: &'a str,
+    comments: Vec<GitHubReviewComment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubPullRequestFile {
+    filename: String,
+    /// Absent for binary files and diffs too large to show.
+    patch: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GitHubReviewComment {
+    path: String,
+    line: u32,
+    side: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_side: Option<&'static str>,
+    body: String,
+}
+
 #[derive(Debug, Serialize)]
 struct GitHubAddLabels<'a> {
     labels: &'a [String],
@@ -280,10 +579,49 @@ impl GitProvider for GitHubProvider {
         &self,
         credentials: &ProviderCredentials,
     ) -> ProviderResult<TokenValidation> {
+        // Installation tokens cannot call `/user`; the installation names the account
+        if let ProviderCredentials::GitHubApp {
+            app_id,
+            private_key,
+            installation_id,
+        } = credentials
+        {
+            let installation = self
+                .get_installation(*app_id, private_key, *installation_id)
+                .await;
+            return match installation {
+                Ok(installation) => Ok(TokenValidation {
+                    is_valid: true,
+                    user_id: Some(installation.account.id.to_string()),
+                    username: Some(installation.account.login),
+                    email: installation.account.email,
+                    avatar_url: installation.account.avatar_url,
+                    scopes: vec![],
+                    expires_at: None,
+                    error_message: None,
+                }),
+                Err(
+                    e @ (ProviderError::AuthenticationFailed(_)
+                    | ProviderError::NotFound(_)
+                    | ProviderError::ConfigError(_)),
+                ) => Ok(TokenValidation {
+                    is_valid: false,
+                    user_id: None,
+                    username: None,
+                    email: None,
+                    avatar_url: None,
+                    scopes: vec![],
+                    expires_at: None,
+                    error_message: Some(e.to_string()),
+                }),
+                Err(e) => Err(e),
+            };
+        }
+
         let response = self
             .client
             .get(self.api_url("/user"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -333,10 +671,28 @@ impl GitProvider for GitHubProvider {
     }
 
     async fn get_user(&self, credentials: &ProviderCredentials) -> ProviderResult<ProviderUser> {
+        if let ProviderCredentials::GitHubApp {
+            app_id,
+            private_key,
+            installation_id,
+        } = credentials
+        {
+            let account = self
+                .get_installation(*app_id, private_key, *installation_id)
+                .await?
+                .account;
+            return Ok(ProviderUser {
+                id: account.id.to_string(),
+                username: account.login,
+                email: account.email,
+                avatar_url: account.avatar_url,
+            });
+        }
+
         let response = self
             .client
             .get(self.api_url("/user"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -370,19 +726,23 @@ impl GitProvider for GitHubProvider {
         page: i32,
         per_page: i32,
     ) -> ProviderResult<Vec<DiscoveredRepository>> {
-        let response = self
-            .client
-            .get(self.api_url("/user/repos"))
-            .header("Authorization", self.auth_header(credentials))
-            .header("Accept", "application/vnd.github+json")
+        let is_app = matches!(credentials, ProviderCredentials::GitHubApp { .. });
+        // An installation sees exactly the repositories it was granted
+        let request = if is_app {
+            self.get(credentials, &self.api_url("/installation/repositories"))
+                .await?
+        } else {
+            self.get(credentials, &self.api_url("/user/repos"))
+                .await?
+                .query(&[
+                    ("affiliation", "owner,collaborator,organization_member"),
+                    ("sort", "updated"),
+                ])
+        };
+        let response = request
             .query(&[
-                (
-                    "affiliation",
-                    "owner,collaborator,organization_member".to_string(),
-                ),
                 ("page", page.to_string()),
                 ("per_page", per_page.to_string()),
-                ("sort", "updated".to_string()),
             ])
             .send()
             .await?;
@@ -394,7 +754,14 @@ impl GitProvider for GitHubProvider {
             });
         }
 
-        let repos: Vec<GitHubRepo> = response.json().await?;
+        let repos: Vec<GitHubRepo> = if is_app {
+            response
+                .json::<GitHubInstallationRepos>()
+                .await?
+                .repositories
+        } else {
+            response.json().await?
+        };
 
         Ok(repos
             .into_iter()
@@ -422,7 +789,7 @@ impl GitProvider for GitHubProvider {
         let response = self
             .client
             .get(self.api_url(&format!("/repos/{}/{}", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -465,51 +832,21 @@ impl GitProvider for GitHubProvider {
         state: Option<&str>,
     ) -> ProviderResult<Vec<ProviderPullRequest>> {
         let state = state.unwrap_or("open");
-        let response = self
-            .client
-            .get(self.api_url(&format!("/repos/{}/{}/pulls", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
-            .header("Accept", "application/vnd.github+json")
-            .query(&[("state", state), ("per_page", "100")])
-            .send()
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!("/repos/{}/{}/pulls", owner, repo)),
+            )
+            .await?
+            .query(&[("state", state), ("per_page", "100")]);
+
+        let prs: Vec<GitHubPR> = self
+            .get_all(credentials, first, "Failed to list pull requests", |page| {
+                page
+            })
             .await?;
 
-        if !response.status().is_success() {
-            return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
-                message: "Failed to list pull requests".to_string(),
-            });
-        }
-
-        let prs: Vec<GitHubPR> = response.json().await?;
-
-        Ok(prs
-            .into_iter()
-            .map(|pr| ProviderPullRequest {
-                provider_id: pr.id.to_string(),
-                number: pr.number,
-                title: pr.title,
-                description: pr.body,
-                url: pr.html_url,
-                state: pr.state,
-                source_branch: pr.head.branch_ref,
-                target_branch: pr.base.branch_ref,
-                author: pr.user.login,
-                author_avatar_url: pr.user.avatar_url,
-                is_draft: pr.draft.unwrap_or(false),
-                is_mergeable: pr.mergeable,
-                has_conflicts: pr.mergeable_state.as_deref() == Some("dirty"),
-                additions: pr.additions.unwrap_or(0),
-                deletions: pr.deletions.unwrap_or(0),
-                changed_files: pr.changed_files.unwrap_or(0),
-                commits_count: pr.commits.unwrap_or(0),
-                comments_count: pr.comments.unwrap_or(0),
-                created_at: parse_datetime(&pr.created_at),
-                updated_at: parse_datetime(&pr.updated_at),
-                merged_at: parse_datetime_opt(&pr.merged_at),
-                closed_at: parse_datetime_opt(&pr.closed_at),
-            })
-            .collect())
+        Ok(prs.into_iter().map(github_pr_to_provider).collect())
     }
 
     async fn get_pull_request(
@@ -519,54 +856,34 @@ impl GitProvider for GitHubProvider {
         repo: &str,
         number: i32,
     ) -> ProviderResult<ProviderPullRequest> {
-        let response = self
-            .client
-            .get(self.api_url(&format!("/repos/{}/{}/pulls/{}", owner, repo, number)))
-            .header("Authorization", self.auth_header(credentials))
-            .header("Accept", "application/vnd.github+json")
-            .send()
-            .await?;
-
-        if response.status() == 404 {
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!("/repos/{}/{}/pulls/{}", owner, repo, number)),
+            )
+            .await?,
+        )
+        .await?;
+
+        if response.status == 404 {
             return Err(ProviderError::NotFound(format!(
                 "Pull request #{} not found",
                 number
             )));
         }
 
-        if !response.status().is_success() {
+        if !response.status.is_success() {
             return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
+                status_code: response.status.as_u16(),
                 message: "Failed to get pull request".to_string(),
             });
         }
 
-        let pr: GitHubPR = response.json().await?;
+        let pr: GitHubPR = response.json()?;
 
-        Ok(ProviderPullRequest {
-            provider_id: pr.id.to_string(),
-            number: pr.number,
-            title: pr.title,
-            description: pr.body,
-            url: pr.html_url,
-            state: pr.state,
-            source_branch: pr.head.branch_ref,
-            target_branch: pr.base.branch_ref,
-            author: pr.user.login,
-            author_avatar_url: pr.user.avatar_url,
-            is_draft: pr.draft.unwrap_or(false),
-            is_mergeable: pr.mergeable,
-            has_conflicts: pr.mergeable_state.as_deref() == Some("dirty"),
-            additions: pr.additions.unwrap_or(0),
-            deletions: pr.deletions.unwrap_or(0),
-            changed_files: pr.changed_files.unwrap_or(0),
-            commits_count: pr.commits.unwrap_or(0),
-            comments_count: pr.comments.unwrap_or(0),
-            created_at: parse_datetime(&pr.created_at),
-            updated_at: parse_datetime(&pr.updated_at),
-            merged_at: parse_datetime_opt(&pr.merged_at),
-            closed_at: parse_datetime_opt(&pr.closed_at),
-        })
+        Ok(github_pr_to_provider(pr))
     }
 
     async fn get_ci_checks(
@@ -581,26 +898,33 @@ impl GitProvider for GitHubProvider {
             .get_pull_request(credentials, owner, repo, pr_number)
             .await?;
 
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/repos/{}/{}/commits/{}/check-runs",
-                owner, repo, pr.source_branch
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .header("Accept", "application/vnd.github+json")
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repos/{}/{}/commits/{}/check-runs",
+                    owner, repo, pr.source_branch
+                )),
+            )
+            .await?
+            .query(&[("per_page", "100")]);
+
+        let checks = match self
+            .get_all(
+                credentials,
+                first,
+                "Failed to get check runs",
+                |page: GitHubCheckRunsResponse| page.check_runs,
+            )
+            .await
+        {
+            Ok(checks) => checks,
             // If check runs fail, return empty list (might not have any checks)
-            return Ok(vec![]);
-        }
-
-        let checks: GitHubCheckRunsResponse = response.json().await?;
+            Err(ProviderError::ApiError { .. }) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
 
         Ok(checks
-            .check_runs
             .into_iter()
             .map(|c| ProviderCICheck {
                 name: c.name,
@@ -620,26 +944,21 @@ impl GitProvider for GitHubProvider {
         repo: &str,
         pr_number: i32,
     ) -> ProviderResult<Vec<ProviderReview>> {
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/repos/{}/{}/pulls/{}/reviews",
-                owner, repo, pr_number
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .header("Accept", "application/vnd.github+json")
-            .send()
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repos/{}/{}/pulls/{}/reviews",
+                    owner, repo, pr_number
+                )),
+            )
+            .await?
+            .query(&[("per_page", "100")]);
+
+        let reviews: Vec<GitHubReview> = self
+            .get_all(credentials, first, "Failed to get reviews", |page| page)
             .await?;
 
-        if !response.status().is_success() {
-            return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
-                message: "Failed to get reviews".to_string(),
-            });
-        }
-
-        let reviews: Vec<GitHubReview> = response.json().await?;
-
         Ok(reviews
             .into_iter()
             .filter_map(|r| {
@@ -648,6 +967,7 @@ impl GitProvider for GitHubProvider {
                     reviewer: r.user.login,
                     reviewer_avatar_url: r.user.avatar_url,
                     state: r.state.to_lowercase(),
+                    commit_sha: r.commit_id,
                     body: r.body,
                     submitted_at: parse_datetime(&submitted_at),
                 })
@@ -681,7 +1001,7 @@ impl GitProvider for GitHubProvider {
                 "/repos/{}/{}/pulls/{}/merge",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&body)
             .send()
@@ -722,7 +1042,7 @@ impl GitProvider for GitHubProvider {
         let response = self
             .client
             .get(self.api_url("/rate_limit"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -742,6 +1062,28 @@ impl GitProvider for GitHubProvider {
             reset_at: Utc.timestamp_opt(rate.resources.core.reset, 0).unwrap(),
         })
     }
+
+    async fn access_token(&self, credentials: &ProviderCredentials) -> ProviderResult<String> {
+        match credentials {
+            ProviderCredentials::Pat { token, .. } => Ok(token.clone()),
+            ProviderCredentials::OAuth { access_token, .. } => Ok(access_token.clone()),
+            ProviderCredentials::GitHubApp {
+                app_id,
+                private_key,
+                installation_id,
+            } => {
+                self.installation_tokens
+                    .token(
+                        &self.client,
+                        &self.base_url,
+                        *app_id,
+                        private_key,
+                        *installation_id,
+                    )
+                    .await
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -764,7 +1106,7 @@ impl RemediationCapable for GitHubProvider {
                 "/repos/{}/{}/git/ref/heads/{}",
                 owner, repo, repository.default_branch
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -795,7 +1137,7 @@ impl RemediationCapable for GitHubProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/repos/{}/{}/git/refs", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&body)
             .send()
@@ -826,7 +1168,7 @@ impl RemediationCapable for GitHubProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/repos/{}/{}/merges", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&body)
             .send()
@@ -871,7 +1213,7 @@ impl RemediationCapable for GitHubProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/repos/{}/{}/pulls", owner, repo)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&payload)
             .send()
@@ -888,6 +1230,37 @@ impl RemediationCapable for GitHubProvider {
         Ok(github_pr_to_provider(pr))
     }
 
+    async fn find_open_pull_request_by_head(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> ProviderResult<Option<ProviderPullRequest>> {
+        let head = format!("{}:{}", owner, branch);
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!("/repos/{}/{}/pulls", owner, repo)),
+            )
+            .await?
+            .query(&[("state", "open"), ("head", head.as_str())]),
+        )
+        .await?;
+
+        if !response.status.is_success() {
+            return Err(ProviderError::ApiError {
+                status_code: response.status.as_u16(),
+                message: "Failed to find pull request by head branch".to_string(),
+            });
+        }
+
+        let prs: Vec<GitHubPR> = response.json()?;
+        Ok(prs.into_iter().next().map(github_pr_to_provider))
+    }
+
     async fn update_pull_request(
         &self,
         credentials: &ProviderCredentials,
@@ -905,7 +1278,7 @@ impl RemediationCapable for GitHubProvider {
         let response = self
             .client
             .patch(self.api_url(&format!("/repos/{}/{}/pulls/{}", owner, repo, pr_number)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&payload)
             .send()
@@ -935,7 +1308,7 @@ impl RemediationCapable for GitHubProvider {
         let response = self
             .client
             .patch(self.api_url(&format!("/repos/{}/{}/pulls/{}", owner, repo, pr_number)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&payload)
             .send()
@@ -965,7 +1338,7 @@ impl RemediationCapable for GitHubProvider {
                 "/repos/{}/{}/issues/{}/comments",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&payload)
             .send()
@@ -982,6 +1355,104 @@ impl RemediationCapable for GitHubProvider {
         Ok(comment.id)
     }
 
+    async fn list_comments(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> ProviderResult<Vec<String>> {
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/repos/{}/{}/issues/{}/comments",
+                    owner, repo, pr_number
+                )),
+            )
+            .await?
+            .query(&[("per_page", "100")]);
+
+        let comments: Vec<GitHubIssueComment> = self
+            .get_all(credentials, first, "Failed to list comments", |page| page)
+            .await?;
+        Ok(comments.into_iter().filter_map(|c| c.body).collect())
+    }
+
+    async fn create_suggestions(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+        suggestions: &[SuggestedChange],
+    ) -> ProviderResult<()> {
+        // Review comments may only sit on lines the PR's diff shows; GitHub
+        // rejects the whole review if any one of them does not.
+        let files = self
+            .pull_request_hunks(credentials, owner, repo, pr_number)
+            .await?;
+        let (in_diff, outside): (Vec<_>, Vec<_>) = suggestions.iter().partition(|s| {
+            files
+                .get(&s.path)
+                .is_some_and(|hunks| hunks.iter().any(|h| h.covers(s)))
+        });
+
+        // One review carries every suggestion that fits, so the author gets a
+        // single notification and can batch-commit them from the "Files" tab.
+        if !in_diff.is_empty() {
+            let comments = in_diff
+                .iter()
+                .map(|s| {
+                    let multi_line = s.start_line < s.end_line;
+                    GitHubReviewComment {
+                        path: s.path.clone(),
+                        line: s.end_line,
+                        side: "RIGHT",
+                        start_line: multi_line.then_some(s.start_line),
+                        start_side: multi_line.then_some("RIGHT"),
+                        body: s.fenced("suggestion"),
+                    }
+                })
+                .collect();
+            let payload = GitHubCreateReview {
+                event: "COMMENT",
+                body: SUGGESTION_REVIEW_BODY,
+                comments,
+            };
+            let response = self
+                .client
+                .post(self.api_url(&format!(
+                    "/repos/{}/{}/pulls/{}/reviews",
+                    owner, repo, pr_number
+                )))
+                .header("Authorization", self.auth_header(credentials).await?)
+                .header("Accept", "application/vnd.github+json")
+                .json(&payload)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::ApiError {
+                    status_code: response.status().as_u16(),
+                    message: format!("Failed to post suggestions on pull request #{}", pr_number),
+                });
+            }
+        }
+
+        for suggestion in outside {
+            self.create_comment(
+                credentials,
+                owner,
+                repo,
+                pr_number,
+                &suggestion.diff_comment(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn add_labels(
         &self,
         credentials: &ProviderCredentials,
@@ -997,7 +1468,7 @@ impl RemediationCapable for GitHubProvider {
                 "/repos/{}/{}/issues/{}/labels",
                 owner, repo, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .json(&payload)
             .send()
@@ -1025,7 +1496,7 @@ impl RemediationCapable for GitHubProvider {
                 "/repos/{}/{}/commits/{}/check-runs",
                 owner, repo, git_ref
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -1065,7 +1536,7 @@ impl RemediationCapable for GitHubProvider {
                 "/repos/{}/{}/git/refs/heads/{}",
                 owner, repo, branch_name
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials).await?)
             .header("Accept", "application/vnd.github+json")
             .send()
             .await?;
@@ -0,0 +1,159 @@
+//! GitHub App authentication.
+//!
+//! An app authenticates as itself with a short-lived RS256 JWT signed by its
+//! private key, and exchanges that JWT for an installation access token that
+//! is valid for one hour. [`InstallationTokenCache`] keeps minted tokens until
+//! shortly before they expire, so every provider call made for an installation
+//! does not cost an extra round trip.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ProviderError, ProviderResult};
+
+/// Tokens this close to expiry are replaced instead of reused.
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+
+/// Lifetime of an app JWT; GitHub rejects anything over ten minutes.
+const APP_JWT_TTL_SECS: i64 = 540;
+
+/// Back-dating of `iat` to tolerate clock drift, as GitHub recommends.
+const APP_JWT_CLOCK_SKEW_SECS: i64 = 60;
+
+#[derive(Debug, Serialize)]
+struct AppClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstallationTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Sign the JWT a GitHub App uses to authenticate as itself
+pub fn app_jwt(app_id: i64, private_key: &str, now: DateTime<Utc>) -> ProviderResult<String> {
+    let key = EncodingKey::from_rsa_pem(private_key.as_bytes()).map_err(|e| {
+        ProviderError::ConfigError(format!("Invalid GitHub App private key: {}", e))
+    })?;
+    let claims = AppClaims {
+        iat: (now - Duration::seconds(APP_JWT_CLOCK_SKEW_SECS)).timestamp(),
+        exp: (now + Duration::seconds(APP_JWT_TTL_SECS)).timestamp(),
+        iss: app_id.to_string(),
+    };
+
+    jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &key)
+        .map_err(|e| ProviderError::ConfigError(format!("Failed to sign GitHub App JWT: {}", e)))
+}
+
+/// Installation access tokens keyed by API base URL, app and installation.
+#[derive(Debug, Default)]
+pub struct InstallationTokenCache {
+    tokens: Mutex<HashMap<(String, i64, i64), InstallationToken>>,
+}
+
+impl InstallationTokenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A valid installation token, minted through
+    /// `POST /app/installations/{id}/access_tokens` when none is cached.
+    ///
+    /// Concurrent callers may both mint a token on a miss; the last one wins,
+    /// and both tokens stay valid.
+    pub async fn token(
+        &self,
+        client: &Client,
+        base_url: &str,
+        app_id: i64,
+        private_key: &str,
+        installation_id: i64,
+    ) -> ProviderResult<String> {
+        let key = (base_url.to_string(), app_id, installation_id);
+        let now = Utc::now();
+
+        if let Some(cached) = self.tokens.lock().unwrap().get(&key) {
+            if cached.expires_at - now > Duration::seconds(TOKEN_REFRESH_MARGIN_SECS) {
+                return Ok(cached.token.clone());
+            }
+        }
+
+        let jwt = app_jwt(app_id, private_key, now)?;
+        let response = client
+            .post(format!(
+                "{}/app/installations/{}/access_tokens",
+                base_url, installation_id
+            ))
+            .header("Authorization", format!("Bearer {}", jwt))
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?;
+
+        match response.status().as_u16() {
+            401 => {
+                return Err(ProviderError::AuthenticationFailed(
+                    "GitHub App credentials were rejected".to_string(),
+                ))
+            }
+            404 => {
+                return Err(ProviderError::NotFound(format!(
+                    "GitHub App installation {} not found",
+                    installation_id
+                )))
+            }
+            _ => {}
+        }
+
+        if !response.status().is_success() {
+            return Err(ProviderError::ApiError {
+                status_code: response.status().as_u16(),
+                message: "Failed to create installation access token".to_string(),
+            });
+        }
+
+        let minted: InstallationTokenResponse = response.json().await?;
+        self.tokens.lock().unwrap().insert(
+            key,
+            InstallationToken {
+                token: minted.token.clone(),
+                expires_at: minted.expires_at,
+            },
+        );
+
+        Ok(minted.token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_invalid_private_key() {
+        let result = app_jwt(1, "not a key", Utc::now());
+
+        assert!(matches!(result, Err(ProviderError::ConfigError(_))));
+    }
+}
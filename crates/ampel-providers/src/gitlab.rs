@@ -1,21 +1,28 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
-use reqwest::Client;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{ProviderError, ProviderResult};
+use crate::http::{self, ResponseCache};
 use crate::remediation::{RemediationCapable, RemediationCaps};
 use crate::traits::{
-    GitProvider, MergeResult, ProviderCICheck, ProviderCredentials, ProviderPullRequest,
-    ProviderReview, ProviderUser, RateLimitInfo, TokenValidation,
+    GitProvider, MergeResult, OAuthClient, OAuthTokens, ProviderCICheck, ProviderCredentials,
+    ProviderPullRequest, ProviderReview, ProviderUser, RateLimitInfo, TokenValidation,
 };
+use crate::webhook::WebhookEvent;
 use ampel_core::models::{
     DiscoveredRepository, GitProvider as Provider, MergeRequest, MergeStrategy,
 };
+use ampel_core::remediation::{diff_hunks, DiffHunk, SuggestedChange};
 
 pub struct GitLabProvider {
     client: Client,
     base_url: String,
+    cache: Arc<ResponseCache>,
 }
 
 impl GitLabProvider {
@@ -28,18 +35,93 @@ impl GitLabProvider {
         Self {
             client,
             base_url: instance_url.unwrap_or_else(|| "https://gitlab.com".to_string()),
+            cache: Arc::new(ResponseCache::new()),
         }
     }
 
+    /// Share an ETag cache with other provider instances
+    pub fn with_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
     fn api_url(&self, path: &str) -> String {
         format!("{}/api/v4{}", self.base_url, path)
     }
 
-    fn auth_header(&self, credentials: &ProviderCredentials) -> String {
+    fn auth_header(&self, credentials: &ProviderCredentials) -> ProviderResult<String> {
         match credentials {
-            ProviderCredentials::Pat { token, .. } => format!("Bearer {}", token),
+            ProviderCredentials::Pat { token, .. } => Ok(format!("Bearer {}", token)),
+            ProviderCredentials::OAuth { access_token, .. } => {
+                Ok(format!("Bearer {}", access_token))
+            }
+            ProviderCredentials::GitHubApp { .. } => Err(ProviderError::ConfigError(
+                "GitHub App credentials cannot be used with GitLab".to_string(),
+            )),
         }
     }
+
+    /// Authenticated GET for an absolute URL
+    fn get(&self, credentials: &ProviderCredentials, url: &str) -> ProviderResult<RequestBuilder> {
+        Ok(self.get_with(&self.auth_header(credentials)?, url))
+    }
+
+    /// GET with an already resolved `Authorization` header
+    fn get_with(&self, auth_header: &str, url: &str) -> RequestBuilder {
+        self.client.get(url).header("Authorization", auth_header)
+    }
+
+    /// Fetch every page of a list endpoint, following `X-Next-Page` (or the
+    /// `Link` header when keyset pagination is in use)
+    async fn get_all<T>(
+        &self,
+        credentials: &ProviderCredentials,
+        first: RequestBuilder,
+        error_message: &str,
+    ) -> ProviderResult<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let auth_header = self.auth_header(credentials)?;
+        http::collect_pages(
+            &self.client,
+            &self.cache,
+            first,
+            error_message,
+            |page| {
+                let next = page.next_page_url().or_else(|| page.next_link());
+                Ok((page.json()?, next))
+            },
+            |url| self.get_with(&auth_header, url),
+        )
+        .await
+    }
+
+    /// The hunks of each file a merge request changes, keyed by new path
+    async fn merge_request_hunks(
+        &self,
+        credentials: &ProviderCredentials,
+        encoded_path: &str,
+        pr_number: i32,
+    ) -> ProviderResult<HashMap<String, Vec<DiffHunk>>> {
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/projects/{}/merge_requests/{}/diffs",
+                    encoded_path, pr_number
+                )),
+            )?
+            .query(&[("per_page", "100")]);
+
+        let diffs: Vec<GitLabMRDiff> = self
+            .get_all(credentials, first, "Failed to list merge request diffs")
+            .await?;
+        Ok(diffs
+            .into_iter()
+            .map(|d| (d.new_path, diff_hunks(&d.diff)))
+            .collect())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -88,6 +170,19 @@ struct GitLabMR {
     updated_at: String,
     merged_at: Option<String>,
     closed_at: Option<String>,
+    #[serde(default)]
+    reviewers: Vec<GitLabMRAuthor>,
+    sha: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    assignees: Vec<GitLabMRAuthor>,
+    milestone: Option<GitLabMilestone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabMilestone {
+    title: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -146,6 +241,10 @@ struct GitLabMergeRequest {
 fn parse_datetime(s: &str) -> DateTime<Utc> {
     DateTime::parse_from_rfc3339(s)
         .map(|dt| dt.with_timezone(&Utc))
+        // Webhook payloads use the legacy `2024-01-02 03:04:05 UTC` form.
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S UTC").map(|dt| dt.and_utc())
+        })
         .unwrap_or_else(|_| Utc::now())
 }
 
@@ -168,6 +267,7 @@ fn gitlab_mr_to_provider(mr: GitLabMR) -> ProviderPullRequest {
         state: state.to_string(),
         source_branch: mr.source_branch,
         target_branch: mr.target_branch,
+        head_sha: mr.sha,
         author: mr.author.username,
         author_avatar_url: mr.author.avatar_url,
         is_draft: mr.draft,
@@ -182,6 +282,198 @@ fn gitlab_mr_to_provider(mr: GitLabMR) -> ProviderPullRequest {
         updated_at: parse_datetime(&mr.updated_at),
         merged_at: parse_datetime_opt(&mr.merged_at),
         closed_at: parse_datetime_opt(&mr.closed_at),
+        requested_reviewers: mr.reviewers.into_iter().map(|r| r.username).collect(),
+        labels: mr.labels,
+        assignees: mr.assignees.into_iter().map(|a| a.username).collect(),
+        milestone: mr.milestone.map(|m| m.title),
+    }
+}
+
+fn gitlab_job_status(status: &str) -> (String, Option<String>) {
+    match status {
+        "pending" | "created" => ("queued".to_string(), None),
+        "running" => ("in_progress".to_string(), None),
+        "success" => ("completed".to_string(), Some("success".to_string())),
+        "failed" => ("completed".to_string(), Some("failure".to_string())),
+        "canceled" => ("completed".to_string(), Some("cancelled".to_string())),
+        "skipped" => ("completed".to_string(), Some("skipped".to_string())),
+        _ => ("queued".to_string(), None),
+    }
+}
+
+// --- Inbound webhooks ---
+
+#[derive(Debug, Deserialize)]
+struct GitLabHookUser {
+    username: String,
+    avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabMergeRequestHook {
+    user: GitLabHookUser,
+    object_attributes: GitLabMergeRequestHookAttrs,
+    #[serde(default)]
+    reviewers: Vec<GitLabHookUser>,
+    #[serde(default)]
+    assignees: Vec<GitLabHookUser>,
+    #[serde(default)]
+    labels: Vec<GitLabHookLabel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabHookLabel {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabMergeRequestHookAttrs {
+    id: i64,
+    iid: i32,
+    title: String,
+    description: Option<String>,
+    url: String,
+    state: String,
+    source_branch: String,
+    target_branch: String,
+    #[serde(default)]
+    draft: bool,
+    merge_status: Option<String>,
+    detailed_merge_status: Option<String>,
+    created_at: String,
+    updated_at: String,
+    action: Option<String>,
+    last_commit: Option<GitLabHookCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabHookCommit {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabPipelineHook {
+    object_attributes: GitLabPipelineHookAttrs,
+    merge_request: Option<GitLabMrRef>,
+    #[serde(default)]
+    builds: Vec<GitLabPipelineHookBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabPipelineHookAttrs {
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabPipelineHookBuild {
+    name: String,
+    status: String,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+}
+
+/// Normalise a GitLab webhook delivery.
+///
+/// `event` is the `X-Gitlab-Event` header. `Merge Request Hook` deliveries
+/// become PR updates (or a review for `approved`/`unapproved` actions) and
+/// `Pipeline Hook` deliveries become one check per job. MR hooks only name the
+/// actor, so the author is filled in for `open` actions only. They carry the
+/// milestone's id but not its title, so the milestone is left unset.
+pub fn parse_webhook(event: &str, body: &[u8]) -> ProviderResult<WebhookEvent> {
+    match event {
+        "Merge Request Hook" => {
+            let payload: GitLabMergeRequestHook = serde_json::from_slice(body)?;
+            let mr = payload.object_attributes;
+            let action = mr.action.as_deref().unwrap_or_default();
+
+            if matches!(action, "approved" | "unapproved") {
+                return Ok(WebhookEvent::Review {
+                    pr_number: mr.iid,
+                    review: ProviderReview {
+                        id: format!("{}:{}", mr.id, payload.user.username),
+                        reviewer: payload.user.username,
+                        reviewer_avatar_url: payload.user.avatar_url,
+                        state: if action == "approved" {
+                            "approved".to_string()
+                        } else {
+                            "dismissed".to_string()
+                        },
+                        commit_sha: None,
+                        body: None,
+                        submitted_at: parse_datetime(&mr.updated_at),
+                    },
+                });
+            }
+
+            let updated_at = parse_datetime(&mr.updated_at);
+            let state = match mr.state.as_str() {
+                "opened" => "open",
+                other => other,
+            };
+            let (author, author_avatar_url) = if action == "open" {
+                (payload.user.username, payload.user.avatar_url)
+            } else {
+                (String::new(), None)
+            };
+            Ok(WebhookEvent::PullRequest(Box::new(ProviderPullRequest {
+                provider_id: mr.id.to_string(),
+                number: mr.iid,
+                title: mr.title,
+                description: mr.description,
+                url: mr.url,
+                state: state.to_string(),
+                source_branch: mr.source_branch,
+                target_branch: mr.target_branch,
+                head_sha: mr.last_commit.map(|c| c.id),
+                author,
+                author_avatar_url,
+                is_draft: mr.draft,
+                is_mergeable: Some(mr.merge_status.as_deref() == Some("can_be_merged")),
+                has_conflicts: mr.detailed_merge_status.as_deref() == Some("conflict"),
+                additions: 0,
+                deletions: 0,
+                changed_files: 0,
+                commits_count: 0,
+                comments_count: 0,
+                created_at: parse_datetime(&mr.created_at),
+                updated_at,
+                merged_at: (state == "merged").then_some(updated_at),
+                closed_at: (state != "open").then_some(updated_at),
+                requested_reviewers: payload.reviewers.into_iter().map(|r| r.username).collect(),
+                labels: payload.labels.into_iter().map(|l| l.title).collect(),
+                assignees: payload.assignees.into_iter().map(|a| a.username).collect(),
+                milestone: None,
+            })))
+        }
+        "Pipeline Hook" => {
+            let payload: GitLabPipelineHook = serde_json::from_slice(body)?;
+            let Some(mr) = payload.merge_request else {
+                return Ok(WebhookEvent::Ignored(
+                    "pipeline not attached to a merge request".to_string(),
+                ));
+            };
+            Ok(WebhookEvent::Checks {
+                pr_numbers: vec![mr.iid],
+                source_branch: payload.object_attributes.git_ref,
+                checks: payload
+                    .builds
+                    .into_iter()
+                    .map(|b| {
+                        let (status, conclusion) = gitlab_job_status(&b.status);
+                        ProviderCICheck {
+                            name: b.name,
+                            status,
+                            conclusion,
+                            url: None,
+                            started_at: parse_datetime_opt(&b.started_at),
+                            completed_at: parse_datetime_opt(&b.finished_at),
+                        }
+                    })
+                    .collect(),
+            })
+        }
+        other => Ok(WebhookEvent::Ignored(format!("event '{}'", other))),
     }
 }
 
@@ -207,6 +499,44 @@ struct GitLabNoteResponse {
     id: i64,
 }
 
+/// Just the `diff_refs` of a merge request, which anchor a diff discussion.
+#[derive(Debug, Deserialize)]
+struct GitLabMRDiffRefs {
+    diff_refs: Option<GitLabDiffRefs>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitLabDiffRefs {
+    base_sha: String,
+    start_sha: String,
+    head_sha: String,
+}
+
+#[derive(Debug, Serialize)]
+struct GitLabCreateDiscussion<'a> {
+    body: String,
+    position: GitLabPosition<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct GitLabPosition<'a> {
+    position_type: &'static str,
+    #[serde(flatten)]
+    refs: &'a GitLabDiffRefs,
+    old_path: &'a str,
+    new_path: &'a str,
+    /// Set for an unchanged line; GitLab needs both sides to place it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_line: Option<u32>,
+    new_line: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabMRDiff {
+    new_path: String,
+    diff: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct GitLabCommitStatus {
     name: Option<String>,
@@ -237,7 +567,7 @@ impl GitProvider for GitLabProvider {
         let response = self
             .client
             .get(self.api_url("/user"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -285,7 +615,7 @@ impl GitProvider for GitLabProvider {
         let response = self
             .client
             .get(self.api_url("/user"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -321,7 +651,7 @@ impl GitProvider for GitLabProvider {
         let response = self
             .client
             .get(self.api_url("/projects"))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[
                 ("membership", "true"),
                 ("page", &page.to_string()),
@@ -381,7 +711,7 @@ impl GitProvider for GitLabProvider {
         let response = self
             .client
             .get(self.api_url(&format!("/projects/{}", encoded_path)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -432,58 +762,18 @@ impl GitProvider for GitLabProvider {
             _ => state,
         };
 
-        let response = self
-            .client
-            .get(self.api_url(&format!("/projects/{}/merge_requests", encoded_path)))
-            .header("Authorization", self.auth_header(credentials))
-            .query(&[("state", gitlab_state), ("per_page", "100")])
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
-                message: "Failed to list merge requests".to_string(),
-            });
-        }
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!("/projects/{}/merge_requests", encoded_path)),
+            )?
+            .query(&[("state", gitlab_state), ("per_page", "100")]);
 
-        let mrs: Vec<GitLabMR> = response.json().await?;
+        let mrs: Vec<GitLabMR> = self
+            .get_all(credentials, first, "Failed to list merge requests")
+            .await?;
 
-        Ok(mrs
-            .into_iter()
-            .map(|mr| {
-                let state = match mr.state.as_str() {
-                    "opened" => "open",
-                    _ => &mr.state,
-                };
-                let changes: i32 = mr.changes_count.and_then(|c| c.parse().ok()).unwrap_or(0);
-
-                ProviderPullRequest {
-                    provider_id: mr.id.to_string(),
-                    number: mr.iid,
-                    title: mr.title,
-                    description: mr.description,
-                    url: mr.web_url,
-                    state: state.to_string(),
-                    source_branch: mr.source_branch,
-                    target_branch: mr.target_branch,
-                    author: mr.author.username,
-                    author_avatar_url: mr.author.avatar_url,
-                    is_draft: mr.draft,
-                    is_mergeable: Some(mr.merge_status.as_deref() == Some("can_be_merged")),
-                    has_conflicts: mr.has_conflicts,
-                    additions: 0,
-                    deletions: 0,
-                    changed_files: changes,
-                    commits_count: 0,
-                    comments_count: mr.user_notes_count.unwrap_or(0),
-                    created_at: parse_datetime(&mr.created_at),
-                    updated_at: parse_datetime(&mr.updated_at),
-                    merged_at: parse_datetime_opt(&mr.merged_at),
-                    closed_at: parse_datetime_opt(&mr.closed_at),
-                }
-            })
-            .collect())
+        Ok(mrs.into_iter().map(gitlab_mr_to_provider).collect())
     }
 
     async fn get_pull_request(
@@ -496,61 +786,36 @@ impl GitProvider for GitLabProvider {
         let project_path = format!("{}/{}", owner, repo);
         let encoded_path = urlencoding::encode(&project_path);
 
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/projects/{}/merge_requests/{}",
-                encoded_path, number
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .send()
-            .await?;
-
-        if response.status() == 404 {
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!(
+                    "/projects/{}/merge_requests/{}",
+                    encoded_path, number
+                )),
+            )?,
+        )
+        .await?;
+
+        if response.status == 404 {
             return Err(ProviderError::NotFound(format!(
                 "Merge request !{} not found",
                 number
             )));
         }
 
-        if !response.status().is_success() {
+        if !response.status.is_success() {
             return Err(ProviderError::ApiError {
-                status_code: response.status().as_u16(),
+                status_code: response.status.as_u16(),
                 message: "Failed to get merge request".to_string(),
             });
         }
 
-        let mr: GitLabMR = response.json().await?;
-        let state = match mr.state.as_str() {
-            "opened" => "open",
-            _ => &mr.state,
-        };
-        let changes: i32 = mr.changes_count.and_then(|c| c.parse().ok()).unwrap_or(0);
-
-        Ok(ProviderPullRequest {
-            provider_id: mr.id.to_string(),
-            number: mr.iid,
-            title: mr.title,
-            description: mr.description,
-            url: mr.web_url,
-            state: state.to_string(),
-            source_branch: mr.source_branch,
-            target_branch: mr.target_branch,
-            author: mr.author.username,
-            author_avatar_url: mr.author.avatar_url,
-            is_draft: mr.draft,
-            is_mergeable: Some(mr.merge_status.as_deref() == Some("can_be_merged")),
-            has_conflicts: mr.has_conflicts,
-            additions: 0,
-            deletions: 0,
-            changed_files: changes,
-            commits_count: 0,
-            comments_count: mr.user_notes_count.unwrap_or(0),
-            created_at: parse_datetime(&mr.created_at),
-            updated_at: parse_datetime(&mr.updated_at),
-            merged_at: parse_datetime_opt(&mr.merged_at),
-            closed_at: parse_datetime_opt(&mr.closed_at),
-        })
+        let mr: GitLabMR = response.json()?;
+
+        Ok(gitlab_mr_to_provider(mr))
     }
 
     async fn get_ci_checks(
@@ -563,22 +828,25 @@ impl GitProvider for GitLabProvider {
         let project_path = format!("{}/{}", owner, repo);
         let encoded_path = urlencoding::encode(&project_path);
 
-        // Get pipelines for the MR
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/projects/{}/merge_requests/{}/pipelines",
-                encoded_path, pr_number
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
+        // Get pipelines for the MR (newest first, only the latest is used)
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!(
+                    "/projects/{}/merge_requests/{}/pipelines",
+                    encoded_path, pr_number
+                )),
+            )?,
+        )
+        .await?;
+
+        if !response.status.is_success() {
             return Ok(vec![]);
         }
 
-        let pipelines: Vec<GitLabPipeline> = response.json().await?;
+        let pipelines: Vec<GitLabPipeline> = response.json()?;
 
         if pipelines.is_empty() {
             return Ok(vec![]);
@@ -586,34 +854,29 @@ impl GitProvider for GitLabProvider {
 
         // Get jobs for the latest pipeline
         let pipeline_id = pipelines[0].id;
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/projects/{}/pipelines/{}/jobs",
-                encoded_path, pipeline_id
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Ok(vec![]);
-        }
-
-        let jobs: Vec<GitLabJob> = response.json().await?;
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/projects/{}/pipelines/{}/jobs",
+                    encoded_path, pipeline_id
+                )),
+            )?
+            .query(&[("per_page", "100")]);
+
+        let jobs: Vec<GitLabJob> = match self
+            .get_all(credentials, first, "Failed to get pipeline jobs")
+            .await
+        {
+            Ok(jobs) => jobs,
+            Err(ProviderError::ApiError { .. }) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
 
         Ok(jobs
             .into_iter()
             .map(|j| {
-                let (status, conclusion) = match j.status.as_str() {
-                    "pending" | "created" => ("queued".to_string(), None),
-                    "running" => ("in_progress".to_string(), None),
-                    "success" => ("completed".to_string(), Some("success".to_string())),
-                    "failed" => ("completed".to_string(), Some("failure".to_string())),
-                    "canceled" => ("completed".to_string(), Some("cancelled".to_string())),
-                    "skipped" => ("completed".to_string(), Some("skipped".to_string())),
-                    _ => ("queued".to_string(), None),
-                };
+                let (status, conclusion) = gitlab_job_status(&j.status);
 
                 ProviderCICheck {
                     name: j.name,
@@ -638,21 +901,24 @@ impl GitProvider for GitLabProvider {
         let encoded_path = urlencoding::encode(&project_path);
 
         // Get approvals
-        let response = self
-            .client
-            .get(self.api_url(&format!(
-                "/projects/{}/merge_requests/{}/approvals",
-                encoded_path, pr_number
-            )))
-            .header("Authorization", self.auth_header(credentials))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!(
+                    "/projects/{}/merge_requests/{}/approvals",
+                    encoded_path, pr_number
+                )),
+            )?,
+        )
+        .await?;
+
+        if !response.status.is_success() {
             return Ok(vec![]);
         }
 
-        let approvals: GitLabApproval = response.json().await?;
+        let approvals: GitLabApproval = response.json()?;
 
         Ok(approvals
             .approved_by
@@ -662,6 +928,7 @@ impl GitProvider for GitLabProvider {
                 reviewer: a.user.username,
                 reviewer_avatar_url: a.user.avatar_url,
                 state: "approved".to_string(),
+                commit_sha: None,
                 body: None,
                 submitted_at: Utc::now(), // GitLab doesn't provide approval time in this endpoint
             })
@@ -692,7 +959,7 @@ impl GitProvider for GitLabProvider {
                 "/projects/{}/merge_requests/{}/merge",
                 encoded_path, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .json(&body)
             .send()
             .await?;
@@ -729,6 +996,24 @@ impl GitProvider for GitLabProvider {
             reset_at: Utc::now() + chrono::Duration::hours(1),
         })
     }
+
+    async fn refresh_oauth_token(
+        &self,
+        client: &OAuthClient,
+        refresh_token: &str,
+    ) -> ProviderResult<OAuthTokens> {
+        let request = self
+            .client
+            .post(format!("{}/oauth/token", self.base_url))
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+                ("client_id", &client.client_id),
+                ("client_secret", &client.client_secret),
+            ]);
+
+        http::refresh_oauth_token(request).await
+    }
 }
 
 #[async_trait]
@@ -755,7 +1040,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/repository/branches/{}",
                 encoded_path, encoded_branch
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -783,7 +1068,7 @@ impl RemediationCapable for GitLabProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/projects/{}/repository/branches", encoded_path)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[("branch", branch_name), ("ref", from_sha)])
             .send()
             .await?;
@@ -813,7 +1098,7 @@ impl RemediationCapable for GitLabProvider {
         let lookup = self
             .client
             .get(self.api_url(&format!("/projects/{}/merge_requests", encoded_path)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[("source_branch", branch_name), ("state", "opened")])
             .send()
             .await?;
@@ -839,7 +1124,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/merge_requests/{}/rebase",
                 encoded_path, iid
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -867,7 +1152,7 @@ impl RemediationCapable for GitLabProvider {
         let response = self
             .client
             .post(self.api_url(&format!("/projects/{}/merge_requests", encoded_path)))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[
                 ("source_branch", head),
                 ("target_branch", base),
@@ -888,6 +1173,37 @@ impl RemediationCapable for GitLabProvider {
         Ok(gitlab_mr_to_provider(mr))
     }
 
+    async fn find_open_pull_request_by_head(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> ProviderResult<Option<ProviderPullRequest>> {
+        let project_path = format!("{}/{}", owner, repo);
+        let encoded_path = urlencoding::encode(&project_path);
+        let response = http::fetch(
+            &self.client,
+            &self.cache,
+            self.get(
+                credentials,
+                &self.api_url(&format!("/projects/{}/merge_requests", encoded_path)),
+            )?
+            .query(&[("state", "opened"), ("source_branch", branch)]),
+        )
+        .await?;
+
+        if !response.status.is_success() {
+            return Err(ProviderError::ApiError {
+                status_code: response.status.as_u16(),
+                message: "Failed to find merge request by source branch".to_string(),
+            });
+        }
+
+        let mrs: Vec<GitLabMR> = response.json()?;
+        Ok(mrs.into_iter().next().map(gitlab_mr_to_provider))
+    }
+
     async fn update_pull_request(
         &self,
         credentials: &ProviderCredentials,
@@ -912,7 +1228,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/merge_requests/{}",
                 encoded_path, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&query)
             .send()
             .await?;
@@ -941,7 +1257,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/merge_requests/{}",
                 encoded_path, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[("state_event", "close")])
             .send()
             .await?;
@@ -971,7 +1287,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/merge_requests/{}/notes",
                 encoded_path, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[("body", body)])
             .send()
             .await?;
@@ -987,6 +1303,125 @@ impl RemediationCapable for GitLabProvider {
         Ok(note.id)
     }
 
+    async fn list_comments(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> ProviderResult<Vec<String>> {
+        let project_path = format!("{}/{}", owner, repo);
+        let encoded_path = urlencoding::encode(&project_path);
+        let first = self
+            .get(
+                credentials,
+                &self.api_url(&format!(
+                    "/projects/{}/merge_requests/{}/notes",
+                    encoded_path, pr_number
+                )),
+            )?
+            .query(&[("sort", "asc"), ("per_page", "100")]);
+
+        let notes: Vec<GitLabNote> = self
+            .get_all(credentials, first, "Failed to list merge request notes")
+            .await?;
+        Ok(notes.into_iter().map(|n| n.body).collect())
+    }
+
+    async fn create_suggestions(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+        suggestions: &[SuggestedChange],
+    ) -> ProviderResult<()> {
+        let project_path = format!("{}/{}", owner, repo);
+        let encoded_path = urlencoding::encode(&project_path);
+        let mr_url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            encoded_path, pr_number
+        ));
+
+        // Diff discussions are positioned against the MR's current diff refs.
+        let response = self
+            .client
+            .get(&mr_url)
+            .header("Authorization", self.auth_header(credentials)?)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(ProviderError::ApiError {
+                status_code: response.status().as_u16(),
+                message: format!("Failed to load merge request !{}", pr_number),
+            });
+        }
+        let refs = response
+            .json::<GitLabMRDiffRefs>()
+            .await?
+            .diff_refs
+            .ok_or_else(|| {
+                ProviderError::InvalidResponse(format!(
+                    "merge request !{} has no diff refs yet",
+                    pr_number
+                ))
+            })?;
+
+        // Discussions may only sit on lines the MR's diff shows; anything
+        // else becomes a note carrying the change as a diff.
+        let files = self
+            .merge_request_hunks(credentials, &encoded_path, pr_number)
+            .await?;
+
+        // GitLab anchors a suggestion on its last line and reaches back
+        // `-N` lines, so each one is its own discussion on `end_line`.
+        for suggestion in suggestions {
+            let Some(hunk) = files
+                .get(&suggestion.path)
+                .and_then(|hunks| hunks.iter().find(|h| h.covers(suggestion)))
+            else {
+                self.create_comment(
+                    credentials,
+                    owner,
+                    repo,
+                    pr_number,
+                    &suggestion.diff_comment(),
+                )
+                .await?;
+                continue;
+            };
+            let info = format!("suggestion:-{}+0", suggestion.line_count() - 1);
+            let payload = GitLabCreateDiscussion {
+                body: suggestion.fenced(&info),
+                position: GitLabPosition {
+                    position_type: "text",
+                    refs: &refs,
+                    old_path: &suggestion.path,
+                    new_path: &suggestion.path,
+                    old_line: hunk.old_line(suggestion.end_line),
+                    new_line: suggestion.end_line,
+                },
+            };
+            let response = self
+                .client
+                .post(format!("{}/discussions", mr_url))
+                .header("Authorization", self.auth_header(credentials)?)
+                .json(&payload)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(ProviderError::ApiError {
+                    status_code: response.status().as_u16(),
+                    message: format!(
+                        "Failed to post suggestion on {} in merge request !{}",
+                        suggestion.path, pr_number
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
     async fn add_labels(
         &self,
         credentials: &ProviderCredentials,
@@ -1004,7 +1439,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/merge_requests/{}",
                 encoded_path, pr_number
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .query(&[("add_labels", add_labels.as_str())])
             .send()
             .await?;
@@ -1034,7 +1469,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/repository/commits/{}/statuses",
                 encoded_path, encoded_ref
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -1086,7 +1521,7 @@ impl RemediationCapable for GitLabProvider {
                 "/projects/{}/repository/branches/{}",
                 encoded_path, encoded_branch
             )))
-            .header("Authorization", self.auth_header(credentials))
+            .header("Authorization", self.auth_header(credentials)?)
             .send()
             .await?;
 
@@ -0,0 +1,473 @@
+//! HTTP plumbing shared by the provider clients: conditional requests and
+//! pagination.
+//!
+//! GET reads go through [`fetch`], which remembers the `ETag` of every
+//! successful response in a [`ResponseCache`] and replays it as
+//! `If-None-Match`. A `304 Not Modified` answer is served from the cache, so
+//! unchanged resources cost no rate limit on providers that honour it.
+//!
+//! List calls go through [`collect_pages`], which keeps requesting the next
+//! page until the provider stops advertising one (GitHub and GitLab `Link`
+//! headers, GitLab `X-Next-Page`, Bitbucket's `next` field).
+//!
+//! Responses that say the account's quota is spent (429, or 403 with
+//! `X-RateLimit-Remaining: 0` / `Retry-After`) become
+//! [`ProviderError::RateLimitExceeded`] carrying the RFC 3339 reset time, so
+//! the poll scheduler can back off until then.
+//!
+//! OAuth refresh grants share [`refresh_oauth_token`].
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::header::{HeaderMap, AUTHORIZATION, ETAG, IF_NONE_MATCH, LINK, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::{ProviderError, ProviderResult};
+use crate::traits::OAuthTokens;
+
+/// Upper bound on pages followed by one list call, against runaway `next` links.
+pub const MAX_PAGES: usize = 50;
+
+/// Entries kept before the cache is flushed.
+const MAX_CACHE_ENTRIES: usize = 4096;
+
+/// Back-off used when a rate-limited response names no reset time.
+const DEFAULT_RATE_LIMIT_BACKOFF_SECS: i64 = 60;
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: String,
+    body: Vec<u8>,
+    link: Option<String>,
+    next_page: Option<String>,
+}
+
+/// ETag cache for provider GET responses.
+///
+/// Keyed by URL and a hash of the `Authorization` header, so one cache can be
+/// shared by every provider instance without leaking responses between
+/// credentials.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    hits: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Responses served from the cache after a `304 Not Modified`
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn key(request: &reqwest::Request) -> String {
+        let auth = request
+            .headers()
+            .get(AUTHORIZATION)
+            .map(|v| v.as_bytes())
+            .unwrap_or_default();
+        format!("{} {}", hex::encode(Sha256::digest(auth)), request.url())
+    }
+
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: String, response: CachedResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHE_ENTRIES && !entries.contains_key(&key) {
+            entries.clear();
+        }
+        entries.insert(key, response);
+    }
+}
+
+/// A GET response, either fresh or replayed from the [`ResponseCache`]
+#[derive(Debug)]
+pub struct Fetched {
+    pub status: StatusCode,
+    /// URL the request was sent to
+    pub url: Url,
+    body: Vec<u8>,
+    link: Option<String>,
+    next_page: Option<String>,
+}
+
+impl Fetched {
+    pub fn json<T: DeserializeOwned>(&self) -> ProviderResult<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Next page URL from an RFC 8288 `Link` header
+    pub fn next_link(&self) -> Option<String> {
+        self.link.as_deref().and_then(parse_next_link)
+    }
+
+    /// Next page URL from GitLab's `X-Next-Page` header, which is empty on the
+    /// last page
+    pub fn next_page_url(&self) -> Option<String> {
+        let page = self.next_page.as_deref().filter(|p| !p.is_empty())?;
+        let mut url = self.url.clone();
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(k, _)| k != "page")
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("page", page);
+        Some(url.to_string())
+    }
+}
+
+/// Send a GET request, revalidating against the cached ETag if there is one.
+///
+/// Non-success responses are returned as-is so callers keep their own error
+/// mapping; only successful responses carrying an `ETag` are cached.
+pub async fn fetch(
+    client: &Client,
+    cache: &ResponseCache,
+    request: RequestBuilder,
+) -> ProviderResult<Fetched> {
+    let mut request = request.build()?;
+    let key = ResponseCache::key(&request);
+    let cached = cache.get(&key);
+
+    if let Some(cached) = &cached {
+        if let Ok(value) = cached.etag.parse() {
+            request.headers_mut().insert(IF_NONE_MATCH, value);
+        }
+    }
+
+    let url = request.url().clone();
+    let response = client.execute(request).await?;
+    let status = response.status();
+
+    if let Some(reset_at) = rate_limited_until(status, response.headers(), Utc::now()) {
+        return Err(ProviderError::RateLimitExceeded(reset_at.to_rfc3339()));
+    }
+
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            cache.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Fetched {
+                status: StatusCode::OK,
+                url,
+                body: cached.body,
+                link: cached.link,
+                next_page: cached.next_page,
+            });
+        }
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = header(ETAG.as_str());
+    let link = header(LINK.as_str());
+    let next_page = header("x-next-page");
+    let body = response.bytes().await?.to_vec();
+
+    if status.is_success() {
+        if let Some(etag) = etag {
+            cache.insert(
+                key,
+                CachedResponse {
+                    etag,
+                    body: body.clone(),
+                    link: link.clone(),
+                    next_page: next_page.clone(),
+                },
+            );
+        }
+    }
+
+    Ok(Fetched {
+        status,
+        url,
+        body,
+        link,
+        next_page,
+    })
+}
+
+/// Fetch every page of a list endpoint.
+///
+/// `parse` turns one page into its items and the next page URL, if any;
+/// `request_for` builds the (authenticated) request for that URL. A next page
+/// URL on another origin than the page that named it fails the call, so the
+/// credentials never leave the provider's host. Stops with a warning after
+/// [`MAX_PAGES`] pages. A non-success page fails the whole call with
+/// `error_message`.
+pub async fn collect_pages<T, P, R>(
+    client: &Client,
+    cache: &ResponseCache,
+    first: RequestBuilder,
+    error_message: &str,
+    mut parse: P,
+    request_for: R,
+) -> ProviderResult<Vec<T>>
+where
+    P: FnMut(&Fetched) -> ProviderResult<(Vec<T>, Option<String>)>,
+    R: Fn(&str) -> RequestBuilder,
+{
+    let mut items = Vec::new();
+    let mut request = Some(first);
+    let mut pages = 0;
+
+    while let Some(next) = request.take() {
+        let page = fetch(client, cache, next).await?;
+        if !page.status.is_success() {
+            return Err(ProviderError::ApiError {
+                status_code: page.status.as_u16(),
+                message: error_message.to_string(),
+            });
+        }
+
+        let (page_items, next_url) = parse(&page)?;
+        items.extend(page_items);
+        pages += 1;
+
+        let Some(next_url) = next_url else {
+            break;
+        };
+        let next_url = same_origin(&page.url, &next_url)?;
+        if pages == MAX_PAGES {
+            tracing::warn!(
+                max_pages = MAX_PAGES,
+                next = %next_url,
+                "Provider list truncated at the page cap"
+            );
+            break;
+        }
+        request = Some(request_for(next_url.as_str()));
+    }
+
+    Ok(items)
+}
+
+/// Resolve a next page link against the page that named it, rejecting links
+/// to any other scheme, host or port.
+fn same_origin(page: &Url, next: &str) -> ProviderResult<Url> {
+    let url = page
+        .join(next)
+        .map_err(|e| ProviderError::InvalidResponse(format!("malformed next page link: {}", e)))?;
+    if url.origin() != page.origin() {
+        return Err(ProviderError::InvalidResponse(format!(
+            "next page link points to another origin: {}",
+            url.origin().ascii_serialization()
+        )));
+    }
+    Ok(url)
+}
+
+/// When the account may retry, if `status`/`headers` say its quota is spent.
+///
+/// Prefers `Retry-After` (seconds), then the epoch-seconds reset header
+/// (`X-RateLimit-Reset` on GitHub, `RateLimit-Reset` on GitLab).
+pub fn rate_limited_until(
+    status: StatusCode,
+    headers: &HeaderMap,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let retry_after = header(RETRY_AFTER.as_str()).and_then(|v| v.trim().parse::<i64>().ok());
+    let exhausted = header("x-ratelimit-remaining")
+        .or_else(|| header("ratelimit-remaining"))
+        .is_some_and(|v| v.trim() == "0");
+
+    let limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && (exhausted || retry_after.is_some()));
+    if !limited {
+        return None;
+    }
+
+    let reset_at = retry_after
+        .map(|secs| now + Duration::seconds(secs))
+        .or_else(|| {
+            header("x-ratelimit-reset")
+                .or_else(|| header("ratelimit-reset"))
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .and_then(|epoch| Utc.timestamp_opt(epoch, 0).single())
+        })
+        .unwrap_or_else(|| now + Duration::seconds(DEFAULT_RATE_LIMIT_BACKOFF_SECS));
+
+    Some(reset_at)
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+/// Send an OAuth `refresh_token` grant and read the standard token response.
+///
+/// A rejected grant (revoked or already used refresh token) is reported as
+/// [`ProviderError::AuthenticationFailed`]: the user has to reconnect.
+pub async fn refresh_oauth_token(request: RequestBuilder) -> ProviderResult<OAuthTokens> {
+    let response = request.send().await?;
+    let status = response.status();
+
+    if status == StatusCode::BAD_REQUEST || status == StatusCode::UNAUTHORIZED {
+        return Err(ProviderError::AuthenticationFailed(
+            "OAuth refresh token was rejected".to_string(),
+        ));
+    }
+
+    if !status.is_success() {
+        return Err(ProviderError::ApiError {
+            status_code: status.as_u16(),
+            message: "Failed to refresh OAuth token".to_string(),
+        });
+    }
+
+    let tokens: OAuthTokenResponse = response.json().await?;
+    Ok(OAuthTokens {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        expires_at: tokens
+            .expires_in
+            .map(|secs| Utc::now() + Duration::seconds(secs)),
+    })
+}
+
+/// Extract the `rel="next"` target from a `Link` header
+pub fn parse_next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|part| {
+        let mut segments = part.split(';');
+        let target = segments.next()?.trim();
+        let is_next = segments.any(|param| {
+            let param = param.trim();
+            param == "rel=\"next\"" || param == "rel=next"
+        });
+        is_next.then(|| {
+            target
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_next_link_among_other_relations() {
+        let header = r#"<https://api.github.com/repos/a/b/pulls?page=1>; rel="prev", <https://api.github.com/repos/a/b/pulls?page=3>; rel="next", <https://api.github.com/repos/a/b/pulls?page=9>; rel="last""#;
+
+        assert_eq!(
+            parse_next_link(header).as_deref(),
+            Some("https://api.github.com/repos/a/b/pulls?page=3")
+        );
+    }
+
+    #[test]
+    fn should_return_none_without_next_relation() {
+        let header = r#"<https://api.github.com/repos/a/b/pulls?page=1>; rel="first""#;
+
+        assert_eq!(parse_next_link(header), None);
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn should_read_github_reset_from_exhausted_quota() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let headers = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1700000900"),
+        ]);
+
+        assert_eq!(
+            rate_limited_until(StatusCode::FORBIDDEN, &headers, now),
+            Utc.timestamp_opt(1_700_000_900, 0).single()
+        );
+    }
+
+    #[test]
+    fn should_prefer_retry_after_on_too_many_requests() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let headers = headers(&[("retry-after", "30"), ("ratelimit-reset", "1700000900")]);
+
+        assert_eq!(
+            rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &headers, now),
+            Some(now + Duration::seconds(30))
+        );
+    }
+
+    #[test]
+    fn should_not_treat_plain_forbidden_as_rate_limit() {
+        let now = Utc::now();
+        let headers = headers(&[("x-ratelimit-remaining", "42")]);
+
+        assert_eq!(
+            rate_limited_until(StatusCode::FORBIDDEN, &headers, now),
+            None
+        );
+    }
+
+    #[test]
+    fn should_build_next_page_url_from_gitlab_header() {
+        let fetched = Fetched {
+            status: StatusCode::OK,
+            url: "https://gitlab.com/api/v4/projects/1/merge_requests?state=opened&page=1&per_page=100"
+                .parse()
+                .unwrap(),
+            body: Vec::new(),
+            link: None,
+            next_page: Some("2".to_string()),
+        };
+
+        assert_eq!(
+            fetched.next_page_url().as_deref(),
+            Some("https://gitlab.com/api/v4/projects/1/merge_requests?state=opened&per_page=100&page=2")
+        );
+    }
+
+    #[test]
+    fn should_stop_on_empty_gitlab_next_page() {
+        let fetched = Fetched {
+            status: StatusCode::OK,
+            url: "https://gitlab.com/api/v4/projects/1/merge_requests"
+                .parse()
+                .unwrap(),
+            body: Vec::new(),
+            link: None,
+            next_page: Some(String::new()),
+        };
+
+        assert_eq!(fetched.next_page_url(), None);
+    }
+}
@@ -2,9 +2,12 @@ pub mod bitbucket;
 pub mod error;
 pub mod factory;
 pub mod github;
+pub mod github_app;
 pub mod gitlab;
+pub mod http;
 pub mod remediation;
 pub mod traits;
+pub mod webhook;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod mock;
@@ -13,9 +16,12 @@ pub use bitbucket::BitbucketProvider;
 pub use error::ProviderError;
 pub use factory::ProviderFactory;
 pub use github::GitHubProvider;
+pub use github_app::InstallationTokenCache;
 pub use gitlab::GitLabProvider;
+pub use http::ResponseCache;
 pub use remediation::{RemediationCapable, RemediationCaps};
 pub use traits::GitProvider;
+pub use webhook::WebhookEvent;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub use mock::MockProvider;
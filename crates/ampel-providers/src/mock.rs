@@ -44,6 +44,7 @@ use crate::traits::{
     ProviderReview, ProviderUser, RateLimitInfo, TokenValidation,
 };
 use ampel_core::models::{DiscoveredRepository, GitProvider as Provider, MergeRequest};
+use ampel_core::remediation::SuggestedChange;
 
 /// A recorded [`RemediationCapable`] write operation.
 ///
@@ -95,6 +96,13 @@ pub enum RemediationCall {
         pr_number: i32,
         body: String,
     },
+    /// `create_suggestions(owner, repo, pr_number, suggestions)`
+    CreateSuggestions {
+        owner: String,
+        repo: String,
+        pr_number: i32,
+        suggestions: Vec<SuggestedChange>,
+    },
     /// `add_labels(owner, repo, pr_number, labels)`
     AddLabels {
         owner: String,
@@ -131,6 +139,7 @@ struct MockState {
     should_fail_user: bool,
     should_fail_repositories: bool,
     should_fail_pull_requests: bool,
+    should_fail_suggestions: bool,
     /// Capability descriptor returned by `RemediationCapable::capabilities`.
     /// `None` means "all supported" (the common case for worker tests).
     remediation_caps: Option<RemediationCaps>,
@@ -307,6 +316,12 @@ impl MockProvider {
         self
     }
 
+    /// Configure create_suggestions to fail as a provider rejecting the review would
+    pub fn with_suggestions_failure(self) -> Self {
+        self.state.lock().unwrap().should_fail_suggestions = true;
+        self
+    }
+
     /// Configure the SHA returned by `get_default_branch_sha`.
     pub fn with_default_branch_sha(self, sha: impl Into<String>) -> Self {
         self.state.lock().unwrap().default_branch_sha = Some(sha.into());
@@ -624,6 +639,7 @@ impl RemediationCapable for MockProvider {
             state: "open".to_string(),
             source_branch: head.to_string(),
             target_branch: base.to_string(),
+            head_sha: None,
             author: "mockuser".to_string(),
             author_avatar_url: None,
             is_draft: false,
@@ -638,9 +654,29 @@ impl RemediationCapable for MockProvider {
             updated_at: now,
             merged_at: None,
             closed_at: None,
+            requested_reviewers: Vec::new(),
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
         })
     }
 
+    async fn find_open_pull_request_by_head(
+        &self,
+        _credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> ProviderResult<Option<ProviderPullRequest>> {
+        let state = self.state.lock().unwrap();
+        let key = format!("{}/{}", owner, repo);
+        Ok(state.pull_requests.get(&key).and_then(|prs| {
+            prs.iter()
+                .find(|pr| pr.state == "open" && pr.source_branch == branch)
+                .cloned()
+        }))
+    }
+
     async fn update_pull_request(
         &self,
         _credentials: &ProviderCredentials,
@@ -704,6 +740,57 @@ impl RemediationCapable for MockProvider {
         Ok(comment_id)
     }
 
+    async fn list_comments(
+        &self,
+        _credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> ProviderResult<Vec<String>> {
+        // The comments this mock was asked to post on the PR
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .remediation_calls
+            .iter()
+            .filter_map(|call| match call {
+                RemediationCall::CreateComment {
+                    owner: o,
+                    repo: r,
+                    pr_number: n,
+                    body,
+                } if o == owner && r == repo && *n == pr_number => Some(body.clone()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn create_suggestions(
+        &self,
+        _credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+        suggestions: &[SuggestedChange],
+    ) -> ProviderResult<()> {
+        let mut state = self.state.lock().unwrap();
+        ensure_supported(&state, |c| c.create_suggestions, "create_suggestions")?;
+        if state.should_fail_suggestions {
+            return Err(ProviderError::ApiError {
+                status_code: 422,
+                message: "MockProvider: suggestions rejected".to_string(),
+            });
+        }
+        state
+            .remediation_calls
+            .push(RemediationCall::CreateSuggestions {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                pr_number,
+                suggestions: suggestions.to_vec(),
+            });
+        Ok(())
+    }
+
     async fn add_labels(
         &self,
         _credentials: &ProviderCredentials,
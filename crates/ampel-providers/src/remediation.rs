@@ -33,6 +33,7 @@ use async_trait::async_trait;
 
 use crate::error::ProviderResult;
 use crate::traits::{GitProvider, ProviderCICheck, ProviderCredentials, ProviderPullRequest};
+use ampel_core::remediation::SuggestedChange;
 
 /// Capability flags returned by [`RemediationCapable::capabilities`].
 ///
@@ -59,6 +60,8 @@ pub struct RemediationCaps {
     pub get_status_for_ref: bool,
     /// Provider can delete a branch.
     pub delete_branch: bool,
+    /// Provider can post line-anchored suggested changes on a pull/merge request.
+    pub create_suggestions: bool,
 }
 
 impl RemediationCaps {
@@ -74,6 +77,7 @@ impl RemediationCaps {
             add_labels: true,
             get_status_for_ref: true,
             delete_branch: true,
+            create_suggestions: true,
         }
     }
 }
@@ -135,6 +139,16 @@ pub trait RemediationCapable: GitProvider {
         base: &str,
     ) -> ProviderResult<ProviderPullRequest>;
 
+    /// The open pull/merge request whose head is `branch`, if any. Filtered by
+    /// the provider, so it is found however many other requests are open.
+    async fn find_open_pull_request_by_head(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> ProviderResult<Option<ProviderPullRequest>>;
+
     /// Edit the title and/or body of an existing pull/merge request.
     async fn update_pull_request(
         &self,
@@ -165,6 +179,29 @@ pub trait RemediationCapable: GitProvider {
         body: &str,
     ) -> ProviderResult<i64>;
 
+    /// The bodies of the comments on a pull/merge request, oldest first.
+    async fn list_comments(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> ProviderResult<Vec<String>>;
+
+    /// Post `suggestions` as the provider's native suggested changes on a
+    /// pull/merge request, anchored to lines of its head revision. Nothing is
+    /// pushed; the author applies (or ignores) each suggestion. A suggestion on
+    /// lines the PR's diff does not show cannot be anchored, so it is posted as
+    /// a plain comment carrying its diff instead.
+    async fn create_suggestions(
+        &self,
+        credentials: &ProviderCredentials,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+        suggestions: &[SuggestedChange],
+    ) -> ProviderResult<()>;
+
     /// Attach labels to a pull/merge request.
     async fn add_labels(
         &self,
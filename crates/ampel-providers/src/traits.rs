@@ -1,7 +1,7 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
-use crate::error::ProviderResult;
+use crate::error::{ProviderError, ProviderResult};
 use ampel_core::models::{DiscoveredRepository, GitProvider as Provider, MergeRequest};
 
 /// Rate limit information from a provider
@@ -13,7 +13,7 @@ pub struct RateLimitInfo {
 }
 
 /// Authentication credentials for a provider
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum ProviderCredentials {
     /// Personal Access Token authentication
     Pat {
@@ -21,6 +21,93 @@ pub enum ProviderCredentials {
         /// For Bitbucket: username for Basic Auth
         username: Option<String>,
     },
+    /// GitHub App installation. The provider mints short-lived installation
+    /// tokens from the app's private key and caches them until shortly
+    /// before they expire.
+    GitHubApp {
+        app_id: i64,
+        /// PEM-encoded RSA private key of the app
+        private_key: String,
+        installation_id: i64,
+    },
+    /// OAuth access token (GitLab, Bitbucket). Whoever stores the account
+    /// refreshes it with [`GitProvider::refresh_oauth_token`] before it
+    /// expires, since providers rotate the refresh token as well.
+    OAuth {
+        access_token: String,
+        refresh_token: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl ProviderCredentials {
+    /// Whether these are OAuth credentials that expire within `margin` of
+    /// `now` and can be refreshed
+    pub fn needs_refresh(&self, now: DateTime<Utc>, margin: Duration) -> bool {
+        match self {
+            ProviderCredentials::OAuth {
+                refresh_token: Some(_),
+                expires_at: Some(expires_at),
+                ..
+            } => *expires_at <= now + margin,
+            _ => false,
+        }
+    }
+}
+
+// Secrets stay out of logs and error messages
+impl std::fmt::Debug for ProviderCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderCredentials::Pat { username, .. } => f
+                .debug_struct("Pat")
+                .field("token", &"[redacted]")
+                .field("username", username)
+                .finish(),
+            ProviderCredentials::GitHubApp {
+                app_id,
+                installation_id,
+                ..
+            } => f
+                .debug_struct("GitHubApp")
+                .field("app_id", app_id)
+                .field("private_key", &"[redacted]")
+                .field("installation_id", installation_id)
+                .finish(),
+            ProviderCredentials::OAuth { expires_at, .. } => f
+                .debug_struct("OAuth")
+                .field("access_token", &"[redacted]")
+                .field("refresh_token", &"[redacted]")
+                .field("expires_at", expires_at)
+                .finish(),
+        }
+    }
+}
+
+/// OAuth application registered with a provider, used to refresh
+/// [`ProviderCredentials::OAuth`]
+#[derive(Clone)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl std::fmt::Debug for OAuthClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthClient")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"[redacted]")
+            .finish()
+    }
+}
+
+/// Tokens issued by an OAuth refresh
+#[derive(Clone)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    /// Replacement refresh token, if the provider rotated it
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// Token validation result
@@ -56,6 +143,8 @@ pub struct ProviderPullRequest {
     pub state: String,
     pub source_branch: String,
     pub target_branch: String,
+    /// Commit at the tip of the source branch
+    pub head_sha: Option<String>,
     pub author: String,
     pub author_avatar_url: Option<String>,
     pub is_draft: bool,
@@ -70,6 +159,14 @@ pub struct ProviderPullRequest {
     pub updated_at: DateTime<Utc>,
     pub merged_at: Option<DateTime<Utc>>,
     pub closed_at: Option<DateTime<Utc>>,
+    /// Logins of reviewers whose review is requested and still outstanding
+    pub requested_reviewers: Vec<String>,
+    /// Names of the labels on the PR
+    pub labels: Vec<String>,
+    /// Logins of the users the PR is assigned to
+    pub assignees: Vec<String>,
+    /// Title of the PR's milestone
+    pub milestone: Option<String>,
 }
 
 /// CI check data from provider
@@ -90,6 +187,8 @@ pub struct ProviderReview {
     pub reviewer: String,
     pub reviewer_avatar_url: Option<String>,
     pub state: String,
+    /// Commit the review was given on; only GitHub reports it
+    pub commit_sha: Option<String>,
     pub body: Option<String>,
     pub submitted_at: DateTime<Utc>,
 }
@@ -136,7 +235,7 @@ pub trait GitProvider: Send + Sync {
         repo: &str,
     ) -> ProviderResult<DiscoveredRepository>;
 
-    /// List pull requests for a repository
+    /// List pull requests for a repository, following pagination to the last page
     async fn list_pull_requests(
         &self,
         credentials: &ProviderCredentials,
@@ -154,7 +253,7 @@ pub trait GitProvider: Send + Sync {
         number: i32,
     ) -> ProviderResult<ProviderPullRequest>;
 
-    /// Get CI checks for a pull request
+    /// Get CI checks for a pull request (all pages)
     async fn get_ci_checks(
         &self,
         credentials: &ProviderCredentials,
@@ -163,7 +262,7 @@ pub trait GitProvider: Send + Sync {
         pr_number: i32,
     ) -> ProviderResult<Vec<ProviderCICheck>>;
 
-    /// Get reviews for a pull request
+    /// Get reviews for a pull request (all pages)
     async fn get_reviews(
         &self,
         credentials: &ProviderCredentials,
@@ -187,4 +286,27 @@ pub trait GitProvider: Send + Sync {
         &self,
         credentials: &ProviderCredentials,
     ) -> ProviderResult<RateLimitInfo>;
+
+    /// Bearer token for git operations outside the REST API (clone, push).
+    /// Providers that mint tokens from the credentials override this.
+    async fn access_token(&self, credentials: &ProviderCredentials) -> ProviderResult<String> {
+        match credentials {
+            ProviderCredentials::Pat { token, .. } => Ok(token.clone()),
+            ProviderCredentials::OAuth { access_token, .. } => Ok(access_token.clone()),
+            ProviderCredentials::GitHubApp { .. } => Err(ProviderError::ConfigError(
+                "GitHub App credentials are only supported for GitHub".to_string(),
+            )),
+        }
+    }
+
+    /// Exchange an OAuth refresh token for a new access token
+    async fn refresh_oauth_token(
+        &self,
+        _client: &OAuthClient,
+        _refresh_token: &str,
+    ) -> ProviderResult<OAuthTokens> {
+        Err(ProviderError::NotSupported(
+            "OAuth token refresh is not supported by this provider".to_string(),
+        ))
+    }
 }
@@ -0,0 +1,118 @@
+//! Inbound webhook support shared by every provider.
+//!
+//! Providers push PR, CI and review changes to Ampel instead of waiting for the
+//! poller. This module holds the provider-neutral pieces: signature/token
+//! verification and the [`WebhookEvent`] shape each provider's payload parser
+//! (`github::parse_webhook`, `gitlab::parse_webhook`, `bitbucket::parse_webhook`)
+//! normalises into, reusing the same `Provider*` structs the polling path
+//! produces so both paths write identical rows.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::traits::{ProviderCICheck, ProviderPullRequest, ProviderReview};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A provider webhook delivery, normalised to the fields Ampel persists.
+#[derive(Debug, Clone)]
+pub enum WebhookEvent {
+    /// A PR/MR was opened, edited, synchronised, closed or merged.
+    ///
+    /// `author` may be empty when the payload only names the *actor* (GitLab
+    /// merge request hooks after the initial `open`); callers should keep the
+    /// stored author in that case.
+    PullRequest(Box<ProviderPullRequest>),
+    /// CI results for one or more PRs. When the provider cannot name the PR
+    /// (Bitbucket commit statuses, GitHub check runs from forks) the checks are
+    /// matched to open PRs by `source_branch` instead.
+    Checks {
+        pr_numbers: Vec<i32>,
+        source_branch: Option<String>,
+        checks: Vec<ProviderCICheck>,
+    },
+    /// A review (or approval) was submitted on a PR.
+    Review {
+        pr_number: i32,
+        review: ProviderReview,
+    },
+    /// A delivery Ampel does not act on (pings, unrelated actions/kinds).
+    Ignored(String),
+}
+
+/// Verify an `X-Hub-Signature-256`-style header (`sha256=<hex>`) against the
+/// HMAC-SHA256 of the raw request body. Used by GitHub and Bitbucket Cloud.
+///
+/// The comparison is constant-time; a missing prefix or malformed hex is a
+/// verification failure, never a panic.
+pub fn verify_hmac_sha256(secret: &str, body: &[u8], signature_header: &str) -> bool {
+    let Some(hex_digest) = signature_header.trim().strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_digest) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Compute the `sha256=<hex>` signature header for `body` — the inverse of
+/// [`verify_hmac_sha256`], used by tests and outbound signed webhooks.
+pub fn sign_hmac_sha256(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Verify a shared-secret token header (GitLab's `X-Gitlab-Token`) in constant
+/// time with respect to the token contents.
+pub fn verify_token(secret: &str, token: &str) -> bool {
+    let (a, b) = (secret.as_bytes(), token.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_accept_signature_computed_with_same_secret() {
+        let body = br#"{"action":"opened"}"#;
+        let header = sign_hmac_sha256("s3cret", body);
+        assert!(verify_hmac_sha256("s3cret", body, &header));
+    }
+
+    #[test]
+    fn should_reject_signature_from_other_secret_or_tampered_body() {
+        let body = br#"{"action":"opened"}"#;
+        let header = sign_hmac_sha256("s3cret", body);
+        assert!(!verify_hmac_sha256("other", body, &header));
+        assert!(!verify_hmac_sha256(
+            "s3cret",
+            br#"{"action":"closed"}"#,
+            &header
+        ));
+    }
+
+    #[test]
+    fn should_reject_malformed_signature_headers() {
+        assert!(!verify_hmac_sha256("s3cret", b"{}", ""));
+        assert!(!verify_hmac_sha256("s3cret", b"{}", "sha1=abcd"));
+        assert!(!verify_hmac_sha256("s3cret", b"{}", "sha256=not-hex"));
+    }
+
+    #[test]
+    fn should_compare_tokens_exactly() {
+        assert!(verify_token("token-123", "token-123"));
+        assert!(!verify_token("token-123", "token-124"));
+        assert!(!verify_token("token-123", "token-12"));
+        assert!(!verify_token("token-123", ""));
+    }
+}
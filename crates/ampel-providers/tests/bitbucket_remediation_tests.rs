@@ -1,5 +1,5 @@
 //! Wiremock-backed tests for `BitbucketProvider`'s `RemediationCapable` write primitives,
-//! including the two operations Bitbucket does not support.
+//! including the operations Bitbucket does not support.
 //!
 //! ```bash
 //! cargo test -p ampel-providers --test bitbucket_remediation_tests
@@ -9,7 +9,7 @@ use ampel_providers::bitbucket::BitbucketProvider;
 use ampel_providers::error::ProviderError;
 use ampel_providers::remediation::RemediationCapable;
 use ampel_providers::traits::ProviderCredentials;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn creds() -> ProviderCredentials {
@@ -27,9 +27,10 @@ fn should_report_partial_capabilities() {
     // Act
     let caps = provider.capabilities();
 
-    // Assert — only update_branch_from_base and add_labels are unsupported.
+    // Assert — update_branch_from_base, add_labels and suggestions are unsupported.
     assert!(!caps.update_branch_from_base);
     assert!(!caps.add_labels);
+    assert!(!caps.create_suggestions);
     assert!(caps.create_branch);
     assert!(caps.create_pull_request);
     assert!(caps.close_pull_request);
@@ -66,6 +67,20 @@ async fn should_return_not_supported_for_add_labels() {
     assert!(matches!(result, Err(ProviderError::NotSupported(_))));
 }
 
+#[tokio::test]
+async fn should_return_not_supported_for_create_suggestions() {
+    // Arrange
+    let provider = BitbucketProvider::new(None);
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 5, &[])
+        .await;
+
+    // Assert
+    assert!(matches!(result, Err(ProviderError::NotSupported(_))));
+}
+
 #[tokio::test]
 async fn should_create_branch() {
     // Arrange
@@ -125,6 +140,41 @@ async fn should_create_pull_request() {
     assert_eq!(pr.source_branch, "consolidate");
 }
 
+#[tokio::test]
+async fn should_find_open_pull_request_by_source_branch_query() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repositories/acme/widget/pullrequests"))
+        .and(query_param(
+            "q",
+            "source.branch.name=\"ampel/remediation/run\" AND state=\"OPEN\"",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "values": [{
+                "id": 7, "title": "Consolidated", "description": null,
+                "links": { "html": { "href": "https://bitbucket.org/acme/widget/pull-requests/7" } },
+                "state": "OPEN",
+                "source": { "branch": { "name": "ampel/remediation/run" } },
+                "destination": { "branch": { "name": "main" } },
+                "author": { "username": "bot", "display_name": "Bot", "links": null },
+                "created_on": "2026-06-24T00:00:00Z", "updated_on": "2026-06-24T00:00:00Z"
+            }]
+        })))
+        .mount(&server)
+        .await;
+    let provider = BitbucketProvider::new(Some(server.uri()));
+
+    // Act
+    let pr = provider
+        .find_open_pull_request_by_head(&creds(), "acme", "widget", "ampel/remediation/run")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(pr.map(|pr| pr.number), Some(7));
+}
+
 #[tokio::test]
 async fn should_decline_pull_request_on_close() {
     // Arrange
@@ -166,6 +216,29 @@ async fn should_create_comment_and_return_id() {
     assert_eq!(id, 555);
 }
 
+#[tokio::test]
+async fn should_list_comment_bodies() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repositories/acme/widget/pullrequests/7/comments"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "values": [{ "id": 1, "content": { "raw": "Superseded by #100" } }]
+        })))
+        .mount(&server)
+        .await;
+    let provider = BitbucketProvider::new(Some(server.uri()));
+
+    // Act
+    let comments = provider
+        .list_comments(&creds(), "acme", "widget", 7)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(comments, vec!["Superseded by #100"]);
+}
+
 #[tokio::test]
 async fn should_get_status_for_ref() {
     // Arrange
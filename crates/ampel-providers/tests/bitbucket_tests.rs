@@ -32,7 +32,9 @@ fn test_provider_credentials_pat_with_username() {
         username: Some("testuser".to_string()),
     };
 
-    let ProviderCredentials::Pat { token, username } = credentials;
+    let ProviderCredentials::Pat { token, username } = credentials else {
+        panic!("expected PAT credentials");
+    };
     assert_eq!(token, "app_password_1234567890");
     assert_eq!(username, Some("testuser".to_string()));
 }
@@ -0,0 +1,360 @@
+//! Wiremock-backed tests for GitHub App installations and OAuth credentials.
+//!
+//! GitHub App tests sign app JWTs with a throwaway RSA key and assert that
+//! installation tokens are minted once and reused. OAuth tests assert that
+//! access tokens are sent as Bearer tokens and that refresh grants reach each
+//! provider's token endpoint.
+//!
+//! ```bash
+//! cargo test -p ampel-providers --test credential_types_tests
+//! ```
+
+use std::sync::Arc;
+
+use ampel_providers::bitbucket::BitbucketProvider;
+use ampel_providers::error::ProviderError;
+use ampel_providers::github::GitHubProvider;
+use ampel_providers::gitlab::GitLabProvider;
+use ampel_providers::traits::{GitProvider, OAuthClient, ProviderCredentials};
+use ampel_providers::InstallationTokenCache;
+use chrono::{Duration, Utc};
+use wiremock::matchers::{basic_auth, body_string_contains, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TEST_APP_KEY: &str = include_str!("test-fixtures/github_app_key.pem");
+
+fn app_creds() -> ProviderCredentials {
+    ProviderCredentials::GitHubApp {
+        app_id: 1234,
+        private_key: TEST_APP_KEY.to_string(),
+        installation_id: 99,
+    }
+}
+
+fn oauth_creds() -> ProviderCredentials {
+    ProviderCredentials::OAuth {
+        access_token: "oauth_access".to_string(),
+        refresh_token: Some("oauth_refresh".to_string()),
+        expires_at: Some(Utc::now() + Duration::hours(1)),
+    }
+}
+
+fn oauth_client() -> OAuthClient {
+    OAuthClient {
+        client_id: "client".to_string(),
+        client_secret: "secret".to_string(),
+    }
+}
+
+fn installation_token_response() -> ResponseTemplate {
+    ResponseTemplate::new(201).set_body_json(serde_json::json!({
+        "token": "ghs_installation",
+        "expires_at": (Utc::now() + Duration::hours(1)).to_rfc3339(),
+    }))
+}
+
+#[tokio::test]
+async fn should_mint_installation_token_once_and_reuse_it() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/app/installations/99/access_tokens"))
+        .respond_with(installation_token_response())
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .and(header("Authorization", "Bearer ghs_installation"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    for _ in 0..2 {
+        provider
+            .list_pull_requests(&app_creds(), "acme", "widget", None)
+            .await
+            .expect("list should succeed");
+    }
+
+    // Assert: the mock expectations are verified when the server drops
+}
+
+#[tokio::test]
+async fn should_share_installation_tokens_between_providers() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/app/installations/99/access_tokens"))
+        .respond_with(installation_token_response())
+        .expect(1)
+        .mount(&server)
+        .await;
+    let tokens = Arc::new(InstallationTokenCache::new());
+
+    // Act
+    for _ in 0..2 {
+        let provider =
+            GitHubProvider::new(Some(server.uri())).with_installation_tokens(tokens.clone());
+        let token = provider.access_token(&app_creds()).await.unwrap();
+        assert_eq!(token, "ghs_installation");
+    }
+
+    // Assert
+    assert_eq!(tokens.len(), 1);
+}
+
+#[tokio::test]
+async fn should_replace_installation_token_close_to_expiry() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/app/installations/99/access_tokens"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+            "token": "ghs_expiring",
+            "expires_at": (Utc::now() + Duration::minutes(2)).to_rfc3339(),
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    provider.access_token(&app_creds()).await.unwrap();
+    provider.access_token(&app_creds()).await.unwrap();
+
+    // Assert: verified on drop
+}
+
+#[tokio::test]
+async fn should_validate_app_through_installation_account() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/app/installations/99"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": 99,
+            "account": { "id": 42, "login": "acme", "avatar_url": null }
+        })))
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let validation = provider.validate_credentials(&app_creds()).await.unwrap();
+
+    // Assert
+    assert!(validation.is_valid);
+    assert_eq!(validation.username.as_deref(), Some("acme"));
+    assert_eq!(validation.user_id.as_deref(), Some("42"));
+}
+
+#[tokio::test]
+async fn should_report_unknown_installation_as_invalid() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/app/installations/99"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    let validation = provider.validate_credentials(&app_creds()).await.unwrap();
+
+    assert!(!validation.is_valid);
+}
+
+#[tokio::test]
+async fn should_list_installation_repositories_for_app() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/app/installations/99/access_tokens"))
+        .respond_with(installation_token_response())
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/installation/repositories"))
+        .and(header("Authorization", "Bearer ghs_installation"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "total_count": 1,
+            "repositories": [{
+                "id": 7, "name": "widget", "full_name": "acme/widget",
+                "owner": { "login": "acme" }, "description": null,
+                "html_url": "https://github.com/acme/widget", "default_branch": "main",
+                "private": true, "archived": false
+            }]
+        })))
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let repos = provider
+        .list_repositories(&app_creds(), 1, 100)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].full_name, "acme/widget");
+}
+
+#[tokio::test]
+async fn should_reject_app_credentials_outside_github() {
+    let provider = GitLabProvider::new(Some("http://127.0.0.1:9".to_string()));
+
+    let result = provider
+        .list_pull_requests(&app_creds(), "acme", "widget", None)
+        .await;
+
+    assert!(matches!(result, Err(ProviderError::ConfigError(_))));
+}
+
+#[tokio::test]
+async fn should_send_oauth_token_as_bearer_on_bitbucket() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repositories/acme/widget/pullrequests"))
+        .and(header("Authorization", "Bearer oauth_access"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "values": [], "next": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = BitbucketProvider::new(Some(server.uri()));
+
+    // Act
+    let prs = provider
+        .list_pull_requests(&oauth_creds(), "acme", "widget", None)
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(prs.is_empty());
+}
+
+#[tokio::test]
+async fn should_refresh_gitlab_oauth_token() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("grant_type=refresh_token"))
+        .and(body_string_contains("refresh_token=oauth_refresh"))
+        .and(body_string_contains("client_id=client"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "new_access",
+            "token_type": "Bearer",
+            "expires_in": 7200,
+            "refresh_token": "new_refresh",
+            "created_at": 1700000000
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+
+    // Act
+    let tokens = provider
+        .refresh_oauth_token(&oauth_client(), "oauth_refresh")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(tokens.access_token, "new_access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("new_refresh"));
+    let expires_at = tokens.expires_at.expect("expiry should be set");
+    assert!(expires_at > Utc::now() + Duration::minutes(119));
+}
+
+#[tokio::test]
+async fn should_refresh_bitbucket_oauth_token_with_client_auth() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/site/oauth2/access_token"))
+        .and(basic_auth("client", "secret"))
+        .and(body_string_contains("grant_type=refresh_token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "new_access",
+            "refresh_token": "new_refresh",
+            "expires_in": 3600,
+            "token_type": "bearer"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = BitbucketProvider::new(Some(server.uri()));
+
+    // Act
+    let tokens = provider
+        .refresh_oauth_token(&oauth_client(), "oauth_refresh")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(tokens.access_token, "new_access");
+}
+
+#[tokio::test]
+async fn should_report_rejected_refresh_token_as_auth_failure() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": "invalid_grant"
+        })))
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+
+    let result = provider
+        .refresh_oauth_token(&oauth_client(), "used_refresh")
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ProviderError::AuthenticationFailed(_))
+    ));
+}
+
+#[tokio::test]
+async fn should_not_support_oauth_refresh_on_github() {
+    let provider = GitHubProvider::new(None);
+
+    let result = provider
+        .refresh_oauth_token(&oauth_client(), "oauth_refresh")
+        .await;
+
+    assert!(matches!(result, Err(ProviderError::NotSupported(_))));
+}
+
+#[test]
+fn should_only_refresh_expiring_oauth_credentials() {
+    let now = Utc::now();
+    let margin = Duration::minutes(5);
+    let expiring = ProviderCredentials::OAuth {
+        access_token: "a".to_string(),
+        refresh_token: Some("r".to_string()),
+        expires_at: Some(now + Duration::minutes(1)),
+    };
+
+    assert!(expiring.needs_refresh(now, margin));
+    assert!(!oauth_creds().needs_refresh(now, margin));
+    assert!(!app_creds().needs_refresh(now, margin));
+}
+
+#[test]
+fn should_redact_secrets_in_debug_output() {
+    let debug = format!("{:?} {:?}", app_creds(), oauth_creds());
+
+    assert!(!debug.contains("PRIVATE KEY"));
+    assert!(!debug.contains("oauth_access"));
+    assert!(!debug.contains("oauth_refresh"));
+}
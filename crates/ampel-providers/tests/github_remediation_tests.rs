@@ -8,10 +8,11 @@
 //! cargo test -p ampel-providers --test github_remediation_tests
 //! ```
 
+use ampel_core::remediation::SuggestedChange;
 use ampel_providers::github::GitHubProvider;
 use ampel_providers::remediation::RemediationCapable;
 use ampel_providers::traits::ProviderCredentials;
-use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::matchers::{body_partial_json, body_string_contains, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn creds() -> ProviderCredentials {
@@ -125,6 +126,37 @@ async fn should_open_pull_request() {
     assert_eq!(pr.target_branch, "main");
 }
 
+#[tokio::test]
+async fn should_find_open_pull_request_by_head_branch() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .and(query_param("state", "open"))
+        .and(query_param("head", "acme:ampel/remediation/run"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "id": 99, "number": 42, "title": "Consolidated", "body": null,
+                "html_url": "https://github.com/acme/widget/pull/42", "state": "open",
+                "head": { "ref": "ampel/remediation/run" }, "base": { "ref": "main" },
+                "user": { "login": "bot", "avatar_url": null },
+                "created_at": "2026-06-24T00:00:00Z", "updated_at": "2026-06-24T00:00:00Z"
+            }])),
+        )
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let pr = provider
+        .find_open_pull_request_by_head(&creds(), "acme", "widget", "ampel/remediation/run")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(pr.map(|pr| pr.number), Some(42));
+}
+
 #[tokio::test]
 async fn should_close_pull_request_with_state_closed() {
     // Arrange
@@ -170,6 +202,199 @@ async fn should_create_comment_and_return_id() {
     assert_eq!(id, 7777);
 }
 
+#[tokio::test]
+async fn should_list_comment_bodies() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/issues/42/comments"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": 1, "body": "LGTM" },
+            { "id": 2, "body": "Superseded by #100" }
+        ])))
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let comments = provider
+        .list_comments(&creds(), "acme", "widget", 42)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(comments, vec!["LGTM", "Superseded by #100"]);
+}
+
+#[tokio::test]
+async fn should_post_suggestions_as_one_review() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/42/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "filename": "Cargo.toml", "patch": "@@ -1,4 +1,4 @@\n [dependencies]\n-a = 1\n+a = 2\n b = 1\n c = 1" },
+            { "filename": "src/lib.rs", "patch": "@@ -9,3 +9,3 @@\n x\n-y\n+z\n w" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/acme/widget/pulls/42/reviews"))
+        .and(body_partial_json(serde_json::json!({
+            "event": "COMMENT",
+            "comments": [
+                {
+                    "path": "Cargo.toml",
+                    "line": 3,
+                    "side": "RIGHT",
+                    "body": "```suggestion\nserde = \"1.0.200\"\n```"
+                },
+                {
+                    "path": "src/lib.rs",
+                    "start_line": 10,
+                    "start_side": "RIGHT",
+                    "line": 11,
+                    "side": "RIGHT"
+                }
+            ]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": 1 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+    let suggestions = vec![
+        SuggestedChange {
+            path: "Cargo.toml".into(),
+            start_line: 3,
+            end_line: 3,
+            replacement: "serde = \"1.0.200\"".into(),
+            diff: String::new(),
+        },
+        SuggestedChange {
+            path: "src/lib.rs".into(),
+            start_line: 10,
+            end_line: 11,
+            replacement: "fn main() {}".into(),
+            diff: String::new(),
+        },
+    ];
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 42, &suggestions)
+        .await;
+
+    // Assert
+    assert!(result.is_ok(), "{result:?}");
+}
+
+#[tokio::test]
+async fn should_post_suggestions_outside_the_pr_diff_as_diff_comments() {
+    // Arrange: the PR only touches the top of Cargo.toml.
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/42/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "filename": "Cargo.toml", "patch": "@@ -1,4 +1,4 @@\n [dependencies]\n-a = 1\n+a = 2\n b = 1\n c = 1" },
+            { "filename": "logo.png" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/acme/widget/pulls/42/reviews"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": 1 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/acme/widget/issues/42/comments"))
+        .and(body_string_contains("```diff"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 7 })))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+    let change = |path: &str, line: u32| SuggestedChange {
+        path: path.into(),
+        start_line: line,
+        end_line: line,
+        replacement: "fixed".into(),
+        diff: format!("--- a/{path}\n+++ b/{path}\n@@ -{line},1 +{line},1 @@\n-broken\n+fixed\n"),
+    };
+    let suggestions = vec![
+        change("Cargo.toml", 3),
+        change("Cargo.toml", 30),
+        change("src/lib.rs", 1),
+    ];
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 42, &suggestions)
+        .await;
+
+    // Assert: only the in-diff line went into the review; the other two
+    // became plain comments with their diffs.
+    assert!(result.is_ok(), "{result:?}");
+    let requests = server.received_requests().await.unwrap();
+    let review: serde_json::Value = requests
+        .iter()
+        .find(|r| r.url.path().ends_with("/reviews"))
+        .unwrap()
+        .body_json()
+        .unwrap();
+    assert_eq!(
+        review["comments"],
+        serde_json::json!([{
+            "path": "Cargo.toml",
+            "line": 3,
+            "side": "RIGHT",
+            "body": "```suggestion\nfixed\n```"
+        }])
+    );
+    let comments: Vec<String> = requests
+        .iter()
+        .filter(|r| r.url.path().ends_with("/comments"))
+        .map(|r| r.body_json::<serde_json::Value>().unwrap()["body"].to_string())
+        .collect();
+    assert!(comments[0].contains("`Cargo.toml` line 30"));
+    assert!(comments[1].contains("+++ b/src/lib.rs"));
+}
+
+#[tokio::test]
+async fn should_fail_when_the_review_is_rejected() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/42/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "filename": "Cargo.toml", "patch": "@@ -1,1 +1,1 @@\n-a = 1\n+a = 2" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/repos/acme/widget/pulls/42/reviews"))
+        .respond_with(ResponseTemplate::new(422))
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+    let suggestions = vec![SuggestedChange {
+        path: "Cargo.toml".into(),
+        start_line: 1,
+        end_line: 1,
+        replacement: "a = 3".into(),
+        diff: String::new(),
+    }];
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 42, &suggestions)
+        .await;
+
+    // Assert
+    assert!(result.is_err());
+}
+
 #[tokio::test]
 async fn should_add_labels() {
     // Arrange
@@ -52,7 +52,9 @@ fn test_provider_credentials_pat_variant() {
         username: None,
     };
 
-    let ProviderCredentials::Pat { token, username } = credentials;
+    let ProviderCredentials::Pat { token, username } = credentials else {
+        panic!("expected PAT credentials");
+    };
     assert_eq!(token, "test_token");
     assert!(username.is_none());
 }
@@ -64,7 +66,9 @@ fn test_provider_credentials_pat_with_username() {
         username: Some("testuser".to_string()),
     };
 
-    let ProviderCredentials::Pat { token, username } = credentials;
+    let ProviderCredentials::Pat { token, username } = credentials else {
+        panic!("expected PAT credentials");
+    };
     assert_eq!(token, "test_token");
     assert_eq!(username, Some("testuser".to_string()));
 }
@@ -4,10 +4,12 @@
 //! cargo test -p ampel-providers --test gitlab_remediation_tests
 //! ```
 
+use ampel_core::remediation::SuggestedChange;
+use ampel_providers::error::ProviderError;
 use ampel_providers::gitlab::GitLabProvider;
 use ampel_providers::remediation::RemediationCapable;
 use ampel_providers::traits::ProviderCredentials;
-use wiremock::matchers::{method, path, query_param};
+use wiremock::matchers::{body_partial_json, method, path, query_param, query_param_contains};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn creds() -> ProviderCredentials {
@@ -88,6 +90,29 @@ async fn should_create_merge_request() {
     assert_eq!(mr.state, "open");
 }
 
+#[tokio::test]
+async fn should_find_open_merge_request_by_source_branch() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/acme%2Fwidget/merge_requests"))
+        .and(query_param("state", "opened"))
+        .and(query_param("source_branch", "ampel/remediation/run"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+
+    // Act
+    let mr = provider
+        .find_open_pull_request_by_head(&creds(), "acme", "widget", "ampel/remediation/run")
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(mr.is_none());
+}
+
 #[tokio::test]
 async fn should_update_branch_via_mr_rebase_lookup() {
     // Arrange — lookup returns one open MR, then rebase succeeds.
@@ -183,6 +208,193 @@ async fn should_create_note_and_return_id() {
     assert_eq!(id, 888);
 }
 
+#[tokio::test]
+async fn should_list_note_bodies_oldest_first() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/api/v4/projects/acme%2Fwidget/merge_requests/5/notes",
+        ))
+        .and(query_param("sort", "asc"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "id": 1,
+                "author": { "id": 2, "username": "ampel", "avatar_url": null },
+                "body": "Superseded by !10",
+                "created_at": "2024-01-01T00:00:00Z",
+                "type": null
+            }])),
+        )
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+
+    // Act
+    let comments = provider
+        .list_comments(&creds(), "acme", "widget", 5)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(comments, vec!["Superseded by !10"]);
+}
+
+#[tokio::test]
+async fn should_post_each_suggestion_as_a_diff_discussion() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/acme%2Fwidget/merge_requests/5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "diff_refs": { "base_sha": "b1", "start_sha": "s1", "head_sha": "h1" }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/acme%2Fwidget/merge_requests/5/diffs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "new_path": "src/lib.rs", "diff": "@@ -11,3 +11,3 @@\n ctx\n-let x = 1;\n+let x = 2;\n let y = 3;\n" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/api/v4/projects/acme%2Fwidget/merge_requests/5/discussions",
+        ))
+        .and(body_partial_json(serde_json::json!({
+            "body": "```suggestion:-1+0\nlet x = 2;\nlet y = 3;\n```",
+            "position": {
+                "position_type": "text",
+                "base_sha": "b1",
+                "start_sha": "s1",
+                "head_sha": "h1",
+                "new_path": "src/lib.rs",
+                "old_line": 13,
+                "new_line": 13
+            }
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": "d1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+    let suggestions = vec![SuggestedChange {
+        path: "src/lib.rs".into(),
+        start_line: 12,
+        end_line: 13,
+        replacement: "let x = 2;\nlet y = 3;".into(),
+        diff: String::new(),
+    }];
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 5, &suggestions)
+        .await;
+
+    // Assert
+    assert!(result.is_ok(), "{result:?}");
+}
+
+#[tokio::test]
+async fn should_post_suggestions_outside_the_mr_diff_as_diff_notes() {
+    // Arrange: the MR only changes src/lib.rs around line 12.
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/acme%2Fwidget/merge_requests/5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "diff_refs": { "base_sha": "b1", "start_sha": "s1", "head_sha": "h1" }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/acme%2Fwidget/merge_requests/5/diffs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "new_path": "src/lib.rs", "diff": "@@ -11,3 +11,3 @@\n ctx\n-let x = 1;\n+let x = 2;\n let y = 3;\n" }
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/api/v4/projects/acme%2Fwidget/merge_requests/5/discussions",
+        ))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": "d1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/api/v4/projects/acme%2Fwidget/merge_requests/5/notes",
+        ))
+        .and(query_param_contains("body", "+++ b/README.md"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 9 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+    let suggestions = vec![
+        SuggestedChange {
+            path: "src/lib.rs".into(),
+            start_line: 12,
+            end_line: 12,
+            replacement: "let x = 3;".into(),
+            diff: String::new(),
+        },
+        SuggestedChange {
+            path: "README.md".into(),
+            start_line: 1,
+            end_line: 1,
+            replacement: "# Widget".into(),
+            diff: "--- a/README.md\n+++ b/README.md\n@@ -1,1 +1,1 @@\n-# widget\n+# Widget\n"
+                .into(),
+        },
+    ];
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 5, &suggestions)
+        .await;
+
+    // Assert: the added line is anchored on its new side only.
+    assert!(result.is_ok(), "{result:?}");
+    let requests = server.received_requests().await.unwrap();
+    let discussion: serde_json::Value = requests
+        .iter()
+        .find(|r| r.url.path().ends_with("/discussions"))
+        .unwrap()
+        .body_json()
+        .unwrap();
+    assert_eq!(discussion["position"]["new_line"], 12);
+    assert!(discussion["position"].get("old_line").is_none());
+}
+
+#[tokio::test]
+async fn should_reject_suggestions_on_merge_request_without_diff_refs() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v4/projects/acme%2Fwidget/merge_requests/5"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+    let suggestions = vec![SuggestedChange {
+        path: "a.txt".into(),
+        start_line: 1,
+        end_line: 1,
+        replacement: "b".into(),
+        diff: String::new(),
+    }];
+
+    // Act
+    let result = provider
+        .create_suggestions(&creds(), "acme", "widget", 5, &suggestions)
+        .await;
+
+    // Assert
+    assert!(matches!(result, Err(ProviderError::InvalidResponse(_))));
+}
+
 #[tokio::test]
 async fn should_add_labels_comma_joined() {
     // Arrange
@@ -249,6 +249,7 @@ async fn test_mock_list_pull_requests() {
             state: "open".to_string(),
             source_branch: "feature-x".to_string(),
             target_branch: "main".to_string(),
+            head_sha: None,
             author: "developer1".to_string(),
             author_avatar_url: Some("https://example.com/avatar1.png".to_string()),
             is_draft: false,
@@ -263,6 +264,10 @@ async fn test_mock_list_pull_requests() {
             updated_at: now,
             merged_at: None,
             closed_at: None,
+            requested_reviewers: Vec::new(),
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
         },
         ProviderPullRequest {
             provider_id: "pr456".to_string(),
@@ -273,6 +278,7 @@ async fn test_mock_list_pull_requests() {
             state: "open".to_string(),
             source_branch: "fix-bug-y".to_string(),
             target_branch: "main".to_string(),
+            head_sha: None,
             author: "developer2".to_string(),
             author_avatar_url: None,
             is_draft: true,
@@ -287,6 +293,10 @@ async fn test_mock_list_pull_requests() {
             updated_at: now,
             merged_at: None,
             closed_at: None,
+            requested_reviewers: Vec::new(),
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
         },
     ];
 
@@ -305,6 +315,54 @@ async fn test_mock_list_pull_requests() {
     assert!(result[1].has_conflicts);
 }
 
+#[tokio::test]
+async fn test_mock_list_pull_requests_beyond_one_page() {
+    let now = Utc::now();
+
+    // More PRs than a single provider page (100) must all come back
+    let prs: Vec<ProviderPullRequest> = (1..=250)
+        .map(|number| ProviderPullRequest {
+            provider_id: format!("pr{}", number),
+            number,
+            title: format!("PR {}", number),
+            description: None,
+            url: format!("https://github.com/testorg/repo1/pull/{}", number),
+            state: "open".to_string(),
+            source_branch: format!("branch-{}", number),
+            target_branch: "main".to_string(),
+            head_sha: None,
+            author: "developer1".to_string(),
+            author_avatar_url: None,
+            is_draft: false,
+            is_mergeable: Some(true),
+            has_conflicts: false,
+            additions: 0,
+            deletions: 0,
+            changed_files: 0,
+            commits_count: 0,
+            comments_count: 0,
+            created_at: now,
+            updated_at: now,
+            merged_at: None,
+            closed_at: None,
+            requested_reviewers: Vec::new(),
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
+        })
+        .collect();
+
+    let mock = MockProvider::new().with_pull_requests("testorg", "repo1", prs);
+
+    let result = mock
+        .list_pull_requests(&test_credentials(), "testorg", "repo1", Some("open"))
+        .await
+        .unwrap();
+
+    assert_eq!(result.len(), 250);
+    assert_eq!(result.last().map(|pr| pr.number), Some(250));
+}
+
 #[tokio::test]
 async fn test_mock_list_pull_requests_empty() {
     let mock = MockProvider::new();
@@ -351,6 +409,7 @@ async fn test_mock_get_pull_request() {
         state: "open".to_string(),
         source_branch: "feature".to_string(),
         target_branch: "main".to_string(),
+        head_sha: None,
         author: "developer".to_string(),
         author_avatar_url: None,
         is_draft: false,
@@ -365,6 +424,10 @@ async fn test_mock_get_pull_request() {
         updated_at: now,
         merged_at: None,
         closed_at: None,
+        requested_reviewers: Vec::new(),
+        labels: Vec::new(),
+        assignees: Vec::new(),
+        milestone: None,
     }];
 
     let mock = MockProvider::new().with_pull_requests("testorg", "repo1", prs);
@@ -464,6 +527,7 @@ async fn test_mock_get_reviews() {
             reviewer: "reviewer1".to_string(),
             reviewer_avatar_url: Some("https://example.com/avatar1.png".to_string()),
             state: "approved".to_string(),
+            commit_sha: None,
             body: Some("LGTM!".to_string()),
             submitted_at: now,
         },
@@ -472,6 +536,7 @@ async fn test_mock_get_reviews() {
             reviewer: "reviewer2".to_string(),
             reviewer_avatar_url: None,
             state: "changes_requested".to_string(),
+            commit_sha: None,
             body: Some("Please fix the tests".to_string()),
             submitted_at: now,
         },
@@ -670,6 +735,7 @@ async fn test_mock_multiple_configurations() {
                 state: "opened".to_string(),
                 source_branch: "feature".to_string(),
                 target_branch: "main".to_string(),
+                head_sha: None,
                 author: "dev".to_string(),
                 author_avatar_url: None,
                 is_draft: false,
@@ -684,6 +750,10 @@ async fn test_mock_multiple_configurations() {
                 updated_at: now,
                 merged_at: None,
                 closed_at: None,
+                requested_reviewers: Vec::new(),
+                labels: Vec::new(),
+                assignees: Vec::new(),
+                milestone: None,
             }],
         );
 
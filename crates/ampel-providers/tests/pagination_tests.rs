@@ -0,0 +1,464 @@
+//! Wiremock-backed tests for list pagination and ETag conditional requests.
+//!
+//! Each test serves a multi-page collection the way the provider does (GitHub
+//! `Link` headers, GitLab `X-Next-Page`, Bitbucket's `next` field) and asserts
+//! that the provider returns every page, never follows a link off the
+//! provider's origin, and stops at the page cap. The ETag tests assert that a
+//! repeated read revalidates with `If-None-Match` and is served from the cache
+//! on `304`, and that an exhausted quota surfaces as a rate-limit error with
+//! its reset time.
+//!
+//! ```bash
+//! cargo test -p ampel-providers --test pagination_tests
+//! ```
+
+use std::sync::Arc;
+
+use ampel_core::models::GitProvider as Provider;
+use ampel_providers::bitbucket::BitbucketProvider;
+use ampel_providers::github::GitHubProvider;
+use ampel_providers::gitlab::GitLabProvider;
+use ampel_providers::http::MAX_PAGES;
+use ampel_providers::traits::{GitProvider, ProviderCredentials};
+use ampel_providers::{ProviderError, ProviderFactory, ResponseCache};
+use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn creds() -> ProviderCredentials {
+    ProviderCredentials::Pat {
+        token: "test_token".to_string(),
+        username: Some("tester".to_string()),
+    }
+}
+
+fn github_pr(number: i32) -> serde_json::Value {
+    serde_json::json!({
+        "id": 1000 + number, "number": number, "title": format!("PR {}", number),
+        "body": null, "html_url": format!("https://github.com/acme/widget/pull/{}", number),
+        "state": "open", "draft": false, "mergeable": true, "mergeable_state": "clean",
+        "head": { "ref": format!("feature-{}", number) }, "base": { "ref": "main" },
+        "user": { "login": "dev", "avatar_url": null },
+        "created_at": "2026-01-01T00:00:00Z", "updated_at": "2026-01-01T00:00:00Z",
+        "merged_at": null, "closed_at": null
+    })
+}
+
+fn gitlab_mr(iid: i32) -> serde_json::Value {
+    serde_json::json!({
+        "id": 2000 + iid, "iid": iid, "title": format!("MR {}", iid), "description": null,
+        "web_url": format!("https://gitlab.com/acme/widget/-/merge_requests/{}", iid),
+        "state": "opened", "source_branch": format!("feature-{}", iid),
+        "target_branch": "main", "author": { "username": "dev", "avatar_url": null },
+        "draft": false, "merge_status": "can_be_merged", "has_conflicts": false,
+        "changes_count": "1", "user_notes_count": 0,
+        "created_at": "2026-01-01T00:00:00Z", "updated_at": "2026-01-01T00:00:00Z",
+        "merged_at": null, "closed_at": null
+    })
+}
+
+fn bitbucket_pr(id: i32) -> serde_json::Value {
+    serde_json::json!({
+        "id": id, "title": format!("PR {}", id), "description": null,
+        "links": { "html": { "href": format!("https://bitbucket.org/acme/widget/pull-requests/{}", id) } },
+        "state": "OPEN",
+        "source": { "branch": { "name": format!("feature-{}", id) } },
+        "destination": { "branch": { "name": "main" } },
+        "author": { "username": "dev", "display_name": "Dev", "links": null },
+        "created_on": "2026-01-01T00:00:00Z", "updated_on": "2026-01-01T00:00:00Z",
+        "merge_commit": null, "closed_by": null, "comment_count": 0
+    })
+}
+
+#[tokio::test]
+async fn should_follow_github_link_header_across_pages() {
+    // Arrange
+    let server = MockServer::start().await;
+    let next = format!(
+        "<{}/repos/acme/widget/pulls?state=open&per_page=100&page=2>; rel=\"next\"",
+        server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .and(query_param_is_missing("page"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", next.as_str())
+                .set_body_json(serde_json::json!([github_pr(1), github_pr(2)])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([github_pr(3)])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let prs = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap();
+
+    // Assert
+    let numbers: Vec<i32> = prs.iter().map(|pr| pr.number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn should_reject_next_link_to_another_origin() {
+    // Arrange: the next link points at a different server
+    let server = MockServer::start().await;
+    let elsewhere = MockServer::start().await;
+    let next = format!(
+        "<{}/repos/acme/widget/pulls?page=2>; rel=\"next\"",
+        elsewhere.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", next.as_str())
+                .set_body_json(serde_json::json!([github_pr(1)])),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(0)
+        .mount(&elsewhere)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let result = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await;
+
+    // Assert
+    assert!(matches!(result, Err(ProviderError::InvalidResponse(_))));
+}
+
+#[tokio::test]
+async fn should_stop_at_page_cap_with_what_was_fetched() {
+    // Arrange: every page links to another page
+    let server = MockServer::start().await;
+    let next = format!(
+        "<{}/repos/acme/widget/pulls?page=again>; rel=\"next\"",
+        server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", next.as_str())
+                .set_body_json(serde_json::json!([github_pr(1)])),
+        )
+        .expect(MAX_PAGES as u64)
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let prs = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(prs.len(), MAX_PAGES);
+}
+
+#[tokio::test]
+async fn should_follow_gitlab_next_page_header() {
+    // Arrange
+    let server = MockServer::start().await;
+    let mrs_path = "/api/v4/projects/acme%2Fwidget/merge_requests";
+    Mock::given(method("GET"))
+        .and(path(mrs_path))
+        .and(query_param_is_missing("page"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Next-Page", "2")
+                .set_body_json(serde_json::json!([gitlab_mr(1)])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(mrs_path))
+        .and(query_param("page", "2"))
+        .and(query_param("state", "opened"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Next-Page", "")
+                .set_body_json(serde_json::json!([gitlab_mr(2)])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = GitLabProvider::new(Some(server.uri()));
+
+    // Act
+    let prs = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap();
+
+    // Assert
+    let numbers: Vec<i32> = prs.iter().map(|pr| pr.number).collect();
+    assert_eq!(numbers, vec![1, 2]);
+    assert!(prs.iter().all(|pr| pr.state == "open"));
+}
+
+#[tokio::test]
+async fn should_follow_bitbucket_next_field() {
+    // Arrange
+    let server = MockServer::start().await;
+    let prs_path = "/repositories/acme/widget/pullrequests";
+    Mock::given(method("GET"))
+        .and(path(prs_path))
+        .and(query_param_is_missing("page"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "values": [bitbucket_pr(1), bitbucket_pr(2)],
+            "next": format!("{}{}?state=OPEN&pagelen=50&page=2", server.uri(), prs_path)
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(prs_path))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "values": [bitbucket_pr(3)]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = BitbucketProvider::new(Some(server.uri()));
+
+    // Act
+    let prs = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap();
+
+    // Assert
+    let numbers: Vec<i32> = prs.iter().map(|pr| pr.number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn should_fail_list_when_a_later_page_errors() {
+    // Arrange
+    let server = MockServer::start().await;
+    let next = format!(
+        "<{}/repos/acme/widget/pulls?page=2>; rel=\"next\"",
+        server.uri()
+    );
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .and(query_param_is_missing("page"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", next.as_str())
+                .set_body_json(serde_json::json!([github_pr(1)])),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(502))
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let result = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await;
+
+    // Assert
+    assert!(
+        result.is_err(),
+        "A partial list must not be mistaken for the full set"
+    );
+}
+
+#[tokio::test]
+async fn should_serve_not_modified_response_from_etag_cache() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/7"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/7"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(github_pr(7)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let cache = Arc::new(ResponseCache::new());
+    let provider = GitHubProvider::new(Some(server.uri())).with_cache(cache.clone());
+
+    // Act
+    let first = provider
+        .get_pull_request(&creds(), "acme", "widget", 7)
+        .await
+        .unwrap();
+    let second = provider
+        .get_pull_request(&creds(), "acme", "widget", 7)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(first.title, second.title);
+    assert_eq!(second.number, 7);
+    assert_eq!(cache.hits(), 1);
+}
+
+#[tokio::test]
+async fn should_revalidate_every_cached_page() {
+    // Arrange
+    let server = MockServer::start().await;
+    let mrs_path = "/api/v4/projects/acme%2Fwidget/merge_requests";
+    Mock::given(method("GET"))
+        .and(path(mrs_path))
+        .and(header("If-None-Match", "\"p1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(mrs_path))
+        .and(header("If-None-Match", "\"p2\""))
+        .respond_with(ResponseTemplate::new(304))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(mrs_path))
+        .and(query_param_is_missing("page"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"p1\"")
+                .insert_header("X-Next-Page", "2")
+                .set_body_json(serde_json::json!([gitlab_mr(1)])),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(mrs_path))
+        .and(query_param("page", "2"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"p2\"")
+                .set_body_json(serde_json::json!([gitlab_mr(2)])),
+        )
+        .mount(&server)
+        .await;
+    let factory = ProviderFactory::new();
+    let provider = factory.create(Provider::GitLab, Some(server.uri()));
+
+    // Act
+    let first = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap();
+    // A fresh provider from the same factory shares the cache
+    let second = factory
+        .create(Provider::GitLab, Some(server.uri()))
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(first.len(), 2);
+    assert_eq!(second.len(), 2, "Cached pages keep their next-page header");
+    assert_eq!(factory.cache().hits(), 2);
+}
+
+#[tokio::test]
+async fn should_not_share_cached_responses_between_credentials() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/7"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls/7"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(github_pr(7)),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let cache = Arc::new(ResponseCache::new());
+    let provider = GitHubProvider::new(Some(server.uri())).with_cache(cache.clone());
+    let other = ProviderCredentials::Pat {
+        token: "other_token".to_string(),
+        username: None,
+    };
+
+    // Act
+    provider
+        .get_pull_request(&creds(), "acme", "widget", 7)
+        .await
+        .unwrap();
+    provider
+        .get_pull_request(&other, "acme", "widget", 7)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(cache.hits(), 0);
+    assert_eq!(cache.len(), 2);
+}
+
+#[tokio::test]
+async fn should_report_exhausted_quota_as_rate_limit_with_reset() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/widget/pulls"))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .insert_header("X-RateLimit-Remaining", "0")
+                .insert_header("X-RateLimit-Reset", "1893456000"),
+        )
+        .mount(&server)
+        .await;
+    let provider = GitHubProvider::new(Some(server.uri()));
+
+    // Act
+    let err = provider
+        .list_pull_requests(&creds(), "acme", "widget", Some("open"))
+        .await
+        .unwrap_err();
+
+    // Assert
+    assert!(matches!(
+        err,
+        ampel_providers::ProviderError::RateLimitExceeded(_)
+    ));
+    assert_eq!(
+        err.rate_limit_reset().map(|reset| reset.timestamp()),
+        Some(1_893_456_000)
+    );
+}
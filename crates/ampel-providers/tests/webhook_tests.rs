@@ -0,0 +1,306 @@
+//! Payload normalisation tests for inbound provider webhooks.
+//!
+//! Each test feeds a trimmed-down but structurally faithful delivery body into
+//! the provider's `parse_webhook` and checks the resulting [`WebhookEvent`].
+
+use ampel_providers::{bitbucket, github, gitlab, WebhookEvent};
+use serde_json::json;
+
+#[test]
+fn github_pull_request_event_becomes_pull_request() {
+    let body = json!({
+        "action": "synchronize",
+        "pull_request": {
+            "id": 42, "number": 7, "title": "Bump serde", "body": null,
+            "html_url": "https://github.com/o/r/pull/7", "state": "open",
+            "draft": false, "mergeable": true, "mergeable_state": "clean",
+            "head": {"ref": "deps/serde"}, "base": {"ref": "main"},
+            "user": {"login": "dependabot[bot]", "avatar_url": null},
+            "additions": 3, "deletions": 1, "changed_files": 1, "commits": 1, "comments": 0,
+            "created_at": "2026-01-01T00:00:00Z", "updated_at": "2026-01-02T00:00:00Z",
+            "merged_at": null, "closed_at": null
+        }
+    });
+
+    let event = github::parse_webhook("pull_request", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::PullRequest(pr) = event else {
+        panic!("expected PullRequest, got {:?}", event);
+    };
+    assert_eq!(pr.number, 7);
+    assert_eq!(pr.provider_id, "42");
+    assert_eq!(pr.source_branch, "deps/serde");
+    assert_eq!(pr.author, "dependabot[bot]");
+    assert_eq!(pr.additions, 3);
+}
+
+#[test]
+fn github_pull_request_event_carries_labels_and_people() {
+    let body = json!({
+        "action": "labeled",
+        "pull_request": {
+            "id": 42, "number": 7, "title": "Bump serde", "body": null,
+            "html_url": "https://github.com/o/r/pull/7", "state": "open",
+            "head": {"ref": "deps/serde"}, "base": {"ref": "main"},
+            "user": {"login": "dependabot[bot]", "avatar_url": null},
+            "created_at": "2026-01-01T00:00:00Z", "updated_at": "2026-01-02T00:00:00Z",
+            "labels": [{"name": "security", "color": "d73a4a"}, {"name": "deps"}],
+            "assignees": [{"login": "alice", "avatar_url": null}],
+            "requested_reviewers": [{"login": "bob", "avatar_url": null}],
+            "milestone": {"title": "v1.2", "number": 3}
+        }
+    });
+
+    let event = github::parse_webhook("pull_request", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::PullRequest(pr) = event else {
+        panic!("expected PullRequest, got {:?}", event);
+    };
+    assert_eq!(pr.labels, vec!["security", "deps"]);
+    assert_eq!(pr.assignees, vec!["alice"]);
+    assert_eq!(pr.requested_reviewers, vec!["bob"]);
+    assert_eq!(pr.milestone.as_deref(), Some("v1.2"));
+}
+
+#[test]
+fn github_check_run_event_targets_listed_pull_requests() {
+    let body = json!({
+        "action": "completed",
+        "check_run": {
+            "name": "build", "status": "completed", "conclusion": "failure",
+            "html_url": "https://github.com/o/r/runs/1",
+            "started_at": "2026-01-01T00:00:00Z", "completed_at": "2026-01-01T00:02:00Z",
+            "pull_requests": [{"number": 7}],
+            "check_suite": {"head_branch": "deps/serde"}
+        }
+    });
+
+    let event = github::parse_webhook("check_run", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::Checks {
+        pr_numbers,
+        source_branch,
+        checks,
+    } = event
+    else {
+        panic!("expected Checks, got {:?}", event);
+    };
+    assert_eq!(pr_numbers, vec![7]);
+    assert_eq!(source_branch.as_deref(), Some("deps/serde"));
+    assert_eq!(checks[0].name, "build");
+    assert_eq!(checks[0].conclusion.as_deref(), Some("failure"));
+}
+
+#[test]
+fn github_review_event_lowercases_state() {
+    let body = json!({
+        "action": "submitted",
+        "review": {
+            "id": 99, "user": {"login": "alice", "avatar_url": null},
+            "state": "APPROVED", "body": "lgtm", "submitted_at": "2026-01-01T00:00:00Z"
+        },
+        "pull_request": {"number": 7}
+    });
+
+    let event = github::parse_webhook("pull_request_review", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::Review { pr_number, review } = event else {
+        panic!("expected Review, got {:?}", event);
+    };
+    assert_eq!(pr_number, 7);
+    assert_eq!(review.reviewer, "alice");
+    assert_eq!(review.state, "approved");
+}
+
+#[test]
+fn github_ping_is_ignored() {
+    let event = github::parse_webhook("ping", br#"{"zen":"Keep it simple."}"#).unwrap();
+    assert!(matches!(event, WebhookEvent::Ignored(_)));
+}
+
+#[test]
+fn gitlab_merge_request_hook_keeps_author_only_on_open() {
+    let mr = |action: &str| {
+        json!({
+            "object_kind": "merge_request",
+            "user": {"username": "bob", "avatar_url": null},
+            "object_attributes": {
+                "id": 1001, "iid": 12, "title": "Fix", "description": "",
+                "url": "https://gitlab.com/o/r/-/merge_requests/12", "state": "opened",
+                "source_branch": "fix", "target_branch": "main", "draft": false,
+                "merge_status": "can_be_merged", "detailed_merge_status": "mergeable",
+                "created_at": "2026-01-01 00:00:00 UTC", "updated_at": "2026-01-02 00:00:00 UTC",
+                "action": action
+            }
+        })
+        .to_string()
+    };
+
+    let opened = gitlab::parse_webhook("Merge Request Hook", mr("open").as_bytes()).unwrap();
+    let updated = gitlab::parse_webhook("Merge Request Hook", mr("update").as_bytes()).unwrap();
+
+    let (WebhookEvent::PullRequest(opened), WebhookEvent::PullRequest(updated)) = (opened, updated)
+    else {
+        panic!("expected PullRequest events");
+    };
+    assert_eq!(opened.author, "bob");
+    assert_eq!(opened.state, "open");
+    assert_eq!(opened.is_mergeable, Some(true));
+    assert_eq!(
+        opened.updated_at.to_rfc3339(),
+        "2026-01-02T00:00:00+00:00",
+        "legacy GitLab timestamps should parse"
+    );
+    assert!(updated.author.is_empty());
+}
+
+#[test]
+fn gitlab_merge_request_hook_carries_labels_and_people() {
+    let body = json!({
+        "object_kind": "merge_request",
+        "user": {"username": "bob", "avatar_url": null},
+        "object_attributes": {
+            "id": 1001, "iid": 12, "title": "Fix", "description": "",
+            "url": "https://gitlab.com/o/r/-/merge_requests/12", "state": "opened",
+            "source_branch": "fix", "target_branch": "main",
+            "created_at": "2026-01-01 00:00:00 UTC", "updated_at": "2026-01-02 00:00:00 UTC",
+            "milestone_id": 5, "action": "update"
+        },
+        "labels": [{"id": 1, "title": "backend"}],
+        "assignees": [{"username": "carol", "avatar_url": null}],
+        "reviewers": [{"username": "dave", "avatar_url": null}]
+    });
+
+    let event = gitlab::parse_webhook("Merge Request Hook", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::PullRequest(mr) = event else {
+        panic!("expected PullRequest, got {:?}", event);
+    };
+    assert_eq!(mr.labels, vec!["backend"]);
+    assert_eq!(mr.assignees, vec!["carol"]);
+    assert_eq!(mr.requested_reviewers, vec!["dave"]);
+    assert_eq!(mr.milestone, None, "hooks carry only the milestone id");
+}
+
+#[test]
+fn gitlab_approval_becomes_review() {
+    let body = json!({
+        "object_kind": "merge_request",
+        "user": {"username": "carol", "avatar_url": null},
+        "object_attributes": {
+            "id": 1001, "iid": 12, "title": "Fix", "description": null,
+            "url": "https://gitlab.com/o/r/-/merge_requests/12", "state": "opened",
+            "source_branch": "fix", "target_branch": "main",
+            "created_at": "2026-01-01T00:00:00Z", "updated_at": "2026-01-02T00:00:00Z",
+            "action": "approved"
+        }
+    });
+
+    let event = gitlab::parse_webhook("Merge Request Hook", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::Review { pr_number, review } = event else {
+        panic!("expected Review, got {:?}", event);
+    };
+    assert_eq!(pr_number, 12);
+    assert_eq!(review.reviewer, "carol");
+    assert_eq!(review.state, "approved");
+}
+
+#[test]
+fn gitlab_pipeline_hook_maps_jobs_to_checks() {
+    let body = json!({
+        "object_kind": "pipeline",
+        "object_attributes": {"id": 5, "ref": "fix", "status": "failed"},
+        "merge_request": {"iid": 12},
+        "builds": [
+            {"name": "test", "status": "failed", "started_at": null, "finished_at": null},
+            {"name": "lint", "status": "running", "started_at": null, "finished_at": null}
+        ]
+    });
+
+    let event = gitlab::parse_webhook("Pipeline Hook", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::Checks {
+        pr_numbers, checks, ..
+    } = event
+    else {
+        panic!("expected Checks, got {:?}", event);
+    };
+    assert_eq!(pr_numbers, vec![12]);
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0].conclusion.as_deref(), Some("failure"));
+    assert_eq!(checks[1].status, "in_progress");
+}
+
+#[test]
+fn gitlab_pipeline_without_merge_request_is_ignored() {
+    let body = json!({
+        "object_kind": "pipeline",
+        "object_attributes": {"id": 5, "ref": "main", "status": "success"},
+        "merge_request": null,
+        "builds": []
+    });
+
+    let event = gitlab::parse_webhook("Pipeline Hook", body.to_string().as_bytes()).unwrap();
+    assert!(matches!(event, WebhookEvent::Ignored(_)));
+}
+
+#[test]
+fn bitbucket_fulfilled_pull_request_is_merged() {
+    let body = json!({
+        "pullrequest": {
+            "id": 3, "title": "Feature", "description": null,
+            "links": {"html": {"href": "https://bitbucket.org/o/r/pull-requests/3"}},
+            "state": "MERGED",
+            "source": {"branch": {"name": "feature"}},
+            "destination": {"branch": {"name": "main"}},
+            "author": {"display_name": "Dana", "links": null},
+            "created_on": "2026-01-01T00:00:00Z", "updated_on": "2026-01-03T00:00:00Z",
+            "comment_count": 2
+        }
+    });
+
+    let event =
+        bitbucket::parse_webhook("pullrequest:fulfilled", body.to_string().as_bytes()).unwrap();
+
+    let WebhookEvent::PullRequest(pr) = event else {
+        panic!("expected PullRequest, got {:?}", event);
+    };
+    assert_eq!(pr.number, 3);
+    assert_eq!(pr.state, "merged");
+    assert!(pr.merged_at.is_some());
+    assert_eq!(pr.author, "Dana");
+}
+
+#[test]
+fn bitbucket_commit_status_matches_by_branch() {
+    let body = json!({
+        "commit_status": {
+            "key": "ci-build", "name": "CI build", "state": "SUCCESSFUL",
+            "url": "https://ci.example.com/1", "refname": "feature",
+            "created_on": "2026-01-01T00:00:00Z", "updated_on": "2026-01-01T00:05:00Z"
+        }
+    });
+
+    let event = bitbucket::parse_webhook("repo:commit_status_updated", body.to_string().as_bytes())
+        .unwrap();
+
+    let WebhookEvent::Checks {
+        pr_numbers,
+        source_branch,
+        checks,
+    } = event
+    else {
+        panic!("expected Checks, got {:?}", event);
+    };
+    assert!(pr_numbers.is_empty());
+    assert_eq!(source_branch.as_deref(), Some("feature"));
+    assert_eq!(checks[0].name, "CI build");
+    assert_eq!(checks[0].conclusion.as_deref(), Some("success"));
+    assert!(checks[0].completed_at.is_some());
+}
+
+#[test]
+fn bitbucket_malformed_payload_is_an_error() {
+    assert!(bitbucket::parse_webhook("pullrequest:created", b"{}").is_err());
+}
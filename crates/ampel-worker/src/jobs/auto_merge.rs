@@ -0,0 +1,649 @@
+use chrono::{DateTime, Utc};
+use rust_i18n::t;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+use ampel_core::models::{
+    AmpelStatus, CICheck, CICheckConclusion, CICheckStatus, GitProvider, MergeRequest,
+    MergeStrategy, PullRequest, Review, ReviewState, StatusPolicy,
+};
+use ampel_core::services::PrService;
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::{auto_merge_rule, provider_account, pull_request, repository};
+use ampel_db::queries::{
+    CICheckQueries, MergeOperationItemQueries, MergeOperationQueries, PrFilterQueries,
+    PrMetadataQueries, PrQueries, RepoQueries, ReviewQueries, StatusPolicyQueries,
+};
+use ampel_providers::traits::ProviderCredentials;
+use ampel_providers::{GitProvider as GitProviderTrait, ProviderFactory};
+
+use crate::services::account_credentials::account_credentials;
+use crate::services::pr_events::record_pr_closed;
+
+/// `merge_operations.trigger` value for operations created by this job.
+pub const AUTO_MERGE_TRIGGER: &str = "auto_merge";
+
+/// Outcome of evaluating one open PR against its repository's auto-merge rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoMergeDecision {
+    /// Every rule condition holds; merge now.
+    Merge,
+    /// Eligible, but not ready yet (CI pending, awaiting approval, draft).
+    /// Re-evaluated on the next run.
+    Wait(String),
+    /// Not eligible under this rule (not a bot author, or blocked).
+    Skip(String),
+}
+
+/// Worker-side view of an `auto_merge_rule` row with `bot_authors` decoded.
+#[derive(Debug, Clone)]
+pub struct AutoMergeRuleConfig {
+    pub bot_authors: Vec<String>,
+    pub require_all_checks: bool,
+    pub require_approval: bool,
+    pub merge_strategy: MergeStrategy,
+    pub delete_branch: bool,
+}
+
+impl From<&auto_merge_rule::Model> for AutoMergeRuleConfig {
+    fn from(rule: &auto_merge_rule::Model) -> Self {
+        Self {
+            bot_authors: serde_json::from_str(&rule.bot_authors).unwrap_or_default(),
+            require_all_checks: rule.require_all_checks,
+            require_approval: rule.require_approval,
+            merge_strategy: match rule.merge_strategy.as_str() {
+                "merge" => MergeStrategy::Merge,
+                "rebase" => MergeStrategy::Rebase,
+                _ => MergeStrategy::Squash,
+            },
+            delete_branch: rule.delete_branch,
+        }
+    }
+}
+
+impl AutoMergeRuleConfig {
+    /// Whether `author` is a bot covered by this rule.
+    ///
+    /// The author must look like a bot to [`PrService::is_bot_author`]; when
+    /// the rule lists `bot_authors`, it must also be one of them. An empty list
+    /// accepts any bot.
+    pub fn matches_author(&self, author: &str) -> bool {
+        PrService::is_bot_author(author)
+            && (self.bot_authors.is_empty()
+                || self
+                    .bot_authors
+                    .iter()
+                    .any(|b| b.eq_ignore_ascii_case(author)))
+    }
+
+    /// Decide whether a PR should be merged under this rule, judging its
+    /// status by the repository's status policy.
+    pub fn evaluate(
+        &self,
+        pr: &PullRequest,
+        ci_checks: &[CICheck],
+        reviews: &[Review],
+        policy: &StatusPolicy,
+    ) -> AutoMergeDecision {
+        if !self.matches_author(&pr.author) {
+            return AutoMergeDecision::Skip(format!("author '{}' not covered by rule", pr.author));
+        }
+
+        if pr.is_draft {
+            return AutoMergeDecision::Wait("draft".to_string());
+        }
+
+        // Red covers conflicts, failed checks and requested changes
+        if AmpelStatus::evaluate(pr, ci_checks, reviews, policy).status == AmpelStatus::Red {
+            return AutoMergeDecision::Skip("status is red".to_string());
+        }
+
+        if self.require_all_checks {
+            if ci_checks.is_empty() {
+                return AutoMergeDecision::Wait("no CI checks reported".to_string());
+            }
+            let all_passed = ci_checks.iter().all(|c| {
+                c.status == CICheckStatus::Completed
+                    && matches!(
+                        c.conclusion,
+                        Some(
+                            CICheckConclusion::Success
+                                | CICheckConclusion::Neutral
+                                | CICheckConclusion::Skipped
+                        )
+                    )
+            });
+            if !all_passed {
+                return AutoMergeDecision::Wait("CI checks pending".to_string());
+            }
+        }
+
+        if self.require_approval && !reviews.iter().any(|r| r.state == ReviewState::Approved) {
+            return AutoMergeDecision::Wait("awaiting approval".to_string());
+        }
+
+        AutoMergeDecision::Merge
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoMergeJob;
+
+impl From<DateTime<Utc>> for AutoMergeJob {
+    fn from(_: DateTime<Utc>) -> Self {
+        Self
+    }
+}
+
+impl AutoMergeJob {
+    pub async fn execute(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+    ) -> anyhow::Result<()> {
+        let rules = auto_merge_rule::Entity::find()
+            .filter(auto_merge_rule::Column::Enabled.eq(true))
+            .all(db)
+            .await?;
+
+        tracing::info!("Evaluating {} auto-merge rules", rules.len());
+
+        for rule in rules {
+            let Some(repo) = RepoQueries::find_by_id(db, rule.repository_id).await? else {
+                continue;
+            };
+            if repo.is_archived {
+                continue;
+            }
+
+            if let Err(e) = self
+                .run_for_repository(db, encryption_service, provider_factory, &repo, &rule)
+                .await
+            {
+                tracing::error!(
+                    "Auto-merge failed for repository {}/{}: {}",
+                    repo.owner,
+                    repo.name,
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_for_repository(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+        repo: &repository::Model,
+        rule: &auto_merge_rule::Model,
+    ) -> anyhow::Result<()> {
+        let provider_type: GitProvider = repo
+            .provider
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!(e))?;
+
+        let account_id = repo
+            .provider_account_id
+            .ok_or_else(|| anyhow::anyhow!(t!("providers.worker.no_account")))?;
+
+        let account = provider_account::Entity::find_by_id(account_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(t!("providers.worker.account_not_found")))?;
+
+        let credentials =
+            account_credentials(db, encryption_service, provider_factory, &account).await?;
+
+        let provider = provider_factory.create(provider_type, account.instance_url.clone());
+
+        self.process_repository(db, provider.as_ref(), &credentials, repo, rule)
+            .await
+    }
+
+    /// Evaluate every open PR of `repo` against `rule` and merge the ready ones.
+    ///
+    /// Each run's decisions are recorded as one `merge_operation` (trigger
+    /// `auto_merge`) owned by the repository owner: an item per merged PR
+    /// holding the merge result, and a `skipped` or `waiting` item with the
+    /// reason for each held PR whose decision changed since the last run.
+    /// Only PRs whose author the rule covers are considered.
+    pub async fn process_repository(
+        &self,
+        db: &DatabaseConnection,
+        provider: &dyn GitProviderTrait,
+        credentials: &ProviderCredentials,
+        repo: &repository::Model,
+        rule: &auto_merge_rule::Model,
+    ) -> anyhow::Result<()> {
+        let config = AutoMergeRuleConfig::from(rule);
+        // PRs by authors the rule doesn't cover are never its concern; leave
+        // them out rather than recording a skip for each on every run
+        let open_prs: Vec<_> = PrQueries::find_open_by_repository(db, repo.id)
+            .await?
+            .into_iter()
+            .filter(|pr| config.matches_author(&pr.author))
+            .collect();
+        let policy = StatusPolicyQueries::policy_for_repository(db, repo.id).await?;
+        let skip_labels = PrFilterQueries::skip_labels_for_user(db, repo.user_id).await?;
+        let pr_ids: Vec<_> = open_prs.iter().map(|pr| pr.id).collect();
+        let metadata = PrMetadataQueries::for_pull_requests(db, &pr_ids).await?;
+
+        let mut ready = Vec::new();
+        // PRs not merged this run, with the item status and reason to record
+        let mut held = Vec::new();
+        for pr in open_prs {
+            let skip_label = metadata.get(&pr.id).and_then(|m| {
+                m.labels
+                    .iter()
+                    .find(|label| skip_labels.iter().any(|s| s.eq_ignore_ascii_case(label)))
+            });
+            if let Some(label) = skip_label {
+                tracing::debug!(
+                    "Not auto-merging PR #{} in {}: labelled {}",
+                    pr.number,
+                    repo.full_name,
+                    label
+                );
+                let reason = format!("labelled {}", label);
+                held.push((pr, "skipped", reason));
+                continue;
+            }
+
+            let checks: Vec<CICheck> = CICheckQueries::find_by_pull_request(db, pr.id)
+                .await?
+                .into_iter()
+                .map(|c| c.into())
+                .collect();
+            let reviews: Vec<Review> = ReviewQueries::find_latest_by_pull_request(db, pr.id)
+                .await?
+                .into_iter()
+                .map(|r| r.into())
+                .collect();
+
+            let pr_model: PullRequest = pr.clone().into();
+            let (status, reason) = match config.evaluate(&pr_model, &checks, &reviews, &policy) {
+                AutoMergeDecision::Merge => {
+                    ready.push(pr);
+                    continue;
+                }
+                AutoMergeDecision::Wait(reason) => ("waiting", reason),
+                AutoMergeDecision::Skip(reason) => ("skipped", reason),
+            };
+            tracing::debug!(
+                "Not auto-merging PR #{} in {}: {}",
+                pr.number,
+                repo.full_name,
+                reason
+            );
+            held.push((pr, status, reason));
+        }
+
+        // Held PRs are re-evaluated every run; only record a decision when it
+        // differs from the last one recorded for the PR
+        let mut changed = Vec::new();
+        for (pr, status, reason) in held {
+            let last =
+                MergeOperationItemQueries::latest_for_pull_request(db, pr.id, AUTO_MERGE_TRIGGER)
+                    .await?;
+            let unchanged = last.is_some_and(|item| {
+                item.status == status && item.error_message.as_deref() == Some(reason.as_str())
+            });
+            if !unchanged {
+                changed.push((pr, status, reason));
+            }
+        }
+
+        if ready.is_empty() && changed.is_empty() {
+            return Ok(());
+        }
+
+        let operation = MergeOperationQueries::create(
+            db,
+            repo.user_id,
+            (ready.len() + changed.len()) as i32,
+            AUTO_MERGE_TRIGGER,
+        )
+        .await?;
+
+        let merge_request = MergeRequest {
+            strategy: config.merge_strategy,
+            commit_title: None,
+            commit_message: None,
+            delete_branch: config.delete_branch,
+        };
+
+        let mut success_count = 0;
+        let mut failed_count = 0;
+        let mut skipped_count = 0;
+
+        let merged_count = ready.len();
+        for (position, pr) in ready.into_iter().enumerate() {
+            let item = MergeOperationItemQueries::create(
+                db,
+                operation.id,
+                pr.id,
+                repo.id,
+                position as i32,
+            )
+            .await?;
+
+            let (status, message, sha) = self
+                .merge_one(db, provider, credentials, repo, &pr, &merge_request)
+                .await?;
+            match status {
+                "success" => success_count += 1,
+                "skipped" => skipped_count += 1,
+                _ => failed_count += 1,
+            }
+            MergeOperationItemQueries::update_status(db, item.id, status, message, sha).await?;
+        }
+
+        for (position, (pr, status, reason)) in (merged_count..).zip(changed) {
+            let item = MergeOperationItemQueries::create(
+                db,
+                operation.id,
+                pr.id,
+                repo.id,
+                position as i32,
+            )
+            .await?;
+            if status == "skipped" {
+                skipped_count += 1;
+            }
+            MergeOperationItemQueries::update_status(db, item.id, status, Some(reason), None)
+                .await?;
+        }
+
+        let final_status = if success_count == 0 && failed_count > 0 {
+            "failed"
+        } else {
+            "completed"
+        };
+
+        MergeOperationQueries::update_counts(
+            db,
+            operation.id,
+            success_count,
+            failed_count,
+            skipped_count,
+            final_status,
+        )
+        .await?;
+
+        tracing::info!(
+            "Auto-merge for {}: {} merged, {} failed, {} skipped",
+            repo.full_name,
+            success_count,
+            failed_count,
+            skipped_count
+        );
+
+        Ok(())
+    }
+
+    /// Merge a single PR after a fresh provider check that it is still open.
+    ///
+    /// Returns the item `(status, error_message, merge_sha)` to record.
+    async fn merge_one(
+        &self,
+        db: &DatabaseConnection,
+        provider: &dyn GitProviderTrait,
+        credentials: &ProviderCredentials,
+        repo: &repository::Model,
+        pr: &pull_request::Model,
+        merge_request: &MergeRequest,
+    ) -> anyhow::Result<(&'static str, Option<String>, Option<String>)> {
+        // The local row can lag the provider; don't merge something already closed
+        let fresh = match provider
+            .get_pull_request(credentials, &repo.owner, &repo.name, pr.number)
+            .await
+        {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                return Ok((
+                    "failed",
+                    Some(format!("Failed to verify PR state: {}", e)),
+                    None,
+                ))
+            }
+        };
+
+        if fresh.state != "open" {
+            let closed = PrQueries::update_state(
+                db,
+                pr.id,
+                fresh.state.clone(),
+                fresh.merged_at,
+                fresh.closed_at,
+            )
+            .await?;
+            record_pr_closed(repo, &closed).await;
+            return Ok(("skipped", Some(format!("PR is {}", fresh.state)), None));
+        }
+
+        match provider
+            .merge_pull_request(
+                credentials,
+                &repo.owner,
+                &repo.name,
+                pr.number,
+                merge_request,
+            )
+            .await
+        {
+            Ok(result) if result.merged => {
+                let now = Utc::now();
+                let merged =
+                    PrQueries::update_state(db, pr.id, "merged".to_string(), Some(now), Some(now))
+                        .await?;
+                record_pr_closed(repo, &merged).await;
+                tracing::info!("Auto-merged PR #{} in {}", pr.number, repo.full_name);
+                Ok(("success", None, result.sha))
+            }
+            Ok(result) => Ok(("failed", Some(result.message), None)),
+            Err(e) => Ok(("failed", Some(e.to_string()), None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn config(
+        bot_authors: &[&str],
+        require_all_checks: bool,
+        require_approval: bool,
+    ) -> AutoMergeRuleConfig {
+        AutoMergeRuleConfig {
+            bot_authors: bot_authors.iter().map(|s| s.to_string()).collect(),
+            require_all_checks,
+            require_approval,
+            merge_strategy: MergeStrategy::Squash,
+            delete_branch: true,
+        }
+    }
+
+    fn pr(author: &str) -> PullRequest {
+        let now = Utc::now();
+        PullRequest {
+            id: Uuid::new_v4(),
+            repository_id: Uuid::new_v4(),
+            provider: GitProvider::GitHub,
+            provider_id: "1".to_string(),
+            number: 1,
+            title: "Bump serde".to_string(),
+            description: None,
+            url: "https://github.com/o/r/pull/1".to_string(),
+            state: ampel_core::models::PullRequestState::Open,
+            source_branch: "dependabot/cargo/serde".to_string(),
+            target_branch: "main".to_string(),
+            head_sha: None,
+            milestone: None,
+            author: author.to_string(),
+            author_avatar_url: None,
+            is_draft: false,
+            is_mergeable: Some(true),
+            has_conflicts: false,
+            additions: 1,
+            deletions: 1,
+            changed_files: 1,
+            commits_count: 1,
+            comments_count: 0,
+            created_at: now,
+            updated_at: now,
+            merged_at: None,
+            closed_at: None,
+            last_synced_at: now,
+        }
+    }
+
+    fn check(status: CICheckStatus, conclusion: Option<CICheckConclusion>) -> CICheck {
+        CICheck {
+            id: Uuid::new_v4(),
+            pull_request_id: Uuid::new_v4(),
+            name: "ci".to_string(),
+            status,
+            conclusion,
+            url: None,
+            started_at: None,
+            completed_at: None,
+            duration_seconds: None,
+        }
+    }
+
+    fn review(state: ReviewState) -> Review {
+        Review {
+            id: Uuid::new_v4(),
+            pull_request_id: Uuid::new_v4(),
+            reviewer: "alice".to_string(),
+            reviewer_avatar_url: None,
+            state,
+            commit_sha: None,
+            body: None,
+            submitted_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn should_only_match_listed_bot_authors() {
+        let rule = config(&["dependabot[bot]"], true, false);
+        assert!(rule.matches_author("dependabot[bot]"));
+        assert!(!rule.matches_author("renovate[bot]"));
+        assert!(!rule.matches_author("alice"));
+
+        let any_bot = config(&[], true, false);
+        assert!(any_bot.matches_author("renovate[bot]"));
+        assert!(!any_bot.matches_author("alice"));
+    }
+
+    #[test]
+    fn should_merge_bot_pr_with_green_checks_when_approval_not_required() {
+        let rule = config(&["dependabot[bot]"], true, false);
+        let checks = [check(
+            CICheckStatus::Completed,
+            Some(CICheckConclusion::Success),
+        )];
+
+        assert_eq!(
+            rule.evaluate(
+                &pr("dependabot[bot]"),
+                &checks,
+                &[],
+                &StatusPolicy::default()
+            ),
+            AutoMergeDecision::Merge
+        );
+    }
+
+    #[test]
+    fn should_wait_for_pending_or_missing_checks() {
+        let rule = config(&[], true, false);
+        let pending = [check(CICheckStatus::InProgress, None)];
+
+        assert!(matches!(
+            rule.evaluate(
+                &pr("dependabot[bot]"),
+                &pending,
+                &[],
+                &StatusPolicy::default()
+            ),
+            AutoMergeDecision::Wait(_)
+        ));
+        assert!(matches!(
+            rule.evaluate(&pr("dependabot[bot]"), &[], &[], &StatusPolicy::default()),
+            AutoMergeDecision::Wait(_)
+        ));
+    }
+
+    #[test]
+    fn should_wait_for_approval_when_required() {
+        let rule = config(&[], true, true);
+        let checks = [check(
+            CICheckStatus::Completed,
+            Some(CICheckConclusion::Success),
+        )];
+
+        assert!(matches!(
+            rule.evaluate(&pr("renovate[bot]"), &checks, &[], &StatusPolicy::default()),
+            AutoMergeDecision::Wait(_)
+        ));
+        assert_eq!(
+            rule.evaluate(
+                &pr("renovate[bot]"),
+                &checks,
+                &[review(ReviewState::Approved)],
+                &StatusPolicy::default()
+            ),
+            AutoMergeDecision::Merge
+        );
+    }
+
+    #[test]
+    fn should_skip_red_and_human_prs() {
+        let rule = config(&[], false, false);
+        let failed = [check(
+            CICheckStatus::Completed,
+            Some(CICheckConclusion::Failure),
+        )];
+
+        assert!(matches!(
+            rule.evaluate(
+                &pr("dependabot[bot]"),
+                &failed,
+                &[],
+                &StatusPolicy::default()
+            ),
+            AutoMergeDecision::Skip(_)
+        ));
+
+        let mut conflicted = pr("dependabot[bot]");
+        conflicted.has_conflicts = true;
+        assert!(matches!(
+            rule.evaluate(&conflicted, &[], &[], &StatusPolicy::default()),
+            AutoMergeDecision::Skip(_)
+        ));
+
+        assert!(matches!(
+            rule.evaluate(&pr("alice"), &[], &[], &StatusPolicy::default()),
+            AutoMergeDecision::Skip(_)
+        ));
+    }
+
+    #[test]
+    fn should_wait_on_drafts() {
+        let rule = config(&[], false, false);
+        let mut draft = pr("dependabot[bot]");
+        draft.is_draft = true;
+
+        assert!(matches!(
+            rule.evaluate(&draft, &[], &[], &StatusPolicy::default()),
+            AutoMergeDecision::Wait(_)
+        ));
+    }
+}
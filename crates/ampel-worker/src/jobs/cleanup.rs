@@ -3,6 +3,9 @@ use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 
 use ampel_db::entities::pull_request;
+use ampel_db::queries::{
+    ApiTokenQueries, NotificationLogQueries, OrganizationInvitationQueries, RefreshTokenQueries,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CleanupJob;
@@ -29,6 +32,30 @@ impl CleanupJob {
             deleted.rows_affected
         );
 
+        // Expired refresh tokens can no longer be presented
+        let expired_tokens = RefreshTokenQueries::delete_expired(db, Utc::now()).await?;
+        tracing::info!("Cleaned up {} expired refresh tokens", expired_tokens);
+
+        let expired_invitations =
+            OrganizationInvitationQueries::delete_expired(db, Utc::now()).await?;
+        tracing::info!(
+            "Cleaned up {} expired organization invitations",
+            expired_invitations
+        );
+
+        // API tokens stay listed for a while after they expire
+        let inactive_api_tokens = ApiTokenQueries::delete_inactive(db, cutoff).await?;
+        tracing::info!(
+            "Cleaned up {} revoked or expired API tokens",
+            inactive_api_tokens
+        );
+
+        let old_notifications = NotificationLogQueries::delete_finished_before(db, cutoff).await?;
+        tracing::info!(
+            "Cleaned up {} delivered or failed notifications",
+            old_notifications
+        );
+
         Ok(())
     }
 }
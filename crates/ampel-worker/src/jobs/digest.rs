@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+use ampel_core::models::{DeliveryChannel, DigestFrequency, DIGEST_NOTIFICATION_KIND};
+use ampel_db::entities::{notification_preferences, user};
+use ampel_db::queries::{NewNotification, NotificationLogQueries};
+
+use crate::services::digest::build_digest;
+use crate::services::notification_queue::email_configured;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestJob;
+
+impl From<DateTime<Utc>> for DigestJob {
+    fn from(_: DateTime<Utc>) -> Self {
+        Self
+    }
+}
+
+impl DigestJob {
+    /// Queue each subscribed user's digest for the period that last ended.
+    ///
+    /// Digests are keyed by frequency and period in the notification log, so
+    /// running this more than once per period queues nothing new; the
+    /// notification dispatch job sends what is queued.
+    pub async fn execute(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        self.execute_at(db, Utc::now()).await
+    }
+
+    /// Queue digests as if the current time were `now`
+    pub async fn execute_at(
+        &self,
+        db: &DatabaseConnection,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let subscribers = notification_preferences::Entity::find()
+            .filter(
+                notification_preferences::Column::DigestFrequency
+                    .ne(DigestFrequency::None.as_str()),
+            )
+            .filter(notification_preferences::Column::EmailEnabled.eq(true))
+            .all(db)
+            .await?;
+
+        let mut queued = 0;
+        for prefs in subscribers {
+            match self.queue_digest(db, &prefs, now).await {
+                Ok(true) => queued += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!(user_id = %prefs.user_id, "Failed to queue digest: {}", e);
+                }
+            }
+        }
+
+        if queued > 0 {
+            tracing::info!("Queued {} digests", queued);
+        }
+
+        Ok(())
+    }
+
+    async fn queue_digest(
+        &self,
+        db: &DatabaseConnection,
+        prefs: &notification_preferences::Model,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        let frequency: DigestFrequency = prefs.digest_frequency.parse()?;
+        let Some(period) = frequency.last_period(now) else {
+            return Ok(false);
+        };
+        if !email_configured(prefs) {
+            return Ok(false);
+        }
+
+        let channel = DeliveryChannel::Email.to_string();
+        let dedup_key = format!(
+            "{}:{}:{}",
+            DIGEST_NOTIFICATION_KIND,
+            frequency,
+            period.0.format("%Y-%m-%d")
+        );
+        if NotificationLogQueries::exists(db, prefs.user_id, &channel, &dedup_key).await? {
+            return Ok(false);
+        }
+
+        let locale = user::Entity::find_by_id(prefs.user_id)
+            .one(db)
+            .await?
+            .and_then(|u| u.language)
+            .unwrap_or_else(|| "en".to_string());
+        let digest = build_digest(db, prefs.user_id, frequency, period, &locale).await?;
+        if digest.is_empty() {
+            return Ok(false);
+        }
+
+        let queued = NotificationLogQueries::enqueue(
+            db,
+            NewNotification {
+                user_id: prefs.user_id,
+                pull_request_id: None,
+                kind: DIGEST_NOTIFICATION_KIND.to_string(),
+                channel,
+                channel_id: None,
+                dedup_key,
+                payload: serde_json::to_string(&digest)?,
+            },
+        )
+        .await?;
+        Ok(queued)
+    }
+}
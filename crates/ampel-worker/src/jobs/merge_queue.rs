@@ -0,0 +1,477 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_i18n::t;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+use ampel_core::models::{
+    AmpelStatus, CICheck, GitProvider, MergeRequest, MergeStrategy, NotificationKind, Review,
+};
+use ampel_core::services::{MergeNotificationPayload, MergeResultItem};
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::{
+    merge_operation, merge_operation_item, provider_account, pull_request, repository,
+};
+use ampel_db::queries::{
+    CICheckQueries, MergeOperationItemQueries, MergeOperationQueries, PrFilterQueries, PrQueries,
+    RepoQueries, ReviewQueries, StatusPolicyQueries,
+};
+use ampel_providers::traits::ProviderCredentials;
+use ampel_providers::{GitProvider as GitProviderTrait, ProviderError, ProviderFactory};
+
+use crate::services::account_credentials::account_credentials;
+use crate::services::notification_queue::{queue_notice, Notice};
+use crate::services::pr_events::record_pr_closed;
+
+use super::poll_repository::PollRepositoryJob;
+
+/// An `in_progress` operation whose heartbeat is older than this is assumed to
+/// belong to a dead worker and is reclaimed. Must comfortably exceed the
+/// longest gap between heartbeats (one merge plus `merge_delay_seconds`).
+pub const MERGE_QUEUE_LEASE_SECS: i64 = 300;
+
+/// Attempts per provider call before a transient error fails the item.
+pub const MAX_MERGE_ATTEMPTS: i32 = 3;
+
+/// Base delay for exponential backoff between transient-error retries.
+const RETRY_BASE_DELAY_MS: u64 = 500;
+
+/// Drains `queued` bulk merge operations created by the API.
+///
+/// Each item is re-checked against the provider immediately before merging, so
+/// a PR that was closed, went red or picked up conflicts while queued is
+/// skipped rather than merged on stale data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeQueueJob;
+
+impl From<DateTime<Utc>> for MergeQueueJob {
+    fn from(_: DateTime<Utc>) -> Self {
+        Self
+    }
+}
+
+impl MergeQueueJob {
+    pub async fn execute(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+    ) -> anyhow::Result<()> {
+        loop {
+            let stale_before = Utc::now() - Duration::seconds(MERGE_QUEUE_LEASE_SECS);
+            let Some(operation) = MergeOperationQueries::claim_next(db, stale_before).await? else {
+                return Ok(());
+            };
+
+            tracing::info!("Draining merge operation {}", operation.id);
+
+            if let Err(e) = self
+                .drain_operation(db, encryption_service, provider_factory, &operation)
+                .await
+            {
+                // Left in_progress; another worker reclaims it once the lease expires
+                tracing::error!("Merge operation {} failed: {}", operation.id, e);
+            }
+        }
+    }
+
+    /// Merge every pending item of a claimed operation in request order.
+    pub async fn drain_operation(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+        operation: &merge_operation::Model,
+    ) -> anyhow::Result<merge_operation::Model> {
+        let merge_request = MergeRequest {
+            strategy: match operation.strategy.as_deref() {
+                Some("merge") => MergeStrategy::Merge,
+                Some("rebase") => MergeStrategy::Rebase,
+                _ => MergeStrategy::Squash,
+            },
+            commit_title: None,
+            commit_message: None,
+            delete_branch: operation.delete_branch,
+        };
+        let merge_delay = StdDuration::from_secs(operation.merge_delay_seconds.max(0) as u64);
+        let skip_labels = PrFilterQueries::skip_labels_for_user(db, operation.user_id).await?;
+
+        // Repositories that already had a merge in this run, for merge_delay_seconds
+        let mut merged_repos: HashSet<uuid::Uuid> = HashSet::new();
+
+        let items = MergeOperationItemQueries::find_pending_by_operation(db, operation.id).await?;
+
+        for item in items {
+            let current = MergeOperationQueries::find_by_id(db, operation.id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Merge operation {} vanished", operation.id))?;
+            if current.cancel_requested {
+                MergeOperationItemQueries::cancel_pending(db, operation.id).await?;
+                tracing::info!("Merge operation {} cancelled", operation.id);
+                return Self::record_progress(db, operation.id, Some("cancelled")).await;
+            }
+            MergeOperationQueries::heartbeat(db, operation.id).await?;
+
+            if merged_repos.contains(&item.repository_id) && !merge_delay.is_zero() {
+                sleep(merge_delay).await;
+                MergeOperationQueries::heartbeat(db, operation.id).await?;
+            }
+
+            let (status, message, sha) = match self
+                .resolve_item(db, encryption_service, provider_factory, &item)
+                .await
+            {
+                Ok((provider, credentials, repo, pr)) => {
+                    self.process_item(
+                        db,
+                        provider.as_ref(),
+                        &credentials,
+                        &repo,
+                        &pr,
+                        &item,
+                        &merge_request,
+                        &skip_labels,
+                    )
+                    .await?
+                }
+                Err(e) => ("failed", Some(e.to_string()), None),
+            };
+
+            if status == "success" {
+                merged_repos.insert(item.repository_id);
+            }
+            MergeOperationItemQueries::update_status(db, item.id, status, message, sha).await?;
+            Self::record_progress(db, operation.id, None).await?;
+        }
+
+        let finished = Self::record_progress(db, operation.id, Some("finished")).await?;
+        if let Err(e) = Self::queue_notification(db, &finished).await {
+            // The merges happened; a missing notification must not undo that
+            tracing::warn!(
+                "Failed to queue notification for merge operation {}: {}",
+                operation.id,
+                e
+            );
+        }
+        Ok(finished)
+    }
+
+    /// Queue the merge summary for the user who started the operation, once.
+    async fn queue_notification(
+        db: &DatabaseConnection,
+        operation: &merge_operation::Model,
+    ) -> anyhow::Result<()> {
+        if operation.notification_sent {
+            return Ok(());
+        }
+
+        let mut results = Vec::new();
+        for item in MergeOperationItemQueries::find_by_operation(db, operation.id).await? {
+            let pr = PrQueries::find_by_id(db, item.pull_request_id).await?;
+            let repo = RepoQueries::find_by_id(db, item.repository_id).await?;
+            results.push(MergeResultItem {
+                repository: repo.map(|r| r.full_name).unwrap_or_default(),
+                pr_number: pr.as_ref().map(|pr| pr.number).unwrap_or_default(),
+                pr_title: pr.map(|pr| pr.title).unwrap_or_default(),
+                status: item.status,
+                error: item.error_message,
+            });
+        }
+        let payload = MergeNotificationPayload {
+            total: operation.total_count,
+            success: operation.success_count,
+            failed: operation.failed_count,
+            skipped: operation.skipped_count,
+            results,
+        };
+
+        let kind = if payload.failed == 0 {
+            NotificationKind::MergeSucceeded
+        } else {
+            NotificationKind::MergeFailed
+        };
+        let notice = Notice {
+            kind,
+            pull_request_id: None,
+            dedup_key: format!("{}:{}", kind, operation.id),
+            payload: serde_json::to_string(&payload)?,
+        };
+        queue_notice(db, operation.user_id, &notice).await?;
+        MergeOperationQueries::mark_notification_sent(db, operation.id).await?;
+        Ok(())
+    }
+
+    async fn resolve_item(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+        item: &merge_operation_item::Model,
+    ) -> anyhow::Result<(
+        Arc<dyn GitProviderTrait>,
+        ProviderCredentials,
+        repository::Model,
+        pull_request::Model,
+    )> {
+        let pr = PrQueries::find_by_id(db, item.pull_request_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pull request not found"))?;
+        let repo = RepoQueries::find_by_id(db, item.repository_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Repository not found"))?;
+
+        let provider_type: GitProvider = repo
+            .provider
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!(e))?;
+
+        let account_id = repo
+            .provider_account_id
+            .ok_or_else(|| anyhow::anyhow!(t!("providers.worker.no_account")))?;
+
+        let account = provider_account::Entity::find_by_id(account_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(t!("providers.worker.account_not_found")))?;
+
+        let credentials =
+            account_credentials(db, encryption_service, provider_factory, &account).await?;
+
+        let provider = provider_factory.create(provider_type, account.instance_url.clone());
+
+        Ok((provider, credentials, repo, pr))
+    }
+
+    /// Re-check one PR against the provider and merge it if it is still safe to
+    /// and carries none of `skip_labels`.
+    ///
+    /// Returns the item status (`success`, `failed` or `skipped`), an optional
+    /// message and the merge commit SHA.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn process_item(
+        &self,
+        db: &DatabaseConnection,
+        provider: &dyn GitProviderTrait,
+        credentials: &ProviderCredentials,
+        repo: &repository::Model,
+        pr: &pull_request::Model,
+        item: &merge_operation_item::Model,
+        merge_request: &MergeRequest,
+        skip_labels: &[String],
+    ) -> anyhow::Result<(&'static str, Option<String>, Option<String>)> {
+        let fresh = match with_retries(|| {
+            provider.get_pull_request(credentials, &repo.owner, &repo.name, pr.number)
+        })
+        .await
+        {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                return Ok((
+                    "failed",
+                    Some(format!("Failed to verify PR state: {}", e)),
+                    None,
+                ))
+            }
+        };
+
+        if fresh.state != "open" {
+            let closed = PrQueries::update_state(
+                db,
+                pr.id,
+                fresh.state.clone(),
+                fresh.merged_at,
+                fresh.closed_at,
+            )
+            .await?;
+            record_pr_closed(repo, &closed).await;
+            let message = match fresh.state.as_str() {
+                "merged" => "PR was already merged",
+                "closed" => "PR was closed",
+                _ => "PR is not open",
+            };
+            return Ok(("skipped", Some(message.to_string()), None));
+        }
+
+        let skip_label = fresh
+            .labels
+            .iter()
+            .find(|label| skip_labels.iter().any(|s| s.eq_ignore_ascii_case(label)))
+            .cloned();
+
+        // Refresh stored PR, checks and reviews so status reflects the provider now
+        let synced =
+            PollRepositoryJob::sync_pull_request(db, provider, credentials, repo, fresh).await?;
+
+        if let Some(label) = skip_label {
+            return Ok(("skipped", Some(format!("PR is labelled {}", label)), None));
+        }
+        if synced.is_draft {
+            return Ok(("skipped", Some("PR is a draft".to_string()), None));
+        }
+        if synced.has_conflicts {
+            return Ok(("skipped", Some("PR has merge conflicts".to_string()), None));
+        }
+        if synced.is_mergeable == Some(false) {
+            return Ok(("skipped", Some("PR is not mergeable".to_string()), None));
+        }
+
+        let checks: Vec<CICheck> = CICheckQueries::find_by_pull_request(db, synced.id)
+            .await?
+            .into_iter()
+            .map(|c| c.into())
+            .collect();
+        let reviews: Vec<Review> = ReviewQueries::find_latest_by_pull_request(db, synced.id)
+            .await?
+            .into_iter()
+            .map(|r| r.into())
+            .collect();
+        let policy = StatusPolicyQueries::policy_for_repository(db, repo.id).await?;
+        if AmpelStatus::evaluate(&synced.clone().into(), &checks, &reviews, &policy).status
+            == AmpelStatus::Red
+        {
+            return Ok(("skipped", Some("PR status is red".to_string()), None));
+        }
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            MergeOperationItemQueries::increment_attempts(db, item.id).await?;
+
+            match provider
+                .merge_pull_request(
+                    credentials,
+                    &repo.owner,
+                    &repo.name,
+                    pr.number,
+                    merge_request,
+                )
+                .await
+            {
+                Ok(result) if result.merged => {
+                    let now = Utc::now();
+                    let merged = PrQueries::update_state(
+                        db,
+                        pr.id,
+                        "merged".to_string(),
+                        Some(now),
+                        Some(now),
+                    )
+                    .await?;
+                    record_pr_closed(repo, &merged).await;
+                    tracing::info!("Merged PR #{} in {}", pr.number, repo.full_name);
+                    return Ok(("success", None, result.sha));
+                }
+                Ok(result) => return Ok(("failed", Some(result.message), None)),
+                Err(e) if e.is_transient() && attempt < MAX_MERGE_ATTEMPTS => {
+                    tracing::warn!(
+                        "Transient error merging PR #{} in {} (attempt {}): {}",
+                        pr.number,
+                        repo.full_name,
+                        attempt,
+                        e
+                    );
+                    sleep(retry_delay(attempt)).await;
+                }
+                Err(e) => return Ok(("failed", Some(e.to_string()), None)),
+            }
+        }
+    }
+
+    /// Recount item results onto the operation.
+    ///
+    /// `finish` is `None` while draining, `Some("cancelled")` on cancellation,
+    /// or `Some("finished")` to pick `completed`/`failed` from the counts.
+    async fn record_progress(
+        db: &DatabaseConnection,
+        operation_id: uuid::Uuid,
+        finish: Option<&str>,
+    ) -> anyhow::Result<merge_operation::Model> {
+        let items = MergeOperationItemQueries::find_by_operation(db, operation_id).await?;
+        let count = |status: &str| items.iter().filter(|i| i.status == status).count() as i32;
+        let (success, failed, skipped) = (count("success"), count("failed"), count("skipped"));
+
+        let status = match finish {
+            None => "in_progress",
+            Some("cancelled") => "cancelled",
+            Some(_) if success == 0 && failed > 0 => "failed",
+            Some(_) => "completed",
+        };
+
+        Ok(
+            MergeOperationQueries::update_counts(
+                db,
+                operation_id,
+                success,
+                failed,
+                skipped,
+                status,
+            )
+            .await?,
+        )
+    }
+}
+
+fn retry_delay(attempt: i32) -> StdDuration {
+    StdDuration::from_millis(RETRY_BASE_DELAY_MS << (attempt - 1).clamp(0, 6))
+}
+
+/// Run a provider call, retrying transient errors with exponential backoff.
+async fn with_retries<T, F, Fut>(mut call: F) -> Result<T, ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ProviderError>>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match call().await {
+            Err(e) if e.is_transient() && attempt < MAX_MERGE_ATTEMPTS => {
+                sleep(retry_delay(attempt)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_back_off_exponentially() {
+        assert_eq!(retry_delay(1), StdDuration::from_millis(500));
+        assert_eq!(retry_delay(2), StdDuration::from_millis(1000));
+        assert_eq!(retry_delay(3), StdDuration::from_millis(2000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_retry_transient_errors_then_give_up() {
+        let mut calls = 0;
+        let result: Result<(), ProviderError> = with_retries(|| {
+            calls += 1;
+            async { Err(ProviderError::NetworkError("reset".into())) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, MAX_MERGE_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_permanent_errors() {
+        let mut calls = 0;
+        let result: Result<(), ProviderError> = with_retries(|| {
+            calls += 1;
+            async { Err(ProviderError::NotFound("pr".into())) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -1,6 +1,11 @@
+pub mod auto_merge;
 pub mod cleanup;
+pub mod digest;
 pub mod health_score;
+pub mod merge_queue;
 pub mod metrics_collection;
+pub mod notification_dispatch;
 pub mod poll_repository;
+pub mod remediation_dispatch;
 pub mod remediation_run;
 pub mod remediation_sweep;
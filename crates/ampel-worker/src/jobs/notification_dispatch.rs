@@ -0,0 +1,356 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+use ampel_core::models::{
+    notification_retry_delay, DeliveryChannel, Digest, NotificationKind, PrNotification,
+    DIGEST_NOTIFICATION_KIND,
+};
+use ampel_core::services::{
+    push_payload, ChannelConfig, ChannelMessage, MergeNotificationPayload, NotificationChannel,
+    NotificationService, SmtpConfig, WebPushSender,
+};
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::{notification_log, notification_preferences, push_subscription};
+use ampel_db::queries::{
+    NotificationChannelQueries, NotificationLogQueries, PushSubscriptionQueries,
+};
+
+use crate::services::digest::render_digest;
+use crate::services::notification_queue::recipient_emails;
+use crate::services::web_push::{load_vapid_keys, push_to_subscriptions, vapid_subject};
+
+/// Notifications delivered per run
+const DISPATCH_BATCH_SIZE: u64 = 100;
+
+/// How long a claimed notification is hidden from other workers. Delivery
+/// normally records an outcome long before this; if a worker dies mid-send
+/// the notification becomes due again afterwards.
+const CLAIM_LEASE_SECS: i64 = 5 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDispatchJob;
+
+impl From<DateTime<Utc>> for NotificationDispatchJob {
+    fn from(_: DateTime<Utc>) -> Self {
+        Self
+    }
+}
+
+/// Where a notification goes, resolved from the user's current preferences
+/// or the configured channel it was queued for
+enum Target {
+    Slack {
+        webhook_url: String,
+        channel: Option<String>,
+    },
+    Email {
+        config: SmtpConfig,
+        to_emails: Vec<String>,
+    },
+    Channel(Box<dyn NotificationChannel>),
+    Push {
+        sender: WebPushSender,
+        subscriptions: Vec<push_subscription::Model>,
+    },
+}
+
+impl NotificationDispatchJob {
+    /// Deliver due notifications and digests from the notification log
+    pub async fn execute(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+    ) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let due = NotificationLogQueries::find_due(db, now, DISPATCH_BATCH_SIZE).await?;
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let lease_until = now + Duration::seconds(CLAIM_LEASE_SECS);
+        let (mut sent, mut failed) = (0, 0);
+        for entry in due {
+            if !NotificationLogQueries::claim(db, entry.id, now, lease_until).await? {
+                continue;
+            }
+
+            let target = match self.resolve_target(db, encryption_service, &entry).await {
+                Ok(target) => target,
+                Err(e) => {
+                    // The channel was switched off or misconfigured since the
+                    // notification was queued; retrying cannot help.
+                    NotificationLogQueries::mark_attempt_failed(db, entry.id, e.to_string(), None)
+                        .await?;
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            match self.deliver(db, &entry, target).await {
+                Ok(()) => {
+                    NotificationLogQueries::mark_sent(db, entry.id).await?;
+                    sent += 1;
+                }
+                Err(e) => {
+                    let retry_at = notification_retry_delay(entry.attempts + 1)
+                        .map(|delay| Utc::now() + delay);
+                    tracing::warn!(
+                        notification_id = %entry.id,
+                        channel = %entry.channel,
+                        attempt = entry.attempts + 1,
+                        will_retry = retry_at.is_some(),
+                        "Notification delivery failed: {}",
+                        e
+                    );
+                    NotificationLogQueries::mark_attempt_failed(
+                        db,
+                        entry.id,
+                        e.to_string(),
+                        retry_at,
+                    )
+                    .await?;
+                    if retry_at.is_none() {
+                        failed += 1;
+                    }
+                }
+            }
+        }
+
+        tracing::info!(
+            "Delivered {} notifications, {} failed for good",
+            sent,
+            failed
+        );
+
+        Ok(())
+    }
+
+    async fn resolve_target(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        entry: &notification_log::Model,
+    ) -> anyhow::Result<Target> {
+        if let Some(channel_id) = entry.channel_id {
+            let channel = NotificationChannelQueries::find_by_id(db, channel_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Notification channel no longer exists"))?;
+            if !channel.enabled {
+                anyhow::bail!("Notification channel is disabled");
+            }
+            let config: ChannelConfig =
+                serde_json::from_str(&encryption_service.decrypt(&channel.config_encrypted)?)?;
+            return Ok(Target::Channel(config.into_channel()));
+        }
+
+        let prefs = notification_preferences::Entity::find()
+            .filter(notification_preferences::Column::UserId.eq(entry.user_id))
+            .one(db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Notification preferences no longer exist"))?;
+
+        match entry.channel.parse::<DeliveryChannel>()? {
+            DeliveryChannel::Slack => {
+                let webhook_url = prefs
+                    .slack_webhook_url
+                    .filter(|_| prefs.slack_enabled)
+                    .ok_or_else(|| anyhow::anyhow!("Slack notifications are disabled"))?;
+                Ok(Target::Slack {
+                    webhook_url,
+                    channel: prefs.slack_channel,
+                })
+            }
+            DeliveryChannel::Email => {
+                let to_emails = recipient_emails(&prefs);
+                let config = smtp_config(prefs, encryption_service)?;
+                Ok(Target::Email { config, to_emails })
+            }
+            DeliveryChannel::Push => {
+                if !prefs.push_enabled {
+                    anyhow::bail!("Push notifications are disabled");
+                }
+                let subscriptions =
+                    PushSubscriptionQueries::list_for_user(db, entry.user_id).await?;
+                if subscriptions.is_empty() {
+                    anyhow::bail!("No push subscriptions registered");
+                }
+                let keys = load_vapid_keys(db, encryption_service).await?;
+                Ok(Target::Push {
+                    sender: WebPushSender::new(keys, vapid_subject()),
+                    subscriptions,
+                })
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        db: &DatabaseConnection,
+        entry: &notification_log::Model,
+        target: Target,
+    ) -> anyhow::Result<()> {
+        if entry.kind == DIGEST_NOTIFICATION_KIND {
+            return self.deliver_digest(entry, target).await;
+        }
+
+        if matches!(
+            entry.kind.parse::<NotificationKind>()?,
+            NotificationKind::MergeSucceeded | NotificationKind::MergeFailed
+        ) {
+            return self.deliver_merge(db, entry, target).await;
+        }
+
+        let notification: PrNotification = serde_json::from_str(&entry.payload)?;
+        match target {
+            Target::Slack {
+                webhook_url,
+                channel,
+            } => {
+                NotificationService::send_pr_slack_notification(
+                    &webhook_url,
+                    channel.as_deref(),
+                    &notification,
+                )
+                .await?
+            }
+            Target::Email { config, to_emails } => {
+                NotificationService::send_pr_email_notification(&config, &to_emails, &notification)
+                    .await?
+            }
+            Target::Channel(channel) => {
+                channel
+                    .send(&ChannelMessage::from_pr(&notification))
+                    .await?
+            }
+            Target::Push {
+                sender,
+                subscriptions,
+            } => {
+                deliver_push(
+                    db,
+                    &sender,
+                    &subscriptions,
+                    &ChannelMessage::from_pr(&notification),
+                )
+                .await?
+            }
+        }
+        Ok(())
+    }
+
+    async fn deliver_merge(
+        &self,
+        db: &DatabaseConnection,
+        entry: &notification_log::Model,
+        target: Target,
+    ) -> anyhow::Result<()> {
+        let payload: MergeNotificationPayload = serde_json::from_str(&entry.payload)?;
+        match target {
+            Target::Slack {
+                webhook_url,
+                channel,
+            } => {
+                NotificationService::send_slack_notification(
+                    &webhook_url,
+                    channel.as_deref(),
+                    &payload,
+                )
+                .await?
+            }
+            Target::Email { config, to_emails } => {
+                NotificationService::send_email_notification(&config, &to_emails, &payload).await?
+            }
+            Target::Channel(channel) => channel.send(&ChannelMessage::from_merge(&payload)).await?,
+            Target::Push {
+                sender,
+                subscriptions,
+            } => {
+                deliver_push(
+                    db,
+                    &sender,
+                    &subscriptions,
+                    &ChannelMessage::from_merge(&payload),
+                )
+                .await?
+            }
+        }
+        Ok(())
+    }
+
+    async fn deliver_digest(
+        &self,
+        entry: &notification_log::Model,
+        target: Target,
+    ) -> anyhow::Result<()> {
+        let Target::Email { config, to_emails } = target else {
+            anyhow::bail!("Digests are only delivered by email");
+        };
+        let digest: Digest = serde_json::from_str(&entry.payload)?;
+        let rendered = render_digest(&digest);
+        NotificationService::send_digest_email(
+            &config,
+            &to_emails,
+            &rendered.subject,
+            rendered.text,
+            rendered.html,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Push a message to a user's browsers. It counts as delivered if any browser
+/// accepted it; if none did and some failed for reasons other than having
+/// expired, it is retried.
+async fn deliver_push(
+    db: &DatabaseConnection,
+    sender: &WebPushSender,
+    subscriptions: &[push_subscription::Model],
+    message: &ChannelMessage,
+) -> anyhow::Result<()> {
+    let outcome = push_to_subscriptions(db, sender, subscriptions, &push_payload(message)).await?;
+    if outcome.delivered > 0 {
+        if !outcome.errors.is_empty() {
+            tracing::warn!(
+                "Push notification reached {} browsers, failed for {}: {}",
+                outcome.delivered,
+                outcome.errors.len(),
+                outcome.errors.join("; ")
+            );
+        }
+        return Ok(());
+    }
+    if outcome.errors.is_empty() {
+        anyhow::bail!("All push subscriptions have expired");
+    }
+    anyhow::bail!("{}", outcome.errors.join("; "))
+}
+
+/// SMTP settings from a user's preferences, with the password decrypted
+fn smtp_config(
+    prefs: notification_preferences::Model,
+    encryption_service: &EncryptionService,
+) -> anyhow::Result<SmtpConfig> {
+    if !prefs.email_enabled {
+        anyhow::bail!("Email notifications are disabled");
+    }
+    let (Some(host), Some(port), Some(username), Some(password_encrypted), Some(from_email)) = (
+        prefs.smtp_host,
+        prefs.smtp_port,
+        prefs.smtp_username,
+        prefs.smtp_password_encrypted,
+        prefs.smtp_from_email,
+    ) else {
+        anyhow::bail!("SMTP settings are incomplete");
+    };
+
+    Ok(SmtpConfig {
+        host,
+        port: port as u16,
+        username,
+        password: encryption_service.decrypt(&password_encrypted)?,
+        from_email,
+        use_tls: prefs.smtp_use_tls,
+    })
+}
@@ -1,14 +1,42 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use chrono::{DateTime, Duration, Utc};
 use rust_i18n::t;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QuerySelect};
+use sea_orm::{
+    sea_query::Condition, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use ampel_core::models::GitProvider;
+use ampel_core::models::{GitProvider, PullRequestMetadata};
 use ampel_db::encryption::EncryptionService;
-use ampel_db::entities::{provider_account, repository};
-use ampel_db::queries::{CICheckQueries, PrQueries, RepoQueries, ReviewQueries};
-use ampel_providers::ProviderFactory;
+use ampel_db::entities::{provider_account, pull_request, repository};
+use ampel_db::queries::{
+    CICheckQueries, PrMetadataQueries, PrQueries, RepoQueries, RepositoryWebhookQueries,
+    ReviewQueries,
+};
+use ampel_providers::traits::{
+    GitProvider as GitProviderTrait, ProviderCredentials, ProviderPullRequest,
+};
+use ampel_providers::{ProviderError, ProviderFactory};
+
+use crate::services::account_credentials::account_credentials;
+use crate::services::poll_scheduler::{PollScheduler, POLL_CYCLE_SECS};
+use crate::services::pr_events::{record_check_changes, record_pr_closed, record_pr_events};
+
+/// A webhook counts as live if it delivered something within this window.
+pub const WEBHOOK_LIVENESS_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// Minimum interval between reconciliation polls of a repository whose
+/// webhook is live. Pushes keep it current; polling only repairs missed
+/// deliveries and closes PRs the provider never told us about.
+pub const WEBHOOK_RECONCILE_INTERVAL_SECS: i64 = 60 * 60;
+
+/// Repositories with an open PR, or a PR updated within this window, are
+/// polled ahead of idle ones when an account's budget is tight.
+pub const ACTIVE_PR_WINDOW_SECS: i64 = 24 * 60 * 60;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PollRepositoryJob;
@@ -25,24 +53,117 @@ impl PollRepositoryJob {
         db: &DatabaseConnection,
         encryption_service: &EncryptionService,
         provider_factory: &ProviderFactory,
+        scheduler: &PollScheduler,
     ) -> anyhow::Result<()> {
-        // Find repositories due for polling
+        // Find repositories due for polling, busiest first
         let repos = self.find_repos_to_poll(db).await?;
+        let repos = self.prioritize(db, repos).await?;
 
         tracing::info!("Found {} repositories to poll", repos.len());
 
+        let mut by_account: HashMap<Option<Uuid>, Vec<repository::Model>> = HashMap::new();
         for repo in repos {
+            by_account
+                .entry(repo.provider_account_id)
+                .or_default()
+                .push(repo);
+        }
+
+        for (account_id, repos) in by_account {
+            let Some(account_id) = account_id else {
+                for repo in &repos {
+                    tracing::error!(
+                        "Failed to poll repository {}/{}: {}",
+                        repo.owner,
+                        repo.name,
+                        t!("providers.worker.no_account")
+                    );
+                }
+                continue;
+            };
+
             if let Err(e) = self
-                .poll_single_repo(db, encryption_service, provider_factory, &repo)
+                .poll_account(
+                    db,
+                    encryption_service,
+                    provider_factory,
+                    scheduler,
+                    account_id,
+                    repos,
+                )
                 .await
             {
-                tracing::error!(
-                    "Failed to poll repository {}/{}: {}",
-                    repo.owner,
-                    repo.name,
-                    e
+                tracing::error!("Failed to poll account {}: {}", account_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll as many of one account's due repositories as its quota allows
+    /// this cycle; the rest stay due and are picked up by a later cycle.
+    async fn poll_account(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+        scheduler: &PollScheduler,
+        account_id: Uuid,
+        repos: Vec<repository::Model>,
+    ) -> anyhow::Result<()> {
+        let (provider, credentials) =
+            Self::account_provider(db, encryption_service, provider_factory, account_id).await?;
+
+        match provider.get_rate_limit(&credentials).await {
+            Ok(rate_limit) => scheduler.refresh(account_id, &rate_limit),
+            Err(e) => tracing::warn!(
+                "Failed to fetch rate limit for account {}: {}",
+                account_id,
+                e
+            ),
+        }
+
+        let allowance = scheduler.allowance(account_id, Utc::now()).min(repos.len());
+        let deferred = repos.len() - allowance;
+        if deferred > 0 {
+            tracing::debug!(
+                "Deferring {} repositories of account {} to stay within its rate limit",
+                deferred,
+                account_id
+            );
+            scheduler.record_deferred(account_id, deferred, Utc::now());
+        }
+
+        for (index, repo) in repos.iter().take(allowance).enumerate() {
+            scheduler.record_poll(account_id);
+
+            let Err(e) = Self::poll_with_provider(db, provider.as_ref(), &credentials, repo).await
+            else {
+                continue;
+            };
+
+            if let Some(rate_limited @ ProviderError::RateLimitExceeded(_)) =
+                e.downcast_ref::<ProviderError>()
+            {
+                let reset_at = rate_limited
+                    .rate_limit_reset()
+                    .unwrap_or_else(|| Utc::now() + Duration::seconds(POLL_CYCLE_SECS));
+                tracing::warn!(
+                    "Account {} is rate limited until {}; deferring its remaining repositories",
+                    account_id,
+                    reset_at
                 );
+                scheduler.record_rate_limited(account_id, reset_at);
+                scheduler.record_deferred(account_id, allowance - index, Utc::now());
+                break;
             }
+
+            tracing::error!(
+                "Failed to poll repository {}/{}: {}",
+                repo.owner,
+                repo.name,
+                e
+            );
         }
 
         Ok(())
@@ -54,32 +175,80 @@ impl PollRepositoryJob {
     ) -> anyhow::Result<Vec<repository::Model>> {
         let now = Utc::now();
 
-        // Find repos where:
-        // 1. Never polled, OR
-        // 2. Last polled + poll_interval < now
-        let repos = repository::Entity::find()
+        // Repos with a live webhook are only reconciled, not polled every
+        // interval. Intervals are per repository, so the due check is one
+        // branch per distinct interval; keeping it in SQL means the batch
+        // limit only ever counts repos that are actually due.
+        let live = RepositoryWebhookQueries::live_repository_ids_query(
+            now - Duration::seconds(WEBHOOK_LIVENESS_WINDOW_SECS),
+        );
+        let intervals: Vec<i32> = repository::Entity::find()
+            .select_only()
+            .column(repository::Column::PollIntervalSeconds)
+            .distinct()
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let mut due = Condition::any().add(repository::Column::LastPolledAt.is_null());
+        for interval in intervals {
+            let polled = interval as i64;
+            let reconciled = polled.max(WEBHOOK_RECONCILE_INTERVAL_SECS);
+            due = due
+                .add(
+                    Condition::all()
+                        .add(repository::Column::PollIntervalSeconds.eq(interval))
+                        .add(repository::Column::Id.not_in_subquery(live.clone()))
+                        .add(repository::Column::LastPolledAt.lt(now - Duration::seconds(polled))),
+                )
+                .add(
+                    Condition::all()
+                        .add(repository::Column::PollIntervalSeconds.eq(interval))
+                        .add(repository::Column::Id.in_subquery(live.clone()))
+                        .add(
+                            repository::Column::LastPolledAt
+                                .lt(now - Duration::seconds(reconciled)),
+                        ),
+                );
+        }
+
+        let due_repos = repository::Entity::find()
+            .filter(due)
             .order_by_asc(repository::Column::LastPolledAt)
             .limit(50)
             .all(db)
             .await?;
 
-        // Filter to repos actually due for polling
-        let due_repos: Vec<_> = repos
-            .into_iter()
-            .filter(|r| {
-                match r.last_polled_at {
-                    None => true, // Never polled
-                    Some(last) => {
-                        let next_poll = last + Duration::seconds(r.poll_interval_seconds as i64);
-                        now > next_poll
-                    }
-                }
-            })
-            .collect();
-
         Ok(due_repos)
     }
 
+    /// Order repositories with open or recently updated PRs ahead of idle
+    /// ones, keeping the oldest-polled-first order within each group.
+    pub async fn prioritize(
+        &self,
+        db: &DatabaseConnection,
+        mut repos: Vec<repository::Model>,
+    ) -> anyhow::Result<Vec<repository::Model>> {
+        let ids: Vec<Uuid> = repos.iter().map(|r| r.id).collect();
+        let active: HashSet<Uuid> = PrQueries::find_active_repository_ids(
+            db,
+            &ids,
+            Utc::now() - Duration::seconds(ACTIVE_PR_WINDOW_SECS),
+        )
+        .await?
+        .into_iter()
+        .collect();
+
+        repos.sort_by_key(|r| !active.contains(&r.id));
+
+        Ok(repos)
+    }
+
+    /// Poll one repository right away, outside the scheduler's budget.
+    ///
+    /// Used by the API's manual refresh; the bin only polls through
+    /// [`Self::execute`], hence the allow.
+    #[allow(dead_code)]
     pub async fn poll_single_repo(
         &self,
         db: &DatabaseConnection,
@@ -87,37 +256,55 @@ impl PollRepositoryJob {
         provider_factory: &ProviderFactory,
         repo: &repository::Model,
     ) -> anyhow::Result<()> {
-        tracing::debug!("Polling repository {}/{}", repo.owner, repo.name);
-
-        let provider_type: GitProvider = repo
-            .provider
-            .parse()
-            .map_err(|e: String| anyhow::anyhow!(e))?;
-
         // Get provider account
         let account_id = repo
             .provider_account_id
             .ok_or_else(|| anyhow::anyhow!(t!("providers.worker.no_account")))?;
 
+        let (provider, credentials) =
+            Self::account_provider(db, encryption_service, provider_factory, account_id).await?;
+
+        Self::poll_with_provider(db, provider.as_ref(), &credentials, repo).await
+    }
+
+    /// Build the provider client and credentials for a provider account.
+    async fn account_provider(
+        db: &DatabaseConnection,
+        encryption_service: &EncryptionService,
+        provider_factory: &ProviderFactory,
+        account_id: Uuid,
+    ) -> anyhow::Result<(Arc<dyn GitProviderTrait>, ProviderCredentials)> {
         let account = provider_account::Entity::find_by_id(account_id)
             .one(db)
             .await?
             .ok_or_else(|| anyhow::anyhow!(t!("providers.worker.account_not_found")))?;
 
-        // Decrypt access token
-        let access_token = encryption_service.decrypt(&account.access_token_encrypted)?;
+        let provider_type: GitProvider = account
+            .provider
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!(e))?;
 
-        // Create credentials
-        let credentials = ampel_providers::traits::ProviderCredentials::Pat {
-            token: access_token.clone(),
-            username: account.auth_username.clone(),
-        };
+        let credentials =
+            account_credentials(db, encryption_service, provider_factory, &account).await?;
 
         let provider = provider_factory.create(provider_type, account.instance_url.clone());
 
+        Ok((provider, credentials))
+    }
+
+    /// Sync a repository's open PRs and close the ones the provider no
+    /// longer lists as open.
+    pub async fn poll_with_provider(
+        db: &DatabaseConnection,
+        provider: &dyn GitProviderTrait,
+        credentials: &ProviderCredentials,
+        repo: &repository::Model,
+    ) -> anyhow::Result<()> {
+        tracing::debug!("Polling repository {}/{}", repo.owner, repo.name);
+
         // Fetch open PRs from provider (works consistently for GitHub, GitLab, Bitbucket)
         let prs = provider
-            .list_pull_requests(&credentials, &repo.owner, &repo.name, Some("open"))
+            .list_pull_requests(credentials, &repo.owner, &repo.name, Some("open"))
             .await?;
 
         tracing::debug!(
@@ -133,94 +320,7 @@ impl PollRepositoryJob {
             prs.iter().map(|pr| pr.number).collect();
 
         for pr in prs {
-            // Upsert PR
-            let pr_model = PrQueries::upsert(
-                db,
-                repo.id,
-                provider_type.to_string(),
-                pr.provider_id.clone(),
-                pr.number,
-                pr.title,
-                pr.description,
-                pr.url,
-                pr.state,
-                pr.source_branch,
-                pr.target_branch,
-                pr.author,
-                pr.author_avatar_url,
-                pr.is_draft,
-                pr.is_mergeable,
-                pr.has_conflicts,
-                pr.additions,
-                pr.deletions,
-                pr.changed_files,
-                pr.commits_count,
-                pr.comments_count,
-                pr.created_at,
-                pr.updated_at,
-                pr.merged_at,
-                pr.closed_at,
-            )
-            .await?;
-
-            // Fetch and update CI checks
-            match provider
-                .get_ci_checks(&credentials, &repo.owner, &repo.name, pr.number)
-                .await
-            {
-                Ok(checks) => {
-                    // Delete old checks and insert new ones
-                    CICheckQueries::delete_by_pull_request(db, pr_model.id).await?;
-
-                    for check in checks {
-                        CICheckQueries::upsert(
-                            db,
-                            pr_model.id,
-                            check.name,
-                            check.status,
-                            check.conclusion,
-                            check.url,
-                            check.started_at,
-                            check.completed_at,
-                            check.completed_at.and_then(|c| {
-                                check.started_at.map(|s| (c - s).num_seconds() as i32)
-                            }),
-                        )
-                        .await?;
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to fetch CI checks for PR #{}: {}", pr.number, e);
-                }
-            }
-
-            // Fetch and update reviews
-            match provider
-                .get_reviews(&credentials, &repo.owner, &repo.name, pr.number)
-                .await
-            {
-                Ok(reviews) => {
-                    // Delete old reviews and insert new ones
-                    ReviewQueries::delete_by_pull_request(db, pr_model.id).await?;
-
-                    for review in reviews {
-                        ReviewQueries::upsert(
-                            db,
-                            Uuid::new_v4(),
-                            pr_model.id,
-                            review.reviewer,
-                            review.reviewer_avatar_url,
-                            review.state,
-                            review.body,
-                            review.submitted_at,
-                        )
-                        .await?;
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to fetch reviews for PR #{}: {}", pr.number, e);
-                }
-            }
+            Self::sync_pull_request(db, provider, credentials, repo, pr).await?;
         }
 
         // Mark PRs as closed if they're no longer in the provider's open list
@@ -236,8 +336,15 @@ impl PollRepositoryJob {
                     repo.owner,
                     repo.name
                 );
-                PrQueries::update_state(db, existing_pr.id, "closed".to_string(), None, Some(now))
-                    .await?;
+                let closed = PrQueries::update_state(
+                    db,
+                    existing_pr.id,
+                    "closed".to_string(),
+                    None,
+                    Some(now),
+                )
+                .await?;
+                record_pr_closed(repo, &closed).await;
             }
         }
 
@@ -248,4 +355,143 @@ impl PollRepositoryJob {
 
         Ok(())
     }
+
+    /// Upsert one provider PR, refresh its CI checks and reviews, and record
+    /// any status change or review request to notify about. Changes a
+    /// dashboard shows are published to the owner's open dashboards.
+    ///
+    /// Shared with the merge queue, which re-syncs each PR just before merging
+    /// it. Check and review fetch failures are logged and leave the stored rows
+    /// untouched, except rate limiting, which aborts the sync.
+    pub async fn sync_pull_request(
+        db: &DatabaseConnection,
+        provider: &dyn GitProviderTrait,
+        credentials: &ProviderCredentials,
+        repo: &repository::Model,
+        pr: ProviderPullRequest,
+    ) -> anyhow::Result<pull_request::Model> {
+        let provider_type = provider.provider_type();
+
+        // Only a PR that was open can be seen closing
+        let was_open = pr.state != "open"
+            && PrQueries::find_by_number(db, repo.id, pr.number)
+                .await?
+                .is_some_and(|existing| existing.state == "open");
+
+        let metadata = PullRequestMetadata {
+            labels: pr.labels,
+            assignees: pr.assignees,
+            requested_reviewers: pr.requested_reviewers.clone(),
+        };
+
+        // Upsert PR
+        let pr_model = PrQueries::upsert(
+            db,
+            repo.id,
+            provider_type.to_string(),
+            pr.provider_id.clone(),
+            pr.number,
+            pr.title,
+            pr.description,
+            pr.url,
+            pr.state,
+            pr.source_branch,
+            pr.target_branch,
+            pr.author,
+            pr.author_avatar_url,
+            pr.is_draft,
+            pr.is_mergeable,
+            pr.has_conflicts,
+            pr.additions,
+            pr.deletions,
+            pr.changed_files,
+            pr.commits_count,
+            pr.comments_count,
+            pr.created_at,
+            pr.updated_at,
+            pr.merged_at,
+            pr.closed_at,
+            pr.head_sha,
+            pr.milestone,
+        )
+        .await?;
+        PrMetadataQueries::replace(db, pr_model.id, &metadata).await?;
+
+        // Fetch and update CI checks
+        match provider
+            .get_ci_checks(credentials, &repo.owner, &repo.name, pr.number)
+            .await
+        {
+            Ok(checks) => {
+                // Delete old checks and insert new ones
+                let previous = CICheckQueries::find_by_pull_request(db, pr_model.id).await?;
+                CICheckQueries::delete_by_pull_request(db, pr_model.id).await?;
+
+                for check in checks {
+                    CICheckQueries::upsert(
+                        db,
+                        pr_model.id,
+                        check.name,
+                        check.status,
+                        check.conclusion,
+                        check.url,
+                        check.started_at,
+                        check.completed_at,
+                        check
+                            .completed_at
+                            .and_then(|c| check.started_at.map(|s| (c - s).num_seconds() as i32)),
+                    )
+                    .await?;
+                }
+
+                if let Err(e) = record_check_changes(db, repo, &pr_model, &previous).await {
+                    tracing::warn!("Failed to publish CI changes for PR #{}: {}", pr.number, e);
+                }
+            }
+            Err(e @ ProviderError::RateLimitExceeded(_)) => return Err(e.into()),
+            Err(e) => {
+                tracing::warn!("Failed to fetch CI checks for PR #{}: {}", pr.number, e);
+            }
+        }
+
+        // Fetch and update reviews
+        match provider
+            .get_reviews(credentials, &repo.owner, &repo.name, pr.number)
+            .await
+        {
+            Ok(reviews) => {
+                // Delete old reviews and insert new ones
+                ReviewQueries::delete_by_pull_request(db, pr_model.id).await?;
+
+                for review in reviews {
+                    ReviewQueries::upsert(
+                        db,
+                        Uuid::new_v4(),
+                        pr_model.id,
+                        review.reviewer,
+                        review.reviewer_avatar_url,
+                        review.state,
+                        review.commit_sha,
+                        review.body,
+                        review.submitted_at,
+                    )
+                    .await?;
+                }
+            }
+            Err(e @ ProviderError::RateLimitExceeded(_)) => return Err(e.into()),
+            Err(e) => {
+                tracing::warn!("Failed to fetch reviews for PR #{}: {}", pr.number, e);
+            }
+        }
+
+        // A notification problem must not fail the sync
+        if let Err(e) = record_pr_events(db, repo, &pr_model, &pr.requested_reviewers).await {
+            tracing::warn!("Failed to record events for PR #{}: {}", pr.number, e);
+        }
+        if was_open {
+            record_pr_closed(repo, &pr_model).await;
+        }
+
+        Ok(pr_model)
+    }
 }
@@ -0,0 +1,90 @@
+//! `RemediationDispatchJob` — hands remediation runs to the durable run queue.
+//!
+//! Runs are created by the sweep and by the manual trigger endpoint with no
+//! queue marker. Each tick this job:
+//!
+//! 1. hands back runs whose lease went stale (their worker died mid-run), so
+//!    they are delivered again and resume from their persisted state;
+//! 2. enqueues a [`RemediationRunJob`] for every dispatchable run not yet
+//!    queued, oldest first — including runs an approval moved on from the
+//!    human gate.
+//!
+//! Marking a run queued is a guarded update, so concurrent dispatchers enqueue
+//! a run once. A duplicate delivery is harmless anyway: the run's lease lets
+//! only one worker drive it.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+
+use ampel_db::queries::RemediationRunQueries;
+use apalis::prelude::Storage;
+use apalis_sql::postgres::PostgresStorage;
+
+use super::remediation_run::{RemediationRunJob, RunQueueConfig};
+
+/// Runs enqueued per dispatch tick.
+const DISPATCH_BATCH: u64 = 100;
+
+/// Where dispatched runs are enqueued.
+#[async_trait]
+pub trait RunQueue: Send + Sync {
+    async fn enqueue(&self, job: RemediationRunJob) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl RunQueue for PostgresStorage<RemediationRunJob> {
+    async fn enqueue(&self, job: RemediationRunJob) -> anyhow::Result<()> {
+        self.clone().push(job).await?;
+        Ok(())
+    }
+}
+
+/// Cron-driven dispatcher for queued remediation runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationDispatchJob;
+
+impl From<DateTime<Utc>> for RemediationDispatchJob {
+    fn from(_: DateTime<Utc>) -> Self {
+        Self
+    }
+}
+
+impl RemediationDispatchJob {
+    /// Reclaim abandoned runs and enqueue undispatched ones. Returns how many
+    /// runs were enqueued.
+    pub async fn execute(
+        &self,
+        db: &DatabaseConnection,
+        queue: &dyn RunQueue,
+        config: &RunQueueConfig,
+    ) -> anyhow::Result<usize> {
+        for run in RemediationRunQueries::find_stale_leases(db, config.stale_before()).await? {
+            tracing::warn!(
+                run_id = %run.id,
+                state = %run.state,
+                "remediation run lease expired, handing it back to the queue"
+            );
+            RemediationRunQueries::requeue(db, run.id).await?;
+        }
+
+        let mut enqueued = 0;
+        for run in RemediationRunQueries::find_undispatched(db, DISPATCH_BATCH).await? {
+            if !RemediationRunQueries::mark_queued(db, run.id).await? {
+                continue;
+            }
+            if let Err(e) = queue.enqueue(RemediationRunJob::new(run.id)).await {
+                // Clear the marker so the next tick tries again.
+                RemediationRunQueries::requeue(db, run.id).await?;
+                return Err(e);
+            }
+            enqueued += 1;
+        }
+
+        if enqueued > 0 {
+            tracing::info!("Remediation dispatch: enqueued {} run(s)", enqueued);
+        }
+        Ok(enqueued)
+    }
+}
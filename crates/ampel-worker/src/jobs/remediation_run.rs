@@ -2,44 +2,314 @@
 //!
 //! Given a `run_id`, it rebuilds the per-repo execution context (authenticated
 //! provider, selected PRs, clone coordinates) and hands off to the
-//! [`RemediationExecutor`]. Run *creation* happens in the sweep; this job only
-//! executes an existing run, so it is safe to (re)dispatch idempotently — the
-//! orchestrator's CAS transitions reject stale work.
+//! [`RemediationExecutor`]. Run *creation* happens in the sweep (or the manual
+//! trigger endpoint); this job only executes an existing run, so it is safe to
+//! (re)dispatch idempotently — the orchestrator's CAS transitions reject stale
+//! work.
+//!
+//! ## Queue delivery
+//! Jobs arrive from the durable run queue (see
+//! [`super::remediation_dispatch`]). [`RemediationRunJob::drive`] takes the
+//! run's worker lease before executing and keeps it alive with a heartbeat, so
+//! a duplicate delivery is skipped while the lease is held and a dead worker's
+//! run is picked up again — from its persisted state — once the lease goes
+//! stale. A run whose deliveries keep failing before it reaches a terminal
+//! state is parked in `dead_letter` after [`RunQueueConfig::max_attempts`].
+//!
+//! When the worker has an [`AgenticRuntime`] and the resolved policy's
+//! `remediation_tier` permits model-driven fixes, the executor also gets a
+//! [`DbAgenticTier`] that edits and pushes the run's consolidation branch in the
+//! sandbox and polls its CI, scoped to the repository owner's model accounts.
+//!
+//! A `suggest_only` run gets a [`SuggestOnlyPublisher`] instead: the preview
+//! plan is posted on the selected PRs (unless the last suggest-only run already
+//! posted the same plan) along with the agent's proposed fixes, and nothing is
+//! pushed.
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use sea_orm::{DatabaseConnection, EntityTrait};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use ampel_core::models::GitProvider as ProviderKind;
+use ampel_core::models::{GitProvider as ProviderKind, NotificationKind};
+use ampel_core::remediation::{AutonomyLevel, ConsolidationPlan, RemediationCriteria, RunState};
 use ampel_core::services::{
-    CredentialHandle, PolicyResolver, RemediationProvider, RemediationRunRepository,
-    RemediationService, RepoContext, SandboxRunner, VerificationService,
+    ChannelConfig, ConsolidationSpec, CredentialHandle, NotificationChannel, PolicyResolver,
+    RemediationProvider, RemediationRunRepository, RemediationService, RepoContext, RunUpdate,
+    SandboxRunner, SlackChannel, VerificationService,
 };
 use ampel_db::encryption::EncryptionService;
 use ampel_db::entities::{provider_account, repository};
-use ampel_db::repositories::SeaOrmRemediationRunRepository;
+use ampel_db::queries::{NotificationChannelQueries, RemediationRunQueries};
+use ampel_db::repositories::{SeaOrmLearningSignalRepository, SeaOrmRemediationRunRepository};
 use ampel_providers::traits::ProviderCredentials;
+use ampel_providers::{ProviderFactory, RemediationCapable};
 
-use crate::services::notifier::{LoggingNotifier, RemediationNotifier, SlackNotifier};
+use crate::services::account_credentials::account_credentials;
+use crate::services::agent_worktree::git_username;
+use crate::services::notification_queue::receives;
+use crate::services::notifier::{ChannelNotifier, LoggingNotifier, RemediationNotifier};
+use crate::services::suggestions::TierFactory;
 use crate::services::{
-    remediation_capable_provider, ProviderAdapter, RemediationExecutor, RunOutcome,
+    remediation_capable_provider, tier_allows_agentic, AccountScope, AgentFixProposer,
+    AgentWorktree, AgenticRuntime, CascadeClassifier, DbAgenticTier, PlaybookContext,
+    ProviderAdapter, ProviderCiVerifier, RemediationExecutor, RunOutcome, SandboxAgentWorktree,
+    SuggestOnlyPublisher,
 };
 
-/// Build the notification delivery channel from the environment. When
-/// `REMEDIATION_SLACK_WEBHOOK_URL` is set, events are delivered to Slack via the
-/// shared `NotificationService`; otherwise they are logged (no network).
-fn notifier_from_env() -> Arc<dyn RemediationNotifier> {
-    match std::env::var("REMEDIATION_SLACK_WEBHOOK_URL") {
-        Ok(url) if !url.is_empty() => {
-            let channel = std::env::var("REMEDIATION_SLACK_CHANNEL").ok();
-            Arc::new(SlackNotifier::new(url, channel))
+/// Default number of runs one worker drives at a time.
+const DEFAULT_CONCURRENCY: usize = 3;
+/// Default age after which an unrefreshed run lease counts as abandoned.
+const DEFAULT_LEASE_TTL_SECS: u64 = 300;
+/// Default number of deliveries before a run is dead-lettered.
+const DEFAULT_MAX_ATTEMPTS: i32 = 3;
+
+/// How the run queue is consumed.
+#[derive(Debug, Clone)]
+pub struct RunQueueConfig {
+    /// Runs one worker drives at a time (`AMPEL_MAX_CONCURRENT_REPOS`).
+    pub concurrency: usize,
+    /// Age after which an unrefreshed lease belongs to a dead worker
+    /// (`AMPEL_REMEDIATION_LEASE_TTL_SECS`).
+    pub lease_ttl: Duration,
+    /// Deliveries a run gets before it is dead-lettered
+    /// (`AMPEL_REMEDIATION_MAX_ATTEMPTS`).
+    pub max_attempts: i32,
+}
+
+impl Default for RunQueueConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            lease_ttl: Duration::from_secs(DEFAULT_LEASE_TTL_SECS),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl RunQueueConfig {
+    /// Read the queue settings from the environment, keeping the default for
+    /// anything unset or unparsable.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+
+        let defaults = Self::default();
+        Self {
+            concurrency: var("AMPEL_MAX_CONCURRENT_REPOS")
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.concurrency),
+            lease_ttl: var("AMPEL_REMEDIATION_LEASE_TTL_SECS")
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.lease_ttl),
+            max_attempts: var("AMPEL_REMEDIATION_MAX_ATTEMPTS")
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_attempts),
+        }
+    }
+
+    /// How often a worker refreshes the lease it holds.
+    pub fn heartbeat_interval(&self) -> Duration {
+        (self.lease_ttl / 3).max(Duration::from_secs(1))
+    }
+
+    /// Leases last refreshed before this instant are abandoned.
+    pub fn stale_before(&self) -> DateTime<Utc> {
+        let ttl = chrono::Duration::from_std(self.lease_ttl)
+            .unwrap_or_else(|_| chrono::Duration::seconds(DEFAULT_LEASE_TTL_SECS as i64));
+        Utc::now() - ttl
+    }
+}
+
+/// Refreshes a run's lease until dropped.
+struct Heartbeat(tokio::task::JoinHandle<()>);
+
+impl Heartbeat {
+    fn start(db: DatabaseConnection, run_id: Uuid, every: Duration) -> Self {
+        Self(tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(every);
+            // The first tick fires immediately; the lease was just taken.
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                if let Err(e) = RemediationRunQueries::heartbeat(&db, run_id).await {
+                    tracing::warn!(run_id = %run_id, error = %e, "failed to refresh run lease");
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Build the notifier for a run on a repository owned by `owner_id`.
+///
+/// Events go to the Slack webhook in `REMEDIATION_SLACK_WEBHOOK_URL` (posting
+/// to `REMEDIATION_SLACK_CHANNEL` if set) and to the configured channels of the
+/// owner and their teams that receive remediation events. Without any channel
+/// they are logged (no network).
+async fn build_notifier(
+    db: &DatabaseConnection,
+    encryption_service: &EncryptionService,
+    owner_id: Uuid,
+) -> Arc<dyn RemediationNotifier> {
+    let mut channels: Vec<Box<dyn NotificationChannel>> = Vec::new();
+    if let Ok(webhook_url) = std::env::var("REMEDIATION_SLACK_WEBHOOK_URL") {
+        if !webhook_url.is_empty() {
+            channels.push(Box::new(SlackChannel {
+                webhook_url,
+                channel: std::env::var("REMEDIATION_SLACK_CHANNEL").ok(),
+            }));
+        }
+    }
+
+    match NotificationChannelQueries::find_enabled_for_recipient(db, owner_id).await {
+        Ok(configured) => {
+            for channel in configured
+                .iter()
+                .filter(|c| receives(c, NotificationKind::RemediationCompleted))
+            {
+                let config = encryption_service
+                    .decrypt(&channel.config_encrypted)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| Ok(serde_json::from_str::<ChannelConfig>(&json)?));
+                match config {
+                    Ok(config) => channels.push(config.into_channel()),
+                    Err(e) => tracing::warn!(
+                        channel_id = %channel.id,
+                        error = %e,
+                        "skipping unreadable notification channel"
+                    ),
+                }
+            }
         }
-        _ => Arc::new(LoggingNotifier),
+        Err(e) => tracing::warn!(error = %e, "failed to load notification channels"),
+    }
+
+    if channels.is_empty() {
+        Arc::new(LoggingNotifier)
+    } else {
+        Arc::new(ChannelNotifier::new(channels))
     }
 }
 
+/// Build the Tier-2 agentic tier for `run_id`: the agent edits `worktree` on
+/// `branch`, a CI verifier polls that branch, and model-account selection is
+/// restricted to the repository owner.
+#[allow(clippy::too_many_arguments)]
+fn build_agentic_tier(
+    runtime: &AgenticRuntime,
+    db: &DatabaseConnection,
+    encryption_service: &Arc<EncryptionService>,
+    repo: &repository::Model,
+    provider: Arc<dyn RemediationCapable>,
+    credentials: ProviderCredentials,
+    worktree: Arc<dyn AgentWorktree>,
+    branch: String,
+    air_gapped: bool,
+) -> DbAgenticTier {
+    let verifier = ProviderCiVerifier::new(
+        provider,
+        credentials,
+        repo.owner.clone(),
+        repo.name.clone(),
+        runtime.ci_poll_interval,
+        runtime.ci_timeout,
+    );
+    let run_ctx = PlaybookContext {
+        repo_full_name: repo.full_name.clone(),
+        base_branch: repo.default_branch.clone(),
+        failure_class: String::new(),
+    };
+
+    // No failing logs up front: the tier fetches them fresh from the verifier.
+    let tier = DbAgenticTier::new(
+        db.clone(),
+        encryption_service.clone(),
+        Arc::new(CascadeClassifier::new()),
+        worktree,
+        Arc::new(verifier),
+        air_gapped,
+        run_ctx,
+        branch,
+        String::new(),
+    )
+    .with_account_scope(AccountScope {
+        organization_id: None,
+        user_id: Some(repo.user_id),
+    })
+    .with_learning_recorder(Arc::new(SeaOrmLearningSignalRepository::new(db.clone())));
+
+    match runtime.reflexion_memory.clone() {
+        Some(memory) => tier.with_reflexion_memory(memory),
+        None => tier,
+    }
+}
+
+/// Build the suggest-only publisher for a run, or `None` when the repository's
+/// PRs already carry an identical plan from an earlier suggest-only run.
+///
+/// With an `agentic` runtime and a tier that permits model-driven fixes, each
+/// red PR also gets the agent's fix, proposed from a one-iteration loop on the
+/// PR's own branch.
+#[allow(clippy::too_many_arguments)]
+async fn build_suggestions(
+    db: &DatabaseConnection,
+    encryption_service: &Arc<EncryptionService>,
+    repo: &repository::Model,
+    provider: Arc<dyn RemediationCapable>,
+    credentials: ProviderCredentials,
+    adapter: Arc<dyn RemediationProvider>,
+    criteria: &RemediationCriteria,
+    agentic: Option<&AgenticRuntime>,
+) -> anyhow::Result<Option<SuggestOnlyPublisher>> {
+    let plan = RemediationService::new(db.clone()).preview(repo.id).await?;
+    let published = RemediationRunQueries::latest_suggested_plan(db, repo.id).await?;
+    if published.and_then(|json| serde_json::from_str::<ConsolidationPlan>(&json).ok())
+        == Some(plan.clone())
+    {
+        tracing::info!(repo = %repo.full_name, "remediation plan unchanged; not posting again");
+        return Ok(None);
+    }
+
+    let mut publisher = SuggestOnlyPublisher::new(adapter, plan);
+    if let Some(runtime) = agentic.filter(|_| tier_allows_agentic(criteria.remediation_tier)) {
+        let (runtime, db, encryption_service, repo) = (
+            runtime.clone(),
+            db.clone(),
+            encryption_service.clone(),
+            repo.clone(),
+        );
+        let air_gapped = criteria.air_gapped;
+        let tier_for: TierFactory = Box::new(move |pr, worktree| {
+            let tier = build_agentic_tier(
+                &runtime,
+                &db,
+                &encryption_service,
+                &repo,
+                provider.clone(),
+                credentials.clone(),
+                worktree,
+                pr.branch.clone(),
+                air_gapped,
+            )
+            .with_max_iterations(1);
+            Arc::new(tier)
+        });
+        publisher = publisher.with_proposer(Arc::new(AgentFixProposer::new(tier_for)));
+    }
+    Ok(Some(publisher))
+}
+
 /// Drives one remediation run identified by `run_id`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemediationRunJob {
@@ -51,12 +321,117 @@ impl RemediationRunJob {
         Self { run_id }
     }
 
+    /// Drive the run as a queue delivery: take its lease, execute it under a
+    /// heartbeat, then settle the lease.
+    ///
+    /// A run leased by a live worker, or no longer dispatchable (terminal,
+    /// parked for approval), is skipped. A run parked at the approval gate is
+    /// handed back to the dispatcher so its approval gets a fresh delivery. A
+    /// delivery that errors while the run is still dispatchable is retried
+    /// until [`RunQueueConfig::max_attempts`], then dead-lettered.
+    pub async fn drive(
+        &self,
+        db: &DatabaseConnection,
+        encryption_service: &Arc<EncryptionService>,
+        provider_factory: &ProviderFactory,
+        sandbox: Arc<dyn SandboxRunner>,
+        agentic: Option<&AgenticRuntime>,
+        config: &RunQueueConfig,
+    ) -> anyhow::Result<()> {
+        let Some(run) =
+            RemediationRunQueries::acquire_lease(db, self.run_id, config.stale_before()).await?
+        else {
+            tracing::debug!(run_id = %self.run_id, "remediation run not available to lease");
+            return Ok(());
+        };
+
+        if run.attempts > config.max_attempts {
+            let state: RunState = run.state.parse()?;
+            return self
+                .dead_letter(
+                    db,
+                    state,
+                    format!(
+                        "abandoned after {} deliveries without finishing",
+                        config.max_attempts
+                    ),
+                )
+                .await;
+        }
+
+        let heartbeat = Heartbeat::start(db.clone(), self.run_id, config.heartbeat_interval());
+        let result = self
+            .execute(db, encryption_service, provider_factory, sandbox, agentic)
+            .await;
+        drop(heartbeat);
+
+        match result {
+            Ok(RunOutcome::AwaitingApproval) => {
+                RemediationRunQueries::requeue(db, self.run_id).await?;
+                Ok(())
+            }
+            Ok(_) => {
+                RemediationRunQueries::release_lease(db, self.run_id).await?;
+                Ok(())
+            }
+            Err(e) => {
+                // The executor fails the run itself on an orchestration error;
+                // only an error before it started leaves the run dispatchable.
+                let current = SeaOrmRemediationRunRepository::new(db.clone())
+                    .get_run(self.run_id)
+                    .await?
+                    .map(|r| r.state);
+                match current {
+                    Some(state) if state.is_active() && state != RunState::AwaitingApproval => {
+                        if run.attempts >= config.max_attempts {
+                            self.dead_letter(
+                                db,
+                                state,
+                                format!("failed on all {} deliveries: {e}", config.max_attempts),
+                            )
+                            .await?;
+                        } else {
+                            RemediationRunQueries::requeue(db, self.run_id).await?;
+                        }
+                    }
+                    _ => RemediationRunQueries::release_lease(db, self.run_id).await?,
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Park the run in `dead_letter` and give up its lease.
+    async fn dead_letter(
+        &self,
+        db: &DatabaseConnection,
+        from: RunState,
+        reason: String,
+    ) -> anyhow::Result<()> {
+        tracing::error!(run_id = %self.run_id, %reason, "dead-lettering remediation run");
+        SeaOrmRemediationRunRepository::new(db.clone())
+            .transition_state(
+                self.run_id,
+                from,
+                RunState::DeadLetter,
+                RunUpdate::with_error_message(reason),
+            )
+            .await?;
+        RemediationRunQueries::release_lease(db, self.run_id).await?;
+        Ok(())
+    }
+
     /// Execute the run end-to-end. Returns the terminal [`RunOutcome`].
+    ///
+    /// Without an `agentic` runtime a red consolidated run always goes to a
+    /// human.
     pub async fn execute(
         &self,
         db: &DatabaseConnection,
-        encryption_service: &EncryptionService,
+        encryption_service: &Arc<EncryptionService>,
+        provider_factory: &ProviderFactory,
         sandbox: Arc<dyn SandboxRunner>,
+        agentic: Option<&AgenticRuntime>,
     ) -> anyhow::Result<RunOutcome> {
         let run_repo: Arc<dyn RemediationRunRepository> =
             Arc::new(SeaOrmRemediationRunRepository::new(db.clone()));
@@ -85,11 +460,8 @@ impl RemediationRunJob {
             .await?
             .ok_or_else(|| anyhow::anyhow!("provider account {account_id} not found"))?;
 
-        let access_token = encryption_service.decrypt(&account.access_token_encrypted)?;
-        let credentials = ProviderCredentials::Pat {
-            token: access_token.clone(),
-            username: account.auth_username.clone(),
-        };
+        let credentials =
+            account_credentials(db, encryption_service, provider_factory, &account).await?;
 
         // Selected PRs (resolve the effective policy, then select).
         let resolver = PolicyResolver::new(db.clone());
@@ -103,25 +475,71 @@ impl RemediationRunJob {
 
         // Authenticated, capability-gated provider adapter.
         let provider = remediation_capable_provider(provider_kind, account.instance_url.clone());
+        // Git over HTTPS needs a bearer token; GitHub Apps mint one here
+        let access_token = provider.access_token(&credentials).await?;
+        let username = git_username(provider_kind, &credentials);
+        let agentic_tier = agentic
+            .filter(|_| tier_allows_agentic(criteria.remediation_tier))
+            .map(|runtime| {
+                let worktree = SandboxAgentWorktree::new(
+                    runtime.sandbox.clone(),
+                    repo.url.clone(),
+                    username.clone(),
+                    CredentialHandle::new(access_token.clone()),
+                );
+                build_agentic_tier(
+                    runtime,
+                    db,
+                    encryption_service,
+                    &repo,
+                    provider.clone(),
+                    credentials.clone(),
+                    Arc::new(worktree),
+                    ConsolidationSpec::branch_name_for(self.run_id),
+                    criteria.air_gapped,
+                )
+            });
         // Required-check names are not yet sourced from branch protection (Phase 2
         // follow-up); an empty set means the verifier gates purely on observed CI.
         let adapter: Arc<dyn RemediationProvider> = Arc::new(ProviderAdapter::new(
-            provider,
-            credentials,
+            provider.clone(),
+            credentials.clone(),
             repo.owner.clone(),
             repo.name.clone(),
             Vec::new(),
         ));
+        let suggestions = if run.autonomy_level == AutonomyLevel::SuggestOnly {
+            build_suggestions(
+                db,
+                encryption_service,
+                &repo,
+                provider,
+                credentials,
+                adapter.clone(),
+                &criteria,
+                agentic,
+            )
+            .await?
+        } else {
+            None
+        };
 
-        let executor =
+        let mut executor =
             RemediationExecutor::new(run_repo, sandbox, VerificationService::new(), adapter)
                 .with_provider_label(repo.provider.clone())
-                .with_notifier(notifier_from_env());
+                .with_consolidation_strategy(criteria.consolidation_strategy)
+                .with_notifier(build_notifier(db, encryption_service, repo.user_id).await);
+        if let Some(tier) = agentic_tier {
+            executor = executor.with_agentic_tier(Arc::new(tier), criteria.remediation_tier);
+        }
+        if let Some(publisher) = suggestions {
+            executor = executor.with_suggestions(Arc::new(publisher));
+        }
 
         let repo_ctx = RepoContext {
             clone_url: repo.url.clone(),
             default_branch: repo.default_branch.clone(),
-            credential: CredentialHandle::new(access_token),
+            credential: CredentialHandle::new(access_token).with_username(username),
         };
 
         let outcome = executor.execute(self.run_id, prs, repo_ctx).await?;
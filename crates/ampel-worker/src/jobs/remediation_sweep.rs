@@ -3,35 +3,24 @@
 //!
 //! Mirrors `poll_repository`'s sweep shape (oldest-first by `last_polled_at`,
 //! `limit(50)`, due-filter), then narrows to repos carrying an *enabled*
-//! remediation policy, caps the batch at `AMPEL_MAX_CONCURRENT_REPOS`, creates
-//! one run per qualifying repo, and drives it.
+//! remediation policy and no unfinished run, caps the batch at
+//! `AMPEL_MAX_CONCURRENT_REPOS`, and creates one run per qualifying repo.
 //!
-//! ## Enqueue decision
-//!
-//! The existing worker has no storage-backed Apalis queue (only `CronStream`
-//! cron jobs), so there is no enqueue primitive to chain a per-run job onto.
-//! Per the brief's fallback, the sweep therefore **drives each run inline**
-//! (sequentially, capped) by calling [`RemediationRunJob::execute`] directly.
-//! When a durable queue lands, swap the inline call for an enqueue of
-//! `RemediationRunJob { run_id }`.
-
-use std::sync::Arc;
+//! The sweep only creates runs. [`super::remediation_dispatch`] hands them to
+//! the durable run queue, where [`super::remediation_run::RemediationRunJob`]
+//! drives each one on its own worker slot, so a long sandbox build no longer
+//! holds up the rest of the batch.
 
 use chrono::{DateTime, Duration, Utc};
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 use serde::{Deserialize, Serialize};
 
 use ampel_core::services::{PolicyResolver, RemediationRunRepository};
-use ampel_db::encryption::EncryptionService;
 use ampel_db::entities::{remediation_policy, repository};
+use ampel_db::queries::RemediationRunQueries;
 use ampel_db::repositories::SeaOrmRemediationRunRepository;
 
-use ampel_core::services::SandboxRunner;
-
-use super::remediation_run::RemediationRunJob;
-
-/// Default cap on runs started per sweep tick.
-const DEFAULT_MAX_CONCURRENT_REPOS: usize = 3;
+use super::remediation_run::RunQueueConfig;
 
 /// Cron-driven sweep that starts remediation runs for due repositories.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,26 +36,17 @@ impl RemediationSweepJob {
     pub async fn execute(
         &self,
         db: &DatabaseConnection,
-        encryption_service: &EncryptionService,
-        sandbox: Arc<dyn SandboxRunner>,
+        config: &RunQueueConfig,
     ) -> anyhow::Result<()> {
-        let max_concurrent = std::env::var("AMPEL_MAX_CONCURRENT_REPOS")
-            .ok()
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_MAX_CONCURRENT_REPOS);
-
         let candidates = self.find_repos_to_remediate(db).await?;
         tracing::info!(
             "Remediation sweep: {} candidate repo(s), cap {}",
             candidates.len(),
-            max_concurrent
+            config.concurrency
         );
 
-        for repo in candidates.into_iter().take(max_concurrent) {
-            if let Err(e) = self
-                .start_run_for_repo(db, encryption_service, sandbox.clone(), &repo)
-                .await
-            {
+        for repo in candidates.into_iter().take(config.concurrency) {
+            if let Err(e) = self.start_run_for_repo(db, &repo).await {
                 tracing::error!(
                     "Failed to start remediation run for {}: {}",
                     repo.full_name,
@@ -79,7 +59,7 @@ impl RemediationSweepJob {
     }
 
     /// Repos due for polling (oldest-first, capped) that also carry an enabled
-    /// repository-scoped remediation policy.
+    /// repository-scoped remediation policy and have no run still in flight.
     pub async fn find_repos_to_remediate(
         &self,
         db: &DatabaseConnection,
@@ -109,7 +89,8 @@ impl RemediationSweepJob {
                 .one(db)
                 .await?
                 .is_some();
-            if has_enabled_policy {
+            if has_enabled_policy && !RemediationRunQueries::has_unfinished_run(db, repo.id).await?
+            {
                 out.push(repo);
             }
         }
@@ -119,8 +100,6 @@ impl RemediationSweepJob {
     async fn start_run_for_repo(
         &self,
         db: &DatabaseConnection,
-        encryption_service: &EncryptionService,
-        sandbox: Arc<dyn SandboxRunner>,
         repo: &repository::Model,
     ) -> anyhow::Result<()> {
         // Resolve the effective policy to learn the granted autonomy level.
@@ -129,17 +108,13 @@ impl RemediationSweepJob {
             .await?
             .ok_or_else(|| anyhow::anyhow!("no remediation policy resolved for {}", repo.id))?;
 
-        // Create the run (read-only autonomy still produces a run that no-ops).
+        // Create the run (read-only autonomy still produces a run that no-ops);
+        // the dispatcher enqueues it.
         let run_repo = SeaOrmRemediationRunRepository::new(db.clone());
         let run = run_repo
             .create_run(repo.id, criteria.autonomy_level)
             .await?;
-
-        // Inline drive (see "Enqueue decision" in the module docs).
-        let outcome = RemediationRunJob::new(run.id)
-            .execute(db, encryption_service, sandbox)
-            .await?;
-        tracing::info!(run_id = %run.id, repo = %repo.full_name, ?outcome, "sweep started run");
+        tracing::info!(run_id = %run.id, repo = %repo.full_name, "sweep created run");
         Ok(())
     }
 }
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use apalis::prelude::*;
 use apalis_cron::CronStream;
+use apalis_sql::postgres::PostgresStorage;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 rust_i18n::i18n!("locales", fallback = "en");
@@ -14,10 +15,19 @@ mod services;
 
 use ampel_core::services::SandboxRunner;
 use jobs::{
-    cleanup::CleanupJob, health_score::HealthScoreJob, metrics_collection::MetricsCollectionJob,
-    poll_repository::PollRepositoryJob, remediation_sweep::RemediationSweepJob,
+    auto_merge::AutoMergeJob,
+    cleanup::CleanupJob,
+    digest::DigestJob,
+    health_score::HealthScoreJob,
+    merge_queue::MergeQueueJob,
+    metrics_collection::MetricsCollectionJob,
+    notification_dispatch::NotificationDispatchJob,
+    poll_repository::PollRepositoryJob,
+    remediation_dispatch::RemediationDispatchJob,
+    remediation_run::{RemediationRunJob, RunQueueConfig},
+    remediation_sweep::RemediationSweepJob,
 };
-use services::PodmanSandboxRunner;
+use services::{AgenticRuntime, PodmanSandboxRunner, PollScheduler, SandboxConfig};
 
 #[derive(Clone)]
 pub struct WorkerState {
@@ -26,6 +36,14 @@ pub struct WorkerState {
     pub provider_factory: Arc<ampel_providers::ProviderFactory>,
     /// Sandbox runner used by the remediation jobs (Podman/Docker in prod).
     pub sandbox_runner: Arc<dyn SandboxRunner>,
+    /// Agentic remediation tier; `None` when no sandbox runtime is available,
+    /// in which case red consolidated runs go straight to a human.
+    pub agentic_runtime: Option<Arc<AgenticRuntime>>,
+    /// Per-account rate-limit budgets, kept across poll cycles.
+    pub poll_scheduler: Arc<PollScheduler>,
+    /// Durable queue remediation runs are dispatched to.
+    pub run_queue: PostgresStorage<RemediationRunJob>,
+    pub run_queue_config: RunQueueConfig,
 }
 
 #[tokio::main]
@@ -72,29 +90,62 @@ async fn main() -> anyhow::Result<()> {
             .expect("Invalid encryption key"),
     );
 
-    let provider_factory = Arc::new(ampel_providers::ProviderFactory::new());
+    let provider_factory =
+        Arc::new(ampel_providers::ProviderFactory::new().with_oauth_clients_from_env());
+
+    // Live dashboard events reach the API through Redis pub/sub
+    match std::env::var("REDIS_URL") {
+        Ok(redis_url) => match services::live_events::connect(&redis_url).await {
+            Ok(()) => tracing::info!("Publishing live dashboard events through Redis"),
+            Err(e) => {
+                tracing::warn!("Failed to connect to Redis, live dashboard events disabled: {e}")
+            }
+        },
+        Err(_) => tracing::info!("Redis URL not configured, live dashboard events disabled"),
+    }
 
     // Sandbox runner for remediation. Detection is deferred to first use in
     // prod; if no runtime is configured/available we log and fall back to a
-    // runner whose execution path errors cleanly (no panics at startup).
-    let sandbox_runner: Arc<dyn SandboxRunner> = match PodmanSandboxRunner::from_env() {
-        Ok(runner) => Arc::new(runner),
-        Err(e) => {
-            tracing::warn!("Sandbox runtime not configured ({e}); remediation runs will error until configured");
-            Arc::new(PodmanSandboxRunner::new(services::SandboxConfig {
-                runtime: services::sandbox_runner::SandboxRuntime::Podman,
-                image: "ghcr.io/ampel/remediation-sandbox:latest".to_string(),
-                clone_depth: 50,
-                subprocess_timeout: std::time::Duration::from_secs(300),
-            }))
-        }
-    };
+    // runner whose execution path errors cleanly (no panics at startup). The
+    // agentic tier shares the sandbox and is disabled without one.
+    let (sandbox_runner, agentic_runtime): (Arc<dyn SandboxRunner>, _) =
+        match SandboxConfig::from_env() {
+            Ok(config) => (
+                Arc::new(PodmanSandboxRunner::new(config.clone())),
+                Some(Arc::new(agentic_runtime(config))),
+            ),
+            Err(e) => {
+                tracing::warn!("Sandbox runtime not configured ({e}); remediation runs will error until configured");
+                let runner = PodmanSandboxRunner::new(SandboxConfig {
+                    runtime: services::sandbox_runner::SandboxRuntime::Podman,
+                    image: "ghcr.io/ampel/remediation-sandbox:latest".to_string(),
+                    clone_depth: 50,
+                    subprocess_timeout: std::time::Duration::from_secs(300),
+                });
+                (Arc::new(runner), None)
+            }
+        };
+
+    // Remediation runs go through a durable queue stored alongside the
+    // application tables, so a queued or in-flight run survives a restart.
+    let run_queue_pool = db.get_postgres_connection_pool().clone();
+    PostgresStorage::setup(&run_queue_pool).await?;
+    let run_queue = PostgresStorage::<RemediationRunJob>::new(run_queue_pool);
+    let run_queue_config = RunQueueConfig::from_env();
+    tracing::info!(
+        concurrency = run_queue_config.concurrency,
+        "Remediation run queue ready"
+    );
 
     let state = WorkerState {
         db,
         encryption_service,
         provider_factory,
         sandbox_runner,
+        agentic_runtime,
+        poll_scheduler: Arc::new(PollScheduler::new()),
+        run_queue: run_queue.clone(),
+        run_queue_config,
     };
 
     // Create job monitors
@@ -108,6 +159,43 @@ async fn main() -> anyhow::Result<()> {
                 ))
                 .build_fn(poll_repositories)
         })
+        .register({
+            WorkerBuilder::new("auto-merge")
+                .data(state.clone())
+                .backend(CronStream::new(
+                    // Run every minute, offset from polling so it sees fresh state
+                    apalis_cron::Schedule::from_str("30 * * * * *").unwrap(),
+                ))
+                .build_fn(run_auto_merge)
+        })
+        .register({
+            WorkerBuilder::new("merge-queue")
+                .data(state.clone())
+                .backend(CronStream::new(
+                    // Run every 5 seconds so queued bulk merges start promptly
+                    apalis_cron::Schedule::from_str("*/5 * * * * *").unwrap(),
+                ))
+                .build_fn(run_merge_queue)
+        })
+        .register({
+            WorkerBuilder::new("notification-dispatch")
+                .data(state.clone())
+                .backend(CronStream::new(
+                    // Run every 15 seconds so notifications go out promptly
+                    apalis_cron::Schedule::from_str("*/15 * * * * *").unwrap(),
+                ))
+                .build_fn(run_notification_dispatch)
+        })
+        .register({
+            WorkerBuilder::new("digest")
+                .data(state.clone())
+                .backend(CronStream::new(
+                    // Run every hour, after health scores are recalculated; a
+                    // digest period is only queued once
+                    apalis_cron::Schedule::from_str("0 10 * * * *").unwrap(),
+                ))
+                .build_fn(run_digest)
+        })
         .register({
             WorkerBuilder::new("cleanup")
                 .data(state.clone())
@@ -143,6 +231,22 @@ async fn main() -> anyhow::Result<()> {
                     apalis_cron::Schedule::from_str("0 */15 * * * *").unwrap(),
                 ))
                 .build_fn(run_remediation_sweep)
+        })
+        .register({
+            WorkerBuilder::new("remediation-dispatch")
+                .data(state.clone())
+                .backend(CronStream::new(
+                    // Run every 10 seconds so new and approved runs start promptly
+                    apalis_cron::Schedule::from_str("*/10 * * * * *").unwrap(),
+                ))
+                .build_fn(run_remediation_dispatch)
+        })
+        .register({
+            WorkerBuilder::new("remediation-run")
+                .concurrency(state.run_queue_config.concurrency)
+                .data(state.clone())
+                .backend(run_queue)
+                .build_fn(run_remediation_run)
         });
 
     tracing::info!("Starting job monitors...");
@@ -152,6 +256,21 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The agentic tier's runtime; with the `reflexion` feature every run shares
+/// one in-memory vector memory.
+fn agentic_runtime(sandbox: SandboxConfig) -> AgenticRuntime {
+    let runtime = AgenticRuntime::from_env(sandbox);
+    #[cfg(feature = "reflexion")]
+    let runtime = match services::reflexion::VectorReflexionMemory::new() {
+        Ok(memory) => runtime.with_reflexion_memory(Arc::new(memory)),
+        Err(e) => {
+            tracing::warn!("Reflexion memory unavailable ({e}); continuing without recall");
+            runtime
+        }
+    };
+    runtime
+}
+
 async fn poll_repositories(_job: PollRepositoryJob, state: Data<WorkerState>) -> Result<(), Error> {
     tracing::info!("Running repository poll job");
 
@@ -161,6 +280,7 @@ async fn poll_repositories(_job: PollRepositoryJob, state: Data<WorkerState>) ->
             &state.db,
             &state.encryption_service,
             &state.provider_factory,
+            &state.poll_scheduler,
         )
         .await
     {
@@ -170,6 +290,67 @@ async fn poll_repositories(_job: PollRepositoryJob, state: Data<WorkerState>) ->
     Ok(())
 }
 
+async fn run_auto_merge(_job: AutoMergeJob, state: Data<WorkerState>) -> Result<(), Error> {
+    tracing::info!("Running auto-merge job");
+
+    let job = jobs::auto_merge::AutoMergeJob;
+    if let Err(e) = job
+        .execute(
+            &state.db,
+            &state.encryption_service,
+            &state.provider_factory,
+        )
+        .await
+    {
+        tracing::error!("Auto-merge job failed: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn run_merge_queue(_job: MergeQueueJob, state: Data<WorkerState>) -> Result<(), Error> {
+    tracing::debug!("Running merge queue job");
+
+    let job = jobs::merge_queue::MergeQueueJob;
+    if let Err(e) = job
+        .execute(
+            &state.db,
+            &state.encryption_service,
+            &state.provider_factory,
+        )
+        .await
+    {
+        tracing::error!("Merge queue job failed: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn run_notification_dispatch(
+    _job: NotificationDispatchJob,
+    state: Data<WorkerState>,
+) -> Result<(), Error> {
+    tracing::debug!("Running notification dispatch job");
+
+    let job = jobs::notification_dispatch::NotificationDispatchJob;
+    if let Err(e) = job.execute(&state.db, &state.encryption_service).await {
+        tracing::error!("Notification dispatch job failed: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn run_digest(_job: DigestJob, state: Data<WorkerState>) -> Result<(), Error> {
+    tracing::debug!("Running digest job");
+
+    let job = jobs::digest::DigestJob;
+    if let Err(e) = job.execute(&state.db).await {
+        tracing::error!("Digest job failed: {}", e);
+    }
+
+    Ok(())
+}
+
 async fn run_cleanup(_job: CleanupJob, state: Data<WorkerState>) -> Result<(), Error> {
     tracing::info!("Running cleanup job");
 
@@ -216,15 +397,50 @@ async fn run_remediation_sweep(
     tracing::info!("Running remediation sweep job");
 
     let job = jobs::remediation_sweep::RemediationSweepJob;
+    if let Err(e) = job.execute(&state.db, &state.run_queue_config).await {
+        tracing::error!("Remediation sweep job failed: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn run_remediation_dispatch(
+    _job: RemediationDispatchJob,
+    state: Data<WorkerState>,
+) -> Result<(), Error> {
+    tracing::debug!("Running remediation dispatch job");
+
+    let job = jobs::remediation_dispatch::RemediationDispatchJob;
     if let Err(e) = job
-        .execute(
+        .execute(&state.db, &state.run_queue, &state.run_queue_config)
+        .await
+    {
+        tracing::error!("Remediation dispatch job failed: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn run_remediation_run(
+    job: RemediationRunJob,
+    state: Data<WorkerState>,
+) -> Result<(), Error> {
+    tracing::info!(run_id = %job.run_id, "Running remediation run job");
+
+    // Retries and dead-lettering are tracked on the run itself (see
+    // `RemediationRunJob::drive`), so the queue job always completes.
+    if let Err(e) = job
+        .drive(
             &state.db,
             &state.encryption_service,
+            &state.provider_factory,
             state.sandbox_runner.clone(),
+            state.agentic_runtime.as_deref(),
+            &state.run_queue_config,
         )
         .await
     {
-        tracing::error!("Remediation sweep job failed: {}", e);
+        tracing::error!(run_id = %job.run_id, "Remediation run job failed: {}", e);
     }
 
     Ok(())
@@ -1,4 +1,5 @@
-//! Worker-side Prometheus metrics for autonomous PR remediation (Phase 3).
+//! Worker-side Prometheus metrics for autonomous PR remediation (Phase 3) and
+//! the rate-limit-aware poll scheduler.
 //!
 //! The metrics crate is kept out of `ampel-core` (which must stay
 //! dependency-light); all remediation counters/histograms are emitted from the
@@ -9,9 +10,14 @@
 //!
 //! Cardinality: every label value is drawn from a small bounded set
 //! (terminal run states, provider kinds, conflict classes, handoff reasons) —
-//! never free-form text or anything secret-bearing.
+//! never free-form text or anything secret-bearing. The poll metrics carry an
+//! `account` label (the provider account id), bounded by the number of
+//! connected accounts.
 
-use metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+use metrics::{
+    counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit,
+};
+use uuid::Uuid;
 
 /// Terminal-run counter, labelled by `state`.
 pub const RUNS_TOTAL: &str = "remediation_runs_total";
@@ -29,6 +35,12 @@ pub const AGENT_ITERATIONS_TOTAL: &str = "remediation_agent_iterations_total";
 pub const AGENT_COST_USD: &str = "remediation_agent_cost_usd";
 /// Agentic-tier session counter (Phase 4), labelled by terminal `outcome`.
 pub const AGENT_SESSIONS_TOTAL: &str = "remediation_agent_sessions_total";
+/// Remaining provider API quota per account, labelled by `account`.
+pub const POLL_QUOTA_REMAINING: &str = "poll_quota_remaining";
+/// Provider API quota per reset window, labelled by `account`.
+pub const POLL_QUOTA_LIMIT: &str = "poll_quota_limit";
+/// Repository polls pushed to a later cycle, labelled by `account` and `reason`.
+pub const POLL_DEFERRALS_TOTAL: &str = "poll_deferrals_total";
 
 /// Describe every remediation metric. Safe to call once at worker startup
 /// (mirrors the `ampel-api` describe pattern).
@@ -66,6 +78,18 @@ pub fn describe_metrics() {
         AGENT_SESSIONS_TOTAL,
         "Total agentic-tier remediation sessions, by terminal outcome"
     );
+    describe_gauge!(
+        POLL_QUOTA_REMAINING,
+        "Provider API calls left in the current rate-limit window, by account"
+    );
+    describe_gauge!(
+        POLL_QUOTA_LIMIT,
+        "Provider API calls allowed per rate-limit window, by account"
+    );
+    describe_counter!(
+        POLL_DEFERRALS_TOTAL,
+        "Total repository polls deferred to a later cycle, by account and reason"
+    );
 }
 
 /// Record one completed agentic-tier session: its iteration + spend totals and a
@@ -103,6 +127,20 @@ pub fn record_handoff(reason: &'static str) {
     counter!(HANDOFFS_TOTAL, "reason" => reason).increment(1);
 }
 
+/// Record an account's current provider quota.
+pub fn record_poll_quota(account_id: Uuid, limit: i32, remaining: i32) {
+    let account = account_id.to_string();
+    gauge!(POLL_QUOTA_LIMIT, "account" => account.clone()).set(limit as f64);
+    gauge!(POLL_QUOTA_REMAINING, "account" => account).set(remaining as f64);
+}
+
+/// Record `count` deferred repository polls with a bounded `reason`
+/// (`budget` or `rate_limited`).
+pub fn record_poll_deferrals(account_id: Uuid, reason: &'static str, count: usize) {
+    counter!(POLL_DEFERRALS_TOTAL, "account" => account_id.to_string(), "reason" => reason)
+        .increment(count as u64);
+}
+
 /// Map a free-form skipped-conflict reason onto a bounded, low-cardinality
 /// class suitable for a Prometheus label. Keeps the `conflict_class` label from
 /// exploding on per-PR reason strings.
@@ -26,7 +26,6 @@
 //!   (`is_untrusted_data = true`); the rendered instructions never contain them.
 //! - Secrets: `creds` are used for the call and never logged (their `Debug`
 //!   redacts `api_key`); nothing here writes the key to a log/transcript.
-#![allow(dead_code)] // wired into the worker binary in slice 3
 
 use std::sync::Arc;
 use std::time::Instant;
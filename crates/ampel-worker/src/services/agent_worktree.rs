@@ -0,0 +1,458 @@
+//! Podman/Docker-backed [`AgentWorktree`] (Phase 4, ADR-003/006).
+//!
+//! The agentic tier edits the consolidation branch (`ampel/remediation/<run_id>`)
+//! that the sandbox consolidation pushed. Each operation runs a short-lived
+//! container against a per-run host workspace mounted at `/workspace`: the
+//! first call clones the branch into `/workspace/repo`, model output is applied
+//! there, and [`AgentWorktree::commit_and_push`] pushes the result back to the
+//! same branch.
+//!
+//! Model output is translated into [`WorktreeEdit`]s first (pure, unit-tested):
+//! a `UnifiedDiff` and the `apply_patch` tool become `git apply --index`, and
+//! `write_file` replaces one repo-relative file. The read-only tools
+//! (`read_file`, `run_tests`, `run_build`) produce no edit — the loop feeds CI
+//! results back on the next iteration instead. `Classification` output is a
+//! no-op.
+//!
+//! Security invariants (same as [`super::sandbox_runner`]):
+//! - The token reaches the container through an inherited env var (`-e NAME`
+//!   with the value set on the runtime process), never as a CLI arg; git reads
+//!   it through an inline credential helper.
+//! - Clone URL, branch, paths and commit message are passed as env vars, so no
+//!   model-controlled text is ever interpolated into the shell script.
+//! - `GIT_TERMINAL_PROMPT=0`; the push is a plain fast-forward (no force).
+
+use std::path::{Component, Path, PathBuf};
+
+use ampel_core::errors::{AmpelError, AmpelResult};
+use ampel_core::models::GitProvider as ProviderKind;
+use ampel_core::remediation::NormalizedProviderOutput;
+use ampel_core::services::CredentialHandle;
+use ampel_providers::traits::ProviderCredentials;
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use super::agent_harness::AgentWorktree;
+use super::sandbox_runner::{
+    io_err, run_in_container, scrub_secret, SandboxConfig, CREDENTIAL_HELPER, REPO_DIR, WORKSPACE,
+};
+
+/// Staging file (outside the clone) for the content of the edit being applied.
+const EDIT_FILE: &str = "ampel-edit";
+
+const CLONE_SCRIPT: &str = r#"set -eu
+git -c "$AMPEL_CREDENTIAL_HELPER" clone --depth "$AMPEL_CLONE_DEPTH" --branch "$AMPEL_BRANCH" "$AMPEL_CLONE_URL" repo"#;
+
+const APPLY_PATCH_SCRIPT: &str = r#"set -eu
+git apply --index --whitespace=nowarn /workspace/ampel-edit"#;
+
+const WRITE_FILE_SCRIPT: &str = r#"set -eu
+mkdir -p "$(dirname -- "$AMPEL_TARGET_PATH")"
+cp /workspace/ampel-edit "$AMPEL_TARGET_PATH"
+git add -- "$AMPEL_TARGET_PATH""#;
+
+const COMMIT_AND_PUSH_SCRIPT: &str = r#"set -eu
+git add -A
+if git diff --cached --quiet; then
+  echo "nothing to commit"
+  exit 0
+fi
+git -c user.name="Ampel Remediation" -c user.email="remediation@ampel.local" commit -q -m "$AMPEL_COMMIT_MESSAGE"
+git -c "$AMPEL_CREDENTIAL_HELPER" push origin "HEAD:refs/heads/$AMPEL_BRANCH""#;
+
+/// One change to the worktree, derived from model output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WorktreeEdit {
+    /// A unified diff applied with `git apply --index`.
+    Patch(String),
+    /// Replace (or create) a repo-relative file.
+    WriteFile { path: String, content: String },
+}
+
+/// Translate normalized model output into worktree edits.
+///
+/// Tool calls the loop cannot act on (`read_file`, `run_tests`, `run_build`)
+/// are dropped; an unknown tool or a malformed edit is an error, which ends the
+/// agent loop rather than pushing a partial change.
+pub fn edits_from_output(output: &NormalizedProviderOutput) -> AmpelResult<Vec<WorktreeEdit>> {
+    match output {
+        NormalizedProviderOutput::UnifiedDiff(diff) => Ok(if diff.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![WorktreeEdit::Patch(diff.clone())]
+        }),
+        NormalizedProviderOutput::ToolCalls(calls) => {
+            let mut edits = Vec::new();
+            for call in calls {
+                match call.name.as_str() {
+                    "apply_patch" => {
+                        let diff = string_argument(&call.arguments, &["diff", "patch"])
+                            .ok_or_else(|| tool_error("apply_patch", "missing `diff`"))?;
+                        edits.push(WorktreeEdit::Patch(diff));
+                    }
+                    "write_file" => {
+                        let path = string_argument(&call.arguments, &["path"])
+                            .ok_or_else(|| tool_error("write_file", "missing `path`"))?;
+                        let content = string_argument(&call.arguments, &["content"])
+                            .ok_or_else(|| tool_error("write_file", "missing `content`"))?;
+                        validate_repo_path(&path)?;
+                        edits.push(WorktreeEdit::WriteFile { path, content });
+                    }
+                    "read_file" | "run_tests" | "run_build" => {}
+                    other => {
+                        return Err(AmpelError::ValidationError(format!(
+                            "unsupported agent tool `{other}`"
+                        )))
+                    }
+                }
+            }
+            Ok(edits)
+        }
+        NormalizedProviderOutput::Classification(_) => Ok(Vec::new()),
+    }
+}
+
+fn string_argument(arguments: &serde_json::Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| arguments.get(key).and_then(|v| v.as_str()))
+        .map(str::to_string)
+}
+
+fn tool_error(tool: &str, message: &str) -> AmpelError {
+    AmpelError::ValidationError(format!("agent tool `{tool}`: {message}"))
+}
+
+/// Reject paths that could escape the clone or touch git internals.
+pub fn validate_repo_path(path: &str) -> AmpelResult<()> {
+    let p = Path::new(path);
+    let escapes = path.is_empty()
+        || p.components()
+            .any(|c| !matches!(c, Component::Normal(_)) || c.as_os_str() == ".git");
+    if escapes {
+        return Err(AmpelError::ValidationError(format!(
+            "agent path `{path}` must be relative to the repository and outside .git"
+        )));
+    }
+    Ok(())
+}
+
+/// The HTTPS username git presents alongside the token. Bitbucket app
+/// passwords carry the account username; token auth uses the provider's fixed
+/// placeholder user.
+pub fn git_username(kind: ProviderKind, credentials: &ProviderCredentials) -> String {
+    match credentials {
+        ProviderCredentials::Pat {
+            username: Some(username),
+            ..
+        } if !username.is_empty() => username.clone(),
+        _ => match kind {
+            ProviderKind::GitHub => "x-access-token",
+            ProviderKind::GitLab => "oauth2",
+            ProviderKind::Bitbucket => "x-token-auth",
+        }
+        .to_string(),
+    }
+}
+
+/// Production [`AgentWorktree`]: a per-run host workspace driven through the
+/// configured container runtime. The workspace is removed on drop.
+pub struct SandboxAgentWorktree {
+    config: SandboxConfig,
+    clone_url: String,
+    git_username: String,
+    credential: CredentialHandle,
+    workspace: PathBuf,
+    cloned: OnceCell<()>,
+}
+
+impl SandboxAgentWorktree {
+    pub fn new(
+        config: SandboxConfig,
+        clone_url: impl Into<String>,
+        git_username: impl Into<String>,
+        credential: CredentialHandle,
+    ) -> Self {
+        Self {
+            config,
+            clone_url: clone_url.into(),
+            git_username: git_username.into(),
+            credential,
+            workspace: std::env::temp_dir().join(format!("ampel-agent-{}", Uuid::new_v4())),
+            cloned: OnceCell::new(),
+        }
+    }
+
+    /// Clone `branch` into the workspace on first use.
+    async fn ensure_cloned(&self, branch: &str) -> AmpelResult<()> {
+        self.cloned
+            .get_or_try_init(|| async {
+                tokio::fs::create_dir_all(&self.workspace)
+                    .await
+                    .map_err(io_err)?;
+                let depth = self.config.clone_depth.to_string();
+                self.run(
+                    WORKSPACE,
+                    &[
+                        ("AMPEL_CLONE_URL", self.clone_url.as_str()),
+                        ("AMPEL_BRANCH", branch),
+                        ("AMPEL_CLONE_DEPTH", depth.as_str()),
+                    ],
+                    CLONE_SCRIPT,
+                )
+                .await
+            })
+            .await
+            .map(|_| ())
+    }
+
+    async fn stage(&self, content: &str) -> AmpelResult<()> {
+        tokio::fs::write(self.workspace.join(EDIT_FILE), content)
+            .await
+            .map_err(io_err)
+    }
+
+    /// Run `script` in the sandbox image. Git credentials and the prompt guard
+    /// are always present; `vars` adds per-step values.
+    async fn run(&self, workdir: &str, vars: &[(&str, &str)], script: &str) -> AmpelResult<()> {
+        let mut env: Vec<(&str, &str)> = vec![
+            ("AMPEL_GIT_USERNAME", self.git_username.as_str()),
+            ("AMPEL_GIT_TOKEN", self.credential.expose()),
+            ("AMPEL_CREDENTIAL_HELPER", CREDENTIAL_HELPER),
+            ("GIT_TERMINAL_PROMPT", "0"),
+        ];
+        env.extend_from_slice(vars);
+        let output =
+            run_in_container(&self.config, &self.workspace, workdir, &env, script, &[]).await?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(AmpelError::InternalError(format!(
+            "sandbox step failed ({}): {}",
+            output.status,
+            scrub_secret(stderr.trim(), self.credential.expose())
+        )))
+    }
+}
+
+#[async_trait]
+impl AgentWorktree for SandboxAgentWorktree {
+    async fn apply_output(
+        &self,
+        worktree_ref: &str,
+        output: &NormalizedProviderOutput,
+    ) -> AmpelResult<()> {
+        let edits = edits_from_output(output)?;
+        if edits.is_empty() {
+            return Ok(());
+        }
+        self.ensure_cloned(worktree_ref).await?;
+        for edit in edits {
+            match edit {
+                WorktreeEdit::Patch(diff) => {
+                    self.stage(&diff).await?;
+                    self.run(REPO_DIR, &[], APPLY_PATCH_SCRIPT).await?;
+                }
+                WorktreeEdit::WriteFile { path, content } => {
+                    self.stage(&content).await?;
+                    self.run(
+                        REPO_DIR,
+                        &[("AMPEL_TARGET_PATH", path.as_str())],
+                        WRITE_FILE_SCRIPT,
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn commit_and_push(&self, worktree_ref: &str, message: &str) -> AmpelResult<()> {
+        self.ensure_cloned(worktree_ref).await?;
+        self.run(
+            REPO_DIR,
+            &[
+                ("AMPEL_BRANCH", worktree_ref),
+                ("AMPEL_COMMIT_MESSAGE", message),
+            ],
+            COMMIT_AND_PUSH_SCRIPT,
+        )
+        .await
+    }
+}
+
+impl Drop for SandboxAgentWorktree {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.workspace) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(
+                    workspace = %self.workspace.display(),
+                    error = %e,
+                    "failed to remove agent workspace"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::sandbox_runner::{container_args, SandboxRuntime};
+    use ampel_core::remediation::ToolCall;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn config(runtime: SandboxRuntime) -> SandboxConfig {
+        SandboxConfig {
+            runtime,
+            image: "ghcr.io/ampel/remediation-sandbox:latest".to_string(),
+            clone_depth: 50,
+            subprocess_timeout: Duration::from_secs(300),
+        }
+    }
+
+    fn call(name: &str, arguments: serde_json::Value) -> ToolCall {
+        ToolCall {
+            name: name.to_string(),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn should_turn_a_unified_diff_into_one_patch() {
+        let edits = edits_from_output(&NormalizedProviderOutput::UnifiedDiff(
+            "--- a\n+++ b\n".into(),
+        ))
+        .unwrap();
+        assert_eq!(edits, vec![WorktreeEdit::Patch("--- a\n+++ b\n".into())]);
+
+        let empty = edits_from_output(&NormalizedProviderOutput::UnifiedDiff("  \n".into()));
+        assert!(empty.unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_translate_edit_tools_and_skip_read_only_ones() {
+        let output = NormalizedProviderOutput::ToolCalls(vec![
+            call("read_file", json!({ "path": "src/lib.rs" })),
+            call("apply_patch", json!({ "diff": "--- a\n+++ b\n" })),
+            call(
+                "write_file",
+                json!({ "path": "src/lib.rs", "content": "fn main() {}\n" }),
+            ),
+            call("run_tests", json!({})),
+        ]);
+
+        assert_eq!(
+            edits_from_output(&output).unwrap(),
+            vec![
+                WorktreeEdit::Patch("--- a\n+++ b\n".into()),
+                WorktreeEdit::WriteFile {
+                    path: "src/lib.rs".into(),
+                    content: "fn main() {}\n".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_tools_and_malformed_edits() {
+        let unknown = NormalizedProviderOutput::ToolCalls(vec![call("shell", json!({}))]);
+        assert!(edits_from_output(&unknown).is_err());
+
+        let missing = NormalizedProviderOutput::ToolCalls(vec![call(
+            "write_file",
+            json!({ "path": "a.txt" }),
+        )]);
+        assert!(edits_from_output(&missing).is_err());
+    }
+
+    #[test]
+    fn should_reject_paths_outside_the_clone() {
+        assert!(validate_repo_path("src/lib.rs").is_ok());
+        for path in [
+            "",
+            "/etc/passwd",
+            "../secrets",
+            "src/../../x",
+            ".git/config",
+        ] {
+            assert!(
+                validate_repo_path(path).is_err(),
+                "{path} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn should_pick_git_username_per_provider() {
+        let oauth = ProviderCredentials::OAuth {
+            access_token: "t".into(),
+            refresh_token: None,
+            expires_at: None,
+        };
+        assert_eq!(git_username(ProviderKind::GitHub, &oauth), "x-access-token");
+        assert_eq!(git_username(ProviderKind::GitLab, &oauth), "oauth2");
+        assert_eq!(
+            git_username(ProviderKind::Bitbucket, &oauth),
+            "x-token-auth"
+        );
+
+        let app_password = ProviderCredentials::Pat {
+            token: "t".into(),
+            username: Some("octo".into()),
+        };
+        assert_eq!(git_username(ProviderKind::Bitbucket, &app_password), "octo");
+    }
+
+    #[test]
+    fn should_pass_env_by_name_only() {
+        let args = container_args(
+            &config(SandboxRuntime::Docker),
+            Path::new("/tmp/ws"),
+            REPO_DIR,
+            &["AMPEL_GIT_TOKEN", "AMPEL_BRANCH"],
+            COMMIT_AND_PUSH_SCRIPT,
+        );
+        assert_eq!(
+            &args[..8],
+            &[
+                "run",
+                "--rm",
+                "-v",
+                "/tmp/ws:/workspace",
+                "-w",
+                REPO_DIR,
+                "-e",
+                "AMPEL_GIT_TOKEN"
+            ]
+        );
+        assert!(args
+            .iter()
+            .all(|a| !a.contains('=') || a == COMMIT_AND_PUSH_SCRIPT));
+        assert_eq!(
+            args.last().map(String::as_str),
+            Some(COMMIT_AND_PUSH_SCRIPT)
+        );
+    }
+
+    #[test]
+    fn should_relabel_the_mount_for_podman() {
+        let args = container_args(
+            &config(SandboxRuntime::Podman),
+            Path::new("/tmp/ws"),
+            WORKSPACE,
+            &[],
+            CLONE_SCRIPT,
+        );
+        assert_eq!(args[3], "/tmp/ws:/workspace:Z");
+    }
+
+    #[test]
+    fn should_never_force_push() {
+        assert!(!COMMIT_AND_PUSH_SCRIPT.contains("--force"));
+        assert!(!COMMIT_AND_PUSH_SCRIPT.contains("+HEAD"));
+    }
+}
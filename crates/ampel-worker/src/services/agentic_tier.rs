@@ -22,15 +22,21 @@
 //! via [`EncryptionService`] for exactly the harness run. They are never
 //! serialized, logged, or written to the session transcript/row.
 //!
-//! NOTE: `#![allow(dead_code)]` — the seam, helpers, and [`DbAgenticTier`] are
-//! exercised by unit + executor integration tests and exported from the library,
-//! but the worker *binary* does not yet construct the sandbox-backed
-//! [`AgentWorktree`]/[`CiVerifier`] bridges; that production wiring is a
-//! follow-up. The bin target would otherwise flag these as unused.
+//! ## Production wiring
+//! The remediation run job builds one [`DbAgenticTier`] per run from the
+//! worker's [`AgenticRuntime`]: the sandbox-backed
+//! [`SandboxAgentWorktree`](super::agent_worktree::SandboxAgentWorktree) edits
+//! and pushes the consolidation branch, and the
+//! [`ProviderCiVerifier`](super::ci_verifier::ProviderCiVerifier) polls its CI.
+//!
+//! NOTE: `#![allow(dead_code)]` — the test seams (provider/playbook overrides)
+//! and helpers are exercised by unit + executor integration tests only; the bin
+//! target would otherwise flag them as unused.
 #![allow(dead_code)]
 
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ampel_core::errors::{AmpelError, AmpelResult};
 use ampel_core::remediation::{
@@ -54,6 +60,7 @@ use uuid::Uuid;
 use super::agent_harness::{AgentWorktree, CiVerifier, RemediationAgentHarness};
 use super::playbook::Playbook;
 use super::playbook_resolver::{resolve, PlaybookContext, PlaybookScope};
+use super::sandbox_runner::{parse_env_u64, SandboxConfig};
 use crate::providers::build_model_provider;
 
 /// Whether a resolved tier may invoke the agentic (model-driven) path.
@@ -136,6 +143,41 @@ impl AccountScope {
     }
 }
 
+/// Worker-wide configuration for the production agentic tier, built once at
+/// startup. The remediation run job derives each run's sandbox worktree and CI
+/// verifier from it.
+#[derive(Clone)]
+pub struct AgenticRuntime {
+    /// Container runtime + image the agent's worktree runs in.
+    pub sandbox: SandboxConfig,
+    /// Delay between CI status polls after a push.
+    pub ci_poll_interval: Duration,
+    /// How long one verification waits for CI to finish before reporting red.
+    pub ci_timeout: Duration,
+    /// Shared reflexion memory (`reflexion` feature); `None` keeps the
+    /// deterministic learning_signal-only path.
+    pub reflexion_memory: Option<Arc<dyn ReflexionMemory>>,
+}
+
+impl AgenticRuntime {
+    /// Read the CI polling knobs (`AMPEL_AGENT_CI_POLL_SECS`, default 30, and
+    /// `AMPEL_AGENT_CI_TIMEOUT_SECS`, default 600) from the environment.
+    pub fn from_env(sandbox: SandboxConfig) -> Self {
+        Self {
+            sandbox,
+            ci_poll_interval: Duration::from_secs(parse_env_u64("AMPEL_AGENT_CI_POLL_SECS", 30)),
+            ci_timeout: Duration::from_secs(parse_env_u64("AMPEL_AGENT_CI_TIMEOUT_SECS", 600)),
+            reflexion_memory: None,
+        }
+    }
+
+    /// Share one reflexion memory across every run the worker drives.
+    pub fn with_reflexion_memory(mut self, memory: Arc<dyn ReflexionMemory>) -> Self {
+        self.reflexion_memory = Some(memory);
+        self
+    }
+}
+
 /// Resolve the EXECUTION playbook + clamped budget for the agentic loop.
 ///
 /// Routes through [`resolve`] (B) so the embedded tools-policy ceiling clamp
@@ -186,11 +228,12 @@ pub struct DbAgenticTier {
     /// Opaque sandbox worktree reference the agent edits in.
     worktree_ref: String,
     /// The current failing CI logs (untrusted data; carried in context blocks).
+    /// Empty → fetched fresh from the verifier at the start of the attempt.
     failing_logs: String,
     /// Optional DB playbook override YAML (else the embedded default is used).
     playbook_override_yaml: Option<String>,
     /// The run's tenant scope for account selection (ADR-008). `None` selects
-    /// globally — see the MUST-FIX-BEFORE-WIRING note on [`select_account`].
+    /// globally — production always sets it (see [`select_account`]).
     account_scope: Option<AccountScope>,
     /// Test seam: inject a provider (e.g. `MockModelProvider`) instead of the
     /// real reqwest factory. `None` in production → [`build_model_provider`].
@@ -205,6 +248,10 @@ pub struct DbAgenticTier {
     /// the deterministic learning_signal-only path. Production constructs the
     /// vector-backed memory only behind the `reflexion` cargo feature.
     reflexion_memory: Option<Arc<dyn ReflexionMemory>>,
+    /// Optional cap on the loop's iterations, applied after the ceiling clamp.
+    /// Suggest-only runs set 1: a proposal is never pushed, so CI cannot say
+    /// whether another iteration would help.
+    max_iterations: Option<u32>,
 }
 
 impl DbAgenticTier {
@@ -235,6 +282,7 @@ impl DbAgenticTier {
             provider_override: None,
             learning_recorder: None,
             reflexion_memory: None,
+            max_iterations: None,
         }
     }
 
@@ -255,8 +303,8 @@ impl DbAgenticTier {
     }
 
     /// Restrict model-account selection to the run's tenant scope (ADR-008). The
-    /// production wiring MUST call this with the run's repository org/owner before
-    /// dispatch (see [`select_account`]).
+    /// production wiring calls this with the run's repository owner and
+    /// organization before dispatch (see [`select_account`]).
     pub fn with_account_scope(mut self, scope: AccountScope) -> Self {
         self.account_scope = Some(scope);
         self
@@ -268,6 +316,12 @@ impl DbAgenticTier {
         self
     }
 
+    /// Cap the loop at `max_iterations`, below whatever the playbook allows.
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
     /// Inject a provider (test seam). Production omits this and the kind-driven
     /// [`build_model_provider`] factory is used instead.
     pub fn with_provider_override(mut self, provider: Arc<dyn ModelProvider>) -> Self {
@@ -282,14 +336,11 @@ impl DbAgenticTier {
     /// organization and/or user so a run can never select another tenant's
     /// account (ADR-008 multi-tenant isolation).
     ///
-    /// MUST-FIX-BEFORE-WIRING: `DbAgenticTier` is not yet constructed by the
-    /// worker binary. When it is, the production caller MUST thread the run's
-    /// repository org/owner via [`DbAgenticTier::with_account_scope`]; an unset
-    /// scope selects GLOBALLY across all tenants and must not reach production.
-    /// (The run_id → repository → organization resolution is intentionally not
-    /// threaded in this slice to avoid a DB lookup the standalone tests can't
-    /// satisfy; the scoping mechanism + filter are in place and tested.)
-    async fn select_account(&self) -> AmpelResult<model_provider_account::Model> {
+    /// The remediation run job always sets the scope from the run's repository
+    /// (owner + organization); an unset scope selects GLOBALLY across all
+    /// tenants and is only used by standalone tests. `None` when no account is
+    /// configured in scope.
+    async fn select_account(&self) -> AmpelResult<Option<model_provider_account::Model>> {
         let mut query = model_provider_account::Entity::find()
             .filter(model_provider_account::Column::Enabled.eq(true));
 
@@ -309,10 +360,7 @@ impl DbAgenticTier {
             .order_by_asc(model_provider_account::Column::CreatedAt)
             .one(&self.db)
             .await
-            .map_err(|e| AmpelError::DatabaseError(e.to_string()))?
-            .ok_or_else(|| {
-                AmpelError::ValidationError("no enabled model provider account configured".into())
-            })
+            .map_err(|e| AmpelError::DatabaseError(e.to_string()))
     }
 
     /// Persist the agent-session row. Never contains secrets.
@@ -356,7 +404,12 @@ impl DbAgenticTier {
     /// Resolve the effective playbook (embedded default, optionally overridden)
     /// and its ceiling-clamped budget via [`resolve_execution_playbook`].
     fn resolve_playbook(&self) -> AmpelResult<(Playbook, AgentBudget)> {
-        resolve_execution_playbook(self.playbook_override_yaml.as_deref())
+        let (playbook, mut budget) =
+            resolve_execution_playbook(self.playbook_override_yaml.as_deref())?;
+        if let Some(cap) = self.max_iterations {
+            budget.max_iterations = budget.max_iterations.min(cap);
+        }
+        Ok((playbook, budget))
     }
 }
 
@@ -366,15 +419,32 @@ impl AgenticTier for DbAgenticTier {
         // Wall-clock start for the learning signal's duration_secs (Phase 5b).
         let started = std::time::Instant::now();
 
-        // 1. Select the account + provider.
-        let account = self.select_account().await?;
+        // 1. The failing logs: supplied, else fetched fresh for the branch. CI that
+        // has meanwhile turned green needs no fix — let the executor re-verify.
+        let failing_logs = if self.failing_logs.is_empty() {
+            let status = self.verifier.verify(&self.worktree_ref).await?;
+            if status.green {
+                tracing::info!(%run_id, "agentic tier skipped: CI is already green");
+                return Ok(AgentTierOutcome::Recovered);
+            }
+            status.logs
+        } else {
+            self.failing_logs.clone()
+        };
+
+        // 2. Select the account + provider. Without one in scope there is no
+        // model to drive, so the run goes to a human.
+        let Some(account) = self.select_account().await? else {
+            tracing::warn!(%run_id, "agentic tier skipped: no enabled model provider account");
+            return Ok(AgentTierOutcome::Exhausted);
+        };
         let kind: ProviderKind = account.provider_kind.parse()?;
         let provider: Arc<dyn ModelProvider> = match &self.provider_override {
             Some(p) => p.clone(),
             None => build_model_provider(kind)?,
         };
 
-        // 2. ADR-014 egress gate BEFORE any inference. Persist + hand off on block.
+        // 3. ADR-014 egress gate BEFORE any inference. Persist + hand off on block.
         if let Err(e) = assert_egress_allowed(self.air_gapped, provider.capabilities().egress) {
             let blocked = AgentOutcome {
                 passed: false,
@@ -398,7 +468,7 @@ impl AgenticTier for DbAgenticTier {
             return Ok(AgentTierOutcome::Exhausted);
         }
 
-        // 2b. Spend ceiling gate BEFORE dispatch (ADR-008). If the account has a
+        // 3b. Spend ceiling gate BEFORE dispatch (ADR-008). If the account has a
         // cap and cumulative spend has already reached it, refuse and hand off.
         if let Some(cap_str) = account.spend_cap_usd.as_deref() {
             let cap = parse_money(cap_str);
@@ -427,7 +497,7 @@ impl AgenticTier for DbAgenticTier {
             }
         }
 
-        // 3. Decrypt credentials AT THE CALL SITE (never logged).
+        // 4. Decrypt credentials AT THE CALL SITE (never logged).
         let api_key = match &account.credentials_encrypted {
             Some(bytes) => Some(self.encryption.decrypt(bytes)?),
             None => None,
@@ -439,9 +509,9 @@ impl AgenticTier for DbAgenticTier {
             model_path: account.model_path.clone(),
         };
 
-        // 4. Resolve playbook + budget, then run the harness.
+        // 5. Resolve playbook + budget, then run the harness.
         let (playbook, budget) = self.resolve_playbook()?;
-        let classification = self.classifier.classify(&self.failing_logs).await;
+        let classification = self.classifier.classify(&failing_logs).await;
 
         let mut harness = RemediationAgentHarness::new(self.classifier.clone());
         // Attach reflexion memory only when configured (feature-flagged). The
@@ -451,7 +521,7 @@ impl AgenticTier for DbAgenticTier {
         }
         let outcome = harness
             .run(
-                self.failing_logs.clone(),
+                failing_logs,
                 self.run_ctx.clone(),
                 &playbook,
                 provider,
@@ -463,7 +533,7 @@ impl AgenticTier for DbAgenticTier {
             )
             .await;
 
-        // 4b. Increment the account's cumulative spend by this run's cost
+        // 5b. Increment the account's cumulative spend by this run's cost
         // (ADR-008; Decimal-as-string, exact money). Skipped when free.
         if outcome.cost > Decimal::ZERO {
             let new_used = parse_money(&account.spend_used_usd) + outcome.cost;
@@ -479,7 +549,7 @@ impl AgenticTier for DbAgenticTier {
                 .map_err(|e| AmpelError::DatabaseError(e.to_string()))?;
         }
 
-        // 5. Persist the session + emit metrics.
+        // 6. Persist the session + emit metrics.
         let status = terminal_label(outcome.terminal_reason);
         self.persist_session(
             run_id,
@@ -497,7 +567,7 @@ impl AgenticTier for DbAgenticTier {
             outcome.cost.to_string().parse::<f64>().unwrap_or(0.0),
         );
 
-        // 6. Strategy-learning signal (Phase 5b). One row per completed
+        // 7. Strategy-learning signal (Phase 5b). One row per completed
         // model-driven session: provider KIND (never a key), classified failure
         // class, the playbook that drove it, terminal outcome, duration, cost.
         // Early egress/spend handoffs return above without a model attempt and so
@@ -0,0 +1,182 @@
+//! Provider-backed [`CiVerifier`] for the agentic tier (Phase 4).
+//!
+//! After the agent pushes a fix, CI re-runs on the consolidation branch. The
+//! verifier polls [`RemediationCapable::get_status_for_ref`] for that branch
+//! until every check has settled (or the timeout elapses) and reports green
+//! only when each check passed or was skipped.
+//!
+//! Providers expose check *results* but not job logs through the API Ampel
+//! uses, so the fresh "logs" fed back into the next iteration are a summary of
+//! the non-passing checks: name, outcome and details URL. That is enough for
+//! the classifier and the model to see *what* failed on this push.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ampel_core::errors::{AmpelError, AmpelResult};
+use ampel_core::services::{CheckStatus, RawCiCheck, VerificationService};
+use ampel_providers::traits::{ProviderCICheck, ProviderCredentials};
+use ampel_providers::RemediationCapable;
+use async_trait::async_trait;
+
+use super::agent_harness::{CiVerifier, VerificationStatus};
+
+/// Collapse one poll of `checks` on `git_ref` into a verdict. `None` while CI
+/// is still running — including before any check has been reported for the
+/// freshly pushed commit.
+pub fn settle_checks(git_ref: &str, checks: &[ProviderCICheck]) -> Option<VerificationStatus> {
+    if checks.is_empty() {
+        return None;
+    }
+    let raw: Vec<RawCiCheck> = checks
+        .iter()
+        .map(|c| RawCiCheck::new(c.name.clone(), c.status.clone(), c.conclusion.as_deref()))
+        .collect();
+    let normalized = VerificationService::new()
+        .verify(&raw, &[], true, git_ref)
+        .checks;
+    if normalized
+        .iter()
+        .any(|c| matches!(c.status, CheckStatus::Pending | CheckStatus::Running))
+    {
+        return None;
+    }
+
+    let failing: Vec<String> = checks
+        .iter()
+        .zip(&normalized)
+        .filter(|(_, n)| !matches!(n.status, CheckStatus::Green | CheckStatus::Skipped))
+        .map(|(c, _)| {
+            let outcome = c.conclusion.as_deref().unwrap_or(&c.status);
+            match &c.url {
+                Some(url) => format!("- {}: {outcome} ({url})", c.name),
+                None => format!("- {}: {outcome}", c.name),
+            }
+        })
+        .collect();
+    if failing.is_empty() {
+        return Some(VerificationStatus {
+            green: true,
+            logs: String::new(),
+        });
+    }
+    Some(VerificationStatus {
+        green: false,
+        logs: format!(
+            "CI failed on {git_ref}. Failing checks:\n{}",
+            failing.join("\n")
+        ),
+    })
+}
+
+/// Polls the provider's CI status for the worktree branch.
+pub struct ProviderCiVerifier {
+    provider: Arc<dyn RemediationCapable>,
+    credentials: ProviderCredentials,
+    owner: String,
+    repo: String,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl ProviderCiVerifier {
+    pub fn new(
+        provider: Arc<dyn RemediationCapable>,
+        credentials: ProviderCredentials,
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            provider,
+            credentials,
+            owner: owner.into(),
+            repo: repo.into(),
+            poll_interval,
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl CiVerifier for ProviderCiVerifier {
+    async fn verify(&self, worktree_ref: &str) -> AmpelResult<VerificationStatus> {
+        let started = Instant::now();
+        loop {
+            let checks = self
+                .provider
+                .get_status_for_ref(&self.credentials, &self.owner, &self.repo, worktree_ref)
+                .await
+                .map_err(|e| AmpelError::ProviderError(e.to_string()))?;
+            if let Some(status) = settle_checks(worktree_ref, &checks) {
+                return Ok(status);
+            }
+            if started.elapsed() >= self.timeout {
+                // Still running: not green, and say so to the next iteration.
+                return Ok(VerificationStatus {
+                    green: false,
+                    logs: format!(
+                        "CI on {worktree_ref} did not finish within {}s",
+                        self.timeout.as_secs()
+                    ),
+                });
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, status: &str, conclusion: Option<&str>) -> ProviderCICheck {
+        ProviderCICheck {
+            name: name.to_string(),
+            status: status.to_string(),
+            conclusion: conclusion.map(str::to_string),
+            url: Some(format!("https://ci.example/{name}")),
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn should_keep_polling_until_checks_settle() {
+        assert_eq!(settle_checks("ampel/remediation/x", &[]), None);
+        let running = [
+            check("build", "completed", Some("success")),
+            check("test", "in_progress", None),
+        ];
+        assert_eq!(settle_checks("ampel/remediation/x", &running), None);
+    }
+
+    #[test]
+    fn should_report_green_when_every_check_passed_or_skipped() {
+        let checks = [
+            check("build", "completed", Some("success")),
+            check("docs", "completed", Some("skipped")),
+        ];
+        let status = settle_checks("ampel/remediation/x", &checks).unwrap();
+        assert!(status.green);
+        assert!(status.logs.is_empty());
+    }
+
+    #[test]
+    fn should_summarize_failing_checks_as_fresh_logs() {
+        let checks = [
+            check("build", "completed", Some("success")),
+            check("test", "completed", Some("failure")),
+            check("lint", "completed", Some("cancelled")),
+        ];
+        let status = settle_checks("ampel/remediation/x", &checks).unwrap();
+        assert!(!status.green);
+        assert_eq!(
+            status.logs,
+            "CI failed on ampel/remediation/x. Failing checks:\n\
+             - test: failure (https://ci.example/test)\n\
+             - lint: cancelled (https://ci.example/lint)"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Builds and renders the periodic digest email.
+//!
+//! [`build_digest`] summarizes a user's repositories over one digest period:
+//! green PRs waiting to be merged, PRs open for more than a week, PRs that
+//! turned red during the period, how many PRs were merged and how repository
+//! health scores moved. [`render_digest`] turns it into a localized subject,
+//! plain-text body and HTML body.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_i18n::t;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+use ampel_core::models::{
+    AmpelStatus, Digest, DigestFrequency, DigestPullRequest, HealthScoreChange,
+};
+use ampel_db::entities::{health_score, pull_request};
+use ampel_db::queries::RepoQueries;
+
+/// Most PRs listed per digest section
+const DIGEST_SECTION_LIMIT: u64 = 20;
+
+/// Open PRs older than this are listed as stale, matching the health score
+const STALE_AFTER_DAYS: i64 = 7;
+
+/// A rendered digest email
+pub struct RenderedDigest {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// Summarize the repositories `user_id` owns over `[period_start, period_end)`
+pub async fn build_digest(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    frequency: DigestFrequency,
+    (period_start, period_end): (DateTime<Utc>, DateTime<Utc>),
+    locale: &str,
+) -> Result<Digest, DbErr> {
+    let repos = RepoQueries::find_by_user_id(db, user_id).await?;
+    let names: HashMap<Uuid, String> = repos
+        .iter()
+        .map(|repo| (repo.id, repo.full_name.clone()))
+        .collect();
+    let repo_ids: Vec<Uuid> = names.keys().copied().collect();
+
+    let open_prs = || {
+        pull_request::Entity::find()
+            .filter(pull_request::Column::RepositoryId.is_in(repo_ids.clone()))
+            .filter(pull_request::Column::State.eq("open"))
+            .order_by_asc(pull_request::Column::CreatedAt)
+            .limit(DIGEST_SECTION_LIMIT)
+    };
+    let listed = |prs: Vec<pull_request::Model>| -> Vec<DigestPullRequest> {
+        prs.into_iter()
+            .map(|pr| DigestPullRequest {
+                repository: names.get(&pr.repository_id).cloned().unwrap_or_default(),
+                number: pr.number,
+                title: pr.title,
+                url: pr.url,
+                author: pr.author,
+                created_at: pr.created_at,
+            })
+            .collect()
+    };
+
+    let ready_to_merge = open_prs()
+        .filter(pull_request::Column::AmpelStatus.eq(AmpelStatus::Green.to_string()))
+        .all(db)
+        .await?;
+    let stale = open_prs()
+        .filter(pull_request::Column::CreatedAt.lt(period_end - Duration::days(STALE_AFTER_DAYS)))
+        .all(db)
+        .await?;
+    let newly_red = open_prs()
+        .filter(pull_request::Column::AmpelStatus.eq(AmpelStatus::Red.to_string()))
+        .filter(pull_request::Column::AmpelStatusChangedAt.gte(period_start))
+        .filter(pull_request::Column::AmpelStatusChangedAt.lt(period_end))
+        .all(db)
+        .await?;
+    let merged_count = pull_request::Entity::find()
+        .filter(pull_request::Column::RepositoryId.is_in(repo_ids.clone()))
+        .filter(pull_request::Column::MergedAt.gte(period_start))
+        .filter(pull_request::Column::MergedAt.lt(period_end))
+        .count(db)
+        .await?;
+
+    let mut health_changes = Vec::new();
+    for repo in &repos {
+        let previous = latest_health_score(db, repo.id, period_start).await?;
+        let current = latest_health_score(db, repo.id, period_end).await?;
+        if let (Some(previous), Some(current)) = (previous, current) {
+            if previous != current {
+                health_changes.push(HealthScoreChange {
+                    repository: repo.full_name.clone(),
+                    previous,
+                    current,
+                });
+            }
+        }
+    }
+
+    Ok(Digest {
+        frequency,
+        period_start,
+        period_end,
+        locale: locale.to_string(),
+        ready_to_merge: listed(ready_to_merge),
+        stale: listed(stale),
+        newly_red: listed(newly_red),
+        merged_count,
+        health_changes,
+    })
+}
+
+/// The repository's most recent health score calculated before `before`
+async fn latest_health_score(
+    db: &DatabaseConnection,
+    repository_id: Uuid,
+    before: DateTime<Utc>,
+) -> Result<Option<i32>, DbErr> {
+    Ok(health_score::Entity::find()
+        .filter(health_score::Column::RepositoryId.eq(repository_id))
+        .filter(health_score::Column::CalculatedAt.lt(before))
+        .order_by_desc(health_score::Column::CalculatedAt)
+        .one(db)
+        .await?
+        .map(|score| score.score))
+}
+
+/// Render a digest in its locale
+pub fn render_digest(digest: &Digest) -> RenderedDigest {
+    let locale = digest.locale.as_str();
+    let date = digest.period_start.format("%Y-%m-%d").to_string();
+    let (subject, intro) = match digest.frequency {
+        DigestFrequency::Weekly => (
+            t!("digest.subject_weekly", locale = locale),
+            t!("digest.intro_weekly", locale = locale, date = date),
+        ),
+        _ => (
+            t!("digest.subject_daily", locale = locale),
+            t!("digest.intro_daily", locale = locale, date = date),
+        ),
+    };
+    let merged = t!(
+        "digest.merged",
+        locale = locale,
+        count = digest.merged_count
+    );
+    let footer = t!("digest.footer", locale = locale);
+
+    let pr_sections = [
+        (
+            t!("digest.ready_to_merge", locale = locale),
+            &digest.ready_to_merge,
+        ),
+        (t!("digest.stale", locale = locale), &digest.stale),
+        (t!("digest.newly_red", locale = locale), &digest.newly_red),
+    ];
+    let pr_line = |pr: &DigestPullRequest| {
+        let days = (digest.period_end - pr.created_at).num_days().max(0);
+        format!(
+            "{} #{} {} ({}, {})",
+            pr.repository,
+            pr.number,
+            pr.title,
+            t!("digest.by", locale = locale, author = pr.author),
+            t!("digest.open_days", locale = locale, days = days)
+        )
+    };
+    let health_title = t!("digest.health", locale = locale);
+    let health_line = |change: &HealthScoreChange| {
+        format!(
+            "{}: {} → {}",
+            change.repository, change.previous, change.current
+        )
+    };
+
+    let mut text = format!("{intro}\n\n{merged}\n");
+    let mut html = format!(
+        "<html><body style=\"font-family: sans-serif\">\n<p>{}</p>\n<p>{}</p>\n",
+        escape_html(&intro),
+        escape_html(&merged)
+    );
+
+    for (title, prs) in &pr_sections {
+        if prs.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n{title}\n"));
+        html.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape_html(title)));
+        for pr in prs.iter() {
+            text.push_str(&format!("  - {}\n    {}\n", pr_line(pr), pr.url));
+            html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                escape_html(&pr.url),
+                escape_html(&pr_line(pr))
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !digest.health_changes.is_empty() {
+        text.push_str(&format!("\n{health_title}\n"));
+        html.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape_html(&health_title)));
+        for change in &digest.health_changes {
+            text.push_str(&format!("  - {}\n", health_line(change)));
+            html.push_str(&format!("<li>{}</li>\n", escape_html(&health_line(change))));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    text.push_str(&format!("\n--\n{footer}\n"));
+    html.push_str(&format!(
+        "<hr>\n<p style=\"color: #666; font-size: 12px\">{}</p>\n</body></html>\n",
+        escape_html(&footer)
+    ));
+
+    RenderedDigest {
+        subject: subject.into_owned(),
+        text,
+        html,
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn digest(locale: &str) -> Digest {
+        let period_start = Utc.with_ymd_and_hms(2026, 7, 7, 0, 0, 0).unwrap();
+        Digest {
+            frequency: DigestFrequency::Daily,
+            period_start,
+            period_end: period_start + Duration::days(1),
+            locale: locale.to_string(),
+            ready_to_merge: vec![DigestPullRequest {
+                repository: "acme/api".to_string(),
+                number: 7,
+                title: "Escape <script> & friends".to_string(),
+                url: "https://github.com/acme/api/pull/7".to_string(),
+                author: "octocat".to_string(),
+                created_at: period_start - Duration::days(2),
+            }],
+            stale: Vec::new(),
+            newly_red: Vec::new(),
+            merged_count: 3,
+            health_changes: vec![HealthScoreChange {
+                repository: "acme/api".to_string(),
+                previous: 80,
+                current: 72,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_render_lists_sections_and_escapes_html() {
+        let rendered = render_digest(&digest("en"));
+
+        assert_eq!(rendered.subject, "Your daily Ampel digest");
+        assert!(rendered.text.contains("2026-07-07"));
+        assert!(rendered.text.contains("Pull requests merged: 3"));
+        assert!(rendered
+            .text
+            .contains("acme/api #7 Escape <script> & friends (by octocat, open for 3 days)"));
+        assert!(rendered.text.contains("acme/api: 80 → 72"));
+        assert!(!rendered.text.contains("Stale pull requests"));
+
+        assert!(rendered
+            .html
+            .contains("Escape &lt;script&gt; &amp; friends"));
+        assert!(!rendered.html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_render_uses_digest_locale() {
+        let rendered = render_digest(&digest("de"));
+        assert_eq!(rendered.subject, "Ihre tägliche Ampel-Zusammenfassung");
+
+        // Unknown languages fall back to English
+        let rendered = render_digest(&digest("xx"));
+        assert_eq!(rendered.subject, "Your daily Ampel digest");
+    }
+}
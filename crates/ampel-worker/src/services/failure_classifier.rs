@@ -13,7 +13,6 @@
 //!
 //! With `onnx` OFF (the CI path) the cascade degrades to "heuristic, else
 //! Unknown" — fully unit-testable with no runtime.
-#![allow(dead_code)] // wired into the worker binary in slice 3
 
 use ampel_core::remediation::{
     classify_heuristic, ClassificationResult, FailureClass, FailureClassifier,
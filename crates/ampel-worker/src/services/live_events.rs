@@ -0,0 +1,91 @@
+//! Live dashboard events.
+//!
+//! Syncs publish a [`LiveEvent`] whenever they persist a change a dashboard
+//! shows: a PR opening, closing or changing status, or a CI check moving on.
+//! Like the `metrics` recorder, the publisher is process-wide, so the poller,
+//! the merge queue and the webhook handler publish without threading a handle
+//! through every sync.
+//!
+//! With Redis configured ([`use_redis`]) events are published to the
+//! [`LIVE_EVENTS_CHANNEL`] pub/sub channel, which every API replica subscribes
+//! to and relays into its local bus. Without Redis they go straight to the
+//! local bus, which only reaches dashboards served by the same process.
+//! Either way publishing is best effort: a failure is logged and never fails
+//! the sync that raised the event.
+
+use std::sync::OnceLock;
+
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use tokio::sync::broadcast;
+
+use ampel_core::models::LiveEvent;
+
+/// Redis pub/sub channel carrying every user's events
+pub const LIVE_EVENTS_CHANNEL: &str = "ampel:live-events";
+
+/// Events buffered per local subscriber before it lags
+const LOCAL_BUS_CAPACITY: usize = 1024;
+
+static REDIS: OnceLock<ConnectionManager> = OnceLock::new();
+static LOCAL_BUS: OnceLock<broadcast::Sender<LiveEvent>> = OnceLock::new();
+
+/// Publish events through Redis from now on. Only the first call has an
+/// effect.
+pub fn use_redis(connection: ConnectionManager) {
+    let _ = REDIS.set(connection);
+}
+
+/// Connect to Redis and publish events through it
+pub async fn connect(redis_url: &str) -> anyhow::Result<()> {
+    let client = redis::Client::open(redis_url)?;
+    use_redis(client.get_connection_manager().await?);
+    Ok(())
+}
+
+fn local_bus() -> &'static broadcast::Sender<LiveEvent> {
+    LOCAL_BUS.get_or_init(|| broadcast::channel(LOCAL_BUS_CAPACITY).0)
+}
+
+/// Receive every event delivered to this process. Subscribers filter by user.
+///
+/// Used by the API's dashboard stream; the bin only publishes, hence the
+/// allow.
+#[allow(dead_code)]
+pub fn subscribe() -> broadcast::Receiver<LiveEvent> {
+    local_bus().subscribe()
+}
+
+/// Hand an event to this process's subscribers
+pub fn deliver_local(event: LiveEvent) {
+    // No receivers just means no dashboard is open here
+    let _ = local_bus().send(event);
+}
+
+/// Publish an event to every process serving the user's dashboards
+pub async fn publish(event: LiveEvent) {
+    let Some(redis) = REDIS.get() else {
+        deliver_local(event);
+        return;
+    };
+
+    let payload = match serde_json::to_string(&event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("Failed to serialize live event: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = redis
+        .clone()
+        .publish::<_, _, ()>(LIVE_EVENTS_CHANNEL, payload)
+        .await
+    {
+        tracing::warn!(
+            user_id = %event.user_id,
+            "Failed to publish live event, delivering locally: {}",
+            e
+        );
+        deliver_local(event);
+    }
+}
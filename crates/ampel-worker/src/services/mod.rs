@@ -5,14 +5,34 @@
 //! - [`sandbox_runner`]: Podman/Docker [`SandboxRunner`] + pure consolidation
 //!   logic (lockfile/regen/merge-sequence/runtime detection).
 //! - [`remediation_executor`]: drives one run through the state machine.
-//! - [`notifier`]: notification delivery seam (Slack via `ampel-core`, or noop).
+//! - [`agentic_tier`]: the Tier-2 model-driven recovery of a red consolidated run.
+//! - [`agent_worktree`]: applies agent output in a sandboxed clone and pushes it.
+//! - [`ci_verifier`]: polls provider CI for the agent's pushes.
+//! - [`suggestions`]: suggest-only runs post their plan and the agent's fixes
+//!   as PR comments and suggested changes, without pushing.
+//! - [`notifier`]: remediation notification seam (configured channels, or noop).
+//! - [`pr_events`]: PR status transitions and review requests to notify about.
+//! - [`live_events`]: publishes dashboard changes to open dashboards via Redis.
+//! - [`notification_queue`]: queues a notification on every channel receiving it.
+//! - [`web_push`]: VAPID keys and browser push delivery, pruning expired subscriptions.
+//! - [`digest`]: periodic digest emails, built from the database and localized.
+//! - [`poll_scheduler`]: per-account API quota budgets for repository polling.
+//! - [`account_credentials`]: stored provider credentials, with OAuth refresh.
 
+pub mod account_credentials;
 pub mod agent_harness;
+pub mod agent_worktree;
 pub mod agentic_tier;
+pub mod ci_verifier;
+pub mod digest;
 pub mod failure_classifier;
+pub mod live_events;
+pub mod notification_queue;
 pub mod notifier;
 pub mod playbook;
 pub mod playbook_resolver;
+pub mod poll_scheduler;
+pub mod pr_events;
 pub mod provider_adapter;
 /// Vector-backed reflexion memory — `reflexion` feature only (compiles out when
 /// off). The trait + Noop/in-memory fakes live in `ampel-core` and are always
@@ -21,16 +41,20 @@ pub mod provider_adapter;
 pub mod reflexion;
 pub mod remediation_executor;
 pub mod sandbox_runner;
+pub mod suggestions;
+pub mod web_push;
 
-// Re-exported for library consumers (slice-3 wiring); the bin target does not
-// use these yet, hence the allow.
+// Re-exported for library consumers; the bin target does not use all of
+// these, hence the allow.
 #[allow(unused_imports)]
 pub use agent_harness::{AgentWorktree, CiVerifier, RemediationAgentHarness, VerificationStatus};
+pub use agent_worktree::SandboxAgentWorktree;
 #[allow(unused_imports)]
 pub use agentic_tier::{
-    assert_egress_allowed, tier_allows_agentic, AgentTierOutcome, AgenticTier, DbAgenticTier,
+    assert_egress_allowed, tier_allows_agentic, AccountScope, AgentTierOutcome, AgenticRuntime,
+    AgenticTier, DbAgenticTier,
 };
-#[allow(unused_imports)]
+pub use ci_verifier::ProviderCiVerifier;
 pub use failure_classifier::CascadeClassifier;
 #[allow(unused_imports)]
 pub use playbook::{clamp_tools, LoopConfig, Playbook, PlaybookTask, ToolsPolicy};
@@ -39,6 +63,8 @@ pub use playbook_resolver::{
     build_system_instruction, embedded_default_yaml, render_instructions, resolve, PlaybookContext,
     PlaybookScope,
 };
+pub use poll_scheduler::PollScheduler;
 pub use provider_adapter::{remediation_capable_provider, ProviderAdapter};
 pub use remediation_executor::{RemediationExecutor, RunOutcome};
 pub use sandbox_runner::{PodmanSandboxRunner, SandboxConfig};
+pub use suggestions::{AgentFixProposer, SuggestOnlyPublisher};
@@ -0,0 +1,148 @@
+//! Queues notifications on every channel a recipient receives them on.
+//!
+//! A notification goes to the Slack, email and browser push settings in the
+//! recipient's preferences, when they enable its kind, and to each configured
+//! notification channel that reaches the recipient (their own, and their
+//! teams') and subscribes to its kind. Entries for configured channels carry
+//! the channel's id and get it appended to their dedup key, so one event is
+//! queued once per channel.
+
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use ampel_core::models::{DeliveryChannel, NotificationKind};
+use ampel_db::entities::{notification_channel, notification_preferences};
+use ampel_db::queries::{
+    NewNotification, NotificationChannelQueries, NotificationLogQueries, PushSubscriptionQueries,
+};
+
+/// An event to queue for one recipient
+pub struct Notice {
+    pub kind: NotificationKind,
+    pub pull_request_id: Option<Uuid>,
+    /// Identifies the event, so it is queued once per channel
+    pub dedup_key: String,
+    /// JSON object describing the event
+    pub payload: String,
+}
+
+/// Queue `notice` for `user_id`. Returns how many entries were queued.
+pub async fn queue_notice(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    notice: &Notice,
+) -> Result<usize, DbErr> {
+    let prefs = notification_preferences::Entity::find()
+        .filter(notification_preferences::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+
+    let mut entries = Vec::new();
+    if let Some(prefs) = &prefs {
+        for channel in enabled_channels(prefs, notice.kind) {
+            if channel == DeliveryChannel::Push
+                && PushSubscriptionQueries::count_for_user(db, user_id).await? == 0
+            {
+                continue;
+            }
+            entries.push((channel.to_string(), None, notice.dedup_key.clone()));
+        }
+    }
+    for channel in NotificationChannelQueries::find_enabled_for_recipient(db, user_id).await? {
+        if receives(&channel, notice.kind) {
+            entries.push((
+                channel.kind,
+                Some(channel.id),
+                format!("{}:{}", notice.dedup_key, channel.id),
+            ));
+        }
+    }
+
+    let mut queued = 0;
+    for (channel, channel_id, dedup_key) in entries {
+        let inserted = NotificationLogQueries::enqueue(
+            db,
+            NewNotification {
+                user_id,
+                pull_request_id: notice.pull_request_id,
+                kind: notice.kind.to_string(),
+                channel,
+                channel_id,
+                dedup_key,
+                payload: notice.payload.clone(),
+            },
+        )
+        .await?;
+        if inserted {
+            queued += 1;
+        }
+    }
+
+    if queued > 0 {
+        tracing::debug!(
+            user_id = %user_id,
+            kind = %notice.kind,
+            "Queued {} notifications",
+            queued
+        );
+    }
+
+    Ok(queued)
+}
+
+/// Whether a configured channel subscribes to notifications of `kind`
+pub fn receives(channel: &notification_channel::Model, kind: NotificationKind) -> bool {
+    match channel.events.as_deref() {
+        None => true,
+        Some(events) => serde_json::from_str::<Vec<String>>(events)
+            .map(|events| events.iter().any(|e| e == kind.as_str()))
+            .unwrap_or(false),
+    }
+}
+
+/// The channels a user wants notifications of `kind` on, among those they
+/// have configured in their preferences. Push is included whenever it is
+/// enabled, whether or not the user has subscribed a browser yet.
+pub fn enabled_channels(
+    prefs: &notification_preferences::Model,
+    kind: NotificationKind,
+) -> Vec<DeliveryChannel> {
+    let wanted = match kind {
+        NotificationKind::PrReady => prefs.notify_on_pr_ready,
+        NotificationKind::PrFailed => prefs.notify_on_pr_failed,
+        NotificationKind::ReviewRequested => prefs.notify_on_review_requested,
+        NotificationKind::MergeSucceeded => prefs.notify_on_merge_success,
+        NotificationKind::MergeFailed => prefs.notify_on_merge_failure,
+        // Remediation events only go to configured channels
+        NotificationKind::RemediationCompleted => false,
+    };
+    if !wanted {
+        return Vec::new();
+    }
+
+    let mut channels = Vec::new();
+    if prefs.slack_enabled && prefs.slack_webhook_url.is_some() {
+        channels.push(DeliveryChannel::Slack);
+    }
+    if email_configured(prefs) {
+        channels.push(DeliveryChannel::Email);
+    }
+    if prefs.push_enabled {
+        channels.push(DeliveryChannel::Push);
+    }
+    channels
+}
+
+/// Whether a user has email enabled with somewhere to send it
+pub fn email_configured(prefs: &notification_preferences::Model) -> bool {
+    prefs.email_enabled && prefs.smtp_host.is_some() && !recipient_emails(prefs).is_empty()
+}
+
+/// The addresses a user's email notifications go to
+pub fn recipient_emails(prefs: &notification_preferences::Model) -> Vec<String> {
+    prefs
+        .smtp_to_emails
+        .as_deref()
+        .and_then(|emails| serde_json::from_str(emails).ok())
+        .unwrap_or_default()
+}
@@ -6,15 +6,17 @@
 //! [`RemediationNotifier`] so the executor stays testable (a fake records the
 //! calls) and CI-safe (the default notifier never touches the network).
 //!
-//! Production delivery is wired through `ampel-core`'s existing
-//! [`ampel_core::services::NotificationService`] via [`SlackNotifier`], which is
-//! only constructed when a webhook URL is configured. Payloads carry run/PR
-//! identifiers only — never tokens, credentials, or other secrets.
+//! Production delivery goes through `ampel-core`'s
+//! [`NotificationChannel`] implementations via [`ChannelNotifier`], which is
+//! only constructed when at least one channel is configured. Payloads carry
+//! run/PR identifiers only — never tokens, credentials, or other secrets.
 
 use async_trait::async_trait;
+use serde_json::json;
 use uuid::Uuid;
 
-use ampel_core::services::{MergeNotificationPayload, MergeResultItem, NotificationService};
+use ampel_core::models::NotificationKind;
+use ampel_core::services::{ChannelMessage, NotificationChannel, Severity};
 
 /// Emitted after the consolidated PR is merged.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -53,7 +55,7 @@ impl RemediationNotifier for NoopNotifier {
 }
 
 /// Logs each event via `tracing` (no network). A sensible default for
-/// environments without Slack configured.
+/// environments without notification channels configured.
 pub struct LoggingNotifier;
 
 #[async_trait]
@@ -77,75 +79,141 @@ impl RemediationNotifier for LoggingNotifier {
     }
 }
 
-/// Delivers events to Slack via the shared [`NotificationService`].
+/// Delivers events to notification channels: the Slack webhook configured for
+/// the deployment and the channels of the repository owner and their teams
+/// that subscribe to remediation events.
 ///
-/// Only constructed when a webhook URL is present. Delivery failures are logged,
-/// never propagated — a notification problem must not fail a successful run.
-pub struct SlackNotifier {
-    webhook_url: String,
-    channel: Option<String>,
+/// Only constructed when there is at least one channel. Delivery failures are
+/// logged, never propagated — a notification problem must not fail a
+/// successful run.
+pub struct ChannelNotifier {
+    channels: Vec<Box<dyn NotificationChannel>>,
 }
 
-impl SlackNotifier {
-    pub fn new(webhook_url: String, channel: Option<String>) -> Self {
-        Self {
-            webhook_url,
-            channel,
-        }
+impl ChannelNotifier {
+    pub fn new(channels: Vec<Box<dyn NotificationChannel>>) -> Self {
+        Self { channels }
     }
 
-    async fn send(&self, payload: MergeNotificationPayload) {
-        if let Err(e) = NotificationService::send_slack_notification(
-            &self.webhook_url,
-            self.channel.as_deref(),
-            &payload,
-        )
-        .await
-        {
-            tracing::warn!(error = %e, "remediation notification delivery failed");
+    async fn send(&self, message: ChannelMessage) {
+        for channel in &self.channels {
+            if let Err(e) = channel.send(&message).await {
+                tracing::warn!(
+                    channel = %channel.kind(),
+                    error = %e,
+                    "remediation notification delivery failed"
+                );
+            }
         }
     }
 }
 
 #[async_trait]
-impl RemediationNotifier for SlackNotifier {
+impl RemediationNotifier for ChannelNotifier {
     async fn run_merged(&self, event: RunMergedNotification) {
-        let payload = MergeNotificationPayload {
-            total: 1,
-            success: 1,
-            failed: 0,
-            skipped: 0,
-            results: vec![MergeResultItem {
-                repository: event.provider,
-                pr_number: event.consolidated_pr_number as i32,
-                pr_title: format!("Remediation run {}", event.run_id),
-                status: "success".to_string(),
-                error: None,
-            }],
+        let message = ChannelMessage {
+            event: NotificationKind::RemediationCompleted.to_string(),
+            title: "Remediation run merged".to_string(),
+            body: format!(
+                "Consolidated PR #{} was merged.",
+                event.consolidated_pr_number
+            ),
+            url: None,
+            facts: vec![
+                ("Run".to_string(), event.run_id.to_string()),
+                ("Provider".to_string(), event.provider.clone()),
+            ],
+            severity: Severity::Success,
+            data: json!({
+                "runId": event.run_id,
+                "consolidatedPrNumber": event.consolidated_pr_number,
+                "provider": event.provider,
+            }),
         };
-        self.send(payload).await;
+        self.send(message).await;
     }
 
     async fn sources_closed(&self, event: SourcePrsClosedNotification) {
-        let results = event
+        let closed = event
             .closed_pr_numbers
             .iter()
-            .map(|n| MergeResultItem {
-                repository: format!("run {}", event.run_id),
-                pr_number: *n as i32,
-                pr_title: format!("Superseded by #{}", event.consolidated_pr_number),
-                status: "success".to_string(),
-                error: None,
-            })
-            .collect::<Vec<_>>();
-        let count = results.len() as i32;
-        let payload = MergeNotificationPayload {
-            total: count,
-            success: count,
-            failed: 0,
-            skipped: 0,
-            results,
+            .map(|n| format!("#{}", n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = ChannelMessage {
+            event: NotificationKind::RemediationCompleted.to_string(),
+            title: "Superseded PRs closed".to_string(),
+            body: format!(
+                "Closed {} in favour of #{}.",
+                closed, event.consolidated_pr_number
+            ),
+            url: None,
+            facts: vec![("Run".to_string(), event.run_id.to_string())],
+            severity: Severity::Info,
+            data: json!({
+                "runId": event.run_id,
+                "consolidatedPrNumber": event.consolidated_pr_number,
+                "closedPrNumbers": event.closed_pr_numbers,
+            }),
         };
-        self.send(payload).await;
+        self.send(message).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use ampel_core::services::{ChannelKind, NotificationError};
+
+    use super::*;
+
+    /// Records messages; fails every send when `fail` is set
+    struct RecordingChannel {
+        sent: Arc<Mutex<Vec<ChannelMessage>>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn kind(&self) -> ChannelKind {
+            ChannelKind::Webhook
+        }
+
+        async fn send(&self, message: &ChannelMessage) -> Result<(), NotificationError> {
+            self.sent.lock().unwrap().push(message.clone());
+            if self.fail {
+                return Err(NotificationError::Delivery("unreachable".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_channel_notifier_reaches_every_channel_despite_failures() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let notifier = ChannelNotifier::new(vec![
+            Box::new(RecordingChannel {
+                sent: sent.clone(),
+                fail: true,
+            }),
+            Box::new(RecordingChannel {
+                sent: sent.clone(),
+                fail: false,
+            }),
+        ]);
+
+        notifier
+            .sources_closed(SourcePrsClosedNotification {
+                run_id: Uuid::nil(),
+                consolidated_pr_number: 9,
+                closed_pr_numbers: vec![3, 4],
+            })
+            .await;
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].event, "remediation_completed");
+        assert_eq!(sent[1].body, "Closed #3, #4 in favour of #9.");
     }
 }
@@ -0,0 +1,299 @@
+//! Rate-limit-aware scheduling for repository polling.
+//!
+//! Every provider account has its own API quota. Before each poll cycle
+//! [`crate::jobs::poll_repository::PollRepositoryJob`] refreshes the account's
+//! budget from `GitProvider::get_rate_limit`, and [`PollScheduler::allowance`]
+//! says how many of the account's due repositories it may poll this cycle, so
+//! the remaining quota is spread evenly until `reset_at` instead of being spent
+//! in the first minutes of the window. The cost of one repository poll is
+//! learned from the quota earlier cycles actually used; conditional requests
+//! answered with `304` are free on GitHub, so this is usually well below the
+//! worst case.
+//!
+//! A rate-limited response blocks the account until the provider's reset time.
+//! Budgets live in memory: after a restart the first refresh rebuilds them.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use ampel_providers::traits::RateLimitInfo;
+
+use crate::observability;
+
+/// Interval between poll cycles (the poll job's cron fires every minute).
+pub const POLL_CYCLE_SECS: i64 = 60;
+
+/// Share of each account's quota left for interactive use through the API.
+pub const QUOTA_RESERVE_RATIO: f64 = 0.1;
+
+/// Provider calls assumed per repository poll until a cost has been measured.
+pub const DEFAULT_CALLS_PER_POLL: f64 = 5.0;
+
+/// Quota state of one provider account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountBudget {
+    pub limit: i32,
+    /// Provider-reported remaining calls, minus the estimated cost of polls
+    /// made since the last refresh
+    pub remaining: i32,
+    pub reset_at: DateTime<Utc>,
+    /// Set after a rate-limited response; nothing is polled before it passes
+    pub blocked_until: Option<DateTime<Utc>>,
+    /// Moving average of quota used per repository poll
+    pub calls_per_poll: f64,
+    reported_remaining: i32,
+    polls_since_refresh: u32,
+}
+
+impl AccountBudget {
+    fn new(info: &RateLimitInfo) -> Self {
+        Self {
+            limit: info.limit,
+            remaining: info.remaining,
+            reset_at: info.reset_at,
+            blocked_until: None,
+            calls_per_poll: DEFAULT_CALLS_PER_POLL,
+            reported_remaining: info.remaining,
+            polls_since_refresh: 0,
+        }
+    }
+}
+
+/// Per-account poll budgets, shared by every run of the poll job.
+#[derive(Debug, Default)]
+pub struct PollScheduler {
+    budgets: Mutex<HashMap<Uuid, AccountBudget>>,
+}
+
+impl PollScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn budget(&self, account_id: Uuid) -> Option<AccountBudget> {
+        self.budgets.lock().unwrap().get(&account_id).cloned()
+    }
+
+    /// Record the quota the provider currently reports for an account.
+    pub fn refresh(&self, account_id: Uuid, info: &RateLimitInfo) {
+        let mut budgets = self.budgets.lock().unwrap();
+        let budget = budgets
+            .entry(account_id)
+            .or_insert_with(|| AccountBudget::new(info));
+
+        // Quota used within the same window tells us what a poll really costs
+        if budget.polls_since_refresh > 0
+            && budget.reset_at == info.reset_at
+            && info.remaining <= budget.reported_remaining
+        {
+            let used = (budget.reported_remaining - info.remaining) as f64;
+            let observed = used / budget.polls_since_refresh as f64;
+            budget.calls_per_poll = ((budget.calls_per_poll + observed) / 2.0).max(1.0);
+        }
+
+        budget.limit = info.limit;
+        budget.remaining = info.remaining;
+        budget.reported_remaining = info.remaining;
+        budget.reset_at = info.reset_at;
+        budget.polls_since_refresh = 0;
+
+        observability::record_poll_quota(account_id, budget.limit, budget.remaining);
+    }
+
+    /// How many repositories of this account may be polled in the current
+    /// cycle. Accounts without a known budget are not throttled.
+    pub fn allowance(&self, account_id: Uuid, now: DateTime<Utc>) -> usize {
+        let budgets = self.budgets.lock().unwrap();
+        let Some(budget) = budgets.get(&account_id) else {
+            return usize::MAX;
+        };
+
+        if budget.blocked_until.is_some_and(|until| until > now) {
+            return 0;
+        }
+
+        // A passed reset means the quota has been replenished
+        let (remaining, secs_left) = if budget.reset_at <= now {
+            (budget.limit, POLL_CYCLE_SECS)
+        } else {
+            (budget.remaining, (budget.reset_at - now).num_seconds())
+        };
+
+        let reserve = (budget.limit as f64 * QUOTA_RESERVE_RATIO).ceil();
+        let usable = remaining as f64 - reserve;
+        if usable <= 0.0 {
+            return 0;
+        }
+
+        let cycles_left = (secs_left as f64 / POLL_CYCLE_SECS as f64).ceil().max(1.0);
+        (usable / cycles_left / budget.calls_per_poll).ceil() as usize
+    }
+
+    /// Charge one repository poll against the account's budget.
+    pub fn record_poll(&self, account_id: Uuid) {
+        let mut budgets = self.budgets.lock().unwrap();
+        if let Some(budget) = budgets.get_mut(&account_id) {
+            budget.polls_since_refresh += 1;
+            budget.remaining = (budget.remaining - budget.calls_per_poll.ceil() as i32).max(0);
+            observability::record_poll_quota(account_id, budget.limit, budget.remaining);
+        }
+    }
+
+    /// Block an account until `reset_at` after the provider rate-limited it.
+    pub fn record_rate_limited(&self, account_id: Uuid, reset_at: DateTime<Utc>) {
+        let mut budgets = self.budgets.lock().unwrap();
+        let budget = budgets.entry(account_id).or_insert_with(|| {
+            AccountBudget::new(&RateLimitInfo {
+                limit: 0,
+                remaining: 0,
+                reset_at,
+            })
+        });
+        budget.remaining = 0;
+        budget.reported_remaining = 0;
+        budget.blocked_until = Some(reset_at);
+        budget.reset_at = budget.reset_at.max(reset_at);
+
+        observability::record_poll_quota(account_id, budget.limit, budget.remaining);
+    }
+
+    /// Count repositories left for a later cycle, labelled by why.
+    pub fn record_deferred(&self, account_id: Uuid, count: usize, now: DateTime<Utc>) {
+        if count == 0 {
+            return;
+        }
+
+        let blocked = self
+            .budgets
+            .lock()
+            .unwrap()
+            .get(&account_id)
+            .and_then(|b| b.blocked_until)
+            .is_some_and(|until| until > now);
+        let reason = if blocked { "rate_limited" } else { "budget" };
+
+        observability::record_poll_deferrals(account_id, reason, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn info(limit: i32, remaining: i32, reset_at: DateTime<Utc>) -> RateLimitInfo {
+        RateLimitInfo {
+            limit,
+            remaining,
+            reset_at,
+        }
+    }
+
+    #[test]
+    fn should_not_throttle_accounts_without_budget() {
+        let scheduler = PollScheduler::new();
+
+        assert_eq!(scheduler.allowance(Uuid::new_v4(), Utc::now()), usize::MAX);
+    }
+
+    #[test]
+    fn should_spread_remaining_quota_across_reset_window() {
+        // Arrange
+        let scheduler = PollScheduler::new();
+        let account = Uuid::new_v4();
+        let now = Utc::now();
+        scheduler.refresh(account, &info(5000, 5000, now + Duration::minutes(60)));
+
+        // Act
+        let allowance = scheduler.allowance(account, now);
+
+        // Assert: (5000 - 500 reserved) / 60 cycles / 5 calls per poll
+        assert_eq!(allowance, 15);
+    }
+
+    #[test]
+    fn should_spend_more_per_cycle_as_reset_approaches() {
+        // Arrange
+        let scheduler = PollScheduler::new();
+        let account = Uuid::new_v4();
+        let now = Utc::now();
+        scheduler.refresh(account, &info(5000, 1000, now + Duration::minutes(2)));
+
+        // Act
+        let allowance = scheduler.allowance(account, now);
+
+        // Assert: (1000 - 500) / 2 cycles / 5 calls per poll
+        assert_eq!(allowance, 50);
+    }
+
+    #[test]
+    fn should_keep_reserve_for_interactive_use() {
+        let scheduler = PollScheduler::new();
+        let account = Uuid::new_v4();
+        let now = Utc::now();
+        scheduler.refresh(account, &info(5000, 400, now + Duration::minutes(30)));
+
+        assert_eq!(scheduler.allowance(account, now), 0);
+    }
+
+    #[test]
+    fn should_block_rate_limited_account_until_reset() {
+        // Arrange
+        let scheduler = PollScheduler::new();
+        let account = Uuid::new_v4();
+        let now = Utc::now();
+        let reset_at = now + Duration::minutes(10);
+        scheduler.refresh(account, &info(5000, 4000, now + Duration::minutes(30)));
+
+        // Act
+        scheduler.record_rate_limited(account, reset_at);
+
+        // Assert
+        assert_eq!(scheduler.allowance(account, now), 0);
+        assert_eq!(
+            scheduler.allowance(account, reset_at - Duration::seconds(1)),
+            0
+        );
+        assert!(scheduler.allowance(account, reset_at + Duration::minutes(21)) > 0);
+    }
+
+    #[test]
+    fn should_learn_poll_cost_from_quota_used() {
+        // Arrange
+        let scheduler = PollScheduler::new();
+        let account = Uuid::new_v4();
+        let reset_at = Utc::now() + Duration::minutes(30);
+        scheduler.refresh(account, &info(5000, 4000, reset_at));
+
+        // Act: four polls cost 4 calls in total (mostly 304s)
+        for _ in 0..4 {
+            scheduler.record_poll(account);
+        }
+        scheduler.refresh(account, &info(5000, 3996, reset_at));
+
+        // Assert: average of the 5.0 default and the observed 1.0
+        let budget = scheduler.budget(account).unwrap();
+        assert_eq!(budget.calls_per_poll, 3.0);
+        assert_eq!(budget.remaining, 3996);
+    }
+
+    #[test]
+    fn should_not_learn_across_reset_windows() {
+        // Arrange
+        let scheduler = PollScheduler::new();
+        let account = Uuid::new_v4();
+        let now = Utc::now();
+        scheduler.refresh(account, &info(5000, 100, now + Duration::minutes(1)));
+        scheduler.record_poll(account);
+
+        // Act: the window reset between refreshes
+        scheduler.refresh(account, &info(5000, 5000, now + Duration::minutes(61)));
+
+        // Assert
+        let budget = scheduler.budget(account).unwrap();
+        assert_eq!(budget.calls_per_poll, DEFAULT_CALLS_PER_POLL);
+    }
+}
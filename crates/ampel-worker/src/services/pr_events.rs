@@ -0,0 +1,228 @@
+//! Detects pull request events worth notifying users about.
+//!
+//! After a sync has written a PR with its checks and reviews,
+//! [`record_pr_events`] recomputes the PR's [`AmpelStatus`] and compares it
+//! with the status stored by the previous sync. A PR turning green or red is
+//! queued for the repository owner, and each requested reviewer with an Ampel
+//! account is queued a review request. Entries go to the notification log once
+//! per channel that receives them (see [`super::notification_queue`]); the
+//! notification dispatch job delivers them.
+//!
+//! The same transitions, along with PRs opening and closing and CI checks
+//! moving on, are published to the owner's open dashboards through
+//! [`super::live_events`].
+//!
+//! Deduplication happens twice. The status is swapped with a compare-and-set,
+//! so when a webhook and a poll see the same change only one raises it, and
+//! each log entry carries a dedup key the log is unique on, so a reviewer is
+//! told about a review request once per PR.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, DbErr};
+use uuid::Uuid;
+
+use ampel_core::models::{
+    AmpelStatus, CICheck, DashboardEvent, LiveEvent, NotificationKind, PrNotification, Review,
+};
+use ampel_db::entities::{ci_check, pull_request, repository};
+use ampel_db::queries::{
+    CICheckQueries, PrQueries, ProviderAccountQueries, ReviewQueries, StatusPolicyQueries,
+};
+
+use super::live_events;
+use super::notification_queue::{queue_notice, Notice};
+
+/// Record status transitions and review requests for a freshly synced PR.
+///
+/// `requested_reviewers` holds provider logins; pass an empty slice when the
+/// caller has no reviewer information. Returns how many notifications were
+/// queued.
+pub async fn record_pr_events(
+    db: &DatabaseConnection,
+    repo: &repository::Model,
+    pr: &pull_request::Model,
+    requested_reviewers: &[String],
+) -> Result<usize, DbErr> {
+    if pr.state != "open" {
+        return Ok(0);
+    }
+
+    let mut queued = 0;
+
+    let checks: Vec<CICheck> = CICheckQueries::find_by_pull_request(db, pr.id)
+        .await?
+        .into_iter()
+        .map(|c| c.into())
+        .collect();
+    let reviews: Vec<Review> = ReviewQueries::find_latest_by_pull_request(db, pr.id)
+        .await?
+        .into_iter()
+        .map(|r| r.into())
+        .collect();
+    let policy = StatusPolicyQueries::policy_for_repository(db, repo.id).await?;
+    let current = AmpelStatus::evaluate(&pr.clone().into(), &checks, &reviews, &policy).status;
+
+    let previous = pr.ampel_status.as_deref();
+    if previous != Some(current.to_string().as_str())
+        && PrQueries::transition_ampel_status(db, pr.id, previous, &current.to_string()).await?
+    {
+        // A PR seen for the first time only records a baseline
+        let previous = previous.and_then(|p| AmpelStatus::from_str(p).ok());
+        publish(
+            repo,
+            match previous {
+                Some(previous) => DashboardEvent::PrStatusChanged {
+                    pull_request_id: pr.id,
+                    repository_id: repo.id,
+                    number: pr.number,
+                    previous,
+                    status: current,
+                },
+                None => DashboardEvent::PrOpened {
+                    pull_request_id: pr.id,
+                    repository_id: repo.id,
+                    number: pr.number,
+                    title: pr.title.clone(),
+                    status: current,
+                },
+            },
+        )
+        .await;
+
+        let kind = previous.and_then(|p| NotificationKind::for_transition(p, current));
+        if let Some(kind) = kind {
+            let dedup_key = format!("{}:{}:{}", kind, pr.id, Utc::now().timestamp());
+            queued += queue_for_user(
+                db,
+                repo.user_id,
+                pr,
+                notification(kind, repo, pr, None),
+                &dedup_key,
+            )
+            .await?;
+        }
+    }
+
+    let mut notified = HashSet::new();
+    for login in requested_reviewers {
+        for account in
+            ProviderAccountQueries::find_by_provider_username(db, &repo.provider, login).await?
+        {
+            if !notified.insert(account.user_id) {
+                continue;
+            }
+            let dedup_key = format!(
+                "{}:{}:{}",
+                NotificationKind::ReviewRequested,
+                pr.id,
+                login.to_lowercase()
+            );
+            let payload = notification(
+                NotificationKind::ReviewRequested,
+                repo,
+                pr,
+                Some(login.clone()),
+            );
+            queued += queue_for_user(db, account.user_id, pr, payload, &dedup_key).await?;
+        }
+    }
+
+    Ok(queued)
+}
+
+/// Publish the CI checks of an open PR that changed since `previous`, the
+/// checks stored before the sync wrote new ones
+pub async fn record_check_changes(
+    db: &DatabaseConnection,
+    repo: &repository::Model,
+    pr: &pull_request::Model,
+    previous: &[ci_check::Model],
+) -> Result<(), DbErr> {
+    if pr.state != "open" {
+        return Ok(());
+    }
+
+    for check in CICheckQueries::find_by_pull_request(db, pr.id).await? {
+        let before = previous.iter().find(|p| p.name == check.name);
+        if before.is_some_and(|p| p.status == check.status && p.conclusion == check.conclusion) {
+            continue;
+        }
+        publish(
+            repo,
+            DashboardEvent::CiCheckChanged {
+                pull_request_id: pr.id,
+                repository_id: repo.id,
+                number: pr.number,
+                name: check.name,
+                previous_status: before.map(|p| p.status.clone()),
+                previous_conclusion: before.and_then(|p| p.conclusion.clone()),
+                status: check.status,
+                conclusion: check.conclusion,
+            },
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+/// Publish that a PR which was open has been closed or merged
+pub async fn record_pr_closed(repo: &repository::Model, pr: &pull_request::Model) {
+    publish(
+        repo,
+        DashboardEvent::PrClosed {
+            pull_request_id: pr.id,
+            repository_id: repo.id,
+            number: pr.number,
+            state: pr.state.clone(),
+        },
+    )
+    .await;
+}
+
+/// Publish a change to the repository owner's dashboards
+async fn publish(repo: &repository::Model, event: DashboardEvent) {
+    live_events::publish(LiveEvent {
+        user_id: repo.user_id,
+        event,
+    })
+    .await;
+}
+
+fn notification(
+    kind: NotificationKind,
+    repo: &repository::Model,
+    pr: &pull_request::Model,
+    reviewer: Option<String>,
+) -> PrNotification {
+    PrNotification {
+        kind,
+        repository: repo.full_name.clone(),
+        pr_number: pr.number,
+        pr_title: pr.title.clone(),
+        pr_url: pr.url.clone(),
+        author: pr.author.clone(),
+        reviewer,
+    }
+}
+
+/// Queue `payload` for `user_id` on each channel that receives its kind
+async fn queue_for_user(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    pr: &pull_request::Model,
+    payload: PrNotification,
+    dedup_key: &str,
+) -> Result<usize, DbErr> {
+    let notice = Notice {
+        kind: payload.kind,
+        pull_request_id: Some(pr.id),
+        dedup_key: dedup_key.to_string(),
+        payload: serde_json::to_string(&payload)
+            .map_err(|e| DbErr::Custom(format!("Failed to serialize notification: {}", e)))?,
+    };
+    queue_notice(db, user_id, &notice).await
+}
@@ -22,6 +22,10 @@
 //!   API primitive, but the sandbox clone-push consolidation already produces the
 //!   fully-merged consolidated branch, so the API-level branch update is never
 //!   required: the sandbox push *is* the fallback.
+//! - **`create_suggestions`** (suggest-only runs) — a provider without native
+//!   suggested changes (`caps.create_suggestions == false`, e.g. Bitbucket) gets
+//!   the same proposals as one plain comment listing each file, line range and
+//!   replacement.
 //! - **`add_labels`** — not part of this seam. When unsupported the run simply
 //!   never issues labels; this is a no-op degrade, never a failure.
 //!
@@ -39,7 +43,8 @@ use std::sync::Arc;
 
 use ampel_core::errors::{AmpelError, AmpelResult};
 use ampel_core::models::GitProvider as ProviderKind;
-use ampel_core::models::{MergeRequest, MergeStrategy};
+use ampel_core::models::{MergeRequest, MergeStrategy, PullRequestState};
+use ampel_core::remediation::SuggestedChange;
 use ampel_core::services::{ProviderRefStatus, RawCiCheck, RemediationProvider};
 use ampel_providers::error::ProviderError;
 use ampel_providers::traits::ProviderCredentials;
@@ -105,6 +110,24 @@ fn provider_err(e: ProviderError) -> AmpelError {
     AmpelError::ProviderError(e.to_string())
 }
 
+/// Plain-comment rendering of suggestions for providers without native ones.
+pub fn suggestions_comment(suggestions: &[SuggestedChange]) -> String {
+    let mut body = String::from(
+        "Ampel remediation proposes the changes below. Nothing was pushed to this branch.\n",
+    );
+    for s in suggestions {
+        let lines = if s.start_line == s.end_line {
+            format!("line {}", s.start_line)
+        } else {
+            format!("lines {}-{}", s.start_line, s.end_line)
+        };
+        body.push_str(&format!("\n`{}` {lines}, replace with:\n", s.path));
+        body.push_str(&s.fenced(""));
+        body.push('\n');
+    }
+    body
+}
+
 #[async_trait]
 impl RemediationProvider for ProviderAdapter {
     async fn get_status_for_ref(&self, pr_number: i64) -> AmpelResult<ProviderRefStatus> {
@@ -213,4 +236,108 @@ impl RemediationProvider for ProviderAdapter {
             .map_err(provider_err)?;
         Ok(())
     }
+
+    async fn pull_request_state(&self, pr_number: i64) -> AmpelResult<PullRequestState> {
+        let pr = self
+            .provider
+            .get_pull_request(&self.credentials, &self.owner, &self.repo, pr_number as i32)
+            .await
+            .map_err(provider_err)?;
+        // Providers normalize to open/closed/merged; GitHub reports a merged PR
+        // as `closed` with `merged_at` set.
+        Ok(if pr.merged_at.is_some() || pr.state == "merged" {
+            PullRequestState::Merged
+        } else if pr.state == "open" {
+            PullRequestState::Open
+        } else {
+            PullRequestState::Closed
+        })
+    }
+
+    async fn open_pull_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> AmpelResult<i64> {
+        self.caps_guard(
+            self.provider.capabilities().create_pull_request,
+            "create_pull_request",
+        )?;
+        let pr = self
+            .provider
+            .create_pull_request(
+                &self.credentials,
+                &self.owner,
+                &self.repo,
+                title,
+                body,
+                head,
+                base,
+            )
+            .await
+            .map_err(provider_err)?;
+        Ok(pr.number as i64)
+    }
+
+    async fn find_open_pull_request(&self, branch: &str) -> AmpelResult<Option<i64>> {
+        let pr = self
+            .provider
+            .find_open_pull_request_by_head(&self.credentials, &self.owner, &self.repo, branch)
+            .await
+            .map_err(provider_err)?;
+        Ok(pr.map(|pr| pr.number as i64))
+    }
+
+    async fn comment_on_pull_request(&self, pr_number: i64, body: &str) -> AmpelResult<()> {
+        self.caps_guard(
+            self.provider.capabilities().create_comment,
+            "create_comment",
+        )?;
+        self.provider
+            .create_comment(
+                &self.credentials,
+                &self.owner,
+                &self.repo,
+                pr_number as i32,
+                body,
+            )
+            .await
+            .map_err(provider_err)?;
+        Ok(())
+    }
+
+    async fn list_comments(&self, pr_number: i64) -> AmpelResult<Vec<String>> {
+        self.provider
+            .list_comments(&self.credentials, &self.owner, &self.repo, pr_number as i32)
+            .await
+            .map_err(provider_err)
+    }
+
+    async fn suggest_changes(
+        &self,
+        pr_number: i64,
+        suggestions: &[SuggestedChange],
+    ) -> AmpelResult<()> {
+        if suggestions.is_empty() {
+            return Ok(());
+        }
+        if self.provider.capabilities().create_suggestions {
+            return self
+                .provider
+                .create_suggestions(
+                    &self.credentials,
+                    &self.owner,
+                    &self.repo,
+                    pr_number as i32,
+                    suggestions,
+                )
+                .await
+                .map_err(provider_err);
+        }
+        // Fallback: the same proposals as one plain comment.
+        self.comment_on_pull_request(pr_number, &suggestions_comment(suggestions))
+            .await
+    }
 }
@@ -18,6 +18,25 @@
 //! executor finds it already in `merging` and **resumes at `do_merge`** (with the
 //! orchestrator's TOCTOU re-verify), then finalizes.
 //!
+//! ## Resuming
+//! Runs are redelivered by the job queue when a worker dies, so the executor
+//! starts from whatever state the run was persisted in. It first hands the run
+//! to [`RemediationOrchestrator::reconcile`], which checks the persisted
+//! consolidated PR and head SHA against the provider: a consolidation whose PR
+//! was opened is adopted, an interrupted `agent_fixing` goes back to
+//! `verifying`, a merge that already landed skips to `finalizing`, and source
+//! PRs already closed are not closed again. The executor then continues at the
+//! step reconciliation names — `verifying` re-verifies, `merging` merges,
+//! `finalizing` closes the remaining source PRs. A run that cannot be continued
+//! safely (no consolidated PR, or one closed unmerged) is failed.
+//!
+//! ## Suggest-only
+//! A `suggest_only` run parks in `no_op` like a dry run. When the job injects a
+//! [`SuggestOnlyPublisher`], the executor first records the publisher's plan on
+//! the run and posts the plan and the agent's proposed fixes on the PRs. This
+//! happens while the run is still `created`, so a fix that could not be posted
+//! fails the run instead of leaving a `no_op` that looks successful.
+//!
 //! ## Observability (Phase 3)
 //! Metric emission lives here (the worker layer) so `ampel-core` stays
 //! dependency-light. The executor records run terminal counts/durations, merge
@@ -28,11 +47,14 @@
 use std::sync::Arc;
 use std::time::Instant;
 
-use ampel_core::errors::AmpelResult;
-use ampel_core::remediation::{MergeDisposition, PrRef, RemediationTier, RunState};
+use ampel_core::errors::{AmpelError, AmpelResult};
+use ampel_core::remediation::{
+    ConsolidationStrategy, MergeDisposition, PrRef, RemediationTier, RunState,
+};
 use ampel_core::services::{
-    ConsolidateResult, HandoffReason, MergeOutcome, RemediationOrchestrator, RemediationProvider,
-    RemediationRunRepository, RepoContext, RunUpdate, SandboxRunner, VerificationService,
+    ConsolidateResult, HandoffReason, MergeOutcome, Reconciliation, RemediationOrchestrator,
+    RemediationProvider, RemediationRunRepository, RepoContext, RunUpdate, SandboxRunner,
+    VerificationService,
 };
 use uuid::Uuid;
 
@@ -41,6 +63,7 @@ use crate::services::agentic_tier::{tier_allows_agentic, AgentTierOutcome, Agent
 use crate::services::notifier::{
     NoopNotifier, RemediationNotifier, RunMergedNotification, SourcePrsClosedNotification,
 };
+use crate::services::suggestions::SuggestOnlyPublisher;
 
 /// Terminal (or parked) outcome of an executor run.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -68,6 +91,8 @@ pub struct RemediationExecutor {
     /// recovery attempt before handing off to a human.
     agentic_tier: Option<Arc<dyn AgenticTier>>,
     remediation_tier: RemediationTier,
+    /// Posts a suggest-only run's plan and fixes before it parks in `no_op`.
+    suggestions: Option<Arc<SuggestOnlyPublisher>>,
 }
 
 impl RemediationExecutor {
@@ -86,16 +111,13 @@ impl RemediationExecutor {
             notifier: Arc::new(NoopNotifier),
             agentic_tier: None,
             remediation_tier: RemediationTier::ConsolidateOnly,
+            suggestions: None,
         }
     }
 
     /// Inject the Tier-2 agentic seam together with the run's resolved
     /// `remediation_tier`. Only `fix_and_consolidate` / `full_remediation` will
     /// actually invoke the model (see [`tier_allows_agentic`]).
-    ///
-    /// Exercised by the executor integration tests; the bin's run job does not
-    /// yet construct the sandbox-backed tier (see `agentic_tier` module note).
-    #[allow(dead_code)]
     pub fn with_agentic_tier(
         mut self,
         tier: Arc<dyn AgenticTier>,
@@ -106,6 +128,18 @@ impl RemediationExecutor {
         self
     }
 
+    /// Consolidate with the policy's `strategy` instead of the default `merge`.
+    pub fn with_consolidation_strategy(mut self, strategy: ConsolidationStrategy) -> Self {
+        self.orchestrator = self.orchestrator.with_consolidation_strategy(strategy);
+        self
+    }
+
+    /// Inject the suggest-only publisher, run before the run parks in `no_op`.
+    pub fn with_suggestions(mut self, publisher: Arc<SuggestOnlyPublisher>) -> Self {
+        self.suggestions = Some(publisher);
+        self
+    }
+
     /// Set the provider-kind label used on merge metrics and notification
     /// payloads (e.g. `github`).
     pub fn with_provider_label(mut self, label: impl Into<String>) -> Self {
@@ -122,8 +156,9 @@ impl RemediationExecutor {
     /// Execute `run_id`: consolidate → verify → (gate?) → (re-verify) merge →
     /// finalize. Short-circuits to [`RunOutcome::NoOp`] under read-only autonomy,
     /// to [`RunOutcome::AwaitingApproval`] at the human gate, and to
-    /// [`RunOutcome::HandoffHuman`] at any safety gate. A run already in `merging`
-    /// (human-approved) resumes at `do_merge`.
+    /// [`RunOutcome::HandoffHuman`] at any safety gate. A run already past
+    /// `created` resumes at the step its persisted state names (see
+    /// "Resuming" in the module docs).
     ///
     /// Crash safety (chaos DoD): ANY error from the orchestration drives a
     /// best-effort CAS of the run from its current active state into
@@ -160,11 +195,56 @@ impl RemediationExecutor {
         prs: Vec<PrRef>,
         repo_ctx: RepoContext,
     ) -> AmpelResult<RunOutcome> {
-        // Resume a human-approved run already advanced to `merging` (the API
-        // approve endpoint CAS-advanced awaiting_approval → merging). Pick up at
-        // do_merge; consolidate/verify already ran on the prior pass.
-        if self.run_state(run_id).await? == Some(RunState::Merging) {
-            return self.merge_and_finalize(run_id, &prs).await;
+        // Resume from the persisted state: a human-approved run (the API approve
+        // endpoint CAS-advanced awaiting_approval → merging) or a run whose
+        // previous worker died picks up at the step it had reached, once the
+        // orchestrator has reconciled that state against the provider.
+        if let Some(state) = self.run_state(run_id).await? {
+            if state != RunState::Created && state.is_active() {
+                let source_prs: Vec<i64> = prs.iter().map(|p| p.number as i64).collect();
+                match self.orchestrator.reconcile(run_id, &source_prs).await? {
+                    Reconciliation::Resume(RunState::Verifying) => {
+                        return self.verify_and_merge(run_id, &prs).await
+                    }
+                    Reconciliation::Resume(RunState::Merging) => {
+                        return self.merge_and_finalize(run_id, &prs).await
+                    }
+                    Reconciliation::Resume(RunState::Finalizing) => {
+                        return self.finalize_sources(run_id, &prs).await
+                    }
+                    Reconciliation::Resume(RunState::AwaitingApproval) => {
+                        return Ok(RunOutcome::AwaitingApproval)
+                    }
+                    Reconciliation::Resume(_) => {}
+                    Reconciliation::Failed { reason } => {
+                        // Already terminal; surfaced as an error so the delivery
+                        // is counted as failed.
+                        return Err(AmpelError::InternalError(format!(
+                            "run {run_id} could not be resumed: {reason}"
+                        )));
+                    }
+                }
+            }
+        }
+
+        // Suggest-only: publish while the run is still `created`, so a failed
+        // post fails the run (see "Suggest-only" in the module docs).
+        if let Some(publisher) = &self.suggestions {
+            let run = self.repo.get_run(run_id).await?;
+            if run
+                .is_some_and(|r| r.state == RunState::Created && !r.autonomy_level.allows_writes())
+            {
+                self.repo
+                    .set_consolidation_plan(run_id, publisher.plan().clone())
+                    .await?;
+                let summary = publisher.publish(run_id).await?;
+                tracing::info!(
+                    %run_id,
+                    plan_comments = summary.plan_comments,
+                    suggestions = summary.suggestions,
+                    "suggest-only run published"
+                );
+            }
         }
 
         // 1. Consolidate (sandbox). Read-only autonomy parks the run in no_op.
@@ -178,11 +258,17 @@ impl RemediationExecutor {
         };
         // Count any skipped-conflict dispositions the sandbox surfaced.
         for (_, disposition) in &consolidated.dispositions {
-            if let MergeDisposition::SkippedConflict { reason } = disposition {
+            if let MergeDisposition::SkippedConflict { reason, .. } = disposition {
                 observability::record_conflict(observability::classify_conflict(reason));
             }
         }
 
+        self.verify_and_merge(run_id, &prs).await
+    }
+
+    /// Steps 2–4 for a run in `verifying`: verify (with the agentic recovery
+    /// attempt), then merge and finalize unless the run hands off or parks.
+    async fn verify_and_merge(&self, run_id: Uuid, prs: &[PrRef]) -> AmpelResult<RunOutcome> {
         // 2. Verify (ADR-010). A non-safe result leaves the run in `verifying`,
         //    so we hand it off. A safe result advances the run — but the *next*
         //    state depends on the autonomy tier (the orchestrator decides):
@@ -207,7 +293,7 @@ impl RemediationExecutor {
                                 {
                                     return Ok(RunOutcome::AwaitingApproval);
                                 }
-                                return self.merge_and_finalize(run_id, &prs).await;
+                                return self.merge_and_finalize(run_id, prs).await;
                             }
                             // Still not safe after recovery → hand off below.
                         }
@@ -235,7 +321,7 @@ impl RemediationExecutor {
         }
 
         // 3 + 4. Re-verify (TOCTOU) + merge, then finalize.
-        self.merge_and_finalize(run_id, &prs).await
+        self.merge_and_finalize(run_id, prs).await
     }
 
     /// Drive `do_merge` (with the orchestrator's TOCTOU re-verify) and, on a
@@ -252,30 +338,30 @@ impl RemediationExecutor {
             }
         }
 
-        let consolidated_pr = self
-            .repo
-            .get_run(run_id)
-            .await?
-            .and_then(|r| r.consolidated_pr_number)
-            .unwrap_or_default();
-
         self.notifier
             .run_merged(RunMergedNotification {
                 run_id,
-                consolidated_pr_number: consolidated_pr,
+                consolidated_pr_number: self.consolidated_pr(run_id).await?,
                 provider: self.provider_label.clone(),
             })
             .await;
 
-        // Finalize: close each source PR with a "Superseded by #N" comment.
+        self.finalize_sources(run_id, prs).await
+    }
+
+    /// Finalize a run in `finalizing`: close each consolidated source PR with a
+    /// "Superseded by #N" comment (already-closed ones are skipped, so this is
+    /// safe to re-enter; conflict-skipped ones stay open) and notify.
+    async fn finalize_sources(&self, run_id: Uuid, prs: &[PrRef]) -> AmpelResult<RunOutcome> {
+        let consolidated_pr = self.consolidated_pr(run_id).await?;
         let source_prs: Vec<i64> = prs.iter().map(|p| p.number as i64).collect();
-        self.orchestrator.finalize(run_id, &source_prs).await?;
+        let closed = self.orchestrator.finalize(run_id, &source_prs).await?;
 
         self.notifier
             .sources_closed(SourcePrsClosedNotification {
                 run_id,
                 consolidated_pr_number: consolidated_pr,
-                closed_pr_numbers: source_prs,
+                closed_pr_numbers: closed,
             })
             .await;
 
@@ -294,6 +380,15 @@ impl RemediationExecutor {
         observability::record_run_terminal(state, duration_secs);
     }
 
+    async fn consolidated_pr(&self, run_id: Uuid) -> AmpelResult<i64> {
+        Ok(self
+            .repo
+            .get_run(run_id)
+            .await?
+            .and_then(|r| r.consolidated_pr_number)
+            .unwrap_or_default())
+    }
+
     async fn run_state(&self, run_id: Uuid) -> AmpelResult<Option<RunState>> {
         Ok(self.repo.get_run(run_id).await?.map(|r| r.state))
     }
@@ -1,36 +1,94 @@
 //! Podman/Docker-backed [`SandboxRunner`] (ADR-003 / ADR-005).
 //!
-//! The mechanical consolidation — clone, sequential `git merge --no-ff` of each
-//! source PR branch, lockfile regeneration, push, open consolidating PR — runs
-//! inside an isolated OCI container. The **pure** decision logic (runtime
-//! detection, lockfile classification + regen command, merge sequencing,
-//! conflict parsing) is factored into free functions that are unit-tested here;
-//! the actual subprocess/container invocation is a thin wrapper that is *not*
-//! exercised in CI (no containers/network on the test runners).
+//! The mechanical consolidation — clone, combining each source PR branch in turn
+//! per the policy's consolidation strategy (`merge --no-ff`, rebase onto the
+//! base, or one squash commit per PR), lockfile regeneration, push — runs inside
+//! an isolated OCI container; the orchestrator then opens the consolidating PR.
+//! The step plan and conflict handling are `ampel-core`'s [`combine_prs`],
+//! shared with the `FakeSandboxRunner`; this module supplies the
+//! [`GitWorkspace`] it drives — one short-lived container per command over a
+//! per-run host workspace — plus the **pure** runtime detection and lockfile
+//! classification, which are unit-tested here. The container invocation itself
+//! is *not* exercised in CI (no containers/network on the test runners).
 //!
 //! Security invariants:
-//! - The PAT is passed via env/tmpfs to git, never as a CLI arg, never logged.
+//! - The PAT reaches only the clone and push containers, through an inherited
+//!   env var read by an inline credential helper — never a CLI arg, never
+//!   logged (command output is scrubbed).
 //! - `GIT_TERMINAL_PROMPT=0` / `GIT_ASKPASS=echo` prevent interactive prompts.
 //! - No force-push primitive exists anywhere in this module.
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use ampel_core::errors::{AmpelError, AmpelResult};
 use ampel_core::remediation::{
-    regen_command_for, HeuristicFingerprinter, LockfileKind, PrRef, RepoFingerprinter,
+    build_merge_sequence, regen_command_for, HeuristicFingerprinter, LockfileKind,
+    MergeDisposition, RepoFingerprinter,
+};
+use ampel_core::services::{
+    combine_prs, CommandOutput, ConsolidationOutcome, ConsolidationSpec, CredentialHandle,
+    GitWorkspace, SandboxRunner,
 };
-use ampel_core::services::{ConsolidationOutcome, ConsolidationSpec, SandboxRunner};
 use async_trait::async_trait;
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// Where the host workspace is mounted inside the container.
+pub(crate) const WORKSPACE: &str = "/workspace";
+/// The clone inside the container.
+pub(crate) const REPO_DIR: &str = "/workspace/repo";
+
+/// Inline credential helper: git's own shell expands the env vars, so the token
+/// never appears in any argv.
+pub(crate) const CREDENTIAL_HELPER: &str = r#"credential.helper=!f() { echo "username=${AMPEL_GIT_USERNAME}"; echo "password=${AMPEL_GIT_TOKEN}"; }; f"#;
+
+/// Clone the default branch, fetch each source PR branch into
+/// `refs/remotes/origin/*`, and create the consolidation branch. Branch names
+/// cannot contain whitespace or glob characters, so word splitting the list is
+/// safe; `set -f` rules out globbing regardless.
+///
+/// A branch that forked before the shallow boundary has no merge base with the
+/// default branch yet, so both are deepened by another `AMPEL_CLONE_DEPTH`
+/// commits until they meet, up to five times. A branch still without one is
+/// left open by `combine_prs` rather than replayed with unrelated commits.
+const CONSOLIDATION_CLONE_SCRIPT: &str = r#"set -euf
+git -c "$AMPEL_CREDENTIAL_HELPER" clone --depth "$AMPEL_CLONE_DEPTH" --branch "$AMPEL_DEFAULT_BRANCH" "$AMPEL_CLONE_URL" repo
+cd repo
+for branch in $AMPEL_SOURCE_BRANCHES; do
+  git -c "$AMPEL_CREDENTIAL_HELPER" fetch --depth "$AMPEL_CLONE_DEPTH" origin "+refs/heads/$branch:refs/remotes/origin/$branch"
+  rounds=0
+  until git merge-base "origin/$AMPEL_DEFAULT_BRANCH" "origin/$branch" >/dev/null 2>&1 || [ "$rounds" -ge 5 ]; do
+    git -c "$AMPEL_CREDENTIAL_HELPER" fetch --deepen "$AMPEL_CLONE_DEPTH" origin "+refs/heads/$AMPEL_DEFAULT_BRANCH:refs/remotes/origin/$AMPEL_DEFAULT_BRANCH" "+refs/heads/$branch:refs/remotes/origin/$branch"
+    rounds=$((rounds + 1))
+  done
+done
+git checkout -b "$AMPEL_BRANCH""#;
+
+/// Push the consolidation branch (plain fast-forward of a new branch, never
+/// forced) and print its head SHA last.
+const CONSOLIDATION_PUSH_SCRIPT: &str = r#"set -eu
+git -c "$AMPEL_CREDENTIAL_HELPER" push origin "HEAD:refs/heads/$AMPEL_BRANCH"
+git rev-parse HEAD"#;
+
+/// Run the positional parameters as one command, without a shell in between.
+const EXEC_SCRIPT: &str = r#"exec "$@""#;
+
+/// Commit identity for merge, cherry-pick and squash commits.
+const GIT_IDENTITY: &[(&str, &str)] = &[
+    ("GIT_AUTHOR_NAME", "Ampel Remediation"),
+    ("GIT_AUTHOR_EMAIL", "remediation@ampel.local"),
+    ("GIT_COMMITTER_NAME", "Ampel Remediation"),
+    ("GIT_COMMITTER_EMAIL", "remediation@ampel.local"),
+];
 
 // ---------------------------------------------------------------------------
 // Pure logic (unit-tested) — no I/O, no subprocess, no env reads.
 // ---------------------------------------------------------------------------
 
-// The following lockfile/conflict helpers are the pure decision logic the
-// container entrypoint + output parser rely on. They are exercised by the unit
-// tests below; `#[allow(dead_code)]` covers the window before the (CI-gated)
-// container invocation path calls them from non-test code.
+// The following lockfile helpers keep the worker's historical lockfile surface;
+// detection and regeneration for consolidation go through `ampel-core`.
 
 /// A recognized lockfile ecosystem requiring deterministic regeneration after a
 /// merge touches it.
@@ -96,38 +154,6 @@ pub fn regen_command(class: LockfileClass) -> &'static [&'static str] {
     regen_command_for(LockfileKind::from(class))
 }
 
-/// A single git step in the consolidation sequence.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum GitStep {
-    /// `git clone --depth <depth> <url> <dir>` then checkout the default branch.
-    Clone { depth: u32 },
-    /// `git checkout -b <branch>` for the deterministic consolidation branch.
-    CreateBranch { branch: String },
-    /// `git merge --no-ff origin/<source_branch>` for one source PR.
-    Merge {
-        pr_number: i32,
-        source_branch: String,
-    },
-}
-
-/// Build the ordered git step sequence for a consolidation: clone, create the
-/// consolidation branch, then `merge --no-ff` each PR in the given (oldest-first)
-/// order. Pure — produces the plan, executes nothing.
-pub fn build_merge_sequence(branch: &str, prs: &[PrRef], clone_depth: u32) -> Vec<GitStep> {
-    let mut steps = Vec::with_capacity(prs.len() + 2);
-    steps.push(GitStep::Clone { depth: clone_depth });
-    steps.push(GitStep::CreateBranch {
-        branch: branch.to_string(),
-    });
-    for pr in prs {
-        steps.push(GitStep::Merge {
-            pr_number: pr.number,
-            source_branch: pr.branch.clone(),
-        });
-    }
-    steps
-}
-
 /// True when git stderr indicates a merge conflict (so the caller records a
 /// `SkippedConflict` disposition rather than aborting the whole run).
 #[allow(dead_code)]
@@ -180,7 +206,6 @@ pub fn detect_runtime(
 }
 
 /// Strip a secret value from arbitrary text so it can never reach a log line.
-#[allow(dead_code)]
 pub fn scrub_secret(text: &str, secret: &str) -> String {
     if secret.is_empty() {
         return text.to_string();
@@ -228,7 +253,7 @@ fn parse_env_u32(key: &str, default: u32) -> u32 {
         .unwrap_or(default)
 }
 
-fn parse_env_u64(key: &str, default: u64) -> u64 {
+pub(crate) fn parse_env_u64(key: &str, default: u64) -> u64 {
     std::env::var(key)
         .ok()
         .and_then(|v| v.parse().ok())
@@ -243,12 +268,221 @@ fn binary_on_path(bin: &str) -> bool {
     std::env::split_paths(&path).any(|dir| dir.join(bin).is_file())
 }
 
+/// Build the `run` argv for one container invocation. Env vars are listed by
+/// NAME only — their values come from the runtime process environment.
+pub fn container_args(
+    config: &SandboxConfig,
+    workspace: &Path,
+    workdir: &str,
+    env_names: &[&str],
+    script: &str,
+) -> Vec<String> {
+    let mut mount = format!("{}:{WORKSPACE}", workspace.display());
+    if config.runtime == SandboxRuntime::Podman {
+        // Relabel for SELinux hosts; rootless podman maps ownership back.
+        mount.push_str(":Z");
+    }
+    let mut args = vec![
+        "run".to_string(),
+        "--rm".to_string(),
+        "-v".to_string(),
+        mount,
+        "-w".to_string(),
+        workdir.to_string(),
+    ];
+    for name in env_names {
+        args.push("-e".to_string());
+        args.push((*name).to_string());
+    }
+    args.extend([
+        config.image.clone(),
+        "sh".to_string(),
+        "-c".to_string(),
+        script.to_string(),
+    ]);
+    args
+}
+
+/// Run `script` in a fresh sandbox container with `workspace` mounted at
+/// [`WORKSPACE`], bounded by the configured subprocess timeout. `env` values
+/// reach the container through the runtime process environment; `args` become
+/// the script's positional parameters (`$1`, ...).
+pub(crate) async fn run_in_container(
+    config: &SandboxConfig,
+    workspace: &Path,
+    workdir: &str,
+    env: &[(&str, &str)],
+    script: &str,
+    args: &[String],
+) -> AmpelResult<std::process::Output> {
+    let names: Vec<&str> = env.iter().map(|(name, _)| *name).collect();
+    let mut argv = container_args(config, workspace, workdir, &names, script);
+    if !args.is_empty() {
+        // `sh -c` takes the first argument after the script as `$0`.
+        argv.push("ampel".to_string());
+        argv.extend(args.iter().cloned());
+    }
+
+    let mut command = Command::new(config.runtime.binary());
+    command
+        .args(argv)
+        .envs(env.iter().copied())
+        .kill_on_drop(true);
+
+    tokio::time::timeout(config.subprocess_timeout, command.output())
+        .await
+        .map_err(|_| {
+            AmpelError::InternalError(format!(
+                "sandbox step timed out after {}s",
+                config.subprocess_timeout.as_secs()
+            ))
+        })?
+        .map_err(io_err)
+}
+
+pub(crate) fn io_err(e: std::io::Error) -> AmpelError {
+    AmpelError::InternalError(format!("sandbox workspace: {e}"))
+}
+
+/// The consolidation clone: a per-run host workspace in which every git and
+/// lockfile command runs in its own sandbox container. Only the clone and push
+/// scripts see the credential. The workspace is removed on drop.
+struct ContainerWorkspace {
+    config: SandboxConfig,
+    credential: CredentialHandle,
+    workspace: PathBuf,
+}
+
+impl ContainerWorkspace {
+    fn new(config: SandboxConfig, credential: CredentialHandle) -> Self {
+        Self {
+            config,
+            credential,
+            workspace: std::env::temp_dir().join(format!("ampel-consolidation-{}", Uuid::new_v4())),
+        }
+    }
+
+    /// Clone the base, fetch the PR branches, create the consolidation branch.
+    async fn prepare(&self, spec: &ConsolidationSpec) -> AmpelResult<()> {
+        tokio::fs::create_dir_all(&self.workspace)
+            .await
+            .map_err(io_err)?;
+        let depth = self.config.clone_depth.to_string();
+        let branches: Vec<&str> = spec.prs.iter().map(|p| p.branch.as_str()).collect();
+        let branches = branches.join(" ");
+        self.credentialed(
+            WORKSPACE,
+            &[
+                ("AMPEL_CLONE_URL", spec.clone_url.as_str()),
+                ("AMPEL_CLONE_DEPTH", depth.as_str()),
+                ("AMPEL_DEFAULT_BRANCH", spec.default_branch.as_str()),
+                ("AMPEL_SOURCE_BRANCHES", branches.as_str()),
+                ("AMPEL_BRANCH", spec.branch_name.as_str()),
+            ],
+            CONSOLIDATION_CLONE_SCRIPT,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Push the consolidation branch and return its head SHA.
+    async fn push(&self, branch: &str) -> AmpelResult<String> {
+        let output = self
+            .credentialed(
+                REPO_DIR,
+                &[("AMPEL_BRANCH", branch)],
+                CONSOLIDATION_PUSH_SCRIPT,
+            )
+            .await?;
+        output
+            .lines()
+            .last()
+            .map(|sha| sha.trim().to_string())
+            .filter(|sha| !sha.is_empty())
+            .ok_or_else(|| AmpelError::InternalError("push printed no head SHA".to_string()))
+    }
+
+    /// Run a script that needs the credential; any failure is an error.
+    async fn credentialed(
+        &self,
+        workdir: &str,
+        vars: &[(&str, &str)],
+        script: &str,
+    ) -> AmpelResult<String> {
+        let username = self.credential.username().unwrap_or("x-access-token");
+        let mut env: Vec<(&str, &str)> = vec![
+            ("AMPEL_GIT_USERNAME", username),
+            ("AMPEL_GIT_TOKEN", self.credential.expose()),
+            ("AMPEL_CREDENTIAL_HELPER", CREDENTIAL_HELPER),
+            ("GIT_TERMINAL_PROMPT", "0"),
+        ];
+        env.extend_from_slice(vars);
+        let output =
+            run_in_container(&self.config, &self.workspace, workdir, &env, script, &[]).await?;
+        if !output.status.success() {
+            return Err(AmpelError::InternalError(format!(
+                "sandbox step failed ({}): {}",
+                output.status,
+                self.scrub(&String::from_utf8_lossy(&output.stderr))
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn scrub(&self, text: &str) -> String {
+        scrub_secret(text.trim(), self.credential.expose())
+    }
+}
+
+#[async_trait]
+impl GitWorkspace for ContainerWorkspace {
+    async fn run(&self, dir: &str, argv: &[String]) -> AmpelResult<CommandOutput> {
+        let workdir = if dir.is_empty() {
+            REPO_DIR.to_string()
+        } else {
+            format!("{REPO_DIR}/{dir}")
+        };
+        let mut env = GIT_IDENTITY.to_vec();
+        env.push(("GIT_TERMINAL_PROMPT", "0"));
+        let output = run_in_container(
+            &self.config,
+            &self.workspace,
+            &workdir,
+            &env,
+            EXEC_SCRIPT,
+            argv,
+        )
+        .await?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok(CommandOutput {
+            success: output.status.success(),
+            output: self.scrub(&text),
+        })
+    }
+}
+
+impl Drop for ContainerWorkspace {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.workspace) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(
+                    workspace = %self.workspace.display(),
+                    error = %e,
+                    "failed to remove consolidation workspace"
+                );
+            }
+        }
+    }
+}
+
 /// Production [`SandboxRunner`]: drives the consolidation inside a Podman/Docker
 /// container. Constructed from env via [`SandboxConfig::from_env`].
 ///
 /// NOTE: `run_consolidation` is a thin wrapper around the container invocation
-/// and is intentionally not exercised in CI. The decision logic it relies on is
-/// unit-tested above as pure functions.
+/// and is intentionally not exercised in CI. The step plan and conflict
+/// handling it drives are unit-tested in `ampel-core` against scripted
+/// workspaces.
 pub struct PodmanSandboxRunner {
     config: SandboxConfig,
     /// Repo fingerprinter used to derive lockfile regen commands (and, later, the
@@ -275,11 +509,6 @@ impl PodmanSandboxRunner {
         self
     }
 
-    /// Construct from the environment; falls back to a Podman/Docker auto-detect.
-    pub fn from_env() -> AmpelResult<Self> {
-        Ok(Self::new(SandboxConfig::from_env()?))
-    }
-
     /// Fingerprint-aware regen selection: given the repo's file listing and the
     /// set of conflicted lockfile paths a merge touched, resolve each path to its
     /// deterministic regen argv **via the injected fingerprinter** (not a local
@@ -326,29 +555,54 @@ impl SandboxRunner for PodmanSandboxRunner {
         spec: ConsolidationSpec,
     ) -> AmpelResult<ConsolidationOutcome> {
         // Build the deterministic, oldest-first git plan (pure).
-        let steps = build_merge_sequence(&spec.branch_name, &spec.prs, self.config.clone_depth);
+        let steps = build_merge_sequence(
+            &spec.branch_name,
+            &spec.prs,
+            self.config.clone_depth,
+            spec.strategy,
+        );
         tracing::info!(
             run_id = %spec.run_id,
+            strategy = %spec.strategy,
             runtime = self.config.runtime.binary(),
             image = %self.config.image,
             steps = steps.len(),
             "preparing sandboxed consolidation"
         );
 
-        // The full container orchestration (write an entrypoint that performs the
-        // git steps + lockfile regen, mount a tmpfs env-file carrying the PAT,
-        // apply the egress allowlist, parse a final JSON result line) is a later
-        // wiring step. It is deliberately gated out of the CI test path; the
-        // CI-safe tests drive the orchestrator with `FakeSandboxRunner` instead.
-        //
-        // Returning an explicit error here keeps the production binary honest
-        // until the container entrypoint ships, rather than silently "succeeding".
-        let _ = (&self.config.subprocess_timeout, spec.credential.expose());
-        Err(AmpelError::InternalError(
-            "PodmanSandboxRunner container execution is not yet wired (Phase 2 slice 2 ships the \
-             pure consolidation logic + entrypoint image; runtime invocation lands next)"
-                .to_string(),
-        ))
+        let workspace = ContainerWorkspace::new(self.config.clone(), spec.credential.clone());
+        workspace.prepare(&spec).await?;
+        let dispositions = combine_prs(&workspace, &steps, &spec.default_branch).await?;
+
+        // A branch every PR was skipped from holds nothing worth pushing.
+        let any_consolidated = dispositions
+            .iter()
+            .any(|(_, d)| *d == MergeDisposition::Consolidated);
+        let head_sha = if any_consolidated {
+            workspace.push(&spec.branch_name).await?
+        } else {
+            String::new()
+        };
+        tracing::info!(
+            run_id = %spec.run_id,
+            consolidated = dispositions
+                .iter()
+                .filter(|(_, d)| *d == MergeDisposition::Consolidated)
+                .count(),
+            skipped = dispositions.len()
+                - dispositions
+                    .iter()
+                    .filter(|(_, d)| *d == MergeDisposition::Consolidated)
+                    .count(),
+            "sandboxed consolidation finished"
+        );
+
+        Ok(ConsolidationOutcome {
+            branch_name: spec.branch_name,
+            consolidated_pr_number: None,
+            dispositions,
+            head_sha,
+        })
     }
 }
 
@@ -356,14 +610,6 @@ impl SandboxRunner for PodmanSandboxRunner {
 mod tests {
     use super::*;
 
-    fn pr(n: i32, branch: &str) -> PrRef {
-        PrRef {
-            number: n,
-            title: format!("PR {n}"),
-            branch: branch.to_string(),
-        }
-    }
-
     #[test]
     fn should_detect_each_lockfile_class_by_filename() {
         assert_eq!(
@@ -439,34 +685,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn should_build_oldest_first_merge_sequence_with_clone_and_branch() {
-        // Arrange
-        let prs = [pr(1, "feature/a"), pr(2, "feature/b")];
-
-        // Act
-        let steps = build_merge_sequence("ampel/remediation/run", &prs, 50);
-
-        // Assert: clone, create-branch, then one --no-ff merge per PR in order.
-        assert_eq!(
-            steps,
-            vec![
-                GitStep::Clone { depth: 50 },
-                GitStep::CreateBranch {
-                    branch: "ampel/remediation/run".to_string()
-                },
-                GitStep::Merge {
-                    pr_number: 1,
-                    source_branch: "feature/a".to_string()
-                },
-                GitStep::Merge {
-                    pr_number: 2,
-                    source_branch: "feature/b".to_string()
-                },
-            ]
-        );
-    }
-
     #[test]
     fn should_detect_git_merge_conflict_in_stderr() {
         assert!(parse_merge_conflict(
@@ -510,6 +728,44 @@ mod tests {
         assert!(detect_runtime(None, |_| false).is_err());
     }
 
+    #[test]
+    fn should_never_force_push_the_consolidation_branch() {
+        assert!(!CONSOLIDATION_PUSH_SCRIPT.contains("--force"));
+        assert!(!CONSOLIDATION_PUSH_SCRIPT.contains("+HEAD"));
+    }
+
+    #[test]
+    fn should_deepen_each_source_branch_until_it_meets_the_default_branch() {
+        assert!(CONSOLIDATION_CLONE_SCRIPT
+            .contains(r#"until git merge-base "origin/$AMPEL_DEFAULT_BRANCH" "origin/$branch""#));
+        assert!(CONSOLIDATION_CLONE_SCRIPT.contains(r#"fetch --deepen "$AMPEL_CLONE_DEPTH""#));
+    }
+
+    #[test]
+    fn should_pass_git_commands_as_positional_args_without_the_token() {
+        // Arrange
+        let config = SandboxConfig {
+            runtime: SandboxRuntime::Docker,
+            image: "ghcr.io/ampel/remediation-sandbox:latest".to_string(),
+            clone_depth: 50,
+            subprocess_timeout: Duration::from_secs(300),
+        };
+
+        // Act: the argv for a `git merge` step, before the positional args.
+        let args = container_args(
+            &config,
+            Path::new("/tmp/ws"),
+            REPO_DIR,
+            &["GIT_TERMINAL_PROMPT"],
+            EXEC_SCRIPT,
+        );
+
+        // Assert: the command runs via `exec "$@"`, never through a shell
+        // string, and no git-step container is handed the credential.
+        assert_eq!(&args[args.len() - 3..], &["sh", "-c", EXEC_SCRIPT]);
+        assert!(GIT_IDENTITY.iter().all(|(name, _)| !name.contains("TOKEN")));
+    }
+
     #[test]
     fn should_scrub_secret_from_text() {
         // Arrange
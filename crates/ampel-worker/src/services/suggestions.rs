@@ -0,0 +1,291 @@
+//! Suggest-only remediation: propose, never push (ADR-002 `suggest_only`).
+//!
+//! A `suggest_only` run parks in `no_op` without touching the repository. What
+//! it leaves behind is on the PRs themselves:
+//!
+//! - the consolidation plan from `RemediationService::preview`, posted as a
+//!   comment on every PR the plan would consolidate;
+//! - for each of those PRs whose CI is red, the agent's fix as the provider's
+//!   native suggestion blocks (GitHub suggested changes, GitLab suggestions),
+//!   or a plain comment where the provider has none.
+//!
+//! The agent runs exactly as in the agentic tier, but against a
+//! [`SuggestionWorktree`] that only records the unified diffs it is given:
+//! nothing is applied, committed or pushed, so the loop is capped at one
+//! iteration. Each diff becomes [`SuggestedChange`]s via
+//! [`suggestions_from_diff`].
+//!
+//! Publishing carries on past a failing PR so the remaining PRs still get their
+//! proposals. A plan comment or agent proposal that fails is only logged, but a
+//! fix the provider would not take fails the publish once every PR is done:
+//! the run must not report success with proposals missing.
+
+use std::sync::{Arc, Mutex};
+
+use ampel_core::errors::{AmpelError, AmpelResult};
+use ampel_core::remediation::{
+    suggestions_from_diff, ConsolidationPlan, NormalizedProviderOutput, PrRef, SuggestedChange,
+};
+use ampel_core::services::RemediationProvider;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::agent_harness::AgentWorktree;
+use super::agent_worktree::{edits_from_output, WorktreeEdit};
+use super::agentic_tier::AgenticTier;
+
+/// An [`AgentWorktree`] that records the agent's diffs instead of applying them.
+#[derive(Default)]
+pub struct SuggestionWorktree {
+    diffs: Mutex<Vec<String>>,
+}
+
+impl SuggestionWorktree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The suggestions from every diff recorded so far.
+    pub fn suggestions(&self) -> Vec<SuggestedChange> {
+        self.diffs
+            .lock()
+            .expect("suggestion worktree lock poisoned")
+            .iter()
+            .flat_map(|diff| suggestions_from_diff(diff))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AgentWorktree for SuggestionWorktree {
+    async fn apply_output(
+        &self,
+        worktree_ref: &str,
+        output: &NormalizedProviderOutput,
+    ) -> AmpelResult<()> {
+        let mut diffs = self
+            .diffs
+            .lock()
+            .expect("suggestion worktree lock poisoned");
+        for edit in edits_from_output(output)? {
+            match edit {
+                WorktreeEdit::Patch(diff) => diffs.push(diff),
+                // Without the file's current content there is no line range to
+                // anchor a whole-file rewrite on.
+                WorktreeEdit::WriteFile { path, .. } => {
+                    tracing::debug!(branch = %worktree_ref, %path, "whole-file edit cannot be suggested");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn commit_and_push(&self, _worktree_ref: &str, _message: &str) -> AmpelResult<()> {
+        Ok(())
+    }
+}
+
+/// Proposes a fix for one PR's failing CI.
+#[async_trait]
+pub trait FixProposer: Send + Sync {
+    /// The suggestions for `pr`; empty when its CI is green or the agent had
+    /// nothing to propose.
+    async fn propose(&self, run_id: Uuid, pr: &PrRef) -> AmpelResult<Vec<SuggestedChange>>;
+}
+
+/// Builds the agentic tier for one PR, editing the given worktree on its branch.
+pub type TierFactory =
+    Box<dyn Fn(&PrRef, Arc<dyn AgentWorktree>) -> Arc<dyn AgenticTier> + Send + Sync>;
+
+/// [`FixProposer`] backed by the agentic tier running on a [`SuggestionWorktree`].
+pub struct AgentFixProposer {
+    tier_for: TierFactory,
+}
+
+impl AgentFixProposer {
+    pub fn new(tier_for: TierFactory) -> Self {
+        Self { tier_for }
+    }
+}
+
+#[async_trait]
+impl FixProposer for AgentFixProposer {
+    async fn propose(&self, run_id: Uuid, pr: &PrRef) -> AmpelResult<Vec<SuggestedChange>> {
+        let worktree = Arc::new(SuggestionWorktree::new());
+        let tier = (self.tier_for)(pr, worktree.clone());
+        // The outcome only says whether CI went green, which it cannot without
+        // a push; the recorded diffs are the result.
+        tier.attempt(run_id).await?;
+        Ok(worktree.suggestions())
+    }
+}
+
+/// Render a consolidation plan as a PR comment.
+pub fn plan_comment(plan: &ConsolidationPlan) -> String {
+    let mut body = format!(
+        "**Ampel remediation plan** (suggest-only, nothing will be pushed)\n\n\
+         These {} pull requests would be consolidated into one, in this order:\n\n",
+        plan.pr_count
+    );
+    for (i, pr) in plan.would_select.iter().enumerate() {
+        body.push_str(&format!(
+            "{}. #{} {} (`{}`)\n",
+            i + 1,
+            pr.number,
+            pr.title,
+            pr.branch
+        ));
+    }
+    if !plan.predicted_conflicts.is_empty() {
+        body.push_str("\nPredicted conflicts:\n\n");
+        for conflict in &plan.predicted_conflicts {
+            body.push_str(&format!("- `{conflict}`\n"));
+        }
+    }
+    body.push_str(&format!(
+        "\nEstimated duration: {}s.",
+        plan.estimated_duration_secs
+    ));
+    if plan.blocked_by_air_gap {
+        body.push_str(" Air-gapping blocks the external model for this repository.");
+    }
+    body
+}
+
+/// What a suggest-only publish posted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SuggestionSummary {
+    /// PRs that received the plan comment.
+    pub plan_comments: usize,
+    /// Suggestions posted, across all PRs.
+    pub suggestions: usize,
+}
+
+/// Posts a suggest-only run's plan and fixes on its PRs.
+pub struct SuggestOnlyPublisher {
+    provider: Arc<dyn RemediationProvider>,
+    plan: ConsolidationPlan,
+    /// `None` when the tier or runtime does not allow model-driven fixes: only
+    /// the plan is posted.
+    proposer: Option<Arc<dyn FixProposer>>,
+}
+
+impl SuggestOnlyPublisher {
+    pub fn new(provider: Arc<dyn RemediationProvider>, plan: ConsolidationPlan) -> Self {
+        Self {
+            provider,
+            plan,
+            proposer: None,
+        }
+    }
+
+    /// Also propose agent fixes for each PR.
+    pub fn with_proposer(mut self, proposer: Arc<dyn FixProposer>) -> Self {
+        self.proposer = Some(proposer);
+        self
+    }
+
+    pub fn plan(&self) -> &ConsolidationPlan {
+        &self.plan
+    }
+
+    /// Post the plan on every PR it selects, then each PR's proposed fix.
+    /// Errors with the first fix that could not be posted.
+    pub async fn publish(&self, run_id: Uuid) -> AmpelResult<SuggestionSummary> {
+        let mut summary = SuggestionSummary::default();
+        let mut failed = None;
+        let comment = plan_comment(&self.plan);
+        for pr in &self.plan.would_select {
+            let pr_number = pr.number as i64;
+            match self
+                .provider
+                .comment_on_pull_request(pr_number, &comment)
+                .await
+            {
+                Ok(()) => summary.plan_comments += 1,
+                Err(e) => {
+                    tracing::warn!(%run_id, pr = pr.number, error = %e, "failed to post remediation plan")
+                }
+            }
+
+            let Some(proposer) = &self.proposer else {
+                continue;
+            };
+            let suggestions = match proposer.propose(run_id, pr).await {
+                Ok(suggestions) => suggestions,
+                Err(e) => {
+                    tracing::warn!(%run_id, pr = pr.number, error = %e, "agent could not propose a fix");
+                    continue;
+                }
+            };
+            if suggestions.is_empty() {
+                continue;
+            }
+            match self.provider.suggest_changes(pr_number, &suggestions).await {
+                Ok(()) => summary.suggestions += suggestions.len(),
+                Err(e) => {
+                    tracing::warn!(%run_id, pr = pr.number, error = %e, "failed to post suggested changes");
+                    failed.get_or_insert(AmpelError::ProviderError(format!(
+                        "failed to post suggested changes on PR #{}: {e}",
+                        pr.number
+                    )));
+                }
+            }
+        }
+        match failed {
+            Some(e) => Err(e),
+            None => Ok(summary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(prs: &[(i32, &str)]) -> ConsolidationPlan {
+        ConsolidationPlan::from_selection(
+            prs.iter()
+                .map(|(number, title)| PrRef {
+                    number: *number,
+                    title: title.to_string(),
+                    branch: format!("dependabot/{number}"),
+                })
+                .collect(),
+            false,
+        )
+    }
+
+    #[test]
+    fn should_list_plan_prs_in_selection_order() {
+        let body = plan_comment(&plan(&[(7, "Bump serde"), (3, "Bump tokio")]));
+        let serde = body.find("1. #7 Bump serde (`dependabot/7`)").unwrap();
+        let tokio = body.find("2. #3 Bump tokio (`dependabot/3`)").unwrap();
+        assert!(serde < tokio);
+        assert!(body.contains("These 2 pull requests"));
+        assert!(!body.contains("Predicted conflicts"));
+    }
+
+    #[tokio::test]
+    async fn should_turn_recorded_diffs_into_suggestions() {
+        // Arrange
+        let worktree = SuggestionWorktree::new();
+        let diff = "--- a/a.txt\n+++ b/a.txt\n@@ -2 +2 @@\n-old\n+new\n";
+
+        // Act
+        worktree
+            .apply_output(
+                "feature",
+                &NormalizedProviderOutput::UnifiedDiff(diff.into()),
+            )
+            .await
+            .unwrap();
+        worktree.commit_and_push("feature", "msg").await.unwrap();
+
+        // Assert
+        let suggestions = worktree.suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].path, "a.txt");
+        assert_eq!(suggestions[0].replacement, "new");
+    }
+}
@@ -0,0 +1,121 @@
+//! Browser push delivery.
+//!
+//! The deployment's VAPID key pair is generated on first use and stored with
+//! its private key encrypted, so the API (which hands the public key to
+//! browsers) and the worker (which signs with the private key) share it.
+//! Subscriptions a push service reports as gone are deleted as they are found.
+
+use sea_orm::{DatabaseConnection, DbErr};
+use uuid::Uuid;
+
+use ampel_core::services::{VapidKeys, WebPushError, WebPushSender, WebPushSubscription};
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::push_subscription;
+use ampel_db::queries::{PushSubscriptionQueries, VapidKeyQueries};
+
+/// Contact for push service operators when `VAPID_SUBJECT` is not set
+const DEFAULT_VAPID_SUBJECT: &str = "http://localhost:3000";
+
+/// The deployment's VAPID keys, generating them if there are none yet
+pub async fn load_vapid_keys(
+    db: &DatabaseConnection,
+    encryption_service: &EncryptionService,
+) -> anyhow::Result<VapidKeys> {
+    let stored = match VapidKeyQueries::current(db).await? {
+        Some(stored) => stored,
+        None => {
+            let keys = VapidKeys::generate();
+            let encrypted = encryption_service.encrypt(&keys.private_key())?;
+            let stored =
+                VapidKeyQueries::insert_if_absent(db, keys.public_key(), encrypted).await?;
+            tracing::info!("Generated VAPID keys for browser push");
+            stored
+        }
+    };
+    let private_key = encryption_service.decrypt(&stored.private_key_encrypted)?;
+    Ok(VapidKeys::from_private_key(&private_key)?)
+}
+
+/// Replace the VAPID keys. Browsers subscribed with the old public key can no
+/// longer be sent to, so every subscription is deleted; browsers subscribe
+/// again the next time the user opens Ampel. Returns the new keys and how
+/// many subscriptions were deleted.
+///
+/// Used by `ampel-admin`; the bin never rotates, hence the allow.
+#[allow(dead_code)]
+pub async fn rotate_vapid_keys(
+    db: &DatabaseConnection,
+    encryption_service: &EncryptionService,
+) -> anyhow::Result<(VapidKeys, u64)> {
+    let keys = VapidKeys::generate();
+    let encrypted = encryption_service.encrypt(&keys.private_key())?;
+    VapidKeyQueries::replace(db, keys.public_key(), encrypted).await?;
+    let deleted = PushSubscriptionQueries::delete_all(db).await?;
+    Ok((keys, deleted))
+}
+
+/// The `mailto:` or `https:` contact sent with push requests: `VAPID_SUBJECT`,
+/// else `APP_URL`
+pub fn vapid_subject() -> String {
+    std::env::var("VAPID_SUBJECT")
+        .or_else(|_| std::env::var("APP_URL"))
+        .unwrap_or_else(|_| DEFAULT_VAPID_SUBJECT.to_string())
+}
+
+/// What happened when a message was pushed to a user's browsers
+#[derive(Debug, Default)]
+pub struct PushOutcome {
+    pub delivered: usize,
+    /// Subscriptions the push service reported as gone, now deleted
+    pub expired: usize,
+    pub errors: Vec<String>,
+}
+
+/// Push `payload` to every browser a user subscribed, deleting subscriptions
+/// that have expired.
+///
+/// Used by the API's test-send endpoint, hence the allow.
+#[allow(dead_code)]
+pub async fn push_to_user(
+    db: &DatabaseConnection,
+    sender: &WebPushSender,
+    user_id: Uuid,
+    payload: &[u8],
+) -> Result<PushOutcome, DbErr> {
+    let subscriptions = PushSubscriptionQueries::list_for_user(db, user_id).await?;
+    push_to_subscriptions(db, sender, &subscriptions, payload).await
+}
+
+/// Push `payload` to each of `subscriptions`
+pub async fn push_to_subscriptions(
+    db: &DatabaseConnection,
+    sender: &WebPushSender,
+    subscriptions: &[push_subscription::Model],
+    payload: &[u8],
+) -> Result<PushOutcome, DbErr> {
+    let mut outcome = PushOutcome::default();
+    for subscription in subscriptions {
+        let target = WebPushSubscription {
+            endpoint: subscription.endpoint.clone(),
+            p256dh: subscription.p256dh.clone(),
+            auth: subscription.auth.clone(),
+        };
+        match sender.send(&target, payload).await {
+            Ok(()) => {
+                PushSubscriptionQueries::touch(db, subscription.id).await?;
+                outcome.delivered += 1;
+            }
+            Err(WebPushError::Expired) => {
+                tracing::info!(
+                    subscription_id = %subscription.id,
+                    user_id = %subscription.user_id,
+                    "Deleting expired push subscription"
+                );
+                PushSubscriptionQueries::delete(db, subscription.id).await?;
+                outcome.expired += 1;
+            }
+            Err(e) => outcome.errors.push(e.to_string()),
+        }
+    }
+    Ok(outcome)
+}
@@ -0,0 +1,176 @@
+/// Integration tests for provider account credentials
+///
+/// These tests verify that stored credentials are resolved correctly:
+/// - An expiring OAuth token is refreshed and the rotated tokens are stored
+/// - A fresh OAuth token is used without calling the provider
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use ampel_core::models::GitProvider;
+use ampel_db::entities::provider_account;
+use ampel_db::queries::ProviderAccountQueries;
+use ampel_providers::traits::{OAuthClient, ProviderCredentials};
+use ampel_providers::ProviderFactory;
+use ampel_worker::services::account_credentials::{
+    account_credentials, decrypt_credentials, encrypt_credentials,
+};
+use chrono::{DateTime, Duration, Utc};
+use common::{create_test_encryption_service, create_test_user, TestDb};
+use sea_orm::{ActiveModelTrait, Set};
+use uuid::Uuid;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn create_oauth_account(
+    db: &sea_orm::DatabaseConnection,
+    user_id: Uuid,
+    instance_url: String,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<provider_account::Model> {
+    let stored = encrypt_credentials(
+        &create_test_encryption_service(),
+        &ProviderCredentials::OAuth {
+            access_token: "old_access".to_string(),
+            refresh_token: Some("old_refresh".to_string()),
+            expires_at: Some(expires_at),
+        },
+    )?;
+
+    let now = Utc::now();
+    let account = provider_account::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider: Set("gitlab".to_string()),
+        instance_url: Set(Some(instance_url)),
+        account_label: Set("work".to_string()),
+        provider_user_id: Set("1".to_string()),
+        provider_username: Set("tester".to_string()),
+        provider_email: Set(None),
+        avatar_url: Set(None),
+        auth_type: Set(stored.auth_type.to_string()),
+        access_token_encrypted: Set(stored.access_token_encrypted),
+        auth_username: Set(None),
+        scopes: Set(None),
+        token_expires_at: Set(stored.token_expires_at),
+        last_validated_at: Set(Some(now)),
+        validation_status: Set("valid".to_string()),
+        is_active: Set(true),
+        is_default: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    Ok(account.insert(db).await?)
+}
+
+fn factory() -> ProviderFactory {
+    ProviderFactory::new().with_oauth_client(
+        GitProvider::GitLab,
+        OAuthClient {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_expiring_oauth_token_is_refreshed_and_stored() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("refresh_token=old_refresh"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "new_access",
+            "refresh_token": "new_refresh",
+            "expires_in": 7200
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account =
+        create_oauth_account(db, user.id, server.uri(), Utc::now() + Duration::minutes(1))
+            .await
+            .expect("Failed to create account");
+    let encryption_service = create_test_encryption_service();
+
+    let credentials = account_credentials(db, &encryption_service, &factory(), &account)
+        .await
+        .expect("Credentials should resolve");
+
+    let ProviderCredentials::OAuth { access_token, .. } = credentials else {
+        panic!("expected OAuth credentials");
+    };
+    assert_eq!(access_token, "new_access");
+
+    let stored = ProviderAccountQueries::find_by_id(db, account.id)
+        .await
+        .expect("Failed to load account")
+        .expect("Account exists");
+    assert!(stored.token_expires_at.unwrap() > Utc::now() + Duration::hours(1));
+    let ProviderCredentials::OAuth { refresh_token, .. } =
+        decrypt_credentials(&encryption_service, &stored).expect("Failed to decrypt")
+    else {
+        panic!("expected OAuth credentials");
+    };
+    assert_eq!(refresh_token.as_deref(), Some("new_refresh"));
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_fresh_oauth_token_is_used_as_is() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_oauth_account(db, user.id, server.uri(), Utc::now() + Duration::hours(2))
+        .await
+        .expect("Failed to create account");
+
+    let credentials =
+        account_credentials(db, &create_test_encryption_service(), &factory(), &account)
+            .await
+            .expect("Credentials should resolve");
+
+    assert!(matches!(
+        credentials,
+        ProviderCredentials::OAuth { ref access_token, .. } if access_token == "old_access"
+    ));
+
+    test_db.cleanup().await;
+}
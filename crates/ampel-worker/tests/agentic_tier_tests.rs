@@ -6,7 +6,7 @@
 //! `remediation_agent_session` row — against in-memory SQLite. The model HTTP is
 //! never touched (a scripted mock + fake worktree/verifier stand in).
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use ampel_core::errors::AmpelResult;
 use ampel_core::remediation::{
@@ -158,6 +158,22 @@ impl CiVerifier for RedVerifier {
     }
 }
 
+/// Verifier that replays a scripted sequence of statuses, one per call.
+struct ScriptedVerifier(Mutex<Vec<VerificationStatus>>);
+
+impl ScriptedVerifier {
+    fn new(statuses: Vec<VerificationStatus>) -> Self {
+        Self(Mutex::new(statuses))
+    }
+}
+
+#[async_trait]
+impl CiVerifier for ScriptedVerifier {
+    async fn verify(&self, _worktree_ref: &str) -> AmpelResult<VerificationStatus> {
+        Ok(self.0.lock().unwrap().remove(0))
+    }
+}
+
 fn local_caps() -> ModelCaps {
     ModelCaps {
         kind: ModelKind::Inference,
@@ -518,3 +534,109 @@ async fn should_select_only_in_tenant_scope() {
     assert_eq!(sessions.len(), 1);
     assert_eq!(sessions[0].model_provider_account_id, Some(account_b));
 }
+
+#[tokio::test]
+async fn should_fetch_fresh_logs_when_none_are_supplied() {
+    // Arrange: no failing logs up front — CI is red on the first poll (the logs
+    // the agent starts from), then green after the agent's push.
+    let db = sqlite().await;
+    seed_ollama_account(&db).await;
+    let run_id = Uuid::new_v4();
+
+    let provider = Arc::new(MockModelProvider::new(local_caps()).with_response(diff_response()));
+    let verifier = ScriptedVerifier::new(vec![
+        VerificationStatus {
+            green: false,
+            logs: "error[E0432]: unresolved import".into(),
+        },
+        VerificationStatus {
+            green: true,
+            logs: String::new(),
+        },
+    ]);
+    let tier = DbAgenticTier::new(
+        db.clone(),
+        Arc::new(EncryptionService::new(&[7u8; 32])),
+        Arc::new(HeuristicClassifier),
+        Arc::new(OkWorktree),
+        Arc::new(verifier),
+        false,
+        run_ctx(),
+        "ampel/remediation/run",
+        "",
+    )
+    .with_provider_override(provider);
+
+    // Act
+    let outcome = tier.attempt(run_id).await.unwrap();
+
+    // Assert: the fetched logs drove classification and the loop recovered.
+    assert_eq!(outcome, AgentTierOutcome::Recovered);
+    let sessions = remediation_agent_session::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].failure_class.as_deref(), Some("build_error"));
+}
+
+#[tokio::test]
+async fn should_skip_the_model_when_ci_is_already_green() {
+    // Arrange: CI finished green between the executor's verify and the attempt.
+    let db = sqlite().await;
+    seed_ollama_account(&db).await;
+
+    let tier = DbAgenticTier::new(
+        db.clone(),
+        Arc::new(EncryptionService::new(&[7u8; 32])),
+        Arc::new(HeuristicClassifier),
+        Arc::new(OkWorktree),
+        Arc::new(GreenVerifier),
+        false,
+        run_ctx(),
+        "ampel/remediation/run",
+        "",
+    )
+    .with_provider_override(Arc::new(MockModelProvider::new(local_caps())));
+
+    // Act
+    let outcome = tier.attempt(Uuid::new_v4()).await.unwrap();
+
+    // Assert: recovered without a model session.
+    assert_eq!(outcome, AgentTierOutcome::Recovered);
+    let sessions = remediation_agent_session::Entity::find()
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(sessions.is_empty());
+}
+
+#[tokio::test]
+async fn should_hand_off_when_no_account_is_in_scope() {
+    // Arrange: the only account belongs to another user.
+    let db = sqlite().await;
+    seed_account(&db, None, Some(Uuid::new_v4()), true, None, "0").await;
+
+    let tier = DbAgenticTier::new(
+        db.clone(),
+        Arc::new(EncryptionService::new(&[7u8; 32])),
+        Arc::new(HeuristicClassifier),
+        Arc::new(OkWorktree),
+        Arc::new(GreenVerifier),
+        false,
+        run_ctx(),
+        "worktree-1",
+        "error[E0432]: build failed",
+    )
+    .with_provider_override(Arc::new(MockModelProvider::new(local_caps())))
+    .with_account_scope(AccountScope {
+        organization_id: None,
+        user_id: Some(Uuid::new_v4()),
+    });
+
+    // Act
+    let outcome = tier.attempt(Uuid::new_v4()).await.unwrap();
+
+    // Assert: exhausted (→ human handoff) rather than an error failing the run.
+    assert_eq!(outcome, AgentTierOutcome::Exhausted);
+}
@@ -0,0 +1,358 @@
+/// Integration tests for the auto_merge job
+///
+/// These tests verify that the auto-merge job correctly:
+/// - Merges bot PRs that satisfy the repository's rule
+/// - Leaves human and not-yet-ready PRs alone
+/// - Records merges and held PRs as an `auto_merge` merge operation
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use ampel_core::models::GitProvider;
+use ampel_db::entities::{auto_merge_rule, pull_request, repository};
+use ampel_db::queries::{
+    CICheckQueries, MergeOperationItemQueries, MergeOperationQueries, PrQueries,
+};
+use ampel_providers::traits::ProviderCredentials;
+use ampel_worker::jobs::auto_merge::{AutoMergeJob, AUTO_MERGE_TRIGGER};
+use chrono::Utc;
+use common::{
+    create_test_pr, create_test_provider_account, create_test_user, MockProvider, TestDb,
+};
+use sea_orm::{ActiveModelTrait, Set};
+use uuid::Uuid;
+
+async fn create_test_repository(
+    db: &sea_orm::DatabaseConnection,
+    user_id: Uuid,
+    provider_account_id: Uuid,
+) -> anyhow::Result<repository::Model> {
+    let now = Utc::now();
+    let repo = repository::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider_account_id: Set(Some(provider_account_id)),
+        provider: Set("github".to_string()),
+        provider_id: Set("repo_auto_merge".to_string()),
+        owner: Set("testowner".to_string()),
+        name: Set("auto-merge".to_string()),
+        full_name: Set("testowner/auto-merge".to_string()),
+        description: Set(None),
+        url: Set("https://github.com/testowner/auto-merge".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        group_id: Set(None),
+        last_polled_at: Set(None),
+        poll_interval_seconds: Set(300),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    Ok(repo.insert(db).await?)
+}
+
+async fn create_rule(
+    db: &sea_orm::DatabaseConnection,
+    repository_id: Uuid,
+) -> anyhow::Result<auto_merge_rule::Model> {
+    let now = Utc::now();
+    let rule = auto_merge_rule::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        repository_id: Set(repository_id),
+        enabled: Set(true),
+        bot_authors: Set(r#"["dependabot[bot]"]"#.to_string()),
+        require_all_checks: Set(true),
+        require_approval: Set(false),
+        merge_strategy: Set("squash".to_string()),
+        delete_branch: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    Ok(rule.insert(db).await?)
+}
+
+/// Insert an open PR authored by `author` with a single CI check
+async fn create_pr_with_check(
+    db: &sea_orm::DatabaseConnection,
+    repo: &repository::Model,
+    number: i32,
+    author: &str,
+    check_status: &str,
+    check_conclusion: Option<&str>,
+) -> anyhow::Result<pull_request::Model> {
+    let mut pr = create_test_pr(number, &format!("PR {}", number), "open");
+    pr.author = author.to_string();
+
+    let model = PrQueries::upsert(
+        db,
+        repo.id,
+        "github".to_string(),
+        pr.provider_id,
+        pr.number,
+        pr.title,
+        pr.description,
+        pr.url,
+        pr.state,
+        pr.source_branch,
+        pr.target_branch,
+        pr.author,
+        pr.author_avatar_url,
+        pr.is_draft,
+        pr.is_mergeable,
+        pr.has_conflicts,
+        pr.additions,
+        pr.deletions,
+        pr.changed_files,
+        pr.commits_count,
+        pr.comments_count,
+        pr.created_at,
+        pr.updated_at,
+        pr.merged_at,
+        pr.closed_at,
+        None,
+        None,
+    )
+    .await?;
+
+    CICheckQueries::upsert(
+        db,
+        model.id,
+        "build".to_string(),
+        check_status.to_string(),
+        check_conclusion.map(|c| c.to_string()),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(model)
+}
+
+fn credentials() -> ProviderCredentials {
+    ProviderCredentials::Pat {
+        token: "test_token".to_string(),
+        username: None,
+    }
+}
+
+#[tokio::test]
+async fn test_auto_merge_merges_ready_bot_pr_and_records_operation() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id)
+        .await
+        .expect("Failed to create repo");
+    let rule = create_rule(db, repo.id)
+        .await
+        .expect("Failed to create rule");
+
+    let bot_pr = create_pr_with_check(
+        db,
+        &repo,
+        1,
+        "dependabot[bot]",
+        "completed",
+        Some("success"),
+    )
+    .await
+    .expect("Failed to create bot PR");
+    create_pr_with_check(db, &repo, 2, "dependabot[bot]", "in_progress", None)
+        .await
+        .expect("Failed to create pending PR");
+    create_pr_with_check(db, &repo, 3, "alice", "completed", Some("success"))
+        .await
+        .expect("Failed to create human PR");
+
+    // The pre-flight check returns the first configured PR, which is open
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "PR 1", "open"));
+
+    AutoMergeJob
+        .process_repository(db, &provider, &credentials(), &repo, &rule)
+        .await
+        .expect("Auto-merge should succeed");
+
+    let merges = provider
+        .get_call_log()
+        .iter()
+        .filter(|c| *c == "merge_pull_request")
+        .count();
+    assert_eq!(merges, 1, "Only the green bot PR should be merged");
+
+    let merged = PrQueries::find_by_id(db, bot_pr.id)
+        .await
+        .expect("Failed to load PR")
+        .expect("PR exists");
+    assert_eq!(merged.state, "merged");
+
+    let operations = MergeOperationQueries::find_by_user(db, user.id, 10)
+        .await
+        .expect("Failed to list operations");
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0].trigger, AUTO_MERGE_TRIGGER);
+    assert_eq!(operations[0].success_count, 1);
+    assert_eq!(operations[0].skipped_count, 0);
+
+    // The merge, plus each held bot PR with its reason; the human PR is
+    // outside the rule and leaves no item
+    let items = MergeOperationItemQueries::find_by_operation(db, operations[0].id)
+        .await
+        .expect("Failed to list items");
+    let mut recorded: Vec<(&str, Option<&str>)> = items
+        .iter()
+        .map(|i| (i.status.as_str(), i.error_message.as_deref()))
+        .collect();
+    recorded.sort();
+    assert_eq!(
+        recorded,
+        vec![("success", None), ("waiting", Some("CI checks pending")),]
+    );
+    assert_eq!(items[0].pull_request_id, bot_pr.id);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_auto_merge_records_skip_for_pr_blocked_by_rule_once() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id)
+        .await
+        .expect("Failed to create repo");
+    let rule = create_rule(db, repo.id)
+        .await
+        .expect("Failed to create rule");
+
+    let failing = create_pr_with_check(
+        db,
+        &repo,
+        1,
+        "dependabot[bot]",
+        "completed",
+        Some("failure"),
+    )
+    .await
+    .expect("Failed to create failing PR");
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+
+    // Two runs with the same decision
+    for _ in 0..2 {
+        AutoMergeJob
+            .process_repository(db, &provider, &credentials(), &repo, &rule)
+            .await
+            .expect("Auto-merge should succeed");
+    }
+
+    assert!(
+        provider.get_call_log().is_empty(),
+        "No provider calls expected"
+    );
+    let operations = MergeOperationQueries::find_by_user(db, user.id, 10)
+        .await
+        .expect("Failed to list operations");
+    assert_eq!(operations.len(), 1, "An unchanged skip is recorded once");
+    assert_eq!(operations[0].trigger, AUTO_MERGE_TRIGGER);
+    assert_eq!(operations[0].success_count, 0);
+    assert_eq!(operations[0].skipped_count, 1);
+    assert_eq!(operations[0].status, "completed");
+
+    let items = MergeOperationItemQueries::find_by_operation(db, operations[0].id)
+        .await
+        .expect("Failed to list items");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].pull_request_id, failing.id);
+    assert_eq!(items[0].status, "skipped");
+    assert_eq!(items[0].error_message.as_deref(), Some("status is red"));
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_auto_merge_records_nothing_for_prs_outside_rule() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id)
+        .await
+        .expect("Failed to create repo");
+    let rule = create_rule(db, repo.id)
+        .await
+        .expect("Failed to create rule");
+
+    create_pr_with_check(db, &repo, 1, "alice", "completed", Some("success"))
+        .await
+        .expect("Failed to create human PR");
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+
+    for _ in 0..2 {
+        AutoMergeJob
+            .process_repository(db, &provider, &credentials(), &repo, &rule)
+            .await
+            .expect("Auto-merge should succeed");
+    }
+
+    assert!(
+        provider.get_call_log().is_empty(),
+        "No provider calls expected"
+    );
+    let operations = MergeOperationQueries::find_by_user(db, user.id, 10)
+        .await
+        .expect("Failed to list operations");
+    assert!(
+        operations.is_empty(),
+        "A PR the rule doesn't target leaves no history"
+    );
+
+    test_db.cleanup().await;
+}
@@ -5,12 +5,14 @@
 /// - Leaves recent closed PRs untouched
 /// - Leaves open PRs untouched
 /// - Respects the 30-day cutoff period
+/// - Deletes expired refresh tokens
 ///
 /// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
 /// features. Tests are automatically skipped when running in SQLite mode.
 mod common;
 
-use ampel_db::entities::{provider_account, pull_request, repository};
+use ampel_db::entities::{provider_account, pull_request, refresh_token, repository};
+use ampel_db::queries::{NewRefreshToken, RefreshTokenQueries};
 use ampel_worker::jobs::cleanup::CleanupJob;
 use chrono::{Duration, Utc};
 use common::{create_test_encryption_service, create_test_user, TestDb};
@@ -85,6 +87,10 @@ async fn create_test_pull_request(
         merged_at: Set(None),
         closed_at: Set(closed_at),
         last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     };
 
     Ok(pr.insert(db).await?)
@@ -532,3 +538,63 @@ async fn test_cleanup_multiple_prs_mixed() {
 
     test_db.cleanup().await;
 }
+
+#[tokio::test]
+async fn test_cleanup_deletes_expired_refresh_tokens() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "cleanup6@example.com", "cleanupuser6")
+        .await
+        .expect("Failed to create user");
+
+    let now = Utc::now();
+    let mut token_ids = Vec::new();
+    for (hash, expires_at) in [
+        ("expired", now - Duration::days(1)),
+        ("live", now + Duration::days(6)),
+    ] {
+        let token = RefreshTokenQueries::create(
+            db,
+            NewRefreshToken {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                family_id: Uuid::new_v4(),
+                token_hash: hash.to_string(),
+                user_agent: None,
+                session_started_at: now - Duration::days(8),
+                expires_at,
+            },
+        )
+        .await
+        .expect("Failed to create refresh token");
+        token_ids.push(token.id);
+    }
+
+    CleanupJob
+        .execute(db)
+        .await
+        .expect("Cleanup should succeed");
+
+    let expired_after = refresh_token::Entity::find_by_id(token_ids[0])
+        .one(db)
+        .await
+        .expect("Failed to query");
+    assert!(expired_after.is_none(), "Expired token should be deleted");
+
+    let live_after = refresh_token::Entity::find_by_id(token_ids[1])
+        .one(db)
+        .await
+        .expect("Failed to query");
+    assert!(live_after.is_some(), "Live token should be kept");
+
+    test_db.cleanup().await;
+}
@@ -247,6 +247,7 @@ pub async fn create_test_provider_account(
 }
 
 /// Create a test encryption service with a predictable key
+#[allow(dead_code)]
 pub fn create_test_encryption_service() -> EncryptionService {
     // Create a deterministic test key (32 bytes)
     let mut key = [0u8; 32];
@@ -502,6 +503,7 @@ pub fn create_test_pr(
         state: state.to_string(),
         source_branch: "feature-branch".to_string(),
         target_branch: "main".to_string(),
+        head_sha: None,
         author: "testauthor".to_string(),
         author_avatar_url: Some("https://example.com/avatar.png".to_string()),
         is_draft: false,
@@ -516,6 +518,10 @@ pub fn create_test_pr(
         updated_at: Utc::now(),
         merged_at: None,
         closed_at: None,
+        requested_reviewers: Vec::new(),
+        labels: Vec::new(),
+        assignees: Vec::new(),
+        milestone: None,
     }
 }
 
@@ -549,6 +555,7 @@ pub fn create_test_review(reviewer: &str, state: &str) -> ampel_providers::trait
         reviewer: reviewer.to_string(),
         reviewer_avatar_url: Some("https://example.com/avatar.png".to_string()),
         state: state.to_string(),
+        commit_sha: None,
         body: Some("Looks good to me".to_string()),
         submitted_at: Utc::now(),
     }
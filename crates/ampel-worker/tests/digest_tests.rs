@@ -0,0 +1,316 @@
+/// Integration tests for digest emails
+///
+/// These tests verify that:
+/// - The digest job summarizes ready, stale and newly red PRs, merges and
+///   health score changes for the period that last ended
+/// - A digest is queued once per period however often the job runs
+/// - Users without digests enabled, or with nothing to report, get none
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use ampel_core::models::{Digest, DigestFrequency};
+use ampel_db::entities::{health_score, notification_preferences, pull_request, repository, user};
+use ampel_db::queries::NotificationLogQueries;
+use ampel_worker::jobs::digest::DigestJob;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use common::{create_test_user, TestDb};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use uuid::Uuid;
+
+async fn create_test_repository(db: &DatabaseConnection, user_id: Uuid) -> repository::Model {
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider_account_id: Set(None),
+        provider: Set("github".to_string()),
+        provider_id: Set("repo_digest".to_string()),
+        owner: Set("testowner".to_string()),
+        name: Set("digest".to_string()),
+        full_name: Set("testowner/digest".to_string()),
+        description: Set(None),
+        url: Set("https://github.com/testowner/digest".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        group_id: Set(None),
+        last_polled_at: Set(None),
+        poll_interval_seconds: Set(300),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create repo")
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_pr(
+    db: &DatabaseConnection,
+    repo_id: Uuid,
+    number: i32,
+    state: &str,
+    created_at: DateTime<Utc>,
+    merged_at: Option<DateTime<Utc>>,
+    ampel_status: Option<&str>,
+    ampel_status_changed_at: Option<DateTime<Utc>>,
+) {
+    pull_request::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        repository_id: Set(repo_id),
+        provider: Set("github".to_string()),
+        provider_id: Set(format!("pr_{}", number)),
+        number: Set(number),
+        title: Set(format!("Test PR #{}", number)),
+        description: Set(None),
+        url: Set(format!(
+            "https://github.com/testowner/digest/pull/{}",
+            number
+        )),
+        state: Set(state.to_string()),
+        source_branch: Set("feature".to_string()),
+        target_branch: Set("main".to_string()),
+        author: Set("testauthor".to_string()),
+        author_avatar_url: Set(None),
+        is_draft: Set(false),
+        is_mergeable: Set(Some(true)),
+        has_conflicts: Set(false),
+        additions: Set(10),
+        deletions: Set(5),
+        changed_files: Set(1),
+        commits_count: Set(1),
+        comments_count: Set(0),
+        created_at: Set(created_at),
+        updated_at: Set(created_at),
+        merged_at: Set(merged_at),
+        closed_at: Set(merged_at),
+        last_synced_at: Set(created_at),
+        ampel_status: Set(ampel_status.map(str::to_string)),
+        ampel_status_changed_at: Set(ampel_status_changed_at),
+        head_sha: Set(None),
+        milestone: Set(None),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create PR");
+}
+
+async fn create_health_score(
+    db: &DatabaseConnection,
+    repo_id: Uuid,
+    score: i32,
+    calculated_at: DateTime<Utc>,
+) {
+    health_score::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        repository_id: Set(repo_id),
+        score: Set(score),
+        avg_time_to_merge: Set(None),
+        avg_review_time: Set(None),
+        stale_pr_count: Set(None),
+        failed_check_rate: Set(None),
+        pr_throughput: Set(None),
+        calculated_at: Set(calculated_at),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create health score");
+}
+
+/// Preferences with email configured and the given digest frequency
+async fn create_email_preferences(db: &DatabaseConnection, user_id: Uuid, frequency: &str) {
+    notification_preferences::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        email_enabled: Set(true),
+        slack_enabled: Set(false),
+        slack_webhook_url: Set(None),
+        push_enabled: Set(false),
+        notify_on_pr_ready: Set(true),
+        notify_on_pr_failed: Set(true),
+        notify_on_review_requested: Set(true),
+        digest_frequency: Set(frequency.to_string()),
+        updated_at: Set(Utc::now()),
+        smtp_host: Set(Some("smtp.example.com".to_string())),
+        smtp_port: Set(Some(587)),
+        smtp_username: Set(Some("ampel".to_string())),
+        smtp_password_encrypted: Set(None),
+        smtp_from_email: Set(Some("ampel@example.com".to_string())),
+        smtp_to_emails: Set(Some(r#"["team@example.com"]"#.to_string())),
+        smtp_use_tls: Set(true),
+        notify_on_merge_success: Set(true),
+        notify_on_merge_failure: Set(true),
+        slack_channel: Set(None),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create preferences");
+}
+
+#[tokio::test]
+async fn test_daily_digest_is_queued_once_per_period() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let owner = create_test_user(db, "owner@example.com", "owner")
+        .await
+        .expect("Failed to create user");
+    let mut german: user::ActiveModel = owner.clone().into();
+    german.language = Set(Some("de".to_string()));
+    german.update(db).await.expect("Failed to update user");
+    create_email_preferences(db, owner.id, "daily").await;
+    let repo = create_test_repository(db, owner.id).await;
+
+    // The job runs on 2026-07-08, so the digest covers 2026-07-07
+    let now = Utc.with_ymd_and_hms(2026, 7, 8, 0, 10, 0).unwrap();
+    let yesterday = Utc.with_ymd_and_hms(2026, 7, 7, 12, 0, 0).unwrap();
+
+    create_pr(db, repo.id, 1, "open", yesterday, None, Some("green"), None).await;
+    create_pr(
+        db,
+        repo.id,
+        2,
+        "open",
+        now - Duration::days(10),
+        None,
+        Some("yellow"),
+        None,
+    )
+    .await;
+    create_pr(
+        db,
+        repo.id,
+        3,
+        "open",
+        yesterday,
+        None,
+        Some("red"),
+        Some(yesterday),
+    )
+    .await;
+    // Turned red before the period, so it is not newly red
+    create_pr(
+        db,
+        repo.id,
+        4,
+        "open",
+        yesterday,
+        None,
+        Some("red"),
+        Some(now - Duration::days(3)),
+    )
+    .await;
+    create_pr(
+        db,
+        repo.id,
+        5,
+        "merged",
+        now - Duration::days(2),
+        Some(yesterday),
+        None,
+        None,
+    )
+    .await;
+    create_health_score(db, repo.id, 80, now - Duration::days(2)).await;
+    create_health_score(db, repo.id, 72, yesterday).await;
+
+    DigestJob.execute_at(db, now).await.expect("Digest failed");
+    DigestJob
+        .execute_at(db, now + Duration::hours(1))
+        .await
+        .expect("Digest failed");
+
+    let log = NotificationLogQueries::list_for_user(db, owner.id, 10)
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 1, "a period must only be queued once");
+    assert_eq!(log[0].kind, "digest");
+    assert_eq!(log[0].channel, "email");
+    assert_eq!(log[0].dedup_key, "digest:daily:2026-07-07");
+
+    let digest: Digest = serde_json::from_str(&log[0].payload).unwrap();
+    assert_eq!(digest.frequency, DigestFrequency::Daily);
+    assert_eq!(digest.locale, "de");
+    let numbers = |prs: &[ampel_core::models::DigestPullRequest]| {
+        prs.iter().map(|pr| pr.number).collect::<Vec<_>>()
+    };
+    assert_eq!(numbers(&digest.ready_to_merge), vec![1]);
+    assert_eq!(numbers(&digest.stale), vec![2]);
+    assert_eq!(numbers(&digest.newly_red), vec![3]);
+    assert_eq!(digest.merged_count, 1);
+    assert_eq!(digest.health_changes.len(), 1);
+    assert_eq!(digest.health_changes[0].previous, 80);
+    assert_eq!(digest.health_changes[0].current, 72);
+
+    // The next day gets its own digest
+    DigestJob
+        .execute_at(db, now + Duration::days(1))
+        .await
+        .expect("Digest failed");
+    let log = NotificationLogQueries::list_for_user(db, owner.id, 10)
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 2);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_digest_skips_unsubscribed_and_empty() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let now = Utc.with_ymd_and_hms(2026, 7, 8, 0, 10, 0).unwrap();
+
+    // Has activity but no digest
+    let unsubscribed = create_test_user(db, "none@example.com", "none")
+        .await
+        .expect("Failed to create user");
+    create_email_preferences(db, unsubscribed.id, "none").await;
+    let repo = create_test_repository(db, unsubscribed.id).await;
+    create_pr(
+        db,
+        repo.id,
+        1,
+        "open",
+        now - Duration::days(1),
+        None,
+        Some("green"),
+        None,
+    )
+    .await;
+
+    // Subscribed, but nothing happened
+    let quiet = create_test_user(db, "quiet@example.com", "quiet")
+        .await
+        .expect("Failed to create user");
+    create_email_preferences(db, quiet.id, "weekly").await;
+
+    DigestJob.execute_at(db, now).await.expect("Digest failed");
+
+    for user_id in [unsubscribed.id, quiet.id] {
+        let log = NotificationLogQueries::list_for_user(db, user_id, 10)
+            .await
+            .unwrap();
+        assert!(log.is_empty());
+    }
+
+    test_db.cleanup().await;
+}
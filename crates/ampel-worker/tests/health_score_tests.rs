@@ -86,6 +86,10 @@ async fn create_test_pull_request(
         merged_at: Set(None),
         closed_at: Set(None),
         last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     };
 
     Ok(pr.insert(db).await?)
@@ -0,0 +1,181 @@
+/// Integration tests for live dashboard events
+///
+/// These tests verify that syncing publishes an event to the repository
+/// owner's dashboards when a PR opens, changes status or closes, and when one
+/// of its CI checks moves on, and that an unchanged sync publishes nothing.
+///
+/// Without Redis configured events go to the in-process bus, which the tests
+/// subscribe to. Other tests in this binary publish too, so events are picked
+/// out by user.
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use ampel_core::models::{AmpelStatus, DashboardEvent, GitProvider};
+use ampel_db::entities::repository;
+use ampel_providers::traits::ProviderCredentials;
+use ampel_worker::jobs::poll_repository::PollRepositoryJob;
+use ampel_worker::services::live_events;
+use chrono::Utc;
+use common::{
+    create_test_ci_check, create_test_pr, create_test_provider_account, create_test_review,
+    create_test_user, MockProvider, TestDb,
+};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+async fn create_test_repository(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    provider_account_id: Uuid,
+) -> repository::Model {
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider_account_id: Set(Some(provider_account_id)),
+        provider: Set("github".to_string()),
+        provider_id: Set("repo_live".to_string()),
+        owner: Set("testowner".to_string()),
+        name: Set("live".to_string()),
+        full_name: Set("testowner/live".to_string()),
+        description: Set(None),
+        url: Set("https://github.com/testowner/live".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        group_id: Set(None),
+        last_polled_at: Set(None),
+        poll_interval_seconds: Set(300),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create repo")
+}
+
+fn credentials() -> ProviderCredentials {
+    ProviderCredentials::Pat {
+        token: "test-token".to_string(),
+        username: None,
+    }
+}
+
+async fn poll(db: &DatabaseConnection, provider: &MockProvider, repo: &repository::Model) {
+    PollRepositoryJob::poll_with_provider(db, provider, &credentials(), repo)
+        .await
+        .expect("Failed to poll");
+}
+
+/// The events published for `user_id` since the last call
+fn drain(
+    events: &mut broadcast::Receiver<ampel_core::models::LiveEvent>,
+    user_id: Uuid,
+) -> Vec<DashboardEvent> {
+    let mut received = Vec::new();
+    while let Ok(live) = events.try_recv() {
+        if live.user_id == user_id {
+            received.push(live.event);
+        }
+    }
+    received
+}
+
+#[tokio::test]
+async fn test_sync_publishes_dashboard_changes() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "owner@example.com", "owner")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "owner", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id).await;
+    let mut events = live_events::subscribe();
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "Live PR", "open"));
+    provider.add_ci_check(create_test_ci_check("CI", "in_progress", None));
+    provider.add_review(create_test_review("reviewer", "approved"));
+
+    poll(db, &provider, &repo).await;
+    let received = drain(&mut events, user.id);
+    assert_eq!(received.len(), 2, "{received:?}");
+    assert!(matches!(
+        &received[0],
+        DashboardEvent::CiCheckChanged { name, previous_status: None, status, .. }
+            if name == "CI" && status == "in_progress"
+    ));
+    let DashboardEvent::PrOpened {
+        pull_request_id,
+        number,
+        title,
+        status,
+        ..
+    } = &received[1]
+    else {
+        panic!("expected the PR to open, got {:?}", received[1]);
+    };
+    assert_eq!((*number, title.as_str()), (1, "Live PR"));
+    assert_eq!(*status, AmpelStatus::Yellow);
+    let pr_id = *pull_request_id;
+
+    // Nothing changed, so nothing is published
+    poll(db, &provider, &repo).await;
+    assert!(drain(&mut events, user.id).is_empty());
+
+    *provider.ci_checks.lock().unwrap() =
+        vec![create_test_ci_check("CI", "completed", Some("success"))];
+    poll(db, &provider, &repo).await;
+    let received = drain(&mut events, user.id);
+    assert_eq!(
+        received,
+        vec![
+            DashboardEvent::CiCheckChanged {
+                pull_request_id: pr_id,
+                repository_id: repo.id,
+                number: 1,
+                name: "CI".to_string(),
+                previous_status: Some("in_progress".to_string()),
+                previous_conclusion: None,
+                status: "completed".to_string(),
+                conclusion: Some("success".to_string()),
+            },
+            DashboardEvent::PrStatusChanged {
+                pull_request_id: pr_id,
+                repository_id: repo.id,
+                number: 1,
+                previous: AmpelStatus::Yellow,
+                status: AmpelStatus::Green,
+            },
+        ]
+    );
+
+    // A PR the provider no longer lists as open is closed
+    provider.pull_requests.lock().unwrap().clear();
+    poll(db, &provider, &repo).await;
+    assert_eq!(
+        drain(&mut events, user.id),
+        vec![DashboardEvent::PrClosed {
+            pull_request_id: pr_id,
+            repository_id: repo.id,
+            number: 1,
+            state: "closed".to_string(),
+        }]
+    );
+
+    test_db.cleanup().await;
+}
@@ -0,0 +1,383 @@
+/// Integration tests for the merge_queue job
+///
+/// These tests verify that the merge queue correctly:
+/// - Claims each queued operation exactly once and reclaims abandoned ones
+/// - Re-checks a PR against the provider before merging it
+/// - Skips PRs that became unmergeable while queued
+/// - Skips PRs carrying one of the user's skip labels
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use ampel_core::models::{GitProvider, MergeRequest, MergeStrategy};
+use ampel_db::entities::{pull_request, repository};
+use ampel_db::queries::{
+    MergeOperationItemQueries, MergeOperationQueries, PrMetadataQueries, PrQueries,
+};
+use ampel_providers::traits::ProviderCredentials;
+use ampel_worker::jobs::merge_queue::MergeQueueJob;
+use chrono::{Duration, Utc};
+use common::{
+    create_test_ci_check, create_test_pr, create_test_provider_account, create_test_user,
+    MockProvider, TestDb,
+};
+use sea_orm::{ActiveModelTrait, Set};
+use uuid::Uuid;
+
+async fn create_test_repository(
+    db: &sea_orm::DatabaseConnection,
+    user_id: Uuid,
+    provider_account_id: Uuid,
+) -> anyhow::Result<repository::Model> {
+    let now = Utc::now();
+    let repo = repository::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider_account_id: Set(Some(provider_account_id)),
+        provider: Set("github".to_string()),
+        provider_id: Set("repo_merge_queue".to_string()),
+        owner: Set("testowner".to_string()),
+        name: Set("merge-queue".to_string()),
+        full_name: Set("testowner/merge-queue".to_string()),
+        description: Set(None),
+        url: Set("https://github.com/testowner/merge-queue".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        group_id: Set(None),
+        last_polled_at: Set(None),
+        poll_interval_seconds: Set(300),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    Ok(repo.insert(db).await?)
+}
+
+async fn create_open_pr(
+    db: &sea_orm::DatabaseConnection,
+    repo: &repository::Model,
+    number: i32,
+) -> anyhow::Result<pull_request::Model> {
+    let pr = create_test_pr(number, &format!("PR {}", number), "open");
+
+    Ok(PrQueries::upsert(
+        db,
+        repo.id,
+        "github".to_string(),
+        pr.provider_id,
+        pr.number,
+        pr.title,
+        pr.description,
+        pr.url,
+        pr.state,
+        pr.source_branch,
+        pr.target_branch,
+        pr.author,
+        pr.author_avatar_url,
+        pr.is_draft,
+        pr.is_mergeable,
+        pr.has_conflicts,
+        pr.additions,
+        pr.deletions,
+        pr.changed_files,
+        pr.commits_count,
+        pr.comments_count,
+        pr.created_at,
+        pr.updated_at,
+        pr.merged_at,
+        pr.closed_at,
+        None,
+        None,
+    )
+    .await?)
+}
+
+fn credentials() -> ProviderCredentials {
+    ProviderCredentials::Pat {
+        token: "test_token".to_string(),
+        username: None,
+    }
+}
+
+fn merge_request() -> MergeRequest {
+    MergeRequest {
+        strategy: MergeStrategy::Squash,
+        commit_title: None,
+        commit_message: None,
+        delete_branch: false,
+    }
+}
+
+#[tokio::test]
+async fn test_merge_queue_claims_once_and_reclaims_stale_operations() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let operation = MergeOperationQueries::enqueue(db, user.id, 0, "squash", false, 0)
+        .await
+        .expect("Failed to enqueue");
+    assert_eq!(operation.status, "queued");
+
+    let stale_before = Utc::now() - Duration::minutes(5);
+    let claimed = MergeOperationQueries::claim_next(db, stale_before)
+        .await
+        .expect("Failed to claim")
+        .expect("Queued operation should be claimed");
+    assert_eq!(claimed.id, operation.id);
+    assert_eq!(claimed.status, "in_progress");
+
+    let again = MergeOperationQueries::claim_next(db, stale_before)
+        .await
+        .expect("Failed to claim");
+    assert!(
+        again.is_none(),
+        "A live operation must not be claimed twice"
+    );
+
+    // Once the heartbeat is older than the lease, another worker takes over
+    let reclaimed = MergeOperationQueries::claim_next(db, Utc::now() + Duration::minutes(1))
+        .await
+        .expect("Failed to claim");
+    assert_eq!(reclaimed.map(|o| o.id), Some(operation.id));
+
+    // Cancelling a running operation only flags it for the worker
+    assert!(MergeOperationQueries::request_cancel(db, operation.id)
+        .await
+        .expect("Failed to cancel"));
+    let flagged = MergeOperationQueries::find_by_id(db, operation.id)
+        .await
+        .expect("Failed to load")
+        .expect("Operation exists");
+    assert_eq!(flagged.status, "in_progress");
+    assert!(flagged.cancel_requested);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_process_item_merges_open_green_pr() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id)
+        .await
+        .expect("Failed to create repo");
+    let pr = create_open_pr(db, &repo, 1)
+        .await
+        .expect("Failed to create PR");
+
+    let operation = MergeOperationQueries::enqueue(db, user.id, 1, "squash", false, 0)
+        .await
+        .expect("Failed to enqueue");
+    let item = MergeOperationItemQueries::create(db, operation.id, pr.id, repo.id, 0)
+        .await
+        .expect("Failed to create item");
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "PR 1", "open"));
+    provider.add_ci_check(create_test_ci_check("build", "completed", Some("success")));
+
+    let (status, message, sha) = MergeQueueJob
+        .process_item(
+            db,
+            &provider,
+            &credentials(),
+            &repo,
+            &pr,
+            &item,
+            &merge_request(),
+            &[],
+        )
+        .await
+        .expect("Processing should succeed");
+
+    assert_eq!(status, "success", "unexpected message: {:?}", message);
+    assert_eq!(sha.as_deref(), Some("abc123"));
+
+    let calls = provider.get_call_log();
+    assert_eq!(calls.first().map(String::as_str), Some("get_pull_request"));
+    assert!(calls.contains(&"get_ci_checks".to_string()));
+    assert!(calls.contains(&"merge_pull_request".to_string()));
+
+    let merged = PrQueries::find_by_id(db, pr.id)
+        .await
+        .expect("Failed to load PR")
+        .expect("PR exists");
+    assert_eq!(merged.state, "merged");
+
+    let items = MergeOperationItemQueries::find_by_operation(db, operation.id)
+        .await
+        .expect("Failed to list items");
+    assert_eq!(items[0].attempts, 1);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_process_item_skips_pr_that_gained_conflicts() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id)
+        .await
+        .expect("Failed to create repo");
+    let pr = create_open_pr(db, &repo, 1)
+        .await
+        .expect("Failed to create PR");
+
+    let operation = MergeOperationQueries::enqueue(db, user.id, 1, "squash", false, 0)
+        .await
+        .expect("Failed to enqueue");
+    let item = MergeOperationItemQueries::create(db, operation.id, pr.id, repo.id, 0)
+        .await
+        .expect("Failed to create item");
+
+    let mut conflicted = create_test_pr(1, "PR 1", "open");
+    conflicted.has_conflicts = true;
+    conflicted.is_mergeable = Some(false);
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(conflicted);
+
+    let (status, message, _) = MergeQueueJob
+        .process_item(
+            db,
+            &provider,
+            &credentials(),
+            &repo,
+            &pr,
+            &item,
+            &merge_request(),
+            &[],
+        )
+        .await
+        .expect("Processing should succeed");
+
+    assert_eq!(status, "skipped");
+    assert_eq!(message.as_deref(), Some("PR has merge conflicts"));
+    assert!(!provider
+        .get_call_log()
+        .contains(&"merge_pull_request".to_string()));
+
+    let stored = PrQueries::find_by_id(db, pr.id)
+        .await
+        .expect("Failed to load PR")
+        .expect("PR exists");
+    assert!(
+        stored.has_conflicts,
+        "Fresh provider state should be stored"
+    );
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_process_item_skips_pr_with_skip_label() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id)
+        .await
+        .expect("Failed to create repo");
+    let pr = create_open_pr(db, &repo, 1)
+        .await
+        .expect("Failed to create PR");
+
+    let operation = MergeOperationQueries::enqueue(db, user.id, 1, "squash", false, 0)
+        .await
+        .expect("Failed to enqueue");
+    let item = MergeOperationItemQueries::create(db, operation.id, pr.id, repo.id, 0)
+        .await
+        .expect("Failed to create item");
+
+    // Labelled on the provider after it was queued
+    let mut labelled = create_test_pr(1, "PR 1", "open");
+    labelled.labels = vec!["deps".to_string(), "WIP".to_string()];
+    labelled.assignees = vec!["testuser".to_string()];
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(labelled);
+    provider.add_ci_check(create_test_ci_check("build", "completed", Some("success")));
+
+    let (status, message, _) = MergeQueueJob
+        .process_item(
+            db,
+            &provider,
+            &credentials(),
+            &repo,
+            &pr,
+            &item,
+            &merge_request(),
+            &["do-not-merge".to_string(), "wip".to_string()],
+        )
+        .await
+        .expect("Processing should succeed");
+
+    assert_eq!(status, "skipped");
+    assert_eq!(message.as_deref(), Some("PR is labelled WIP"));
+    assert!(!provider
+        .get_call_log()
+        .contains(&"merge_pull_request".to_string()));
+
+    let mut metadata = PrMetadataQueries::for_pull_request(db, pr.id)
+        .await
+        .expect("Failed to load metadata");
+    metadata.labels.sort();
+    assert_eq!(metadata.labels, vec!["WIP", "deps"]);
+    assert_eq!(metadata.assignees, vec!["testuser"]);
+
+    test_db.cleanup().await;
+}
@@ -90,6 +90,10 @@ async fn create_test_pull_request(
         merged_at: Set(merged_at),
         closed_at: Set(merged_at),
         last_synced_at: Set(now),
+        ampel_status: Set(None),
+        ampel_status_changed_at: Set(None),
+        head_sha: Set(None),
+        milestone: Set(None),
     };
 
     Ok(pr.insert(db).await?)
@@ -111,6 +115,7 @@ async fn create_test_review(
         state: Set(state.to_string()),
         body: Set(Some("Review comment".to_string())),
         submitted_at: Set(submitted_at),
+        commit_sha: Set(None),
     };
 
     Ok(review.insert(db).await?)
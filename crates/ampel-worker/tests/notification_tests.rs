@@ -0,0 +1,609 @@
+/// Integration tests for PR event notifications
+///
+/// These tests verify that:
+/// - Syncing a PR records a status baseline, then queues a notification each
+///   time the PR turns red or green
+/// - Requested reviewers with an Ampel account are notified once per PR
+/// - The dispatch job delivers due notifications and retries failures with
+///   backoff until it gives up
+/// - Configured personal and team channels receive the kinds they subscribe
+///   to, in their own formats
+/// - Browser push notifications are encrypted and signed for every subscribed
+///   browser, and subscriptions the push service reports as gone are deleted
+///
+/// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
+/// features. Tests are automatically skipped when running in SQLite mode.
+mod common;
+
+use ampel_core::models::{
+    GitProvider, NotificationKind, PrNotification, NOTIFICATION_MAX_ATTEMPTS,
+};
+use ampel_core::services::{
+    ChannelConfig, VapidKeys, WEBHOOK_EVENT_HEADER, WEBHOOK_SIGNATURE_HEADER,
+};
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::{
+    notification_log, notification_preferences, organization, repository, team, team_member,
+};
+use ampel_db::queries::{
+    NewNotification, NewNotificationChannel, NewPushSubscription, NotificationChannelQueries,
+    NotificationLogQueries, PrQueries, PushSubscriptionQueries,
+};
+use ampel_providers::traits::ProviderCredentials;
+use ampel_worker::jobs::notification_dispatch::NotificationDispatchJob;
+use ampel_worker::jobs::poll_repository::PollRepositoryJob;
+use chrono::{Duration, Utc};
+use common::{
+    create_test_ci_check, create_test_encryption_service, create_test_pr,
+    create_test_provider_account, create_test_review, create_test_user, MockProvider, TestDb,
+};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, IntoActiveModel, Set};
+use uuid::Uuid;
+use wiremock::matchers::{body_partial_json, header, header_exists, header_regex, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn create_test_repository(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    provider_account_id: Uuid,
+) -> repository::Model {
+    let now = Utc::now();
+    repository::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider_account_id: Set(Some(provider_account_id)),
+        provider: Set("github".to_string()),
+        provider_id: Set("repo_notify".to_string()),
+        owner: Set("testowner".to_string()),
+        name: Set("notify".to_string()),
+        full_name: Set("testowner/notify".to_string()),
+        description: Set(None),
+        url: Set("https://github.com/testowner/notify".to_string()),
+        default_branch: Set("main".to_string()),
+        is_private: Set(false),
+        is_archived: Set(false),
+        group_id: Set(None),
+        last_polled_at: Set(None),
+        poll_interval_seconds: Set(300),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create repo")
+}
+
+/// Preferences that send every PR notification to a Slack webhook
+async fn create_slack_preferences(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    webhook_url: &str,
+) -> notification_preferences::Model {
+    notification_preferences::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        email_enabled: Set(false),
+        slack_enabled: Set(true),
+        slack_webhook_url: Set(Some(webhook_url.to_string())),
+        push_enabled: Set(false),
+        notify_on_pr_ready: Set(true),
+        notify_on_pr_failed: Set(true),
+        notify_on_review_requested: Set(true),
+        digest_frequency: Set("none".to_string()),
+        updated_at: Set(Utc::now()),
+        smtp_host: Set(None),
+        smtp_port: Set(None),
+        smtp_username: Set(None),
+        smtp_password_encrypted: Set(None),
+        smtp_from_email: Set(None),
+        smtp_to_emails: Set(None),
+        smtp_use_tls: Set(true),
+        notify_on_merge_success: Set(true),
+        notify_on_merge_failure: Set(true),
+        slack_channel: Set(None),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create preferences")
+}
+
+async fn log_for(db: &DatabaseConnection, user_id: Uuid) -> Vec<notification_log::Model> {
+    NotificationLogQueries::list_for_user(db, user_id, 100)
+        .await
+        .expect("Failed to load notification log")
+}
+
+async fn poll(db: &DatabaseConnection, provider: &MockProvider, repo: &repository::Model) {
+    PollRepositoryJob::poll_with_provider(db, provider, &credentials(), repo)
+        .await
+        .expect("Failed to poll");
+}
+
+#[tokio::test]
+async fn test_status_transitions_queue_notifications() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "owner@example.com", "owner")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "owner", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id).await;
+    create_slack_preferences(db, user.id, "https://hooks.example.com/owner").await;
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "Green PR", "open"));
+    provider.add_ci_check(create_test_ci_check("CI", "completed", Some("success")));
+    provider.add_review(create_test_review("reviewer", "approved"));
+
+    // The first sync only records a baseline
+    poll(db, &provider, &repo).await;
+    let pr = PrQueries::find_by_number(db, repo.id, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pr.ampel_status.as_deref(), Some("green"));
+    assert!(log_for(db, user.id).await.is_empty());
+
+    *provider.ci_checks.lock().unwrap() =
+        vec![create_test_ci_check("CI", "completed", Some("failure"))];
+    poll(db, &provider, &repo).await;
+    poll(db, &provider, &repo).await;
+
+    let log = log_for(db, user.id).await;
+    assert_eq!(log.len(), 1, "an unchanged status must not notify again");
+    assert_eq!(log[0].kind, "pr_failed");
+    assert_eq!(log[0].channel, "slack");
+    assert_eq!(log[0].status, "pending");
+    assert_eq!(log[0].pull_request_id, Some(pr.id));
+    let payload: PrNotification = serde_json::from_str(&log[0].payload).unwrap();
+    assert_eq!(payload.kind, NotificationKind::PrFailed);
+    assert_eq!(payload.repository, "testowner/notify");
+    assert_eq!(payload.pr_number, 1);
+
+    *provider.ci_checks.lock().unwrap() =
+        vec![create_test_ci_check("CI", "completed", Some("success"))];
+    poll(db, &provider, &repo).await;
+
+    let log = log_for(db, user.id).await;
+    assert_eq!(log.len(), 2);
+    assert!(log.iter().any(|entry| entry.kind == "pr_ready"));
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_review_requests_notify_matching_users_once() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let owner = create_test_user(db, "owner@example.com", "owner")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, owner.id, "github", "owner", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, owner.id, account.id).await;
+
+    // Provider usernames are `username_<label>`
+    let reviewer = create_test_user(db, "reviewer@example.com", "reviewer")
+        .await
+        .expect("Failed to create user");
+    create_test_provider_account(db, reviewer.id, "github", "reviewer", true)
+        .await
+        .expect("Failed to create account");
+    create_slack_preferences(db, reviewer.id, "https://hooks.example.com/reviewer").await;
+
+    let mut pr = create_test_pr(2, "Needs eyes", "open");
+    pr.requested_reviewers = vec!["Username_Reviewer".to_string(), "stranger".to_string()];
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(pr);
+
+    poll(db, &provider, &repo).await;
+    poll(db, &provider, &repo).await;
+
+    let log = log_for(db, reviewer.id).await;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].kind, "review_requested");
+    let payload: PrNotification = serde_json::from_str(&log[0].payload).unwrap();
+    assert_eq!(payload.reviewer.as_deref(), Some("Username_Reviewer"));
+    assert_eq!(payload.pr_title, "Needs eyes");
+
+    // The owner has no notification preferences, so nothing is queued for them
+    assert!(log_for(db, owner.id).await.is_empty());
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_dispatch_delivers_and_retries_with_backoff() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let encryption_service = create_test_encryption_service();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/ok"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/down"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let happy = create_test_user(db, "happy@example.com", "happy")
+        .await
+        .expect("Failed to create user");
+    create_slack_preferences(db, happy.id, &format!("{}/ok", server.uri())).await;
+    let unlucky = create_test_user(db, "unlucky@example.com", "unlucky")
+        .await
+        .expect("Failed to create user");
+    create_slack_preferences(db, unlucky.id, &format!("{}/down", server.uri())).await;
+
+    for user_id in [happy.id, unlucky.id] {
+        let payload = PrNotification {
+            kind: NotificationKind::PrReady,
+            repository: "testowner/notify".to_string(),
+            pr_number: 3,
+            pr_title: "Ship it".to_string(),
+            pr_url: "https://github.com/testowner/notify/pull/3".to_string(),
+            author: "testauthor".to_string(),
+            reviewer: None,
+        };
+        let queued = NotificationLogQueries::enqueue(
+            db,
+            NewNotification {
+                user_id,
+                pull_request_id: None,
+                kind: "pr_ready".to_string(),
+                channel: "slack".to_string(),
+                channel_id: None,
+                dedup_key: "pr_ready:test".to_string(),
+                payload: serde_json::to_string(&payload).unwrap(),
+            },
+        )
+        .await
+        .expect("Failed to enqueue");
+        assert!(queued);
+    }
+
+    NotificationDispatchJob
+        .execute(db, &encryption_service)
+        .await
+        .expect("Dispatch failed");
+
+    let sent = &log_for(db, happy.id).await[0];
+    assert_eq!(sent.status, "sent");
+    assert_eq!(sent.attempts, 1);
+    assert!(sent.sent_at.is_some());
+
+    let retrying = log_for(db, unlucky.id).await.remove(0);
+    assert_eq!(retrying.status, "pending");
+    assert_eq!(retrying.attempts, 1);
+    assert!(retrying.last_error.is_some());
+    assert!(retrying.next_attempt_at > Utc::now() + Duration::seconds(30));
+
+    // The last allowed attempt gives up for good
+    let mut last_try: notification_log::ActiveModel = retrying.into();
+    last_try.attempts = Set(NOTIFICATION_MAX_ATTEMPTS - 1);
+    last_try.next_attempt_at = Set(Utc::now() - Duration::seconds(1));
+    let last_try = last_try.update(db).await.expect("Failed to update entry");
+
+    NotificationDispatchJob
+        .execute(db, &encryption_service)
+        .await
+        .expect("Dispatch failed");
+
+    let given_up = notification_log::Entity::find_by_id(last_try.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(given_up.status, "failed");
+    assert_eq!(given_up.attempts, NOTIFICATION_MAX_ATTEMPTS);
+    assert!(given_up.sent_at.is_none());
+
+    test_db.cleanup().await;
+}
+
+/// A team with `user_id` as a member
+async fn create_test_team(db: &DatabaseConnection, user_id: Uuid) -> team::Model {
+    let now = Utc::now();
+    let org = organization::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        owner_id: Set(user_id),
+        name: Set("Acme".to_string()),
+        slug: Set(format!("acme-{}", Uuid::new_v4())),
+        description: Set(None),
+        logo_url: Set(None),
+        air_gapped: Set(false),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create organization");
+    let team = team::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        organization_id: Set(org.id),
+        name: Set("Platform".to_string()),
+        slug: Set("platform".to_string()),
+        description: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create team");
+    team_member::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        team_id: Set(team.id),
+        user_id: Set(user_id),
+        role: Set("member".to_string()),
+        joined_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to add team member");
+    team
+}
+
+async fn create_channel(
+    db: &DatabaseConnection,
+    encryption_service: &EncryptionService,
+    owner: (Option<Uuid>, Option<Uuid>),
+    config: ChannelConfig,
+    events: Option<&str>,
+) {
+    let json = serde_json::to_string(&config).unwrap();
+    NotificationChannelQueries::create(
+        db,
+        NewNotificationChannel {
+            user_id: owner.0,
+            team_id: owner.1,
+            kind: config.kind().to_string(),
+            name: "Test channel".to_string(),
+            config_encrypted: encryption_service.encrypt(&json).unwrap(),
+            events: events.map(str::to_string),
+        },
+    )
+    .await
+    .expect("Failed to create channel");
+}
+
+#[tokio::test]
+async fn test_configured_channels_receive_subscribed_events() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let encryption_service = create_test_encryption_service();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header(WEBHOOK_EVENT_HEADER, "pr_failed"))
+        .and(header_exists(WEBHOOK_SIGNATURE_HEADER))
+        .and(body_partial_json(serde_json::json!({
+            "event": "pr_failed",
+            "data": { "prNumber": 1 }
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/discord"))
+        .and(body_partial_json(serde_json::json!({
+            "embeds": [{ "url": "https://github.com/test/repo/pull/1" }]
+        })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // No preferences, so only configured channels are notified
+    let user = create_test_user(db, "owner@example.com", "owner")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "owner", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id).await;
+    let team = create_test_team(db, user.id).await;
+
+    create_channel(
+        db,
+        &encryption_service,
+        (Some(user.id), None),
+        ChannelConfig::Webhook {
+            url: format!("{}/hook", server.uri()),
+            secret: "0123456789abcdef".to_string(),
+        },
+        Some(r#"["pr_failed"]"#),
+    )
+    .await;
+    create_channel(
+        db,
+        &encryption_service,
+        (Some(user.id), None),
+        ChannelConfig::Teams {
+            webhook_url: format!("{}/teams", server.uri()),
+        },
+        Some(r#"["review_requested"]"#),
+    )
+    .await;
+    create_channel(
+        db,
+        &encryption_service,
+        (None, Some(team.id)),
+        ChannelConfig::Discord {
+            webhook_url: format!("{}/discord", server.uri()),
+        },
+        None,
+    )
+    .await;
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "Red PR", "open"));
+    provider.add_ci_check(create_test_ci_check("CI", "completed", Some("success")));
+    provider.add_review(create_test_review("reviewer", "approved"));
+    poll(db, &provider, &repo).await;
+    *provider.ci_checks.lock().unwrap() =
+        vec![create_test_ci_check("CI", "completed", Some("failure"))];
+    poll(db, &provider, &repo).await;
+
+    let log = log_for(db, user.id).await;
+    let mut channels: Vec<_> = log.iter().map(|entry| entry.channel.as_str()).collect();
+    channels.sort();
+    assert_eq!(channels, vec!["discord", "webhook"]);
+    assert!(log.iter().all(|entry| entry.channel_id.is_some()));
+
+    NotificationDispatchJob
+        .execute(db, &encryption_service)
+        .await
+        .expect("Dispatch failed");
+
+    for entry in log_for(db, user.id).await {
+        assert_eq!(entry.status, "sent", "{:?}", entry.last_error);
+    }
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_push_notifications_reach_browsers_and_prune_expired_ones() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+    let encryption_service = create_test_encryption_service();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push/live"))
+        .and(header("Content-Encoding", "aes128gcm"))
+        .and(header_exists("TTL"))
+        .and(header_regex(
+            "Authorization",
+            r"^vapid t=[\w-]+\.[\w-]+\.[\w-]+, k=[\w-]+$",
+        ))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/push/gone"))
+        .respond_with(ResponseTemplate::new(410))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let user = create_test_user(db, "owner@example.com", "owner")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "owner", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id).await;
+    let mut prefs = create_slack_preferences(db, user.id, "https://hooks.slack.invalid/x")
+        .await
+        .into_active_model();
+    prefs.slack_enabled = Set(false);
+    prefs.push_enabled = Set(true);
+    prefs.update(db).await.expect("Failed to enable push");
+
+    // Browsers' keys are P-256 public keys, like the VAPID key
+    for endpoint in ["live", "gone"] {
+        PushSubscriptionQueries::upsert(
+            db,
+            NewPushSubscription {
+                user_id: user.id,
+                endpoint: format!("{}/push/{}", server.uri(), endpoint),
+                p256dh: VapidKeys::generate().public_key(),
+                auth: "BTBZMqHH6r4Tts7J_aSIgg".to_string(),
+                user_agent: None,
+            },
+        )
+        .await
+        .expect("Failed to subscribe");
+    }
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "Red PR", "open"));
+    provider.add_ci_check(create_test_ci_check("CI", "completed", Some("success")));
+    provider.add_review(create_test_review("reviewer", "approved"));
+    poll(db, &provider, &repo).await;
+    *provider.ci_checks.lock().unwrap() =
+        vec![create_test_ci_check("CI", "completed", Some("failure"))];
+    poll(db, &provider, &repo).await;
+
+    // One entry covers every browser
+    let log = log_for(db, user.id).await;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].channel, "push");
+
+    NotificationDispatchJob
+        .execute(db, &encryption_service)
+        .await
+        .expect("Dispatch failed");
+
+    let entry = &log_for(db, user.id).await[0];
+    assert_eq!(entry.status, "sent", "{:?}", entry.last_error);
+    let remaining = PushSubscriptionQueries::list_for_user(db, user.id)
+        .await
+        .expect("Failed to list subscriptions");
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].endpoint.ends_with("/push/live"));
+    assert!(remaining[0].last_used_at.is_some());
+
+    test_db.cleanup().await;
+}
+
+fn credentials() -> ProviderCredentials {
+    ProviderCredentials::Pat {
+        token: "test_token".to_string(),
+        username: None,
+    }
+}
@@ -5,6 +5,7 @@
 /// - Fetches pull requests from providers
 /// - Updates database with latest PR data
 /// - Updates last polled timestamps
+/// - Polls repositories with open or recently changed PRs ahead of idle ones
 ///
 /// Note: These tests require PostgreSQL because migrations use PostgreSQL-specific
 /// features. Tests are automatically skipped when running in SQLite mode.
@@ -12,12 +13,13 @@ mod common;
 
 use ampel_core::models::GitProvider;
 use ampel_db::entities::{provider_account, repository};
-use ampel_db::queries::RepoQueries;
+use ampel_db::queries::{PrQueries, RepoQueries, RepositoryWebhookQueries};
+use ampel_providers::traits::ProviderCredentials;
 use ampel_worker::jobs::poll_repository::PollRepositoryJob;
 use chrono::{Duration, Utc};
 use common::{
-    create_test_ci_check, create_test_encryption_service, create_test_pr, create_test_review,
-    create_test_user, MockProvider, TestDb,
+    create_test_ci_check, create_test_encryption_service, create_test_pr,
+    create_test_provider_account, create_test_review, create_test_user, MockProvider, TestDb,
 };
 use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use uuid::Uuid;
@@ -235,6 +237,174 @@ async fn test_find_repos_to_poll_due() {
     test_db.cleanup().await;
 }
 
+#[tokio::test]
+async fn test_find_repos_to_poll_live_webhook_reconciles_hourly() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+
+    let encryption_service = create_test_encryption_service();
+    let encrypted_token = encryption_service
+        .encrypt("test_token")
+        .expect("Failed to encrypt token");
+
+    let provider_account = provider_account::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user.id),
+        provider: Set("github".to_string()),
+        instance_url: Set(None),
+        account_label: Set("Test Account".to_string()),
+        provider_user_id: Set("123".to_string()),
+        provider_username: Set("testuser".to_string()),
+        provider_email: Set(Some("test@example.com".to_string())),
+        avatar_url: Set(None),
+        auth_type: Set("pat".to_string()),
+        access_token_encrypted: Set(encrypted_token.clone()),
+        auth_username: Set(None),
+        scopes: Set(None),
+        token_expires_at: Set(None),
+        last_validated_at: Set(Some(Utc::now())),
+        validation_status: Set("valid".to_string()),
+        is_active: Set(true),
+        is_default: Set(true),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+    }
+    .insert(db)
+    .await
+    .expect("Failed to create provider account");
+
+    // Both repos are past their 300s interval, but only one has a live webhook
+    let last_polled = Utc::now() - Duration::seconds(400);
+    let live_repo = create_test_repository(
+        db,
+        user.id,
+        provider_account.id,
+        "webhook-repo",
+        Some(last_polled),
+        300,
+    )
+    .await
+    .expect("Failed to create repo");
+    create_test_repository(
+        db,
+        user.id,
+        provider_account.id,
+        "polled-repo",
+        Some(last_polled),
+        300,
+    )
+    .await
+    .expect("Failed to create repo");
+
+    let webhook = RepositoryWebhookQueries::upsert_secret(
+        db,
+        live_repo.id,
+        "github".to_string(),
+        encrypted_token,
+    )
+    .await
+    .expect("Failed to create webhook");
+    RepositoryWebhookQueries::record_delivery(db, webhook.id, "pull_request".to_string())
+        .await
+        .expect("Failed to record delivery");
+
+    let job = PollRepositoryJob;
+    let repos = job
+        .find_repos_to_poll(db)
+        .await
+        .expect("Failed to find repos");
+
+    assert_eq!(
+        repos.len(),
+        1,
+        "Live webhook repo should wait for reconciliation"
+    );
+    assert_eq!(repos[0].name, "polled-repo");
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_find_repos_to_poll_live_webhooks_do_not_crowd_out_due_repos() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+
+    // More live repos than one batch holds, all polled before the due repo
+    // but still inside their hourly reconcile window
+    let now = Utc::now();
+    for i in 0..51 {
+        let repo = create_test_repository(
+            db,
+            user.id,
+            account.id,
+            &format!("live-{i}"),
+            Some(now - Duration::seconds(1_000)),
+            300,
+        )
+        .await
+        .expect("Failed to create repo");
+        let webhook = RepositoryWebhookQueries::upsert_secret(
+            db,
+            repo.id,
+            "github".to_string(),
+            b"secret".to_vec(),
+        )
+        .await
+        .expect("Failed to create webhook");
+        RepositoryWebhookQueries::record_delivery(db, webhook.id, "pull_request".to_string())
+            .await
+            .expect("Failed to record delivery");
+    }
+    create_test_repository(
+        db,
+        user.id,
+        account.id,
+        "polled-repo",
+        Some(now - Duration::seconds(400)),
+        300,
+    )
+    .await
+    .expect("Failed to create repo");
+
+    let job = PollRepositoryJob;
+    let repos = job
+        .find_repos_to_poll(db)
+        .await
+        .expect("Failed to find repos");
+
+    let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["polled-repo"]);
+
+    test_db.cleanup().await;
+}
+
 #[tokio::test]
 async fn test_find_repos_to_poll_not_due() {
     if TestDb::skip_if_sqlite() {
@@ -492,3 +662,118 @@ async fn test_find_repos_to_poll_mixed_scenarios() {
 
     test_db.cleanup().await;
 }
+
+#[tokio::test]
+async fn test_prioritize_polls_active_repositories_first() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+
+    // The idle repo was polled longest ago, so it would normally go first
+    let now = Utc::now();
+    let idle = create_test_repository(
+        db,
+        user.id,
+        account.id,
+        "idle",
+        Some(now - Duration::hours(3)),
+        300,
+    )
+    .await
+    .expect("Failed to create repo");
+    let active = create_test_repository(
+        db,
+        user.id,
+        account.id,
+        "active",
+        Some(now - Duration::hours(1)),
+        300,
+    )
+    .await
+    .expect("Failed to create repo");
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(1, "Open PR", "open"));
+    PollRepositoryJob::poll_with_provider(db, &provider, &credentials(), &active)
+        .await
+        .expect("Failed to poll");
+
+    let job = PollRepositoryJob;
+    let ordered = job
+        .prioritize(db, vec![idle.clone(), active.clone()])
+        .await
+        .expect("Failed to prioritize");
+
+    let names: Vec<&str> = ordered.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["active", "idle"]);
+
+    test_db.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_poll_with_provider_syncs_prs_and_marks_polled() {
+    if TestDb::skip_if_sqlite() {
+        return;
+    }
+
+    let test_db = TestDb::new().await.expect("Failed to create test DB");
+    test_db
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    let db = test_db.connection();
+
+    let user = create_test_user(db, "test@example.com", "testuser")
+        .await
+        .expect("Failed to create user");
+    let account = create_test_provider_account(db, user.id, "github", "work", true)
+        .await
+        .expect("Failed to create account");
+    let repo = create_test_repository(db, user.id, account.id, "synced", None, 300)
+        .await
+        .expect("Failed to create repo");
+
+    let provider = MockProvider::new(GitProvider::GitHub);
+    provider.add_pull_request(create_test_pr(7, "Synced PR", "open"));
+    provider.add_ci_check(create_test_ci_check("CI", "completed", Some("success")));
+
+    PollRepositoryJob::poll_with_provider(db, &provider, &credentials(), &repo)
+        .await
+        .expect("Failed to poll");
+
+    let prs = PrQueries::find_open_by_repository(db, repo.id)
+        .await
+        .expect("Failed to load PRs");
+    assert_eq!(prs.len(), 1);
+    assert_eq!(prs[0].number, 7);
+
+    let polled = repository::Entity::find_by_id(repo.id)
+        .one(db)
+        .await
+        .expect("Failed to fetch repo")
+        .expect("Repo not found");
+    assert!(polled.last_polled_at.is_some());
+
+    test_db.cleanup().await;
+}
+
+fn credentials() -> ProviderCredentials {
+    ProviderCredentials::Pat {
+        token: "test_token".to_string(),
+        username: None,
+    }
+}
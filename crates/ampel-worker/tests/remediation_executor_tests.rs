@@ -8,25 +8,32 @@
 
 use std::sync::Arc;
 
-use ampel_core::remediation::{AutonomyLevel, PrRef, RunState};
-use ampel_core::services::{ConsolidateResult, FakeSandboxRunner};
+use ampel_core::remediation::{
+    AutonomyLevel, ConsolidationPlan, ConsolidationStrategy, MergeDisposition, PrRef, RunState,
+    SuggestedChange,
+};
+use ampel_core::services::{ConsolidateResult, ConsolidationSpec, FakeSandboxRunner};
 use ampel_core::services::{
     CredentialHandle, MergeOutcome, RemediationOrchestrator, RemediationProvider,
-    RemediationRunRepository, RepoContext, VerificationService,
+    RemediationRunRepository, RepoContext, RunUpdate, VerificationService,
 };
+use ampel_db::entities::remediation_run_pr;
 use ampel_db::migrations::test_support::apply_remediation_schema;
+use ampel_db::queries::RemediationRunQueries;
 use ampel_db::repositories::SeaOrmRemediationRunRepository;
 use ampel_providers::mock::{MockProvider, RemediationCall};
 use ampel_providers::traits::{ProviderCICheck, ProviderCredentials, ProviderPullRequest};
-use ampel_providers::RemediationCapable;
+use ampel_providers::{RemediationCapable, RemediationCaps};
 use ampel_worker::services::notifier::{
     RemediationNotifier, RunMergedNotification, SourcePrsClosedNotification,
 };
+use ampel_worker::services::suggestions::FixProposer;
 use ampel_worker::services::{
     AgentTierOutcome, AgenticTier, ProviderAdapter, RemediationExecutor, RunOutcome,
+    SuggestOnlyPublisher,
 };
 use async_trait::async_trait;
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::{ColumnTrait, Database, DatabaseConnection, EntityTrait, QueryFilter};
 use sea_orm_migration::SchemaManager;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
@@ -86,6 +93,7 @@ fn consolidated_pr_record(number: i32, mergeable: Option<bool>) -> ProviderPullR
         state: "open".to_string(),
         source_branch: "ampel/remediation".to_string(),
         target_branch: "main".to_string(),
+        head_sha: None,
         author: "ampel".to_string(),
         author_avatar_url: None,
         is_draft: false,
@@ -100,6 +108,10 @@ fn consolidated_pr_record(number: i32, mergeable: Option<bool>) -> ProviderPullR
         updated_at: now,
         merged_at: None,
         closed_at: None,
+        requested_reviewers: Vec::new(),
+        labels: Vec::new(),
+        assignees: Vec::new(),
+        milestone: None,
     }
 }
 
@@ -236,6 +248,184 @@ async fn should_no_op_with_zero_provider_writes_under_dry_run() {
     assert!(!sandbox.was_invoked());
 }
 
+/// Proposes one fix for PR #1 and nothing for any other PR.
+struct FixFirstPr;
+
+#[async_trait]
+impl FixProposer for FixFirstPr {
+    async fn propose(&self, _run_id: Uuid, pr: &PrRef) -> AmpelResult<Vec<SuggestedChange>> {
+        Ok(if pr.number == 1 {
+            vec![SuggestedChange {
+                path: "Cargo.toml".into(),
+                start_line: 4,
+                end_line: 4,
+                replacement: "serde = \"1.0.200\"".into(),
+                diff: String::new(),
+            }]
+        } else {
+            Vec::new()
+        })
+    }
+}
+
+/// Run `prs` under `suggest_only` with a publisher over `mock`.
+async fn run_suggest_only(
+    conn: &DatabaseConnection,
+    mock: &MockProvider,
+    prs: Vec<PrRef>,
+) -> (Uuid, RunOutcome, Arc<FakeSandboxRunner>) {
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(FakeSandboxRunner::new());
+    let provider = adapter(mock);
+    let publisher = SuggestOnlyPublisher::new(
+        provider.clone(),
+        ConsolidationPlan::from_selection(prs.clone(), false),
+    )
+    .with_proposer(Arc::new(FixFirstPr));
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox.clone(),
+        VerificationService::new(),
+        provider,
+    )
+    .with_suggestions(Arc::new(publisher));
+    let repository_id = Uuid::new_v4();
+    let run = run_repo
+        .create_run(repository_id, AutonomyLevel::SuggestOnly)
+        .await
+        .unwrap();
+
+    let outcome = executor.execute(run.id, prs, repo_ctx()).await.unwrap();
+    (repository_id, outcome, sandbox)
+}
+
+#[tokio::test]
+async fn should_post_plan_and_suggestions_without_pushing_under_suggest_only() {
+    // Arrange
+    let conn = sqlite().await;
+    let mock = MockProvider::new();
+
+    // Act
+    let (repository_id, outcome, sandbox) =
+        run_suggest_only(&conn, &mock, vec![pr(1), pr(2)]).await;
+
+    // Assert: parked in no_op with nothing pushed; each PR got the plan and
+    // only PR #1 got a suggestion.
+    assert_eq!(outcome, RunOutcome::NoOp);
+    assert!(!sandbox.was_invoked());
+    let calls = mock.remediation_calls();
+    let plan_comments: Vec<i32> = calls
+        .iter()
+        .filter_map(|c| match c {
+            RemediationCall::CreateComment {
+                pr_number, body, ..
+            } if body.contains("Ampel remediation plan") => Some(*pr_number),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(plan_comments, vec![1, 2]);
+    let suggested: Vec<(i32, usize)> = calls
+        .iter()
+        .filter_map(|c| match c {
+            RemediationCall::CreateSuggestions {
+                pr_number,
+                suggestions,
+                ..
+            } => Some((*pr_number, suggestions.len())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(suggested, vec![(1, 1)]);
+    assert_eq!(calls.len(), 3, "no branch, PR or close writes: {calls:?}");
+
+    // The published plan is recorded for the next run's dedupe.
+    let stored = RemediationRunQueries::latest_suggested_plan(&conn, repository_id)
+        .await
+        .unwrap()
+        .expect("plan recorded on the run");
+    let stored: ConsolidationPlan = serde_json::from_str(&stored).unwrap();
+    assert_eq!(stored.would_select, vec![pr(1), pr(2)]);
+}
+
+#[tokio::test]
+async fn should_fall_back_to_plain_comment_when_provider_lacks_suggestions() {
+    // Arrange: a Bitbucket-like provider without native suggested changes.
+    let conn = sqlite().await;
+    let mock = MockProvider::new().with_remediation_caps(RemediationCaps {
+        create_suggestions: false,
+        ..RemediationCaps::all()
+    });
+
+    // Act
+    let (_, outcome, _) = run_suggest_only(&conn, &mock, vec![pr(1)]).await;
+
+    // Assert: the proposal arrives as a second comment on PR #1.
+    assert_eq!(outcome, RunOutcome::NoOp);
+    let calls = mock.remediation_calls();
+    assert!(!calls
+        .iter()
+        .any(|c| matches!(c, RemediationCall::CreateSuggestions { .. })));
+    let fallback = calls
+        .iter()
+        .find_map(|c| match c {
+            RemediationCall::CreateComment { body, .. } if body.contains("replace with") => {
+                Some(body.clone())
+            }
+            _ => None,
+        })
+        .expect("fallback comment posted");
+    assert!(fallback.contains("`Cargo.toml` line 4"));
+    assert!(fallback.contains("serde = \"1.0.200\""));
+}
+
+#[tokio::test]
+async fn should_fail_a_suggest_only_run_whose_suggestions_are_rejected() {
+    // Arrange
+    let conn = sqlite().await;
+    let mock = MockProvider::new().with_suggestions_failure();
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let provider = adapter(&mock);
+    let publisher = SuggestOnlyPublisher::new(
+        provider.clone(),
+        ConsolidationPlan::from_selection(vec![pr(1), pr(2)], false),
+    )
+    .with_proposer(Arc::new(FixFirstPr));
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        Arc::new(FakeSandboxRunner::new()),
+        VerificationService::new(),
+        provider,
+    )
+    .with_suggestions(Arc::new(publisher));
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::SuggestOnly)
+        .await
+        .unwrap();
+
+    // Act
+    let result = executor
+        .execute(run.id, vec![pr(1), pr(2)], repo_ctx())
+        .await;
+
+    // Assert: both PRs still got the plan, but the run failed instead of
+    // parking in no_op as if the fix had been posted.
+    assert!(result.is_err());
+    let persisted = run_repo.get_run(run.id).await.unwrap().unwrap();
+    assert_eq!(persisted.state, RunState::Failed);
+    assert!(persisted
+        .error_message
+        .unwrap()
+        .contains("failed to post suggested changes on PR #1"));
+    let plan_comments = mock
+        .remediation_calls()
+        .iter()
+        .filter(|c| matches!(c, RemediationCall::CreateComment { .. }))
+        .count();
+    assert_eq!(plan_comments, 2);
+}
+
 #[tokio::test]
 async fn should_handoff_without_merge_when_sha_changes_at_gate() {
     // Arrange: drive the orchestrator step-by-step so the consolidated branch's
@@ -707,6 +897,136 @@ async fn should_resume_and_merge_when_run_already_in_merging_after_approval() {
     assert_eq!(notifier.closed.lock().unwrap().len(), 1);
 }
 
+#[tokio::test]
+async fn should_resume_from_verifying_without_reconsolidating_after_worker_crash() {
+    // Arrange: a run whose worker died after consolidating — persisted in
+    // `verifying` with its consolidated PR recorded.
+    let conn = sqlite().await;
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(FakeSandboxRunner::with_outcome(
+        Some(CONSOLIDATED_PR),
+        "headsha",
+    ));
+    let mock = green_mergeable_mock();
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox.clone(),
+        VerificationService::new(),
+        adapter(&mock),
+    );
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap();
+    for (from, to, update) in [
+        (RunState::Created, RunState::Selecting, RunUpdate::none()),
+        (
+            RunState::Selecting,
+            RunState::Consolidating,
+            RunUpdate::none(),
+        ),
+        (
+            RunState::Consolidating,
+            RunState::Verifying,
+            RunUpdate {
+                consolidated_pr_number: Some(CONSOLIDATED_PR),
+                ..RunUpdate::none()
+            },
+        ),
+    ] {
+        assert!(run_repo
+            .transition_state(run.id, from, to, update)
+            .await
+            .unwrap());
+    }
+
+    // Act: the redelivered job executes the run again.
+    let outcome = executor
+        .execute(run.id, vec![pr(1), pr(2)], repo_ctx())
+        .await
+        .unwrap();
+
+    // Assert: picked up at verify — completed without a second sandbox run.
+    assert_eq!(outcome, RunOutcome::Completed);
+    assert_eq!(
+        run_repo.get_run(run.id).await.unwrap().unwrap().state,
+        RunState::Completed
+    );
+    assert!(!sandbox.was_invoked());
+}
+
+#[tokio::test]
+async fn should_finalize_without_merging_again_when_merge_landed_before_crash() {
+    // Arrange: the worker merged the consolidated PR and died before recording
+    // `merging → finalizing`. A second merge attempt would fail the run.
+    let conn = sqlite().await;
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(FakeSandboxRunner::with_outcome(
+        Some(CONSOLIDATED_PR),
+        "headsha",
+    ));
+    let mut merged = consolidated_pr_record(CONSOLIDATED_PR as i32, Some(true));
+    merged.state = "closed".to_string();
+    merged.merged_at = Some(chrono::Utc::now());
+    let mock = MockProvider::new()
+        .with_pull_requests(OWNER, REPO, vec![merged])
+        .with_merge_failure(
+            OWNER,
+            REPO,
+            CONSOLIDATED_PR as i32,
+            ampel_providers::error::ProviderError::ApiError {
+                status_code: 405,
+                message: "Pull Request is not mergeable".to_string(),
+            },
+        );
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox.clone(),
+        VerificationService::new(),
+        adapter(&mock),
+    );
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap();
+    for (from, to, update) in [
+        (RunState::Created, RunState::Selecting, RunUpdate::none()),
+        (
+            RunState::Selecting,
+            RunState::Consolidating,
+            RunUpdate::none(),
+        ),
+        (
+            RunState::Consolidating,
+            RunState::Verifying,
+            RunUpdate {
+                consolidated_pr_number: Some(CONSOLIDATED_PR),
+                head_sha: Some("headsha".into()),
+                ..RunUpdate::none()
+            },
+        ),
+        (RunState::Verifying, RunState::Merging, RunUpdate::none()),
+    ] {
+        assert!(run_repo
+            .transition_state(run.id, from, to, update)
+            .await
+            .unwrap());
+    }
+
+    // Act: the redelivered job executes the run again.
+    let outcome = executor.execute(run.id, vec![], repo_ctx()).await.unwrap();
+
+    // Assert: reconciled straight to finalize and completed.
+    assert_eq!(outcome, RunOutcome::Completed);
+    assert_eq!(
+        run_repo.get_run(run.id).await.unwrap().unwrap().state,
+        RunState::Completed
+    );
+    assert!(!sandbox.was_invoked());
+}
+
 #[tokio::test]
 async fn should_emit_notifications_on_happy_path() {
     // Arrange: fully-autonomous green run with a recording notifier.
@@ -812,3 +1132,235 @@ async fn should_handoff_without_merge_when_ci_turns_red_at_gate() {
         .iter()
         .any(|c| matches!(c, RemediationCall::ClosePullRequest { .. })));
 }
+
+/// The dispositions consolidation recorded for `run_id`, by source PR number
+/// (finalize's `ClosedWithRef` rows are left out).
+async fn dispositions(conn: &DatabaseConnection, run_id: Uuid) -> Vec<(i64, MergeDisposition)> {
+    let mut rows = remediation_run_pr::Entity::find()
+        .filter(remediation_run_pr::Column::RemediationRunId.eq(run_id))
+        .all(conn)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| {
+            (
+                row.pr_number,
+                serde_json::from_str(&row.disposition).unwrap(),
+            )
+        })
+        .filter(|(_, d)| !matches!(d, MergeDisposition::ClosedWithRef { .. }))
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|(pr, _)| *pr);
+    rows
+}
+
+fn closed_prs(mock: &MockProvider) -> Vec<i32> {
+    mock.remediation_calls()
+        .iter()
+        .filter_map(|c| match c {
+            RemediationCall::ClosePullRequest { pr_number, .. } => Some(*pr_number),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn should_skip_conflicting_pr_with_its_files_and_leave_it_open() {
+    // Arrange: PR 2 conflicts on a source file, which is never auto-resolved.
+    let conn = sqlite().await;
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(
+        FakeSandboxRunner::with_outcome(Some(CONSOLIDATED_PR), "headsha")
+            .with_conflict(2, &["src/lib.rs"]),
+    );
+    let mock = green_mergeable_mock();
+    let notifier = Arc::new(FakeNotifier::default());
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox.clone(),
+        VerificationService::new(),
+        adapter(&mock),
+    )
+    .with_notifier(notifier.clone());
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap();
+
+    // Act
+    let outcome = executor
+        .execute(run.id, vec![pr(1), pr(2), pr(3)], repo_ctx())
+        .await
+        .unwrap();
+
+    // Assert: the merge was backed out, PR 2 is skipped with its files, and
+    // only the consolidated PRs are closed as superseded.
+    assert_eq!(outcome, RunOutcome::Completed);
+    assert!(sandbox
+        .git_commands()
+        .contains(&"git merge --abort".to_string()));
+    let recorded = dispositions(&conn, run.id).await;
+    assert_eq!(recorded[0], (1, MergeDisposition::Consolidated));
+    assert_eq!(recorded[2], (3, MergeDisposition::Consolidated));
+    match &recorded[1] {
+        (2, MergeDisposition::SkippedConflict { reason, files }) => {
+            assert_eq!(files, &vec!["src/lib.rs".to_string()]);
+            assert!(reason.contains("src/lib.rs"));
+        }
+        other => panic!("expected PR 2 skipped over a conflict, got {other:?}"),
+    }
+    assert_eq!(closed_prs(&mock), vec![1, 3]);
+    assert_eq!(
+        notifier.closed.lock().unwrap()[0].closed_pr_numbers,
+        vec![1, 3]
+    );
+}
+
+#[tokio::test]
+async fn should_leave_a_rebased_pr_without_merge_base_open() {
+    // Arrange: PR 2's branch forked before the shallow clone's history.
+    let conn = sqlite().await;
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(
+        FakeSandboxRunner::with_outcome(Some(CONSOLIDATED_PR), "headsha").without_merge_base(2),
+    );
+    let mock = green_mergeable_mock();
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox.clone(),
+        VerificationService::new(),
+        adapter(&mock),
+    )
+    .with_consolidation_strategy(ConsolidationStrategy::RebaseOntoBase);
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap();
+
+    // Act
+    let outcome = executor
+        .execute(run.id, vec![pr(1), pr(2), pr(3)], repo_ctx())
+        .await
+        .unwrap();
+
+    // Assert: PR 2 was never cherry-picked and stays open with the reason.
+    assert_eq!(outcome, RunOutcome::Completed);
+    assert!(!sandbox
+        .git_commands()
+        .iter()
+        .any(|c| c.starts_with("git cherry-pick") && c.ends_with("origin/dependabot/dep-2")));
+    let recorded = dispositions(&conn, run.id).await;
+    assert_eq!(
+        recorded[1],
+        (
+            2,
+            MergeDisposition::LeftOpen {
+                reason: "no merge base with origin/main within the fetched history".to_string(),
+            }
+        )
+    );
+    assert_eq!(closed_prs(&mock), vec![1, 3]);
+}
+
+#[tokio::test]
+async fn should_regenerate_lockfile_only_conflict_and_consolidate_the_pr() {
+    // Arrange: PR 2 conflicts only on Cargo.lock.
+    let conn = sqlite().await;
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(
+        FakeSandboxRunner::with_outcome(Some(CONSOLIDATED_PR), "headsha")
+            .with_conflict(2, &["Cargo.lock"]),
+    );
+    let mock = green_mergeable_mock();
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox.clone(),
+        VerificationService::new(),
+        adapter(&mock),
+    );
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap();
+
+    // Act
+    let outcome = executor
+        .execute(run.id, vec![pr(1), pr(2), pr(3)], repo_ctx())
+        .await
+        .unwrap();
+
+    // Assert: the lockfile was regenerated, the merge committed, and every PR
+    // consolidated and closed.
+    assert_eq!(outcome, RunOutcome::Completed);
+    let commands = sandbox.git_commands();
+    assert!(commands.contains(&"cargo generate-lockfile".to_string()));
+    assert!(commands.contains(&"git commit --no-edit".to_string()));
+    assert!(dispositions(&conn, run.id)
+        .await
+        .iter()
+        .all(|(_, d)| *d == MergeDisposition::Consolidated));
+    assert_eq!(closed_prs(&mock), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn should_open_the_consolidated_pr_when_the_sandbox_only_pushes_the_branch() {
+    // Arrange: the sandbox pushes the branch but opens no PR; the mock numbers
+    // the first PR it creates #1, so the source PRs start above that.
+    let conn = sqlite().await;
+    let run_repo: Arc<dyn RemediationRunRepository> =
+        Arc::new(SeaOrmRemediationRunRepository::new(conn.clone()));
+    let sandbox = Arc::new(FakeSandboxRunner::with_outcome(None, "headsha"));
+    let mock = MockProvider::new()
+        .with_ci_checks(OWNER, REPO, 1, vec![green_check("build")])
+        .with_pull_requests(OWNER, REPO, vec![consolidated_pr_record(1, Some(true))]);
+    let executor = RemediationExecutor::new(
+        run_repo.clone(),
+        sandbox,
+        VerificationService::new(),
+        adapter(&mock),
+    );
+    let run = run_repo
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap();
+
+    // Act
+    let outcome = executor
+        .execute(run.id, vec![pr(11), pr(12)], repo_ctx())
+        .await
+        .unwrap();
+
+    // Assert: one PR opened from the consolidation branch onto the default
+    // branch, recorded on the run, and the sources closed against it.
+    assert_eq!(outcome, RunOutcome::Completed);
+    let opened: Vec<(String, String)> = mock
+        .remediation_calls()
+        .iter()
+        .filter_map(|c| match c {
+            RemediationCall::CreatePullRequest { head, base, .. } => {
+                Some((head.clone(), base.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        opened,
+        vec![(
+            ConsolidationSpec::branch_name_for(run.id),
+            "main".to_string()
+        )]
+    );
+    assert_eq!(
+        run_repo
+            .get_run(run.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .consolidated_pr_number,
+        Some(1)
+    );
+    assert_eq!(closed_prs(&mock), vec![11, 12]);
+}
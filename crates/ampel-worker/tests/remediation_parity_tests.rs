@@ -84,6 +84,7 @@ fn consolidated_pr_record(number: i32) -> ProviderPullRequest {
         state: "open".to_string(),
         source_branch: "ampel/remediation".to_string(),
         target_branch: "main".to_string(),
+        head_sha: None,
         author: "ampel".to_string(),
         author_avatar_url: None,
         is_draft: false,
@@ -98,6 +99,10 @@ fn consolidated_pr_record(number: i32) -> ProviderPullRequest {
         updated_at: now,
         merged_at: None,
         closed_at: None,
+        requested_reviewers: Vec::new(),
+        labels: Vec::new(),
+        assignees: Vec::new(),
+        milestone: None,
     }
 }
 
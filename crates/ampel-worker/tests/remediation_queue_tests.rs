@@ -0,0 +1,280 @@
+//! CI-safe integration tests for the remediation run queue.
+//!
+//! The dispatcher, the per-run lease and dead-lettering run against the
+//! remediation schema on in-memory SQLite, with a recording [`RunQueue`]
+//! standing in for the Postgres-backed job storage.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ampel_core::remediation::{AutonomyLevel, RunState};
+use ampel_core::services::{FakeSandboxRunner, RemediationRunRepository, RunUpdate};
+use ampel_db::encryption::EncryptionService;
+use ampel_db::entities::remediation_run;
+use ampel_db::migrations::test_support::apply_remediation_schema;
+use ampel_db::queries::RemediationRunQueries;
+use ampel_db::repositories::SeaOrmRemediationRunRepository;
+use ampel_providers::ProviderFactory;
+use ampel_worker::jobs::remediation_dispatch::{RemediationDispatchJob, RunQueue};
+use ampel_worker::jobs::remediation_run::{RemediationRunJob, RunQueueConfig};
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Database, DatabaseConnection, EntityTrait, QueryFilter};
+use sea_orm_migration::SchemaManager;
+use uuid::Uuid;
+
+/// Fresh in-memory SQLite with just the remediation schema applied.
+async fn sqlite() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:")
+        .await
+        .expect("connect sqlite");
+    let manager = SchemaManager::new(&conn);
+    apply_remediation_schema(&manager)
+        .await
+        .expect("apply remediation schema");
+    conn
+}
+
+/// Records enqueued run ids; optionally refuses every push.
+#[derive(Default)]
+struct RecordingQueue {
+    enqueued: Mutex<Vec<Uuid>>,
+    fail: bool,
+}
+
+#[async_trait]
+impl RunQueue for RecordingQueue {
+    async fn enqueue(&self, job: RemediationRunJob) -> anyhow::Result<()> {
+        if self.fail {
+            anyhow::bail!("queue unavailable");
+        }
+        self.enqueued.lock().unwrap().push(job.run_id);
+        Ok(())
+    }
+}
+
+fn config() -> RunQueueConfig {
+    RunQueueConfig {
+        concurrency: 2,
+        lease_ttl: Duration::from_secs(60),
+        max_attempts: 2,
+    }
+}
+
+async fn create_run(conn: &DatabaseConnection) -> Uuid {
+    SeaOrmRemediationRunRepository::new(conn.clone())
+        .create_run(Uuid::new_v4(), AutonomyLevel::FullyAutonomous)
+        .await
+        .unwrap()
+        .id
+}
+
+async fn load(conn: &DatabaseConnection, id: Uuid) -> remediation_run::Model {
+    remediation_run::Entity::find_by_id(id)
+        .one(conn)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn should_enqueue_each_new_run_once() {
+    // Arrange
+    let conn = sqlite().await;
+    let first = create_run(&conn).await;
+    let second = create_run(&conn).await;
+    let queue = RecordingQueue::default();
+
+    // Act: two dispatch ticks.
+    let enqueued = RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await
+        .unwrap();
+    let again = RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await
+        .unwrap();
+
+    // Assert: both runs went out on the first tick only.
+    assert_eq!(enqueued, 2);
+    assert_eq!(again, 0);
+    let mut sent = queue.enqueued.lock().unwrap().clone();
+    sent.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(sent, expected);
+    assert!(load(&conn, first).await.queued_at.is_some());
+}
+
+#[tokio::test]
+async fn should_clear_queue_marker_when_enqueue_fails() {
+    // Arrange
+    let conn = sqlite().await;
+    let run_id = create_run(&conn).await;
+    let queue = RecordingQueue {
+        fail: true,
+        ..Default::default()
+    };
+
+    // Act
+    let result = RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await;
+
+    // Assert: the run is left for the next tick.
+    assert!(result.is_err());
+    assert!(load(&conn, run_id).await.queued_at.is_none());
+}
+
+#[tokio::test]
+async fn should_dispatch_approved_run_but_not_one_awaiting_approval() {
+    // Arrange: a run parked at the gate and handed back by its worker.
+    let conn = sqlite().await;
+    let run_repo = SeaOrmRemediationRunRepository::new(conn.clone());
+    let run_id = create_run(&conn).await;
+    for (from, to) in [
+        (RunState::Created, RunState::Selecting),
+        (RunState::Selecting, RunState::Consolidating),
+        (RunState::Consolidating, RunState::Verifying),
+        (RunState::Verifying, RunState::AwaitingApproval),
+    ] {
+        assert!(run_repo
+            .transition_state(run_id, from, to, RunUpdate::none())
+            .await
+            .unwrap());
+    }
+    let queue = RecordingQueue::default();
+
+    // Act + Assert: nothing to dispatch while it waits for a human.
+    let parked = RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await
+        .unwrap();
+    assert_eq!(parked, 0);
+
+    // Act + Assert: approval makes it dispatchable again.
+    run_repo
+        .transition_state(
+            run_id,
+            RunState::AwaitingApproval,
+            RunState::Merging,
+            RunUpdate::none(),
+        )
+        .await
+        .unwrap();
+    let approved = RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await
+        .unwrap();
+    assert_eq!(approved, 1);
+    assert_eq!(*queue.enqueued.lock().unwrap(), vec![run_id]);
+}
+
+#[tokio::test]
+async fn should_hold_lease_until_it_goes_stale() {
+    // Arrange
+    let conn = sqlite().await;
+    let run_id = create_run(&conn).await;
+    let stale_before = config().stale_before();
+
+    // Act
+    let first = RemediationRunQueries::acquire_lease(&conn, run_id, stale_before)
+        .await
+        .unwrap();
+    let second = RemediationRunQueries::acquire_lease(&conn, run_id, stale_before)
+        .await
+        .unwrap();
+
+    // Assert: one worker holds it and the delivery is counted.
+    assert_eq!(first.map(|r| r.attempts), Some(1));
+    assert!(second.is_none());
+
+    // Act: the holder stops heartbeating.
+    let long_ago = Utc::now() - chrono::Duration::hours(1);
+    remediation_run::Entity::update_many()
+        .col_expr(remediation_run::Column::HeartbeatAt, Expr::value(long_ago))
+        .filter(remediation_run::Column::Id.eq(run_id))
+        .exec(&conn)
+        .await
+        .unwrap();
+    let taken_over = RemediationRunQueries::acquire_lease(&conn, run_id, stale_before)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(taken_over.map(|r| r.attempts), Some(2));
+}
+
+#[tokio::test]
+async fn should_redeliver_run_whose_worker_died() {
+    // Arrange: a queued run whose lease was last refreshed an hour ago.
+    let conn = sqlite().await;
+    let run_id = create_run(&conn).await;
+    let queue = RecordingQueue::default();
+    RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await
+        .unwrap();
+    let long_ago = Utc::now() - chrono::Duration::hours(1);
+    remediation_run::Entity::update_many()
+        .col_expr(remediation_run::Column::HeartbeatAt, Expr::value(long_ago))
+        .filter(remediation_run::Column::Id.eq(run_id))
+        .exec(&conn)
+        .await
+        .unwrap();
+
+    // Act
+    let enqueued = RemediationDispatchJob
+        .execute(&conn, &queue, &config())
+        .await
+        .unwrap();
+
+    // Assert: enqueued again, with the lease free for the next worker.
+    assert_eq!(enqueued, 1);
+    assert_eq!(*queue.enqueued.lock().unwrap(), vec![run_id, run_id]);
+    assert!(load(&conn, run_id).await.heartbeat_at.is_none());
+}
+
+#[tokio::test]
+async fn should_dead_letter_run_that_fails_on_every_delivery() {
+    // Arrange: the remediation-only schema has no `repository` table, so every
+    // delivery errors before the executor starts and leaves the run `created`.
+    let conn = sqlite().await;
+    let run_id = create_run(&conn).await;
+    let encryption = Arc::new(EncryptionService::new(&[7u8; 32]));
+    let factory = ProviderFactory::new();
+    let config = config();
+    let job = RemediationRunJob::new(run_id);
+    let drive = || {
+        job.drive(
+            &conn,
+            &encryption,
+            &factory,
+            Arc::new(FakeSandboxRunner::new()),
+            None,
+            &config,
+        )
+    };
+
+    // Act + Assert: the first failure is retried.
+    assert!(drive().await.is_err());
+    let retried = load(&conn, run_id).await;
+    assert_eq!(retried.state, RunState::Created.to_string());
+    assert!(retried.queued_at.is_none());
+    assert!(retried.heartbeat_at.is_none());
+
+    // Act + Assert: the last allowed delivery dead-letters it.
+    assert!(drive().await.is_err());
+    let dead = load(&conn, run_id).await;
+    assert_eq!(dead.state, RunState::DeadLetter.to_string());
+    assert_eq!(dead.attempts, 2);
+    assert!(dead
+        .error_message
+        .unwrap()
+        .starts_with("failed on all 2 deliveries"));
+
+    // Act + Assert: a late duplicate delivery is ignored.
+    assert!(drive().await.is_ok());
+    assert_eq!(load(&conn, run_id).await.attempts, 2);
+}
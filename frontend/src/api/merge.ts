@@ -12,14 +12,15 @@ export interface MergeItemResult {
   repositoryName: string;
   prNumber: number;
   prTitle: string;
-  status: 'pending' | 'success' | 'failed' | 'skipped';
+  status: 'pending' | 'success' | 'failed' | 'skipped' | 'waiting' | 'cancelled';
   errorMessage: string | null;
   mergeSha: string | null;
 }
 
 export interface BulkMergeResponse {
   operationId: string;
-  status: 'in_progress' | 'completed' | 'failed';
+  status: 'queued' | 'in_progress' | 'completed' | 'failed' | 'cancelled';
+  trigger: 'manual' | 'auto_merge';
   total: number;
   success: number;
   failed: number;
@@ -27,7 +28,10 @@ export interface BulkMergeResponse {
   results: MergeItemResult[];
 }
 
+const TERMINAL_STATUSES: BulkMergeResponse['status'][] = ['completed', 'failed', 'cancelled'];
+
 export const mergeApi = {
+  /** Enqueue a bulk merge; the worker merges the PRs in the background. */
   async bulkMerge(request: BulkMergeRequest): Promise<BulkMergeResponse> {
     const response = await apiClient.post<ApiResponse<BulkMergeResponse>>('/merge/bulk', request);
     return response.data.data!;
@@ -44,4 +48,28 @@ export const mergeApi = {
     const response = await apiClient.get<ApiResponse<BulkMergeResponse[]>>('/merge/operations');
     return response.data.data!;
   },
+  async cancelOperation(operationId: string): Promise<BulkMergeResponse> {
+    const response = await apiClient.post<ApiResponse<BulkMergeResponse>>(
+      `/merge/operations/${operationId}/cancel`
+    );
+    return response.data.data!;
+  },
+
+  async resumeOperation(operationId: string): Promise<BulkMergeResponse> {
+    const response = await apiClient.post<ApiResponse<BulkMergeResponse>>(
+      `/merge/operations/${operationId}/resume`
+    );
+    return response.data.data!;
+  },
+
+  /** Poll an operation until the merge queue finishes or cancels it. */
+  async waitForOperation(operationId: string, intervalMs = 2000): Promise<BulkMergeResponse> {
+    for (;;) {
+      const operation = await mergeApi.getOperation(operationId);
+      if (TERMINAL_STATUSES.includes(operation.status)) {
+        return operation;
+      }
+      await new Promise((resolve) => setTimeout(resolve, intervalMs));
+    }
+  },
 };
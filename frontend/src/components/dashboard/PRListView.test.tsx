@@ -37,6 +37,7 @@ vi.mock('react-i18next', () => ({
 vi.mock('@/api/merge', () => ({
   mergeApi: {
     bulkMerge: vi.fn(),
+    waitForOperation: vi.fn(),
   },
 }));
 
@@ -417,6 +418,15 @@ describe('PRListView', () => {
       } as unknown as UseInfiniteQueryResult<PaginatedResponse<PullRequestWithDetails>, Error>);
 
       mockedMergeApi.bulkMerge.mockResolvedValue({
+        operationId: 'op-1',
+        status: 'queued',
+        success: 0,
+        failed: 0,
+        results: [{ pullRequestId: '1', status: 'pending' }],
+      });
+      mockedMergeApi.waitForOperation.mockResolvedValue({
+        operationId: 'op-1',
+        status: 'completed',
         success: 1,
         failed: 0,
         results: [{ pullRequestId: '1', success: true }],
@@ -444,6 +454,7 @@ describe('PRListView', () => {
           deleteBranch: false,
         });
       });
+      expect(mockedMergeApi.waitForOperation).toHaveBeenCalledWith('op-1');
 
       expect(mockToast).toHaveBeenCalledWith({
         title: 'Bulk merge complete',
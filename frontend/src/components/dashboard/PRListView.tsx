@@ -57,7 +57,10 @@ export default function PRListView({ filterStatus }: PRListViewProps) {
   const selectedMergeablePrs = mergeablePrs.filter((pr) => selectedPrs.has(pr.id));
 
   const bulkMergeMutation = useMutation({
-    mutationFn: (request: BulkMergeRequest) => mergeApi.bulkMerge(request),
+    mutationFn: async (request: BulkMergeRequest) => {
+      const queued = await mergeApi.bulkMerge(request);
+      return mergeApi.waitForOperation(queued.operationId);
+    },
     onSuccess: (data) => {
       setMergeResults(data);
       setShowResults(true);
@@ -32,6 +32,15 @@ export function ConflictReport({ report }: ConflictReportProps) {
               >
                 <span className="font-medium">#{c.prNumber}</span>
                 <span className="ml-2 text-muted-foreground">{c.reason}</span>
+                {c.files.length > 0 && (
+                  <ul className="mt-1 flex flex-wrap gap-1.5">
+                    {c.files.map((file) => (
+                      <li key={file}>
+                        <code className="rounded bg-muted px-1 py-0.5 text-xs">{file}</code>
+                      </li>
+                    ))}
+                  </ul>
+                )}
               </li>
             ))}
           </ul>
@@ -29,6 +29,7 @@ function policy(overrides: Partial<RemediationPolicy>): RemediationPolicy {
     prSelection: 'all_open',
     autonomyLevel: 'auto_with_approval',
     remediationTier: 'consolidate_only',
+    consolidationStrategy: 'merge',
     maxPrsPerRun: 5,
     allowedTargets: [],
     skipDraft: true,
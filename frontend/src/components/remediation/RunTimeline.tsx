@@ -18,7 +18,14 @@ const MAIN_FLOW: RunState[] = [
 ];
 
 /** Off-path / terminal states shown only when the run actually reaches them. */
-const BRANCH_STATES: RunState[] = ['agent_fixing', 'handoff_human', 'failed', 'cancelled', 'no_op'];
+const BRANCH_STATES: RunState[] = [
+  'agent_fixing',
+  'handoff_human',
+  'failed',
+  'cancelled',
+  'no_op',
+  'dead_letter',
+];
 
 const TERMINAL: ReadonlySet<RunState> = new Set([
   'completed',
@@ -26,6 +33,7 @@ const TERMINAL: ReadonlySet<RunState> = new Set([
   'failed',
   'cancelled',
   'no_op',
+  'dead_letter',
 ]);
 
 type StepStatus = 'done' | 'current' | 'pending';
@@ -34,6 +42,7 @@ function dispositionVariant(
   d: Disposition
 ): 'success' | 'secondary' | 'warning' | 'destructive' | 'outline' {
   if (d === 'Consolidated') return 'success';
+  if (d === 'MergedExternally' || d === 'ClosedExternally') return 'outline';
   if ('ClosedWithRef' in d) return 'secondary';
   if ('SkippedConflict' in d) return 'warning';
   return 'outline'; // LeftOpen
@@ -41,6 +50,8 @@ function dispositionVariant(
 
 function dispositionKey(d: Disposition): string {
   if (d === 'Consolidated') return 'consolidated';
+  if (d === 'MergedExternally') return 'mergedExternally';
+  if (d === 'ClosedExternally') return 'closedExternally';
   if ('ClosedWithRef' in d) return 'closedWithRef';
   if ('SkippedConflict' in d) return 'skippedConflict';
   return 'leftOpen';
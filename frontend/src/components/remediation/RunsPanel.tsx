@@ -25,6 +25,7 @@ function stateVariant(
     case 'awaiting_approval':
       return 'warning';
     case 'failed':
+    case 'dead_letter':
       return 'destructive';
     case 'cancelled':
     case 'handoff_human':
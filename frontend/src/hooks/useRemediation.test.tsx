@@ -45,6 +45,7 @@ const mockPolicy: RemediationPolicy = {
   prSelection: 'all_open',
   autonomyLevel: 'dry_run_only',
   remediationTier: 'consolidate_only',
+  consolidationStrategy: 'merge',
   maxPrsPerRun: 10,
   allowedTargets: ['main'],
   skipDraft: true,
@@ -1103,6 +1103,7 @@ export interface RemediationTranslations {
     completed: string;
     consolidating: string;
     created: string;
+    dead_letter: string;
     failed: string;
     finalizing: string;
     handoff_human: string;
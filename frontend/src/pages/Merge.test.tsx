@@ -81,6 +81,7 @@ vi.mock('@/api/pullRequests', () => ({
 vi.mock('@/api/merge', () => ({
   mergeApi: {
     bulkMerge: vi.fn(),
+    waitForOperation: vi.fn(),
   },
 }));
 
@@ -496,6 +497,15 @@ describe('Merge', () => {
         deleteBranchesDefault: false,
       });
       mockedMergeApi.bulkMerge.mockResolvedValue({
+        operationId: 'op-1',
+        status: 'queued',
+        success: 0,
+        failed: 0,
+        results: [{ pullRequestId: '1', status: 'pending' }],
+      });
+      mockedMergeApi.waitForOperation.mockResolvedValue({
+        operationId: 'op-1',
+        status: 'completed',
         success: 1,
         failed: 0,
         results: [{ pullRequestId: '1', success: true }],
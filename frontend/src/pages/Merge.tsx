@@ -187,7 +187,10 @@ export default function Merge() {
   });
 
   const bulkMergeMutation = useMutation({
-    mutationFn: (request: BulkMergeRequest) => mergeApi.bulkMerge(request),
+    mutationFn: async (request: BulkMergeRequest) => {
+      const queued = await mergeApi.bulkMerge(request);
+      return mergeApi.waitForOperation(queued.operationId);
+    },
     onSuccess: (data) => {
       setMergeResults(data);
       setShowResults(true);
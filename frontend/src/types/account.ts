@@ -2,6 +2,8 @@ import type { GitProvider } from './index';
 
 export type ValidationStatus = 'pending' | 'valid' | 'invalid' | 'expired';
 
+export type AuthType = 'pat' | 'github_app' | 'oauth';
+
 export interface ProviderAccount {
   id: string;
   provider: GitProvider;
@@ -10,6 +12,7 @@ export interface ProviderAccount {
   providerUsername: string;
   providerEmail: string | null;
   avatarUrl: string | null;
+  authType: AuthType;
   scopes: string[];
   tokenExpiresAt: string | null;
   validationStatus: ValidationStatus;
@@ -24,13 +27,22 @@ export interface AddAccountRequest {
   provider: GitProvider;
   instanceUrl?: string;
   accountLabel: string;
-  accessToken: string;
+  authType?: AuthType; // Defaults to 'pat'
+  accessToken?: string; // PAT or OAuth access token
   username?: string; // Required for Bitbucket
+  appId?: number; // GitHub App
+  installationId?: number;
+  privateKey?: string;
+  refreshToken?: string; // OAuth
+  tokenExpiresAt?: string;
 }
 
 export interface UpdateAccountRequest {
   accountLabel?: string;
   accessToken?: string;
+  refreshToken?: string;
+  tokenExpiresAt?: string;
+  privateKey?: string;
   isActive?: boolean;
   isDefault?: boolean;
 }
@@ -29,6 +29,8 @@ export type AutonomyLevel =
 
 export type RemediationTier = 'consolidate_only' | 'fix_and_consolidate' | 'full_remediation';
 
+export type ConsolidationStrategy = 'merge' | 'rebase_onto_base' | 'squash_per_pr';
+
 export type PolicyState =
   'none' | 'disabled' | 'dry_run' | 'suggest' | 'auto_with_approval' | 'auto_merge';
 
@@ -51,6 +53,7 @@ export interface RemediationPolicy {
   prSelection: PrSelectionStrategy;
   autonomyLevel: AutonomyLevel;
   remediationTier: RemediationTier;
+  consolidationStrategy: ConsolidationStrategy;
   maxPrsPerRun: number;
   allowedTargets: string[];
   skipDraft: boolean;
@@ -74,6 +77,7 @@ export interface CreatePolicyRequest {
   prSelection?: PrSelectionStrategy;
   autonomyLevel: AutonomyLevel;
   remediationTier?: RemediationTier;
+  consolidationStrategy?: ConsolidationStrategy;
   maxPrsPerRun: number;
   allowedTargets?: string[];
   skipDraft?: boolean;
@@ -133,7 +137,8 @@ export type RunState =
   | 'handoff_human'
   | 'failed'
   | 'cancelled'
-  | 'no_op';
+  | 'no_op'
+  | 'dead_letter';
 
 /** Aggregate CI status. Loosely typed — providers report varying labels. */
 export type CiStatus = string;
@@ -164,8 +169,10 @@ export interface RemediationRun {
 export type Disposition =
   | 'Consolidated'
   | { ClosedWithRef: { consolidatedPrNumber: number } }
-  | { SkippedConflict: { reason: string } }
-  | { LeftOpen: { reason: string } };
+  | { SkippedConflict: { reason: string; files?: string[] } }
+  | { LeftOpen: { reason: string } }
+  | 'MergedExternally'
+  | 'ClosedExternally';
 
 export interface PrDisposition {
   prNumber: number;
@@ -182,6 +189,8 @@ export interface CiMatrix {
 export interface RemediationConflict {
   prNumber: number;
   reason: string;
+  /** Conflicting files; empty for conflicts recorded before files were kept. */
+  files: string[];
 }
 
 export interface RemediationSkipped {